@@ -1,20 +1,81 @@
-use crate::error::Result;
-use crate::git::commit::{CommitId, EditableField};
-use crate::git::validation::{validate_date, validate_email};
-use crate::git::{rewrite_history, Repository};
-use crate::state::{AppMode, AppState, ConfirmAction, VisualType};
+use crate::clipboard::SystemClipboard;
+use crate::config::{Config, IdentityProfiles};
+use crate::error::{HistError, Result};
+use crate::git::commit::{
+    CommitData, CommitId, CommitModifications, CommitSplit, EditableField, NewRoot, Person,
+    SquashKind, Trailer,
+};
+use crate::git::conventional::COMMIT_TYPES;
+use crate::git::identity::KnownIdentities;
+use crate::git::metadata::{commit_records, render_records, DumpFormat};
+use crate::git::report::{ApplyGuidance, Bypass, RewriteReport, RewriteSummary};
+use crate::git::repository::{BackupRef, TagRef};
+use crate::git::rewrite::{order_changed, preview_rewrite, DependentBranch, RewritePlan};
+use crate::git::signing::Signer;
+use crate::git::todo::rebase_todo;
+use crate::git::validation::{format_date_for_edit, validate_date, validate_email};
+use crate::git::worker::{
+    ApplyOutcome, CommitLoader, LoadEvent, RewriteJob, RewriteWorker, LOAD_BATCH,
+};
+use crate::git::Repository;
+use crate::plugin::{run_callback, run_plugin, PluginCommand, PluginCommit};
+use crate::state::app_state::{HeldChanges, UndoSnapshot};
+use crate::state::session::{discard_recovery, flush_recovery, stage_recovery};
+use crate::state::{
+    AppMode, AppState, AuthorGroups, BackupList, BranchPicker, Command, CommandLine,
+    CommandPalette, ConfirmAction, DateFix, DateSource, DivergenceReport, EditPlan, HashPreview,
+    IdentityPicker, LintReport, Marks, PaletteAction, PendingEdit, ReplaceTarget, SearchHistory,
+    SearchQuery, Session, SortKey, SortOrder, SplitChooser, StashConflict, StepApply, TouchedPath,
+    TrailerEditor, UndoBrowser, VisualType, CLIPBOARD_REGISTER, UNNAMED_REGISTER,
+};
 use crate::ui::layout::AppLayout;
 use crate::ui::theme::Theme;
+use crate::ui::widgets::commit_table::format_gap;
 use crate::ui::widgets::{
-    get_column_value, help_max_scroll, render_commit_table, render_confirmation_dialog,
-    render_detail_pane, render_edit_popup, render_help_screen, render_search_bar,
-    render_status_bar, render_title_bar, Column, ConfirmDialogState, SearchState,
+    get_column_value, help_max_scroll, render_apply_guidance, render_apply_progress,
+    render_author_groups, render_backups, render_branch_picker, render_command_bar,
+    render_command_palette, render_commit_table, render_confirmation_dialog, render_detail_pane,
+    render_divergence_report, render_edit_popup, render_hash_preview, render_help_screen,
+    render_identity_picker, render_lint_report, render_replace_dialog, render_search_bar,
+    render_split_pane, render_stash_dialog, render_status_bar, render_step_dialog,
+    render_title_bar, render_trailer_editor, render_undo_history, Column, ConfirmDialogState,
+    ReplaceState, SearchState, StatusBarConfig,
 };
+use chrono::{DateTime, FixedOffset, Local, TimeDelta};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
+use std::collections::{HashMap, HashSet};
 use std::io::Stdout;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Where to send the Markdown report written after each apply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportTarget {
+    /// Print to stdout after the TUI exits
+    Stdout,
+    /// Append to a file
+    File(PathBuf),
+}
+
+impl ReportTarget {
+    /// Interpret a `--report` argument (`-` means stdout)
+    #[must_use]
+    pub fn from_arg(path: &Path) -> Self {
+        if path == Path::new("-") {
+            Self::Stdout
+        } else {
+            Self::File(path.to_path_buf())
+        }
+    }
+}
+
+/// Older commits loaded at a time by `+`, or by moving down past the last one
+const LOAD_MORE: usize = 50;
+
+/// How often the crash recovery snapshot is written while there are changes
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Main application struct
 pub struct App {
@@ -26,12 +87,83 @@ pub struct App {
     theme: Theme,
     /// Should the app quit?
     should_quit: bool,
+    /// Quit once the pending apply succeeds (`:wq`)
+    quit_after_apply: bool,
     /// Search state (when searching)
     search: SearchState,
+    /// Past search queries for Up/Down recall
+    search_history: SearchHistory,
+    /// Command line input (when in command mode)
+    command: SearchState,
+    /// Find/replace dialog state (when replacing in a message)
+    replace: ReplaceState,
+    /// Known name/email pairings for identity auto-fill
+    identities: KnownIdentities,
     /// Confirmation dialog state
     confirm_dialog: ConfirmDialogState,
     /// Last known terminal area (for scroll calculations)
     last_area: ratatui::layout::Rect,
+    /// Where to write a report after each apply (None = no report)
+    report_target: Option<ReportTarget>,
+    /// Reports waiting to be printed to stdout on exit
+    stdout_reports: Vec<String>,
+    /// One summary per rewrite applied this session (`--summary`)
+    summaries: Vec<RewriteSummary>,
+    /// Whether the last normal-mode key was `g` (for `gg`, `ge` and `gs`)
+    pending_g: bool,
+    /// Count typed before a motion (`5j`)
+    pending_count: Option<usize>,
+    /// Whether the last key was `"` (a register name comes next)
+    register_prefix: bool,
+    /// Marks and the jump list
+    marks: Marks,
+    /// `m` or `'` when a mark name comes next
+    mark_prefix: Option<char>,
+    /// Register named with `"x` for the next yank or paste
+    pending_register: Option<char>,
+    /// Rewrite hashes mentioned in messages to their new values when applying
+    update_hash_references: bool,
+    /// Keep a numbered backup of the branch each time a rewrite is applied
+    numbered_backups: bool,
+    /// Refuse to rewrite commits already on the upstream, unless forced
+    protect_pushed: bool,
+    /// Message of the commit `:newroot` adds
+    new_root_message: String,
+    /// How `M` puts dates back in order (`date_fix_nudge_seconds`)
+    date_fix: DateFix,
+    /// Custom `:` commands from the config file
+    plugins: Vec<PluginCommand>,
+    /// Status bar layout from the config file
+    status_bar: StatusBarConfig,
+    /// System clipboard (connected on first copy)
+    clipboard: SystemClipboard,
+    /// How many commits to load (`--limit`, `:limit`)
+    commit_limit: usize,
+    /// Signs rewritten commits when `state.sign_commits` is on
+    signer: Option<Signer>,
+    /// `state.revision` and dirtiness the recovery snapshot was staged for
+    recovery_staged: (u64, bool),
+    /// When the staged recovery snapshot was last written out
+    recovery_flushed: Instant,
+    /// The rewrite running in the background (`AppMode::Applying`)
+    applying: Option<PendingApply>,
+    /// Older commits still loading in the background
+    loader: Option<CommitLoader>,
+}
+
+/// A rewrite running on a worker thread, with what finishing it needs
+struct PendingApply {
+    worker: RewriteWorker,
+    /// Branch tip before the rewrite
+    old_tip: CommitId,
+    /// The `--from` start point before the rewrite
+    old_start: Option<CommitId>,
+    /// Pending changes as they were when the apply started
+    pending: UndoSnapshot,
+    branches: Vec<DependentBranch>,
+    tags: Vec<TagRef>,
+    /// Changes of the commits skipped in `W`, still pending after the rewrite
+    held: Vec<(CommitId, HeldChanges)>,
 }
 
 impl App {
@@ -41,6 +173,7 @@ impl App {
     /// * `repo` - The git repository to operate on
     /// * `commit_limit` - Maximum number of commits to load
     /// * `sync_author_to_committer` - Whether editing author fields should also update committer fields
+    /// * `config` - User configuration
     ///
     /// # Errors
     /// Returns an error if the repository cannot be read or commits cannot be loaded.
@@ -48,26 +181,407 @@ impl App {
         repo: Repository,
         commit_limit: usize,
         sync_author_to_committer: bool,
+        config: &Config,
+    ) -> Result<Self> {
+        Self::build(repo, commit_limit, false, sync_author_to_committer, config)
+    }
+
+    /// Like [`App::new`], but only the first `LOAD_BATCH` commits are loaded
+    /// before it returns; the rest load in the background once `run` starts
+    ///
+    /// # Errors
+    /// Returns an error if the repository cannot be read or commits cannot be loaded.
+    pub fn new_loading(
+        repo: Repository,
+        commit_limit: usize,
+        sync_author_to_committer: bool,
+        config: &Config,
+    ) -> Result<Self> {
+        Self::build(repo, commit_limit, true, sync_author_to_committer, config)
+    }
+
+    fn build(
+        repo: Repository,
+        commit_limit: usize,
+        in_background: bool,
+        sync_author_to_committer: bool,
+        config: &Config,
     ) -> Result<Self> {
         let branch_name = repo.current_branch_name()?;
         let has_upstream = repo.has_upstream().unwrap_or(false);
-        let commits = repo.load_commits(commit_limit)?;
+        let upfront = if in_background {
+            commit_limit.min(LOAD_BATCH)
+        } else {
+            commit_limit
+        };
+        let commits = repo.load_commits(upfront)?;
+        // A full first batch means there may be more to load
+        let load_more = commits.len() == upfront && upfront < commit_limit;
+        let head_offset_base = repo.commits_above_start()?.len();
 
+        let identities = load_identities(&repo, &commits);
         let mut state = AppState::new(commits, branch_name, has_upstream);
         // Start at first editable column (Name)
         state.column_index = Column::Name as usize;
         // Configure author-to-committer sync behavior
         state.set_sync_author_to_committer(sync_author_to_committer);
+        state.user = repo.author_identity();
+        state.warn_foreign_commits = config.warn_foreign_commits;
+        state.remap_branches = config.rewrite_dependent_branches;
+        state.migrate_tags = config.migrate_tags;
+        // A broken signing setup only matters once signing is asked for (`--sign`)
+        let signer = repo.signer().ok().flatten();
+        state.signing_key = signer.as_ref().map(Signer::describe);
+        state.sign_commits = signer.is_some() && repo.signs_by_default();
+        state.lock_age = config
+            .lock_after_days
+            .map(|days| TimeDelta::days(days.into()));
+        state.divergence_threshold = TimeDelta::hours(config.date_divergence_hours.into());
+        state.delete_confirm_threshold = config.delete_confirm_threshold;
+        state.show_lint = config.lint_conventional_commits;
+        state.head_offset_base = head_offset_base;
+        // A detached HEAD has no branch to rewrite: offer to pick one
+        if repo.current_branch_name()? == "HEAD" {
+            let branches = repo.branch_names()?;
+            if !branches.is_empty() {
+                state.mode = AppMode::BranchPicker(BranchPicker {
+                    branches,
+                    cursor: 0,
+                });
+            }
+        }
 
-        Ok(Self {
+        let mut app = Self {
             state,
             repo,
             theme: Theme::default(),
             should_quit: false,
+            quit_after_apply: false,
             search: SearchState::new(),
+            search_history: SearchHistory::load_default(),
+            command: SearchState::new(),
+            replace: ReplaceState::new(),
+            identities,
             confirm_dialog: ConfirmDialogState::default(),
             last_area: ratatui::layout::Rect::default(),
-        })
+            report_target: None,
+            stdout_reports: Vec::new(),
+            summaries: Vec::new(),
+            pending_g: false,
+            pending_count: None,
+            register_prefix: false,
+            marks: Marks::default(),
+            mark_prefix: None,
+            pending_register: None,
+            update_hash_references: config.update_hash_references,
+            numbered_backups: config.numbered_backups,
+            protect_pushed: config.protect_pushed,
+            new_root_message: config.new_root_message.clone(),
+            date_fix: config
+                .date_fix_nudge_seconds
+                .map_or(DateFix::Swap, |seconds| DateFix::Nudge(i64::from(seconds))),
+            plugins: config.commands.clone(),
+            status_bar: config.status_bar.clone(),
+            clipboard: SystemClipboard::default(),
+            signer,
+            commit_limit,
+            recovery_staged: (0, false),
+            recovery_flushed: Instant::now(),
+            applying: None,
+            loader: None,
+        };
+        if load_more {
+            app.start_loading()?;
+        }
+        Ok(app)
+    }
+
+    /// Sign the rewritten commits with the key from git config (`--sign`)
+    ///
+    /// # Errors
+    /// Returns an error if no signing key is configured or the setup is invalid.
+    pub fn enable_signing(&mut self) -> Result<()> {
+        let signer = self.repo.signer()?.ok_or_else(|| {
+            HistError::Signing("no signing key configured (set user.signingkey)".to_string())
+        })?;
+        self.state.signing_key = Some(signer.describe());
+        self.state.sign_commits = true;
+        self.signer = Some(signer);
+        Ok(())
+    }
+
+    /// Remap authors and committers through a mailmap file, or the repo's
+    /// `.mailmap` without one (`--mailmap`, `:mailmap`)
+    ///
+    /// Merge commits are left alone, and so are commits locked by age unless
+    /// forced. The changes are staged for review like any other edit.
+    /// Returns how many commits changed.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read, or there is no `.mailmap`.
+    pub fn apply_mailmap(&mut self, path: Option<&Path>) -> Result<usize> {
+        let mailmap = match path {
+            Some(path) => git2::Mailmap::from_buffer(&std::fs::read_to_string(path)?)?,
+            None => self
+                .repo
+                .mailmap()
+                .ok_or_else(|| HistError::Config("no .mailmap in the repository".to_string()))?,
+        };
+        let force = self.state.force;
+        let ids: Vec<CommitId> = self
+            .state
+            .commits
+            .iter()
+            .filter(|c| !c.is_merge && (force || !self.state.is_locked(c.id)))
+            .map(|c| c.id)
+            .collect();
+        let changed = self.state.apply_mailmap(&ids, &mailmap);
+        if changed > 0 {
+            self.identities.add_mailmap(&mailmap, &self.state.commits);
+        }
+        Ok(changed)
+    }
+
+    /// Stage the output of filter-repo style callbacks for every loaded
+    /// commit (`--message-callback`, `--name-callback`)
+    ///
+    /// Each callback is a shell command reading the current value (pending
+    /// edits applied) on stdin and printing the new one. The name callback
+    /// runs once per distinct name and covers authors and committers. Merge
+    /// commits, and commits locked by age unless forced, are left alone. The
+    /// changes are staged as one undo step; returns how many commits changed.
+    ///
+    /// # Errors
+    /// Returns an error if a callback fails; nothing is staged then.
+    pub fn apply_callbacks(&mut self, message: Option<&str>, name: Option<&str>) -> Result<usize> {
+        let dir = self
+            .repo
+            .inner()
+            .workdir()
+            .unwrap_or_else(|| self.repo.inner().path())
+            .to_path_buf();
+        let force = self.state.force;
+        let mut names: HashMap<String, String> = HashMap::new();
+        let mut rename = |old: &str| -> Result<Option<String>> {
+            let Some(command) = name else {
+                return Ok(None);
+            };
+            if !names.contains_key(old) {
+                names.insert(old.to_string(), run_callback(command, old, &dir)?);
+            }
+            Ok(Some(names[old].clone()).filter(|new| new != old))
+        };
+
+        let mut staged = Vec::new();
+        for commit in &self.state.commits {
+            if commit.is_merge || (!force && self.state.is_locked(commit.id)) {
+                continue;
+            }
+            let mods = self.state.modifications.get(&commit.id);
+            let current = PluginCommit::new(commit, mods);
+            let changes = CommitModifications {
+                author_name: rename(&current.author_name)?,
+                committer_name: rename(&current.committer_name)?,
+                message: match message {
+                    Some(command) => Some(run_callback(command, &current.message, &dir)?)
+                        .filter(|new| new != current.message.trim_end()),
+                    None => None,
+                },
+                ..Default::default()
+            };
+            if !changes.is_empty() {
+                staged.push((commit.id, changes));
+            }
+        }
+        if !staged.is_empty() {
+            self.state.save_undo("Run callbacks");
+            for (id, changes) in &staged {
+                self.state
+                    .get_or_create_modifications(*id)
+                    .merge(changes.clone());
+            }
+        }
+        Ok(staged.len())
+    }
+
+    /// Stage the edits of a plan file (`--plan`), replacing any pending ones
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read or names commits that
+    /// aren't loaded.
+    pub fn load_plan(&mut self, path: &Path) -> Result<()> {
+        let plan = EditPlan::load(path)?;
+        self.state.apply_plan(&plan)
+    }
+
+    /// Where this repository's session is saved (`.git/retcon-session.json`)
+    fn session_path(&self) -> PathBuf {
+        Session::path(self.repo.inner().path())
+    }
+
+    /// Offer to resume a session saved on quit, if there is one for this
+    /// branch and nothing else is staged yet
+    pub fn offer_session(&mut self) {
+        if self.state.is_dirty() || !matches!(self.state.mode, AppMode::Normal) {
+            return;
+        }
+        // A session may change any commit up to the limit
+        if self.recovery_path().exists() || self.session_path().exists() {
+            self.finish_loading();
+        }
+        match Session::load(&self.recovery_path()) {
+            Ok(Some(session)) if session.branch == self.state.branch_name => {
+                self.confirm_dialog = ConfirmDialogState::default();
+                self.state.mode = AppMode::Confirming(ConfirmAction::Recover(Box::new(session)));
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => self
+                .state
+                .set_error(format!("Recovery snapshot not loaded: {e}")),
+        }
+        match Session::load(&self.session_path()) {
+            Ok(Some(session)) if session.branch == self.state.branch_name => {
+                self.confirm_dialog = ConfirmDialogState::default();
+                self.state.mode =
+                    AppMode::Confirming(ConfirmAction::ResumeSession(Box::new(session)));
+            }
+            Ok(_) => {}
+            Err(e) => self
+                .state
+                .set_error(format!("Saved session not loaded: {e}")),
+        }
+    }
+
+    /// Save the pending changes and undo history to resume next time
+    fn save_session(&self) -> Result<()> {
+        let session = self.state.session(Local::now().fixed_offset());
+        session.save(&self.session_path())
+    }
+
+    /// Describe what applying the pending changes would do, predicted new
+    /// hashes included, without writing anything (`--dry-run`)
+    ///
+    /// The hashes are those of unsigned commits, as signatures differ on
+    /// every run.
+    ///
+    /// # Errors
+    /// Returns an error if the rewrite can't be computed.
+    pub fn dry_run(&self) -> Result<String> {
+        if !self.state.is_dirty() {
+            return Ok(format!(
+                "Dry run on branch {}: nothing would change.\n",
+                self.state.branch_name
+            ));
+        }
+        let descendants = self.repo.commits_above_start()?;
+        let plan = RewritePlan {
+            signer: None,
+            ..self.rewrite_plan(&descendants, &[])
+        };
+        let commit_map = preview_rewrite(self.repo.inner(), &plan)?;
+        let report = RewriteReport {
+            branch_name: &self.state.branch_name,
+            commits: &self.state.commits,
+            modifications: &self.state.modifications,
+            deleted: &self.state.deleted,
+            order_changed: order_changed(&self.state.original_order, &self.state.current_order),
+            commit_map: &commit_map,
+            has_upstream: self.state.has_upstream,
+            notes: &self.state.notes,
+            forced: &[],
+        };
+        Ok(report.to_dry_run())
+    }
+
+    /// Apply the pending changes without the TUI (`retcon apply --plan`)
+    ///
+    /// Runs the checks the TUI would, but refuses instead of asking: commits
+    /// locked by age, pushed commits (`protect_pushed`) and malformed emails
+    /// are errors unless forced. Returns the rewrite's summary.
+    ///
+    /// # Errors
+    /// Returns an error if there is nothing to apply, a check fails, the
+    /// rewrite fails, or the stashed changes can't be restored afterwards.
+    pub fn apply_headless(&mut self) -> Result<RewriteSummary> {
+        if !self.state.is_dirty() {
+            return Err(HistError::Plan("nothing to apply".to_string()));
+        }
+        let changed: Vec<CommitId> = self.state.changed_commits().into_iter().collect();
+        if !self.check_unlocked(&changed) {
+            let message = self.state.error_message.take().unwrap_or_default();
+            return Err(HistError::Plan(message));
+        }
+        let bad_email = self
+            .state
+            .modifications
+            .values()
+            .flat_map(|mods| [&mods.author_email, &mods.committer_email])
+            .flatten()
+            .find(|email| validate_email(email).is_err());
+        if let Some(email) = bad_email {
+            if !self.state.force {
+                return Err(HistError::InvalidEmail(email.clone()));
+            }
+            self.state.record_bypass(Bypass::Validation);
+        }
+
+        self.apply_changes()?;
+        if let AppMode::StashConflict(conflict) = &self.state.mode {
+            return Err(HistError::StashConflict(conflict.paths.clone()));
+        }
+        self.summaries
+            .last()
+            .cloned()
+            .ok_or_else(|| HistError::RewriteFailed("no summary recorded".to_string()))
+    }
+
+    /// The loaded commits' metadata in their current order, with the values
+    /// pending edits would give them (`:dump`, `retcon dump`)
+    #[must_use]
+    pub fn metadata_dump(&self, format: DumpFormat) -> String {
+        let commits: Vec<&CommitData> = self.state.commits.iter().collect();
+        let records = commit_records(&commits, &self.state.modifications, &self.state.deleted);
+        render_records(&records, format)
+    }
+
+    /// Write the metadata dump to a `.csv` or `.json` file, replacing it;
+    /// returns how many commits were written
+    ///
+    /// # Errors
+    /// Returns an error if the file name has another extension, or the file
+    /// can't be written.
+    pub fn dump_metadata(&self, path: &Path) -> Result<usize> {
+        let format = DumpFormat::from_path(path).ok_or_else(|| {
+            HistError::InvalidCommand(format!(
+                "don't know how to dump to '{}' (try .csv or .json)",
+                path.display()
+            ))
+        })?;
+        std::fs::write(path, self.metadata_dump(format))?;
+        Ok(self.state.commits.len())
+    }
+
+    /// Skip the safety checks (`--force`); each one skipped is logged in the
+    /// apply summary
+    pub fn set_force(&mut self, force: bool) {
+        self.state.force = force;
+    }
+
+    /// Set where the Markdown report is written after each successful apply
+    pub fn set_report_target(&mut self, target: Option<ReportTarget>) {
+        self.report_target = target;
+    }
+
+    /// Take the reports that should be printed to stdout once the TUI has exited
+    pub fn take_stdout_reports(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.stdout_reports)
+    }
+
+    /// Summaries of the rewrites applied this session, oldest first
+    #[must_use]
+    pub fn summaries(&self) -> &[RewriteSummary] {
+        &self.summaries
     }
 
     /// Run the main event loop
@@ -75,16 +589,32 @@ impl App {
     /// # Errors
     /// Returns an error if terminal operations fail or an unrecoverable error occurs.
     pub fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+        let result = self.event_loop(terminal);
+        // A clean exit leaves nothing to recover; an error keeps the snapshot
+        let recovery = if result.is_ok() {
+            discard_recovery(&self.recovery_path())
+        } else {
+            flush_recovery()
+        };
+        result.and(recovery)
+    }
+
+    fn event_loop(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
         loop {
             // Draw UI
             terminal.draw(|frame| self.draw(frame))?;
 
             // Handle events with a small timeout for responsiveness
             if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    self.handle_key(key)?;
+                match event::read()? {
+                    Event::Key(key) => self.handle_key(key)?,
+                    Event::Resize(width, height) => self.handle_resize(width, height),
+                    _ => {}
                 }
             }
+            self.poll_apply()?;
+            self.poll_loading();
+            self.track_recovery();
 
             if self.should_quit {
                 break;
@@ -94,6 +624,113 @@ impl App {
         Ok(())
     }
 
+    /// Load the commits past those loaded, up to the limit, in the background
+    fn start_loading(&mut self) -> Result<()> {
+        let loaded = self.state.commits.len();
+        if loaded < self.commit_limit {
+            self.loader = Some(CommitLoader::spawn(
+                self.repo.reopen()?,
+                loaded,
+                self.commit_limit - loaded,
+            ));
+            self.state.loading = true;
+        }
+        Ok(())
+    }
+
+    /// Stop loading in the background, returning whether it was
+    fn stop_loading(&mut self) -> bool {
+        self.state.loading = false;
+        self.loader.take().is_some()
+    }
+
+    /// Add the commits loaded in the background since the last frame
+    fn poll_loading(&mut self) {
+        while let Some(event) = self.loader.as_ref().and_then(CommitLoader::poll) {
+            self.receive_commits(event);
+        }
+    }
+
+    /// Wait for the background loading to finish
+    fn finish_loading(&mut self) {
+        while let Some(event) = self.loader.as_ref().map(CommitLoader::wait) {
+            self.receive_commits(event);
+        }
+    }
+
+    fn receive_commits(&mut self, event: LoadEvent) {
+        match event {
+            LoadEvent::Batch(older) => self.state.extend_commits(older),
+            LoadEvent::Done(result) => {
+                self.stop_loading();
+                self.identities = load_identities(&self.repo, &self.state.commits);
+                // The older commits may touch the searched paths
+                let refreshed = result.and_then(|()| {
+                    if !self.state.touched_paths.is_empty() {
+                        self.state.touched_paths.clear();
+                        self.refresh_touched_paths()?;
+                        self.state.apply_filter();
+                    }
+                    Ok(())
+                });
+                if let Err(e) = refreshed {
+                    self.state.set_error(format!("Loading stopped: {e}"));
+                }
+            }
+        }
+    }
+
+    /// Where this repository's crash recovery snapshot is written
+    fn recovery_path(&self) -> PathBuf {
+        Session::recovery_path(self.repo.inner().path())
+    }
+
+    /// Keep the crash recovery snapshot up to date with the pending changes
+    ///
+    /// The snapshot is staged after every edit (the panic hook writes it
+    /// out) and written every `RECOVERY_INTERVAL`; once nothing is pending
+    /// it is deleted.
+    fn track_recovery(&mut self) {
+        let current = (self.state.revision, self.state.is_dirty());
+        if current != self.recovery_staged {
+            self.recovery_staged = current;
+            if current.1 {
+                let session = Session {
+                    branch: self.state.branch_name.clone(),
+                    saved_at: Local::now().fixed_offset(),
+                    current: self.state.snapshot("Recovered changes"),
+                    undo_stack: Vec::new(),
+                    redo_stack: Vec::new(),
+                };
+                if let Ok(json) = session.to_json() {
+                    stage_recovery(self.recovery_path(), json);
+                }
+            } else if let Err(e) = discard_recovery(&self.recovery_path()) {
+                self.state
+                    .set_error(format!("Failed to remove the recovery snapshot: {e}"));
+            }
+        }
+        if self.recovery_flushed.elapsed() >= RECOVERY_INTERVAL {
+            self.recovery_flushed = Instant::now();
+            if let Err(e) = flush_recovery() {
+                self.state
+                    .set_error(format!("Failed to write the recovery snapshot: {e}"));
+            }
+        }
+    }
+
+    /// Re-fit scroll offsets and the cursor to a new terminal size
+    fn handle_resize(&mut self, width: u16, height: u16) {
+        let area = ratatui::layout::Rect::new(0, 0, width, height);
+        self.last_area = area;
+        if AppLayout::is_too_small(area) {
+            return;
+        }
+        let layout = AppLayout::new(area, matches!(self.state.mode, AppMode::Search));
+        self.state.fit_to_height(layout.table_height());
+        self.state.help_scroll = self.state.help_scroll.min(help_max_scroll(area));
+    }
+
     /// Draw the entire UI
     fn draw(&mut self, frame: &mut ratatui::Frame<'_>) {
         use ratatui::layout::Alignment;
@@ -122,26 +759,57 @@ impl App {
         let layout = AppLayout::new(area, search_active);
 
         // Update scroll for actual table height
-        self.state.update_scroll_for_height(layout.table_height());
+        self.state.fit_to_height(layout.table_height());
 
         // Render base UI
         render_title_bar(frame, layout.title, &self.state, &self.theme);
 
         if let Some(search_area) = layout.search {
-            let result_count = self.state.filtered_indices.as_ref().map(Vec::len);
+            // A sort alone shows every row, which isn't a search result
+            let result_count = self
+                .state
+                .filtered_indices
+                .as_ref()
+                .filter(|_| !self.state.search_query.is_empty())
+                .map(Vec::len);
+            let error = SearchQuery::parse(&self.search.query)
+                .err()
+                .map(|e| e.to_string());
             render_search_bar(
                 frame,
                 search_area,
                 &self.search.query,
                 self.search.cursor,
                 result_count,
+                error.as_deref(),
                 &self.theme,
             );
         }
 
         render_commit_table(frame, layout.table, &self.state, &self.theme);
-        render_detail_pane(frame, layout.detail, &self.state, &self.theme);
-        render_status_bar(frame, layout.status, &self.state, &self.theme);
+        if let AppMode::Split(chooser) = &self.state.mode {
+            render_split_pane(frame, layout.detail, chooser, &self.state, &self.theme);
+        } else {
+            self.load_file_changes();
+            render_detail_pane(frame, layout.detail, &self.state, &self.theme);
+        }
+        if matches!(self.state.mode, AppMode::Command) {
+            render_command_bar(
+                frame,
+                layout.status,
+                &self.command.query,
+                self.command.cursor,
+                &self.theme,
+            );
+        } else {
+            render_status_bar(
+                frame,
+                layout.status,
+                &self.state,
+                &self.status_bar,
+                &self.theme,
+            );
+        }
 
         // Render overlays based on mode
         match &self.state.mode {
@@ -158,6 +826,65 @@ impl App {
                     &self.theme,
                 );
             }
+            AppMode::Replace(target) => {
+                let text = match target {
+                    ReplaceTarget::Message { commit_idx } => {
+                        self.state.visible_commit(*commit_idx).map(|commit| {
+                            let mods = self.state.modifications.get(&commit.id);
+                            get_column_value(commit, mods, Column::Message)
+                        })
+                    }
+                    ReplaceTarget::EditBuffer { .. } => Some(self.state.edit_buffer.clone()),
+                    ReplaceTarget::SplitMessage(chooser) => Some(chooser.message.clone()),
+                };
+                if let Some(text) = text {
+                    render_replace_dialog(frame, area, &self.replace, &text, &self.theme);
+                }
+            }
+            AppMode::StepApply(step) => {
+                render_step_dialog(frame, area, step, &self.state, &self.theme);
+            }
+            AppMode::Applying => {
+                if let Some(pending) = &self.applying {
+                    render_apply_progress(frame, area, &pending.worker, &self.theme);
+                }
+            }
+            AppMode::StashConflict(conflict) => {
+                render_stash_dialog(frame, area, conflict, &self.theme);
+            }
+            AppMode::Applied(guidance) => {
+                render_apply_guidance(frame, area, guidance, &self.theme);
+            }
+            AppMode::BranchPicker(picker) => {
+                render_branch_picker(frame, area, picker, &self.state, &self.theme);
+            }
+            AppMode::Palette(palette) => {
+                render_command_palette(frame, area, palette, &self.theme);
+            }
+            AppMode::IdentityPicker(picker) => {
+                render_identity_picker(frame, area, picker, &self.theme);
+            }
+            AppMode::Trailers(editor) => {
+                render_trailer_editor(frame, area, editor, &self.theme);
+            }
+            AppMode::Divergence(report) => {
+                render_divergence_report(frame, area, report, &self.state, &self.theme);
+            }
+            AppMode::Lint(report) => {
+                render_lint_report(frame, area, report, &self.state, &self.theme);
+            }
+            AppMode::UndoHistory(browser) => {
+                render_undo_history(frame, area, *browser, &self.state, &self.theme);
+            }
+            AppMode::Backups(list) => {
+                render_backups(frame, area, list, &self.state, &self.theme);
+            }
+            AppMode::Authors(view) => {
+                render_author_groups(frame, area, view, &self.theme);
+            }
+            AppMode::Preview(preview) => {
+                render_hash_preview(frame, area, preview, &self.state, &self.theme);
+            }
             AppMode::Help => {
                 render_help_screen(frame, area, self.state.help_scroll, &self.theme);
             }
@@ -165,6 +892,18 @@ impl App {
         }
     }
 
+    /// Load the files changed by the cursor commit for the detail pane, once
+    fn load_file_changes(&mut self) {
+        let Some(id) = self.state.cursor_commit_id() else {
+            return;
+        };
+        if !self.state.file_changes.contains_key(&id) {
+            // A commit whose diff can't be read just lists no files
+            let files = self.repo.file_changes(id).unwrap_or_default();
+            self.state.file_changes.insert(id, files);
+        }
+    }
+
     /// Handle a key press
     fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
         // Clear messages on any key press
@@ -177,10 +916,90 @@ impl App {
                 self.handle_search_key(key);
                 Ok(())
             }
+            AppMode::Command => {
+                self.handle_command_key(key);
+                Ok(())
+            }
+            AppMode::Replace(target) => {
+                let target = target.clone();
+                self.handle_replace_key(key, target);
+                Ok(())
+            }
             AppMode::Confirming(action) => {
                 let action = action.clone();
                 self.handle_confirm_key(key, &action)
             }
+            AppMode::StepApply(step) => {
+                let step = step.clone();
+                self.handle_step_key(key, step)
+            }
+            AppMode::Applying => {
+                self.handle_applying_key(key);
+                Ok(())
+            }
+            AppMode::StashConflict(conflict) => {
+                let conflict = (**conflict).clone();
+                self.handle_stash_conflict_key(key, conflict)
+            }
+            AppMode::Split(chooser) => {
+                let chooser = (**chooser).clone();
+                self.handle_split_key(key, chooser);
+                Ok(())
+            }
+            AppMode::Applied(guidance) => {
+                let guidance = (**guidance).clone();
+                self.handle_applied_key(key, &guidance);
+                Ok(())
+            }
+            AppMode::BranchPicker(picker) => {
+                let picker = picker.clone();
+                self.handle_branch_picker_key(key, picker);
+                Ok(())
+            }
+            AppMode::Palette(palette) => {
+                let palette = palette.clone();
+                self.handle_palette_key(key, palette)
+            }
+            AppMode::IdentityPicker(picker) => {
+                let picker = picker.clone();
+                self.handle_identity_picker_key(key, picker);
+                Ok(())
+            }
+            AppMode::Trailers(editor) => {
+                let editor = (**editor).clone();
+                self.handle_trailer_key(key, editor);
+                Ok(())
+            }
+            AppMode::Divergence(report) => {
+                let report = report.clone();
+                self.handle_divergence_key(key, report);
+                Ok(())
+            }
+            AppMode::Lint(report) => {
+                let report = report.clone();
+                self.handle_lint_key(key, report);
+                Ok(())
+            }
+            AppMode::UndoHistory(browser) => {
+                let browser = *browser;
+                self.handle_undo_history_key(key, browser);
+                Ok(())
+            }
+            AppMode::Backups(list) => {
+                let list = list.clone();
+                self.handle_backups_key(key, list);
+                Ok(())
+            }
+            AppMode::Authors(view) => {
+                let view = (**view).clone();
+                self.handle_authors_key(key, view);
+                Ok(())
+            }
+            AppMode::Preview(preview) => {
+                let preview = (**preview).clone();
+                self.handle_preview_key(key, preview);
+                Ok(())
+            }
             AppMode::Help => {
                 self.handle_help_key(key);
                 Ok(())
@@ -195,32 +1014,81 @@ impl App {
 
     /// Handle key in normal mode
     fn handle_normal_key(&mut self, key: KeyEvent) -> Result<()> {
+        if self.take_register_key(key) {
+            return Ok(());
+        }
+        let register = self.pending_register.take();
+        if self.take_mark_key(key) {
+            return Ok(());
+        }
+
+        // `gg`: go to the first commit; `ge`: edit the cursor column on every
+        // visible commit; `gs`: sort by it. Any other key drops the `g`
+        if std::mem::take(&mut self.pending_g) {
+            match (key.code, key.modifiers) {
+                (KeyCode::Char('g'), KeyModifiers::NONE) => {
+                    self.remember_jump();
+                    self.state.cursor_top();
+                    return Ok(());
+                }
+                (KeyCode::Char('e'), KeyModifiers::NONE) => return self.start_edit_all_visible(),
+                (KeyCode::Char('s'), KeyModifiers::NONE) => {
+                    self.cycle_sort();
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        // Count prefix for j/k (`0` only continues a count)
+        let count = self.pending_count.take();
+        if let (KeyCode::Char(c @ '0'..='9'), KeyModifiers::NONE) = (key.code, key.modifiers) {
+            if c != '0' || count.is_some() {
+                let digit = c.to_digit(10).unwrap_or(0) as usize;
+                self.pending_count =
+                    Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                return Ok(());
+            }
+        }
+        // Moving further than the list is long gets nowhere new
+        let count = count.unwrap_or(1).min(self.state.visible_len().max(1));
+
         match (key.code, key.modifiers) {
             // Quit
             (KeyCode::Char('q'), KeyModifiers::NONE) => {
-                if self.state.is_dirty() {
-                    self.state.mode = AppMode::Quitting;
-                } else {
-                    self.should_quit = true;
-                }
+                self.execute_command(Command::Quit { force: false });
             }
 
             // Vertical navigation
             (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => {
-                self.state.cursor_down();
+                self.load_past_end()?;
+                for _ in 0..count {
+                    self.state.cursor_down();
+                }
             }
             (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => {
-                self.state.cursor_up();
+                for _ in 0..count {
+                    self.state.cursor_up();
+                }
             }
-            (KeyCode::Char('g') | KeyCode::Home, KeyModifiers::NONE) => {
+            (KeyCode::Char('g'), KeyModifiers::NONE) => self.pending_g = true,
+            (KeyCode::Home, KeyModifiers::NONE) => {
+                self.remember_jump();
                 self.state.cursor_top();
             }
             (KeyCode::Char('G') | KeyCode::End, KeyModifiers::NONE) => {
+                self.remember_jump();
                 self.state.cursor_bottom();
             }
+            (KeyCode::Char('o'), KeyModifiers::CONTROL) => self.jump_back(),
+            (KeyCode::Char('i'), KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                self.jump_forward();
+            }
             (KeyCode::Char('d'), KeyModifiers::CONTROL) | (KeyCode::PageDown, _) => {
+                self.load_past_end()?;
                 self.state.page_down(10);
             }
+            (KeyCode::Char('+'), _) => self.load_more()?,
             (KeyCode::Char('u'), KeyModifiers::CONTROL) | (KeyCode::PageUp, _) => {
                 self.state.page_up(10);
             }
@@ -252,6 +1120,13 @@ impl App {
                 self.toggle_deletion();
             }
 
+            // Fold commit into the one below (fixup)
+            (KeyCode::Char('f'), KeyModifiers::NONE) => {
+                self.toggle_fixup();
+            }
+
+            (KeyCode::Char('S'), _) => self.start_split(),
+
             // Move commit up/down (reorder)
             (KeyCode::Char('K'), KeyModifiers::SHIFT)
             | (KeyCode::Char('k'), KeyModifiers::CONTROL) => {
@@ -261,19 +1136,53 @@ impl App {
             | (KeyCode::Char('j'), KeyModifiers::CONTROL) => {
                 self.move_commit_down();
             }
+            (KeyCode::Char('M'), KeyModifiers::SHIFT) => {
+                self.execute_command(Command::FixDates(self.date_fix));
+            }
 
             // Start inline editing with Enter or 'e'
             (KeyCode::Enter | KeyCode::Char('e'), KeyModifiers::NONE) => {
                 self.start_inline_editing()?;
             }
+            // Edit the cursor column in the external editor
+            (KeyCode::Char('E'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.start_editing(true)?;
+            }
+
+            // Yank the cursor cell, paste into the commits to edit
+            (KeyCode::Char('y'), KeyModifiers::NONE) => self.yank_cell(register),
+            (KeyCode::Char('Y'), KeyModifiers::NONE | KeyModifiers::SHIFT) => self.copy_hash(),
+            (KeyCode::Char('p'), KeyModifiers::NONE) => self.paste_register(register),
+
+            // Find/replace in the cursor commit's message
+            (KeyCode::Char('R'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.start_replace();
+            }
 
             // Search
             (KeyCode::Char('/'), KeyModifiers::NONE) => {
                 self.search = SearchState::from_query(&self.state.search_query);
+                self.search_history.reset_navigation();
                 self.state.mode = AppMode::Search;
             }
+            (KeyCode::Char('n'), KeyModifiers::NONE) => {
+                self.jump_to_search_match(true);
+            }
+            (KeyCode::Char('N'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.jump_to_search_match(false);
+            }
 
-            // Undo/Redo
+            // Command line
+            (KeyCode::Char(':'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.command = SearchState::new();
+                self.state.mode = AppMode::Command;
+            }
+            (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                self.state.mode =
+                    AppMode::Palette(CommandPalette::new(String::new(), &self.plugins));
+            }
+
+            // Undo/Redo
             (KeyCode::Char('u'), KeyModifiers::NONE) => {
                 if self.state.undo() {
                     self.state.set_success("Undone");
@@ -288,6 +1197,16 @@ impl App {
                     self.state.set_error("Nothing to redo");
                 }
             }
+            (KeyCode::Char('U'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                let steps = self.state.undo_history().len();
+                if steps == 0 {
+                    self.state.set_error("Nothing to undo");
+                } else {
+                    // Start on the latest step still applied
+                    let cursor = self.state.redo_stack.len().min(steps - 1);
+                    self.state.mode = AppMode::UndoHistory(UndoBrowser { cursor });
+                }
+            }
 
             // Reset
             (KeyCode::Char('r'), KeyModifiers::NONE) => {
@@ -299,14 +1218,31 @@ impl App {
 
             // Apply changes
             (KeyCode::Char('w'), KeyModifiers::NONE) => {
-                if self.state.is_dirty() {
-                    self.confirm_dialog = ConfirmDialogState::default();
-                    self.state.mode = AppMode::Confirming(ConfirmAction::ApplyChanges);
-                } else {
-                    self.state.set_error("No changes to apply");
+                self.execute_command(Command::Write { quit: false });
+            }
+
+            // Apply changes, reviewing each changed commit first
+            (KeyCode::Char('W'), _) => self.start_step_apply(),
+
+            // Preview the new hashes
+            (KeyCode::Char('P'), _) => {
+                if let Err(e) = self.open_hash_preview() {
+                    self.state.set_error(e.to_string());
                 }
             }
 
+            (KeyCode::Char('B'), _) => self.open_branch_picker(),
+
+            // Trailers, or just co-authors
+            (KeyCode::Char('T'), _) => self.open_trailers(false),
+            (KeyCode::Char('A'), _) => self.open_trailers(true),
+
+            // Set the author from a list of identities
+            (KeyCode::Char('i'), KeyModifiers::NONE) => self.open_identity_picker(),
+
+            // Sign off the selected commits
+            (KeyCode::Char('O'), _) => self.execute_command(Command::SignOff { all: false }),
+
             // Help
             (KeyCode::Char('?'), KeyModifiers::NONE) => {
                 self.state.reset_help_scroll();
@@ -376,6 +1312,11 @@ impl App {
 
     /// Handle key in visual selection mode
     fn handle_visual_key(&mut self, key: KeyEvent) -> Result<()> {
+        if self.take_register_key(key) {
+            return Ok(());
+        }
+        let register = self.pending_register.take();
+
         match (key.code, key.modifiers) {
             // Exit visual mode
             (KeyCode::Esc, _) => {
@@ -449,13 +1390,17 @@ impl App {
                 self.state.column_right();
             }
 
+            // Jump to the other end of the selection
+            (KeyCode::Char('o'), KeyModifiers::NONE) => {
+                self.state.swap_visual_anchor();
+            }
+
             // Toggle checkbox selection for visual range
             (KeyCode::Char(' '), KeyModifiers::NONE) => {
                 // Toggle checkbox selection without exiting visual mode
                 if let Some(((start_row, _), (end_row, _))) = self.state.visual_range() {
-                    let visible = self.state.visible_commits();
                     let ids: Vec<_> = (start_row..=end_row)
-                        .filter_map(|row| visible.get(row).map(|c| c.id))
+                        .filter_map(|row| self.state.visible_commit(row).map(|c| c.id))
                         .collect();
                     for id in ids {
                         if self.state.selected.contains(&id) {
@@ -467,6 +1412,22 @@ impl App {
                 }
             }
 
+            // Fill the cursor cell's value into the rest of the selection
+            (KeyCode::Char('f'), KeyModifiers::NONE) => {
+                self.start_fill();
+            }
+
+            // Yank the cursor cell, or paste over the whole selection
+            (KeyCode::Char('y'), KeyModifiers::NONE) => {
+                self.yank_cell(register);
+                self.state.exit_visual_mode();
+            }
+            (KeyCode::Char('p'), KeyModifiers::NONE) => {
+                if self.state.capture_visual_edit_targets() > 0 {
+                    self.paste_register(register);
+                }
+            }
+
             // Edit visual selection (capture targets and start editing)
             (KeyCode::Char('e') | KeyCode::Enter, KeyModifiers::NONE) => {
                 let count = self.state.capture_visual_edit_targets();
@@ -474,6 +1435,12 @@ impl App {
                     self.start_inline_editing()?;
                 }
             }
+            (KeyCode::Char('E'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                let count = self.state.capture_visual_edit_targets();
+                if count > 0 {
+                    self.start_editing(true)?;
+                }
+            }
 
             _ => {}
         }
@@ -481,10 +1448,35 @@ impl App {
         Ok(())
     }
 
+    /// Sort by the cursor column: ascending, then descending, then back to
+    /// the commit order
+    fn cycle_sort(&mut self) {
+        let Some(key) = Column::from_index(self.state.column_index)
+            .and_then(|column| column.to_editable_field())
+            .and_then(SortKey::for_field)
+        else {
+            self.state.set_error("Can't sort by this column");
+            return;
+        };
+        let sort = match self.state.sort {
+            Some(sort) if sort.key == key && sort.descending => None,
+            Some(sort) if sort.key == key => Some(SortOrder {
+                key,
+                descending: true,
+            }),
+            _ => Some(SortOrder {
+                key,
+                descending: false,
+            }),
+        };
+        self.execute_command(Command::Sort(sort));
+    }
+
     /// Move commit at cursor up (swap with previous)
     fn move_commit_up(&mut self) {
         if self.state.filtered_indices.is_some() {
-            self.state.set_error("Cannot reorder while filtering");
+            self.state
+                .set_error("Cannot reorder while filtering or sorting");
             return;
         }
 
@@ -501,15 +1493,31 @@ impl App {
             }
         }
 
+        // Swapping rewrites both commits
+        if !self.check_unlocked(&self.swap_pair(self.state.cursor - 1)) {
+            return;
+        }
+
         // AppState.move_commit_up() handles save_undo internally
         self.state.move_commit_up();
-        self.state.set_success("Commit moved up");
+        self.state
+            .set_success(self.reorder_message("Commit moved up"));
+    }
+
+    /// Status message after a reorder, pointing at `M` when the dates are
+    /// out of order
+    fn reorder_message(&self, done: &str) -> String {
+        match self.state.dates_out_of_order() {
+            0 => done.to_string(),
+            count => format!("{done}; {count} commit(s) now dated before the one below (M fixes)"),
+        }
     }
 
     /// Move commit at cursor down (swap with next)
     fn move_commit_down(&mut self) {
         if self.state.filtered_indices.is_some() {
-            self.state.set_error("Cannot reorder while filtering");
+            self.state
+                .set_error("Cannot reorder while filtering or sorting");
             return;
         }
 
@@ -526,9 +1534,23 @@ impl App {
             }
         }
 
+        // Swapping rewrites both commits
+        if !self.check_unlocked(&self.swap_pair(self.state.cursor + 1)) {
+            return;
+        }
+
         // AppState.move_commit_down() handles save_undo internally
         self.state.move_commit_down();
-        self.state.set_success("Commit moved down");
+        self.state
+            .set_success(self.reorder_message("Commit moved down"));
+    }
+
+    /// The cursor commit and the commit at `other` (rows being swapped)
+    fn swap_pair(&self, other: usize) -> Vec<CommitId> {
+        [self.state.cursor, other]
+            .into_iter()
+            .filter_map(|idx| self.state.commits.get(idx).map(|c| c.id))
+            .collect()
     }
 
     /// Toggle deletion on the current commit or selected commits
@@ -543,16 +1565,91 @@ impl App {
         };
 
         // Check if we're toggling on or off (based on first commit)
-        let will_delete = !self.state.is_deleted(commit_ids[0]);
-        let count = commit_ids.len();
+        if self.state.is_deleted(commit_ids[0]) {
+            self.set_deletion(&commit_ids, false);
+        } else {
+            self.delete_commits(&commit_ids);
+        }
+    }
 
+    /// Mark commits for deletion, asking first when there are many
+    fn delete_commits(&mut self, commit_ids: &[CommitId]) {
         // Don't allow deleting all commits
         let remaining_after = self.state.commits.len() - self.state.deleted.len();
-        if will_delete && count >= remaining_after {
+        if commit_ids.len() >= remaining_after {
             self.state.set_error("Cannot delete all commits");
             return;
         }
 
+        if !self.check_unlocked(commit_ids) {
+            return;
+        }
+
+        if let Some(action) = self.state.delete_confirmation(commit_ids) {
+            self.confirm_dialog = ConfirmDialogState::default();
+            self.state.mode = AppMode::Confirming(action);
+            return;
+        }
+
+        self.set_deletion(commit_ids, true);
+    }
+
+    /// Toggle folding the selected commits (or cursor commit) into the commit
+    /// below, keeping the latter's message (`git rebase` fixup)
+    fn toggle_fixup(&mut self) {
+        let commit_ids: Vec<CommitId> = if !self.state.selected.is_empty() {
+            self.state.selected.iter().copied().collect()
+        } else if let Some(id) = self.state.cursor_commit_id() {
+            vec![id]
+        } else {
+            return;
+        };
+
+        // Based on the first commit, like deletion
+        let will_fold = self.state.squashes.get(&commit_ids[0]) != Some(&SquashKind::Fixup);
+        let count = commit_ids.len();
+        let hidden_note = hidden_suffix(self.state.hidden_count(&commit_ids));
+        if !will_fold {
+            self.state.set_squash(&commit_ids, None);
+            self.state
+                .set_success(format!("{count} commit(s) no longer folded{hidden_note}"));
+            return;
+        }
+
+        if self.state.cannot_fold(&commit_ids) {
+            self.state
+                .set_error("Merges and the oldest loaded commit have nothing to fold into");
+            return;
+        }
+        if commit_ids
+            .iter()
+            .any(|id| self.state.splits.contains_key(id))
+        {
+            self.state
+                .set_error("Split commits can't be folded (remove the split with S first)");
+            return;
+        }
+        // Folding rewrites the commit below too
+        let mut touched = commit_ids.clone();
+        touched.extend(
+            commit_ids
+                .iter()
+                .filter_map(|&id| self.state.fold_target(id)),
+        );
+        if !self.check_unlocked(&touched) {
+            return;
+        }
+        self.state.set_squash(&commit_ids, Some(SquashKind::Fixup));
+        self.state.set_success(format!(
+            "{count} commit(s) will be folded into the commit below{hidden_note}"
+        ));
+    }
+
+    /// Mark (or restore) commits for deletion, with undo and feedback
+    fn set_deletion(&mut self, commit_ids: &[CommitId], will_delete: bool) {
+        let count = commit_ids.len();
+        let hidden = self.state.hidden_count(commit_ids);
+
         // Save undo state
         let description = if will_delete {
             format!("Delete {count} commit(s)")
@@ -562,7 +1659,7 @@ impl App {
         self.state.save_undo(&description);
 
         // Toggle deletion for all target commits
-        for id in commit_ids {
+        for &id in commit_ids {
             if will_delete {
                 self.state.mark_deleted(id);
             } else {
@@ -571,22 +1668,41 @@ impl App {
         }
 
         // Show feedback
+        let hidden_note = hidden_suffix(hidden);
         if will_delete {
             if count > 1 {
                 self.state
-                    .set_success(format!("{count} commits marked for deletion"));
+                    .set_success(format!("{count} commits marked for deletion{hidden_note}"));
             } else {
-                self.state.set_success("Commit marked for deletion");
+                self.state
+                    .set_success(format!("Commit marked for deletion{hidden_note}"));
             }
         } else if count > 1 {
-            self.state.set_success(format!("{count} commits restored"));
+            self.state
+                .set_success(format!("{count} commits restored{hidden_note}"));
         } else {
-            self.state.set_success("Commit restored");
+            self.state
+                .set_success(format!("Commit restored{hidden_note}"));
+        }
+
+        // Reverts of (or references to) the deleted commits would dangle
+        if will_delete {
+            let pairs = self.state.referencing_commits(commit_ids);
+            if !pairs.is_empty() {
+                self.confirm_dialog = ConfirmDialogState::default();
+                self.state.mode = AppMode::Confirming(ConfirmAction::DeleteReferencing { pairs });
+            }
         }
     }
 
     /// Start inline editing at current column
     fn start_inline_editing(&mut self) -> Result<()> {
+        self.start_editing(false)
+    }
+
+    /// Start editing the current column: inline, or in the external editor
+    /// for messages and when `external` is set
+    fn start_editing(&mut self, external: bool) -> Result<()> {
         let Some(commit) = self.state.cursor_commit() else {
             return Ok(());
         };
@@ -614,8 +1730,13 @@ impl App {
         let mods = self.state.modifications.get(&commit.id);
         let current_value = get_column_value(commit, mods, column);
 
+        let targets = self.state.commits_to_edit();
+        if !self.check_editable(&targets, PendingEdit::InlineEdit { external }) {
+            return Ok(());
+        }
+
         // For commit messages (multiline), open external editor
-        if field == EditableField::Message {
+        if field == EditableField::Message || external {
             return self.open_external_editor(field, &current_value);
         }
 
@@ -623,6 +1744,7 @@ impl App {
         self.state.edit_buffer = current_value.clone();
         self.state.edit_original = current_value;
         self.state.edit_cursor = self.state.edit_buffer.len();
+        self.state.reset_buffer_history();
 
         self.state.mode = AppMode::Editing {
             commit_idx: self.state.cursor,
@@ -632,6 +1754,70 @@ impl App {
         Ok(())
     }
 
+    /// Edit the cursor column on every visible (filtered) commit at once
+    fn start_edit_all_visible(&mut self) -> Result<()> {
+        let editable = Column::from_index(self.state.column_index)
+            .and_then(|c| c.to_editable_field())
+            .is_some();
+        if !editable {
+            self.state.set_error("This column is not editable");
+            return Ok(());
+        }
+
+        let ids: Vec<CommitId> = self.state.visible_iter().map(|c| c.id).collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.state.visual_edit_targets = Some(ids);
+        self.start_inline_editing()
+    }
+
+    /// Start a fill from visual mode: copy the cursor cell's value (including any
+    /// pending edit) into the current column of every other selected row
+    fn start_fill(&mut self) {
+        let editable = Column::from_index(self.state.column_index)
+            .and_then(|c| c.to_editable_field())
+            .is_some();
+        if !editable {
+            self.state.set_error("This column is not editable");
+            return;
+        }
+
+        self.state.capture_visual_edit_targets();
+        let targets = self.state.commits_to_edit();
+        if self.check_editable(&targets, PendingEdit::Fill) {
+            self.fill_from_cursor();
+        }
+    }
+
+    /// Apply a fill to the captured visual edit targets
+    fn fill_from_cursor(&mut self) {
+        let targets = self.state.commits_to_edit();
+        self.state.clear_visual_edit_targets();
+
+        let Some(field) =
+            Column::from_index(self.state.column_index).and_then(|c| c.to_editable_field())
+        else {
+            return;
+        };
+        let Some(source) = self.state.cursor_commit_id() else {
+            return;
+        };
+
+        let filled = self.state.fill(source, &targets, field);
+        if filled.is_empty() {
+            self.state.set_success("Nothing to fill");
+            return;
+        }
+        let hidden = self.state.hidden_count(&filled);
+        self.state.set_success(format!(
+            "Filled {} into {} commit(s){}",
+            field.display_name(),
+            filled.len(),
+            hidden_suffix(hidden)
+        ));
+    }
+
     /// Open external editor for multiline/long content
     fn open_external_editor(&mut self, field: EditableField, current_value: &str) -> Result<()> {
         use std::io::Write;
@@ -669,6 +1855,28 @@ impl App {
                 let new_value = std::fs::read_to_string(&temp_path)?;
                 let new_value = new_value.trim_end().to_string();
 
+                // Single-line fields go through the same checks as typing them
+                if !field.is_multiline() {
+                    let new_value = new_value.trim_start();
+                    if new_value.contains('\n') {
+                        self.state.clear_visual_edit_targets();
+                        self.state
+                            .set_error(format!("{} must be one line", field.display_name()));
+                        return Ok(());
+                    }
+                    // A value that doesn't validate is left in the inline editor to fix
+                    self.state.edit_buffer = new_value.to_string();
+                    self.state.edit_original = current_value.to_string();
+                    self.state.edit_cursor = self.state.edit_buffer.len();
+                    self.state.reset_buffer_history();
+                    self.state.mode = AppMode::Editing {
+                        commit_idx: self.state.cursor,
+                        field,
+                    };
+                    self.confirm_inline_edit(self.state.cursor, field);
+                    return Ok(());
+                }
+
                 if new_value != current_value {
                     // Get commits to edit: visual targets > checkbox selected > cursor
                     let commit_ids = self.state.commits_to_edit();
@@ -678,18 +1886,22 @@ impl App {
                     }
 
                     let count = commit_ids.len();
+                    let hidden = self.state.hidden_count(&commit_ids);
                     let field_name = field.display_name();
                     self.state
                         .save_undo(&format!("Edit {field_name} on {count} commit(s)"));
 
                     for cid in commit_ids {
-                        self.apply_field_edit(cid, field, &new_value, current_value);
+                        self.state.set_field(cid, field, &new_value, current_value);
                     }
 
                     self.state.clear_visual_edit_targets();
 
                     if count > 1 {
-                        self.state.set_success(format!("Updated {count} commits"));
+                        self.state.set_success(format!(
+                            "Updated {count} commits{}",
+                            hidden_suffix(hidden)
+                        ));
                     } else {
                         self.state.set_success("Message updated");
                     }
@@ -745,6 +1957,65 @@ impl App {
                 }
             }
 
+            // Find/replace in the value being edited, then back here
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                self.open_replace(ReplaceTarget::EditBuffer { commit_idx, field });
+            }
+
+            // Paste from the clipboard at the cursor
+            (KeyCode::Char('v'), KeyModifiers::CONTROL) => match self.clipboard.paste() {
+                Ok(text) => {
+                    let before_buffer = self.state.edit_buffer.clone();
+                    let before_cursor = self.state.edit_cursor;
+                    // Only the message holds more than one line
+                    let text = if field.is_multiline() {
+                        text
+                    } else {
+                        text.lines()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .trim()
+                            .to_string()
+                    };
+                    self.state.edit_buffer.insert_str(before_cursor, &text);
+                    self.state.edit_cursor += text.len();
+                    self.state
+                        .record_buffer_change(before_buffer, before_cursor, false);
+                }
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+
+            // Undo/redo within the edit buffer (separate from app-level undo)
+            (KeyCode::Char('z'), KeyModifiers::CONTROL) => {
+                if !self.state.undo_buffer_edit() {
+                    self.state.set_error("Nothing to undo in this field");
+                }
+            }
+            (KeyCode::Char('y'), KeyModifiers::CONTROL) => {
+                if !self.state.redo_buffer_edit() {
+                    self.state.set_error("Nothing to redo in this field");
+                }
+            }
+
+            _ => {
+                let before_buffer = self.state.edit_buffer.clone();
+                let before_cursor = self.state.edit_cursor;
+                let typing = matches!(
+                    (key.code, key.modifiers),
+                    (KeyCode::Char(_), KeyModifiers::NONE | KeyModifiers::SHIFT)
+                );
+                self.handle_edit_buffer_key(key);
+                self.state
+                    .record_buffer_change(before_buffer, before_cursor, typing);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply a text-editing key to the inline edit buffer
+    fn handle_edit_buffer_key(&mut self, key: KeyEvent) {
+        match (key.code, key.modifiers) {
             // Text editing - insert at cursor position
             (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
                 let cursor = self.state.edit_cursor;
@@ -815,8 +2086,6 @@ impl App {
 
             _ => {}
         }
-
-        Ok(())
     }
 
     /// Move edit cursor to previous word boundary
@@ -871,23 +2140,14 @@ impl App {
         let new_value = self.state.edit_buffer.clone();
         let original_value = self.state.edit_original.clone();
 
-        // Validate based on field type
-        if field.is_email() {
-            if let Err(e) = validate_email(&new_value) {
-                self.state.set_error(e.to_string());
-                return;
-            }
-        }
-
-        if field.is_date() {
-            if let Err(e) = validate_date(&new_value) {
-                self.state.set_error(e.to_string());
-                return;
-            }
+        if !self.check_field_value(field, &new_value) {
+            return;
         }
 
         // Only save if value changed
-        if new_value != original_value {
+        let autofill = if new_value == original_value {
+            None
+        } else {
             // Get commits to edit: visual targets > checkbox selected > cursor
             let commit_ids = self.state.commits_to_edit();
             if commit_ids.is_empty() {
@@ -898,19 +2158,24 @@ impl App {
 
             // Save undo state before modification
             let count = commit_ids.len();
+            let hidden = self.state.hidden_count(&commit_ids);
             let field_name = field.display_name();
             self.state
                 .save_undo(&format!("Edit {field_name} on {count} commit(s)"));
 
             // Apply the modification to all target commits
-            for cid in commit_ids {
-                self.apply_field_edit(cid, field, &new_value, &original_value);
+            for &cid in &commit_ids {
+                self.state
+                    .set_field(cid, field, &new_value, &original_value);
             }
 
             if count > 1 {
-                self.state.set_success(format!("Updated {count} commits"));
+                self.state
+                    .set_success(format!("Updated {count} commits{}", hidden_suffix(hidden)));
             }
-        }
+
+            self.identity_autofill(field, &new_value, &commit_ids)
+        };
 
         // Clear edit state
         self.state.edit_buffer.clear();
@@ -918,233 +2183,2885 @@ impl App {
         self.state.edit_cursor = 0;
         self.state.clear_visual_edit_targets();
         self.state.mode = AppMode::Normal;
+
+        if let Some(action) = autofill {
+            self.confirm_dialog = ConfirmDialogState::default();
+            self.state.mode = AppMode::Confirming(action);
+        }
     }
 
-    /// Apply a field edit to a single commit
+    /// Validate a value for a field, setting the error if it isn't valid
     ///
-    /// When `sync_author_to_committer` is enabled in the app state, editing
-    /// author fields (name, email, date) will also update the corresponding
-    /// committer fields. This is the default behavior since most workflows
-    /// keep author and committer identical.
-    fn apply_field_edit(
-        &mut self,
-        commit_id: CommitId,
-        field: EditableField,
-        new_value: &str,
-        original_value: &str,
-    ) {
-        let sync = self.state.sync_author_to_committer;
-        let mods = self.state.get_or_create_modifications(commit_id);
-
-        match field {
-            EditableField::AuthorName => {
-                mods.author_name = Some(new_value.to_string());
-                // Sync to committer if enabled
-                if sync {
-                    mods.committer_name = Some(new_value.to_string());
-                }
-            }
-            EditableField::AuthorEmail => {
-                mods.author_email = Some(new_value.to_string());
-                // Sync to committer if enabled
-                if sync {
-                    mods.committer_email = Some(new_value.to_string());
-                }
-            }
-            EditableField::AuthorDate => {
-                if new_value != original_value {
-                    if let Ok(dt) = validate_date(new_value) {
-                        mods.author_date = Some(dt);
-                        // Sync to committer if enabled
-                        if sync {
-                            mods.committer_date = Some(dt);
-                        }
-                    }
+    /// Invalid emails pass (and are logged as a bypass) with `--force`.
+    fn check_field_value(&mut self, field: EditableField, value: &str) -> bool {
+        if field.is_email() {
+            if let Err(e) = validate_email(value) {
+                if !self.state.force {
+                    self.state.set_error(e.to_string());
+                    return false;
                 }
+                self.state.record_bypass(Bypass::Validation);
             }
-            EditableField::CommitterName => {
-                mods.committer_name = Some(new_value.to_string());
+        }
+        if field.is_date() {
+            if let Err(e) = validate_date(value) {
+                self.state.set_error(e.to_string());
+                return false;
             }
-            EditableField::CommitterEmail => {
-                mods.committer_email = Some(new_value.to_string());
+        }
+        true
+    }
+
+    /// Take a `"x` register prefix; true if the key was part of one
+    fn take_register_key(&mut self, key: KeyEvent) -> bool {
+        if std::mem::take(&mut self.register_prefix) {
+            match key.code {
+                KeyCode::Char(c)
+                    if c.is_ascii_lowercase()
+                        || c.is_ascii_digit()
+                        || c == UNNAMED_REGISTER
+                        || c == CLIPBOARD_REGISTER =>
+                {
+                    self.pending_register = Some(c);
+                }
+                _ => self
+                    .state
+                    .set_error("Registers are named a-z or 0-9 (+ for the clipboard)"),
             }
-            EditableField::CommitterDate => {
-                if new_value != original_value {
-                    if let Ok(dt) = validate_date(new_value) {
-                        mods.committer_date = Some(dt);
+            return true;
+        }
+        if key.code == KeyCode::Char('"') {
+            self.register_prefix = true;
+            return true;
+        }
+        false
+    }
+
+    /// Handle `m{a-z}` (set a mark) and `'{a-z}` (jump to one); true if the
+    /// key was taken
+    fn take_mark_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(prefix) = self.mark_prefix.take() {
+            match key.code {
+                KeyCode::Char(name) if Marks::is_valid_name(name) => {
+                    if prefix == 'm' {
+                        self.set_mark(name);
+                    } else {
+                        self.jump_to_mark(name);
                     }
                 }
+                KeyCode::Esc => {}
+                _ => self.state.set_error("Marks are named a-z"),
             }
-            EditableField::Message => {
-                mods.message = Some(new_value.to_string());
+            return true;
+        }
+        if key.modifiers == KeyModifiers::NONE {
+            if let KeyCode::Char(prefix @ ('m' | '\'')) = key.code {
+                self.mark_prefix = Some(prefix);
+                return true;
             }
         }
+        false
     }
 
-    /// Handle key in search mode
-    fn handle_search_key(&mut self, key: KeyEvent) {
-        match (key.code, key.modifiers) {
-            (KeyCode::Esc, _) => {
-                self.state.clear_filter();
-                self.state.mode = AppMode::Normal;
-            }
-            (KeyCode::Enter, _) => {
-                self.state.search_query = self.search.query.clone();
-                self.state.apply_filter();
-                self.state.mode = AppMode::Normal;
-            }
-            // Delete character
-            (KeyCode::Backspace, KeyModifiers::NONE) => {
-                self.search.backspace();
-            }
-            (KeyCode::Delete, KeyModifiers::NONE) => {
-                self.search.delete();
-            }
-            // Delete word (Alt+Backspace on Mac, Ctrl+W or Ctrl+Backspace)
-            (KeyCode::Backspace, KeyModifiers::ALT | KeyModifiers::CONTROL)
-            | (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
-                self.search.delete_word_backward();
-            }
-            // Delete to start of line (Ctrl+U)
-            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
-                self.search.delete_to_start();
-            }
-            // Delete to end of line (Ctrl+K)
-            (KeyCode::Char('k'), KeyModifiers::CONTROL) => {
-                self.search.delete_to_end();
-            }
-            // Move by character
-            (KeyCode::Left, KeyModifiers::NONE) => {
-                self.search.move_left();
-            }
-            (KeyCode::Right, KeyModifiers::NONE) => {
-                self.search.move_right();
-            }
-            // Move by word (Alt+Arrow on Mac, Ctrl+Arrow)
-            (KeyCode::Left, KeyModifiers::ALT | KeyModifiers::CONTROL) => {
-                self.search.move_word_left();
-            }
-            (KeyCode::Right, KeyModifiers::ALT | KeyModifiers::CONTROL) => {
-                self.search.move_word_right();
-            }
-            // Move to start/end (Home or Ctrl+A)
-            (KeyCode::Home, _) | (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
-                self.search.move_start();
-            }
-            // Move to end (End or Ctrl+E)
-            (KeyCode::End, _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
-                self.search.move_end();
-            }
-            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
-                self.search.insert(c);
-            }
-            _ => {}
+    fn set_mark(&mut self, name: char) {
+        if let Some(commit) = self.state.cursor_commit() {
+            let msg = format!("Mark '{name}' set on {}", commit.short_hash);
+            self.marks.set(name, commit.id);
+            self.state.set_success(msg);
         }
     }
 
-    /// Handle key in confirmation dialog
-    fn handle_confirm_key(&mut self, key: KeyEvent, action: &ConfirmAction) -> Result<()> {
-        match (key.code, key.modifiers) {
-            (KeyCode::Esc, _) | (KeyCode::Char('n'), KeyModifiers::NONE) => {
-                self.state.mode = AppMode::Normal;
-            }
-            (KeyCode::Char('y'), KeyModifiers::NONE) | (KeyCode::Enter, _)
-                if self.confirm_dialog.is_yes_selected() =>
-            {
-                self.execute_confirmed_action(action)?;
+    fn jump_to_mark(&mut self, name: char) {
+        match self.marks.get(name) {
+            Some(id) => {
+                if let Some(row) = self.state.visible_index_of(id) {
+                    self.remember_jump();
+                    self.state.set_cursor_row(row);
+                } else {
+                    self.state
+                        .set_error(format!("Mark '{name}' is on a commit that isn't shown"));
+                }
             }
-            (KeyCode::Char('y'), KeyModifiers::NONE) => {
+            None => self.state.set_error(format!("Mark '{name}' is not set")),
+        }
+    }
+
+    /// Add the cursor commit to the jump list before jumping away from it
+    fn remember_jump(&mut self) {
+        if let Some(id) = self.state.cursor_commit_id() {
+            self.marks.record_jump(id);
+        }
+    }
+
+    /// Go back through the jump list (`Ctrl+o`)
+    fn jump_back(&mut self) {
+        let target = self
+            .state
+            .cursor_commit_id()
+            .and_then(|current| self.marks.back(current));
+        match target {
+            Some(id) => self.move_to_jump(id),
+            None => self.state.set_error("Already at the oldest jump"),
+        }
+    }
+
+    /// Go forward through the jump list again (`Ctrl+i`)
+    fn jump_forward(&mut self) {
+        match self.marks.forward() {
+            Some(id) => self.move_to_jump(id),
+            None => self.state.set_error("Already at the newest jump"),
+        }
+    }
+
+    fn move_to_jump(&mut self, id: CommitId) {
+        match self.state.visible_index_of(id) {
+            Some(row) => self.state.set_cursor_row(row),
+            None => self
+                .state
+                .set_error("That commit is hidden by the filter or no longer loaded"),
+        }
+    }
+
+    /// Copy the cursor cell's value (with pending edits) into a register (`y`)
+    fn yank_cell(&mut self, register: Option<char>) {
+        let Some(commit) = self.state.cursor_commit() else {
+            return;
+        };
+        let Some(column) = Column::from_index(self.state.column_index) else {
+            return;
+        };
+        let (what, value) = match (column, column.to_editable_field()) {
+            (Column::Hash, _) => ("hash", commit.id.0.to_string()),
+            (_, Some(field)) => (
+                field.display_name(),
+                get_column_value(commit, self.state.modifications.get(&commit.id), column),
+            ),
+            (_, None) => {
+                self.state.set_error("Nothing to yank in this column");
+                return;
+            }
+        };
+        if register == Some(CLIPBOARD_REGISTER) {
+            self.copy_to_clipboard(&value, what);
+            self.state.yank(None, &value);
+            return;
+        }
+        self.state.yank(register, &value);
+        let into = register.map_or_else(String::new, |name| format!(" into \"{name}"));
+        self.state.set_success(format!("Yanked {what}{into}"));
+    }
+
+    /// Copy the cursor commit's full hash to the clipboard (`Y`)
+    fn copy_hash(&mut self) {
+        if let Some(hash) = self.state.cursor_commit().map(|c| c.id.0.to_string()) {
+            self.copy_to_clipboard(&hash, "hash");
+        }
+    }
+
+    /// Put text on the clipboard, reporting it in the status bar
+    fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        match self.clipboard.copy(text) {
+            Ok(()) => {
+                let mut shown: String =
+                    text.lines().next().unwrap_or("").chars().take(40).collect();
+                if shown.len() < text.len() {
+                    shown.push('…');
+                }
+                self.state
+                    .set_success(format!("Copied {what} to the clipboard: {shown}"));
+            }
+            Err(e) => self.state.set_error(e.to_string()),
+        }
+    }
+
+    /// Paste a register into the cursor column of the commits to edit (`p`)
+    fn paste_register(&mut self, register: Option<char>) {
+        let Some(field) =
+            Column::from_index(self.state.column_index).and_then(|c| c.to_editable_field())
+        else {
+            self.state.clear_visual_edit_targets();
+            self.state.set_error("This column is not editable");
+            return;
+        };
+        let value = if register == Some(CLIPBOARD_REGISTER) {
+            match self.clipboard.paste() {
+                Ok(text) => Some(text),
+                Err(e) => {
+                    self.state.clear_visual_edit_targets();
+                    self.state.set_error(e.to_string());
+                    return;
+                }
+            }
+        } else {
+            self.state.register(register).map(str::to_string)
+        };
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            self.state.clear_visual_edit_targets();
+            let name = register.unwrap_or(UNNAMED_REGISTER);
+            self.state.set_error(format!("Register \"{name} is empty"));
+            return;
+        };
+        let value = if field.is_multiline() {
+            value
+        } else {
+            value.trim().to_string()
+        };
+        if !field.is_multiline() && value.contains('\n') {
+            self.state.clear_visual_edit_targets();
+            self.state
+                .set_error(format!("{} must be one line", field.display_name()));
+            return;
+        }
+        if !self.check_field_value(field, &value) {
+            self.state.clear_visual_edit_targets();
+            return;
+        }
+
+        let targets = self.state.commits_to_edit();
+        if !self.check_editable(&targets, PendingEdit::Paste { register }) {
+            return;
+        }
+        self.state.clear_visual_edit_targets();
+        let column = Column::from_index(self.state.column_index).unwrap_or(Column::Message);
+        let pastes: Vec<(CommitId, String)> = targets
+            .iter()
+            .filter_map(|&id| {
+                let commit = self
+                    .state
+                    .commits
+                    .iter()
+                    .find(|c| c.id == id && !c.is_merge)?;
+                let current = get_column_value(commit, self.state.modifications.get(&id), column);
+                (current != value).then_some((id, current))
+            })
+            .collect();
+        if pastes.is_empty() {
+            self.state.set_success("Nothing to paste");
+            return;
+        }
+
+        let count = pastes.len();
+        let ids: Vec<CommitId> = pastes.iter().map(|(id, _)| *id).collect();
+        let hidden = self.state.hidden_count(&ids);
+        self.state.save_undo(&format!(
+            "Paste {} on {count} commit(s)",
+            field.display_name()
+        ));
+        for (id, current) in &pastes {
+            self.state.set_field(*id, field, &value, current);
+        }
+        self.state.set_success(format!(
+            "Pasted {} into {count} commit(s){}",
+            field.display_name(),
+            hidden_suffix(hidden)
+        ));
+    }
+
+    /// Offer to fill the other half of a known identity after a name/email edit
+    ///
+    /// Returns the confirmation to show, if any target's counterpart field
+    /// differs from the known pairing.
+    fn identity_autofill(
+        &self,
+        field: EditableField,
+        value: &str,
+        commit_ids: &[CommitId],
+    ) -> Option<ConfirmAction> {
+        let counterpart = field.identity_counterpart()?;
+        let known = if field.is_email() {
+            self.identities.name_for(value)
+        } else {
+            self.identities.email_for(value)
+        }?;
+
+        let targets: Vec<CommitId> = commit_ids
+            .iter()
+            .copied()
+            .filter(|&id| {
+                self.state
+                    .effective_identity_value(id, counterpart)
+                    .is_some_and(|v| v != known)
+            })
+            .collect();
+        if targets.is_empty() {
+            return None;
+        }
+
+        Some(ConfirmAction::AutoFillIdentity {
+            field: counterpart,
+            value: known.to_string(),
+            targets,
+        })
+    }
+
+    /// Filter the table by a search query (`/`, `--touching`)
+    ///
+    /// # Errors
+    /// Returns an error if the query is invalid or finding the commits that
+    /// touch its paths fails.
+    pub fn set_search(&mut self, query: &str) -> Result<()> {
+        SearchQuery::parse(query)?;
+        self.state.search_query = query.to_string();
+        let result = self.refresh_touched_paths();
+        self.state.apply_filter();
+        result
+    }
+
+    /// Find the loaded commits touching the paths of the current and last
+    /// searches' `path:` filters, forgetting paths no longer searched for
+    fn refresh_touched_paths(&mut self) -> Result<()> {
+        let mut paths: Vec<TouchedPath> = Vec::new();
+        for query in [&self.state.search_query, &self.state.last_search] {
+            if let Ok(query) = SearchQuery::parse(query) {
+                paths.extend(query.paths().cloned());
+            }
+        }
+        self.state
+            .touched_paths
+            .retain(|path, _| paths.contains(path));
+        paths.retain(|path| !self.state.touched_paths.contains_key(path));
+        paths.sort();
+        paths.dedup();
+        if paths.is_empty() {
+            return Ok(());
+        }
+        // Renames are followed from newest to oldest, whatever the table's order
+        let by_id: HashMap<CommitId, &CommitData> =
+            self.state.commits.iter().map(|c| (c.id, c)).collect();
+        let history: Vec<CommitData> = self
+            .state
+            .original_order
+            .iter()
+            .filter_map(|id| by_id.get(id).map(|c| (*c).clone()))
+            .collect();
+        for path in paths {
+            let touching =
+                self.repo
+                    .commits_touching_path(&history, &path.path, path.follow_renames)?;
+            self.state.touched_paths.insert(path, touching);
+        }
+        Ok(())
+    }
+
+    /// Jump to the next/previous commit matching the last search (n/N)
+    fn jump_to_search_match(&mut self, forward: bool) {
+        if self.state.last_search.is_empty() {
+            self.state.set_error("No previous search");
+            return;
+        }
+        self.remember_jump();
+        match self.state.jump_to_match(forward) {
+            None => {
+                let msg = format!("Pattern not found: {}", self.state.last_search);
+                self.state.set_error(msg);
+            }
+            Some(true) if forward => self
+                .state
+                .set_success("Search hit bottom, continuing at top"),
+            Some(true) => self
+                .state
+                .set_success("Search hit top, continuing at bottom"),
+            Some(false) => {}
+        }
+    }
+
+    /// Handle key in search mode
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) => {
+                self.state.clear_filter();
+                self.state.mode = AppMode::Normal;
+            }
+            // An invalid regex stays in the search bar to be fixed
+            (KeyCode::Enter, _) if SearchQuery::parse(&self.search.query).is_err() => {}
+            (KeyCode::Enter, _) => {
+                self.search_history.record(&self.search.query);
+                self.state.mode = AppMode::Normal;
+                let query = self.search.query.clone();
+                if let Err(e) = self.set_search(&query) {
+                    self.state.set_error(e.to_string());
+                }
+            }
+            // Recall past queries
+            (KeyCode::Up, KeyModifiers::NONE) => {
+                if let Some(query) = self.search_history.older(&self.search.query) {
+                    self.search = SearchState::from_query(&query);
+                }
+            }
+            (KeyCode::Down, KeyModifiers::NONE) => {
+                if let Some(query) = self.search_history.newer() {
+                    self.search = SearchState::from_query(&query);
+                }
+            }
+            _ => handle_line_edit_key(&mut self.search, key),
+        }
+    }
+
+    /// Handle key in command line mode
+    fn handle_command_key(&mut self, key: KeyEvent) {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) => {
+                self.state.mode = AppMode::Normal;
+            }
+            // Backspace on an empty command line leaves command mode (like vim)
+            (KeyCode::Backspace, KeyModifiers::NONE) if self.command.query.is_empty() => {
+                self.state.mode = AppMode::Normal;
+            }
+            // Look the typed text up in the command palette instead
+            (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                self.state.mode = AppMode::Palette(CommandPalette::new(
+                    std::mem::take(&mut self.command.query),
+                    &self.plugins,
+                ));
+            }
+            // Paste the first line of the clipboard, e.g. a copied hash
+            (KeyCode::Char('v'), KeyModifiers::CONTROL) => match self.clipboard.paste() {
+                Ok(text) => {
+                    for c in text.lines().next().unwrap_or("").trim().chars() {
+                        self.command.insert(c);
+                    }
+                }
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            (KeyCode::Enter, _) => {
+                self.state.mode = AppMode::Normal;
+                let input = std::mem::take(&mut self.command.query);
+                if !input.trim().is_empty() {
+                    self.run_command_input(&input);
+                }
+            }
+            _ => handle_line_edit_key(&mut self.command, key),
+        }
+    }
+
+    /// Run a typed command line: a built-in, or else a command from the config
+    fn run_command_input(&mut self, input: &str) {
+        match CommandLine::parse(input) {
+            Ok(line) => self.execute_command_line(line),
+            // Not a built-in: maybe a plugin from the config
+            Err(e) => {
+                if !self.run_plugin_command(input) {
+                    self.state.set_error(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Execute a command line, on the rows its range names (instead of the
+    /// selection) if it has one
+    fn execute_command_line(&mut self, line: CommandLine) {
+        let CommandLine { range, command } = line;
+        let Some(range) = range else {
+            if let Some(command) = command {
+                self.execute_command(command);
+            }
+            return;
+        };
+        let rows = match range.rows(self.state.cursor, self.state.visible_len()) {
+            Ok(rows) => rows,
+            Err(e) => {
+                self.state.set_error(e.to_string());
+                return;
+            }
+        };
+        // A range alone goes to its last row, like a line number in vim
+        let Some(command) = command else {
+            self.remember_jump();
+            self.state.set_cursor_row(*rows.end());
+            return;
+        };
+
+        let ids = rows
+            .filter_map(|row| self.state.visible_commit(row))
+            .map(|c| c.id)
+            .collect();
+        self.state.visual_edit_targets = Some(ids);
+        self.execute_command(command);
+        // A confirmation about others' commits resumes on the same rows
+        if !matches!(
+            self.state.mode,
+            AppMode::Confirming(ConfirmAction::EditOthersCommits { .. })
+        ) {
+            self.state.clear_visual_edit_targets();
+        }
+    }
+
+    /// Execute a parsed command line command
+    fn execute_command(&mut self, command: Command) {
+        match command {
+            Command::SelectRange { from, to } => {
+                match self.state.select_range_by_hash(&from, &to) {
+                    Ok(count) => self
+                        .state
+                        .set_success(format!("Selected {count} commit(s) in range")),
+                    Err(e) => self.state.set_error(e.to_string()),
+                }
+            }
+            Command::Goto(hash) => match self.goto_commit(&hash) {
+                Ok(msg) => self.state.set_success(msg),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::Highlight(pattern) => match self.state.add_highlight(&pattern) {
+                Ok(group) => self
+                    .state
+                    .set_success(format!("Highlight group {}: {pattern}", group + 1)),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::NoHighlight(Some(pattern)) => {
+                if self.state.remove_highlight(&pattern) {
+                    self.state
+                        .set_success(format!("Removed highlight for {pattern}"));
+                } else {
+                    self.state.set_error(format!("No highlight for {pattern}"));
+                }
+            }
+            Command::NoHighlight(None) => {
+                self.state.highlights.clear();
+                self.state.set_success("Cleared all highlights");
+            }
+            Command::Limit(limit) => match self.set_commit_limit(limit.unwrap_or(usize::MAX)) {
+                Ok(msg) => self.state.set_success(msg),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::Autosquash => {
+                if self.state.filtered_indices.is_some() {
+                    self.state
+                        .set_error("Cannot reorder while filtering or sorting");
+                    return;
+                }
+                match self.state.autosquash() {
+                    0 => self
+                        .state
+                        .set_success("No fixup!/squash! commits to squash"),
+                    count => self.state.set_success(format!(
+                        "Staged {count} fixup/squash commit(s) above their targets"
+                    )),
+                }
+            }
+            Command::EditAll => {
+                if let Err(e) = self.start_edit_all_visible() {
+                    self.state.set_error(e.to_string());
+                }
+            }
+            Command::Snap { step, mode } => {
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Snap { step, mode }) {
+                    return;
+                }
+                let count = self.state.snap_dates(&ids, step, mode);
+                let hidden = self.state.hidden_count(&ids);
+                self.state.set_success(format!(
+                    "Snapped dates on {count} commit(s){}",
+                    hidden_suffix(hidden)
+                ));
+            }
+            Command::Shift(seconds) => {
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Shift { seconds }) {
+                    return;
+                }
+                let count = self.state.shift_dates(&ids, seconds);
+                let hidden = self.state.hidden_count(&ids);
+                self.state.set_success(format!(
+                    "Shifted dates on {count} commit(s){}",
+                    hidden_suffix(hidden)
+                ));
+            }
+            Command::Spread { start, end, jitter } => {
+                let ids = self.state.commits_to_edit();
+                let resume = PendingEdit::Spread { start, end, jitter };
+                if !self.check_editable(&ids, resume) {
+                    return;
+                }
+                let count = self.state.spread_dates(&ids, start, end, jitter);
+                let hidden = self.state.hidden_count(&ids);
+                self.state.set_success(format!(
+                    "Spread dates on {count} commit(s){}",
+                    hidden_suffix(hidden)
+                ));
+            }
+            Command::FixDates(fix) => {
+                let ids: Vec<CommitId> = self
+                    .state
+                    .date_order_fixes(fix)
+                    .iter()
+                    .map(|(id, _, _)| *id)
+                    .collect();
+                if ids.is_empty() {
+                    self.state.set_success("Dates are already in order");
+                    return;
+                }
+                if !self.check_editable(&ids, PendingEdit::FixDates(fix)) {
+                    return;
+                }
+                let count = self.state.fix_date_order(fix);
+                self.state
+                    .set_success(format!("Put dates back in order on {count} commit(s)"));
+            }
+            Command::Timezone(offset) => {
+                let Some(tz) = FixedOffset::east_opt(offset) else {
+                    return;
+                };
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Timezone { offset }) {
+                    return;
+                }
+                let count = self.state.convert_timezone(&ids, tz);
+                let hidden = self.state.hidden_count(&ids);
+                self.state.set_success(format!(
+                    "Converted dates to {tz} on {count} commit(s){}",
+                    hidden_suffix(hidden)
+                ));
+            }
+            Command::Me => {
+                let Some(user) = self.state.user.clone() else {
+                    self.state.set_error(
+                        "No git identity (set user.name/user.email or GIT_AUTHOR_NAME/GIT_AUTHOR_EMAIL)",
+                    );
+                    return;
+                };
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Me) {
+                    return;
+                }
+                self.set_author(&ids, &user);
+            }
+            Command::Author(person) => {
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Author(person.clone())) {
+                    return;
+                }
+                self.set_author(&ids, &person);
+            }
+            Command::Date(date) => {
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Date(date)) {
+                    return;
+                }
+                self.set_author_date(&ids, date);
+            }
+            Command::Delete => {
+                let ids: Vec<CommitId> = self
+                    .state
+                    .commits_to_edit()
+                    .into_iter()
+                    .filter(|id| !self.state.is_deleted(*id))
+                    .collect();
+                if ids.is_empty() {
+                    self.state.set_error("Already marked for deletion");
+                } else {
+                    self.delete_commits(&ids);
+                }
+            }
+            Command::Write { quit } => {
+                if self.state.is_dirty() {
+                    self.confirm_apply();
+                    self.quit_after_apply = quit;
+                } else if quit {
+                    self.should_quit = true;
+                } else {
+                    self.state.set_error("No changes to apply");
+                }
+            }
+            Command::Quit { force } => {
+                if force || !self.state.is_dirty() {
+                    self.should_quit = true;
+                } else {
+                    self.state.mode = AppMode::Quitting;
+                }
+            }
+            Command::NewRoot(paths) => {
+                let Some(old_root) = self.state.root_commit() else {
+                    self.state
+                        .set_error("The root commit isn't loaded (:limit all)");
+                    return;
+                };
+                let missing = match self.repo.missing_paths(old_root.id, &paths) {
+                    Ok(missing) => missing,
+                    Err(e) => {
+                        self.state.set_error(e.to_string());
+                        return;
+                    }
+                };
+                if !missing.is_empty() {
+                    self.state.set_error(format!(
+                        "Not in the root commit {}: {}",
+                        old_root.short_hash,
+                        missing.join(", ")
+                    ));
+                    return;
+                }
+                // Backdated to just before the old root
+                let root = NewRoot {
+                    paths,
+                    message: self.new_root_message.clone(),
+                    author: self
+                        .state
+                        .user
+                        .clone()
+                        .unwrap_or_else(|| old_root.author.clone()),
+                    date: old_root.author_date - TimeDelta::minutes(1),
+                };
+                let short_hash = old_root.short_hash.clone();
+                self.state.set_new_root(Some(root));
+                self.state
+                    .set_success(format!("A new root commit will go beneath {short_hash}"));
+            }
+            Command::NoNewRoot => {
+                if self.state.set_new_root(None) {
+                    self.state.set_success("Removed the new root commit");
+                } else {
+                    self.state.set_error("No new root commit");
+                }
+            }
+            Command::Reflow(width) => {
+                let ids = self.state.commits_to_edit();
+                if !self.check_editable(&ids, PendingEdit::Reflow { width }) {
+                    return;
+                }
+                let count = self.state.reflow_messages(&ids, width);
+                if count == 0 {
+                    self.state.set_error("Messages unchanged");
+                    return;
+                }
+                let hidden = self.state.hidden_count(&ids);
+                self.state.set_success(format!(
+                    "Reflowed {count} message(s) to {width} columns{}",
+                    hidden_suffix(hidden)
+                ));
+            }
+            Command::SignOff { all } => {
+                let Some(user) = self.state.user.clone() else {
+                    self.state.set_error(
+                        "No git identity (set user.name/user.email or GIT_AUTHOR_NAME/GIT_AUTHOR_EMAIL)",
+                    );
+                    return;
+                };
+                let ids = if all {
+                    self.state.commits.iter().map(|c| c.id).collect()
+                } else {
+                    self.state.commits_to_edit()
+                };
+                if !self.check_editable(&ids, PendingEdit::SignOff { all }) {
+                    return;
+                }
+                let count = self.state.add_trailer(&ids, &Trailer::signed_off_by(&user));
+                if count == 0 {
+                    self.state.set_error("Already signed off");
+                    return;
+                }
+                let hidden = self.state.hidden_count(&ids);
+                self.state.set_success(format!(
+                    "Signed off {count} commit(s) as {}{}",
+                    user.format_full(),
+                    hidden_suffix(hidden)
+                ));
+            }
+            Command::Mailmap(path) => match self.apply_mailmap(path.as_deref().map(Path::new)) {
+                Ok(0) => self.state.set_error("No commits match the mailmap"),
+                Ok(count) => self
+                    .state
+                    .set_success(format!("Remapped identities on {count} commit(s)")),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::Export(path)
+                if Path::new(&path)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("json")) =>
+            {
+                match export_plan(&self.state, Path::new(&path)) {
+                    Ok(()) => self.state.set_success(format!(
+                        "Wrote the pending edits to {path}; apply them with retcon apply --plan"
+                    )),
+                    Err(e) => self.state.set_error(e.to_string()),
+                }
+            }
+            Command::Export(path) if path.ends_with("todo") => {
+                match self.export_rebase_todo(Path::new(&path)) {
+                    Ok(()) => self.state.set_success(format!(
+                        "Wrote the rebase todo to {path}; its header says how to apply it"
+                    )),
+                    Err(e) => self.state.set_error(e.to_string()),
+                }
+            }
+            Command::Export(path) => match export_mailmap(&self.state, Path::new(&path)) {
+                Ok(0) => self
+                    .state
+                    .set_success(format!("{path} already has every identity edit")),
+                Ok(count) => self
+                    .state
+                    .set_success(format!("Added {count} mailmap line(s) to {path}")),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::Dump(path) => match self.dump_metadata(Path::new(&path)) {
+                Ok(count) => self
+                    .state
+                    .set_success(format!("Wrote {count} commit(s) to {path}")),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::Unlock { all } => {
+                if all {
+                    self.state.lock_age = None;
+                    self.state
+                        .set_success("Unlocked all commits for this session");
+                } else {
+                    let ids = self.state.commits_to_edit();
+                    let count = self.state.locked_count(&ids);
+                    self.state.unlocked.extend(ids);
+                    self.state
+                        .set_success(format!("Unlocked {count} commit(s) for this session"));
+                }
+            }
+            Command::Branch(None) => self.open_branch_picker(),
+            Command::Branch(Some(name)) => match self.switch_branch(&name) {
+                Ok(()) => self
+                    .state
+                    .set_success(format!("Editing branch {}", self.state.branch_name)),
+                Err(e) => self.state.set_error(e.to_string()),
+            },
+            Command::Authors => self.open_author_groups(None),
+            Command::Preview => {
+                if let Err(e) = self.open_hash_preview() {
+                    self.state.set_error(e.to_string());
+                }
+            }
+            Command::Backups => self.open_backups(0),
+            Command::Divergence(threshold) => {
+                let threshold =
+                    threshold.map_or(self.state.divergence_threshold, TimeDelta::seconds);
+                self.open_divergence_report(threshold);
+            }
+            Command::Lint(None) => self.open_lint_report(),
+            Command::Lint(Some(kind)) => self.set_commit_type(kind, None),
+            Command::Note(note) => {
+                let ids = self.state.commits_to_edit();
+                let count = self.state.set_note(&ids, note.as_deref());
+                let hidden = hidden_suffix(self.state.hidden_count(&ids));
+                match note {
+                    _ if count == 0 => self.state.set_error("Notes unchanged"),
+                    Some(_) => self
+                        .state
+                        .set_success(format!("Noted {count} commit(s){hidden}")),
+                    None => self
+                        .state
+                        .set_success(format!("Removed notes from {count} commit(s){hidden}")),
+                }
+            }
+            Command::Clear(fields) => {
+                let ids = self.state.commits_to_edit();
+                let count = self.state.clear_fields(&ids, &fields);
+                if count == 0 {
+                    self.state
+                        .set_error("No pending edits of those fields to clear");
+                } else {
+                    let names: Vec<&str> = fields.iter().map(EditableField::display_name).collect();
+                    self.state.set_success(format!(
+                        "Cleared {} edits on {count} commit(s){}",
+                        names.join("/"),
+                        hidden_suffix(self.state.hidden_count(&ids))
+                    ));
+                }
+            }
+            Command::Sort(sort) => {
+                self.state.set_sort(sort);
+                self.state.set_success(match sort {
+                    Some(sort) => format!(
+                        "Sorted by {} ({}); :nosort restores the commit order",
+                        sort.key.name(),
+                        if sort.descending { "desc" } else { "asc" }
+                    ),
+                    None => "Commit order".to_string(),
+                });
+            }
+            Command::Set { option, value } => {
+                let on = self.state.set_display_option(option, value);
+                let prefix = if on { "" } else { "no" };
+                self.state.set_success(format!("{prefix}{}", option.name()));
+            }
+        }
+    }
+
+    /// Run the plugin named by the first word of a command line, if there is one
+    ///
+    /// Returns false if no plugin has that name.
+    fn run_plugin_command(&mut self, input: &str) -> bool {
+        let mut words = input.split_whitespace();
+        let Some(plugin) = words
+            .next()
+            .and_then(|name| self.plugins.iter().find(|p| p.name == name))
+            .cloned()
+        else {
+            return false;
+        };
+        let args: Vec<&str> = words.collect();
+
+        let ids = self.state.commits_to_edit();
+        if !self.check_unlocked(&ids) {
+            return true;
+        }
+        let commits: Vec<PluginCommit> = ids
+            .iter()
+            .filter_map(|id| self.state.commits.iter().find(|c| c.id == *id))
+            .map(|c| PluginCommit::new(c, self.state.modifications.get(&c.id)))
+            .collect();
+        let dir = self
+            .repo
+            .inner()
+            .workdir()
+            .unwrap_or_else(|| self.repo.inner().path())
+            .to_path_buf();
+
+        let staged = run_plugin(&plugin, &args, &commits, &dir).and_then(|edits| {
+            edits
+                .iter()
+                .map(|edit| {
+                    let idx = self.state.resolve_hash(&edit.id)?;
+                    Ok((
+                        self.state.commits[idx].id,
+                        edit.to_modifications(!self.state.force)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()
+        });
+        let staged = match staged {
+            Ok(staged) => staged,
+            Err(e) => {
+                self.state.set_error(e.to_string());
+                return true;
+            }
+        };
+        if staged.is_empty() {
+            self.state
+                .set_success(format!("{}: no changes", plugin.name));
+            return true;
+        }
+        if staged.iter().any(|(_, mods)| {
+            [&mods.author_email, &mods.committer_email]
+                .into_iter()
+                .flatten()
+                .any(|email| validate_email(email).is_err())
+        }) {
+            self.state.record_bypass(Bypass::Validation);
+        }
+
+        self.state.save_undo(&format!("Run {}", plugin.name));
+        let count = staged.len();
+        for (id, mods) in staged {
+            self.state.get_or_create_modifications(id).merge(mods);
+        }
+        self.state
+            .set_success(format!("{}: updated {count} commit(s)", plugin.name));
+        true
+    }
+
+    /// Refuse edits to locked commits, and ask before editing commits authored
+    /// by someone else (once per session)
+    ///
+    /// Returns true if the edit can go ahead now; otherwise an error or a
+    /// confirmation (which resumes the edit when accepted) is shown.
+    fn check_editable(&mut self, targets: &[CommitId], resume: PendingEdit) -> bool {
+        if !self.check_unlocked(targets) {
+            return false;
+        }
+        let authors = self.state.foreign_authors(targets);
+        if authors.is_empty() {
+            return true;
+        }
+        self.confirm_dialog = ConfirmDialogState::default();
+        self.state.mode = AppMode::Confirming(ConfirmAction::EditOthersCommits { authors, resume });
+        false
+    }
+
+    /// Refuse (with an error) to touch commits locked by age
+    fn check_unlocked(&mut self, targets: &[CommitId]) -> bool {
+        let locked = self.state.locked_count(targets);
+        if locked == 0 {
+            return true;
+        }
+        if self.state.force {
+            self.state.record_bypass(Bypass::Locked);
+            return true;
+        }
+        let days = self.state.lock_age.map_or(0, |age| age.num_days());
+        self.state.set_error(format!(
+            "{locked} commit(s) older than {days} days are locked; :unlock to change them"
+        ));
+        false
+    }
+
+    /// Open the find/replace dialog for the cursor commit's message
+    fn start_replace(&mut self) {
+        let Some(commit) = self.state.cursor_commit() else {
+            return;
+        };
+        if commit.is_merge {
+            self.state.set_error("Cannot edit merge commits");
+            return;
+        }
+        let id = commit.id;
+        if !self.check_editable(&[id], PendingEdit::Replace) {
+            return;
+        }
+        self.open_replace(ReplaceTarget::Message {
+            commit_idx: self.state.cursor,
+        });
+    }
+
+    /// Open the find/replace dialog on `target`, starting empty
+    fn open_replace(&mut self, target: ReplaceTarget) {
+        self.replace = ReplaceState::new();
+        self.state.mode = AppMode::Replace(target);
+    }
+
+    /// Handle key in the find/replace dialog
+    fn handle_replace_key(&mut self, key: KeyEvent, target: ReplaceTarget) {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) => self.close_replace(target),
+            (KeyCode::Tab | KeyCode::BackTab, _) => {
+                self.replace.toggle_focus();
+            }
+            (KeyCode::Enter, _) => {
+                let find = self.replace.find.query.clone();
+                if find.is_empty() {
+                    self.state.set_error("Nothing to find");
+                    return;
+                }
+                let replacement = self.replace.replacement.query.clone();
+                let (count, target) = match target {
+                    ReplaceTarget::Message { commit_idx } => {
+                        let Some(id) = self.state.visible_commit(commit_idx).map(|c| c.id) else {
+                            self.state.mode = AppMode::Normal;
+                            return;
+                        };
+                        let count = self.state.replace_in_message(id, &find, &replacement);
+                        (count, target)
+                    }
+                    ReplaceTarget::EditBuffer { .. } => {
+                        let count = self.state.replace_in_edit_buffer(&find, &replacement);
+                        (count, target)
+                    }
+                    ReplaceTarget::SplitMessage(mut chooser) => {
+                        let count = chooser.message.matches(find.as_str()).count();
+                        chooser.message = chooser.message.replace(&find, &replacement);
+                        (count, ReplaceTarget::SplitMessage(chooser))
+                    }
+                };
+                self.close_replace(target);
+                if count == 0 {
+                    self.state.set_error(format!("Pattern not found: {find}"));
+                } else {
+                    self.state
+                        .set_success(format!("Replaced {count} occurrence(s)"));
+                }
+            }
+            _ => handle_line_edit_key(self.replace.focused_input(), key),
+        }
+    }
+
+    /// Leave the find/replace dialog for whatever opened it
+    fn close_replace(&mut self, target: ReplaceTarget) {
+        self.state.mode = match target {
+            ReplaceTarget::Message { .. } => AppMode::Normal,
+            ReplaceTarget::EditBuffer { commit_idx, field } => {
+                AppMode::Editing { commit_idx, field }
+            }
+            ReplaceTarget::SplitMessage(chooser) => AppMode::Split(chooser),
+        };
+    }
+
+    /// Open the list of local branches to switch to
+    fn open_branch_picker(&mut self) {
+        match self.repo.branch_names() {
+            Ok(branches) if branches.is_empty() => self.state.set_error("No local branches"),
+            Ok(branches) => {
+                let cursor = branches
+                    .iter()
+                    .position(|name| *name == self.state.branch_name)
+                    .unwrap_or(0);
+                self.state.mode = AppMode::BranchPicker(BranchPicker { branches, cursor });
+            }
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to list branches: {e}")),
+        }
+    }
+
+    /// Handle key in the command palette
+    fn handle_palette_key(&mut self, key: KeyEvent, mut palette: CommandPalette) -> Result<()> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) => {
+                self.state.mode = AppMode::Normal;
+                return Ok(());
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                let last = palette.matches().len().saturating_sub(1);
+                palette.cursor = (palette.cursor + 1).min(last);
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                palette.cursor = palette.cursor.saturating_sub(1);
+            }
+            (KeyCode::Enter, _) => {
+                self.state.mode = AppMode::Normal;
+                return match palette.selected() {
+                    Some(entry) => self.run_palette_action(entry.action.clone()),
+                    None => Ok(()),
+                };
+            }
+            (KeyCode::Backspace, _) => {
+                palette.query.pop();
+                palette.cursor = 0;
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                palette.query.push(c);
+                palette.cursor = 0;
+            }
+            _ => {}
+        }
+        self.state.mode = AppMode::Palette(palette);
+        Ok(())
+    }
+
+    /// Do what a palette entry stands for, through the same handler as its
+    /// key or command
+    fn run_palette_action(&mut self, action: PaletteAction) -> Result<()> {
+        match action {
+            PaletteAction::Key { key, ctrl } => {
+                let modifiers = if ctrl {
+                    KeyModifiers::CONTROL
+                } else if key.is_ascii_uppercase() {
+                    KeyModifiers::SHIFT
+                } else {
+                    KeyModifiers::NONE
+                };
+                self.handle_normal_key(KeyEvent::new(KeyCode::Char(key), modifiers))
+            }
+            PaletteAction::Run(input) => {
+                self.run_command_input(&input);
+                Ok(())
+            }
+            PaletteAction::Prompt(input) => {
+                self.command = SearchState::from_query(input);
+                self.state.mode = AppMode::Command;
+                Ok(())
+            }
+        }
+    }
+
+    /// Handle key in the branch picker
+    fn handle_branch_picker_key(&mut self, key: KeyEvent, mut picker: BranchPicker) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                picker.cursor = (picker.cursor + 1).min(picker.branches.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                picker.cursor = picker.cursor.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                let name = picker.branches[picker.cursor].clone();
+                match self.switch_branch(&name) {
+                    Ok(()) => self.state.set_success(format!("Editing branch {name}")),
+                    Err(e) => self.state.set_error(e.to_string()),
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.state.mode = AppMode::BranchPicker(picker);
+    }
+
+    /// Set the author (and committer, when synced) of commits, as one undo step
+    fn set_author(&mut self, ids: &[CommitId], person: &Person) {
+        let who = person.format_full();
+        self.state
+            .save_undo(&format!("Set author to {who} on {} commit(s)", ids.len()));
+        for &id in ids {
+            self.state
+                .set_field(id, EditableField::AuthorName, &person.name, "");
+            self.state
+                .set_field(id, EditableField::AuthorEmail, &person.email, "");
+        }
+        let hidden = self.state.hidden_count(ids);
+        self.state.set_success(format!(
+            "Set author to {who} on {} commit(s){}",
+            ids.len(),
+            hidden_suffix(hidden)
+        ));
+    }
+
+    /// Set the author date of `ids` (and the committer date, when synced)
+    fn set_author_date(&mut self, ids: &[CommitId], date: DateTime<FixedOffset>) {
+        let value = format_date_for_edit(&date);
+        self.state
+            .save_undo(&format!("Set date to {value} on {} commit(s)", ids.len()));
+        for &id in ids {
+            self.state
+                .set_field(id, EditableField::AuthorDate, &value, "");
+        }
+        let hidden = self.state.hidden_count(ids);
+        self.state.set_success(format!(
+            "Set date to {value} on {} commit(s){}",
+            ids.len(),
+            hidden_suffix(hidden)
+        ));
+    }
+
+    /// Open the list of identities to set as author of the selected commits
+    /// (or the cursor commit): the git config ones, then the profiles
+    fn open_identity_picker(&mut self) {
+        let mut identities = self.repo.config_identities();
+        match IdentityProfiles::load() {
+            Ok(profiles) => identities.extend(profiles.0),
+            Err(e) => {
+                self.state.set_error(e.to_string());
+                return;
+            }
+        }
+        if identities.is_empty() {
+            self.state.set_error(
+                "No identities (set user.name/user.email, or add profiles to ~/.config/retcon/identities.toml)",
+            );
+            return;
+        }
+        let targets = self.state.commits_to_edit();
+        if targets.is_empty() || !self.check_editable(&targets, PendingEdit::IdentityPicker) {
+            return;
+        }
+        self.state.mode = AppMode::IdentityPicker(IdentityPicker {
+            targets,
+            identities,
+            cursor: 0,
+        });
+    }
+
+    /// Handle key in the identity picker
+    fn handle_identity_picker_key(&mut self, key: KeyEvent, mut picker: IdentityPicker) {
+        let pick = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                picker.cursor = (picker.cursor + 1).min(picker.identities.len() - 1);
+                None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                picker.cursor = picker.cursor.saturating_sub(1);
+                None
+            }
+            KeyCode::Enter => Some(picker.cursor),
+            KeyCode::Char(c @ '1'..='9') => c
+                .to_digit(10)
+                .map(|n| n as usize - 1)
+                .filter(|&idx| idx < picker.identities.len()),
+            _ => None,
+        };
+        match pick {
+            Some(idx) => {
+                self.state.mode = AppMode::Normal;
+                let person = picker.identities[idx].1.clone();
+                self.set_author(&picker.targets, &person);
+            }
+            None => self.state.mode = AppMode::IdentityPicker(picker),
+        }
+    }
+
+    /// Show the commits grouped by author, optionally with the author of
+    /// group `edit_row` being changed
+    fn open_author_groups(&mut self, edit_row: Option<usize>) {
+        let groups = self.state.author_groups();
+        if groups.is_empty() {
+            self.state.set_error("No editable commits");
+            return;
+        }
+        let cursor = edit_row.unwrap_or(0).min(groups.len() - 1);
+        let input = edit_row.map(|_| groups[cursor].0.format_full());
+        self.state.mode = AppMode::Authors(Box::new(AuthorGroups {
+            groups,
+            cursor,
+            input,
+        }));
+    }
+
+    /// Handle key in the author view
+    fn handle_authors_key(&mut self, key: KeyEvent, mut view: AuthorGroups) {
+        let (author, ids) = view.groups[view.cursor].clone();
+        if let Some(input) = &mut view.input {
+            match key.code {
+                KeyCode::Esc => view.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Tab => *input = self.complete_identity(input),
+                KeyCode::Enter => match Person::parse(&self.complete_identity(input)) {
+                    Some(person) => {
+                        self.set_author(&ids, &person);
+                        view.groups = self.state.author_groups();
+                        view.cursor = view
+                            .groups
+                            .iter()
+                            .position(|(author, _)| *author == person)
+                            .unwrap_or(0);
+                        view.input = None;
+                    }
+                    None => self
+                        .state
+                        .set_error("Enter the author as Name <email> (Tab completes known ones)"),
+                },
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+        } else {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    self.state.mode = AppMode::Normal;
+                    return;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    view.cursor = (view.cursor + 1).min(view.groups.len() - 1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    view.cursor = view.cursor.saturating_sub(1);
+                }
+                KeyCode::Char('e') | KeyCode::Enter => {
+                    let row = view.cursor;
+                    if !self.check_editable(&ids, PendingEdit::AuthorGroup { row }) {
+                        return;
+                    }
+                    view.input = Some(author.format_full());
+                }
+                KeyCode::Char('s') => {
+                    self.state.selected = ids.iter().copied().collect();
+                    self.state.mode = AppMode::Normal;
+                    let hidden = self.state.hidden_count(&ids);
+                    self.state.set_success(format!(
+                        "Selected {} commit(s) by {}{}",
+                        ids.len(),
+                        author.format_full(),
+                        hidden_suffix(hidden)
+                    ));
+                    return;
+                }
+                _ => {}
+            }
+        }
+        self.state.mode = AppMode::Authors(Box::new(view));
+    }
+
+    /// Open the trailer editor for the selected commits (or the cursor
+    /// commit), for every trailer or just `Co-authored-by`
+    fn open_trailers(&mut self, co_authors: bool) {
+        let targets = self.state.commits_to_edit();
+        if targets.is_empty()
+            || !self.check_editable(&targets, PendingEdit::Trailers { co_authors })
+        {
+            return;
+        }
+        let key = co_authors.then(|| "Co-authored-by".to_string());
+        let trailers = self.state.trailers(&targets, key.as_deref());
+        self.state.mode = AppMode::Trailers(Box::new(TrailerEditor {
+            targets,
+            key,
+            trailers,
+            cursor: 0,
+            input: None,
+            editing: None,
+        }));
+    }
+
+    /// Handle key in the trailer editor
+    fn handle_trailer_key(&mut self, key: KeyEvent, mut editor: TrailerEditor) {
+        let highlighted = editor
+            .trailers
+            .get(editor.cursor)
+            .map(|(trailer, _)| trailer.clone());
+        if let Some(input) = &mut editor.input {
+            match key.code {
+                KeyCode::Esc => {
+                    editor.input = None;
+                    editor.editing = None;
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Tab if editor.key.is_some() => *input = self.complete_identity(input),
+                KeyCode::Enter => match self.parse_trailer_input(editor.key.as_deref(), input) {
+                    Some(trailer) => {
+                        let count = match &editor.editing {
+                            Some(old) => self.state.replace_trailer(&editor.targets, old, &trailer),
+                            None => self.state.add_trailer(&editor.targets, &trailer),
+                        };
+                        self.state
+                            .set_success(format!("{trailer} on {count} commit(s)"));
+                        editor.input = None;
+                        editor.editing = None;
+                    }
+                    None if editor.key.is_some() => self
+                        .state
+                        .set_error("Enter a co-author as Name <email> (Tab completes known ones)"),
+                    None => self.state.set_error("Enter a trailer as Key: value"),
+                },
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+        } else {
+            match (key.code, highlighted) {
+                (KeyCode::Esc | KeyCode::Char('q'), _) => {
+                    self.state.mode = AppMode::Normal;
+                    return;
+                }
+                (KeyCode::Char('j') | KeyCode::Down, _) => {
+                    editor.cursor =
+                        (editor.cursor + 1).min(editor.trailers.len().saturating_sub(1));
+                }
+                (KeyCode::Char('k') | KeyCode::Up, _) => {
+                    editor.cursor = editor.cursor.saturating_sub(1);
+                }
+                (KeyCode::Char('a'), _) => editor.input = Some(String::new()),
+                (KeyCode::Char('e') | KeyCode::Enter, Some(trailer)) => {
+                    editor.input = Some(editor.display(&trailer));
+                    editor.editing = Some(trailer);
+                }
+                (KeyCode::Char('d' | 'x'), Some(trailer)) => {
+                    let count = self.state.remove_trailer(&editor.targets, &trailer);
+                    self.state
+                        .set_success(format!("Removed {trailer} from {count} commit(s)"));
+                }
+                (KeyCode::Char(' '), Some(trailer)) => {
+                    let count = self.state.add_trailer(&editor.targets, &trailer);
+                    self.state
+                        .set_success(format!("Added {trailer} to {count} more commit(s)"));
+                }
+                _ => {}
+            }
+        }
+        editor.trailers = self.state.trailers(&editor.targets, editor.key.as_deref());
+        editor.cursor = editor.cursor.min(editor.trailers.len().saturating_sub(1));
+        self.state.mode = AppMode::Trailers(Box::new(editor));
+    }
+
+    /// The trailer typed into the trailer editor: `Key: value`, or with a
+    /// fixed key (co-authors) a `Name <email>` completed from known identities
+    fn parse_trailer_input(&self, key: Option<&str>, input: &str) -> Option<Trailer> {
+        match key {
+            Some(key) => Person::parse(&self.complete_identity(input))
+                .map(|person| Trailer::new(key, person.format_full())),
+            None => Trailer::parse(input.trim()),
+        }
+    }
+
+    /// Complete a bare name or email to `Name <email>` from the known identities
+    fn complete_identity(&self, input: &str) -> String {
+        let input = input.trim();
+        if input.contains('<') {
+            return input.to_string();
+        }
+        let completed = if input.contains('@') {
+            self.identities
+                .name_for(input)
+                .map(|name| Person::new(name, input))
+        } else {
+            self.identities
+                .email_for(input)
+                .map(|email| Person::new(input, email))
+        };
+        completed.map_or_else(|| input.to_string(), |person| person.format_full())
+    }
+
+    /// List commits whose author and committer dates are further apart than `threshold`
+    fn open_divergence_report(&mut self, threshold: TimeDelta) {
+        let rows = self.state.date_divergences(threshold);
+        if rows.is_empty() {
+            self.state.set_success(format!(
+                "No commits with author and committer dates more than {} apart",
+                format_gap(threshold)
+            ));
+            return;
+        }
+        self.state.mode = AppMode::Divergence(DivergenceReport {
+            rows,
+            cursor: 0,
+            threshold,
+        });
+    }
+
+    /// Handle key in the date divergence report
+    fn handle_divergence_key(&mut self, key: KeyEvent, mut report: DivergenceReport) {
+        let (source, all) = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                report.cursor = (report.cursor + 1).min(report.rows.len() - 1);
+                self.state.mode = AppMode::Divergence(report);
+                return;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                report.cursor = report.cursor.saturating_sub(1);
+                self.state.mode = AppMode::Divergence(report);
+                return;
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                let id = report.rows[report.cursor].0;
+                match self.state.visible_index_of(id) {
+                    Some(row) => self.state.set_cursor_row(row),
+                    None => self.state.set_error("That commit is hidden by the filter"),
+                }
+                return;
+            }
+            KeyCode::Char('a') => (DateSource::Author, false),
+            KeyCode::Char('c') => (DateSource::Committer, false),
+            KeyCode::Char('A') => (DateSource::Author, true),
+            KeyCode::Char('C') => (DateSource::Committer, true),
+            _ => {
+                self.state.mode = AppMode::Divergence(report);
+                return;
+            }
+        };
+
+        let row = (!all).then_some(report.cursor);
+        self.state.mode = AppMode::Divergence(report.clone());
+        self.normalize_report_dates(&report, source, row);
+    }
+
+    /// List the backup refs, highlighting row `cursor`
+    fn open_backups(&mut self, cursor: usize) {
+        match self.repo.backup_refs() {
+            Ok(rows) => {
+                let cursor = cursor.min(rows.len().saturating_sub(1));
+                self.state.mode = AppMode::Backups(BackupList { rows, cursor });
+            }
+            Err(e) => {
+                self.state.mode = AppMode::Normal;
+                self.state.set_error(format!("Failed to list backups: {e}"));
+            }
+        }
+    }
+
+    /// Handle key in the backup list
+    fn handle_backups_key(&mut self, key: KeyEvent, mut list: BackupList) {
+        let selected = list.rows.get(list.cursor).cloned();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                list.cursor = (list.cursor + 1).min(list.rows.len().saturating_sub(1));
+                self.state.mode = AppMode::Backups(list);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                list.cursor = list.cursor.saturating_sub(1);
+                self.state.mode = AppMode::Backups(list);
+            }
+            KeyCode::Char('n') => {
+                let created = self.repo.tip_commit_id().and_then(|tip| {
+                    self.repo
+                        .create_numbered_backup(&self.state.branch_name, tip)
+                });
+                match created {
+                    Ok(name) => {
+                        let row = self
+                            .repo
+                            .backup_refs()
+                            .ok()
+                            .and_then(|rows| rows.iter().position(|b| b.name == name))
+                            .unwrap_or(0);
+                        self.open_backups(row);
+                        self.state.set_success(format!("Created {name}"));
+                    }
+                    Err(e) => {
+                        self.state.mode = AppMode::Backups(list);
+                        self.state
+                            .set_error(format!("Failed to create a backup: {e}"));
+                    }
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('r') => {
+                let Some(backup) = selected else {
+                    self.state.mode = AppMode::Backups(list);
+                    return;
+                };
+                self.state.mode = AppMode::Backups(list);
+                if backup.branch != self.state.branch_name {
+                    self.state.set_error(format!(
+                        "Switch to {0} (:branch {0}) to restore its backups",
+                        backup.branch
+                    ));
+                } else if self.state.is_dirty() {
+                    self.state
+                        .set_error("Apply or discard the pending changes first");
+                } else {
+                    self.confirm_dialog = ConfirmDialogState::default();
+                    self.state.mode = AppMode::Confirming(ConfirmAction::RestoreBackup(backup));
+                }
+            }
+            KeyCode::Char('d') => {
+                self.state.mode = AppMode::Backups(list);
+                if let Some(backup) = selected {
+                    self.confirm_dialog = ConfirmDialogState::default();
+                    self.state.mode = AppMode::Confirming(ConfirmAction::DeleteBackup(backup));
+                }
+            }
+            _ => self.state.mode = AppMode::Backups(list),
+        }
+    }
+
+    /// Point the edited branch at a backup and reload its history
+    ///
+    /// The tip it had is kept as a new numbered backup first, so restoring
+    /// can itself be undone. Returns the name of that backup.
+    fn restore_backup(&mut self, backup: &BackupRef) -> Result<String> {
+        let checked_out = self.repo.is_checked_out();
+        if checked_out && self.repo.has_uncommitted_changes()? {
+            return Err(HistError::DirtyWorkingTree);
+        }
+        let saved = self
+            .repo
+            .create_numbered_backup(&backup.branch, self.repo.tip_commit_id()?)?;
+        if checked_out {
+            self.repo.restore_branch(&backup.branch, backup.tip)?;
+        } else {
+            self.repo.move_branch(&backup.branch, backup.tip)?;
+        }
+        self.reload_commits(self.state.commits.len())?;
+        Ok(saved)
+    }
+
+    /// Handle key in the undo history browser
+    fn handle_undo_history_key(&mut self, key: KeyEvent, mut browser: UndoBrowser) {
+        let steps = self.state.undo_history().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'U') => {
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                browser.cursor = (browser.cursor + 1).min(steps - 1);
+                self.state.mode = AppMode::UndoHistory(browser);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                browser.cursor = browser.cursor.saturating_sub(1);
+                self.state.mode = AppMode::UndoHistory(browser);
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                let redo = browser.cursor < self.state.redo_stack.len();
+                let count = self.state.jump_undo_history(browser.cursor);
+                if redo {
+                    self.state.set_success(format!("Redid {count} step(s)"));
+                } else {
+                    self.state.set_success(format!("Undid {count} step(s)"));
+                }
+            }
+            _ => self.state.mode = AppMode::UndoHistory(browser),
+        }
+    }
+
+    /// Normalize the dates of one report row (or all of them), then refresh the report
+    fn normalize_report_dates(
+        &mut self,
+        report: &DivergenceReport,
+        source: DateSource,
+        row: Option<usize>,
+    ) {
+        let ids: Vec<CommitId> = match row {
+            Some(row) => vec![report.rows[row].0],
+            None => report.rows.iter().map(|(id, _)| *id).collect(),
+        };
+        let resume = PendingEdit::NormalizeDates {
+            source,
+            row,
+            threshold: report.threshold,
+        };
+        if !self.check_editable(&ids, resume) {
+            return;
+        }
+
+        let count = self.state.normalize_dates(&ids, source);
+        let rows = self.state.date_divergences(report.threshold);
+        if rows.is_empty() {
+            self.state.mode = AppMode::Normal;
+        } else {
+            self.state.mode = AppMode::Divergence(DivergenceReport {
+                cursor: report.cursor.min(rows.len() - 1),
+                rows,
+                threshold: report.threshold,
+            });
+        }
+        let which = match source {
+            DateSource::Author => "author",
+            DateSource::Committer => "committer",
+        };
+        self.state.set_success(format!(
+            "Normalized dates to the {which} date on {count} commit(s)"
+        ));
+    }
+
+    /// Redo a normalization from the divergence report after the ownership warning
+    fn resume_normalize_dates(
+        &mut self,
+        source: DateSource,
+        row: Option<usize>,
+        threshold: TimeDelta,
+    ) {
+        let rows = self.state.date_divergences(threshold);
+        if rows.is_empty() || row.is_some_and(|row| row >= rows.len()) {
+            return;
+        }
+        let report = DivergenceReport {
+            cursor: row.unwrap_or(0),
+            rows,
+            threshold,
+        };
+        self.normalize_report_dates(&report, source, row);
+    }
+
+    /// List commit messages that aren't Conventional Commits, marking them
+    /// in the table from now on
+    fn open_lint_report(&mut self) {
+        self.state.show_lint = true;
+        let rows = self.state.lint_issues();
+        if rows.is_empty() {
+            self.state
+                .set_success("Every commit message is a Conventional Commit");
+            return;
+        }
+        self.state.mode = AppMode::Lint(LintReport {
+            rows,
+            cursor: 0,
+            kind: 0,
+        });
+    }
+
+    /// Handle key in the lint report
+    fn handle_lint_key(&mut self, key: KeyEvent, mut report: LintReport) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                report.cursor = (report.cursor + 1).min(report.rows.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                report.cursor = report.cursor.saturating_sub(1);
+            }
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => {
+                report.kind = (report.kind + 1) % COMMIT_TYPES.len();
+            }
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => {
+                report.kind = (report.kind + COMMIT_TYPES.len() - 1) % COMMIT_TYPES.len();
+            }
+            KeyCode::Char(' ') => {
+                let id = report.rows[report.cursor].0;
+                if !self.state.selected.remove(&id) {
+                    self.state.selected.insert(id);
+                }
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                let id = report.rows[report.cursor].0;
+                match self.state.visible_index_of(id) {
+                    Some(row) => self.state.set_cursor_row(row),
+                    None => self.state.set_error("That commit is hidden by the filter"),
+                }
+                return;
+            }
+            KeyCode::Char('f') => {
+                let (kind, row) = (report.kind, report.cursor);
+                self.state.mode = AppMode::Lint(report);
+                self.set_commit_type(kind, Some(row));
+                return;
+            }
+            _ => {}
+        }
+        self.state.mode = AppMode::Lint(report);
+    }
+
+    /// Give commits the type `COMMIT_TYPES[kind]`: from the lint report
+    /// (`row`), the selected commits or else the one on that row; from
+    /// `:lint <type>`, the commits to edit
+    fn set_commit_type(&mut self, kind: usize, row: Option<usize>) {
+        let ids = match row {
+            Some(_) if !self.state.selected.is_empty() => {
+                self.state.selected.iter().copied().collect()
+            }
+            Some(row) => match self.state.lint_issues().get(row) {
+                Some((id, _)) => vec![*id],
+                None => return,
+            },
+            None => self.state.commits_to_edit(),
+        };
+        if !self.check_editable(&ids, PendingEdit::CommitType { kind, row }) {
+            return;
+        }
+
+        let count = self.state.set_commit_type(&ids, COMMIT_TYPES[kind]);
+        if let Some(row) = row {
+            let rows = self.state.lint_issues();
+            self.state.mode = if rows.is_empty() {
+                AppMode::Normal
+            } else {
+                AppMode::Lint(LintReport {
+                    cursor: row.min(rows.len() - 1),
+                    rows,
+                    kind,
+                })
+            };
+        }
+        if count == 0 {
+            self.state.set_error("Commit types unchanged");
+        } else {
+            let hidden = hidden_suffix(self.state.hidden_count(&ids));
+            self.state.set_success(format!(
+                "Set type '{}' on {count} commit(s){hidden}",
+                COMMIT_TYPES[kind]
+            ));
+        }
+    }
+
+    /// Load another branch's history in place of the current one
+    fn switch_branch(&mut self, name: &str) -> Result<()> {
+        if self.state.is_dirty() {
+            return Err(HistError::InvalidCommand(
+                "apply or discard the pending changes before switching branches".to_string(),
+            ));
+        }
+        self.repo.set_branch(name)?;
+        self.state.branch_name = self.repo.current_branch_name()?;
+        self.state.has_upstream = self.repo.has_upstream().unwrap_or(false);
+        self.state.head_offset_base = 0;
+        self.reload_commits(self.commit_limit)?;
+
+        self.state.selected.clear();
+        self.state.clear_filter();
+        self.state.cursor = 0;
+        Ok(())
+    }
+
+    /// Load the next `LOAD_MORE` older commits (`+`)
+    fn load_more(&mut self) -> Result<()> {
+        if self.loader.is_some() {
+            self.state.set_success("Older commits are still loading");
+            return Ok(());
+        }
+        let limit = self.state.commits.len().saturating_add(LOAD_MORE);
+        let message = self.set_commit_limit(limit)?;
+        self.state.set_success(message);
+        Ok(())
+    }
+
+    /// Move the cursor to the commit a full or abbreviated hash names, loading
+    /// older commits until it is loaded and clearing a filter that hides it
+    fn goto_commit(&mut self, hash: &str) -> Result<String> {
+        let mut found = self.state.resolve_hash(hash);
+        if matches!(found, Err(HistError::CommitNotFound(_))) && self.loader.is_some() {
+            self.finish_loading();
+            found = self.state.resolve_hash(hash);
+        }
+        let mut loaded = String::new();
+        if matches!(found, Err(HistError::CommitNotFound(_))) {
+            let position = self.repo.history_position(hash)?.ok_or_else(|| {
+                HistError::InvalidRevision(format!(
+                    "{hash} is not in the history of {}",
+                    self.state.branch_name
+                ))
+            })?;
+            loaded = format!(" ({})", self.set_commit_limit(position + 1)?);
+            found = self.state.resolve_hash(hash);
+        }
+        let commit = &self.state.commits[found?];
+        let (id, label) = (
+            commit.id,
+            format!("{} {}", commit.short_hash, commit.summary),
+        );
+
+        let row = match self.state.visible_index_of(id) {
+            Some(row) => row,
+            None => {
+                self.state.clear_filter();
+                self.state
+                    .visible_index_of(id)
+                    .ok_or_else(|| HistError::CommitNotFound(hash.to_string()))?
+            }
+        };
+        self.remember_jump();
+        self.state.set_cursor_row(row);
+        Ok(format!("Jumped to {label}{loaded}"))
+    }
+
+    /// Moving down from the last row loads older commits, as long as the
+    /// limit was reached (so the history may go on)
+    fn load_past_end(&mut self) -> Result<()> {
+        let last_row = self.state.cursor + 1 >= self.state.visible_len();
+        if last_row && self.loader.is_none() && self.state.commits.len() >= self.commit_limit {
+            self.load_more()?;
+        }
+        Ok(())
+    }
+
+    /// Load more commits, or drop older ones, keeping pending changes
+    fn set_commit_limit(&mut self, limit: usize) -> Result<String> {
+        self.stop_loading();
+        let loaded = self.state.commits.len();
+        if limit < loaded {
+            let dropped = self.state.truncate_commits(limit)?;
+            self.commit_limit = limit;
+            return Ok(format!(
+                "Dropped {dropped} older commit(s) ({limit} loaded)"
+            ));
+        }
+
+        let older = self.repo.load_commits_page(loaded, limit - loaded)?;
+        self.commit_limit = limit;
+        if older.is_empty() {
+            return Ok(format!("All {loaded} commits are loaded"));
+        }
+        let added = older.len();
+        self.state.extend_commits(older);
+        self.identities = load_identities(&self.repo, &self.state.commits);
+        // The older commits may touch the searched paths
+        if !self.state.touched_paths.is_empty() {
+            self.state.touched_paths.clear();
+            self.refresh_touched_paths()?;
+            self.state.apply_filter();
+        }
+        Ok(format!(
+            "Loaded {added} more commit(s) ({} total)",
+            self.state.commits.len()
+        ))
+    }
+
+    /// Handle key in confirmation dialog
+    fn handle_confirm_key(&mut self, key: KeyEvent, action: &ConfirmAction) -> Result<()> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('n'), KeyModifiers::NONE) => {
+                self.state.clear_visual_edit_targets();
+                self.quit_after_apply = false;
+                self.state.mode = AppMode::Normal;
+            }
+            (KeyCode::Char('y'), KeyModifiers::NONE) | (KeyCode::Enter, _)
+                if self.confirm_dialog.is_yes_selected() =>
+            {
                 self.execute_confirmed_action(action)?;
             }
-            (KeyCode::Tab | KeyCode::Left | KeyCode::Right, _) => {
-                self.confirm_dialog.toggle();
+            (KeyCode::Char('y'), KeyModifiers::NONE) => {
+                self.execute_confirmed_action(action)?;
+            }
+            (KeyCode::Tab | KeyCode::Left | KeyCode::Right, _) => {
+                self.confirm_dialog.toggle();
+            }
+            (KeyCode::Char('b'), KeyModifiers::NONE)
+                if *action == ConfirmAction::ApplyChanges
+                    && !self.state.dependent_branches.is_empty() =>
+            {
+                self.state.remap_branches = !self.state.remap_branches;
+            }
+            (KeyCode::Char('s'), KeyModifiers::NONE)
+                if *action == ConfirmAction::ApplyChanges && self.signer.is_some() =>
+            {
+                self.state.sign_commits = !self.state.sign_commits;
+            }
+            (KeyCode::Char('t'), KeyModifiers::NONE)
+                if *action == ConfirmAction::ApplyChanges
+                    && !self.state.tags_to_move.is_empty() =>
+            {
+                self.state.migrate_tags = !self.state.migrate_tags;
+            }
+            (KeyCode::Enter, _) => {
+                if self.confirm_dialog.is_yes_selected() {
+                    self.execute_confirmed_action(action)?;
+                } else {
+                    self.state.clear_visual_edit_targets();
+                    self.quit_after_apply = false;
+                    self.state.mode = AppMode::Normal;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Execute a confirmed action
+    fn execute_confirmed_action(&mut self, action: &ConfirmAction) -> Result<()> {
+        match action {
+            ConfirmAction::ApplyChanges => {
+                self.state.mode = AppMode::Normal;
+                self.apply_in_background(Vec::new())?;
+                return Ok(());
+            }
+            ConfirmAction::DiscardChanges => {
+                self.state.clear_modifications();
+                self.state.set_success("All changes discarded");
+            }
+            ConfirmAction::EditOthersCommits { resume, .. } => {
+                self.state.ownership_acknowledged = true;
+                self.state.mode = AppMode::Normal;
+                // Resume the edit; it may switch into an editing mode
+                match resume {
+                    PendingEdit::InlineEdit { external } => self.start_editing(*external)?,
+                    PendingEdit::Replace => self.start_replace(),
+                    PendingEdit::Fill => self.fill_from_cursor(),
+                    PendingEdit::Paste { register } => self.paste_register(*register),
+                    PendingEdit::Snap { step, mode } => {
+                        self.execute_command(Command::Snap {
+                            step: *step,
+                            mode: *mode,
+                        });
+                    }
+                    PendingEdit::Shift { seconds } => {
+                        self.execute_command(Command::Shift(*seconds));
+                    }
+                    PendingEdit::Spread { start, end, jitter } => {
+                        self.execute_command(Command::Spread {
+                            start: *start,
+                            end: *end,
+                            jitter: *jitter,
+                        });
+                    }
+                    PendingEdit::FixDates(fix) => {
+                        self.execute_command(Command::FixDates(*fix));
+                    }
+                    PendingEdit::Timezone { offset } => {
+                        self.execute_command(Command::Timezone(*offset));
+                    }
+                    PendingEdit::Me => self.execute_command(Command::Me),
+                    PendingEdit::Author(person) => {
+                        self.execute_command(Command::Author(person.clone()));
+                    }
+                    PendingEdit::Date(date) => self.execute_command(Command::Date(*date)),
+                    PendingEdit::IdentityPicker => self.open_identity_picker(),
+                    PendingEdit::Trailers { co_authors } => self.open_trailers(*co_authors),
+                    PendingEdit::SignOff { all } => {
+                        self.execute_command(Command::SignOff { all: *all });
+                    }
+                    PendingEdit::Reflow { width } => {
+                        self.execute_command(Command::Reflow(*width));
+                    }
+                    PendingEdit::AuthorGroup { row } => self.open_author_groups(Some(*row)),
+                    PendingEdit::CommitType { kind, row } => self.set_commit_type(*kind, *row),
+                    PendingEdit::NormalizeDates {
+                        source,
+                        row,
+                        threshold,
+                    } => self.resume_normalize_dates(*source, *row, *threshold),
+                }
+                // Rows a command line's range named are done with, unless
+                // the edit went on into another mode
+                if self.state.mode == AppMode::Normal {
+                    self.state.clear_visual_edit_targets();
+                }
+                return Ok(());
+            }
+            ConfirmAction::AutoFillIdentity {
+                field,
+                value,
+                targets,
+            } => {
+                let count = targets.len();
+                self.state.save_undo(&format!(
+                    "Auto-fill {} on {count} commit(s)",
+                    field.display_name()
+                ));
+                for &cid in targets {
+                    self.state.set_field(cid, *field, value, "");
+                }
+                self.state
+                    .set_success(format!("Set {} to {value}", field.display_name()));
+            }
+            ConfirmAction::DeleteCommits { ids } => {
+                // Deleting may ask about referencing commits next
+                self.state.mode = AppMode::Normal;
+                self.set_deletion(ids, true);
+                return Ok(());
+            }
+            ConfirmAction::DeleteReferencing { pairs } => {
+                let remaining = self.state.commits.len() - self.state.deleted.len();
+                if pairs.len() >= remaining {
+                    self.state.set_error("Cannot delete all commits");
+                } else {
+                    self.state
+                        .save_undo(&format!("Delete {} referencing commit(s)", pairs.len()));
+                    for &(id, _) in pairs {
+                        self.state.mark_deleted(id);
+                    }
+                    self.state.set_success(format!(
+                        "Also marked {} referencing commit(s) for deletion",
+                        pairs.len()
+                    ));
+                }
+            }
+            ConfirmAction::ResumeSession(session) => {
+                let resumed = self.state.resume_session((**session).clone());
+                match resumed.and_then(|()| Session::remove(&self.session_path())) {
+                    Ok(()) => self.state.set_success(format!(
+                        "Resumed the session saved {}",
+                        session.saved_at.format("%Y-%m-%d %H:%M")
+                    )),
+                    Err(e) => self.state.set_error(format!("Can't resume: {e}")),
+                }
             }
-            (KeyCode::Enter, _) => {
-                if self.confirm_dialog.is_yes_selected() {
-                    self.execute_confirmed_action(action)?;
+            ConfirmAction::Recover(session) => {
+                match self.state.resume_session((**session).clone()) {
+                    Ok(()) => self.state.set_success(format!(
+                        "Recovered the changes from {}",
+                        session.saved_at.format("%Y-%m-%d %H:%M:%S")
+                    )),
+                    Err(e) => self.state.set_error(format!("Can't recover: {e}")),
+                }
+            }
+            ConfirmAction::RestoreBackup(backup) => {
+                // Back to the list, with the tip just replaced among the backups
+                match self.restore_backup(backup) {
+                    Ok(saved) => {
+                        self.open_backups(0);
+                        self.state.set_success(format!(
+                            "Restored {} to {} (the previous tip is kept as {saved})",
+                            backup.branch, backup.tip
+                        ));
+                    }
+                    Err(e) => {
+                        self.open_backups(0);
+                        self.state.set_error(format!("Restore failed: {e}"));
+                    }
+                }
+                return Ok(());
+            }
+            ConfirmAction::DeleteBackup(backup) => {
+                // Keep the highlight where the deleted backup was
+                let row = self
+                    .repo
+                    .backup_refs()
+                    .ok()
+                    .and_then(|rows| rows.iter().position(|b| b.name == backup.name))
+                    .unwrap_or(0);
+                let deleted = self.repo.delete_backup(&backup.name);
+                self.open_backups(row);
+                match deleted {
+                    Ok(()) => self.state.set_success(format!("Deleted {}", backup.name)),
+                    Err(e) => self.state.set_error(format!("Failed to delete: {e}")),
+                }
+                return Ok(());
+            }
+            ConfirmAction::QuitWithChanges => {
+                self.should_quit = true;
+            }
+        }
+
+        self.state.mode = AppMode::Normal;
+        Ok(())
+    }
+
+    /// Open the file chooser for splitting the cursor commit in two
+    fn start_split(&mut self) {
+        let Some(commit) = self.state.cursor_commit() else {
+            return;
+        };
+        let (id, summary, is_merge) = (commit.id, commit.summary.clone(), commit.is_merge);
+        if is_merge {
+            self.state.set_error("Merge commits can't be split");
+            return;
+        }
+        if self.state.squashes.contains_key(&id) {
+            self.state
+                .set_error("A commit staged to be folded can't be split");
+            return;
+        }
+        if !self.check_unlocked(&[id]) {
+            return;
+        }
+        let files = match self.repo.changed_files(id) {
+            Ok(files) => files,
+            Err(e) => {
+                self.state.set_error(format!("Failed to list files: {e}"));
+                return;
+            }
+        };
+        if files.len() < 2 {
+            self.state
+                .set_error("Splitting needs a commit that changes at least two files");
+            return;
+        }
+
+        let existing = self.state.splits.get(&id);
+        let first = files
+            .iter()
+            .map(|(_, path)| existing.is_some_and(|s| s.paths.contains(path)))
+            .collect();
+        let message = existing.map_or_else(|| format!("{summary} (part 2)"), |s| s.message.clone());
+        self.state.mode = AppMode::Split(Box::new(SplitChooser {
+            commit_id: id,
+            files,
+            first,
+            cursor: 0,
+            message,
+            editing_message: false,
+        }));
+    }
+
+    /// Handle key in the split file chooser
+    fn handle_split_key(&mut self, key: KeyEvent, mut chooser: SplitChooser) {
+        if chooser.editing_message {
+            if (key.code, key.modifiers) == (KeyCode::Char('r'), KeyModifiers::CONTROL) {
+                self.open_replace(ReplaceTarget::SplitMessage(Box::new(chooser)));
+                return;
+            }
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => chooser.editing_message = false,
+                KeyCode::Backspace => {
+                    chooser.message.pop();
+                }
+                KeyCode::Char(c) => chooser.message.push(c),
+                _ => {}
+            }
+            self.state.mode = AppMode::Split(Box::new(chooser));
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                chooser.cursor = (chooser.cursor + 1).min(chooser.files.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                chooser.cursor = chooser.cursor.saturating_sub(1);
+            }
+            KeyCode::Char(' ') => chooser.first[chooser.cursor] = !chooser.first[chooser.cursor],
+            KeyCode::Char('m') => chooser.editing_message = true,
+            KeyCode::Char('d') => {
+                self.state.mode = AppMode::Normal;
+                if self.state.splits.contains_key(&chooser.commit_id) {
+                    if let Err(e) = self.state.set_split(chooser.commit_id, None) {
+                        self.state.set_error(e.to_string());
+                    } else {
+                        self.state.set_success("Split removed");
+                    }
+                }
+                return;
+            }
+            KeyCode::Enter => {
+                let paths = chooser.first_paths();
+                if paths.is_empty() || paths.len() == chooser.files.len() {
+                    self.state
+                        .set_error("Put at least one file in each commit (Space toggles)");
+                } else if chooser.message.trim().is_empty() {
+                    self.state
+                        .set_error("The second commit needs a message (m to edit)");
                 } else {
+                    let second = chooser.files.len() - paths.len();
+                    let first = paths.len();
+                    let split = CommitSplit {
+                        paths,
+                        message: chooser.message.trim().to_string(),
+                    };
                     self.state.mode = AppMode::Normal;
+                    match self.state.set_split(chooser.commit_id, Some(split)) {
+                        Ok(()) => self.state.set_success(format!(
+                            "Commit will be split: {first} file(s) first, {second} in the second"
+                        )),
+                        Err(e) => self.state.set_error(e.to_string()),
+                    }
+                    return;
                 }
             }
             _ => {}
         }
+        self.state.mode = AppMode::Split(Box::new(chooser));
+    }
 
-        Ok(())
+    /// Start reviewing the changed commits one by one before applying
+    fn start_step_apply(&mut self) {
+        if !self.state.is_dirty() {
+            self.state.set_error("No changes to apply");
+            return;
+        }
+        if let Err(e) = self.check_pushed() {
+            self.state.set_error(e.to_string());
+            return;
+        }
+        let queue = self.state.pending_commits();
+        if queue.is_empty() {
+            // Only the order changed: nothing to review per commit
+            self.confirm_apply();
+        } else {
+            self.state.mode = AppMode::StepApply(StepApply {
+                queue,
+                index: 0,
+                skipped: Vec::new(),
+            });
+        }
     }
 
-    /// Execute a confirmed action
-    fn execute_confirmed_action(&mut self, action: &ConfirmAction) -> Result<()> {
-        match action {
-            ConfirmAction::ApplyChanges => {
-                self.apply_changes()?;
+    /// Ask for confirmation before applying, listing the other branches and
+    /// the tags the rewrite would move
+    fn confirm_apply(&mut self) {
+        let (branches, tags) = self.rewrite_targets(true).unwrap_or_default();
+        self.state.signed_to_rewrite = self.signed_to_rewrite(&branches).unwrap_or_default();
+        self.state.dependent_branches = branches.into_iter().map(|b| b.name).collect();
+        self.state.tags_to_move = tags.into_iter().map(|t| t.name).collect();
+        self.confirm_dialog = ConfirmDialogState::default();
+        self.state.mode = AppMode::Confirming(ConfirmAction::ApplyChanges);
+    }
+
+    /// Short hashes of the signed commits the rewrite would replace (losing
+    /// their signatures): the rewritten loaded commits, those above them and
+    /// the dependent branches' own commits
+    fn signed_to_rewrite(&self, branches: &[DependentBranch]) -> Result<Vec<String>> {
+        let mut ids = self.state.rewritten_commits();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        ids.extend(self.repo.commits_above_start()?.iter().rev().map(|c| c.id));
+        ids.extend(branches.iter().flat_map(|b| b.commits.iter().map(|c| c.id)));
+        Ok(self
+            .repo
+            .signed_commits(&ids)
+            .iter()
+            .map(|id| id.0.to_string()[..7].to_string())
+            .collect())
+    }
+
+    /// Other local branches built on the commits a rewrite touches (the loaded
+    /// commits and those above them), and the tags naming any of those commits
+    /// or, with `with_branches`, the branches' own commits
+    fn rewrite_targets(&self, with_branches: bool) -> Result<(Vec<DependentBranch>, Vec<TagRef>)> {
+        let mut rewritten: HashSet<CommitId> = self.state.commits.iter().map(|c| c.id).collect();
+        rewritten.extend(self.repo.commits_above_start()?.iter().map(|c| c.id));
+        let branches = if with_branches {
+            self.repo.dependent_branches(&rewritten)?
+        } else {
+            Vec::new()
+        };
+        rewritten.extend(branches.iter().flat_map(|b| b.commits.iter().map(|c| c.id)));
+        let tags = self.repo.tags_on(&rewritten)?;
+        Ok((branches, tags))
+    }
+
+    /// Handle key while reviewing a commit in a step-through apply
+    fn handle_step_key(&mut self, key: KeyEvent, mut step: StepApply) -> Result<()> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc | KeyCode::Char('a' | 'q'), _) => {
+                self.state.mode = AppMode::Normal;
+                self.state.set_error("Apply aborted; nothing was written");
+                return Ok(());
             }
-            ConfirmAction::DiscardChanges => {
-                self.state.clear_modifications();
-                self.state.set_success("All changes discarded");
+            (KeyCode::Char('y') | KeyCode::Enter, _) => {}
+            (KeyCode::Char('s'), _) => step.skipped.push(step.queue[step.index]),
+            _ => return Ok(()),
+        }
+
+        step.index += 1;
+        if step.index < step.queue.len() {
+            self.state.mode = AppMode::StepApply(step);
+            return Ok(());
+        }
+        self.state.mode = AppMode::Normal;
+        self.finish_step_apply(&step.skipped)
+    }
+
+    /// Apply everything except the skipped commits' changes, which stay pending
+    fn finish_step_apply(&mut self, skipped: &[CommitId]) -> Result<()> {
+        let held = self.state.take_changes(skipped);
+        if !self.state.is_dirty() {
+            self.state.restore_changes(held, &HashMap::new());
+            self.state
+                .set_error("Every commit was skipped; nothing was written");
+            return Ok(());
+        }
+
+        self.apply_in_background(held)
+    }
+
+    /// With `protect_pushed`, refuse a rewrite that changes commits already
+    /// on the upstream, unless forced
+    fn check_pushed(&mut self) -> Result<()> {
+        if !self.protect_pushed {
+            return Ok(());
+        }
+        let pushed = self.repo.pushed_count(&self.state.changed_commits())?;
+        if pushed == 0 {
+            return Ok(());
+        }
+        if !self.state.force {
+            return Err(HistError::RemoteCommits(pushed));
+        }
+        self.state.record_bypass(Bypass::Pushed);
+        Ok(())
+    }
+
+    /// Apply all pending changes to the git history, waiting for the rewrite
+    ///
+    /// Returns the map from old to new commit IDs.
+    fn apply_changes(&mut self) -> Result<HashMap<CommitId, CommitId>> {
+        let mut pending = self.start_apply()?;
+        let outcome = pending.worker.wait();
+        self.finish_apply(pending, outcome)
+    }
+
+    /// Start applying the pending changes on a worker thread, showing its
+    /// progress until `poll_apply` sees it finish
+    ///
+    /// `held` are changes taken out of this apply, put back afterwards.
+    fn apply_in_background(&mut self, held: Vec<(CommitId, HeldChanges)>) -> Result<()> {
+        match self.start_apply() {
+            Ok(mut pending) => {
+                pending.held = held;
+                self.applying = Some(pending);
+                self.state.mode = AppMode::Applying;
+                Ok(())
             }
-            ConfirmAction::QuitWithChanges => {
-                self.should_quit = true;
+            Err(e) => {
+                self.state.restore_changes(held, &HashMap::new());
+                Err(e)
             }
         }
+    }
 
+    /// Finish the background rewrite once its worker is done
+    fn poll_apply(&mut self) -> Result<()> {
+        let Some(outcome) = self.applying.as_mut().and_then(|p| p.worker.poll()) else {
+            return Ok(());
+        };
+        let Some(pending) = self.applying.take() else {
+            return Ok(());
+        };
+        // A stash conflict after the rewrite switches to its own screen
         self.state.mode = AppMode::Normal;
-        Ok(())
+        let quit = std::mem::take(&mut self.quit_after_apply);
+        match self.finish_apply(pending, outcome) {
+            Err(HistError::Cancelled) => {
+                self.state
+                    .set_success("Rewrite cancelled; nothing was written");
+                Ok(())
+            }
+            Err(e) => Err(e),
+            Ok(_) => {
+                self.should_quit = quit && self.state.mode == AppMode::Normal;
+                Ok(())
+            }
+        }
     }
 
-    /// Apply all pending changes to the git history
-    fn apply_changes(&mut self) -> Result<()> {
+    /// Handle a key while a rewrite runs in the background
+    fn handle_applying_key(&self, key: KeyEvent) {
+        let cancel = matches!(key.code, KeyCode::Esc | KeyCode::Char('q'))
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+        if let Some(pending) = self.applying.as_ref().filter(|_| cancel) {
+            pending.worker.cancel();
+        }
+    }
+
+    /// Check the pending changes can be applied, create the backups and
+    /// start the rewrite on a worker thread
+    fn start_apply(&mut self) -> Result<PendingApply> {
+        self.check_pushed()?;
+        // Remember enough to undo the rewrite if the stash can't be restored
+        let old_tip = self.repo.tip_commit_id()?;
+        let old_start = self.repo.start();
+        let pending = self.state.snapshot("Apply");
+        let (branches, mut tags) = self.rewrite_targets(self.state.remap_branches)?;
+        if !self.state.migrate_tags {
+            tags.clear();
+        }
+
         // Auto-stash any uncommitted changes before rewriting
-        let stashed = self.repo.stash_changes()?;
+        // (only when the edited branch is checked out; others leave it alone).
+        // Forced, they stay put and the working tree isn't checked out.
+        let checked_out = self.repo.is_checked_out();
+        let keep_worktree =
+            checked_out && self.state.force && self.repo.has_uncommitted_changes()?;
+        if keep_worktree {
+            self.state.record_bypass(Bypass::DirtyWorktree);
+        }
+
+        // Create backup references
+        self.repo.create_backup_ref(&self.state.branch_name)?;
+        if self.numbered_backups {
+            self.repo
+                .create_numbered_backup(&self.state.branch_name, old_tip)?;
+        }
+        for branch in &branches {
+            self.repo.create_backup_ref_at(&branch.name, branch.tip);
+        }
 
-        // Perform the rewrite (with auto-restore on failure)
-        let result = self.apply_changes_inner();
+        let descendants = self.repo.commits_above_start()?;
+        let job = RewriteJob::from_plan(&self.rewrite_plan(&descendants, &branches));
+        let worktree = checked_out && !keep_worktree;
+        let worker = RewriteWorker::spawn(self.repo.reopen()?, job, worktree, worktree);
+        Ok(PendingApply {
+            worker,
+            old_tip,
+            old_start,
+            pending,
+            branches,
+            tags,
+            held: Vec::new(),
+        })
+    }
 
-        // Restore stashed changes if we stashed them
-        if stashed {
-            // Try to restore even if rewrite failed
-            if let Err(e) = self.repo.unstash_changes() {
-                // If unstash fails after successful rewrite, warn but don't fail
-                if result.is_ok() {
-                    self.state.set_error(format!(
-                        "Warning: Could not restore stashed changes: {e}. Use 'git stash pop' manually."
+    /// Finish an apply once its worker is done: move the tags, report, and
+    /// deal with stashed changes that couldn't be restored
+    fn finish_apply(
+        &mut self,
+        pending: PendingApply,
+        outcome: ApplyOutcome,
+    ) -> Result<HashMap<CommitId, CommitId>> {
+        let PendingApply {
+            old_tip,
+            old_start,
+            pending,
+            branches,
+            tags,
+            held,
+            ..
+        } = pending;
+        let result = outcome
+            .result
+            .and_then(|commit_map| self.finish_rewrite(&branches, &tags, old_tip, commit_map));
+
+        // The worker restored stashed changes even if the rewrite failed
+        match (outcome.unstash, &result) {
+            (None | Some(Ok(())), _) | (Some(Err(_)), Err(_)) => {}
+            (Some(Err(HistError::StashConflict(paths))), Ok(commit_map)) => {
+                self.state.mode = AppMode::StashConflict(Box::new(StashConflict {
+                    paths,
+                    old_tip,
+                    old_start,
+                    commit_map: commit_map.clone(),
+                    pending,
+                    moved_branches: branches.into_iter().map(|b| (b.name, b.tip)).collect(),
+                    moved_tags: tags,
+                }));
+            }
+            // If unstash fails after successful rewrite, warn but don't fail
+            (Some(Err(e)), Ok(_)) => {
+                self.state.set_error(format!(
+                    "Warning: Could not restore stashed changes: {e}. Use 'git stash pop' manually."
+                ));
+            }
+        }
+
+        // Changes of skipped commits stay pending, on the rewritten commits
+        let skipped = held.len();
+        match &result {
+            Ok(commit_map) => {
+                self.state.restore_changes(held, commit_map);
+                if skipped > 0 && self.state.error_message.is_none() {
+                    self.state.set_success(format!(
+                        "History rewritten; {skipped} skipped commit(s) still pending"
                     ));
-                    return Ok(());
                 }
-                // If both failed, return the original error
             }
+            Err(_) => self.state.restore_changes(held, &HashMap::new()),
         }
 
         result
     }
 
-    /// Inner implementation of `apply_changes` (separated for stash handling)
-    fn apply_changes_inner(&mut self) -> Result<()> {
-        // Create backup reference
-        self.repo.create_backup_ref(&self.state.branch_name)?;
+    /// Handle a key on the post-apply guidance screen
+    fn handle_applied_key(&mut self, key: KeyEvent, guidance: &ApplyGuidance) {
+        let (text, what) = match key.code {
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('y') => match guidance.push_command() {
+                Some(push) => (push, "Push command"),
+                None => (guidance.undo_command(), "Undo command"),
+            },
+            KeyCode::Char('Y') => (guidance.to_text(), "Guidance"),
+            _ => return,
+        };
+        match self.clipboard.copy(&text) {
+            Ok(()) => self
+                .state
+                .set_success(format!("{what} copied to the clipboard")),
+            Err(e) => self.state.set_error(e.to_string()),
+        }
+    }
+
+    /// Handle a key on the stash conflict screen
+    fn handle_stash_conflict_key(&mut self, key: KeyEvent, conflict: StashConflict) -> Result<()> {
+        match key.code {
+            KeyCode::Char('k') | KeyCode::Enter | KeyCode::Esc => {
+                self.state.mode = AppMode::Normal;
+                self.state.set_success(
+                    "History rewritten; your changes are kept in stash@{0} (resolve with git stash pop)",
+                );
+                Ok(())
+            }
+            KeyCode::Char('a') => {
+                self.state.mode = AppMode::Normal;
+                self.abort_rewrite(conflict)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Undo a rewrite whose stash couldn't be restored: put the branch back,
+    /// pop the stash onto it and bring back the pending changes
+    fn abort_rewrite(&mut self, conflict: StashConflict) -> Result<()> {
+        // Changes still pending on rewritten commits (skipped in `W`) move back too
+        let pending_ids = self.state.pending_commits();
+        let held = self.state.take_changes(&pending_ids);
+        let old_ids: HashMap<CommitId, CommitId> = conflict
+            .commit_map
+            .iter()
+            .map(|(old, new)| (*new, *old))
+            .collect();
+
+        self.repo
+            .restore_branch(&self.state.branch_name, conflict.old_tip)?;
+        for (name, tip) in &conflict.moved_branches {
+            self.repo.move_branch(name, *tip)?;
+        }
+        self.repo.restore_tags(&conflict.moved_tags)?;
+        self.repo.unstash_changes()?;
+        if let Some(start) = conflict.old_start {
+            self.repo.update_start(start);
+        }
+        self.reload_commits(self.state.commits.len())?;
+
+        self.state.restore_snapshot(conflict.pending);
+        self.state.restore_changes(held, &old_ids);
+        self.state.set_success(format!(
+            "Rewrite aborted; branch restored to {} with your changes and pending edits",
+            &conflict.old_tip.0.to_string()[..7]
+        ));
+        Ok(())
+    }
+
+    /// The rewrite of the pending changes, with the commits above the loaded
+    /// ones and the dependent branches to carry along
+    fn rewrite_plan<'a>(
+        &'a self,
+        descendants: &'a [CommitData],
+        branches: &'a [DependentBranch],
+    ) -> RewritePlan<'a> {
+        RewritePlan {
+            commits: &self.state.commits,
+            descendants,
+            modifications: &self.state.modifications,
+            deleted: &self.state.deleted,
+            squashes: &self.state.squashes,
+            splits: &self.state.splits,
+            original_order: &self.state.original_order,
+            new_order: &self.state.current_order,
+            branch_name: &self.state.branch_name,
+            branches,
+            update_hash_references: self.update_hash_references,
+            signer: self.signer.as_ref().filter(|_| self.state.sign_commits),
+            new_root: self.state.new_root.as_ref(),
+        }
+    }
+
+    /// Write the pending reorders, deletions, squashes and edits as a
+    /// `git rebase -i` todo list, replacing the file
+    fn export_rebase_todo(&self, path: &Path) -> Result<()> {
+        if !self.state.is_dirty() {
+            return Err(HistError::InvalidCommand(
+                "no changes to export".to_string(),
+            ));
+        }
+        let descendants = self.repo.commits_above_start()?;
+        let todo = rebase_todo(&self.rewrite_plan(&descendants, &[]))?;
+        std::fs::write(path, todo)?;
+        Ok(())
+    }
+
+    /// Show the hash each commit would get from applying, without moving
+    /// the branch
+    fn open_hash_preview(&mut self) -> Result<()> {
+        if !self.state.is_dirty() {
+            return Err(HistError::InvalidCommand(
+                "no changes to preview".to_string(),
+            ));
+        }
+        let descendants = self.repo.commits_above_start()?;
+        // Signatures differ on every run, so the preview leaves them out
+        let plan = RewritePlan {
+            signer: None,
+            ..self.rewrite_plan(&descendants, &[])
+        };
+        let commit_map = preview_rewrite(self.repo.inner(), &plan)?;
+        let rows = self
+            .state
+            .current_order
+            .iter()
+            .map(|id| (*id, commit_map.get(id).copied()))
+            .collect::<Vec<_>>();
+        let tip = descendants
+            .first()
+            .or_else(|| {
+                self.state
+                    .current_order
+                    .iter()
+                    .find(|id| !self.state.is_deleted(**id))
+                    .and_then(|id| self.state.commits.iter().find(|c| c.id == *id))
+            })
+            .and_then(|commit| commit_map.get(&commit.id).copied());
+        self.state.mode = AppMode::Preview(Box::new(HashPreview {
+            rows,
+            descendants: descendants.len(),
+            tip,
+            signed: self.signer.is_some() && self.state.sign_commits,
+            cursor: 0,
+        }));
+        Ok(())
+    }
+
+    /// Handle key in the hash preview
+    fn handle_preview_key(&mut self, key: KeyEvent, mut preview: HashPreview) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'P') => {
+                self.state.mode = AppMode::Normal;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                preview.cursor = (preview.cursor + 1).min(preview.rows.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                preview.cursor = preview.cursor.saturating_sub(1);
+            }
+            KeyCode::Char('w') => {
+                self.confirm_apply();
+                return;
+            }
+            _ => {}
+        }
+        self.state.mode = AppMode::Preview(Box::new(preview));
+    }
+
+    /// Record a successful rewrite: move the tags, write the report and
+    /// reload the rewritten commits
+    fn finish_rewrite(
+        &mut self,
+        branches: &[DependentBranch],
+        tags: &[TagRef],
+        old_tip: CommitId,
+        commit_map: HashMap<CommitId, CommitId>,
+    ) -> Result<HashMap<CommitId, CommitId>> {
+        let moved_tags = self.repo.move_tags(tags, &commit_map)?;
+        self.marks.remap(&commit_map);
+        let forced = std::mem::take(&mut self.state.bypassed);
+
+        let report = RewriteReport {
+            branch_name: &self.state.branch_name,
+            commits: &self.state.commits,
+            modifications: &self.state.modifications,
+            deleted: &self.state.deleted,
+            order_changed: order_changed(&self.state.original_order, &self.state.current_order),
+            commit_map: &commit_map,
+            has_upstream: self.state.has_upstream,
+            notes: &self.state.notes,
+            forced: &forced,
+        };
+        let summary = report.summary(self.repo.tip_commit_id()?);
+        let markdown = self.report_target.is_some().then(|| report.to_markdown());
+        self.summaries.push(summary);
+        let report_error = markdown.and_then(|markdown| self.write_report(markdown).err());
+
+        // Keep loading from the rewritten counterpart of the `--from` commit
+        if let Some(new_tip) = self
+            .state
+            .current_order
+            .iter()
+            .find(|id| !self.state.deleted.contains(id))
+            .and_then(|id| commit_map.get(id))
+        {
+            self.repo.update_start(*new_tip);
+        }
+
+        // A new root commit is one more to load
+        let loaded = self.state.commits.len() + usize::from(self.state.new_root.is_some());
+        self.reload_commits(loaded)?;
+
+        match report_error {
+            Some(e) => self
+                .state
+                .set_error(format!("History rewritten, but the report failed: {e}")),
+            None => {
+                let mut moved: Vec<String> = branches.iter().map(|b| b.name.clone()).collect();
+                if moved_tags > 0 {
+                    moved.push(format!("{moved_tags} tag(s)"));
+                }
+                let mut message = "History rewritten successfully!".to_string();
+                if !moved.is_empty() {
+                    message = format!("{message} Also moved {}", moved.join(", "));
+                }
+                if !forced.is_empty() {
+                    let keys: Vec<&str> = forced.iter().map(|b| b.key()).collect();
+                    message = format!("{message} (--force skipped: {})", keys.join(", "));
+                }
+                self.state.set_success(message);
+            }
+        }
+        self.state.mode = AppMode::Applied(Box::new(ApplyGuidance {
+            branch_name: self.state.branch_name.clone(),
+            old_tip,
+            new_tip: self.repo.tip_commit_id()?,
+            remote: self.repo.upstream_remote(),
+            forced,
+        }));
+
+        Ok(commit_map)
+    }
 
-        // Perform the rewrite
-        rewrite_history(
-            self.repo.inner(),
-            &self.state.commits,
-            &self.state.modifications,
-            &self.state.deleted,
-            &self.state.current_order,
-            &self.state.branch_name,
-        )?;
-
-        // Reload commits
-        let commits = self.repo.load_commits(self.state.commits.len())?;
+    /// Reload up to `limit` commits after the branch moved, dropping all pending
+    /// changes
+    fn reload_commits(&mut self, limit: usize) -> Result<()> {
+        // Commits still loading came from the old history
+        let was_loading = self.stop_loading();
+        let commits = self.repo.load_commits(limit)?;
+        let more = was_loading && commits.len() == limit;
         let original_order: Vec<_> = commits.iter().map(|c| c.id).collect();
+        self.identities = load_identities(&self.repo, &commits);
 
         self.state.commits = commits;
+        self.state.commits_changed();
         self.state.original_order = original_order.clone();
         self.state.current_order = original_order;
         self.state.modifications.clear();
+        self.state.deleted.clear();
+        self.state.squashes.clear();
+        self.state.splits.clear();
+        self.state.notes.clear();
+        self.state.new_root = None;
         self.state.undo_stack.clear();
         self.state.redo_stack.clear();
+        self.state.touched_paths.clear();
+        if more {
+            self.start_loading()?;
+        }
+        self.refresh_touched_paths()
+    }
 
-        self.state.set_success("History rewritten successfully!");
+    /// Write the Markdown report for a just-completed rewrite where requested
+    fn write_report(&mut self, report: String) -> Result<()> {
+        let Some(target) = &self.report_target else {
+            return Ok(());
+        };
 
+        match target {
+            ReportTarget::Stdout => self.stdout_reports.push(report),
+            ReportTarget::File(path) => {
+                use std::io::Write;
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?;
+                writeln!(file, "{report}")?;
+            }
+        }
         Ok(())
     }
 
@@ -1200,6 +5117,14 @@ impl App {
             KeyCode::Char('y' | 'Y') => {
                 self.should_quit = true;
             }
+            KeyCode::Char('s' | 'S') => match self.save_session() {
+                Ok(()) => self.should_quit = true,
+                Err(e) => {
+                    self.state.mode = AppMode::Normal;
+                    self.state
+                        .set_error(format!("Failed to save the session: {e}"));
+                }
+            },
             KeyCode::Char('n' | 'N') | KeyCode::Esc => {
                 self.state.mode = AppMode::Normal;
             }
@@ -1207,3 +5132,116 @@ impl App {
         }
     }
 }
+
+/// Status message suffix noting how many affected commits are hidden by the filter
+fn hidden_suffix(hidden: usize) -> String {
+    if hidden > 0 {
+        format!(" ({hidden} hidden by filter)")
+    } else {
+        String::new()
+    }
+}
+
+/// Append the pending identity edits (old → new) to a `.mailmap` file,
+/// keeping what's already in it; returns how many entries were added
+fn export_mailmap(state: &AppState, path: &Path) -> Result<usize> {
+    if !path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with("mailmap"))
+    {
+        return Err(HistError::InvalidCommand(format!(
+            "don't know how to export to '{}' (try .mailmap, plan.json or rebase-todo)",
+            path.display()
+        )));
+    }
+    let mailmap = state.identity_mailmap();
+    if mailmap.is_empty() {
+        return Err(HistError::InvalidCommand(
+            "no name or email edits to export".to_string(),
+        ));
+    }
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let (content, added) = mailmap.append_to(&existing)?;
+    if added > 0 {
+        std::fs::write(path, content)?;
+    }
+    Ok(added)
+}
+
+/// Write the pending order, edits and deletions to a JSON plan file,
+/// replacing it
+fn export_plan(state: &AppState, path: &Path) -> Result<()> {
+    if !state.is_dirty() {
+        return Err(HistError::InvalidCommand(
+            "no changes to export".to_string(),
+        ));
+    }
+    let plan = state.edit_plan()?;
+    std::fs::write(path, plan.to_json() + "\n")?;
+    Ok(())
+}
+
+/// Build the known identity index from loaded history and the repo's `.mailmap`
+fn load_identities(repo: &Repository, commits: &[CommitData]) -> KnownIdentities {
+    let mut identities = KnownIdentities::from_commits(commits);
+    if let Some(mailmap) = repo.mailmap() {
+        identities.add_mailmap(&mailmap, commits);
+    }
+    identities
+}
+
+/// Apply a line-editing key (cursor movement, insert, delete) to a text input
+fn handle_line_edit_key(input: &mut SearchState, key: KeyEvent) {
+    match (key.code, key.modifiers) {
+        // Delete character
+        (KeyCode::Backspace, KeyModifiers::NONE) => {
+            input.backspace();
+        }
+        (KeyCode::Delete, KeyModifiers::NONE) => {
+            input.delete();
+        }
+        // Delete word (Alt+Backspace on Mac, Ctrl+W or Ctrl+Backspace)
+        (KeyCode::Backspace, KeyModifiers::ALT | KeyModifiers::CONTROL)
+        | (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+            input.delete_word_backward();
+        }
+        // Delete to start of line (Ctrl+U)
+        (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+            input.delete_to_start();
+        }
+        // Delete to end of line (Ctrl+K)
+        (KeyCode::Char('k'), KeyModifiers::CONTROL) => {
+            input.delete_to_end();
+        }
+        // Move by character
+        (KeyCode::Left, KeyModifiers::NONE) => {
+            input.move_left();
+        }
+        (KeyCode::Right, KeyModifiers::NONE) => {
+            input.move_right();
+        }
+        // Move by word (Alt+Arrow on Mac, Ctrl+Arrow)
+        (KeyCode::Left, KeyModifiers::ALT | KeyModifiers::CONTROL) => {
+            input.move_word_left();
+        }
+        (KeyCode::Right, KeyModifiers::ALT | KeyModifiers::CONTROL) => {
+            input.move_word_right();
+        }
+        // Move to start/end (Home or Ctrl+A)
+        (KeyCode::Home, _) | (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
+            input.move_start();
+        }
+        // Move to end (End or Ctrl+E)
+        (KeyCode::End, _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
+            input.move_end();
+        }
+        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+            input.insert(c);
+        }
+        _ => {}
+    }
+}
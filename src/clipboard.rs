@@ -0,0 +1,36 @@
+//! Access to the system clipboard
+
+use crate::error::{HistError, Result};
+
+/// The system clipboard, connected on first use
+///
+/// The connection is kept open for the life of the app: on X11 the copied
+/// text is only served while its owner is alive.
+#[derive(Default)]
+pub struct SystemClipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl SystemClipboard {
+    /// Put `text` on the clipboard
+    pub fn copy(&mut self, text: &str) -> Result<()> {
+        self.connect()?
+            .set_text(text)
+            .map_err(|e| HistError::Clipboard(e.to_string()))
+    }
+
+    /// Get the text on the clipboard
+    pub fn paste(&mut self) -> Result<String> {
+        self.connect()?
+            .get_text()
+            .map_err(|e| HistError::Clipboard(e.to_string()))
+    }
+
+    fn connect(&mut self) -> Result<&mut arboard::Clipboard> {
+        let clipboard = match self.inner.take() {
+            Some(clipboard) => clipboard,
+            None => arboard::Clipboard::new().map_err(|e| HistError::Clipboard(e.to_string()))?,
+        };
+        Ok(self.inner.insert(clipboard))
+    }
+}
@@ -0,0 +1,266 @@
+//! User configuration loaded from `~/.config/retcon/config.toml`
+
+use crate::error::{HistError, Result};
+use crate::git::commit::Person;
+use crate::plugin::PluginCommand;
+use crate::ui::widgets::StatusBarConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// User configuration
+///
+/// Every setting is optional in the file; missing settings use the defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Warn before editing commits authored by someone other than the current git user
+    pub warn_foreign_commits: bool,
+    /// Rewrite hashes mentioned in commit messages (reverts, cherry-picks) to
+    /// the rewritten commits' new hashes when applying
+    pub update_hash_references: bool,
+    /// Move other local branches built on the rewritten commits onto the new
+    /// commits when applying
+    pub rewrite_dependent_branches: bool,
+    /// Re-point tags of rewritten commits (lightweight and annotated) at the
+    /// new commits when applying
+    pub migrate_tags: bool,
+    /// Also keep a numbered backup of the branch (`refs/retcon/backups/<branch>/<n>`)
+    /// each time a rewrite is applied
+    pub numbered_backups: bool,
+    /// Refuse to apply a rewrite that changes commits already on the upstream
+    /// (`--force` overrides it)
+    pub protect_pushed: bool,
+    /// Make commits older than this many days read-only until `:unlock`ed
+    pub lock_after_days: Option<u32>,
+    /// `:divergence` lists commits whose author and committer dates are more
+    /// than this many hours apart
+    pub date_divergence_hours: u32,
+    /// Ask before marking more than this many commits for deletion at once
+    pub delete_confirm_threshold: usize,
+    /// `M` moves each commit dated before the one below it to that date plus
+    /// this many seconds, instead of handing the dates out again in order
+    pub date_fix_nudge_seconds: Option<u32>,
+    /// Mark commit messages that aren't Conventional Commits from the start
+    /// (`:set lint`)
+    pub lint_conventional_commits: bool,
+    /// Message of the commit `:newroot` adds beneath the root commit
+    pub new_root_message: String,
+    /// Custom `:` commands backed by shell commands (`[[commands]]` tables)
+    pub commands: Vec<PluginCommand>,
+    /// Status bar segments (`[status_bar]` table)
+    pub status_bar: StatusBarConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            warn_foreign_commits: true,
+            update_hash_references: false,
+            rewrite_dependent_branches: true,
+            migrate_tags: true,
+            numbered_backups: false,
+            protect_pushed: false,
+            lock_after_days: None,
+            date_divergence_hours: 24,
+            delete_confirm_threshold: 1,
+            date_fix_nudge_seconds: None,
+            lint_conventional_commits: false,
+            new_root_message: "Initial commit".to_string(),
+            commands: Vec::new(),
+            status_bar: StatusBarConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Default config file location
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("retcon").join("config.toml"))
+    }
+
+    /// Load the config from the default location (defaults if there is no file)
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load the config from a file (defaults if it does not exist)
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content)
+                .map_err(|e| HistError::Config(format!("{}: {e}", path.display()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Parse config file content
+    ///
+    /// # Errors
+    /// Returns an error if the content is not valid TOML or has unknown settings.
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| HistError::Config(e.message().to_string()))
+    }
+}
+
+/// Named identities to pick from with `i`, from
+/// `~/.config/retcon/identities.toml`:
+///
+/// ```toml
+/// [work]
+/// name = "Jane Doe"
+/// email = "jane.doe@corp.example"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdentityProfiles(pub Vec<(String, Person)>);
+
+impl IdentityProfiles {
+    /// Default profiles file location
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("retcon").join("identities.toml"))
+    }
+
+    /// Load the profiles from the default location (none if there is no file)
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::default_path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content)
+                .map_err(|e| HistError::Config(format!("{}: {e}", path.display()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Parse profiles file content, sorted by profile name
+    ///
+    /// # Errors
+    /// Returns an error if the content is not valid TOML or a profile lacks a
+    /// name or email.
+    pub fn parse(content: &str) -> Result<Self> {
+        let profiles: BTreeMap<String, Person> =
+            toml::from_str(content).map_err(|e| HistError::Config(e.message().to_string()))?;
+        Ok(Self(profiles.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::default().warn_foreign_commits);
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let config = Config::parse("warn_foreign_commits = false\n").unwrap();
+        assert!(!config.warn_foreign_commits);
+        let config = Config::parse("update_hash_references = true\n").unwrap();
+        assert!(config.update_hash_references);
+        let config = Config::parse("rewrite_dependent_branches = false\n").unwrap();
+        assert!(!config.rewrite_dependent_branches);
+        let config = Config::parse("migrate_tags = false\n").unwrap();
+        assert!(!config.migrate_tags);
+        let config = Config::parse("numbered_backups = true\n").unwrap();
+        assert!(config.numbered_backups);
+        let config = Config::parse("new_root_message = \"Add license\"\n").unwrap();
+        assert_eq!(config.new_root_message, "Add license");
+        let config = Config::parse("delete_confirm_threshold = 10\n").unwrap();
+        assert_eq!(config.delete_confirm_threshold, 10);
+        let config = Config::parse("protect_pushed = true\n").unwrap();
+        assert!(config.protect_pushed);
+        let config = Config::parse("lock_after_days = 90\n").unwrap();
+        assert_eq!(config.lock_after_days, Some(90));
+        let config = Config::parse("date_divergence_hours = 2\n").unwrap();
+        assert_eq!(config.date_divergence_hours, 2);
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let config = Config::parse(
+            "[[commands]]\nname = \"upper\"\nrun = \"./upper.sh\"\ndescription = \"Shout\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.commands.len(), 1);
+        assert_eq!(config.commands[0].name, "upper");
+        assert_eq!(config.commands[0].run, "./upper.sh");
+    }
+
+    #[test]
+    fn test_parse_status_bar() {
+        use crate::ui::widgets::StatusSegment;
+
+        let config =
+            Config::parse("[status_bar]\nleft = [\"mode\", \"text\"]\ntext = \"prod repo!\"\n")
+                .unwrap();
+        assert_eq!(
+            config.status_bar.left,
+            [StatusSegment::Mode, StatusSegment::Text]
+        );
+        assert_eq!(config.status_bar.text, "prod repo!");
+        // Unset lists keep their defaults
+        assert_eq!(config.status_bar.right, StatusBarConfig::default().right);
+        assert!(Config::parse("[status_bar]\nleft = [\"clock\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_unknown_setting_is_an_error() {
+        assert!(matches!(
+            Config::parse("no_such_setting = 1"),
+            Err(HistError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&temp_dir.path().join("config.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_parse_identity_profiles() {
+        let profiles = IdentityProfiles::parse(
+            "[work]\nname = \"Jane Doe\"\nemail = \"jane@corp.example\"\n\n\
+             [oss]\nname = \"jd\"\nemail = \"jd@users.noreply.example\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            profiles.0,
+            vec![
+                (
+                    "oss".to_string(),
+                    Person::new("jd", "jd@users.noreply.example")
+                ),
+                (
+                    "work".to_string(),
+                    Person::new("Jane Doe", "jane@corp.example")
+                ),
+            ]
+        );
+        assert_eq!(
+            IdentityProfiles::parse("").unwrap(),
+            IdentityProfiles::default()
+        );
+        assert!(IdentityProfiles::parse("[work]\nname = \"Jane\"\n").is_err());
+    }
+}
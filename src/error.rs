@@ -30,9 +30,8 @@ pub enum RetconError {
     #[error("Uncommitted changes detected - commit or stash first")]
     DirtyWorkingTree,
 
-    #[allow(dead_code)]
-    #[error("Cannot modify commits that have been pushed to remote without --force")]
-    RemoteCommits,
+    #[error("Cannot rewrite {0} commit(s) already pushed to the upstream without --force (protect_pushed)")]
+    RemoteCommits(usize),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -43,11 +42,45 @@ pub enum RetconError {
     #[error("Commit not found: {0}")]
     CommitNotFound(String),
 
-    #[allow(dead_code)]
+    #[error("Ambiguous commit hash: {0}")]
+    AmbiguousHash(String),
+
     #[error("Invalid commit range: {0}")]
     InvalidRange(String),
 
-    #[allow(dead_code)]
+    #[error("Invalid revision: {0}")]
+    InvalidRevision(String),
+
+    #[error("Invalid command: {0}")]
+    InvalidCommand(String),
+
+    #[error("Invalid search: {0}")]
+    InvalidSearch(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Invalid plan: {0}")]
+    Plan(String),
+
+    #[error("Invalid session: {0}")]
+    Session(String),
+
+    #[error("Plugin failed: {0}")]
+    Plugin(String),
+
+    #[error("Signing failed: {0}")]
+    Signing(String),
+
+    #[error("Backend error: {0}")]
+    Backend(String),
+
+    #[error("Clipboard unavailable: {0}")]
+    Clipboard(String),
+
+    #[error("Stashed changes conflict with the rewritten history in: {}", .0.join(", "))]
+    StashConflict(Vec<String>),
+
     #[error("Operation cancelled by user")]
     Cancelled,
 }
@@ -0,0 +1,387 @@
+//! Libraries the history can be walked with
+//!
+//! libgit2 (through `git2`) does everything by default. Built with the `gix`
+//! feature, `--backend gix` loads the history with gitoxide instead: it walks
+//! the commits with the help of the commit-graph file and decodes each one
+//! itself, which is considerably faster on very large repositories.
+//!
+//! Everything else stays on libgit2, whatever the backend: diffs (path
+//! filters, the files pane, splits), signing, and the whole write side of a
+//! rewrite, which stages the new objects in memory and moves every ref in a
+//! single transaction, with no gitoxide counterpart yet. `--backend gix`
+//! doesn't make applying faster.
+
+use crate::error::{HistError, Result};
+use crate::git::commit::CommitData;
+use std::str::FromStr;
+
+/// Library used to walk the history (`--backend`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Git2,
+    #[cfg(feature = "gix")]
+    Gix,
+}
+
+impl FromStr for Backend {
+    type Err = HistError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "git2" => Ok(Self::Git2),
+            #[cfg(feature = "gix")]
+            "gix" => Ok(Self::Gix),
+            #[cfg(not(feature = "gix"))]
+            "gix" => Err(HistError::Backend(
+                "retcon was built without the `gix` feature".to_string(),
+            )),
+            _ => Err(HistError::Backend(format!(
+                "unknown backend {s} (expected git2 or gix)"
+            ))),
+        }
+    }
+}
+
+/// The commits to walk: those reachable from `tip` but not from `hide`, in
+/// date order with every commit before its parents
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryWalk {
+    pub tip: git2::Oid,
+    pub hide: Option<git2::Oid>,
+    /// Follow only the first parent of merge commits
+    pub first_parent: bool,
+    pub skip: usize,
+    pub count: usize,
+}
+
+impl Backend {
+    /// Hand the ID of each commit of `walk` to `each`, stopping early when it
+    /// returns false
+    pub fn walk(
+        self,
+        repo: &git2::Repository,
+        walk: &HistoryWalk,
+        each: impl FnMut(git2::Oid) -> Result<bool>,
+    ) -> Result<()> {
+        match self {
+            Self::Git2 => walk_git2(repo, walk, each),
+            #[cfg(feature = "gix")]
+            Self::Gix => {
+                let mut each = each;
+                walk_gix(repo.path(), walk, |_, id| each(to_git2(id)?))
+            }
+        }
+    }
+
+    /// Load each commit of `walk` and hand it to `each`, stopping early when
+    /// it returns false
+    pub fn load(
+        self,
+        repo: &git2::Repository,
+        walk: &HistoryWalk,
+        mut each: impl FnMut(CommitData) -> Result<bool>,
+    ) -> Result<()> {
+        match self {
+            Self::Git2 => walk_git2(repo, walk, |oid| {
+                each(CommitData::from_git2_commit(&repo.find_commit(oid)?))
+            }),
+            #[cfg(feature = "gix")]
+            Self::Gix => {
+                use gix::prelude::FindExt;
+
+                let mut buf = Vec::new();
+                walk_gix(repo.path(), walk, |repo, id| {
+                    let commit = repo
+                        .objects
+                        .find_commit(&id, &mut buf)
+                        .map_err(backend_error)?;
+                    each(commit_data(id, &commit)?)
+                })
+            }
+        }
+    }
+}
+
+fn walk_git2(
+    repo: &git2::Repository,
+    walk: &HistoryWalk,
+    mut each: impl FnMut(git2::Oid) -> Result<bool>,
+) -> Result<()> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    if walk.first_parent {
+        revwalk.simplify_first_parent()?;
+    }
+    revwalk.push(walk.tip)?;
+    if let Some(hide) = walk.hide {
+        revwalk.hide(hide)?;
+    }
+
+    for oid in revwalk.skip(walk.skip).take(walk.count) {
+        if !each(oid?)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Walk with gitoxide, opening the repository at `git_dir` for the walk and
+/// handing it to `each` with every commit's ID
+///
+/// gitoxide handles can't be sent to other threads without its `parallel`
+/// feature, so none is kept around; opening one is cheap next to the walk.
+#[cfg(feature = "gix")]
+fn walk_gix(
+    git_dir: &std::path::Path,
+    walk: &HistoryWalk,
+    mut each: impl FnMut(&gix::Repository, gix::ObjectId) -> Result<bool>,
+) -> Result<()> {
+    use gix::traverse::commit::{topo, Parents};
+
+    let repo = gix::open(git_dir).map_err(backend_error)?;
+    let tips = [to_gix(walk.tip)?];
+    let ends = walk.hide.map(to_gix).transpose()?;
+    let commits = topo::Builder::from_iters(&repo.objects, tips, ends.map(|end| [end]))
+        .sorting(topo::Sorting::DateOrder)
+        .parents(if walk.first_parent {
+            Parents::First
+        } else {
+            Parents::All
+        })
+        .with_commit_graph(repo.commit_graph().ok())
+        .build()
+        .map_err(backend_error)?;
+
+    for info in commits.skip(walk.skip).take(walk.count) {
+        let id = info.map_err(backend_error)?.id;
+        if !each(&repo, id)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// A commit gitoxide decoded, read the way libgit2 reads it: names, emails
+/// and messages that aren't UTF-8 fall back like `from_git2_commit`'s, and
+/// leading blank lines are dropped from the message
+#[cfg(feature = "gix")]
+fn commit_data(id: gix::ObjectId, commit: &gix::objs::CommitRef<'_>) -> Result<CommitData> {
+    use crate::git::commit::{datetime_at, CommitId, Person};
+
+    let person = |sig: &gix::actor::SignatureRef<'_>| {
+        Person::new(
+            std::str::from_utf8(sig.name).unwrap_or("Unknown"),
+            std::str::from_utf8(sig.email).unwrap_or("unknown@example.com"),
+        )
+    };
+    let (author, committer) = (commit.author(), commit.committer());
+    let message = std::str::from_utf8(commit.message)
+        .unwrap_or("")
+        .trim_start_matches('\n');
+    let parent_ids = commit
+        .parents()
+        .map(|parent| to_git2(parent).map(CommitId))
+        .collect::<Result<Vec<_>>>()?;
+    let id = to_git2(id)?;
+
+    Ok(CommitData {
+        id: CommitId(id),
+        short_hash: id.to_string()[..7].to_string(),
+        author: person(&author),
+        author_date: datetime_at(author.time.seconds, author.time.offset),
+        committer: person(&committer),
+        committer_date: datetime_at(committer.time.seconds, committer.time.offset),
+        summary: summary(message),
+        message: message.to_string(),
+        is_merge: parent_ids.len() > 1,
+        parent_ids,
+        tree_id: to_git2(commit.tree())?,
+    })
+}
+
+/// The summary libgit2 gives a message: its first paragraph, with runs of
+/// whitespace that span lines squashed to one space
+#[cfg(feature = "gix")]
+fn summary(message: &str) -> String {
+    let mut summary = String::new();
+    // A run of whitespace not yet copied, and whether it holds a line break
+    let mut space: Option<(usize, bool)> = None;
+    for (idx, c) in message.char_indices() {
+        if c == '\n' {
+            let rest = &message[idx + 1..];
+            let next_line = rest.split('\n').next().unwrap_or("");
+            // A blank line (or the end) closes the first paragraph
+            if rest.is_empty() || next_line.chars().all(char::is_whitespace) {
+                break;
+            }
+        }
+        if c.is_ascii_whitespace() {
+            let (start, newline) = space.unwrap_or((idx, false));
+            space = Some((start, newline || c == '\n'));
+        } else {
+            match space.take() {
+                Some((_, true)) => summary.push(' '),
+                Some((start, false)) => summary.push_str(&message[start..idx]),
+                None => {}
+            }
+            summary.push(c);
+        }
+    }
+    summary
+}
+
+#[cfg(feature = "gix")]
+fn to_git2(id: gix::ObjectId) -> Result<git2::Oid> {
+    Ok(git2::Oid::from_bytes(id.as_bytes())?)
+}
+
+#[cfg(feature = "gix")]
+fn to_gix(oid: git2::Oid) -> Result<gix::ObjectId> {
+    gix::ObjectId::try_from(oid.as_bytes()).map_err(backend_error)
+}
+
+#[cfg(feature = "gix")]
+fn backend_error(e: impl std::fmt::Display) -> HistError {
+    HistError::Backend(e.to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    #[test]
+    fn test_parse_backend() {
+        assert_eq!("git2".parse::<Backend>().unwrap(), Backend::Git2);
+        #[cfg(feature = "gix")]
+        assert_eq!("gix".parse::<Backend>().unwrap(), Backend::Gix);
+        #[cfg(not(feature = "gix"))]
+        assert!("gix".parse::<Backend>().is_err());
+        assert!("svn".parse::<Backend>().is_err());
+    }
+
+    fn walked(backend: Backend, test: &TestRepo, walk: &HistoryWalk) -> Vec<git2::Oid> {
+        let mut ids = Vec::new();
+        backend
+            .walk(test.git(), walk, |oid| {
+                ids.push(oid);
+                Ok(true)
+            })
+            .unwrap();
+        ids
+    }
+
+    #[test]
+    fn test_walk_order() {
+        let test = TestRepo::merged().unwrap();
+        let git = test.git();
+        let tip = git.head().unwrap().target().unwrap();
+        let base = git
+            .revparse_single(":/Base")
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .id();
+        let summary = |oid| git.find_commit(oid).unwrap().summary().unwrap().to_string();
+        let mut walk = HistoryWalk {
+            tip,
+            hide: None,
+            first_parent: false,
+            skip: 0,
+            count: usize::MAX,
+        };
+
+        let all = walked(Backend::Git2, &test, &walk);
+        assert_eq!(all.len(), 4);
+        assert_eq!(summary(all[0]), "Merge branch 'side'");
+        assert_eq!(summary(all[3]), "Base");
+
+        walk.first_parent = true;
+        let first_parent: Vec<_> = walked(Backend::Git2, &test, &walk)
+            .into_iter()
+            .map(summary)
+            .collect();
+        assert_eq!(first_parent, ["Merge branch 'side'", "Main work", "Base"]);
+
+        walk.hide = Some(base);
+        walk.skip = 1;
+        let hidden: Vec<_> = walked(Backend::Git2, &test, &walk)
+            .into_iter()
+            .map(summary)
+            .collect();
+        assert_eq!(hidden, ["Main work"]);
+    }
+
+    #[cfg(feature = "gix")]
+    #[test]
+    fn test_gix_loads_like_git2() {
+        let test = TestRepo::merged().unwrap();
+        let tip = test.git().head().unwrap().target().unwrap();
+        let walk = HistoryWalk {
+            tip,
+            hide: None,
+            first_parent: false,
+            skip: 0,
+            count: usize::MAX,
+        };
+        let loaded = |backend: Backend| {
+            let mut commits = Vec::new();
+            backend
+                .load(test.git(), &walk, |commit| {
+                    commits.push(format!("{commit:?}"));
+                    Ok(true)
+                })
+                .unwrap();
+            commits
+        };
+        assert_eq!(loaded(Backend::Gix), loaded(Backend::Git2));
+    }
+
+    #[cfg(feature = "gix")]
+    #[test]
+    fn test_summary_like_libgit2() {
+        let test = TestRepo::new().unwrap();
+        let git = test.git();
+        let sig = git2::Signature::now("A", "a@example.com").unwrap();
+        let tree = git
+            .find_tree(git.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        for message in [
+            "Subject\n",
+            "Subject\n\nBody",
+            "Wrapped\nsubject  line\n\nBody",
+            "Trailing blank line\n \t\nBody",
+            "  Leading space",
+            "",
+        ] {
+            let id = git.commit(None, &sig, &sig, message, &tree, &[]).unwrap();
+            let expected = git.find_commit(id).unwrap().summary().unwrap().to_string();
+            assert_eq!(summary(message), expected, "{message:?}");
+        }
+    }
+
+    #[cfg(feature = "gix")]
+    #[test]
+    fn test_gix_walks_like_git2() {
+        let test = TestRepo::merged().unwrap();
+        let tip = test.git().head().unwrap().target().unwrap();
+        let mut walk = HistoryWalk {
+            tip,
+            hide: None,
+            first_parent: false,
+            skip: 0,
+            count: usize::MAX,
+        };
+        for (first_parent, skip, count) in [(false, 0, usize::MAX), (true, 0, 10), (false, 1, 2)] {
+            walk.first_parent = first_parent;
+            walk.skip = skip;
+            walk.count = count;
+            assert_eq!(
+                walked(Backend::Gix, &test, &walk),
+                walked(Backend::Git2, &test, &walk)
+            );
+        }
+    }
+}
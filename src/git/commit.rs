@@ -1,7 +1,7 @@
 use chrono::{DateTime, FixedOffset};
 use git2::Oid;
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::fmt::{self, Write};
 
 /// Unique identifier for a commit
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -13,6 +13,25 @@ impl fmt::Display for CommitId {
     }
 }
 
+impl CommitId {
+    /// Whether the full hex hash starts with `prefix` (lowercase), compared
+    /// on the raw bytes so nothing is formatted
+    #[must_use]
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        let bytes = self.0.as_bytes();
+        prefix.len() <= bytes.len() * 2
+            && prefix.bytes().enumerate().all(|(i, digit)| {
+                let nibble = match digit {
+                    b'0'..=b'9' => digit - b'0',
+                    b'a'..=b'f' => digit - b'a' + 10,
+                    _ => return false,
+                };
+                let byte = bytes[i / 2];
+                nibble == if i % 2 == 0 { byte >> 4 } else { byte & 0xf }
+            })
+    }
+}
+
 /// Serde support for `git2::Oid`
 mod oid_serde {
     use git2::Oid;
@@ -55,6 +74,16 @@ impl Person {
     pub fn format_full(&self) -> String {
         format!("{} <{}>", self.name, self.email)
     }
+
+    /// Parse "Name <email>"
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (name, rest) = text.trim().split_once('<')?;
+        let email = rest.strip_suffix('>')?.trim();
+        let name = name.trim();
+        (!name.is_empty() && !email.is_empty() && !email.contains(['<', '>']))
+            .then(|| Self::new(name, email))
+    }
 }
 
 impl fmt::Display for Person {
@@ -156,18 +185,23 @@ impl CommitData {
 
 /// Convert `git2::Time` to `chrono::DateTime`<FixedOffset>
 fn git_time_to_datetime(time: &git2::Time) -> DateTime<FixedOffset> {
-    let offset_minutes = time.offset_minutes();
+    datetime_at(time.seconds(), time.offset_minutes() * 60)
+}
+
+/// The date `seconds` after the epoch, at a UTC offset of `offset` seconds
+/// east (UTC if that isn't a valid offset)
+pub(crate) fn datetime_at(seconds: i64, offset: i32) -> DateTime<FixedOffset> {
     // UTC (offset 0) is always valid - this cannot fail
     #[allow(clippy::expect_used)]
     let utc = FixedOffset::east_opt(0).expect("UTC offset is always valid");
-    let offset = FixedOffset::east_opt(offset_minutes * 60).unwrap_or(utc);
-    DateTime::from_timestamp(time.seconds(), 0)
+    let offset = FixedOffset::east_opt(offset).unwrap_or(utc);
+    DateTime::from_timestamp(seconds, 0)
         .unwrap_or_default()
         .with_timezone(&offset)
 }
 
 /// Tracks pending modifications to a commit
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CommitModifications {
     pub author_name: Option<String>,
     pub author_email: Option<String>,
@@ -197,29 +231,76 @@ impl CommitModifications {
         !self.is_empty()
     }
 
+    /// Whether a pending value is set for `field`
+    #[must_use]
+    pub const fn is_field_modified(&self, field: EditableField) -> bool {
+        match field {
+            EditableField::AuthorName => self.author_name.is_some(),
+            EditableField::AuthorEmail => self.author_email.is_some(),
+            EditableField::AuthorDate => self.author_date.is_some(),
+            EditableField::CommitterName => self.committer_name.is_some(),
+            EditableField::CommitterEmail => self.committer_email.is_some(),
+            EditableField::CommitterDate => self.committer_date.is_some(),
+            EditableField::Message => self.message.is_some(),
+        }
+    }
+
+    /// Superscript letters naming the modified fields (`ⁿᵉᵈᵐᶜ`), in column
+    /// order; `ᶜ` stands for any committer field, which have no column
+    #[must_use]
+    pub fn badges(&self) -> String {
+        const FIELD_BADGES: [(EditableField, &str); 4] = [
+            (EditableField::AuthorName, "ⁿ"),
+            (EditableField::AuthorEmail, "ᵉ"),
+            (EditableField::AuthorDate, "ᵈ"),
+            (EditableField::Message, "ᵐ"),
+        ];
+        let mut badges: String = FIELD_BADGES
+            .iter()
+            .filter(|(field, _)| self.is_field_modified(*field))
+            .map(|(_, badge)| *badge)
+            .collect();
+        if self.committer_name.is_some()
+            || self.committer_email.is_some()
+            || self.committer_date.is_some()
+        {
+            badges.push('ᶜ');
+        }
+        badges
+    }
+
+    /// Drop the pending value for `field`, returning whether there was one
+    pub fn clear_field(&mut self, field: EditableField) -> bool {
+        match field {
+            EditableField::AuthorName => self.author_name.take().is_some(),
+            EditableField::AuthorEmail => self.author_email.take().is_some(),
+            EditableField::AuthorDate => self.author_date.take().is_some(),
+            EditableField::CommitterName => self.committer_name.take().is_some(),
+            EditableField::CommitterEmail => self.committer_email.take().is_some(),
+            EditableField::CommitterDate => self.committer_date.take().is_some(),
+            EditableField::Message => self.message.take().is_some(),
+        }
+    }
+
     /// Get the effective author name (modified or original)
-    #[allow(dead_code)]
     #[must_use]
     pub fn effective_author_name<'a>(&'a self, original: &'a str) -> &'a str {
         self.author_name.as_deref().unwrap_or(original)
     }
 
     /// Get the effective author email (modified or original)
-    #[allow(dead_code)]
     #[must_use]
     pub fn effective_author_email<'a>(&'a self, original: &'a str) -> &'a str {
         self.author_email.as_deref().unwrap_or(original)
     }
 
     /// Get the effective committer name (modified or original)
-    #[allow(dead_code)]
     #[must_use]
     pub fn effective_committer_name<'a>(&'a self, original: &'a str) -> &'a str {
         self.committer_name.as_deref().unwrap_or(original)
     }
 
     /// Get the effective committer email (modified or original)
-    #[allow(dead_code)]
     #[must_use]
     pub fn effective_committer_email<'a>(&'a self, original: &'a str) -> &'a str {
         self.committer_email.as_deref().unwrap_or(original)
@@ -232,8 +313,71 @@ impl CommitModifications {
         self.message.as_deref().unwrap_or(original)
     }
 
+    /// Add a trailer to the effective message unless it already has it;
+    /// copies of it beyond the first are dropped
+    ///
+    /// Returns whether the message changed.
+    pub fn add_trailer(&mut self, original_message: &str, trailer: &Trailer) -> bool {
+        let (text, trailers) = split_trailers(self.effective_message(original_message));
+        let copies = trailers.iter().filter(|t| t.matches(trailer)).count();
+        if copies == 1 {
+            return false;
+        }
+        let mut kept: Vec<Trailer> = Vec::with_capacity(trailers.len() + 1);
+        for t in trailers {
+            if !t.matches(trailer) || !kept.iter().any(|k| k.matches(trailer)) {
+                kept.push(t);
+            }
+        }
+        if copies == 0 {
+            kept.push(trailer.clone());
+        }
+        self.message = Some(join_trailers(text, &kept));
+        true
+    }
+
+    /// Remove every copy of a trailer from the effective message
+    ///
+    /// Returns whether the message changed.
+    pub fn remove_trailer(&mut self, original_message: &str, trailer: &Trailer) -> bool {
+        let (text, trailers) = split_trailers(self.effective_message(original_message));
+        if !trailers.iter().any(|t| t.matches(trailer)) {
+            return false;
+        }
+        let kept: Vec<Trailer> = trailers
+            .into_iter()
+            .filter(|t| !t.matches(trailer))
+            .collect();
+        self.message = Some(join_trailers(text, &kept));
+        true
+    }
+
+    /// Replace every copy of a trailer in the effective message with another,
+    /// in place (dropped if the message already has the new one)
+    ///
+    /// Returns whether the message changed.
+    pub fn replace_trailer(
+        &mut self,
+        original_message: &str,
+        old: &Trailer,
+        new: &Trailer,
+    ) -> bool {
+        let (text, trailers) = split_trailers(self.effective_message(original_message));
+        if old.matches(new) || !trailers.iter().any(|t| t.matches(old)) {
+            return false;
+        }
+        let mut kept: Vec<Trailer> = Vec::with_capacity(trailers.len());
+        for t in trailers {
+            let t = if t.matches(old) { new.clone() } else { t };
+            if !t.matches(new) || !kept.iter().any(|k| k.matches(new)) {
+                kept.push(t);
+            }
+        }
+        self.message = Some(join_trailers(text, &kept));
+        true
+    }
+
     /// Get summary from effective message
-    #[allow(dead_code)]
     #[must_use]
     pub fn effective_summary<'a>(&'a self, original: &'a str) -> &'a str {
         self.message
@@ -241,6 +385,73 @@ impl CommitModifications {
             .map_or(original, |m| m.lines().next().unwrap_or(""))
     }
 
+    /// Overlay the fields set in `other`
+    pub fn merge(&mut self, other: Self) {
+        let Self {
+            author_name,
+            author_email,
+            author_date,
+            committer_name,
+            committer_email,
+            committer_date,
+            message,
+        } = other;
+        if author_name.is_some() {
+            self.author_name = author_name;
+        }
+        if author_email.is_some() {
+            self.author_email = author_email;
+        }
+        if author_date.is_some() {
+            self.author_date = author_date;
+        }
+        if committer_name.is_some() {
+            self.committer_name = committer_name;
+        }
+        if committer_email.is_some() {
+            self.committer_email = committer_email;
+        }
+        if committer_date.is_some() {
+            self.committer_date = committer_date;
+        }
+        if message.is_some() {
+            self.message = message;
+        }
+    }
+
+    /// Changed single-line fields of `commit` as `(label, old, new)`
+    ///
+    /// The message is left out; callers show it as a multi-line diff.
+    #[must_use]
+    pub fn field_changes(&self, commit: &CommitData) -> Vec<(&'static str, String, String)> {
+        let fmt_date =
+            |date: DateTime<FixedOffset>| date.format("%Y-%m-%d %H:%M:%S %z").to_string();
+        let mut fields = Vec::new();
+        if let Some(v) = &self.author_name {
+            fields.push(("Author name", commit.author.name.clone(), v.clone()));
+        }
+        if let Some(v) = &self.author_email {
+            fields.push(("Author email", commit.author.email.clone(), v.clone()));
+        }
+        if let Some(v) = self.author_date {
+            fields.push(("Author date", fmt_date(commit.author_date), fmt_date(v)));
+        }
+        if let Some(v) = &self.committer_name {
+            fields.push(("Committer name", commit.committer.name.clone(), v.clone()));
+        }
+        if let Some(v) = &self.committer_email {
+            fields.push(("Committer email", commit.committer.email.clone(), v.clone()));
+        }
+        if let Some(v) = self.committer_date {
+            fields.push((
+                "Committer date",
+                fmt_date(commit.committer_date),
+                fmt_date(v),
+            ));
+        }
+        fields
+    }
+
     /// Count how many fields have been modified
     #[allow(dead_code)]
     #[must_use]
@@ -271,6 +482,306 @@ impl CommitModifications {
     }
 }
 
+/// How to split a commit in two
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitSplit {
+    /// Paths whose changes go into the first (older) commit; the rest stay in
+    /// the second, which keeps the original tree
+    pub paths: Vec<String>,
+    /// Message of the second commit (the first keeps the commit's message)
+    pub message: String,
+}
+
+/// A commit to add beneath the root commit, e.g. a LICENSE/README bootstrap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewRoot {
+    /// Paths it takes from the old root's tree (none: an empty commit); the
+    /// old root keeps its tree, so it no longer adds them
+    pub paths: Vec<String>,
+    pub message: String,
+    /// Author and committer
+    pub author: Person,
+    /// Author and committer date
+    pub date: DateTime<FixedOffset>,
+}
+
+/// How a commit is folded into the commit before it (`git rebase --autosquash`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SquashKind {
+    /// Keep only the target's message (`fixup!`)
+    Fixup,
+    /// Append the commit's message to the target's (`squash!`)
+    Squash,
+}
+
+impl SquashKind {
+    /// Parse a `fixup! <target>` / `squash! <target>` subject
+    ///
+    /// Returns the kind (from the first prefix) and the target subject or hash
+    /// with any further nested prefixes stripped.
+    #[must_use]
+    pub fn from_subject(subject: &str) -> Option<(Self, &str)> {
+        let (kind, mut rest) = Self::strip_prefix(subject)?;
+        while let Some((_, inner)) = Self::strip_prefix(rest) {
+            rest = inner;
+        }
+        let rest = rest.trim();
+        (!rest.is_empty()).then_some((kind, rest))
+    }
+
+    fn strip_prefix(subject: &str) -> Option<(Self, &str)> {
+        if let Some(rest) = subject.strip_prefix("fixup! ") {
+            Some((Self::Fixup, rest))
+        } else {
+            subject
+                .strip_prefix("squash! ")
+                .map(|rest| (Self::Squash, rest))
+        }
+    }
+
+    /// Short marker shown in the selection column
+    #[must_use]
+    pub const fn marker(self) -> &'static str {
+        match self {
+            Self::Fixup => "[f]",
+            Self::Squash => "[s]",
+        }
+    }
+}
+
+/// Shortest abbreviated hash recognized in commit messages (git's default)
+pub const MIN_HASH_REFERENCE_LEN: usize = 7;
+
+/// Hex words in a message that look like full or abbreviated commit hashes
+///
+/// Returns each word with its byte offset. Only words of 7 to 40 hex digits
+/// count, so callers still need to match them against real commits.
+#[must_use]
+pub fn hash_references(text: &str) -> Vec<(usize, &str)> {
+    let mut refs = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(s) = start.take() {
+            let word = &text[s..i];
+            if (MIN_HASH_REFERENCE_LEN..=40).contains(&word.len())
+                && word.bytes().all(|b| b.is_ascii_hexdigit())
+            {
+                refs.push((s, word));
+            }
+        }
+    }
+    refs
+}
+
+/// A `Key: value` line in the trailer block at the end of a commit message
+/// (`Signed-off-by`, `Co-authored-by`, `Reviewed-by`, ...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl Trailer {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// `Signed-off-by: Name <email>`
+    #[must_use]
+    pub fn signed_off_by(person: &Person) -> Self {
+        Self::new("Signed-off-by", person.format_full())
+    }
+
+    /// `Co-authored-by: Name <email>`, as GitHub credits co-authors
+    #[must_use]
+    pub fn co_authored_by(person: &Person) -> Self {
+        Self::new("Co-authored-by", person.format_full())
+    }
+
+    /// Parse a `Key: value` line; keys are letters, digits and dashes
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let (key, value) = line.split_once(':')?;
+        let valid_key =
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        (valid_key && !value.trim().is_empty()).then(|| Self::new(key, value.trim()))
+    }
+
+    /// Whether this is the same trailer as `other` (keys compare
+    /// case-insensitively, like git)
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        self.key.eq_ignore_ascii_case(&other.key) && self.value == other.value
+    }
+
+    /// Whether the key is `key`, ignoring case
+    #[must_use]
+    pub fn is(&self, key: &str) -> bool {
+        self.key.eq_ignore_ascii_case(key)
+    }
+}
+
+impl fmt::Display for Trailer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.value)
+    }
+}
+
+/// Split a message into its text and its trailers
+///
+/// The trailers are the last paragraph, if it isn't the subject and every
+/// line is a trailer; indented lines continue the previous trailer's value.
+#[must_use]
+pub fn split_trailers(message: &str) -> (&str, Vec<Trailer>) {
+    let message = message.trim_end();
+    let Some(start) = message.rfind("\n\n") else {
+        return (message, Vec::new());
+    };
+    let mut trailers: Vec<Trailer> = Vec::new();
+    for line in message[start + 2..].lines() {
+        if line.starts_with([' ', '\t']) {
+            let Some(last) = trailers.last_mut() else {
+                return (message, Vec::new());
+            };
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+            continue;
+        }
+        match Trailer::parse(line) {
+            Some(trailer) => trailers.push(trailer),
+            None => return (message, Vec::new()),
+        }
+    }
+    (message[..start].trim_end(), trailers)
+}
+
+/// Put a message back together from its text and trailers
+#[must_use]
+pub fn join_trailers(text: &str, trailers: &[Trailer]) -> String {
+    let mut message = text.trim_end().to_string();
+    if !trailers.is_empty() {
+        message.push_str("\n\n");
+        for trailer in trailers {
+            let _ = writeln!(message, "{trailer}");
+        }
+        message.pop();
+    }
+    message.push('\n');
+    message
+}
+
+/// Rewrap a message's body to `width` columns
+///
+/// The subject line and the trailers are kept as they are, and so are
+/// blank lines, indented lines and fenced code blocks. List items (`-`, `*`,
+/// `+`, `1.`) are wrapped under their own text. Words longer than `width`
+/// (URLs, mostly) get a line of their own rather than being broken.
+#[must_use]
+pub fn reflow_message(message: &str, width: usize) -> String {
+    let trimmed = message.trim_end();
+    let (text, trailers) = split_trailers(trimmed);
+    let trailer_block = if trailers.is_empty() {
+        ""
+    } else {
+        trimmed[text.len()..].trim_start_matches('\n')
+    };
+    let (subject, body) = text.split_once('\n').unwrap_or((text, ""));
+
+    let mut lines: Vec<String> = vec![subject.to_string()];
+    // Marker of the list item being collected ("" for a plain paragraph), and its words
+    let mut paragraph: Option<(&str, Vec<&str>)> = None;
+    let mut in_fence = false;
+    for line in body.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        if in_fence || is_fence {
+            flush_paragraph(&mut lines, paragraph.take(), width);
+            lines.push(line.to_string());
+            in_fence ^= is_fence;
+        } else if line.trim().is_empty() {
+            flush_paragraph(&mut lines, paragraph.take(), width);
+            lines.push(String::new());
+        } else if let Some(marker) = list_marker(line) {
+            flush_paragraph(&mut lines, paragraph.take(), width);
+            paragraph = Some((marker, line[marker.len()..].split_whitespace().collect()));
+        } else if line.starts_with([' ', '\t']) {
+            // Continues a list item, or is preformatted text
+            match &mut paragraph {
+                Some((marker, words)) if !marker.is_empty() => {
+                    words.extend(line.split_whitespace());
+                }
+                _ => {
+                    flush_paragraph(&mut lines, paragraph.take(), width);
+                    lines.push(line.to_string());
+                }
+            }
+        } else {
+            paragraph
+                .get_or_insert(("", Vec::new()))
+                .1
+                .extend(line.split_whitespace());
+        }
+    }
+    flush_paragraph(&mut lines, paragraph, width);
+
+    let mut out = lines.join("\n");
+    if !trailer_block.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(trailer_block);
+    }
+    if message.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// A list item's marker with the space after it (`- `, `12. `), if the line is one
+fn list_marker(line: &str) -> Option<&str> {
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let marker_len = if line.starts_with(['-', '*', '+']) {
+        1
+    } else if digits > 0 && line[digits..].starts_with(['.', ')']) {
+        digits + 1
+    } else {
+        return None;
+    };
+    line[marker_len..]
+        .starts_with(' ')
+        .then(|| &line[..=marker_len])
+}
+
+/// Wrap a collected paragraph's words, continuation lines indented under the
+/// list item's text
+fn flush_paragraph(lines: &mut Vec<String>, paragraph: Option<(&str, Vec<&str>)>, width: usize) {
+    let Some((marker, words)) = paragraph else {
+        return;
+    };
+    let indent = " ".repeat(marker.chars().count());
+    let mut line = marker.to_string();
+    let mut line_words = 0;
+    for word in words {
+        if line_words > 0 && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::replace(&mut line, indent.clone()));
+            line_words = 0;
+        }
+        if line_words > 0 {
+            line.push(' ');
+        }
+        line.push_str(word);
+        line_words += 1;
+    }
+    lines.push(line);
+}
+
 /// Fields that can be edited on a commit
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EditableField {
@@ -285,7 +796,6 @@ pub enum EditableField {
 
 impl EditableField {
     /// Get all editable fields in order
-    #[allow(dead_code)]
     #[must_use]
     pub fn all() -> &'static [EditableField] {
         &[
@@ -376,8 +886,19 @@ impl EditableField {
         )
     }
 
+    /// The other half of a name/email identity pair (name <-> email)
+    #[must_use]
+    pub fn identity_counterpart(&self) -> Option<EditableField> {
+        match self {
+            EditableField::AuthorName => Some(EditableField::AuthorEmail),
+            EditableField::AuthorEmail => Some(EditableField::AuthorName),
+            EditableField::CommitterName => Some(EditableField::CommitterEmail),
+            EditableField::CommitterEmail => Some(EditableField::CommitterName),
+            _ => None,
+        }
+    }
+
     /// Is this a multiline field?
-    #[allow(dead_code)]
     #[must_use]
     pub fn is_multiline(&self) -> bool {
         matches!(self, EditableField::Message)
@@ -397,6 +918,181 @@ mod tests {
         assert_eq!(id.to_string(), "1234567");
     }
 
+    #[test]
+    fn test_commit_id_starts_with() {
+        let id = CommitId(git2::Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap());
+        assert!(id.starts_with("1234"));
+        assert!(id.starts_with("1234567890a"));
+        assert!(id.starts_with("1234567890abcdef1234567890abcdef12345678"));
+        assert!(!id.starts_with("1235"));
+        assert!(!id.starts_with("123456789A"));
+        assert!(!id.starts_with("1234567890abcdef1234567890abcdef123456780"));
+    }
+
+    #[test]
+    fn test_squash_kind_from_subject() {
+        assert_eq!(
+            SquashKind::from_subject("fixup! Add parser"),
+            Some((SquashKind::Fixup, "Add parser"))
+        );
+        assert_eq!(
+            SquashKind::from_subject("squash! fixup! Add parser"),
+            Some((SquashKind::Squash, "Add parser"))
+        );
+        assert_eq!(SquashKind::from_subject("fixup! "), None);
+        assert_eq!(SquashKind::from_subject("Add fixup! support"), None);
+    }
+
+    #[test]
+    fn test_hash_references() {
+        let text = "Revert \"Add cafe\"\n\nThis reverts commit 1234567890abcdef1234567890abcdef12345678.\nSee also abc1234, deadbee_x and facade.";
+        assert_eq!(
+            hash_references(text),
+            vec![
+                (39, "1234567890abcdef1234567890abcdef12345678"),
+                (90, "abc1234")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_and_join_trailers() {
+        let message = "Fix parser\n\nHandles empty input.\n\nSigned-off-by: Jane <jane@example.com>\nReviewed-by: Bob\n  Smith\n";
+        let (text, trailers) = split_trailers(message);
+        assert_eq!(text, "Fix parser\n\nHandles empty input.");
+        assert_eq!(
+            trailers,
+            vec![
+                Trailer::new("Signed-off-by", "Jane <jane@example.com>"),
+                Trailer::new("Reviewed-by", "Bob Smith"),
+            ]
+        );
+        assert_eq!(
+            join_trailers(text, &trailers[..1]),
+            "Fix parser\n\nHandles empty input.\n\nSigned-off-by: Jane <jane@example.com>\n"
+        );
+
+        // A subject line or prose paragraph is not a trailer block
+        assert_eq!(split_trailers("Fix: parser\n").1, Vec::new());
+        assert_eq!(split_trailers("Fix\n\nSee: the docs for more\n").1.len(), 1);
+        assert_eq!(split_trailers("Fix\n\nThis is prose.\n").1, Vec::new());
+    }
+
+    #[test]
+    fn test_reflow_message() {
+        let message = "Fix parser that is far too long a subject to wrap\n\n\
+            This body line is long enough that it has to wrap somewhere.\n\
+            Short one.\n\
+            \n\
+            - A list item that also runs past the width\n\
+            \x20 and continues here\n\
+            12. Numbered\n\
+            \n\
+            \x20   indented code stays as it is, however long it might be\n\
+            ```\n\
+            fenced  code   too\n\
+            ```\n\
+            \n\
+            Signed-off-by: Jane Doe <jane@example.com>\n";
+        assert_eq!(
+            reflow_message(message, 30),
+            "Fix parser that is far too long a subject to wrap\n\n\
+             This body line is long enough\n\
+             that it has to wrap somewhere.\n\
+             Short one.\n\
+             \n\
+             - A list item that also runs\n\
+             \x20 past the width and continues\n\
+             \x20 here\n\
+             12. Numbered\n\
+             \n\
+             \x20   indented code stays as it is, however long it might be\n\
+             ```\n\
+             fenced  code   too\n\
+             ```\n\
+             \n\
+             Signed-off-by: Jane Doe <jane@example.com>\n"
+        );
+
+        // Overlong words get their own line; a subject alone is left alone
+        assert_eq!(
+            reflow_message("Fix\n\nsee https://example.com/a/very/long/path ok", 20),
+            "Fix\n\nsee\nhttps://example.com/a/very/long/path\nok"
+        );
+        assert_eq!(reflow_message("Fix parser\n", 5), "Fix parser\n");
+    }
+
+    #[test]
+    fn test_add_trailer() {
+        let jane = Trailer::signed_off_by(&Person::new("Jane", "jane@example.com"));
+        let mut mods = CommitModifications::default();
+        assert!(mods.add_trailer("Fix parser\n", &jane));
+        assert_eq!(
+            mods.message.as_deref(),
+            Some("Fix parser\n\nSigned-off-by: Jane <jane@example.com>\n")
+        );
+        // Already there
+        assert!(!mods.add_trailer("Fix parser\n", &jane));
+
+        // Duplicates collapse to one
+        let doubled = "Fix\n\nsigned-off-by: Jane <jane@example.com>\nSigned-off-by: Jane <jane@example.com>\n";
+        let mut mods = CommitModifications::default();
+        assert!(mods.add_trailer(doubled, &jane));
+        assert_eq!(
+            mods.message.as_deref(),
+            Some("Fix\n\nsigned-off-by: Jane <jane@example.com>\n")
+        );
+    }
+
+    #[test]
+    fn test_remove_trailer() {
+        let bob = Trailer::co_authored_by(&Person::new("Bob", "bob@example.com"));
+        let message =
+            "Fix\n\nCo-authored-by: Bob <bob@example.com>\nCo-authored-by: Eve <eve@example.com>\n";
+        let mut mods = CommitModifications::default();
+        assert!(mods.remove_trailer(message, &bob));
+        assert_eq!(
+            mods.message.as_deref(),
+            Some("Fix\n\nCo-authored-by: Eve <eve@example.com>\n")
+        );
+        assert!(!mods.remove_trailer(message, &bob));
+
+        // The last trailer takes the blank line with it
+        let eve = Trailer::co_authored_by(&Person::new("Eve", "eve@example.com"));
+        assert!(mods.remove_trailer(message, &eve));
+        assert_eq!(mods.message.as_deref(), Some("Fix\n"));
+    }
+
+    #[test]
+    fn test_replace_trailer() {
+        let old = Trailer::new("Ticket", "ABC-1");
+        let new = Trailer::new("Ticket", "ABC-2");
+        let message = "Fix\n\nTicket: ABC-1\nReviewed-by: Bob\n";
+        let mut mods = CommitModifications::default();
+        assert!(mods.replace_trailer(message, &old, &new));
+        assert_eq!(
+            mods.message.as_deref(),
+            Some("Fix\n\nTicket: ABC-2\nReviewed-by: Bob\n")
+        );
+        assert!(!mods.replace_trailer(message, &old, &new));
+
+        // Replacing with a trailer that's already there merges them
+        let bob = Trailer::new("Reviewed-by", "Bob");
+        assert!(mods.replace_trailer(message, &new, &bob));
+        assert_eq!(mods.message.as_deref(), Some("Fix\n\nReviewed-by: Bob\n"));
+    }
+
+    #[test]
+    fn test_parse_person() {
+        assert_eq!(
+            Person::parse(" Jane Doe <jane@example.com> "),
+            Some(Person::new("Jane Doe", "jane@example.com"))
+        );
+        assert_eq!(Person::parse("Jane Doe"), None);
+        assert_eq!(Person::parse("<jane@example.com>"), None);
+        assert_eq!(Person::parse("Jane <>"), None);
+    }
+
     #[test]
     fn test_commit_id_equality() {
         let oid1 = git2::Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap();
@@ -455,6 +1151,27 @@ mod tests {
 
         assert_eq!(mods.modification_count(), 3);
         assert!(mods.has_modifications());
+        assert!(mods.is_field_modified(EditableField::AuthorEmail));
+        assert!(!mods.is_field_modified(EditableField::AuthorDate));
+        assert!(!mods.is_field_modified(EditableField::CommitterName));
+    }
+
+    #[test]
+    fn test_commit_modifications_badges() {
+        assert_eq!(CommitModifications::default().badges(), "");
+
+        let mut mods = CommitModifications {
+            message: Some("New message".to_string()),
+            author_name: Some("New Author".to_string()),
+            ..Default::default()
+        };
+        // Column order, whatever order the fields were set in
+        assert_eq!(mods.badges(), "ⁿᵐ");
+
+        // Committer fields share one badge
+        mods.committer_email = Some("new@example.com".to_string());
+        mods.committer_date = Some(chrono::Utc::now().fixed_offset());
+        assert_eq!(mods.badges(), "ⁿᵐᶜ");
     }
 
     #[test]
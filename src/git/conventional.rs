@@ -0,0 +1,155 @@
+//! Conventional Commits checks on commit subjects (`type(scope)!: subject`)
+
+use std::fmt;
+
+/// Commit types accepted in a subject, as `@commitlint/config-conventional` has them
+pub const COMMIT_TYPES: [&str; 11] = [
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Why a subject isn't a Conventional Commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintIssue {
+    /// No `type:` before the subject
+    MissingType,
+    /// A type that isn't one of `COMMIT_TYPES`
+    UnknownType(String),
+    /// `type()` with nothing in the parentheses, or no closing one
+    BadScope,
+    /// No space after the colon
+    MissingSpace,
+    /// Nothing after `type: `
+    EmptySubject,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingType => write!(f, "no type prefix"),
+            Self::UnknownType(kind) => write!(f, "unknown type '{kind}'"),
+            Self::BadScope => write!(f, "malformed scope"),
+            Self::MissingSpace => write!(f, "no space after ':'"),
+            Self::EmptySubject => write!(f, "empty description"),
+        }
+    }
+}
+
+/// The `type(scope)!` part of a subject, if it has something in that place
+struct Header<'a> {
+    kind: &'a str,
+    /// Scope and `!`, as written
+    rest: &'a str,
+    /// Text after the colon
+    description: &'a str,
+}
+
+fn split_header(subject: &str) -> Option<Header<'_>> {
+    let (head, description) = subject.split_once(':')?;
+    if head.is_empty() || head.contains(char::is_whitespace) {
+        return None;
+    }
+    let end = head.find(['(', '!']).unwrap_or(head.len());
+    if end == 0 {
+        return None;
+    }
+    Some(Header {
+        kind: &head[..end],
+        rest: &head[end..],
+        description,
+    })
+}
+
+/// Check a subject line; None if it's a Conventional Commit
+///
+/// Subjects git writes itself (`Merge ...`, `Revert "..."`) and
+/// `fixup!`/`squash!` subjects are not checked.
+#[must_use]
+pub fn lint_subject(subject: &str) -> Option<LintIssue> {
+    if subject.starts_with("Merge ")
+        || subject.starts_with("Revert \"")
+        || subject.starts_with("fixup! ")
+        || subject.starts_with("squash! ")
+    {
+        return None;
+    }
+    let Some(header) = split_header(subject) else {
+        return Some(LintIssue::MissingType);
+    };
+    if !COMMIT_TYPES.contains(&header.kind) {
+        return Some(LintIssue::UnknownType(header.kind.to_string()));
+    }
+    let scope = header.rest.strip_suffix('!').unwrap_or(header.rest);
+    if !scope.is_empty()
+        && !scope
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .is_some_and(|s| !s.is_empty() && !s.contains(['(', ')']))
+    {
+        return Some(LintIssue::BadScope);
+    }
+    match header.description.strip_prefix(' ') {
+        None if header.description.trim().is_empty() => Some(LintIssue::EmptySubject),
+        None => Some(LintIssue::MissingSpace),
+        Some(description) if description.trim().is_empty() => Some(LintIssue::EmptySubject),
+        Some(_) => None,
+    }
+}
+
+/// Give a subject the type `kind`
+///
+/// A type already in place (even an unknown one) is replaced, keeping its
+/// scope and `!`; otherwise `kind: ` is put in front.
+#[must_use]
+pub fn with_type(subject: &str, kind: &str) -> String {
+    match split_header(subject) {
+        Some(header) => format!("{kind}{}: {}", header.rest, header.description.trim_start()),
+        None => format!("{kind}: {subject}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_subject() {
+        assert_eq!(lint_subject("feat: add parser"), None);
+        assert_eq!(lint_subject("fix(ui)!: drop old keys"), None);
+        assert_eq!(lint_subject("chore!: bump MSRV"), None);
+        assert_eq!(lint_subject("Add parser"), Some(LintIssue::MissingType));
+        assert_eq!(
+            lint_subject("Add parser: first pass"),
+            Some(LintIssue::MissingType)
+        );
+        assert_eq!(
+            lint_subject("feature: add parser"),
+            Some(LintIssue::UnknownType("feature".to_string()))
+        );
+        assert_eq!(
+            lint_subject("feat(): add parser"),
+            Some(LintIssue::BadScope)
+        );
+        assert_eq!(
+            lint_subject("feat(ui: add parser"),
+            Some(LintIssue::BadScope)
+        );
+        assert_eq!(
+            lint_subject("feat:add parser"),
+            Some(LintIssue::MissingSpace)
+        );
+        assert_eq!(lint_subject("feat: "), Some(LintIssue::EmptySubject));
+
+        // Subjects git generates are left alone
+        assert_eq!(lint_subject("Merge branch 'main'"), None);
+        assert_eq!(lint_subject("Revert \"feat: add parser\""), None);
+        assert_eq!(lint_subject("fixup! feat: add parser"), None);
+    }
+
+    #[test]
+    fn test_with_type() {
+        assert_eq!(with_type("Add parser", "feat"), "feat: Add parser");
+        assert_eq!(with_type("feature(ui)!: add", "feat"), "feat(ui)!: add");
+        assert_eq!(with_type("fix:typo", "docs"), "docs: typo");
+        assert_eq!(with_type("(ui): add", "feat"), "feat: (ui): add");
+    }
+}
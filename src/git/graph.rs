@@ -0,0 +1,129 @@
+use crate::git::commit::{CommitData, CommitId};
+
+/// Draw a `git log --graph` style column for commits listed newest first
+///
+/// Returns one row per commit: `*` marks the commit's lane, `|` lanes
+/// passing by, `\` and `/` lanes opening for a merge's other parents (going
+/// down) or ending at a commit they branched off from (coming from above).
+/// Lanes are two characters wide. Parents that aren't loaded leave their
+/// lane running to the bottom.
+#[must_use]
+pub fn graph_rows(commits: &[CommitData]) -> Vec<String> {
+    // The commit each lane is waiting for
+    let mut lanes: Vec<Option<CommitId>> = Vec::new();
+    let mut rows = Vec::with_capacity(commits.len());
+
+    for commit in commits {
+        let column = lanes
+            .iter()
+            .position(|lane| *lane == Some(commit.id))
+            .unwrap_or_else(|| take_free_lane(&mut lanes));
+
+        let mut cells: Vec<char> = lanes
+            .iter()
+            .enumerate()
+            .map(|(idx, lane)| match lane {
+                _ if idx == column => '*',
+                // Another branch that forked off from this commit
+                Some(id) if *id == commit.id => {
+                    if idx > column {
+                        '/'
+                    } else {
+                        '\\'
+                    }
+                }
+                Some(_) => '|',
+                None => ' ',
+            })
+            .collect();
+
+        for lane in &mut lanes {
+            if *lane == Some(commit.id) {
+                *lane = None;
+            }
+        }
+        lanes[column] = commit.parent_ids.first().copied();
+        for parent in commit.parent_ids.iter().skip(1) {
+            if lanes.contains(&Some(*parent)) {
+                continue;
+            }
+            let idx = take_free_lane(&mut lanes);
+            lanes[idx] = Some(*parent);
+            cells.resize(lanes.len(), ' ');
+            cells[idx] = if idx > column { '\\' } else { '/' };
+        }
+        while lanes.last() == Some(&None) {
+            lanes.pop();
+        }
+
+        let row: Vec<String> = cells.iter().map(char::to_string).collect();
+        rows.push(row.join(" ").trim_end().to_string());
+    }
+    rows
+}
+
+/// Index of the first lane not waiting for a commit, adding one if needed
+fn take_free_lane(lanes: &mut Vec<Option<CommitId>>) -> usize {
+    lanes.iter().position(Option::is_none).unwrap_or_else(|| {
+        lanes.push(None);
+        lanes.len() - 1
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::Person;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn oid(n: u8) -> CommitId {
+        CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap())
+    }
+
+    fn commit(n: u8, parents: &[u8]) -> CommitData {
+        let dt = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap();
+        CommitData {
+            id: oid(n),
+            short_hash: format!("{n:07}"),
+            author: Person::new("A", "a@example.com"),
+            author_date: dt,
+            committer: Person::new("A", "a@example.com"),
+            committer_date: dt,
+            message: String::new(),
+            summary: String::new(),
+            parent_ids: parents.iter().map(|&p| oid(p)).collect(),
+            tree_id: git2::Oid::zero(),
+            is_merge: parents.len() > 1,
+        }
+    }
+
+    #[test]
+    fn test_linear_history() {
+        let commits = [commit(3, &[2]), commit(2, &[1]), commit(1, &[])];
+        assert_eq!(graph_rows(&commits), ["*", "*", "*"]);
+    }
+
+    #[test]
+    fn test_merge_and_fork() {
+        // 5 merges 4 (main) and 3 (topic); both branched off from 2
+        let commits = [
+            commit(5, &[4, 3]),
+            commit(4, &[2]),
+            commit(3, &[2]),
+            commit(2, &[1]),
+            commit(1, &[]),
+        ];
+        assert_eq!(graph_rows(&commits), ["* \\", "* |", "| *", "* /", "*"]);
+    }
+
+    #[test]
+    fn test_unloaded_parent_keeps_lane() {
+        // The merged branch's commits are older than the loaded range
+        let commits = [commit(3, &[2, 9]), commit(2, &[1]), commit(1, &[])];
+        assert_eq!(graph_rows(&commits), ["* \\", "* |", "* |"]);
+    }
+}
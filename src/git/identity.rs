@@ -0,0 +1,166 @@
+use crate::git::commit::CommitData;
+use crate::git::mailmap;
+use std::collections::HashMap;
+
+/// Known name/email pairings, used to suggest the matching half of an identity
+///
+/// Built from the identities in the loaded history (the most frequent pairing
+/// wins, ties go to the most recent) plus what the repository's `.mailmap`
+/// maps them to, which takes precedence.
+#[derive(Debug, Default, Clone)]
+pub struct KnownIdentities {
+    /// Name -> email
+    emails: HashMap<String, String>,
+    /// Lowercased email -> name
+    names: HashMap<String, String>,
+}
+
+impl KnownIdentities {
+    /// Collect identities from commit authors and committers
+    ///
+    /// Commits are expected newest first, so ties favor recent pairings.
+    #[must_use]
+    pub fn from_commits(commits: &[CommitData]) -> Self {
+        // (name, email) -> (count, first seen position)
+        let mut pairs: HashMap<(&str, &str), (usize, usize)> = HashMap::new();
+        let people = commits
+            .iter()
+            .flat_map(|c| [&c.author, &c.committer])
+            .enumerate();
+        for (position, person) in people {
+            if person.name.is_empty() || person.email.is_empty() {
+                continue;
+            }
+            pairs
+                .entry((person.name.as_str(), person.email.as_str()))
+                .or_insert((0, position))
+                .0 += 1;
+        }
+
+        // Most frequent first, then most recent
+        let mut ranked: Vec<_> = pairs.into_iter().collect();
+        ranked.sort_by(|(_, (count_a, pos_a)), (_, (count_b, pos_b))| {
+            count_b.cmp(count_a).then(pos_a.cmp(pos_b))
+        });
+
+        let mut identities = Self::default();
+        for ((name, email), _) in ranked {
+            identities
+                .emails
+                .entry(name.to_string())
+                .or_insert_with(|| email.to_string());
+            identities
+                .names
+                .entry(email.to_lowercase())
+                .or_insert_with(|| name.to_string());
+        }
+        identities
+    }
+
+    /// Add the canonical identities `mailmap` gives the authors and
+    /// committers of `commits`, overriding history
+    ///
+    /// Commits are expected newest first, so the most recent mapping wins.
+    pub fn add_mailmap(&mut self, mailmap: &git2::Mailmap, commits: &[CommitData]) {
+        let canonical = commits
+            .iter()
+            .flat_map(|c| [&c.author, &c.committer])
+            .filter_map(|person| mailmap::resolve(mailmap, person))
+            .filter(|person| !person.name.is_empty() && !person.email.is_empty());
+        // Oldest first, so newer mappings overwrite older ones
+        for person in canonical.rev() {
+            self.names
+                .insert(person.email.to_lowercase(), person.name.clone());
+            self.emails.insert(person.name, person.email);
+        }
+    }
+
+    /// The known email for an exact author name
+    #[must_use]
+    pub fn email_for(&self, name: &str) -> Option<&str> {
+        self.emails.get(name).map(String::as_str)
+    }
+
+    /// The known name for an email (case-insensitive)
+    #[must_use]
+    pub fn name_for(&self, email: &str) -> Option<&str> {
+        self.names.get(&email.to_lowercase()).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::{CommitId, Person};
+    use chrono::{FixedOffset, TimeZone};
+
+    fn commit(n: u8, name: &str, email: &str) -> CommitData {
+        let dt = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 15, 14, 30, 0)
+            .unwrap();
+        CommitData {
+            id: CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap()),
+            short_hash: format!("{n:07}"),
+            author: Person::new(name, email),
+            author_date: dt,
+            committer: Person::new(name, email),
+            committer_date: dt,
+            message: "msg".to_string(),
+            summary: "msg".to_string(),
+            parent_ids: vec![],
+            tree_id: git2::Oid::zero(),
+            is_merge: false,
+        }
+    }
+
+    #[test]
+    fn test_most_frequent_pairing_wins() {
+        let commits = vec![
+            commit(1, "Jane Doe", "jane@old.example"),
+            commit(2, "Jane Doe", "jane@example.com"),
+            commit(3, "Jane Doe", "jane@example.com"),
+        ];
+        let ids = KnownIdentities::from_commits(&commits);
+
+        assert_eq!(ids.email_for("Jane Doe"), Some("jane@example.com"));
+        assert_eq!(ids.name_for("JANE@old.example"), Some("Jane Doe"));
+        assert_eq!(ids.email_for("jane doe"), None);
+    }
+
+    #[test]
+    fn test_ties_favor_most_recent() {
+        let commits = vec![
+            commit(1, "Jane Doe", "jane@new.example"),
+            commit(2, "Jane Doe", "jane@old.example"),
+        ];
+        let ids = KnownIdentities::from_commits(&commits);
+        assert_eq!(ids.email_for("Jane Doe"), Some("jane@new.example"));
+    }
+
+    #[test]
+    fn test_mailmap_overrides_history() {
+        let commits = vec![
+            commit(1, "Jane Doe", "jane@old.example"),
+            commit(2, "Jane Doe", "jane@old.example"),
+            commit(3, "carol", "carol@example.com"),
+            commit(4, "Dave", "dave@example.com"),
+        ];
+        let mailmap = git2::Mailmap::from_buffer(
+            "# comment\n\
+             Jane Doe <jane@example.com> <jane@old.example>\n\
+             Carol <carol@example.com> # trailing comment\n\
+             Erin <erin@example.com>\n",
+        )
+        .unwrap();
+        let mut ids = KnownIdentities::from_commits(&commits);
+        ids.add_mailmap(&mailmap, &commits);
+
+        assert_eq!(ids.email_for("Jane Doe"), Some("jane@example.com"));
+        assert_eq!(ids.name_for("carol@example.com"), Some("Carol"));
+        assert_eq!(ids.name_for("dave@example.com"), Some("Dave"));
+        // Only identities from the history are looked up
+        assert_eq!(ids.name_for("erin@example.com"), None);
+    }
+}
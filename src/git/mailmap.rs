@@ -0,0 +1,220 @@
+//! `.mailmap` lookup through libgit2, and writing new entries for `:export`
+
+use crate::error::Result;
+use crate::git::commit::Person;
+use std::fmt::{self, Write};
+
+/// One `.mailmap` line: the identity to use, and the one it replaces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailmapEntry {
+    /// Name to use (None keeps the commit's)
+    pub proper_name: Option<String>,
+    /// Email to use (None keeps the commit's)
+    pub proper_email: Option<String>,
+    /// Only replace this name (None: any name with `commit_email`)
+    pub commit_name: Option<String>,
+    pub commit_email: String,
+}
+
+/// Entries to write to a `.mailmap` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mailmap {
+    pub entries: Vec<MailmapEntry>,
+}
+
+impl Mailmap {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add an entry mapping one identity to another, unless it's already there
+    pub fn add(&mut self, from: &Person, to: &Person) {
+        let entry = MailmapEntry {
+            proper_name: Some(to.name.clone()),
+            proper_email: Some(to.email.clone()),
+            commit_name: Some(from.name.clone()),
+            commit_email: from.email.clone(),
+        };
+        if !self.entries.contains(&entry) {
+            self.entries.push(entry);
+        }
+    }
+
+    /// Append these entries to existing `.mailmap` content, skipping the ones
+    /// it already maps the same way; returns the new content and how many
+    /// were added
+    ///
+    /// # Errors
+    /// Returns an error if the existing content can't be read as a mailmap.
+    pub fn append_to(&self, content: &str) -> Result<(String, usize)> {
+        let existing = git2::Mailmap::from_buffer(content)?;
+        let new = Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|entry| {
+                    let from = Person::new(
+                        entry.commit_name.as_deref().unwrap_or(""),
+                        &entry.commit_email,
+                    );
+                    let to = resolve(&existing, &from).unwrap_or(from);
+                    entry
+                        .proper_name
+                        .as_ref()
+                        .is_some_and(|name| *name != to.name)
+                        || entry
+                            .proper_email
+                            .as_ref()
+                            .is_some_and(|email| *email != to.email)
+                })
+                .cloned()
+                .collect(),
+        };
+        let mut content = content.to_string();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&new.to_string());
+        Ok((content, new.entries.len()))
+    }
+}
+
+/// The identity `mailmap` gives `person`, if it changes it
+///
+/// Resolution is libgit2's, which follows `git log --use-mailmap` except
+/// that names and emails match case-sensitively. An identity libgit2 can't represent
+/// (say, a name with `<` in it) is left alone.
+#[must_use]
+pub fn resolve(mailmap: &git2::Mailmap, person: &Person) -> Option<Person> {
+    let signature = git2::Signature::now(&person.name, &person.email).ok()?;
+    let resolved = mailmap.resolve_signature(&signature).ok()?;
+    let resolved = Person::new(resolved.name()?, resolved.email()?);
+    (resolved != *person).then_some(resolved)
+}
+
+impl fmt::Display for Mailmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let mut line = String::new();
+            if let Some(name) = &entry.proper_name {
+                line.push_str(name);
+            }
+            if let Some(email) = &entry.proper_email {
+                let _ = write!(line, " <{email}>");
+            }
+            if let Some(name) = &entry.commit_name {
+                let _ = write!(line, " {name}");
+            }
+            let _ = write!(line, " <{}>", entry.commit_email);
+            writeln!(f, "{}", line.trim_start())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const MAILMAP: &str = "\
+# Canonical identities
+Jane Doe <jane@example.com>
+<jane@example.com> <jane@old-laptop.local>
+Bob Smith <bob@example.com> <bobby@example.com>
+Bob Smith <bob@example.com> root <root@localhost>
+";
+
+    #[test]
+    fn test_write_forms() {
+        let entry = |proper_name: Option<&str>,
+                     proper_email: Option<&str>,
+                     commit_name: Option<&str>,
+                     email| {
+            MailmapEntry {
+                proper_name: proper_name.map(str::to_string),
+                proper_email: proper_email.map(str::to_string),
+                commit_name: commit_name.map(str::to_string),
+                commit_email: String::from(email),
+            }
+        };
+        let mailmap = Mailmap {
+            entries: vec![
+                entry(Some("Jane Doe"), None, None, "jane@example.com"),
+                entry(
+                    None,
+                    Some("jane@example.com"),
+                    None,
+                    "jane@old-laptop.local",
+                ),
+                entry(
+                    Some("Bob Smith"),
+                    Some("bob@example.com"),
+                    None,
+                    "bobby@example.com",
+                ),
+                entry(
+                    Some("Bob Smith"),
+                    Some("bob@example.com"),
+                    Some("root"),
+                    "root@localhost",
+                ),
+            ],
+        };
+        let entries: Vec<&str> = MAILMAP.lines().skip(1).collect();
+        assert_eq!(mailmap.to_string(), entries.join("\n") + "\n");
+    }
+
+    #[test]
+    fn test_append_to() {
+        let mut mailmap = Mailmap::default();
+        let old = Person::new("root", "root@localhost");
+        let bob = Person::new("Bob Smith", "bob@example.com");
+        mailmap.add(&old, &bob);
+        mailmap.add(&old, &bob);
+        mailmap.add(&Person::new("jdoe", "jdoe@laptop"), &bob);
+        assert_eq!(mailmap.entries.len(), 2);
+
+        // The existing file already maps root
+        let (content, added) = mailmap.append_to(MAILMAP.trim_end()).unwrap();
+        assert_eq!(added, 1);
+        assert!(content.starts_with(MAILMAP));
+        assert!(content.ends_with("Bob Smith <bob@example.com> jdoe <jdoe@laptop>\n"));
+        assert_eq!(mailmap.append_to(&content).unwrap(), (content.clone(), 0));
+
+        let written = git2::Mailmap::from_buffer(&content).unwrap();
+        assert_eq!(resolve(&written, &old), Some(bob));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mailmap = git2::Mailmap::from_buffer(MAILMAP).unwrap();
+        let resolve = |name: &str, email: &str| resolve(&mailmap, &Person::new(name, email));
+
+        assert_eq!(
+            resolve("jdoe", "jane@example.com"),
+            Some(Person::new("Jane Doe", "jane@example.com"))
+        );
+        // Unlike git, libgit2 matches case-sensitively
+        assert_eq!(resolve("jdoe", "JANE@example.com"), None);
+        // Only the email is replaced; the name is kept
+        assert_eq!(
+            resolve("jdoe", "jane@old-laptop.local"),
+            Some(Person::new("jdoe", "jane@example.com"))
+        );
+        assert_eq!(
+            resolve("Bobby", "bobby@example.com"),
+            Some(Person::new("Bob Smith", "bob@example.com"))
+        );
+        // Name-specific entries only match that name
+        assert_eq!(
+            resolve("root", "root@localhost"),
+            Some(Person::new("Bob Smith", "bob@example.com"))
+        );
+        assert_eq!(resolve("Root", "root@localhost"), None);
+        assert_eq!(resolve("admin", "root@localhost"), None);
+        // Already canonical
+        assert_eq!(resolve("Jane Doe", "jane@example.com"), None);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::git::commit::{CommitData, CommitId, CommitModifications};
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// File formats for a metadata dump (`:dump`, `retcon dump`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    Csv,
+    Json,
+}
+
+impl DumpFormat {
+    /// The format a file name asks for, by its extension
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?;
+        if ext.eq_ignore_ascii_case("csv") {
+            Some(Self::Csv)
+        } else if ext.eq_ignore_ascii_case("json") {
+            Some(Self::Json)
+        } else {
+            None
+        }
+    }
+}
+
+/// One commit's metadata, with the values pending edits would give it
+///
+/// The `new_` fields are only set for values that would change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitRecord {
+    pub hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub author_date: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committer_date: String,
+    pub summary: String,
+    pub deleted: bool,
+    pub new_author_name: Option<String>,
+    pub new_author_email: Option<String>,
+    pub new_author_date: Option<String>,
+    pub new_committer_name: Option<String>,
+    pub new_committer_email: Option<String>,
+    pub new_committer_date: Option<String>,
+    pub new_summary: Option<String>,
+}
+
+/// CSV header, in the order of `CommitRecord`'s fields
+const CSV_HEADER: &str = "hash,author_name,author_email,author_date,committer_name,\
+committer_email,committer_date,summary,deleted,new_author_name,new_author_email,\
+new_author_date,new_committer_name,new_committer_email,new_committer_date,new_summary";
+
+/// Records for commits, in the order given
+#[must_use]
+pub fn commit_records(
+    commits: &[&CommitData],
+    modifications: &HashMap<CommitId, CommitModifications>,
+    deleted: &HashSet<CommitId>,
+) -> Vec<CommitRecord> {
+    let no_changes = CommitModifications::default();
+    commits
+        .iter()
+        .map(|commit| {
+            let mods = modifications.get(&commit.id).unwrap_or(&no_changes);
+            let changed = |old: &str, new: &str| (old != new).then(|| new.to_string());
+            let author_date = format_date(commit.author_date);
+            let committer_date = format_date(commit.committer_date);
+            CommitRecord {
+                hash: commit.id.0.to_string(),
+                new_author_name: changed(
+                    &commit.author.name,
+                    mods.effective_author_name(&commit.author.name),
+                ),
+                new_author_email: changed(
+                    &commit.author.email,
+                    mods.effective_author_email(&commit.author.email),
+                ),
+                new_author_date: mods
+                    .author_date
+                    .map(format_date)
+                    .filter(|date| *date != author_date),
+                new_committer_name: changed(
+                    &commit.committer.name,
+                    mods.effective_committer_name(&commit.committer.name),
+                ),
+                new_committer_email: changed(
+                    &commit.committer.email,
+                    mods.effective_committer_email(&commit.committer.email),
+                ),
+                new_committer_date: mods
+                    .committer_date
+                    .map(format_date)
+                    .filter(|date| *date != committer_date),
+                new_summary: changed(&commit.summary, mods.effective_summary(&commit.summary)),
+                author_name: commit.author.name.clone(),
+                author_email: commit.author.email.clone(),
+                author_date,
+                committer_name: commit.committer.name.clone(),
+                committer_email: commit.committer.email.clone(),
+                committer_date,
+                summary: commit.summary.clone(),
+                deleted: deleted.contains(&commit.id),
+            }
+        })
+        .collect()
+}
+
+/// Records as CSV with a header row (RFC 4180 quoting, `\n` line ends)
+#[must_use]
+pub fn records_to_csv(records: &[CommitRecord]) -> String {
+    let mut out = String::from(CSV_HEADER);
+    out.push('\n');
+    for record in records {
+        let fields = [
+            Some(record.hash.as_str()),
+            Some(&record.author_name),
+            Some(&record.author_email),
+            Some(&record.author_date),
+            Some(&record.committer_name),
+            Some(&record.committer_email),
+            Some(&record.committer_date),
+            Some(&record.summary),
+            Some(if record.deleted { "true" } else { "false" }),
+            record.new_author_name.as_deref(),
+            record.new_author_email.as_deref(),
+            record.new_author_date.as_deref(),
+            record.new_committer_name.as_deref(),
+            record.new_committer_email.as_deref(),
+            record.new_committer_date.as_deref(),
+            record.new_summary.as_deref(),
+        ];
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| csv_field(field.unwrap_or_default()))
+            .collect();
+        let _ = writeln!(out, "{}", row.join(","));
+    }
+    out
+}
+
+/// Records as a pretty-printed JSON array
+#[must_use]
+pub fn records_to_json(records: &[CommitRecord]) -> String {
+    serde_json::to_string_pretty(records).unwrap_or_default()
+}
+
+/// Render records in a format
+#[must_use]
+pub fn render_records(records: &[CommitRecord], format: DumpFormat) -> String {
+    match format {
+        DumpFormat::Csv => records_to_csv(records),
+        DumpFormat::Json => records_to_json(records) + "\n",
+    }
+}
+
+fn format_date(date: DateTime<FixedOffset>) -> String {
+    date.format("%Y-%m-%d %H:%M:%S %z").to_string()
+}
+
+/// Quote a CSV field if it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::Person;
+    use chrono::TimeZone;
+
+    fn commit(summary: &str) -> CommitData {
+        let dt = FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 1, 12, 0, 0)
+            .unwrap();
+        CommitData {
+            id: CommitId(git2::Oid::from_bytes(&[1; 20]).unwrap()),
+            short_hash: "0101010".to_string(),
+            author: Person::new("Jane Doe", "jane@old.example"),
+            author_date: dt,
+            committer: Person::new("Jane Doe", "jane@old.example"),
+            committer_date: dt,
+            message: format!("{summary}\n\nBody"),
+            summary: summary.to_string(),
+            parent_ids: vec![],
+            tree_id: git2::Oid::zero(),
+            is_merge: false,
+        }
+    }
+
+    #[test]
+    fn test_records_with_pending_values() {
+        let commit = commit("Fix \"quoted\", parser");
+        let modifications = HashMap::from([(
+            commit.id,
+            CommitModifications {
+                author_name: Some("Jane Doe".to_string()),
+                author_email: Some("jane@example.com".to_string()),
+                message: Some("Fix parser\n\nBody".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let records = commit_records(&[&commit], &modifications, &HashSet::new());
+        let record = &records[0];
+        // Values set to what they already were aren't changes
+        assert_eq!(record.new_author_name, None);
+        assert_eq!(record.new_author_email.as_deref(), Some("jane@example.com"));
+        assert_eq!(record.new_summary.as_deref(), Some("Fix parser"));
+        assert!(!record.deleted);
+
+        let csv = records_to_csv(&records);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next().unwrap(),
+            format!(
+                "{},Jane Doe,jane@old.example,2024-03-01 12:00:00 +0100,Jane Doe,jane@old.example,\
+                 2024-03-01 12:00:00 +0100,\"Fix \"\"quoted\"\", parser\",false,,jane@example.com,,,,,Fix parser",
+                "01".repeat(20)
+            )
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&records_to_json(&records)).unwrap();
+        assert_eq!(json[0]["new_author_email"], "jane@example.com");
+        assert!(json[0]["new_author_name"].is_null());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            DumpFormat::from_path(Path::new("audit.CSV")),
+            Some(DumpFormat::Csv)
+        );
+        assert_eq!(
+            DumpFormat::from_path(Path::new("audit.json")),
+            Some(DumpFormat::Json)
+        );
+        assert_eq!(DumpFormat::from_path(Path::new("audit.txt")), None);
+    }
+}
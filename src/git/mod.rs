@@ -1,7 +1,17 @@
+pub mod backend;
 pub mod commit;
+pub mod conventional;
+pub mod graph;
+pub mod identity;
+pub mod mailmap;
+pub mod metadata;
+pub mod report;
 pub mod repository;
 pub mod rewrite;
+pub mod signing;
+pub mod todo;
 pub mod validation;
+pub mod worker;
 
 pub use repository::Repository;
 pub use rewrite::rewrite_history;
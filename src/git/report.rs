@@ -0,0 +1,647 @@
+use crate::git::commit::{CommitData, CommitId, CommitModifications, EditableField};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Everything needed to describe a completed rewrite
+pub struct RewriteReport<'a> {
+    pub branch_name: &'a str,
+    /// Commits as they were before the rewrite (display order, newest first)
+    pub commits: &'a [CommitData],
+    pub modifications: &'a HashMap<CommitId, CommitModifications>,
+    pub deleted: &'a HashSet<CommitId>,
+    pub order_changed: bool,
+    /// Old commit ID -> new commit ID, as returned by `rewrite_history`
+    pub commit_map: &'a HashMap<CommitId, CommitId>,
+    pub has_upstream: bool,
+    /// Why commits were changed (`:note`)
+    pub notes: &'a HashMap<CommitId, String>,
+    /// Safety checks skipped with `--force`
+    pub forced: &'a [Bypass],
+}
+
+impl RewriteReport<'_> {
+    /// Render the report as Markdown (old/new hashes, field diffs, next steps)
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let branch = self.branch_name;
+
+        let modified = self
+            .modifications
+            .values()
+            .filter(|m| m.has_modifications())
+            .count();
+        let _ = writeln!(out, "# retcon rewrite report\n");
+        let _ = write!(
+            out,
+            "Rewrote branch `{branch}`: {modified} commit(s) modified, {} deleted",
+            self.deleted.len()
+        );
+        if self.order_changed {
+            out.push_str(", commits reordered");
+        }
+        out.push_str(".\n\n");
+
+        self.write_hash_table(&mut out);
+        self.write_field_changes(&mut out);
+        self.write_notes(&mut out);
+        if !self.forced.is_empty() {
+            let _ = writeln!(out, "## Checks skipped (--force)\n");
+            for bypass in self.forced {
+                let _ = writeln!(out, "- {}", bypass.describe());
+            }
+            out.push('\n');
+        }
+
+        let _ = writeln!(out, "## Next steps\n");
+        if self.has_upstream {
+            let _ = writeln!(
+                out,
+                "The branch has an upstream, so publishing the rewrite needs a force push:\n"
+            );
+            let _ = writeln!(out, "```sh\ngit push --force-with-lease\n```\n");
+        } else {
+            let _ = writeln!(
+                out,
+                "The branch has no upstream; nothing needs to be pushed.\n"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "The previous tip is kept at `refs/original/heads/{branch}`. To undo:\n"
+        );
+        let _ = writeln!(
+            out,
+            "```sh\ngit reset --hard refs/original/heads/{branch}\n```"
+        );
+
+        out
+    }
+
+    /// Describe the rewrite before it happens, as plain text (`--dry-run`)
+    ///
+    /// `commit_map` holds the predicted new hashes (`preview_rewrite`), and
+    /// `commits` are in their new order. Commits keeping their hash are left
+    /// out.
+    #[must_use]
+    pub fn to_dry_run(&self) -> String {
+        let mut out = String::new();
+        let modified = self
+            .modifications
+            .values()
+            .filter(|m| m.has_modifications())
+            .count();
+        let _ = write!(
+            out,
+            "Dry run on branch {}: {modified} commit(s) modified, {} deleted",
+            self.branch_name,
+            self.deleted.len()
+        );
+        if self.order_changed {
+            out.push_str(", commits reordered (listed in their new order)");
+        }
+        out.push_str(". Nothing was written.\n");
+
+        for commit in self.commits {
+            let new = match self.commit_map.get(&commit.id) {
+                Some(new) if *new == commit.id => continue,
+                Some(new) => short(new),
+                None => "deleted".to_string(),
+            };
+            let _ = writeln!(out, "\n{} -> {new}  {}", commit.short_hash, commit.summary);
+            let Some(mods) = self
+                .modifications
+                .get(&commit.id)
+                .filter(|_| !self.deleted.contains(&commit.id))
+            else {
+                continue;
+            };
+            for (name, old, new) in mods.field_changes(commit) {
+                let _ = writeln!(out, "    {name}: {old} -> {new}");
+            }
+            if let Some(message) = &mods.message {
+                let _ = writeln!(out, "    Message:");
+                for line in commit.message.lines() {
+                    let _ = writeln!(out, "    -{line}");
+                }
+                for line in message.lines() {
+                    let _ = writeln!(out, "    +{line}");
+                }
+            }
+        }
+
+        let loaded: HashSet<CommitId> = self.commits.iter().map(|c| c.id).collect();
+        let replayed = self
+            .commit_map
+            .iter()
+            .filter(|(old, new)| !loaded.contains(old) && old != new)
+            .count();
+        if replayed > 0 {
+            let _ = writeln!(
+                out,
+                "\n{replayed} newer commit(s) above these would be replayed with new hashes."
+            );
+        }
+        out
+    }
+
+    /// Condense the report into the counts scripts care about
+    #[must_use]
+    pub fn summary(&self, new_tip: CommitId) -> RewriteSummary {
+        let fields_changed = self
+            .modifications
+            .iter()
+            .filter(|(id, _)| !self.deleted.contains(id))
+            .map(|(_, mods)| {
+                EditableField::all()
+                    .iter()
+                    .filter(|field| mods.is_field_modified(**field))
+                    .count()
+            })
+            .sum();
+        RewriteSummary {
+            branch_name: self.branch_name.to_string(),
+            rewritten: self
+                .commit_map
+                .iter()
+                .filter(|(old, new)| old != new)
+                .count(),
+            fields_changed,
+            deleted: self.deleted.len(),
+            new_tip,
+            forced: self.forced.to_vec(),
+        }
+    }
+
+    fn write_hash_table(&self, out: &mut String) {
+        let rows: Vec<&CommitData> = self
+            .commits
+            .iter()
+            .filter(|c| {
+                self.deleted.contains(&c.id)
+                    || self.commit_map.get(&c.id).is_some_and(|new| *new != c.id)
+            })
+            .collect();
+        if rows.is_empty() {
+            return;
+        }
+
+        let _ = writeln!(out, "## Commits\n");
+        let _ = writeln!(out, "| Old | New | Summary |");
+        let _ = writeln!(out, "|-----|-----|---------|");
+        for commit in rows {
+            let new = self
+                .commit_map
+                .get(&commit.id)
+                .map_or_else(|| "_deleted_".to_string(), |id| format!("`{}`", short(id)));
+            let _ = writeln!(
+                out,
+                "| `{}` | {new} | {} |",
+                commit.short_hash,
+                escape_cell(&commit.summary)
+            );
+        }
+        out.push('\n');
+    }
+
+    fn write_field_changes(&self, out: &mut String) {
+        let changed: Vec<(&CommitData, &CommitModifications)> = self
+            .commits
+            .iter()
+            .filter(|c| !self.deleted.contains(&c.id))
+            .filter_map(|c| {
+                self.modifications
+                    .get(&c.id)
+                    .filter(|m| m.has_modifications())
+                    .map(|m| (c, m))
+            })
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+
+        let _ = writeln!(out, "## Field changes\n");
+        for (commit, mods) in changed {
+            let new = self
+                .commit_map
+                .get(&commit.id)
+                .map_or_else(String::new, |id| format!(" → `{}`", short(id)));
+            let _ = writeln!(
+                out,
+                "### `{}`{new}: {}\n",
+                commit.short_hash, commit.summary
+            );
+
+            let fields = mods.field_changes(commit);
+            if !fields.is_empty() {
+                let _ = writeln!(out, "| Field | Old | New |");
+                let _ = writeln!(out, "|-------|-----|-----|");
+                for (name, old, new) in fields {
+                    let _ = writeln!(
+                        out,
+                        "| {name} | {} | {} |",
+                        escape_cell(&old),
+                        escape_cell(&new)
+                    );
+                }
+                out.push('\n');
+            }
+
+            if let Some(message) = &mods.message {
+                let _ = writeln!(out, "Message:\n\n```diff");
+                for line in commit.message.lines() {
+                    let _ = writeln!(out, "-{line}");
+                }
+                for line in message.lines() {
+                    let _ = writeln!(out, "+{line}");
+                }
+                let _ = writeln!(out, "```\n");
+            }
+        }
+    }
+
+    fn write_notes(&self, out: &mut String) {
+        let noted: Vec<(&CommitData, &String)> = self
+            .commits
+            .iter()
+            .filter_map(|c| self.notes.get(&c.id).map(|note| (c, note)))
+            .collect();
+        if noted.is_empty() {
+            return;
+        }
+
+        let _ = writeln!(out, "## Notes\n");
+        for (commit, note) in noted {
+            let _ = writeln!(out, "- `{}` {}: {note}", commit.short_hash, commit.summary);
+        }
+        out.push('\n');
+    }
+}
+
+/// The outcome of a rewrite in one line, for scripts (`--summary`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteSummary {
+    pub branch_name: String,
+    /// Commits that got a new ID (including newer commits replayed on top)
+    pub rewritten: usize,
+    /// Fields changed, counting each field of each commit once
+    pub fields_changed: usize,
+    pub deleted: usize,
+    pub new_tip: CommitId,
+    /// Safety checks skipped with `--force`
+    pub forced: Vec<Bypass>,
+}
+
+impl RewriteSummary {
+    /// `key=value` pairs separated by spaces, e.g.
+    /// `branch=main rewritten=3 fields=4 deleted=1 tip=<hash> backup=<ref>`,
+    /// ending with `forced=<check>,...` when `--force` skipped any checks
+    #[must_use]
+    pub fn to_line(&self) -> String {
+        let mut line = format!(
+            "branch={} rewritten={} fields={} deleted={} tip={} backup=refs/original/heads/{}",
+            self.branch_name,
+            self.rewritten,
+            self.fields_changed,
+            self.deleted,
+            self.new_tip.0,
+            self.branch_name
+        );
+        if !self.forced.is_empty() {
+            let keys: Vec<&str> = self.forced.iter().map(|b| b.key()).collect();
+            let _ = write!(line, " forced={}", keys.join(","));
+        }
+        line
+    }
+}
+
+/// A safety check skipped because of `--force`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bypass {
+    /// Uncommitted changes were left in place instead of being stashed
+    DirtyWorktree,
+    /// Commits locked by age (`lock_after_days`) were edited
+    Locked,
+    /// Commits already on the upstream were rewritten (`protect_pushed`)
+    Pushed,
+    /// Values that fail validation (malformed emails) were accepted
+    Validation,
+}
+
+impl Bypass {
+    /// Name in the `--summary` line
+    #[must_use]
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::DirtyWorktree => "worktree",
+            Self::Locked => "locked",
+            Self::Pushed => "pushed",
+            Self::Validation => "validation",
+        }
+    }
+
+    /// What was let through, for the report and the guidance screen
+    #[must_use]
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::DirtyWorktree => {
+                "Uncommitted changes were left in the working tree instead of being stashed; \
+                 it still has the files of the old history"
+            }
+            Self::Locked => "Commits locked by age (lock_after_days) were edited",
+            Self::Pushed => "Commits already on the upstream were rewritten (protect_pushed)",
+            Self::Validation => {
+                "Values that failed validation (such as malformed emails) were kept"
+            }
+        }
+    }
+}
+
+/// What to do after a rewrite: where the backup is, how to publish it and
+/// how teammates catch up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyGuidance {
+    pub branch_name: String,
+    /// Tip of the branch before the rewrite
+    pub old_tip: CommitId,
+    /// Tip of the branch after the rewrite
+    pub new_tip: CommitId,
+    /// Remote the branch tracks, if it has an upstream
+    pub remote: Option<String>,
+    /// Safety checks skipped with `--force`
+    pub forced: Vec<Bypass>,
+}
+
+impl ApplyGuidance {
+    /// Ref that keeps the history from before the first rewrite of the branch
+    #[must_use]
+    pub fn backup_ref(&self) -> String {
+        format!("refs/original/heads/{}", self.branch_name)
+    }
+
+    /// Command that publishes the rewritten branch, if it has an upstream
+    #[must_use]
+    pub fn push_command(&self) -> Option<String> {
+        self.remote
+            .as_ref()
+            .map(|remote| format!("git push --force-with-lease {remote} {}", self.branch_name))
+    }
+
+    /// Command that undoes this rewrite
+    #[must_use]
+    pub fn undo_command(&self) -> String {
+        format!("git reset --hard {}", self.old_tip.0)
+    }
+
+    /// All the guidance as plain text (what gets copied with `Y`)
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "# Rewrote {}: {} -> {}\n",
+            self.branch_name,
+            short(&self.old_tip),
+            short(&self.new_tip)
+        );
+        let _ = writeln!(
+            out,
+            "# Backup of the original history: {}",
+            self.backup_ref()
+        );
+        for bypass in &self.forced {
+            let _ = writeln!(out, "# --force: {}", bypass.describe());
+        }
+        let _ = writeln!(out, "# Undo:\n{}", self.undo_command());
+        if let Some(push) = self.push_command() {
+            let _ = writeln!(out, "# Publish:\n{push}");
+            let _ = writeln!(
+                out,
+                "# Teammates with the old history, on {}:\ngit pull --rebase",
+                self.branch_name
+            );
+        }
+        out
+    }
+}
+
+fn short(id: &CommitId) -> String {
+    id.0.to_string()[..7].to_string()
+}
+
+/// Escape a value for use inside a Markdown table cell
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::Person;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn commit(n: u8, summary: &str) -> CommitData {
+        let dt = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 15, 14, 30, 0)
+            .unwrap();
+        let id = CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap());
+        CommitData {
+            id,
+            short_hash: id.0.to_string()[..7].to_string(),
+            author: Person::new("Jane", "jane@old.example"),
+            author_date: dt,
+            committer: Person::new("Jane", "jane@old.example"),
+            committer_date: dt,
+            message: summary.to_string(),
+            summary: summary.to_string(),
+            parent_ids: vec![],
+            tree_id: git2::Oid::zero(),
+            is_merge: false,
+        }
+    }
+
+    fn id(n: u8) -> CommitId {
+        CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap())
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let commits = vec![
+            commit(3, "Third | piped"),
+            commit(2, "Second"),
+            commit(1, "First"),
+        ];
+        let mut modifications = HashMap::new();
+        modifications.insert(
+            id(3),
+            CommitModifications {
+                author_email: Some("jane@example.com".to_string()),
+                message: Some("Third, reworded".to_string()),
+                ..Default::default()
+            },
+        );
+        let deleted: HashSet<CommitId> = std::iter::once(id(2)).collect();
+        let commit_map: HashMap<CommitId, CommitId> =
+            [(id(3), id(0xaa)), (id(1), id(1))].into_iter().collect();
+
+        let report = RewriteReport {
+            branch_name: "main",
+            commits: &commits,
+            modifications: &modifications,
+            deleted: &deleted,
+            order_changed: false,
+            commit_map: &commit_map,
+            has_upstream: true,
+            notes: &std::iter::once((id(2), "Pushed by mistake".to_string())).collect(),
+            forced: &[Bypass::Locked],
+        }
+        .to_markdown();
+
+        assert!(report.contains("1 commit(s) modified, 1 deleted."));
+        // Rewritten and deleted commits are listed; unchanged ones are not
+        assert!(report.contains("| `0303030` | `aaaaaaa` | Third \\| piped |"));
+        assert!(report.contains("| `0202020` | _deleted_ | Second |"));
+        assert!(!report.contains("| `0101010` |"));
+        assert!(report.contains("| Author email | jane@old.example | jane@example.com |"));
+        assert!(report.contains("-Third | piped\n+Third, reworded\n"));
+        assert!(report.contains("## Notes\n\n- `0202020` Second: Pushed by mistake\n"));
+        assert!(report.contains("## Checks skipped (--force)\n\n- Commits locked by age"));
+        assert!(report.contains("git push --force-with-lease\n"));
+        assert!(report.contains("git reset --hard refs/original/heads/main"));
+    }
+
+    #[test]
+    fn test_dry_run_text() {
+        let commits = vec![commit(3, "Third"), commit(2, "Second"), commit(1, "First")];
+        let modifications = HashMap::from([(
+            id(3),
+            CommitModifications {
+                author_email: Some("jane@example.com".to_string()),
+                message: Some("Third, reworded".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let deleted = HashSet::from([id(2)]);
+        let commit_map = HashMap::from([(id(3), id(0xaa)), (id(1), id(1)), (id(4), id(0xbb))]);
+
+        let text = RewriteReport {
+            branch_name: "main",
+            commits: &commits,
+            modifications: &modifications,
+            deleted: &deleted,
+            order_changed: false,
+            commit_map: &commit_map,
+            has_upstream: false,
+            notes: &HashMap::new(),
+            forced: &[],
+        }
+        .to_dry_run();
+
+        assert!(text.starts_with("Dry run on branch main: 1 commit(s) modified, 1 deleted."));
+        assert!(text.contains(
+            "\n0303030 -> aaaaaaa  Third\n    Author email: jane@old.example -> jane@example.com\n    Message:\n    -Third\n    +Third, reworded\n"
+        ));
+        assert!(text.contains("\n0202020 -> deleted  Second\n"));
+        assert!(!text.contains("0101010"));
+        assert!(
+            text.ends_with("\n1 newer commit(s) above these would be replayed with new hashes.\n")
+        );
+    }
+
+    #[test]
+    fn test_summary_line() {
+        let commits = vec![commit(2, "Second"), commit(1, "First")];
+        let mut modifications = HashMap::new();
+        modifications.insert(
+            id(2),
+            CommitModifications {
+                author_name: Some("Jane Doe".to_string()),
+                committer_name: Some("Jane Doe".to_string()),
+                ..Default::default()
+            },
+        );
+        // Edits of deleted commits don't count
+        modifications.insert(
+            id(1),
+            CommitModifications {
+                message: Some("Gone".to_string()),
+                ..Default::default()
+            },
+        );
+        let deleted: HashSet<CommitId> = std::iter::once(id(1)).collect();
+        let commit_map: HashMap<CommitId, CommitId> = std::iter::once((id(2), id(0xbb))).collect();
+
+        let summary = RewriteReport {
+            branch_name: "main",
+            commits: &commits,
+            modifications: &modifications,
+            deleted: &deleted,
+            order_changed: false,
+            commit_map: &commit_map,
+            has_upstream: false,
+            notes: &HashMap::new(),
+            forced: &[],
+        }
+        .summary(id(0xbb));
+
+        let line = format!(
+            "branch=main rewritten=1 fields=2 deleted=1 tip={} backup=refs/original/heads/main",
+            id(0xbb).0
+        );
+        assert_eq!(summary.to_line(), line);
+        let forced = RewriteSummary {
+            forced: vec![Bypass::DirtyWorktree, Bypass::Pushed],
+            ..summary
+        };
+        assert_eq!(forced.to_line(), format!("{line} forced=worktree,pushed"));
+    }
+
+    #[test]
+    fn test_apply_guidance() {
+        let mut guidance = ApplyGuidance {
+            branch_name: "main".to_string(),
+            old_tip: id(1),
+            new_tip: id(2),
+            remote: Some("origin".to_string()),
+            forced: Vec::new(),
+        };
+        assert_eq!(guidance.backup_ref(), "refs/original/heads/main");
+        assert_eq!(
+            guidance.push_command().as_deref(),
+            Some("git push --force-with-lease origin main")
+        );
+        let text = guidance.to_text();
+        assert!(text.starts_with("# Rewrote main: 0101010 -> 0202020\n"));
+        assert!(text.contains(&format!("git reset --hard {}\n", id(1).0)));
+        assert!(text.contains("git pull --rebase"));
+
+        guidance.forced = vec![Bypass::Pushed];
+        assert!(guidance
+            .to_text()
+            .contains("# --force: Commits already on the upstream were rewritten"));
+
+        guidance.remote = None;
+        assert_eq!(guidance.push_command(), None);
+        assert!(!guidance.to_text().contains("git push"));
+    }
+
+    #[test]
+    fn test_report_without_upstream() {
+        let report = RewriteReport {
+            branch_name: "feature",
+            commits: &[],
+            modifications: &HashMap::new(),
+            deleted: &HashSet::new(),
+            order_changed: true,
+            commit_map: &HashMap::new(),
+            has_upstream: false,
+            notes: &HashMap::new(),
+            forced: &[],
+        }
+        .to_markdown();
+
+        assert!(report.contains("commits reordered"));
+        assert!(report.contains("nothing needs to be pushed"));
+        assert!(!report.contains("## Commits"));
+    }
+}
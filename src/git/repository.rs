@@ -1,13 +1,35 @@
 #![allow(clippy::missing_errors_doc)]
 
 use crate::error::{HistError, Result};
-use crate::git::commit::{CommitData, CommitId};
-use git2::{Repository as Git2Repository, RepositoryState, StatusOptions};
+use crate::git::backend::{Backend, HistoryWalk};
+use crate::git::commit::{CommitData, CommitId, Person};
+use crate::git::rewrite::DependentBranch;
+use crate::git::signing::Signer;
+use chrono::{DateTime, FixedOffset, TimeZone};
+use git2::{ConfigLevel, Repository as Git2Repository, RepositoryState, StatusOptions};
+use std::collections::HashSet;
 use std::path::Path;
 
+/// Where the backup of a branch's history before its first rewrite is kept
+const ORIGINAL_PREFIX: &str = "refs/original/heads/";
+
+/// Where numbered backups (one per rewrite, or made by hand) are kept, as
+/// `refs/retcon/backups/<branch>/<n>`
+const NUMBERED_PREFIX: &str = "refs/retcon/backups/";
+
 /// Wrapper around `git2::Repository` with convenience methods for retcon
 pub struct Repository {
     inner: Git2Repository,
+    /// Branch to edit instead of the checked-out one (`--branch`)
+    branch: Option<String>,
+    /// Commit to start loading from instead of HEAD (`--from`)
+    start: Option<git2::Oid>,
+    /// Commit whose history is left out (the `A` of `--rev-range A..B`)
+    base: Option<git2::Oid>,
+    /// Follow only first parents through merges (`--first-parent`)
+    first_parent: bool,
+    /// Library the history is walked with (`--backend`)
+    backend: Backend,
 }
 
 impl Repository {
@@ -20,11 +42,34 @@ impl Repository {
         let inner = Git2Repository::discover(path)
             .map_err(|_| HistError::NotARepository(path.display().to_string()))?;
 
-        let repo = Self { inner };
+        let repo = Self {
+            inner,
+            branch: None,
+            start: None,
+            base: None,
+            first_parent: false,
+            backend: Backend::default(),
+        };
         repo.validate_state()?;
         Ok(repo)
     }
 
+    /// Open another handle on the same repository, editing the same branch
+    /// from the same commits (for a worker thread)
+    ///
+    /// # Errors
+    /// Returns an error if the repository can't be opened again.
+    pub fn reopen(&self) -> Result<Self> {
+        Ok(Self {
+            inner: Git2Repository::open(self.inner.path())?,
+            branch: self.branch.clone(),
+            start: self.start,
+            base: self.base,
+            first_parent: self.first_parent,
+            backend: self.backend,
+        })
+    }
+
     /// Open a repository at the current directory
     pub fn open_current_dir() -> Result<Self> {
         Self::open(".")
@@ -75,42 +120,261 @@ impl Repository {
         Ok(!statuses.is_empty())
     }
 
-    /// Get the current branch name
+    /// Name of the branch being edited (the checked-out one unless `set_branch`
+    /// picked another)
     pub fn current_branch_name(&self) -> Result<String> {
-        let head = self.inner.head()?;
-        Ok(head.shorthand().unwrap_or("HEAD").to_string())
+        let branch = self.branch_ref()?;
+        Ok(branch.shorthand().unwrap_or("HEAD").to_string())
     }
 
-    /// Check if the current branch has an upstream
+    /// Check if the branch being edited has an upstream
     pub fn has_upstream(&self) -> Result<bool> {
-        let head = self.inner.head()?;
-        if !head.is_branch() {
+        let branch = self.branch_ref()?;
+        if !branch.is_branch() {
             return Ok(false);
         }
+        Ok(git2::Branch::wrap(branch).upstream().is_ok())
+    }
 
-        let branch_name = head.shorthand().unwrap_or("");
-        let branch = self
-            .inner
-            .find_branch(branch_name, git2::BranchType::Local)?;
-        Ok(branch.upstream().is_ok())
+    /// How many of `commits` are already on the upstream of the branch being
+    /// edited (0 without an upstream)
+    pub fn pushed_count(&self, commits: &HashSet<CommitId>) -> Result<usize> {
+        let branch = self.branch_ref()?;
+        if !branch.is_branch() {
+            return Ok(0);
+        }
+        let Some(upstream) = git2::Branch::wrap(branch)
+            .upstream()
+            .ok()
+            .and_then(|upstream| upstream.get().target())
+        else {
+            return Ok(0);
+        };
+        let mut count = 0;
+        for id in commits {
+            if id.0 == upstream || self.inner.graph_descendant_of(upstream, id.0)? {
+                count += 1;
+            }
+        }
+        Ok(count)
     }
 
-    /// Load commits from HEAD, up to the specified limit
-    pub fn load_commits(&self, limit: usize) -> Result<Vec<CommitData>> {
-        let mut revwalk = self.inner.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    /// Name of the remote the branch being edited tracks, if it has an upstream
+    pub fn upstream_remote(&self) -> Option<String> {
+        let branch = self.branch_ref().ok()?;
+        if !branch.is_branch() {
+            return None;
+        }
+        let remote = self.inner.branch_upstream_remote(branch.name()?).ok()?;
+        remote.as_str().map(str::to_string)
+    }
 
-        let mut commits = Vec::new();
-        for (count, oid_result) in revwalk.enumerate() {
-            if count >= limit {
-                break;
+    /// Edit the history of another local branch, without checking it out
+    ///
+    /// Resets the `--from`/`--rev-range` start point, which belonged to the
+    /// previous branch.
+    pub fn set_branch(&mut self, name: &str) -> Result<()> {
+        self.inner
+            .find_branch(name, git2::BranchType::Local)
+            .map_err(|_| HistError::InvalidRevision(format!("no local branch named {name}")))?;
+        let head = self.inner.head().ok();
+        let checked_out = head
+            .as_ref()
+            .is_some_and(|head| head.is_branch() && head.shorthand() == Some(name));
+        self.branch = (!checked_out).then(|| name.to_string());
+        self.start = None;
+        self.base = None;
+        Ok(())
+    }
+
+    /// Whether the branch being edited is the one checked out in the working tree
+    #[must_use]
+    pub fn is_checked_out(&self) -> bool {
+        self.branch.is_none()
+    }
+
+    /// Local branch names, sorted
+    pub fn branch_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for branch in self.inner.branches(Some(git2::BranchType::Local))? {
+            if let Some(name) = branch?.0.name()? {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Other local branches whose history includes any of the `rewritten` commits
+    ///
+    /// Each comes with its commits that aren't in the edited branch's history,
+    /// which a rewrite replays onto the new commits. Sorted by name. The
+    /// checked-out branch is never included when another branch is edited, so
+    /// the working tree doesn't change under the user.
+    pub fn dependent_branches(
+        &self,
+        rewritten: &HashSet<CommitId>,
+    ) -> Result<Vec<DependentBranch>> {
+        let edited = self.current_branch_name()?;
+        let head = self.inner.head().ok();
+        let checked_out = head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(git2::Reference::shorthand);
+        let tip = self.tip_commit_id()?.0;
+        let mut dependents = Vec::new();
+        for name in self.branch_names()? {
+            if name == edited || Some(name.as_str()) == checked_out {
+                continue;
             }
+            let branch_tip = self
+                .inner
+                .find_reference(&format!("refs/heads/{name}"))?
+                .peel_to_commit()?
+                .id();
+            if rewritten.contains(&CommitId(branch_tip)) {
+                dependents.push(DependentBranch {
+                    name,
+                    tip: CommitId(branch_tip),
+                    commits: Vec::new(),
+                });
+                continue;
+            }
+
+            let mut revwalk = self.inner.revwalk()?;
+            revwalk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+            revwalk.push(branch_tip)?;
+            revwalk.hide(tip)?;
+            let commits = revwalk
+                .map(|oid| Ok(CommitData::from_git2_commit(&self.inner.find_commit(oid?)?)))
+                .collect::<Result<Vec<_>>>()?;
+            if commits
+                .iter()
+                .flat_map(|c| &c.parent_ids)
+                .any(|parent| rewritten.contains(parent))
+            {
+                dependents.push(DependentBranch {
+                    name,
+                    tip: CommitId(branch_tip),
+                    commits,
+                });
+            }
+        }
+        Ok(dependents)
+    }
+
+    /// The reference of the branch being edited (HEAD when it's checked out)
+    fn branch_ref(&self) -> Result<git2::Reference<'_>> {
+        Ok(match &self.branch {
+            Some(name) => self.inner.find_reference(&format!("refs/heads/{name}"))?,
+            None => self.inner.head()?,
+        })
+    }
+
+    /// Start loading commits at `rev` (a commit, tag or other revision) instead of
+    /// the branch tip
+    ///
+    /// The revision must be reachable from the branch tip, so that the commits
+    /// above it can be replayed onto the rewritten history.
+    pub fn set_start(&mut self, rev: &str) -> Result<()> {
+        let commit = self
+            .inner
+            .revparse_single(rev)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|_| HistError::InvalidRevision(rev.to_string()))?;
+        let tip = self.tip_commit_id()?.0;
+
+        if commit.id() != tip && !self.inner.graph_descendant_of(tip, commit.id())? {
+            return Err(HistError::InvalidRevision(format!(
+                "{rev} is not reachable from the branch tip"
+            )));
+        }
+
+        self.start = Some(commit.id());
+        Ok(())
+    }
 
-            let oid = oid_result?;
-            let commit = self.inner.find_commit(oid)?;
-            commits.push(CommitData::from_git2_commit(&commit));
+    /// Load only the commits in `range` (`<A>..<B>`, like `git log A..B`)
+    ///
+    /// `B` becomes the start point (the branch tip when omitted) and must be
+    /// reachable from the tip; the commits above it are replayed unchanged on apply.
+    pub fn set_range(&mut self, range: &str) -> Result<()> {
+        let (base, tip) = range
+            .split_once("..")
+            .filter(|(base, tip)| !base.is_empty() && !tip.starts_with('.'))
+            .ok_or_else(|| HistError::InvalidRange(format!("{range} (expected <A>..<B>)")))?;
+        let base = self
+            .inner
+            .revparse_single(base)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|_| HistError::InvalidRevision(base.to_string()))?
+            .id();
+        match tip {
+            "" => self.start = Some(self.tip_commit_id()?.0),
+            tip => self.set_start(tip)?,
         }
+        self.base = Some(base);
+
+        if self.load_commits_page(0, 1)?.is_empty() {
+            self.start = None;
+            self.base = None;
+            return Err(HistError::InvalidRange(format!(
+                "{range} contains no commits"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Follow only the first parent of merge commits when loading history
+    pub fn set_first_parent(&mut self, first_parent: bool) {
+        self.first_parent = first_parent;
+    }
+
+    /// Walk the history with another library when loading commits
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+
+    /// Move the start point, e.g. to its rewritten counterpart after an apply
+    pub fn update_start(&mut self, id: CommitId) {
+        if self.start.is_some() {
+            self.start = Some(id.0);
+        }
+    }
+
+    /// The `--from` start point, if any
+    #[must_use]
+    pub fn start(&self) -> Option<CommitId> {
+        self.start.map(CommitId)
+    }
+
+    /// The commits between the start point and the branch tip (newest first,
+    /// start excluded)
+    ///
+    /// Empty when loading starts at the tip.
+    pub fn commits_above_start(&self) -> Result<Vec<CommitData>> {
+        let Some(start) = self.start else {
+            return Ok(Vec::new());
+        };
+
+        let mut commits = Vec::new();
+        self.load_history(
+            self.tip_commit_id()?.0,
+            Some(start),
+            0,
+            usize::MAX,
+            |commit| {
+                commits.push(commit);
+                Ok(true)
+            },
+        )?;
+        Ok(commits)
+    }
+
+    /// Load commits from the start point (the branch tip by default), up to the
+    /// specified limit
+    pub fn load_commits(&self, limit: usize) -> Result<Vec<CommitData>> {
+        let commits = self.load_commits_page(0, limit)?;
 
         if commits.is_empty() {
             return Err(HistError::NoCommits);
@@ -119,34 +383,142 @@ impl Repository {
         Ok(commits)
     }
 
-    /// Load commits in a specific range (exclusive start, inclusive end)
-    #[allow(dead_code)]
-    pub fn load_commits_range(
+    /// Load up to `count` commits from the start point, skipping the first `skip`
+    ///
+    /// Uses the same ordering as `load_commits`, so consecutive pages line up.
+    /// With a `--rev-range`, loading stops at the range's base.
+    pub fn load_commits_page(&self, skip: usize, count: usize) -> Result<Vec<CommitData>> {
+        let to = match self.start {
+            Some(start) => CommitId(start),
+            None => self.tip_commit_id()?,
+        };
+        self.load_commits_range(self.base.map(CommitId), to, skip, count)
+    }
+
+    /// Load up to `count` commits from the start point after skipping the
+    /// first `skip`, handing them to `batch` `size` at a time (the last batch
+    /// may be smaller)
+    ///
+    /// Walks the history once, in the order of `load_commits`; stops early
+    /// when `batch` returns false.
+    pub fn load_commits_batched(
         &self,
-        from: Option<CommitId>,
-        to: CommitId,
-        limit: usize,
-    ) -> Result<Vec<CommitData>> {
-        let mut revwalk = self.inner.revwalk()?;
-        revwalk.push(to.0)?;
+        skip: usize,
+        count: usize,
+        size: usize,
+        mut batch: impl FnMut(Vec<CommitData>) -> bool,
+    ) -> Result<()> {
+        let tip = match self.start {
+            Some(start) => start,
+            None => self.tip_commit_id()?.0,
+        };
+
+        let mut commits = Vec::with_capacity(size.min(count));
+        let mut stopped = false;
+        self.load_history(tip, self.base, skip, count, |commit| {
+            commits.push(commit);
+            if commits.len() == size && !batch(std::mem::take(&mut commits)) {
+                stopped = true;
+            }
+            Ok(!stopped)
+        })?;
+        if !stopped && !commits.is_empty() {
+            batch(commits);
+        }
+        Ok(())
+    }
 
-        if let Some(from_id) = from {
-            revwalk.hide(from_id.0)?;
+    /// The walk from `tip` (leaving out what `hide` reaches) with the
+    /// traversal options used for loading history
+    fn history_walk(
+        &self,
+        tip: git2::Oid,
+        hide: Option<git2::Oid>,
+        skip: usize,
+        count: usize,
+    ) -> HistoryWalk {
+        HistoryWalk {
+            tip,
+            hide,
+            first_parent: self.first_parent,
+            skip,
+            count,
         }
+    }
 
-        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    /// Walk the history's commit IDs with the backend
+    fn walk_history(
+        &self,
+        tip: git2::Oid,
+        hide: Option<git2::Oid>,
+        skip: usize,
+        count: usize,
+        each: impl FnMut(git2::Oid) -> Result<bool>,
+    ) -> Result<()> {
+        let walk = self.history_walk(tip, hide, skip, count);
+        self.backend.walk(&self.inner, &walk, each)
+    }
 
-        let mut commits = Vec::new();
-        for (count, oid_result) in revwalk.enumerate() {
-            if count >= limit {
-                break;
-            }
+    /// Load the history's commits with the backend
+    fn load_history(
+        &self,
+        tip: git2::Oid,
+        hide: Option<git2::Oid>,
+        skip: usize,
+        count: usize,
+        each: impl FnMut(CommitData) -> Result<bool>,
+    ) -> Result<()> {
+        let walk = self.history_walk(tip, hide, skip, count);
+        self.backend.load(&self.inner, &walk, each)
+    }
 
-            let oid = oid_result?;
-            let commit = self.inner.find_commit(oid)?;
-            commits.push(CommitData::from_git2_commit(&commit));
+    /// Where the commit a full or abbreviated hash names comes in the loading
+    /// order (the position `load_commits_page` counts), or None when it isn't
+    /// part of the history being edited
+    pub fn history_position(&self, hash: &str) -> Result<Option<usize>> {
+        let id = self
+            .inner
+            .find_commit_by_prefix(hash)
+            .map_err(|e| match e.code() {
+                git2::ErrorCode::Ambiguous => HistError::AmbiguousHash(hash.to_string()),
+                _ => HistError::CommitNotFound(hash.to_string()),
+            })?
+            .id();
+        let tip = match self.start {
+            Some(start) => start,
+            None => self.tip_commit_id()?.0,
+        };
+        if id != tip && !self.inner.graph_descendant_of(tip, id)? {
+            return Ok(None);
         }
 
+        let mut position = None;
+        let mut seen = 0;
+        self.walk_history(tip, self.base, 0, usize::MAX, |oid| {
+            if oid == id {
+                position = Some(seen);
+                return Ok(false);
+            }
+            seen += 1;
+            Ok(true)
+        })?;
+        Ok(position)
+    }
+
+    /// Load commits in a specific range (exclusive start, inclusive end),
+    /// skipping the first `skip` and taking up to `count`
+    pub fn load_commits_range(
+        &self,
+        from: Option<CommitId>,
+        to: CommitId,
+        skip: usize,
+        count: usize,
+    ) -> Result<Vec<CommitData>> {
+        let mut commits = Vec::new();
+        self.load_history(to.0, from.map(|id| id.0), skip, count, |commit| {
+            commits.push(commit);
+            Ok(true)
+        })?;
         Ok(commits)
     }
 
@@ -154,7 +526,7 @@ impl Repository {
     #[allow(dead_code)]
     pub fn commit_count(&self) -> Result<usize> {
         let mut revwalk = self.inner.revwalk()?;
-        revwalk.push_head()?;
+        revwalk.push(self.tip_commit_id()?.0)?;
         Ok(revwalk.count())
     }
 
@@ -165,6 +537,217 @@ impl Repository {
         Ok(CommitData::from_git2_commit(&commit))
     }
 
+    /// Find the commits among `commits` whose changes touch `path`
+    ///
+    /// A commit touches the path when its diff against its first parent adds,
+    /// modifies or removes the path itself or anything below it. With
+    /// `follow_renames`, a rename onto the tracked file (like `git log --follow`)
+    /// switches the search to the file's previous name for older commits.
+    /// `commits` must be newest first.
+    pub fn commits_touching_path(
+        &self,
+        commits: &[CommitData],
+        path: &str,
+        follow_renames: bool,
+    ) -> Result<HashSet<CommitId>> {
+        let mut tracked = path.trim_end_matches('/').to_string();
+        let mut touching = HashSet::new();
+
+        for data in commits {
+            let commit = self.inner.find_commit(data.id.0)?;
+            let parent_tree = match commit.parents().next() {
+                Some(parent) => Some(parent.tree()?),
+                None => None,
+            };
+            let mut diff =
+                self.inner
+                    .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+            if follow_renames {
+                diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+            }
+
+            let mut renamed_from = None;
+            for delta in diff.deltas() {
+                let old = delta.old_file().path().and_then(|p| p.to_str());
+                let new = delta.new_file().path().and_then(|p| p.to_str());
+                if !old.is_some_and(|p| path_matches(p, &tracked))
+                    && !new.is_some_and(|p| path_matches(p, &tracked))
+                {
+                    continue;
+                }
+                touching.insert(data.id);
+                if follow_renames
+                    && delta.status() == git2::Delta::Renamed
+                    && new == Some(tracked.as_str())
+                {
+                    renamed_from = old.map(str::to_string);
+                }
+            }
+            if let Some(old) = renamed_from {
+                tracked = old;
+            }
+        }
+
+        Ok(touching)
+    }
+
+    /// Files a commit changes against its first parent, as `(status, path)`
+    /// pairs (`A`dded, `M`odified, `D`eleted, `T`ype changed) sorted by path
+    pub fn changed_files(&self, id: CommitId) -> Result<Vec<(char, String)>> {
+        let diff = self.first_parent_diff(id)?;
+        let mut files: Vec<(char, String)> = diff.deltas().filter_map(|d| delta_file(&d)).collect();
+        files.sort_by(|a, b| a.1.cmp(&b.1));
+        Ok(files)
+    }
+
+    /// Files a commit changes against its first parent with their line
+    /// counts, sorted by path
+    pub fn file_changes(&self, id: CommitId) -> Result<Vec<FileChange>> {
+        let diff = self.first_parent_diff(id)?;
+        let mut files = Vec::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some((status, path)) = delta_file(&delta) else {
+                continue;
+            };
+            let (insertions, deletions) = match git2::Patch::from_diff(&diff, idx)? {
+                Some(patch) if !delta.flags().is_binary() => {
+                    let (_, insertions, deletions) = patch.line_stats()?;
+                    (insertions, deletions)
+                }
+                _ => (0, 0),
+            };
+            files.push(FileChange {
+                status,
+                path,
+                insertions,
+                deletions,
+                binary: delta.flags().is_binary(),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Diff of a commit's tree against its first parent's (or nothing, for
+    /// a root commit)
+    fn first_parent_diff(&self, id: CommitId) -> Result<git2::Diff<'_>> {
+        let commit = self.inner.find_commit(id.0)?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        Ok(self
+            .inner
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?)
+    }
+
+    /// Which of `paths` a commit's tree doesn't have
+    pub fn missing_paths(&self, id: CommitId, paths: &[String]) -> Result<Vec<String>> {
+        let tree = self.inner.find_commit(id.0)?.tree()?;
+        Ok(paths
+            .iter()
+            .filter(|path| tree.get_path(Path::new(path.as_str())).is_err())
+            .cloned()
+            .collect())
+    }
+
+    /// The commit signing setup from git config, if any
+    pub fn signer(&self) -> Result<Option<Signer>> {
+        Signer::from_config(&self.inner.config()?)
+    }
+
+    /// Whether git signs new commits by default (`commit.gpgsign`)
+    #[must_use]
+    pub fn signs_by_default(&self) -> bool {
+        self.inner
+            .config()
+            .and_then(|config| config.get_bool("commit.gpgsign"))
+            .unwrap_or(false)
+    }
+
+    /// The identity git would record as author, if known
+    ///
+    /// Like git, `GIT_AUTHOR_NAME`/`GIT_AUTHOR_EMAIL` take precedence over
+    /// `user.name`/`user.email`.
+    #[must_use]
+    pub fn author_identity(&self) -> Option<Person> {
+        self.identity("GIT_AUTHOR")
+    }
+
+    /// The identity git would record as committer, if known
+    ///
+    /// Like git, `GIT_COMMITTER_NAME`/`GIT_COMMITTER_EMAIL` take precedence
+    /// over `user.name`/`user.email`.
+    #[must_use]
+    pub fn committer_identity(&self) -> Option<Person> {
+        self.identity("GIT_COMMITTER")
+    }
+
+    fn identity(&self, env_prefix: &str) -> Option<Person> {
+        let config = self.inner.config().ok();
+        let get = |var: &str, key: &str| {
+            std::env::var(format!("{env_prefix}_{var}"))
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or_else(|| config.as_ref()?.get_string(key).ok())
+        };
+        Some(Person::new(
+            get("NAME", "user.name")?,
+            get("EMAIL", "user.email")?,
+        ))
+    }
+
+    /// The `user.name`/`user.email` set in this repository's config and in
+    /// the global config, labeled `repo` and `global`
+    ///
+    /// Levels without both a name and an email are left out, and so is a
+    /// global identity the repository repeats.
+    #[must_use]
+    pub fn config_identities(&self) -> Vec<(String, Person)> {
+        let Ok(config) = self.inner.config() else {
+            return Vec::new();
+        };
+        let at_level = |level: ConfigLevel| {
+            let config = config.open_level(level).ok()?;
+            Some(Person::new(
+                config.get_string("user.name").ok()?,
+                config.get_string("user.email").ok()?,
+            ))
+        };
+        let mut identities: Vec<(String, Person)> = Vec::new();
+        for (label, level) in [
+            ("repo", ConfigLevel::Local),
+            ("global", ConfigLevel::Global),
+            ("global", ConfigLevel::XDG),
+        ] {
+            if let Some(person) = at_level(level) {
+                if !identities.iter().any(|(_, p)| *p == person) {
+                    identities.push((label.to_string(), person));
+                }
+            }
+        }
+        identities
+    }
+
+    /// The repository's mailmap, as `git log --use-mailmap` reads it (the
+    /// working tree's `.mailmap`, `mailmap.file` and `mailmap.blob`), if it
+    /// has one
+    #[must_use]
+    pub fn mailmap(&self) -> Option<git2::Mailmap> {
+        let has_file = self
+            .inner
+            .workdir()
+            .is_some_and(|workdir| workdir.join(".mailmap").is_file());
+        let configured = self.inner.config().is_ok_and(|config| {
+            ["mailmap.file", "mailmap.blob"]
+                .iter()
+                .any(|key| config.get_string(key).is_ok())
+        });
+        (has_file || configured)
+            .then(|| self.inner.mailmap().ok())
+            .flatten()
+    }
+
     /// Get the inner git2 repository (for rewriting operations)
     #[must_use]
     pub fn inner(&self) -> &Git2Repository {
@@ -179,27 +762,105 @@ impl Repository {
 
     /// Create a backup reference before rewriting
     pub fn create_backup_ref(&self, branch_name: &str) -> Result<()> {
-        let head = self.inner.head()?;
-        let commit = head.peel_to_commit()?;
+        self.create_backup_ref_at(branch_name, self.tip_commit_id()?);
+        Ok(())
+    }
 
-        let backup_ref = format!("refs/original/heads/{branch_name}");
+    /// Create a backup reference for a branch at `tip`, unless one exists
+    pub fn create_backup_ref_at(&self, branch_name: &str, tip: CommitId) {
+        let backup_ref = format!("{ORIGINAL_PREFIX}{branch_name}");
+        // A reflog records when the backup was made
+        self.inner.reference_ensure_log(&backup_ref).ok();
         self.inner
             .reference(
                 &backup_ref,
-                commit.id(),
+                tip.0,
                 false, // Don't overwrite if exists
                 "retcon: backup before rewrite",
             )
             .ok(); // Ignore error if already exists
+    }
+
+    /// Create the next numbered backup of a branch at `tip`
+    ///
+    /// Returns the name of the new ref.
+    pub fn create_numbered_backup(&self, branch_name: &str, tip: CommitId) -> Result<String> {
+        let next = self
+            .backup_refs()?
+            .iter()
+            .filter(|b| b.branch == branch_name)
+            .filter_map(|b| b.number)
+            .max()
+            .map_or(1, |n| n + 1);
+        let name = format!("{NUMBERED_PREFIX}{branch_name}/{next}");
+        self.inner.reference_ensure_log(&name)?;
+        self.inner
+            .reference(&name, tip.0, false, "retcon: numbered backup")?;
+        Ok(name)
+    }
+
+    /// Backup refs of every branch, by branch and then oldest first
+    pub fn backup_refs(&self) -> Result<Vec<BackupRef>> {
+        let mut backups = Vec::new();
+        for reference in self.inner.references()? {
+            let reference = reference?;
+            let Some(name) = reference.name() else {
+                continue;
+            };
+            let (branch, number) = if let Some(branch) = name.strip_prefix(ORIGINAL_PREFIX) {
+                (branch.to_string(), None)
+            } else if let Some((branch, n)) = name
+                .strip_prefix(NUMBERED_PREFIX)
+                .and_then(|rest| rest.rsplit_once('/'))
+            {
+                let Ok(n) = n.parse() else {
+                    continue;
+                };
+                (branch.to_string(), Some(n))
+            } else {
+                continue;
+            };
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            let created = self
+                .inner
+                .reflog(name)
+                .ok()
+                .and_then(|log| log.iter().next_back().map(|entry| entry.committer().when()))
+                .and_then(|when| {
+                    FixedOffset::east_opt(when.offset_minutes() * 60)?
+                        .timestamp_opt(when.seconds(), 0)
+                        .single()
+                });
+            backups.push(BackupRef {
+                name: name.to_string(),
+                branch,
+                number,
+                tip: CommitId(commit.id()),
+                created,
+                summary: commit.summary().unwrap_or_default().to_string(),
+            });
+        }
+        backups.sort_by(|a, b| {
+            (&a.branch, a.number.is_some(), a.number).cmp(&(
+                &b.branch,
+                b.number.is_some(),
+                b.number,
+            ))
+        });
+        Ok(backups)
+    }
 
+    /// Delete a backup ref
+    pub fn delete_backup(&self, name: &str) -> Result<()> {
+        self.inner.find_reference(name)?.delete()?;
         Ok(())
     }
 
-    /// Get the HEAD commit ID
-    #[allow(dead_code)]
-    pub fn head_commit_id(&self) -> Result<CommitId> {
-        let head = self.inner.head()?;
-        let commit = head.peel_to_commit()?;
+    /// Get the ID of the branch tip (HEAD when the branch is checked out)
+    pub fn tip_commit_id(&self) -> Result<CommitId> {
+        let commit = self.branch_ref()?.peel_to_commit()?;
         Ok(CommitId(commit.id()))
     }
 
@@ -212,8 +873,11 @@ impl Repository {
             return Ok(false);
         }
 
-        // Get signature for stash
-        let signature = self.inner.signature()?;
+        // Get signature for stash (honoring GIT_COMMITTER_* like git does)
+        let signature = match self.committer_identity() {
+            Some(person) => git2::Signature::now(&person.name, &person.email)?,
+            None => self.inner.signature()?,
+        };
 
         // Create stash with a recognizable message
         self.inner.stash_save(
@@ -228,11 +892,240 @@ impl Repository {
     /// Restore previously stashed changes
     ///
     /// This pops the most recent stash entry. Should only be called after
-    /// `stash_changes` returned true.
+    /// `stash_changes` returned true. If the changes would conflict with HEAD,
+    /// nothing is touched, the stash is kept, and the conflicting paths are
+    /// returned as `StashConflict` (libgit2 would otherwise write conflict
+    /// markers and drop the stash).
     pub fn unstash_changes(&mut self) -> Result<()> {
+        let conflicts = self.stash_conflicts()?;
+        if !conflicts.is_empty() {
+            return Err(HistError::StashConflict(conflicts));
+        }
         self.inner.stash_pop(0, None)?;
         Ok(())
     }
+
+    /// Paths where popping the most recent stash onto HEAD would conflict
+    fn stash_conflicts(&self) -> Result<Vec<String>> {
+        let stash = self.inner.find_reference("refs/stash")?.peel_to_commit()?;
+        let base = stash.parent(0)?.tree()?;
+        let head = self.inner.head()?.peel_to_tree()?;
+
+        // Tracked changes: a three-way merge of the stash onto HEAD
+        let merged = self.inner.merge_trees(&base, &head, &stash.tree()?, None)?;
+        let mut paths: Vec<String> = merged
+            .conflicts()?
+            .filter_map(|conflict| {
+                let conflict = conflict.ok()?;
+                let entry = conflict.our.or(conflict.their).or(conflict.ancestor)?;
+                Some(String::from_utf8_lossy(&entry.path).into_owned())
+            })
+            .collect();
+
+        // Untracked files (the stash's third parent) can't replace tracked ones
+        if let Ok(untracked) = stash.parent(2) {
+            untracked
+                .tree()?
+                .walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+                    if entry.kind() == Some(git2::ObjectType::Blob) {
+                        let path = format!("{dir}{}", entry.name().unwrap_or_default());
+                        if head.get_path(Path::new(&path)).is_ok() {
+                            paths.push(path);
+                        }
+                    }
+                    git2::TreeWalkResult::Ok
+                })?;
+        }
+
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    /// Make the index and working tree match HEAD, e.g. after the branch moved
+    ///
+    /// Only call this with a clean working tree (after `stash_changes`).
+    pub fn checkout_head(&self) -> Result<()> {
+        self.inner
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+        Ok(())
+    }
+
+    /// Those of `commits` carrying a signature (a `gpgsig` header), in order
+    pub fn signed_commits(&self, commits: &[CommitId]) -> Vec<CommitId> {
+        commits
+            .iter()
+            .filter(|id| self.inner.extract_signature(&id.0, None).is_ok())
+            .copied()
+            .collect()
+    }
+
+    /// Tags naming any of `commits`, sorted by name
+    pub fn tags_on(&self, commits: &HashSet<CommitId>) -> Result<Vec<TagRef>> {
+        let mut tags = Vec::new();
+        for name in self.inner.tag_names(None)?.iter().flatten() {
+            let reference = self.inner.find_reference(&format!("refs/tags/{name}"))?;
+            let Some(target) = reference.target() else {
+                continue;
+            };
+            // Tags of trees or blobs have no commit to follow
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            if commits.contains(&CommitId(commit.id())) {
+                tags.push(TagRef {
+                    name: name.to_string(),
+                    target,
+                    commit: CommitId(commit.id()),
+                    annotated: reference.peel_to_tag().is_ok(),
+                });
+            }
+        }
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    /// Re-point tags at the rewritten counterparts of their commits
+    ///
+    /// Annotated tags are recreated with the same tagger and message. Tags of
+    /// deleted commits (missing from `commit_map`) are left alone. Returns how
+    /// many tags moved.
+    pub fn move_tags(
+        &self,
+        tags: &[TagRef],
+        commit_map: &std::collections::HashMap<CommitId, CommitId>,
+    ) -> Result<usize> {
+        let mut moved = 0;
+        for tag in tags {
+            let Some(new) = commit_map
+                .get(&tag.commit)
+                .filter(|new| **new != tag.commit)
+            else {
+                continue;
+            };
+            let commit = self
+                .inner
+                .find_object(new.0, Some(git2::ObjectType::Commit))?;
+            if tag.annotated {
+                let old = self.inner.find_tag(tag.target)?;
+                let tagger = match old.tagger() {
+                    Some(tagger) => tagger.to_owned(),
+                    None => self.inner.signature()?,
+                };
+                self.inner.tag(
+                    &tag.name,
+                    &commit,
+                    &tagger,
+                    old.message().unwrap_or(""),
+                    true,
+                )?;
+            } else {
+                self.inner.reference(
+                    &format!("refs/tags/{}", tag.name),
+                    new.0,
+                    true,
+                    "retcon: follow rewritten commit",
+                )?;
+            }
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    /// Point tags back at what they named before `move_tags`
+    pub fn restore_tags(&self, tags: &[TagRef]) -> Result<()> {
+        for tag in tags {
+            self.inner.reference(
+                &format!("refs/tags/{}", tag.name),
+                tag.target,
+                true,
+                "retcon: abort rewrite",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Point a branch back at `tip` and check it out, undoing a rewrite
+    pub fn restore_branch(&self, branch_name: &str, tip: CommitId) -> Result<()> {
+        self.move_branch(branch_name, tip)?;
+        self.checkout_head()
+    }
+
+    /// Point a branch back at `tip` without touching the working tree
+    pub fn move_branch(&self, branch_name: &str, tip: CommitId) -> Result<()> {
+        self.inner.reference(
+            &format!("refs/heads/{branch_name}"),
+            tip.0,
+            true,
+            "retcon: abort rewrite",
+        )?;
+        Ok(())
+    }
+}
+
+/// A saved copy of a branch tip under `refs/original/` or `refs/retcon/backups/`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupRef {
+    /// Full ref name
+    pub name: String,
+    /// Branch it is a backup of
+    pub branch: String,
+    /// Number of a numbered backup (None: the backup from before the first
+    /// rewrite, under `refs/original/`)
+    pub number: Option<u32>,
+    /// The commit it points at
+    pub tip: CommitId,
+    /// When it was made, from its reflog (None for backups without one)
+    pub created: Option<DateTime<FixedOffset>>,
+    /// Subject of the tip commit
+    pub summary: String,
+}
+
+/// A tag pointing at a commit, lightweight or annotated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRef {
+    /// Tag name, without `refs/tags/`
+    pub name: String,
+    /// What the tag ref points at: the commit, or the annotated tag object
+    pub target: git2::Oid,
+    /// The commit the tag names
+    pub commit: CommitId,
+    pub annotated: bool,
+}
+
+/// A file changed by a commit, for the detail pane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// `A`dded, `M`odified, `D`eleted or `T`ype changed
+    pub status: char,
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Binary files have no line counts
+    pub binary: bool,
+}
+
+/// A diff entry's status letter and path (the old path for deletions)
+fn delta_file(delta: &git2::DiffDelta<'_>) -> Option<(char, String)> {
+    let status = match delta.status() {
+        git2::Delta::Added => 'A',
+        git2::Delta::Deleted => 'D',
+        git2::Delta::Typechange => 'T',
+        _ => 'M',
+    };
+    let file = match delta.status() {
+        git2::Delta::Deleted => delta.old_file(),
+        _ => delta.new_file(),
+    };
+    file.path()
+        .and_then(|p| p.to_str())
+        .map(|p| (status, p.to_string()))
+}
+
+/// Whether `file` is `path` itself or lies below it
+fn path_matches(file: &str, path: &str) -> bool {
+    file.strip_prefix(path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
 }
 
 #[cfg(test)]
@@ -352,6 +1245,84 @@ mod tests {
         // New repo has no upstream
         let has_upstream = repo.has_upstream().unwrap();
         assert!(!has_upstream);
+        assert_eq!(repo.upstream_remote(), None);
+    }
+
+    #[test]
+    fn test_signed_commits() {
+        let test = crate::testing::TestRepo::signed(&["Signed"]).unwrap();
+        let signed = test.repo().load_commits(10).unwrap()[0].id;
+        let git = test.git();
+        let sig = git.signature().unwrap();
+        let tree = git.find_commit(signed.0).unwrap().tree().unwrap();
+        let unsigned = CommitId(
+            git.commit(None, &sig, &sig, "Unsigned", &tree, &[])
+                .unwrap(),
+        );
+        assert_eq!(
+            test.repo().signed_commits(&[signed, unsigned]),
+            vec![signed]
+        );
+    }
+
+    #[test]
+    fn test_file_changes() {
+        let (_temp, path) = create_test_repo();
+        fs::write(path.join("test2.txt"), "one\ntwo\n").unwrap();
+        let git = Git2Repository::open(&path).unwrap();
+        let mut index = git.index().unwrap();
+        index.add_path(Path::new("test2.txt")).unwrap();
+        index.remove_path(Path::new("test.txt")).unwrap();
+        let tree = git.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let parent = git.head().unwrap().peel_to_commit().unwrap();
+        let id = git
+            .commit(Some("HEAD"), &sig, &sig, "Third", &tree, &[&parent])
+            .unwrap();
+
+        let repo = Repository::open(&path).unwrap();
+        let files = repo.file_changes(CommitId(id)).unwrap();
+        let summary: Vec<_> = files
+            .iter()
+            .map(|f| (f.status, f.path.as_str(), f.insertions, f.deletions))
+            .collect();
+        assert_eq!(summary, [('D', "test.txt", 0, 1), ('M', "test2.txt", 2, 1)]);
+    }
+
+    #[test]
+    fn test_missing_paths() {
+        let test = crate::testing::TestRepo::linear(&["Add a", "Add b"]).unwrap();
+        let root = test.repo().load_commits(10).unwrap()[1].id;
+        let paths = vec!["0.txt".to_string(), "1.txt".to_string()];
+        assert_eq!(
+            test.repo().missing_paths(root, &paths).unwrap(),
+            vec!["1.txt"]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_pushed_count() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+        let commits = repo.load_commits(10).unwrap();
+        let all: HashSet<CommitId> = commits.iter().map(|c| c.id).collect();
+        assert_eq!(repo.pushed_count(&all).unwrap(), 0);
+
+        // origin/main has the older commit only
+        let git = repo.inner();
+        git.reference("refs/remotes/origin/main", commits[1].id.0, true, "test")
+            .unwrap();
+        let mut config = git.config().unwrap();
+        config.set_str("remote.origin.url", "/dev/null").unwrap();
+        config
+            .set_str("remote.origin.fetch", "+refs/heads/*:refs/remotes/origin/*")
+            .unwrap();
+        config.set_str("branch.main.remote", "origin").unwrap();
+        config
+            .set_str("branch.main.merge", "refs/heads/main")
+            .unwrap();
+        assert_eq!(repo.pushed_count(&all).unwrap(), 1);
     }
 
     #[test]
@@ -410,11 +1381,11 @@ mod tests {
 
     #[test]
     #[serial]
-    fn test_head_commit_id() {
+    fn test_tip_commit_id() {
         let (_temp_dir, repo_path) = create_test_repo();
         let repo = Repository::open(&repo_path).unwrap();
 
-        let head_id = repo.head_commit_id().unwrap();
+        let head_id = repo.tip_commit_id().unwrap();
         let commits = repo.load_commits(1).unwrap();
 
         assert_eq!(head_id, commits[0].id);
@@ -434,6 +1405,46 @@ mod tests {
         assert!(backup_ref.is_ok());
     }
 
+    #[test]
+    #[serial]
+    fn test_backup_refs() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+        let tip = repo.tip_commit_id().unwrap();
+        assert_eq!(repo.backup_refs().unwrap(), Vec::new());
+
+        repo.create_backup_ref("main").unwrap();
+        assert_eq!(
+            repo.create_numbered_backup("main", tip).unwrap(),
+            "refs/retcon/backups/main/1"
+        );
+        assert_eq!(
+            repo.create_numbered_backup("main", tip).unwrap(),
+            "refs/retcon/backups/main/2"
+        );
+
+        let backups = repo.backup_refs().unwrap();
+        let names: Vec<&str> = backups.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "refs/original/heads/main",
+                "refs/retcon/backups/main/1",
+                "refs/retcon/backups/main/2"
+            ]
+        );
+        assert!(backups.iter().all(|b| b.tip == tip && b.created.is_some()));
+        assert_eq!(backups[2].number, Some(2));
+
+        repo.delete_backup("refs/retcon/backups/main/1").unwrap();
+        assert_eq!(repo.backup_refs().unwrap().len(), 2);
+        // Numbering goes on from the highest one left
+        assert_eq!(
+            repo.create_numbered_backup("main", tip).unwrap(),
+            "refs/retcon/backups/main/3"
+        );
+    }
+
     #[test]
     #[serial]
     fn test_load_commits_range() {
@@ -444,10 +1455,113 @@ mod tests {
         let head_id = all_commits[0].id;
 
         // Load commits excluding the first one
-        let commits = repo.load_commits_range(Some(head_id), head_id, 10).unwrap();
+        let commits = repo
+            .load_commits_range(Some(head_id), head_id, 0, 10)
+            .unwrap();
         assert_eq!(commits.len(), 0); // Exclusive range, so no commits
     }
 
+    #[test]
+    #[serial]
+    fn test_set_branch() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let mut repo = Repository::open(&repo_path).unwrap();
+        let all = repo.load_commits(10).unwrap();
+        {
+            let older = repo.inner.find_commit(all[1].id.0).unwrap();
+            repo.inner.branch("feature", &older, false).unwrap();
+        }
+        assert_eq!(repo.branch_names().unwrap(), ["feature", "main"]);
+
+        repo.set_branch("feature").unwrap();
+        assert!(!repo.is_checked_out());
+        assert_eq!(repo.current_branch_name().unwrap(), "feature");
+        assert_eq!(repo.tip_commit_id().unwrap(), all[1].id);
+        assert_eq!(repo.load_commits(10).unwrap().len(), 1);
+        // HEAD's commit isn't on the branch
+        assert!(repo.set_start(&all[0].id.0.to_string()).is_err());
+
+        repo.set_branch("main").unwrap();
+        assert!(repo.is_checked_out());
+        assert!(repo.set_branch("nope").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_tags_on_and_move_tags() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+        let all = repo.load_commits(10).unwrap();
+        let sig = git2::Signature::now("Tagger", "tagger@example.com").unwrap();
+        {
+            let tip = repo.inner.find_object(all[0].id.0, None).unwrap();
+            let older = repo.inner.find_object(all[1].id.0, None).unwrap();
+            repo.inner
+                .tag("v2", &tip, &sig, "Release 2", false)
+                .unwrap();
+            repo.inner.tag_lightweight("v1", &older, false).unwrap();
+        }
+
+        let tags = repo.tags_on(&std::iter::once(all[0].id).collect()).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!((tags[0].name.as_str(), tags[0].annotated), ("v2", true));
+        let tags = repo.tags_on(&all.iter().map(|c| c.id).collect()).unwrap();
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["v1", "v2"]);
+
+        // Move both tags onto the tip's parent, then put them back
+        let map: std::collections::HashMap<CommitId, CommitId> =
+            [(all[0].id, all[1].id), (all[1].id, all[0].id)]
+                .into_iter()
+                .collect();
+        assert_eq!(repo.move_tags(&tags, &map).unwrap(), 2);
+        let v2 = repo
+            .inner
+            .find_reference("refs/tags/v2")
+            .unwrap()
+            .peel_to_tag()
+            .unwrap();
+        assert_eq!(v2.target_id(), all[1].id.0);
+        assert_eq!(v2.message(), Some("Release 2"));
+        assert_eq!(v2.tagger().unwrap().name(), Some("Tagger"));
+        let v1 = repo.inner.find_reference("refs/tags/v1").unwrap();
+        assert_eq!(v1.target(), Some(all[0].id.0));
+
+        repo.restore_tags(&tags).unwrap();
+        let restored = repo.tags_on(&all.iter().map(|c| c.id).collect()).unwrap();
+        assert_eq!(restored, tags);
+    }
+
+    #[test]
+    #[serial]
+    fn test_set_range() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let mut repo = Repository::open(&repo_path).unwrap();
+        let all = repo.load_commits(10).unwrap();
+
+        repo.set_range("HEAD~1..HEAD").unwrap();
+        let commits = repo.load_commits(10).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].id, all[0].id);
+
+        // An open end means HEAD
+        repo.set_range("HEAD~1..").unwrap();
+        assert_eq!(repo.load_commits(10).unwrap().len(), 1);
+
+        assert!(matches!(
+            repo.set_range("HEAD..HEAD~1"),
+            Err(HistError::InvalidRange(_))
+        ));
+        assert!(matches!(
+            repo.set_range("HEAD~1...HEAD"),
+            Err(HistError::InvalidRange(_))
+        ));
+        assert!(matches!(
+            repo.set_range("HEAD"),
+            Err(HistError::InvalidRange(_))
+        ));
+    }
+
     #[test]
     #[serial]
     fn test_commit_data_from_git2() {
@@ -532,4 +1646,226 @@ mod tests {
         let content = fs::read_to_string(&file_path).unwrap();
         assert_eq!(content, "modified content");
     }
+
+    #[test]
+    #[serial]
+    fn test_unstash_conflict_keeps_stash() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let git_repo = Git2Repository::open(&repo_path).unwrap();
+        let old_tip = git_repo.head().unwrap().peel_to_commit().unwrap();
+
+        // Third commit changes test.txt; the working tree changes it again
+        fs::write(repo_path.join("test.txt"), "v2\n").unwrap();
+        let mut index = git_repo.index().unwrap();
+        index.add_path(Path::new("test.txt")).unwrap();
+        index.write().unwrap();
+        let tree = git_repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let third = git_repo
+            .commit(Some("HEAD"), &sig, &sig, "Third", &tree, &[&old_tip])
+            .unwrap();
+        fs::write(repo_path.join("test.txt"), "v3\n").unwrap();
+
+        let mut repo = Repository::open(&repo_path).unwrap();
+        assert!(repo.stash_changes().unwrap());
+
+        // Drop the third commit, as deleting it in retcon would
+        repo.restore_branch("main", CommitId(old_tip.id())).unwrap();
+        let err = repo.unstash_changes().unwrap_err();
+        assert!(
+            matches!(&err, HistError::StashConflict(paths) if paths == &["test.txt"]),
+            "{err}"
+        );
+        // Nothing was applied and the stash is still there
+        assert!(!repo.has_uncommitted_changes().unwrap());
+        assert!(git_repo.find_reference("refs/stash").is_ok());
+
+        // Back on the original tip the stash applies cleanly
+        repo.restore_branch("main", CommitId(third)).unwrap();
+        repo.unstash_changes().unwrap();
+        assert_eq!(
+            fs::read_to_string(repo_path.join("test.txt")).unwrap(),
+            "v3\n"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_commits_page_lines_up() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+        let all = repo.load_commits(usize::MAX).unwrap();
+        let first = repo.load_commits_page(0, 1).unwrap();
+        let rest = repo.load_commits_page(1, usize::MAX).unwrap();
+
+        assert_eq!(first.len() + rest.len(), all.len());
+        assert_eq!(first[0].id, all[0].id);
+        assert_eq!(
+            rest.iter().map(|c| c.id).collect::<Vec<_>>(),
+            all[1..].iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_load_commits_batched() {
+        let test = crate::testing::TestRepo::linear(&["a", "b", "c", "d", "e"]).unwrap();
+        let repo = test.repo();
+        let all = repo.load_commits(usize::MAX).unwrap();
+
+        let mut batches = Vec::new();
+        repo.load_commits_batched(1, usize::MAX, 2, |batch| {
+            batches.push(batch.iter().map(|c| c.id).collect::<Vec<_>>());
+            true
+        })
+        .unwrap();
+        assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), [2, 2]);
+        assert_eq!(
+            batches.concat(),
+            all[1..].iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+
+        // Stops once told to
+        let mut calls = 0;
+        repo.load_commits_batched(0, usize::MAX, 1, |_| {
+            calls += 1;
+            false
+        })
+        .unwrap();
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_commits_touching_path_follows_renames() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+
+        // Third commit: rename test.txt -> moved.txt without changing it
+        {
+            let git = repo.inner();
+            let mut index = git.index().unwrap();
+            fs::rename(repo_path.join("test.txt"), repo_path.join("moved.txt")).unwrap();
+            index.remove_path(Path::new("test.txt")).unwrap();
+            index.add_path(Path::new("moved.txt")).unwrap();
+            index.write().unwrap();
+            let tree = git.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+            let parent = git.head().unwrap().peel_to_commit().unwrap();
+            git.commit(Some("HEAD"), &sig, &sig, "Move", &tree, &[&parent])
+                .unwrap();
+        }
+
+        let commits = repo.load_commits(10).unwrap();
+        let ids: Vec<_> = commits.iter().map(|c| c.id).collect();
+
+        let plain = repo
+            .commits_touching_path(&commits, "moved.txt", false)
+            .unwrap();
+        assert_eq!(plain, HashSet::from([ids[0]]));
+
+        // Following the rename also finds the commit that created test.txt
+        let followed = repo
+            .commits_touching_path(&commits, "moved.txt", true)
+            .unwrap();
+        assert_eq!(followed, HashSet::from([ids[0], ids[2]]));
+
+        // Directory prefixes match whole path components only
+        assert!(path_matches("src/lib.rs", "src"));
+        assert!(!path_matches("src2/lib.rs", "src"));
+    }
+
+    #[test]
+    fn test_history_position() {
+        let test = crate::testing::TestRepo::linear(&["a", "b", "c"]).unwrap();
+        let commits = test.repo().load_commits(10).unwrap();
+        let other = test.commit_on("other", "x.txt", "unrelated root").unwrap();
+        let position = |hash: &str| test.repo().history_position(hash);
+
+        assert_eq!(position(&commits[0].id.to_string()).unwrap(), Some(0));
+        assert_eq!(position(&commits[2].short_hash).unwrap(), Some(2));
+        assert_eq!(position(&other.to_string()).unwrap(), None);
+        assert!(matches!(
+            position("0000000"),
+            Err(HistError::CommitNotFound(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_first_parent_skips_merged_branch() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let mut repo = Repository::open(&repo_path).unwrap();
+
+        // Side commit off the initial commit, merged back into main
+        {
+            let git = repo.inner();
+            let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+            let head = git.head().unwrap().peel_to_commit().unwrap();
+            let root = head.parent(0).unwrap();
+            let side = git
+                .commit(None, &sig, &sig, "Side", &root.tree().unwrap(), &[&root])
+                .unwrap();
+            let side = git.find_commit(side).unwrap();
+            git.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "Merge side",
+                &head.tree().unwrap(),
+                &[&head, &side],
+            )
+            .unwrap();
+        }
+
+        assert_eq!(repo.load_commits(10).unwrap().len(), 4);
+
+        repo.set_first_parent(true);
+        let summaries: Vec<_> = repo
+            .load_commits(10)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.summary)
+            .collect();
+        assert_eq!(summaries, ["Merge side", "Second commit", "Initial commit"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_identity_env_overrides() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+        assert_eq!(
+            repo.author_identity(),
+            Some(Person::new("Test User", "test@example.com"))
+        );
+
+        std::env::set_var("GIT_AUTHOR_NAME", "Env Author");
+        std::env::set_var("GIT_COMMITTER_EMAIL", "bot@example.com");
+        let author = repo.author_identity();
+        let committer = repo.committer_identity();
+        std::env::remove_var("GIT_AUTHOR_NAME");
+        std::env::remove_var("GIT_COMMITTER_EMAIL");
+
+        assert_eq!(author, Some(Person::new("Env Author", "test@example.com")));
+        assert_eq!(committer, Some(Person::new("Test User", "bot@example.com")));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_identities() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let repo = Repository::open(&repo_path).unwrap();
+        let identities = repo.config_identities();
+        assert_eq!(
+            identities.first(),
+            Some(&(
+                "repo".to_string(),
+                Person::new("Test User", "test@example.com")
+            ))
+        );
+        assert!(identities
+            .iter()
+            .skip(1)
+            .all(|(label, _)| label == "global"));
+    }
 }
@@ -1,37 +1,361 @@
 #![allow(clippy::missing_errors_doc, clippy::implicit_hasher)]
 
 use crate::error::{HistError, Result};
-use crate::git::commit::{CommitData, CommitId, CommitModifications};
+use crate::git::commit::{
+    hash_references, CommitData, CommitId, CommitModifications, CommitSplit, NewRoot, Person,
+    SquashKind,
+};
+use crate::git::signing::Signer;
 use chrono::{DateTime, FixedOffset};
-use git2::{Repository as Git2Repository, Signature, Time};
+use git2::{FileMode, Repository as Git2Repository, Signature, Time};
 use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Everything a history rewrite needs to know
+pub struct RewritePlan<'a> {
+    /// Loaded commits in display order (newest first)
+    pub commits: &'a [CommitData],
+    /// Commits between the newest loaded commit and the branch tip (newest
+    /// first), replayed unchanged on top of the rewritten commits; empty when
+    /// loading started at HEAD
+    pub descendants: &'a [CommitData],
+    /// Pending metadata changes per commit
+    pub modifications: &'a HashMap<CommitId, CommitModifications>,
+    /// Commits to delete
+    pub deleted: &'a HashSet<CommitId>,
+    /// Commits to fold into the commit just below them in `new_order`
+    pub squashes: &'a HashMap<CommitId, SquashKind>,
+    /// Commits to split in two
+    pub splits: &'a HashMap<CommitId, CommitSplit>,
+    /// Order of the loaded commits as they are in the repository (newest first)
+    pub original_order: &'a [CommitId],
+    /// New order of the loaded commits (newest first)
+    pub new_order: &'a [CommitId],
+    /// Name of the branch to update
+    pub branch_name: &'a str,
+    /// Other local branches built on the rewritten commits, moved onto their
+    /// new counterparts
+    pub branches: &'a [DependentBranch],
+    /// Replace hashes of rewritten commits mentioned in later messages (e.g.
+    /// "This reverts commit ...") with their new hashes
+    pub update_hash_references: bool,
+    /// Sign every new commit with this
+    pub signer: Option<&'a Signer>,
+    /// A commit to put beneath the oldest loaded commit, which must be the root
+    pub new_root: Option<&'a NewRoot>,
+}
+
+/// Writes new commit objects, signing them when there's a signer
+struct CommitWriter<'r> {
+    repo: &'r Git2Repository,
+    signer: Option<&'r Signer>,
+}
+
+impl CommitWriter<'_> {
+    fn write(
+        &self,
+        author: &Signature<'_>,
+        committer: &Signature<'_>,
+        message: &str,
+        tree: &git2::Tree<'_>,
+        parents: &[&git2::Commit<'_>],
+    ) -> Result<git2::Oid> {
+        let buffer = self
+            .repo
+            .commit_create_buffer(author, committer, message, tree, parents)?;
+        let content = buffer
+            .as_str()
+            .ok_or_else(|| HistError::RewriteFailed("commit is not valid UTF-8".to_string()))?;
+        self.write_content(content)
+    }
+
+    /// Write a commit object formatted ahead of time
+    fn write_content(&self, content: &str) -> Result<git2::Oid> {
+        let Some(signer) = self.signer else {
+            return Ok(self
+                .repo
+                .odb()?
+                .write(git2::ObjectType::Commit, content.as_bytes())?);
+        };
+        let signature = signer.sign(content)?;
+        Ok(self.repo.commit_signed(content, &signature, None)?)
+    }
+}
 
-/// Rewrite git history with the specified modifications and deletions
+/// How far a running rewrite has got, shared with whoever asked for it
+///
+/// The rewrite counts the commits it writes and stops with
+/// [`HistError::Cancelled`] before the next one once cancelled. Past the
+/// last commit it can no longer be cancelled: the branch is about to move.
+#[derive(Debug, Default)]
+pub struct RewriteProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl RewriteProgress {
+    /// Commits written so far and the number to write (0 until known)
+    #[must_use]
+    pub fn counts(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Ask the rewrite to stop before its next commit
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Count one more commit about to be written, unless cancelled
+    fn step(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(HistError::Cancelled);
+        }
+        self.done.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Another local branch whose history includes commits being rewritten
+#[derive(Debug, Clone)]
+pub struct DependentBranch {
+    pub name: String,
+    /// Its tip before the rewrite
+    pub tip: CommitId,
+    /// Its commits that aren't part of the rewritten history (newest first),
+    /// replayed unchanged onto the rewritten commits; empty when the tip is
+    /// itself a rewritten commit
+    pub commits: Vec<CommitData>,
+}
+
+/// Rewrite git history according to a plan
 ///
 /// This function rewrites commits from oldest to newest, creating new commits
 /// with the modified metadata while preserving the tree (file contents).
+/// Commits older than the first change (not edited, on unchanged parents)
+/// keep their IDs; they aren't written again, nor signed.
 /// Deleted commits are skipped and their children are reparented to the
 /// deleted commit's parent(s).
 ///
-/// # Arguments
-/// * `repo` - The git repository
-/// * `commits` - List of commits in display order (newest first)
-/// * `modifications` - Map of commit ID to modifications
-/// * `deleted` - Set of commit IDs to delete
-/// * `new_order` - New order of commits (for reordering support)
-/// * `branch_name` - Name of the branch to update
+/// When commits were reordered or squashes are staged, the loaded commits are
+/// rebuilt as a linear chain in the new order instead: each commit whose parent
+/// changed is cherry-picked onto its new parent, and a squashed commit's changes
+/// are folded into the commit below it. This needs a linear (merge-free)
+/// segment and fails if a cherry-pick conflicts.
+///
+/// A split commit becomes two: the first holds the changes to the split's
+/// paths (on top of the commit's new parent), the second the rest of the
+/// changes with the split's message. The commit maps to the second one.
+///
+/// Each dependent branch is moved too: its own commits are replayed onto the
+/// rewritten commits they were based on (keeping their trees, like the commits
+/// above the loaded segment), or the branch simply follows its tip when that
+/// was rewritten. A branch pointing at a deleted commit moves to its parent.
+///
+/// With a signer, every new commit is signed (rewriting drops the old signatures).
+///
+/// With a new root, the commits that would have no parents are put on top of
+/// it; everything keeps its tree.
+///
+/// The new commits are prepared (trees looked up, author and committer lines
+/// formatted) before anything is written, on worker threads for large
+/// rewrites; only writing them, in order, is left for once their parents exist.
+///
+/// The rewrite is all or nothing: new objects are staged in memory, the new
+/// tip is checked to reach every kept commit, and only then are they written
+/// (as one pack) and the branch and dependent branches moved together. A
+/// failure leaves the repository as it was; the error names the commit that
+/// failed, and a branch that moved meanwhile stops the rewrite.
+///
+/// With `update_hash_references`, hashes in a commit's message that name an
+/// already rewritten (older) commit are replaced by the new hash, keeping the
+/// same abbreviation length. Newer commits can't be updated this way, since
+/// their new hashes depend on the message being written.
 ///
 /// # Returns
-/// * `Ok(())` on success
+/// * `Ok(map)` on success, mapping each rewritten commit's old ID to its new ID
+///   (deleted commits are not included; squashed commits map to the commit they
+///   were folded into)
 /// * `Err(HistError)` on failure
 pub fn rewrite_history(
     repo: &Git2Repository,
-    commits: &[CommitData],
-    modifications: &HashMap<CommitId, CommitModifications>,
-    deleted: &HashSet<CommitId>,
-    new_order: &[CommitId],
-    branch_name: &str,
-) -> Result<()> {
+    plan: &RewritePlan<'_>,
+) -> Result<HashMap<CommitId, CommitId>> {
+    rewrite(repo, plan, true, &RewriteProgress::default())
+}
+
+/// [`rewrite_history`], reporting progress and stopping when cancelled
+///
+/// A cancelled rewrite fails with [`HistError::Cancelled`] and, like any
+/// other failure, leaves the repository as it was.
+pub fn rewrite_history_with_progress(
+    repo: &Git2Repository,
+    plan: &RewritePlan<'_>,
+    progress: &RewriteProgress,
+) -> Result<HashMap<CommitId, CommitId>> {
+    rewrite(repo, plan, true, progress)
+}
+
+/// Work out the commits a rewrite would create without moving any branch
+///
+/// The new commits are only staged in memory; nothing is written to the
+/// repository. The map of old to new IDs is returned as by
+/// [`rewrite_history`]. Applying the same plan later creates the same
+/// commits, unless it signs them.
+pub fn preview_rewrite(
+    repo: &Git2Repository,
+    plan: &RewritePlan<'_>,
+) -> Result<HashMap<CommitId, CommitId>> {
+    rewrite(repo, plan, false, &RewriteProgress::default())
+}
+
+/// Priority of the in-memory object store new objects are staged in (above
+/// the on-disk backends, so every write lands there)
+const STAGING_PRIORITY: i32 = 1000;
+
+fn rewrite(
+    repo: &Git2Repository,
+    plan: &RewritePlan<'_>,
+    update_refs: bool,
+    progress: &RewriteProgress,
+) -> Result<HashMap<CommitId, CommitId>> {
+    // The branch must still be where it was when the rewrite started
+    let ref_name = format!("refs/heads/{}", plan.branch_name);
+    let old_tip = if update_refs {
+        Some(repo.refname_to_id(&ref_name)?)
+    } else {
+        None
+    };
+
+    // New objects are staged in memory, on a handle of their own so the
+    // caller's keeps writing to disk; nothing reaches the repository unless
+    // the whole rewrite succeeds
+    let staging = Git2Repository::open(repo.path())?;
+    let odb = staging.odb()?;
+    let mempack = odb.add_new_mempack_backend(STAGING_PRIORITY)?;
+
+    let mut failed_at = None;
+    let written =
+        write_commits(&staging, plan, progress, &mut failed_at, None).map_err(
+            |e| match failed_at {
+                Some(commit) if !matches!(e, HistError::Cancelled) => HistError::RewriteFailed(
+                    format!("failed at {} {}: {e}", commit.short_hash, commit.summary),
+                ),
+                _ => e,
+            },
+        )?;
+    verify_chain(&staging, plan, &written)?;
+    if progress.is_cancelled() {
+        return Err(HistError::Cancelled);
+    }
+
+    if let Some(old_tip) = old_tip {
+        // Lock every ref first, so a branch that moved stops the rewrite
+        // before anything is written
+        let mut transaction = repo.transaction()?;
+        transaction.lock_ref(&ref_name)?;
+        for (name, _) in &written.branch_tips {
+            transaction.lock_ref(name)?;
+        }
+        if repo.refname_to_id(&ref_name)? != old_tip {
+            return Err(HistError::RewriteFailed(format!(
+                "{} moved while it was being rewritten",
+                plan.branch_name
+            )));
+        }
+
+        let mut pack = git2::Buf::new();
+        mempack.dump(&staging, &mut pack)?;
+        let odb = repo.odb()?;
+        let mut writer = odb.packwriter()?;
+        writer.write_all(&pack)?;
+        writer.commit()?;
+
+        let rewritten = plan
+            .commits
+            .iter()
+            .chain(plan.descendants)
+            .filter(|c| !plan.deleted.contains(&c.id))
+            .filter(|c| {
+                written
+                    .commit_map
+                    .get(&c.id.0)
+                    .is_some_and(|new| *new != c.id.0)
+            })
+            .count();
+        transaction.set_target(
+            &ref_name,
+            written.head,
+            None,
+            &reflog_message(plan, rewritten),
+        )?;
+        for (name, tip) in &written.branch_tips {
+            transaction.set_target(
+                name,
+                *tip,
+                None,
+                &format!("retcon: follow rewrite of {}", plan.branch_name),
+            )?;
+        }
+        transaction.commit()?;
+    }
+
+    Ok(written
+        .commit_map
+        .into_iter()
+        .map(|(old, new)| (CommitId(old), CommitId(new)))
+        .collect())
+}
+
+/// The commits a rewrite wrote, before any ref points at them
+struct Written {
+    /// Old commit ID to new (deleted commits to where their children went)
+    commit_map: HashMap<git2::Oid, git2::Oid>,
+    /// New tip of the rewritten branch
+    head: git2::Oid,
+    /// New tips of the dependent branches, by full ref name
+    branch_tips: Vec<(String, git2::Oid)>,
+}
+
+/// Write the new commits, keeping `failed_at` on the commit being written
+///
+/// They are prepared on `workers` threads first (by default, as many as the
+/// number of commits warrants).
+fn write_commits<'a>(
+    repo: &Git2Repository,
+    plan: &RewritePlan<'a>,
+    progress: &RewriteProgress,
+    failed_at: &mut Option<&'a CommitData>,
+    workers: Option<usize>,
+) -> Result<Written> {
+    let RewritePlan {
+        commits,
+        descendants,
+        modifications,
+        deleted,
+        squashes,
+        splits,
+        original_order,
+        new_order,
+        branch_name: _,
+        branches,
+        update_hash_references,
+        signer,
+        new_root,
+    } = *plan;
+    let writer = CommitWriter { repo, signer };
+
     // Build a lookup map for commits by ID
     let commit_lookup: HashMap<CommitId, &CommitData> = commits.iter().map(|c| (c.id, c)).collect();
 
@@ -50,6 +374,78 @@ pub fn rewrite_history(
         }
     }
 
+    // Prepare every commit to write up front
+    let to_write: Vec<(&CommitData, Option<&CommitModifications>)> = commits
+        .iter()
+        .filter(|c| !deleted.contains(&c.id))
+        .map(|c| (c, modifications.get(&c.id)))
+        .chain(
+            descendants
+                .iter()
+                .chain(branches.iter().flat_map(|b| &b.commits))
+                .map(|c| (c, None)),
+        )
+        .collect();
+    progress.total.store(to_write.len(), Ordering::Relaxed);
+    let workers = workers.unwrap_or_else(|| worker_count(to_write.len()));
+    let prepared = prepare_commits(repo, &to_write, workers)?;
+    let prepared_of = |original: &CommitData| {
+        prepared
+            .get(&original.id.0)
+            .ok_or_else(|| HistError::CommitNotFound(original.id.to_string()))
+    };
+
+    // A commit that wasn't edited and still sits on the same parents keeps
+    // its ID (and signature): everything below the first change stays as it is
+    let unchanged = |original: &CommitData,
+                     parents: &[git2::Oid],
+                     commit_map: &HashMap<git2::Oid, git2::Oid>| {
+        modifications
+            .get(&original.id)
+            .is_none_or(CommitModifications::is_empty)
+            && !splits.contains_key(&original.id)
+            && original
+                .parent_ids
+                .iter()
+                .map(|p| p.0)
+                .eq(parents.iter().copied())
+            && (!update_hash_references
+                || rewrite_hash_references(&original.message, commit_map) == original.message)
+    };
+
+    let chain =
+        order_changed(original_order, new_order) || squashes.keys().any(|id| !deleted.contains(id));
+    if chain {
+        if let Some(merge) = commits
+            .iter()
+            .find(|c| c.is_merge && !deleted.contains(&c.id))
+        {
+            return Err(HistError::RewriteFailed(format!(
+                "reordering and squashing need a linear history, but {} is a merge commit",
+                merge.short_hash
+            )));
+        }
+    }
+
+    // In chain mode, the oldest commit sits on the parent(s) of the original
+    // oldest commit (deleted commits keep their parents, so this is the base)
+    let mut chain_parents: Vec<git2::Oid> = original_order
+        .last()
+        .and_then(|id| commit_lookup.get(id))
+        .map(|c| c.parent_ids.iter().map(|p| p.0).collect())
+        .unwrap_or_default();
+    let root_oid = new_root
+        .map(|root| {
+            let old_root = original_order.last().and_then(|id| commit_lookup.get(id));
+            write_new_root(&writer, root, old_root.copied())
+        })
+        .transpose()?;
+    if chain_parents.is_empty() {
+        chain_parents.extend(root_oid);
+    }
+    // Old commits folded into the most recent new commit (chain mode)
+    let mut chain_members: Vec<git2::Oid> = Vec::new();
+
     // Process commits from oldest to newest (reverse of display order)
     for commit_id in new_order.iter().rev() {
         // Skip deleted commits
@@ -60,102 +456,599 @@ pub fn rewrite_history(
         let original = commit_lookup
             .get(commit_id)
             .ok_or_else(|| HistError::CommitNotFound(commit_id.to_string()))?;
+        *failed_at = Some(*original);
+        progress.step()?;
 
-        let mods = modifications.get(commit_id);
+        let prepared = prepared_of(original)?;
+        let mut expected = translate_parents(original, &commit_map, &deleted_parent_map, None);
+        if expected.is_empty() {
+            expected.extend(root_oid);
+        }
+        if !chain {
+            if unchanged(original, &expected, &commit_map) {
+                commit_map.insert(original.id.0, original.id.0);
+                continue;
+            }
+            let refs = update_hash_references.then_some(&commit_map);
+            let new_oid = match splits.get(commit_id) {
+                Some(split) => {
+                    split_commit(&writer, prepared, &expected, original.tree_id, split, refs)?
+                }
+                None => recreate_commit(&writer, prepared, &expected, original.tree_id, refs)?,
+            };
+            // Record the mapping
+            commit_map.insert(original.id.0, new_oid);
+            continue;
+        }
 
-        // Get parent commits, translating through commit_map if they were rewritten
-        // If a parent was deleted, use its parents instead (reparenting)
-        let parent_oids: Vec<git2::Oid> = original
-            .parent_ids
-            .iter()
-            .flat_map(|p| {
-                // If the parent was deleted, use its parents
-                if let Some(grandparents) = deleted_parent_map.get(&p.0) {
-                    grandparents
-                        .iter()
-                        .map(|gp| *commit_map.get(gp).unwrap_or(gp))
-                        .collect()
-                } else {
-                    vec![*commit_map.get(&p.0).unwrap_or(&p.0)]
+        if let Some(&kind) = squashes.get(commit_id) {
+            // Fold into the commit just created below this one
+            let target_oid = *chain_members
+                .last()
+                .and_then(|old| commit_map.get(old))
+                .ok_or_else(|| {
+                    HistError::RewriteFailed(format!(
+                        "{} has no commit below it to squash into",
+                        original.short_hash
+                    ))
+                })?;
+            let target = repo.find_commit(target_oid)?;
+            let tree = pick_tree(repo, original, Some(&target))?;
+            let message = match kind {
+                SquashKind::Fixup => target.message().unwrap_or("").to_string(),
+                SquashKind::Squash => {
+                    squash_message(target.message().unwrap_or(""), &prepared.spec.message)
                 }
-            })
-            .collect();
+            };
+            let message = if update_hash_references {
+                rewrite_hash_references(&message, &commit_map)
+            } else {
+                message
+            };
+            let parents: Vec<git2::Commit<'_>> = target.parents().collect();
+            let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+            let folded = writer.write(
+                &target.author(),
+                &target.committer(),
+                &message,
+                &repo.find_tree(tree)?,
+                &parent_refs,
+            )?;
+
+            chain_members.push(original.id.0);
+            for old in &chain_members {
+                commit_map.insert(*old, folded);
+            }
+            chain_parents = vec![folded];
+            continue;
+        }
 
-        let parents: Vec<git2::Commit<'_>> = parent_oids
-            .iter()
+        if unchanged(original, &chain_parents, &commit_map) {
+            commit_map.insert(original.id.0, original.id.0);
+            chain_members = vec![original.id.0];
+            chain_parents = vec![original.id.0];
+            continue;
+        }
+
+        // Keep the original tree when the commit's new parent has the same
+        // content as its original parent (a moved parent's content changes)
+        let parent = chain_parents
+            .first()
             .map(|oid| repo.find_commit(*oid))
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-
-        let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
-
-        // Build author signature
-        let new_author_name = mods
-            .and_then(|m| m.author_name.as_deref())
-            .unwrap_or(&original.author.name);
-        let new_author_email = mods
-            .and_then(|m| m.author_email.as_deref())
-            .unwrap_or(&original.author.email);
-
-        let author = build_signature(
-            new_author_name,
-            new_author_email,
-            mods.and_then(|m| m.author_date)
+            .transpose()?;
+        let original_parent_tree = original
+            .parent_ids
+            .first()
+            .map(|p| repo.find_commit(p.0).map(|c| c.tree_id()))
+            .transpose()?;
+        let tree = if parent.as_ref().map(git2::Commit::tree_id) == original_parent_tree {
+            original.tree_id
+        } else {
+            pick_tree(repo, original, parent.as_ref())?
+        };
+        let refs = update_hash_references.then_some(&commit_map);
+        let new_oid = match splits.get(commit_id) {
+            Some(split) => split_commit(&writer, prepared, &chain_parents, tree, split, refs)?,
+            None => recreate_commit(&writer, prepared, &chain_parents, tree, refs)?,
+        };
+        commit_map.insert(original.id.0, new_oid);
+        chain_members = vec![original.id.0];
+        chain_parents = vec![new_oid];
+    }
+
+    *failed_at = None;
+
+    // Find the first non-deleted commit in new_order: the tip of the rewritten segment
+    let newest_commit_id = new_order
+        .iter()
+        .find(|id| !deleted.contains(id))
+        .ok_or_else(|| HistError::RewriteFailed("All commits would be deleted".to_string()))?;
+
+    let segment_tip = *commit_map
+        .get(&newest_commit_id.0)
+        .ok_or_else(|| HistError::RewriteFailed("Failed to find new HEAD commit".to_string()))?;
+
+    // Replay the commits above the loaded segment unchanged on top of it
+    let tip_remap = original_order.first().map(|id| (id.0, segment_tip));
+    let mut new_head_oid = segment_tip;
+    for original in descendants.iter().rev() {
+        *failed_at = Some(original);
+        progress.step()?;
+        let parent_oids = translate_parents(original, &commit_map, &deleted_parent_map, tip_remap);
+        new_head_oid = if unchanged(original, &parent_oids, &commit_map) {
+            original.id.0
+        } else {
+            let refs = update_hash_references.then_some(&commit_map);
+            recreate_commit(
+                &writer,
+                prepared_of(original)?,
+                &parent_oids,
+                original.tree_id,
+                refs,
+            )?
+        };
+        commit_map.insert(original.id.0, new_head_oid);
+    }
+
+    // Move the other branches built on the rewritten commits
+    let mut branch_tips = Vec::new();
+    for branch in branches {
+        for original in branch.commits.iter().rev() {
+            *failed_at = Some(original);
+            progress.step()?;
+            let parent_oids =
+                translate_parents(original, &commit_map, &deleted_parent_map, tip_remap);
+            let new_oid = if unchanged(original, &parent_oids, &commit_map) {
+                original.id.0
+            } else {
+                let refs = update_hash_references.then_some(&commit_map);
+                recreate_commit(
+                    &writer,
+                    prepared_of(original)?,
+                    &parent_oids,
+                    original.tree_id,
+                    refs,
+                )?
+            };
+            commit_map.insert(original.id.0, new_oid);
+        }
+        let new_tip = translate_commit(branch.tip.0, &commit_map, &deleted_parent_map, tip_remap);
+        branch_tips.push((format!("refs/heads/{}", branch.name), new_tip));
+    }
+    *failed_at = None;
+
+    Ok(Written {
+        commit_map,
+        head: new_head_oid,
+        branch_tips,
+    })
+}
+
+/// Check that the new branch tip reaches every commit kept by the rewrite
+fn verify_chain(repo: &Git2Repository, plan: &RewritePlan<'_>, written: &Written) -> Result<()> {
+    let mut walk = repo.revwalk()?;
+    walk.push(written.head)?;
+    // The loaded segment sits on the parents of its oldest commit
+    if let Some(oldest) = plan
+        .commits
+        .iter()
+        .find(|c| Some(&c.id) == plan.original_order.last())
+    {
+        for parent in &oldest.parent_ids {
+            walk.hide(parent.0)?;
+        }
+    }
+    let reachable: HashSet<git2::Oid> = walk.collect::<std::result::Result<_, _>>()?;
+    match plan
+        .commits
+        .iter()
+        .chain(plan.descendants)
+        .filter(|c| !plan.deleted.contains(&c.id))
+        .find(|c| {
+            written
+                .commit_map
+                .get(&c.id.0)
+                .is_none_or(|new| !reachable.contains(new))
+        }) {
+        Some(lost) => Err(HistError::RewriteFailed(format!(
+            "{} {} would not be on the rewritten branch",
+            lost.short_hash, lost.summary
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Apply a commit's changes on top of another commit (or onto nothing, for a
+/// new root), returning the resulting tree
+fn pick_tree(
+    repo: &Git2Repository,
+    original: &CommitData,
+    onto: Option<&git2::Commit<'_>>,
+) -> Result<git2::Oid> {
+    let empty_tree = || -> Result<git2::Tree<'_>> {
+        let oid = repo.treebuilder(None)?.write()?;
+        Ok(repo.find_tree(oid)?)
+    };
+    let commit = repo.find_commit(original.id.0)?;
+    let ancestor = match commit.parents().next() {
+        Some(parent) => parent.tree()?,
+        None => empty_tree()?,
+    };
+    let ours = match onto {
+        Some(onto) => onto.tree()?,
+        None => empty_tree()?,
+    };
+
+    let mut index = repo.merge_trees(&ancestor, &ours, &commit.tree()?, None)?;
+    if index.has_conflicts() {
+        return Err(HistError::RewriteFailed(format!(
+            "moving {} conflicts with the commits it now follows",
+            original.short_hash
+        )));
+    }
+    Ok(index.write_tree_to(repo)?)
+}
+
+/// Combine a squash target's message with a `squash!` commit's message
+///
+/// The `squash!` subject line is dropped; its body (if any) is appended.
+fn squash_message(target: &str, squashed: &str) -> String {
+    let body = squashed
+        .split_once('\n')
+        .map_or("", |(_, body)| body)
+        .trim();
+    if body.is_empty() {
+        target.to_string()
+    } else {
+        format!("{}\n\n{body}\n", target.trim_end())
+    }
+}
+
+/// Get a commit's parents, translated through the commits rewritten so far
+///
+/// A deleted parent is replaced by its own parents (reparenting), repeatedly
+/// for a run of deleted commits. `tip_remap` sends one old parent to a
+/// specific new commit, regardless of `commit_map`.
+fn translate_parents(
+    original: &CommitData,
+    commit_map: &HashMap<git2::Oid, git2::Oid>,
+    deleted_parent_map: &HashMap<git2::Oid, Vec<git2::Oid>>,
+    tip_remap: Option<(git2::Oid, git2::Oid)>,
+) -> Vec<git2::Oid> {
+    fn translate(
+        oid: git2::Oid,
+        commit_map: &HashMap<git2::Oid, git2::Oid>,
+        deleted_parent_map: &HashMap<git2::Oid, Vec<git2::Oid>>,
+    ) -> Vec<git2::Oid> {
+        // If the parent was deleted, use its parents
+        match deleted_parent_map.get(&oid) {
+            Some(grandparents) => grandparents
+                .iter()
+                .flat_map(|gp| translate(*gp, commit_map, deleted_parent_map))
+                .collect(),
+            None => vec![*commit_map.get(&oid).unwrap_or(&oid)],
+        }
+    }
+
+    original
+        .parent_ids
+        .iter()
+        .flat_map(|p| match tip_remap {
+            Some((old, new)) if p.0 == old => vec![new],
+            _ => translate(p.0, commit_map, deleted_parent_map),
+        })
+        .collect()
+}
+
+/// Translate one commit through the commits rewritten so far, like a parent in
+/// `translate_parents` (a deleted commit becomes its first parent's counterpart)
+fn translate_commit(
+    oid: git2::Oid,
+    commit_map: &HashMap<git2::Oid, git2::Oid>,
+    deleted_parent_map: &HashMap<git2::Oid, Vec<git2::Oid>>,
+    tip_remap: Option<(git2::Oid, git2::Oid)>,
+) -> git2::Oid {
+    if let Some((old, new)) = tip_remap {
+        if oid == old {
+            return new;
+        }
+    }
+    match deleted_parent_map
+        .get(&oid)
+        .and_then(|parents| parents.first())
+    {
+        Some(parent) => translate_commit(*parent, commit_map, deleted_parent_map, None),
+        None => *commit_map.get(&oid).unwrap_or(&oid),
+    }
+}
+
+/// Replace hashes of commits in `commit_map` mentioned in a message with their
+/// new hashes, keeping each reference's length
+///
+/// Ambiguous abbreviations (matching several rewritten commits) are left alone.
+#[must_use]
+pub fn rewrite_hash_references(
+    message: &str,
+    commit_map: &HashMap<git2::Oid, git2::Oid>,
+) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut last = 0;
+    for (start, word) in hash_references(message) {
+        let word_lower = word.to_lowercase();
+        let mut matches = commit_map
+            .iter()
+            .filter(|(old, new)| old != new && old.to_string().starts_with(&word_lower));
+        let (Some((_, new)), None) = (matches.next(), matches.next()) else {
+            continue;
+        };
+        result.push_str(&message[last..start]);
+        result.push_str(&new.to_string()[..word.len()]);
+        last = start + word.len();
+    }
+    result.push_str(&message[last..]);
+    result
+}
+
+/// A rewritten commit's metadata: its original's, with the pending
+/// modifications applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CommitSpec {
+    author: Person,
+    author_date: DateTime<FixedOffset>,
+    committer: Person,
+    committer_date: DateTime<FixedOffset>,
+    message: String,
+}
+
+impl CommitSpec {
+    fn resolve(original: &CommitData, mods: Option<&CommitModifications>) -> Self {
+        let field = |value: Option<&String>, original: &str| {
+            value.map_or_else(|| original.to_string(), Clone::clone)
+        };
+        Self {
+            author: Person::new(
+                field(
+                    mods.and_then(|m| m.author_name.as_ref()),
+                    &original.author.name,
+                ),
+                field(
+                    mods.and_then(|m| m.author_email.as_ref()),
+                    &original.author.email,
+                ),
+            ),
+            author_date: mods
+                .and_then(|m| m.author_date)
                 .unwrap_or(original.author_date),
-        )?;
+            committer: Person::new(
+                field(
+                    mods.and_then(|m| m.committer_name.as_ref()),
+                    &original.committer.name,
+                ),
+                field(
+                    mods.and_then(|m| m.committer_email.as_ref()),
+                    &original.committer.email,
+                ),
+            ),
+            committer_date: mods
+                .and_then(|m| m.committer_date)
+                .unwrap_or(original.committer_date),
+            message: field(mods.and_then(|m| m.message.as_ref()), &original.message),
+        }
+    }
+}
+
+/// A commit the rewrite may write: its spec, with the author and committer
+/// header lines already formatted
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PreparedCommit {
+    spec: CommitSpec,
+    /// The `author` and `committer` lines of the commit object, as libgit2
+    /// writes them
+    identities: String,
+}
 
-        // Build committer signature
+impl PreparedCommit {
+    fn prepare(
+        repo: &Git2Repository,
+        original: &CommitData,
+        mods: Option<&CommitModifications>,
+    ) -> Result<Self> {
+        let spec = CommitSpec::resolve(original, mods);
+        let author = build_signature(&spec.author.name, &spec.author.email, spec.author_date)?;
         let committer = build_signature(
-            mods.and_then(|m| m.committer_name.as_deref())
-                .unwrap_or(&original.committer.name),
-            mods.and_then(|m| m.committer_email.as_deref())
-                .unwrap_or(&original.committer.email),
-            mods.and_then(|m| m.committer_date)
-                .unwrap_or(original.committer_date),
+            &spec.committer.name,
+            &spec.committer.email,
+            spec.committer_date,
         )?;
+        let tree = repo.find_tree(original.tree_id)?;
+        // Everything between the tree line and the message
+        let buffer = repo.commit_create_buffer(&author, &committer, "", &tree, &[])?;
+        let identities = buffer
+            .as_str()
+            .and_then(|content| content.split_once('\n'))
+            .and_then(|(_, rest)| rest.strip_suffix('\n'))
+            .ok_or_else(|| HistError::RewriteFailed("commit is not valid UTF-8".to_string()))?
+            .to_string();
+        Ok(Self { spec, identities })
+    }
+
+    /// The same commit with another message
+    fn with_message(&self, message: &str) -> Self {
+        Self {
+            spec: CommitSpec {
+                message: message.to_string(),
+                ..self.spec.clone()
+            },
+            identities: self.identities.clone(),
+        }
+    }
+}
 
-        // Get the message
-        let message = mods
-            .and_then(|m| m.message.as_deref())
-            .unwrap_or(&original.message);
+/// One worker per this many commits (up to the available cores); below it,
+/// threads cost more than they save
+const COMMITS_PER_WORKER: usize = 500;
 
-        // Get the original tree (file contents unchanged)
-        let tree = repo.find_tree(original.tree_id)?;
+/// How many threads should prepare `commits` commits
+fn worker_count(commits: usize) -> usize {
+    let cores = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    (commits / COMMITS_PER_WORKER).clamp(1, cores)
+}
 
-        // Create the new commit
-        let new_oid = repo.commit(
-            None, // Don't update any ref yet
-            &author,
-            &committer,
-            message,
-            &tree,
-            &parent_refs,
-        )?;
+/// Prepare every commit the rewrite may write, on `workers` threads
+///
+/// Each worker looks up the trees and formats the signatures of its share of
+/// the commits on a repository handle of its own (a `git2::Repository` can't
+/// be shared); the objects are still written one at a time, in order,
+/// afterwards, once their parents are known. A missing tree fails the rewrite
+/// before anything is written.
+fn prepare_commits(
+    repo: &Git2Repository,
+    commits: &[(&CommitData, Option<&CommitModifications>)],
+    workers: usize,
+) -> Result<HashMap<git2::Oid, PreparedCommit>> {
+    fn prepare_chunk(
+        repo: &Git2Repository,
+        chunk: &[(&CommitData, Option<&CommitModifications>)],
+    ) -> Result<Vec<(git2::Oid, PreparedCommit)>> {
+        chunk
+            .iter()
+            .map(|(original, mods)| {
+                let prepared = PreparedCommit::prepare(repo, original, *mods).map_err(|e| {
+                    HistError::RewriteFailed(format!(
+                        "failed at {} {}: {e}",
+                        original.short_hash, original.summary
+                    ))
+                })?;
+                Ok((original.id.0, prepared))
+            })
+            .collect()
+    }
 
-        // Record the mapping
-        commit_map.insert(original.id.0, new_oid);
+    if workers <= 1 || commits.len() < 2 {
+        return Ok(prepare_chunk(repo, commits)?.into_iter().collect());
     }
+    let path = repo.path();
+    let chunk_size = commits.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = commits
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || prepare_chunk(&Git2Repository::open(path)?, chunk)))
+            .collect();
+        let mut prepared = HashMap::with_capacity(commits.len());
+        for handle in handles {
+            let chunk = handle.join().map_err(|_| {
+                HistError::RewriteFailed("a rewrite worker thread panicked".to_string())
+            })??;
+            prepared.extend(chunk);
+        }
+        Ok(prepared)
+    })
+}
 
-    // Update the branch reference to point to the new HEAD
-    // Find the first non-deleted commit in new_order
-    let newest_commit_id = new_order
-        .iter()
-        .find(|id| !deleted.contains(id))
-        .ok_or_else(|| HistError::RewriteFailed("All commits would be deleted".to_string()))?;
+/// Write a prepared commit with the given parents and tree
+///
+/// With `refs`, hashes in the message are updated through that old-to-new map.
+fn recreate_commit(
+    writer: &CommitWriter<'_>,
+    prepared: &PreparedCommit,
+    parent_oids: &[git2::Oid],
+    tree_id: git2::Oid,
+    refs: Option<&HashMap<git2::Oid, git2::Oid>>,
+) -> Result<git2::Oid> {
+    let message = &prepared.spec.message;
+    let message = refs.map_or_else(
+        || message.clone(),
+        |map| rewrite_hash_references(message, map),
+    );
+    let mut content = format!("tree {tree_id}\n");
+    for parent in parent_oids {
+        let _ = writeln!(content, "parent {parent}");
+    }
+    content.push_str(&prepared.identities);
+    content.push('\n');
+    content.push_str(&message);
 
-    let new_head_oid = commit_map
-        .get(&newest_commit_id.0)
-        .ok_or_else(|| HistError::RewriteFailed("Failed to find new HEAD commit".to_string()))?;
+    // Create the new commit (no ref is updated yet)
+    writer.write_content(&content)
+}
 
-    // Update the branch reference
-    let ref_name = format!("refs/heads/{branch_name}");
-    repo.reference(
-        &ref_name,
-        *new_head_oid,
-        true, // Force update
-        "retcon: rewrite history",
-    )?;
+/// Create `original` as two commits, returning the second (newer) one
+///
+/// The first commit is the first parent's tree with the split's paths taken
+/// from `tree_id`; the second has `tree_id` itself and the split's message.
+fn split_commit(
+    writer: &CommitWriter<'_>,
+    prepared: &PreparedCommit,
+    parent_oids: &[git2::Oid],
+    tree_id: git2::Oid,
+    split: &CommitSplit,
+    refs: Option<&HashMap<git2::Oid, git2::Oid>>,
+) -> Result<git2::Oid> {
+    let repo = writer.repo;
+    let base = match parent_oids.first() {
+        Some(oid) => repo.find_commit(*oid)?.tree()?,
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+    };
+    let tree = repo.find_tree(tree_id)?;
+    let mut update = git2::build::TreeUpdateBuilder::new();
+    for path in &split.paths {
+        match tree.get_path(Path::new(path)) {
+            Ok(entry) => {
+                update.upsert(path.as_str(), entry.id(), entry_mode(&entry));
+            }
+            // Deleted by the commit (and not already gone from the new parent)
+            Err(_) if base.get_path(Path::new(path)).is_ok() => {
+                update.remove(path.as_str());
+            }
+            Err(_) => {}
+        }
+    }
+    let first_tree = update.create_updated(repo, &base)?;
+    let first = recreate_commit(writer, prepared, parent_oids, first_tree, refs)?;
+    let second = prepared.with_message(&split.message);
+    recreate_commit(writer, &second, &[first], tree_id, refs)
+}
 
-    Ok(())
+/// Write a new root commit holding `root`'s paths from the old root's tree
+fn write_new_root(
+    writer: &CommitWriter<'_>,
+    root: &NewRoot,
+    old_root: Option<&CommitData>,
+) -> Result<git2::Oid> {
+    let old_root = old_root
+        .filter(|c| c.parent_ids.is_empty())
+        .ok_or_else(|| {
+            HistError::RewriteFailed(
+                "a new root commit needs the root commit loaded (:limit all)".to_string(),
+            )
+        })?;
+    let repo = writer.repo;
+    let tree = repo.find_tree(old_root.tree_id)?;
+    let mut update = git2::build::TreeUpdateBuilder::new();
+    for path in &root.paths {
+        let entry = tree.get_path(Path::new(path)).map_err(|_| {
+            HistError::RewriteFailed(format!(
+                "{path} is not in the root commit {}",
+                old_root.short_hash
+            ))
+        })?;
+        update.upsert(path.as_str(), entry.id(), entry_mode(&entry));
+    }
+    let empty = repo.find_tree(repo.treebuilder(None)?.write()?)?;
+    let tree = repo.find_tree(update.create_updated(repo, &empty)?)?;
+    let signature = build_signature(&root.author.name, &root.author.email, root.date)?;
+    writer.write(&signature, &signature, &root.message, &tree, &[])
+}
+
+/// The mode to give a copy of a tree entry
+fn entry_mode(entry: &git2::TreeEntry<'_>) -> FileMode {
+    match entry.filemode() {
+        0o040_000 => FileMode::Tree,
+        0o100_755 => FileMode::BlobExecutable,
+        0o120_000 => FileMode::Link,
+        0o160_000 => FileMode::Commit,
+        _ => FileMode::Blob,
+    }
 }
 
 /// Build a git2 Signature from name, email, and datetime
@@ -195,6 +1088,41 @@ pub fn order_changed(original_order: &[CommitId], new_order: &[CommitId]) -> boo
         .any(|(a, b)| a != b)
 }
 
+/// Reflog message for the branch update, e.g.
+/// `retcon: rewrite 12 commits (3 modified, 1 deleted)`
+fn reflog_message(plan: &RewritePlan<'_>, rewritten: usize) -> String {
+    let modified = plan
+        .modifications
+        .iter()
+        .filter(|(id, m)| m.has_modifications() && !plan.deleted.contains(id))
+        .count();
+    let mut parts = Vec::new();
+    if modified > 0 {
+        parts.push(format!("{modified} modified"));
+    }
+    if !plan.deleted.is_empty() {
+        parts.push(format!("{} deleted", plan.deleted.len()));
+    }
+    if order_changed(plan.original_order, plan.new_order) {
+        parts.push("reordered".to_string());
+    }
+    if !plan.squashes.is_empty() {
+        parts.push(format!("{} folded", plan.squashes.len()));
+    }
+    if !plan.splits.is_empty() {
+        parts.push(format!("{} split", plan.splits.len()));
+    }
+    if plan.new_root.is_some() {
+        parts.push("new root".to_string());
+    }
+    let noun = if rewritten == 1 { "commit" } else { "commits" };
+    let mut message = format!("retcon: rewrite {rewritten} {noun}");
+    if !parts.is_empty() {
+        let _ = write!(message, " ({})", parts.join(", "));
+    }
+    message
+}
+
 /// Count total number of modified commits
 #[must_use]
 pub fn count_modified_commits(modifications: &HashMap<CommitId, CommitModifications>) -> usize {
@@ -277,6 +1205,277 @@ pub fn generate_change_summary(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_prepare_commits() {
+        let test = crate::testing::TestRepo::linear(&["a", "b", "c", "d", "e"]).unwrap();
+        let mut commits = test.repo().load_commits(10).unwrap();
+        let mods = CommitModifications {
+            author_name: Some("Jane".to_string()),
+            message: Some("Edited\n".to_string()),
+            ..Default::default()
+        };
+        let to_write: Vec<_> = commits
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c, (i == 1).then_some(&mods)))
+            .collect();
+
+        // Threads prepare the same commits as a single pass
+        let prepared = prepare_commits(test.git(), &to_write, 1).unwrap();
+        assert_eq!(prepare_commits(test.git(), &to_write, 3).unwrap(), prepared);
+        let edited = &prepared[&commits[1].id.0];
+        assert_eq!(edited.spec.author.name, "Jane");
+        assert_eq!(edited.spec.author.email, commits[1].author.email);
+        assert_eq!(edited.spec.message, "Edited\n");
+        assert!(edited.identities.starts_with("author Jane <"));
+        assert_eq!(edited.identities.lines().count(), 2);
+        assert_eq!(prepared[&commits[0].id.0].spec.message, commits[0].message);
+
+        // A missing tree fails up front
+        commits[2].tree_id =
+            git2::Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap();
+        let to_write: Vec<_> = commits.iter().map(|c| (c, None)).collect();
+        assert!(prepare_commits(test.git(), &to_write, 2).is_err());
+    }
+
+    #[test]
+    fn test_parallel_rewrite_matches_serial() {
+        let messages: Vec<String> = (0..120).map(|i| format!("Commit {i}")).collect();
+        let messages: Vec<&str> = messages.iter().map(String::as_str).collect();
+        let test = crate::testing::TestRepo::linear(&messages).unwrap();
+        let commits = test.repo().load_commits(200).unwrap();
+        let ids: Vec<CommitId> = commits.iter().map(|c| c.id).collect();
+        // Editing the oldest commit rewrites every one of them
+        let oldest = commits.last().unwrap();
+        let mut modifications = HashMap::new();
+        modifications.insert(
+            oldest.id,
+            CommitModifications {
+                author_name: Some("Jane".to_string()),
+                ..Default::default()
+            },
+        );
+        let branch_name = test.branch_name().unwrap();
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &ids,
+            new_order: &ids,
+            branch_name: &branch_name,
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+        let progress = RewriteProgress::default();
+        let serial = write_commits(test.git(), &plan, &progress, &mut None, Some(1)).unwrap();
+        let parallel = write_commits(test.git(), &plan, &progress, &mut None, Some(4)).unwrap();
+        assert_eq!(parallel.commit_map, serial.commit_map);
+        assert_eq!(parallel.head, serial.head);
+
+        // The same commits libgit2 creates itself
+        let repo = test.git();
+        let mut parent: Option<git2::Commit<'_>> = None;
+        for original in commits.iter().rev() {
+            let old = repo.find_commit(original.id.0).unwrap();
+            let author = if original.id == oldest.id {
+                let when = old.author().when();
+                Signature::new("Jane", old.author().email().unwrap(), &when).unwrap()
+            } else {
+                old.author()
+            };
+            let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+            let oid = repo
+                .commit(
+                    None,
+                    &author,
+                    &old.committer(),
+                    old.message().unwrap(),
+                    &old.tree().unwrap(),
+                    &parents,
+                )
+                .unwrap();
+            assert_eq!(serial.commit_map[&original.id.0], oid);
+            parent = Some(repo.find_commit(oid).unwrap());
+        }
+        assert_eq!(parent.unwrap().id(), serial.head);
+    }
+
+    #[test]
+    fn test_failed_rewrite_leaves_repository_alone() {
+        let test = crate::testing::TestRepo::new().unwrap();
+        for content in ["a", "b", "c"] {
+            test.commit("x.txt", content).unwrap();
+        }
+        let commits = test.repo().load_commits(10).unwrap();
+        let original_order: Vec<_> = commits.iter().map(|c| c.id).collect();
+        // "c" moved onto "a" conflicts
+        let new_order = [original_order[1], original_order[0], original_order[2]];
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &HashMap::new(),
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &original_order,
+            new_order: &new_order,
+            branch_name: &test.branch_name().unwrap(),
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+        let count_objects = || {
+            let mut count = 0;
+            test.git()
+                .odb()
+                .unwrap()
+                .foreach(|_| {
+                    count += 1;
+                    true
+                })
+                .unwrap();
+            count
+        };
+        let (objects, tip) = (count_objects(), test.repo().tip_commit_id().unwrap());
+
+        let err = rewrite_history(test.git(), &plan).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&format!("failed at {} c", commits[0].short_hash)));
+        assert_eq!(test.repo().tip_commit_id().unwrap(), tip);
+        assert_eq!(count_objects(), objects);
+    }
+
+    #[test]
+    fn test_rewrite_progress() {
+        let test = crate::testing::TestRepo::linear(&["a", "b", "c"]).unwrap();
+        let commits = test.repo().load_commits(10).unwrap();
+        let order: Vec<_> = commits.iter().map(|c| c.id).collect();
+        let modifications = HashMap::from([(
+            order[1],
+            CommitModifications {
+                author_name: Some("Jane".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &order,
+            new_order: &order,
+            branch_name: &test.branch_name().unwrap(),
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+        let tip = test.repo().tip_commit_id().unwrap();
+
+        // Cancelled, nothing moves
+        let progress = RewriteProgress::default();
+        progress.cancel();
+        let err = rewrite_history_with_progress(test.git(), &plan, &progress).unwrap_err();
+        assert!(matches!(err, HistError::Cancelled));
+        assert_eq!(progress.counts(), (0, 3));
+        assert_eq!(test.repo().tip_commit_id().unwrap(), tip);
+
+        let progress = RewriteProgress::default();
+        rewrite_history_with_progress(test.git(), &plan, &progress).unwrap();
+        assert_eq!(progress.counts(), (3, 3));
+        assert_ne!(test.repo().tip_commit_id().unwrap(), tip);
+    }
+
+    #[test]
+    fn test_commits_below_first_change_keep_ids() {
+        let test = crate::testing::TestRepo::signed(&["a", "b", "c"]).unwrap();
+        let commits = test.repo().load_commits(10).unwrap();
+        let order: Vec<_> = commits.iter().map(|c| c.id).collect();
+        let modifications = HashMap::from([(
+            order[0],
+            CommitModifications {
+                message: Some("c, reworded".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &order,
+            new_order: &order,
+            branch_name: &test.branch_name().unwrap(),
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+
+        let commit_map = rewrite_history(test.git(), &plan).unwrap();
+        assert_ne!(commit_map[&order[0]], order[0]);
+        assert_eq!(commit_map[&order[1]], order[1]);
+        assert_eq!(commit_map[&order[2]], order[2]);
+        // Kept commits keep their signatures too
+        let signed = crate::testing::signed_commits(test.git(), &commit_map);
+        assert!(!signed[&commit_map[&order[0]]]);
+        assert!(signed[&order[1]] && signed[&order[2]]);
+    }
+
+    #[test]
+    fn test_reflog_message() {
+        let id = |n: u8| CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap());
+        let modifications = HashMap::from([
+            (
+                id(1),
+                CommitModifications {
+                    author_name: Some("Jane".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (id(2), CommitModifications::default()),
+        ]);
+        let deleted = HashSet::from([id(3)]);
+        let (original_order, new_order) = ([id(1), id(2), id(3)], [id(2), id(1), id(3)]);
+        let plan = RewritePlan {
+            commits: &[],
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &deleted,
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &original_order,
+            new_order: &new_order,
+            branch_name: "main",
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+        assert_eq!(
+            reflog_message(&plan, 12),
+            "retcon: rewrite 12 commits (1 modified, 1 deleted, reordered)"
+        );
+        let plan = RewritePlan {
+            modifications: &HashMap::new(),
+            deleted: &HashSet::new(),
+            new_order: &original_order,
+            ..plan
+        };
+        assert_eq!(reflog_message(&plan, 1), "retcon: rewrite 1 commit");
+    }
+
     #[test]
     fn test_has_modifications_empty() {
         let mods: HashMap<CommitId, CommitModifications> = HashMap::new();
@@ -294,6 +1493,24 @@ mod tests {
         assert!(order_changed(&[id1], &[id1, id2]));
     }
 
+    #[test]
+    fn test_rewrite_hash_references() {
+        use git2::Oid;
+        let old = Oid::from_str("1111111111111111111111111111111111111111").unwrap();
+        let new = Oid::from_str("abcdefabcdefabcdefabcdefabcdefabcdefabcd").unwrap();
+        let map = HashMap::from([(old, new)]);
+
+        assert_eq!(
+            rewrite_hash_references(
+                "This reverts commit 1111111111111111111111111111111111111111.\n(see 1111111)",
+                &map
+            ),
+            "This reverts commit abcdefabcdefabcdefabcdefabcdefabcdefabcd.\n(see abcdefa)"
+        );
+        // Unknown hashes are kept
+        assert_eq!(rewrite_hash_references("Fix 2222222", &map), "Fix 2222222");
+    }
+
     #[test]
     fn test_count_modified_commits() {
         let mut mods: HashMap<CommitId, CommitModifications> = HashMap::new();
@@ -339,7 +1556,7 @@ mod tests {
         let order2 = vec![];
 
         let summary = generate_change_summary(&commits, &mods, &deleted, &order1, &order2);
-        assert!(summary.is_empty());
+        assert_eq!(summary, Vec::<String>::new());
     }
 
     #[test]
@@ -0,0 +1,235 @@
+//! Signing rewritten commits with the user's GPG or SSH key
+//!
+//! Rewriting a commit drops its signature. A [`Signer`] reads the settings
+//! `git commit -S` uses and runs the same programs to sign the new commits.
+
+use crate::error::{HistError, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Kind of signature, from `gpg.format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignFormat {
+    OpenPgp,
+    Ssh,
+    X509,
+}
+
+impl SignFormat {
+    /// Name as written in `gpg.format`
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::OpenPgp => "openpgp",
+            Self::Ssh => "ssh",
+            Self::X509 => "x509",
+        }
+    }
+}
+
+/// Signs commit objects the way git would
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    pub format: SignFormat,
+    /// `user.signingkey`: a key ID for GPG, a key file (or `key::` literal) for SSH
+    pub key: Option<String>,
+    /// Program run to sign (`gpg.program`, `gpg.ssh.program`, `gpg.x509.program`)
+    pub program: String,
+}
+
+impl Signer {
+    /// Read the signing setup from git config
+    ///
+    /// Returns None when signing isn't set up: no `user.signingkey`, and
+    /// `commit.gpgsign` off.
+    ///
+    /// # Errors
+    /// Returns an error for an unknown `gpg.format`, or SSH signing without a key.
+    pub fn from_config(config: &git2::Config) -> Result<Option<Self>> {
+        let key = config.get_string("user.signingkey").ok();
+        let gpgsign = config.get_bool("commit.gpgsign").unwrap_or(false);
+        if key.is_none() && !gpgsign {
+            return Ok(None);
+        }
+
+        let format = match config.get_string("gpg.format").ok().as_deref() {
+            None | Some("openpgp") => SignFormat::OpenPgp,
+            Some("ssh") => SignFormat::Ssh,
+            Some("x509") => SignFormat::X509,
+            Some(other) => {
+                return Err(HistError::Signing(format!("unknown gpg.format '{other}'")));
+            }
+        };
+        if format == SignFormat::Ssh && key.is_none() {
+            return Err(HistError::Signing(
+                "gpg.format is ssh but user.signingkey is not set".to_string(),
+            ));
+        }
+        let program = match format {
+            SignFormat::OpenPgp => config
+                .get_string("gpg.openpgp.program")
+                .or_else(|_| config.get_string("gpg.program"))
+                .unwrap_or_else(|_| "gpg".to_string()),
+            SignFormat::Ssh => config
+                .get_string("gpg.ssh.program")
+                .unwrap_or_else(|_| "ssh-keygen".to_string()),
+            SignFormat::X509 => config
+                .get_string("gpg.x509.program")
+                .unwrap_or_else(|_| "gpgsm".to_string()),
+        };
+        Ok(Some(Self {
+            format,
+            key,
+            program,
+        }))
+    }
+
+    /// Short description for the apply dialog, e.g. `ssh key ~/.ssh/id_ed25519.pub`
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match &self.key {
+            Some(key) if literal_ssh_key(key).is_some() => {
+                format!("{} key (literal)", self.format.name())
+            }
+            Some(key) => format!("{} key {key}", self.format.name()),
+            None => format!("default {} key", self.format.name()),
+        }
+    }
+
+    /// Sign a commit object's content, returning the armored signature
+    ///
+    /// # Errors
+    /// Returns an error if the signing program can't be run or fails.
+    pub fn sign(&self, content: &str) -> Result<String> {
+        let key = self.key.as_deref().unwrap_or_default();
+        // A literal SSH key is handed to ssh-keygen through a file
+        let literal_key = match literal_ssh_key(key) {
+            Some(literal) if self.format == SignFormat::Ssh => {
+                let mut file = tempfile::NamedTempFile::new()?;
+                writeln!(file, "{literal}")?;
+                Some(file)
+            }
+            _ => None,
+        };
+
+        let mut command = Command::new(&self.program);
+        match self.format {
+            SignFormat::Ssh => {
+                let path = literal_key
+                    .as_ref()
+                    .map_or_else(|| expand_home(key), |file| file.path().to_path_buf());
+                command.args(["-Y", "sign", "-n", "git", "-f"]).arg(path);
+            }
+            SignFormat::OpenPgp | SignFormat::X509 => {
+                command.arg("--status-fd=2");
+                match &self.key {
+                    Some(key) => command.arg("-bsau").arg(key),
+                    None => command.arg("-bsa"),
+                };
+            }
+        }
+
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| HistError::Signing(format!("{}: {e}", self.program)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        let signature = String::from_utf8_lossy(&output.stdout).into_owned();
+        if !output.status.success() || signature.trim().is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr
+                .lines()
+                .rfind(|line| !line.starts_with("[GNUPG:]"))
+                .map_or_else(|| output.status.to_string(), str::to_string);
+            return Err(HistError::Signing(format!("{}: {reason}", self.program)));
+        }
+        Ok(signature)
+    }
+}
+
+/// The key itself, when `user.signingkey` holds a public key rather than a path
+fn literal_ssh_key(key: &str) -> Option<&str> {
+    key.strip_prefix("key::")
+        .or_else(|| key.starts_with("ssh-").then_some(key))
+}
+
+/// Expand a leading `~/` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn config(entries: &[(&str, &str)]) -> (tempfile::TempDir, git2::Config) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = git2::Config::open(&dir.path().join("config")).unwrap();
+        for (name, value) in entries {
+            config.set_str(name, value).unwrap();
+        }
+        (dir, config)
+    }
+
+    #[test]
+    fn test_from_config() {
+        let (_dir, empty) = config(&[]);
+        assert_eq!(Signer::from_config(&empty).unwrap(), None);
+
+        let (_dir, gpg) = config(&[("user.signingkey", "ABCD1234")]);
+        let signer = Signer::from_config(&gpg).unwrap().unwrap();
+        assert_eq!(signer.format, SignFormat::OpenPgp);
+        assert_eq!(signer.program, "gpg");
+        assert_eq!(signer.describe(), "openpgp key ABCD1234");
+
+        let (_dir, ssh) = config(&[
+            ("user.signingkey", "~/.ssh/id.pub"),
+            ("gpg.format", "ssh"),
+            ("gpg.ssh.program", "/opt/ssh-keygen"),
+        ]);
+        let signer = Signer::from_config(&ssh).unwrap().unwrap();
+        assert_eq!(signer.format, SignFormat::Ssh);
+        assert_eq!(signer.program, "/opt/ssh-keygen");
+
+        let (_dir, keyless) = config(&[("gpg.format", "ssh"), ("commit.gpgsign", "true")]);
+        assert!(Signer::from_config(&keyless).is_err());
+        let (_dir, unknown) = config(&[("user.signingkey", "k"), ("gpg.format", "pgp")]);
+        assert!(Signer::from_config(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_sign_runs_program() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("fake-gpg");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n[ \"$2\" = -bsau ] || exit 2\nprintf 'SIG %s %s\\n' \"$3\" \"$(wc -c)\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+
+        let signer = Signer {
+            format: SignFormat::OpenPgp,
+            key: Some("ABCD".to_string()),
+            program: script.display().to_string(),
+        };
+        assert_eq!(signer.sign("tree 1234\n").unwrap().trim(), "SIG ABCD 10");
+
+        let failing = Signer {
+            program: "false".to_string(),
+            ..signer
+        };
+        assert!(matches!(failing.sign("x"), Err(HistError::Signing(_))));
+    }
+}
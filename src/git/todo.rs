@@ -0,0 +1,257 @@
+use crate::error::{HistError, Result};
+use crate::git::commit::{CommitData, CommitId, CommitModifications, SquashKind};
+use crate::git::rewrite::RewritePlan;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Write a rewrite plan as a `git rebase -i` todo list, oldest commit first
+///
+/// Reorders become the line order, deletions `drop`, squashes `squash` and
+/// `fixup`. Metadata edits become an `exec git commit --amend` line after
+/// the commit (and anything folded into it), setting the author, committer,
+/// dates and message through git's own options and environment. Newer
+/// commits above the loaded range are picked unchanged at the end.
+///
+/// # Errors
+/// Returns an error for what a todo list can't express the same way: merge
+/// commits, splits and new root commits.
+pub fn rebase_todo(plan: &RewritePlan<'_>) -> Result<String> {
+    if !plan.splits.is_empty() || plan.new_root.is_some() {
+        return Err(HistError::InvalidCommand(
+            "splits and new root commits can't be exported as a rebase todo".to_string(),
+        ));
+    }
+    if plan.commits.iter().any(|c| c.is_merge) {
+        return Err(HistError::InvalidCommand(
+            "merge commits can't be exported as a rebase todo".to_string(),
+        ));
+    }
+    let by_id: HashMap<CommitId, &CommitData> = plan.commits.iter().map(|c| (c.id, c)).collect();
+    let commits: Vec<&CommitData> = plan
+        .new_order
+        .iter()
+        .rev()
+        .filter_map(|id| by_id.get(id).copied())
+        .collect();
+    let base = plan
+        .original_order
+        .last()
+        .and_then(|id| by_id.get(id))
+        .and_then(|oldest| oldest.parent_ids.first())
+        .map_or_else(|| "--root".to_string(), |id| id.0.to_string());
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Rebase todo for {} exported by retcon. To apply it:",
+        plan.branch_name
+    );
+    let _ = writeln!(
+        out,
+        "#   GIT_SEQUENCE_EDITOR=\"cp /path/to/this/file\" git rebase -i {base}"
+    );
+    out.push('\n');
+
+    // The commit the following squash and fixup lines fold into
+    let mut group_head: Option<&CommitData> = None;
+    for commit in &commits {
+        let action = if plan.deleted.contains(&commit.id) {
+            "drop"
+        } else {
+            match plan.squashes.get(&commit.id) {
+                Some(SquashKind::Fixup) => "fixup",
+                Some(SquashKind::Squash) => "squash",
+                None => "pick",
+            }
+        };
+        if matches!(action, "pick" | "drop") {
+            push_amend(&mut out, group_head.take(), plan.modifications);
+        }
+        let _ = writeln!(out, "{action} {} {}", commit.short_hash, commit.summary);
+        match action {
+            "pick" => group_head = Some(commit),
+            "drop" => {}
+            _ if plan
+                .modifications
+                .get(&commit.id)
+                .is_some_and(CommitModifications::has_modifications) =>
+            {
+                let _ = writeln!(
+                    out,
+                    "# retcon: the edits to {} are lost in the squash",
+                    commit.short_hash
+                );
+            }
+            _ => {}
+        }
+    }
+    push_amend(&mut out, group_head, plan.modifications);
+
+    if !plan.descendants.is_empty() {
+        out.push_str("\n# Newer commits, replayed unchanged\n");
+        for commit in plan.descendants.iter().rev() {
+            let _ = writeln!(out, "pick {} {}", commit.short_hash, commit.summary);
+        }
+    }
+    Ok(out)
+}
+
+/// Add the `exec` line that gives a picked commit its pending metadata
+fn push_amend(
+    out: &mut String,
+    commit: Option<&CommitData>,
+    modifications: &HashMap<CommitId, CommitModifications>,
+) {
+    let Some((commit, mods)) = commit.and_then(|c| Some((c, modifications.get(&c.id)?))) else {
+        return;
+    };
+    if !mods.has_modifications() {
+        return;
+    }
+
+    let mut env = Vec::new();
+    if mods.committer_name.is_some() {
+        let name = mods.effective_committer_name(&commit.committer.name);
+        env.push(format!("GIT_COMMITTER_NAME={}", shell_quote(name)));
+    }
+    if mods.committer_email.is_some() {
+        let email = mods.effective_committer_email(&commit.committer.email);
+        env.push(format!("GIT_COMMITTER_EMAIL={}", shell_quote(email)));
+    }
+    if let Some(date) = mods.committer_date {
+        env.push(format!(
+            "GIT_COMMITTER_DATE={}",
+            shell_quote(&date.to_rfc2822())
+        ));
+    }
+
+    let mut args = vec!["git commit --amend --allow-empty --no-verify".to_string()];
+    if mods.author_name.is_some() || mods.author_email.is_some() {
+        let author = format!(
+            "{} <{}>",
+            mods.effective_author_name(&commit.author.name),
+            mods.effective_author_email(&commit.author.email)
+        );
+        args.push(format!("--author={}", shell_quote(&author)));
+    }
+    if let Some(date) = mods.author_date {
+        args.push(format!("--date={}", shell_quote(&date.to_rfc2822())));
+    }
+
+    let mut line = String::from("exec ");
+    if let Some(message) = &mods.message {
+        // One printf argument per line keeps the exec line on one line
+        let lines: Vec<String> = message.lines().map(shell_quote).collect();
+        let _ = write!(line, "printf '%s\\n' {} | ", lines.join(" "));
+        args.push("-F -".to_string());
+    } else {
+        args.push("--no-edit".to_string());
+    }
+    for var in env {
+        line.push_str(&var);
+        line.push(' ');
+    }
+    line.push_str(&args.join(" "));
+    let _ = writeln!(out, "{line}");
+}
+
+/// Quote a value for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::Person;
+    use chrono::{FixedOffset, TimeZone};
+    use std::collections::HashSet;
+
+    fn id(n: u8) -> CommitId {
+        CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap())
+    }
+
+    fn commit(n: u8, summary: &str) -> CommitData {
+        let dt = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap();
+        CommitData {
+            id: id(n),
+            short_hash: format!("{n:07}"),
+            author: Person::new("Jane", "jane@old.example"),
+            author_date: dt,
+            committer: Person::new("Jane", "jane@old.example"),
+            committer_date: dt,
+            message: summary.to_string(),
+            summary: summary.to_string(),
+            parent_ids: vec![id(n - 1)],
+            tree_id: git2::Oid::zero(),
+            is_merge: false,
+        }
+    }
+
+    #[test]
+    fn test_rebase_todo() {
+        let commits = [
+            commit(4, "fixup! Second"),
+            commit(3, "Third"),
+            commit(2, "Second"),
+            commit(1, "First"),
+        ];
+        let original: Vec<_> = commits.iter().map(|c| c.id).collect();
+        // The fixup moved above its target, the third commit deleted
+        let new_order = vec![id(3), id(4), id(2), id(1)];
+        let modifications = HashMap::from([(
+            id(2),
+            CommitModifications {
+                author_email: Some("jane@example.com".to_string()),
+                message: Some("Second, it's fixed\n\nBody".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[commit(5, "Newer")],
+            modifications: &modifications,
+            deleted: &HashSet::from([id(3)]),
+            squashes: &HashMap::from([(id(4), SquashKind::Fixup)]),
+            splits: &HashMap::new(),
+            original_order: &original,
+            new_order: &new_order,
+            branch_name: "main",
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+
+        let todo = rebase_todo(&plan).unwrap();
+        let lines: Vec<&str> = todo.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(
+            lines,
+            [
+                "",
+                "pick 0000001 First",
+                "pick 0000002 Second",
+                "fixup 0000004 fixup! Second",
+                "exec printf '%s\\n' 'Second, it'\\''s fixed' '' 'Body' | git commit --amend \
+                 --allow-empty --no-verify --author='Jane <jane@example.com>' -F -",
+                "drop 0000003 Third",
+                "",
+                "pick 0000005 Newer",
+            ]
+        );
+        assert!(todo.contains(&format!("git rebase -i {}\n", id(0).0)));
+
+        let mut merge = commits[0].clone();
+        merge.is_merge = true;
+        let commits = [merge];
+        assert!(rebase_todo(&RewritePlan {
+            commits: &commits,
+            ..plan
+        })
+        .is_err());
+    }
+}
@@ -31,9 +31,24 @@ pub fn validate_email(email: &str) -> Result<()> {
 /// - "2024-01-15 14:30:00" (assumes UTC)
 /// - "2024-01-15 14:30" (assumes UTC, 0 seconds)
 /// - "2024-01-15" (assumes midnight UTC)
+/// - "2024-01-15T14:30:00+05:30" (ISO 8601; without an offset, UTC)
+/// - "@1705330200" or "1705330200" (seconds since the epoch, as in
+///   `GIT_AUTHOR_DATE`; UTC unless followed by an offset like "+0530")
 pub fn validate_date(date_str: &str) -> Result<DateTime<FixedOffset>> {
     let date_str = date_str.trim();
 
+    if let Some(dt) = parse_timestamp(date_str) {
+        return Ok(dt);
+    }
+
+    // ISO 8601 with a `T`: "2024-01-15T14:30:00+05:30", "...Z", "...+0530"
+    if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
+        return Ok(dt);
+    }
+    if let Ok(dt) = DateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M:%S%z") {
+        return Ok(dt);
+    }
+
     // Try full format with timezone: "2024-01-15 14:30:00 +0000"
     if let Ok(dt) = DateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S %z") {
         return Ok(dt);
@@ -62,6 +77,15 @@ pub fn validate_date(date_str: &str) -> Result<DateTime<FixedOffset>> {
         }
     }
 
+    // ISO 8601 without an offset (assume UTC)
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(date_str, format) {
+            if let Some(dt) = utc.from_local_datetime(&naive).single() {
+                return Ok(dt);
+            }
+        }
+    }
+
     // Try date only (midnight UTC)
     if let Ok(naive) =
         NaiveDateTime::parse_from_str(&format!("{date_str} 00:00:00"), "%Y-%m-%d %H:%M:%S")
@@ -74,6 +98,34 @@ pub fn validate_date(date_str: &str) -> Result<DateTime<FixedOffset>> {
     Err(HistError::InvalidDate(date_str.to_string()))
 }
 
+/// Parse git's timestamp forms: `@<seconds>` or a bare number of more than
+/// eight digits (shorter ones could be dates), optionally followed by an
+/// offset such as `+0530`
+fn parse_timestamp(date_str: &str) -> Option<DateTime<FixedOffset>> {
+    let (seconds, offset) = match date_str.split_once(' ') {
+        Some((seconds, offset)) => (seconds, Some(offset.trim())),
+        None => (date_str, None),
+    };
+    let digits = match seconds.strip_prefix('@') {
+        Some(digits) => digits,
+        None if seconds.len() > 8 => seconds,
+        None => return None,
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let offset = match offset {
+        Some(offset) => DateTime::parse_from_str(
+            &format!("1970-01-01 00:00:00 {offset}"),
+            "%Y-%m-%d %H:%M:%S %z",
+        )
+        .ok()?
+        .timezone(),
+        None => FixedOffset::east_opt(0)?,
+    };
+    DateTime::from_timestamp(digits.parse().ok()?, 0).map(|utc| utc.with_timezone(&offset))
+}
+
 /// Format a date for editing (reversible format)
 #[allow(dead_code)]
 #[must_use]
@@ -111,6 +163,28 @@ mod tests {
         assert!(validate_date("2024-01-15").is_ok());
     }
 
+    #[test]
+    fn test_timestamp_and_iso_dates() {
+        let expected = validate_date("2024-01-15 14:50:00 +0000").unwrap();
+        assert_eq!(validate_date("@1705330200").unwrap(), expected);
+        assert_eq!(validate_date("1705330200").unwrap(), expected);
+        let dt = validate_date("@1705330200 +0530").unwrap();
+        assert_eq!(dt, expected);
+        assert_eq!(dt.offset().local_minus_utc(), 5 * 3600 + 1800);
+
+        let dt = validate_date("2024-01-15T20:20:00+05:30").unwrap();
+        assert_eq!(dt, expected);
+        assert_eq!(dt.offset().local_minus_utc(), 5 * 3600 + 1800);
+        assert_eq!(validate_date("2024-01-15T14:50:00Z").unwrap(), expected);
+        assert_eq!(validate_date("2024-01-15T20:20:00+0530").unwrap(), expected);
+        assert_eq!(validate_date("2024-01-15T14:50:00").unwrap(), expected);
+
+        // Too short to be a timestamp, and not a date either
+        assert!(validate_date("20240115").is_err());
+        assert!(validate_date("@").is_err());
+        assert!(validate_date("@12ab").is_err());
+    }
+
     #[test]
     fn test_invalid_dates() {
         assert!(validate_date("invalid").is_err());
@@ -0,0 +1,358 @@
+//! Work done on worker threads, each with its own repository handle
+//!
+//! A [`RewriteWorker`] stashes uncommitted changes, rewrites, checks the new
+//! tip out and restores the stash, reporting each phase over a channel.
+//! Progress within the rewrite is read from the shared [`RewriteProgress`],
+//! which is also how the rewrite is cancelled.
+//!
+//! A [`CommitLoader`] walks the history and sends the commits back in
+//! batches, so the table fills in while a long history loads.
+
+use crate::error::{HistError, Result};
+use crate::git::commit::{
+    CommitData, CommitId, CommitModifications, CommitSplit, NewRoot, SquashKind,
+};
+use crate::git::rewrite::{
+    rewrite_history_with_progress, DependentBranch, RewritePlan, RewriteProgress,
+};
+use crate::git::signing::Signer;
+use crate::git::Repository;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// An owned copy of a [`RewritePlan`], to hand to a worker thread
+pub struct RewriteJob {
+    pub commits: Vec<CommitData>,
+    pub descendants: Vec<CommitData>,
+    pub modifications: HashMap<CommitId, CommitModifications>,
+    pub deleted: HashSet<CommitId>,
+    pub squashes: HashMap<CommitId, SquashKind>,
+    pub splits: HashMap<CommitId, CommitSplit>,
+    pub original_order: Vec<CommitId>,
+    pub new_order: Vec<CommitId>,
+    pub branch_name: String,
+    pub branches: Vec<DependentBranch>,
+    pub update_hash_references: bool,
+    pub signer: Option<Signer>,
+    pub new_root: Option<NewRoot>,
+}
+
+impl RewriteJob {
+    /// Copy a plan
+    #[must_use]
+    pub fn from_plan(plan: &RewritePlan<'_>) -> Self {
+        Self {
+            commits: plan.commits.to_vec(),
+            descendants: plan.descendants.to_vec(),
+            modifications: plan.modifications.clone(),
+            deleted: plan.deleted.clone(),
+            squashes: plan.squashes.clone(),
+            splits: plan.splits.clone(),
+            original_order: plan.original_order.to_vec(),
+            new_order: plan.new_order.to_vec(),
+            branch_name: plan.branch_name.to_string(),
+            branches: plan.branches.to_vec(),
+            update_hash_references: plan.update_hash_references,
+            signer: plan.signer.cloned(),
+            new_root: plan.new_root.cloned(),
+        }
+    }
+
+    /// The plan to rewrite with
+    #[must_use]
+    pub fn plan(&self) -> RewritePlan<'_> {
+        RewritePlan {
+            commits: &self.commits,
+            descendants: &self.descendants,
+            modifications: &self.modifications,
+            deleted: &self.deleted,
+            squashes: &self.squashes,
+            splits: &self.splits,
+            original_order: &self.original_order,
+            new_order: &self.new_order,
+            branch_name: &self.branch_name,
+            branches: &self.branches,
+            update_hash_references: self.update_hash_references,
+            signer: self.signer.as_ref(),
+            new_root: self.new_root.as_ref(),
+        }
+    }
+}
+
+/// What a worker is busy with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyPhase {
+    Stashing,
+    Rewriting,
+    CheckingOut,
+    Unstashing,
+}
+
+impl ApplyPhase {
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Stashing => "Stashing uncommitted changes",
+            Self::Rewriting => "Rewriting commits",
+            Self::CheckingOut => "Checking out the new tip",
+            Self::Unstashing => "Restoring stashed changes",
+        }
+    }
+}
+
+/// How a worker's rewrite ended
+#[derive(Debug)]
+pub struct ApplyOutcome {
+    /// Old commit ID -> new commit ID, or why the rewrite (or the stash or
+    /// checkout around it) failed
+    pub result: Result<HashMap<CommitId, CommitId>>,
+    /// Restoring the stashed changes, when there were any
+    pub unstash: Option<Result<()>>,
+}
+
+enum WorkerMessage {
+    Phase(ApplyPhase),
+    Done(ApplyOutcome),
+}
+
+/// A rewrite running on its own thread
+pub struct RewriteWorker {
+    progress: Arc<RewriteProgress>,
+    messages: Receiver<WorkerMessage>,
+    phase: ApplyPhase,
+}
+
+impl RewriteWorker {
+    /// Start rewriting on a new thread
+    ///
+    /// With `stash`, uncommitted changes are stashed first and restored
+    /// afterwards (even if the rewrite failed); with `checkout`, the new tip
+    /// is checked out once the rewrite succeeded.
+    #[must_use]
+    pub fn spawn(mut repo: Repository, job: RewriteJob, stash: bool, checkout: bool) -> Self {
+        let progress = Arc::new(RewriteProgress::default());
+        let (sender, messages) = mpsc::channel();
+        let shared = Arc::clone(&progress);
+        thread::spawn(move || {
+            // The receiver may be gone (e.g. the app is exiting); nothing to do then
+            let send = |message| {
+                let _ = sender.send(message);
+            };
+            let stashed = if stash {
+                send(WorkerMessage::Phase(ApplyPhase::Stashing));
+                match repo.stash_changes() {
+                    Ok(stashed) => stashed,
+                    Err(e) => {
+                        send(WorkerMessage::Done(ApplyOutcome {
+                            result: Err(e),
+                            unstash: None,
+                        }));
+                        return;
+                    }
+                }
+            } else {
+                false
+            };
+
+            send(WorkerMessage::Phase(ApplyPhase::Rewriting));
+            let mut result = rewrite_history_with_progress(repo.inner(), &job.plan(), &shared);
+            if result.is_ok() && checkout {
+                // The working tree is clean (changes were stashed), so bring it along
+                send(WorkerMessage::Phase(ApplyPhase::CheckingOut));
+                if let Err(e) = repo.checkout_head() {
+                    result = Err(e);
+                }
+            }
+            let unstash = stashed.then(|| {
+                send(WorkerMessage::Phase(ApplyPhase::Unstashing));
+                repo.unstash_changes()
+            });
+            send(WorkerMessage::Done(ApplyOutcome { result, unstash }));
+        });
+        Self {
+            progress,
+            messages,
+            phase: ApplyPhase::Stashing,
+        }
+    }
+
+    #[must_use]
+    pub fn phase(&self) -> ApplyPhase {
+        self.phase
+    }
+
+    #[must_use]
+    pub fn progress(&self) -> &RewriteProgress {
+        &self.progress
+    }
+
+    /// Ask the rewrite to stop; it ends with [`HistError::Cancelled`] unless
+    /// it was already moving the branch
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// The outcome, if the worker is done (doesn't block)
+    pub fn poll(&mut self) -> Option<ApplyOutcome> {
+        loop {
+            match self.messages.try_recv() {
+                Ok(WorkerMessage::Phase(phase)) => self.phase = phase,
+                Ok(WorkerMessage::Done(outcome)) => return Some(outcome),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => return Some(Self::lost()),
+            }
+        }
+    }
+
+    /// Wait for the worker to finish
+    pub fn wait(&mut self) -> ApplyOutcome {
+        loop {
+            match self.messages.recv() {
+                Ok(WorkerMessage::Phase(phase)) => self.phase = phase,
+                Ok(WorkerMessage::Done(outcome)) => return outcome,
+                Err(_) => return Self::lost(),
+            }
+        }
+    }
+
+    /// The outcome of a worker that stopped without reporting one (it panicked)
+    fn lost() -> ApplyOutcome {
+        ApplyOutcome {
+            result: Err(HistError::RewriteFailed(
+                "the rewrite stopped unexpectedly".to_string(),
+            )),
+            unstash: None,
+        }
+    }
+}
+
+/// Commits sent back by a loader at a time
+pub const LOAD_BATCH: usize = 500;
+
+/// What a loader sends back
+#[derive(Debug)]
+pub enum LoadEvent {
+    /// The next older commits, in display order
+    Batch(Vec<CommitData>),
+    /// Loading is over, or failed
+    Done(Result<()>),
+}
+
+/// Older commits loading on their own thread
+///
+/// Dropping the loader stops it after its current batch.
+pub struct CommitLoader {
+    events: Receiver<LoadEvent>,
+}
+
+impl CommitLoader {
+    /// Start loading up to `count` commits after the first `skip`
+    #[must_use]
+    pub fn spawn(repo: Repository, skip: usize, count: usize) -> Self {
+        let (sender, events) = mpsc::channel();
+        thread::spawn(move || {
+            let result = repo.load_commits_batched(skip, count, LOAD_BATCH, |batch| {
+                sender.send(LoadEvent::Batch(batch)).is_ok()
+            });
+            let _ = sender.send(LoadEvent::Done(result));
+        });
+        Self { events }
+    }
+
+    /// The next event, if there is one yet (doesn't block)
+    #[must_use]
+    pub fn poll(&self) -> Option<LoadEvent> {
+        match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Self::lost()),
+        }
+    }
+
+    /// Wait for the next event
+    #[must_use]
+    pub fn wait(&self) -> LoadEvent {
+        self.events.recv().unwrap_or_else(|_| Self::lost())
+    }
+
+    /// The end of a loader that stopped without saying so (it panicked)
+    fn lost() -> LoadEvent {
+        LoadEvent::Done(Err(
+            git2::Error::from_str("loading stopped unexpectedly").into()
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_rewrites_and_restores_stash() {
+        let test = crate::testing::TestRepo::linear(&["a", "b"]).unwrap();
+        let repo = test.repo();
+        let commits = repo.load_commits(10).unwrap();
+        let order: Vec<_> = commits.iter().map(|c| c.id).collect();
+        let modifications = HashMap::from([(
+            order[0],
+            CommitModifications {
+                message: Some("b, reworded".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &order,
+            new_order: &order,
+            branch_name: &test.branch_name().unwrap(),
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        };
+        let dirty = test.path().join("0.txt");
+        std::fs::write(&dirty, "uncommitted").unwrap();
+
+        let mut worker = RewriteWorker::spawn(
+            repo.reopen().unwrap(),
+            RewriteJob::from_plan(&plan),
+            true,
+            true,
+        );
+        let outcome = worker.wait();
+        let commit_map = outcome.result.unwrap();
+        assert!(matches!(outcome.unstash, Some(Ok(()))));
+        assert_eq!(worker.phase(), ApplyPhase::Unstashing);
+        assert_eq!(worker.progress().counts(), (2, 2));
+
+        let tip = test.repo().tip_commit_id().unwrap();
+        assert_eq!(commit_map[&order[0]], tip);
+        let head = test.git().find_commit(tip.0).unwrap();
+        assert_eq!(head.message(), Some("b, reworded"));
+        assert_eq!(std::fs::read_to_string(&dirty).unwrap(), "uncommitted");
+    }
+
+    #[test]
+    fn test_loader_sends_older_commits() {
+        let test = crate::testing::TestRepo::linear(&["a", "b", "c"]).unwrap();
+        let all = test.repo().load_commits(10).unwrap();
+
+        let loader = CommitLoader::spawn(test.repo().reopen().unwrap(), 1, 10);
+        let mut loaded = Vec::new();
+        loop {
+            match loader.wait() {
+                LoadEvent::Batch(batch) => loaded.extend(batch.into_iter().map(|c| c.id)),
+                LoadEvent::Done(result) => break result.unwrap(),
+            }
+        }
+        assert_eq!(loaded, [all[1].id, all[2].id]);
+    }
+}
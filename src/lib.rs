@@ -4,50 +4,185 @@
 //! including author/committer information, dates, and commit messages.
 
 pub mod app;
+pub mod clipboard;
+pub mod config;
 pub mod error;
 pub mod git;
+pub mod plugin;
 pub mod state;
+pub mod testing;
 pub mod ui;
 
-pub use app::App;
+pub use app::{App, ReportTarget};
+pub use config::Config;
 pub use error::{HistError, Result};
 pub use git::Repository;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
+use git::backend::Backend;
+use git::metadata::DumpFormat;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
+use state::session::flush_recovery;
 use std::io::{self, stdout};
 use std::panic;
 use std::path::PathBuf;
 
+/// Commits loaded when no `--limit` is given
+const DEFAULT_LIMIT: usize = 50;
+
 /// Command-line arguments for retcon.
 #[derive(Parser, Debug)]
 #[command(name = "retcon")]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Path to the git repository (default: current directory)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     path: Option<PathBuf>,
 
-    /// Maximum number of commits to load
-    #[arg(short = 'n', long, default_value = "50")]
-    limit: usize,
+    /// Maximum number of commits to load (0 = all; change later with `:limit`)
+    /// [default: 50, or the whole range with --rev-range]
+    #[arg(short = 'n', long, global = true)]
+    limit: Option<usize>,
+
+    /// Load the full history (same as `-n 0`)
+    #[arg(long, global = true)]
+    all: bool,
+
+    /// Edit this local branch instead of the checked-out one (switch later
+    /// with `B` or `:branch`)
+    #[arg(short, long, value_name = "NAME", global = true)]
+    branch: Option<String>,
+
+    /// Start loading at this commit, tag or revision instead of HEAD (must be
+    /// reachable from HEAD; newer commits are replayed unchanged on apply)
+    #[arg(long, value_name = "REV", global = true)]
+    from: Option<String>,
+
+    /// Load only the commits in a revision range, like `git log A..B` (B must
+    /// be reachable from HEAD; newer commits are replayed unchanged on apply)
+    #[arg(
+        long,
+        visible_alias = "range",
+        value_name = "A..B",
+        conflicts_with = "from"
+    )]
+    rev_range: Option<String>,
 
-    /// Skip validation checks (dangerous!)
-    #[arg(long, hide = true)]
+    /// Follow only the first parent of merge commits, skipping the history
+    /// merged in from other branches
+    #[arg(long, global = true)]
+    first_parent: bool,
+
+    /// Load the history with this library: git2 (default) or gix, which is
+    /// faster on very large repositories (needs the `gix` feature; diffs and
+    /// rewriting always use git2)
+    #[arg(long, value_name = "BACKEND", global = true)]
+    backend: Option<Backend>,
+
+    /// Start with the table filtered to the commits that change this file
+    /// or anything below this directory, like searching for `path:PATH`
+    #[arg(long, value_name = "PATH")]
+    touching: Option<String>,
+
+    /// Follow renames of the `--touching` file back through its history, like
+    /// `git log --follow` (searching for `path!:PATH`)
+    #[arg(long, requires = "touching")]
+    follow: bool,
+
+    /// Skip the safety checks (dangerous!): apply without stashing uncommitted
+    /// changes, edit commits locked by age, rewrite pushed commits despite
+    /// `protect_pushed`, and accept malformed emails. Every skipped check is
+    /// logged in the apply summary
+    #[arg(long, global = true)]
     force: bool,
 
     /// Keep author and committer fields separate (by default, editing author
     /// fields also updates the corresponding committer fields)
     #[arg(long, short = 's')]
     separate_author_committer: bool,
+
+    /// After each successful apply, append a Markdown report of the rewrite
+    /// to this file (use `-` to print it to stdout on exit)
+    #[arg(long, value_name = "FILE", global = true)]
+    report: Option<PathBuf>,
+
+    /// Sign the rewritten commits with `user.signingkey` (GPG or SSH, per
+    /// `gpg.format`); on by default with `commit.gpgsign`
+    #[arg(long, global = true)]
+    sign: bool,
+
+    /// Stage identity fixes from a mailmap file (the repo's `.mailmap` if no
+    /// file is given) for every loaded commit whose author or committer it
+    /// remaps, to review before applying
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "")]
+    mailmap: Option<PathBuf>,
+
+    /// On exit, print one `key=value` line per applied rewrite to stdout and
+    /// exit with 3 if nothing was applied or 4 if pending changes were left
+    #[arg(long)]
+    summary: bool,
+
+    /// Stage the edits of a plan file (JSON, as written by `:export
+    /// plan.json`), replacing the mailmap's
+    #[arg(long, value_name = "FILE")]
+    plan: Option<PathBuf>,
+
+    /// Stage a new message for every loaded commit from a shell command, run
+    /// per commit with the current message on stdin (e.g. `sed
+    /// 's/^WIP: //'`); what it prints is the new message
+    #[arg(long, value_name = "COMMAND", global = true)]
+    message_callback: Option<String>,
+
+    /// Stage new author and committer names from a shell command, run once
+    /// per distinct name with the name on stdin; what it prints is the new
+    /// name
+    #[arg(long, value_name = "COMMAND", global = true)]
+    name_callback: Option<String>,
+
+    /// Print what applying the staged edits (`--plan`, `--mailmap`,
+    /// callbacks) would change, with the predicted new hashes, and exit
+    /// without writing or opening the TUI
+    #[arg(long, global = true)]
+    dry_run: bool,
 }
 
+/// What to do instead of opening the TUI
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Apply a plan file's edits (as written by `:export plan.json`) without
+    /// the TUI, with the same safety checks, and print the summary line
+    Apply {
+        /// The plan to apply
+        #[arg(long, value_name = "FILE")]
+        plan: PathBuf,
+    },
+    /// Write the loaded commits' metadata (hash, author, committer, dates and
+    /// summary), with the values staged edits (`--plan`, `--mailmap`) would
+    /// give them, to a CSV or JSON file for auditing
+    Dump {
+        /// File to write, `.csv` or `.json` (default: print CSV to stdout)
+        file: Option<PathBuf>,
+        /// Print JSON instead of CSV
+        #[arg(long, conflicts_with = "file")]
+        json: bool,
+    },
+}
+
+/// `--summary` exit code: the session ended without applying anything
+const EXIT_NOTHING_APPLIED: i32 = 3;
+
+/// `--summary` exit code: pending changes were left unapplied on quit
+const EXIT_PENDING_LEFT: i32 = 4;
+
 /// Main entry point for the retcon application.
 ///
 /// This function is called by both `retcon` and `ret` binaries.
@@ -59,23 +194,121 @@ pub fn main() {
     let args = Args::parse();
 
     // Run the app
-    if let Err(e) = run(&args) {
-        eprintln!("Error: {e}");
-        std::process::exit(1);
+    match run(&args) {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
     }
 }
 
-fn run(args: &Args) -> Result<()> {
+/// Run a session, returning the exit code
+fn run(args: &Args) -> Result<i32> {
     // Open repository
-    let repo = match &args.path {
+    let mut repo = match &args.path {
         Some(path) => Repository::open(path)?,
         None => Repository::open_current_dir()?,
     };
+    repo.set_first_parent(args.first_parent);
+    if let Some(backend) = args.backend {
+        repo.set_backend(backend);
+    }
+    if let Some(branch) = &args.branch {
+        repo.set_branch(branch)?;
+    }
+    if let Some(rev) = &args.from {
+        repo.set_start(rev)?;
+    }
+    if let Some(range) = &args.rev_range {
+        repo.set_range(range)?;
+    }
+
+    let config = Config::load()?;
 
     // Create app
     // When separate_author_committer is true, we DON'T want to sync (sync = false)
     let sync_author_to_committer = !args.separate_author_committer;
-    let mut app = App::new(repo, args.limit, sync_author_to_committer)?;
+    let default_limit = if args.rev_range.is_some() {
+        0
+    } else {
+        DEFAULT_LIMIT
+    };
+    let limit = match args.limit.unwrap_or(default_limit) {
+        0 => usize::MAX,
+        _ if args.all => usize::MAX,
+        limit => limit,
+    };
+    // Going straight into the TUI, older commits can load while it runs;
+    // anything staged or run from the command line needs them all first
+    let straight_to_tui = args.command.is_none()
+        && !args.dry_run
+        && args.touching.is_none()
+        && args.mailmap.is_none()
+        && args.plan.is_none()
+        && args.message_callback.is_none()
+        && args.name_callback.is_none();
+    let mut app = if straight_to_tui {
+        App::new_loading(repo, limit, sync_author_to_committer, &config)?
+    } else {
+        App::new(repo, limit, sync_author_to_committer, &config)?
+    };
+    app.set_report_target(args.report.as_deref().map(ReportTarget::from_arg));
+    app.set_force(args.force);
+    if args.sign {
+        app.enable_signing()?;
+    }
+    if let Some(path) = &args.touching {
+        let key = if args.follow { "path!" } else { "path" };
+        app.set_search(&format!("{key}:{path}"))?;
+    }
+    if let Some(path) = &args.mailmap {
+        let path = (!path.as_os_str().is_empty()).then_some(path.as_path());
+        let count = app.apply_mailmap(path)?;
+        app.state
+            .set_success(format!("Mailmap: remapped identities on {count} commit(s)"));
+    }
+    if let Some(path) = &args.plan {
+        app.load_plan(path)?;
+        app.state
+            .set_success(format!("Loaded plan {}", path.display()));
+    }
+    if let Some(Commands::Apply { plan }) = &args.command {
+        app.load_plan(plan)?;
+    }
+    if args.message_callback.is_some() || args.name_callback.is_some() {
+        let count = app.apply_callbacks(
+            args.message_callback.as_deref(),
+            args.name_callback.as_deref(),
+        )?;
+        app.state
+            .set_success(format!("Callbacks: changed {count} commit(s)"));
+    }
+    if let Some(Commands::Dump { file, json }) = &args.command {
+        match file {
+            Some(path) => {
+                app.dump_metadata(path)?;
+            }
+            None if *json => print!("{}", app.metadata_dump(DumpFormat::Json)),
+            None => print!("{}", app.metadata_dump(DumpFormat::Csv)),
+        }
+        return Ok(0);
+    }
+    if args.dry_run {
+        print!("{}", app.dry_run()?);
+        return Ok(0);
+    }
+    if let Some(Commands::Apply { .. }) = &args.command {
+        let summary = app.apply_headless()?;
+        for report in app.take_stdout_reports() {
+            println!("{report}");
+        }
+        println!("{}", summary.to_line());
+        return Ok(0);
+    }
+
+    app.offer_session();
 
     // Set up terminal
     let mut terminal = setup_terminal()?;
@@ -86,7 +319,27 @@ fn run(args: &Args) -> Result<()> {
     // Restore terminal
     restore_terminal(&mut terminal)?;
 
-    result
+    // Reports destined for stdout are printed once the TUI is gone
+    for report in app.take_stdout_reports() {
+        println!("{report}");
+    }
+    result?;
+
+    if !args.summary {
+        return Ok(0);
+    }
+    for summary in app.summaries() {
+        println!("{}", summary.to_line());
+    }
+    Ok(if app.state.is_dirty() {
+        eprintln!("retcon: pending changes were not applied");
+        EXIT_PENDING_LEFT
+    } else if app.summaries().is_empty() {
+        eprintln!("retcon: nothing applied");
+        EXIT_NOTHING_APPLIED
+    } else {
+        0
+    })
 }
 
 fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
@@ -119,6 +372,9 @@ fn setup_panic_hook() {
         let _ = disable_raw_mode();
         let _ = execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture);
 
+        // Keep the latest edits for the next start to recover
+        let _ = flush_recovery();
+
         // Then call original hook to print panic info
         original_hook(panic_info);
     }));
@@ -0,0 +1,305 @@
+//! External command plugins declared in the config file
+//!
+//! A plugin is a shell command run as a `:` command. It gets the selected
+//! commits (with pending edits applied) as a JSON array on stdin and prints a
+//! JSON array of field changes on stdout, which retcon stages as ordinary
+//! pending edits.
+//!
+//! Callbacks (`--message-callback`, `--name-callback`) are simpler: a shell
+//! command run on one value at a time, reading it on stdin and printing the
+//! replacement.
+
+use crate::error::{HistError, Result};
+use crate::git::commit::{CommitData, CommitModifications};
+use crate::git::validation::{validate_date, validate_email};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Date format used in plugin input (also accepted in output, as is RFC 3339)
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// A custom `:` command backed by a shell command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginCommand {
+    /// Name typed after `:` (built-in commands take precedence)
+    pub name: String,
+    /// Shell command line, run with `sh -c` in the repository's working directory
+    pub run: String,
+    /// Short description for the help screen
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A commit as a plugin sees it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginCommit {
+    /// Full hash of the original commit
+    pub id: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub author_date: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committer_date: String,
+    pub message: String,
+}
+
+impl PluginCommit {
+    /// Describe a commit with its pending modifications applied
+    #[must_use]
+    pub fn new(commit: &CommitData, mods: Option<&CommitModifications>) -> Self {
+        let default = CommitModifications::default();
+        let mods = mods.unwrap_or(&default);
+        let date = |d: DateTime<FixedOffset>| d.format(DATE_FORMAT).to_string();
+        Self {
+            id: commit.id.0.to_string(),
+            author_name: mods.effective_author_name(&commit.author.name).to_string(),
+            author_email: mods
+                .effective_author_email(&commit.author.email)
+                .to_string(),
+            author_date: date(mods.author_date.unwrap_or(commit.author_date)),
+            committer_name: mods
+                .effective_committer_name(&commit.committer.name)
+                .to_string(),
+            committer_email: mods
+                .effective_committer_email(&commit.committer.email)
+                .to_string(),
+            committer_date: date(mods.committer_date.unwrap_or(commit.committer_date)),
+            message: mods.effective_message(&commit.message).to_string(),
+        }
+    }
+}
+
+/// Field changes a plugin returns for one commit; missing fields are left alone
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginEdit {
+    /// Full or abbreviated hash of the commit to change
+    pub id: String,
+    pub author_name: Option<String>,
+    pub author_email: Option<String>,
+    pub author_date: Option<String>,
+    pub committer_name: Option<String>,
+    pub committer_email: Option<String>,
+    pub committer_date: Option<String>,
+    pub message: Option<String>,
+}
+
+impl PluginEdit {
+    /// Validate the returned values and turn them into modifications
+    ///
+    /// Without `check_emails` (`--force`), malformed emails are kept as given.
+    pub fn to_modifications(&self, check_emails: bool) -> Result<CommitModifications> {
+        for email in [&self.author_email, &self.committer_email]
+            .into_iter()
+            .flatten()
+            .filter(|_| check_emails)
+        {
+            validate_email(email)?;
+        }
+        let date = |value: &Option<String>| -> Result<Option<DateTime<FixedOffset>>> {
+            value
+                .as_deref()
+                .map(|v| DateTime::parse_from_rfc3339(v).or_else(|_| validate_date(v)))
+                .transpose()
+        };
+        Ok(CommitModifications {
+            author_name: self.author_name.clone(),
+            author_email: self.author_email.clone(),
+            author_date: date(&self.author_date)?,
+            committer_name: self.committer_name.clone(),
+            committer_email: self.committer_email.clone(),
+            committer_date: date(&self.committer_date)?,
+            message: self.message.clone(),
+        })
+    }
+}
+
+/// Run a plugin on some commits and parse the edits it prints
+///
+/// `args` (the rest of the `:` command line) become the shell's `$1`, `$2`, ...
+pub fn run_plugin(
+    plugin: &PluginCommand,
+    args: &[&str],
+    commits: &[PluginCommit],
+    dir: &Path,
+) -> Result<Vec<PluginEdit>> {
+    let input = serde_json::to_vec(commits).map_err(|e| plugin_error(plugin, e))?;
+    let stdout = run_shell(&plugin.run, &plugin.name, args, &input, dir)
+        .map_err(|reason| plugin_error(plugin, reason))?;
+    if stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Vec::new());
+    }
+    serde_json::from_slice(&stdout).map_err(|e| plugin_error(plugin, e))
+}
+
+/// Run a filter-repo style callback on one value: the shell command gets the
+/// value (and a newline) on stdin, and what it prints, without trailing line
+/// breaks, is the new value
+///
+/// # Errors
+/// Returns an error if the command can't be run, fails, or prints something
+/// that isn't UTF-8.
+pub fn run_callback(command: &str, value: &str, dir: &Path) -> Result<String> {
+    let callback_error = |reason: String| HistError::Plugin(format!("{command}: {reason}"));
+    let stdout = run_shell(command, "retcon", &[], format!("{value}\n").as_bytes(), dir)
+        .map_err(callback_error)?;
+    let output = String::from_utf8(stdout).map_err(|e| callback_error(e.to_string()))?;
+    Ok(output.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// Run a shell command line with `sh -c`, feeding it `input`, and return
+/// its stdout, or why it failed (the last line of its stderr)
+fn run_shell(
+    script: &str,
+    name: &str,
+    args: &[&str],
+    input: &[u8],
+    dir: &Path,
+) -> std::result::Result<Vec<u8>, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .arg(name)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    // Feed stdin from another thread while the output is read, or a command
+    // that streams (like `cat`) fills its stdout pipe and both sides wait
+    // forever. It may exit without reading its input; that's its business.
+    let stdin = child.stdin.take();
+    let output = std::thread::scope(|scope| {
+        if let Some(mut stdin) = stdin {
+            scope.spawn(move || {
+                let _ = stdin.write_all(input);
+            });
+        }
+        child.wait_with_output()
+    })
+    .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr
+            .lines()
+            .last()
+            .map_or_else(|| output.status.to_string(), str::to_string));
+    }
+    Ok(output.stdout)
+}
+
+fn plugin_error(plugin: &PluginCommand, reason: impl std::fmt::Display) -> HistError {
+    HistError::Plugin(format!("{}: {reason}", plugin.name))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn plugin(run: &str) -> PluginCommand {
+        PluginCommand {
+            name: "test".to_string(),
+            run: run.to_string(),
+            description: None,
+        }
+    }
+
+    fn input() -> Vec<PluginCommit> {
+        vec![PluginCommit {
+            id: "1111111111111111111111111111111111111111".to_string(),
+            author_name: "Old".to_string(),
+            author_email: "old@example.com".to_string(),
+            author_date: "2024-01-15 14:30:00 +0000".to_string(),
+            committer_name: "Old".to_string(),
+            committer_email: "old@example.com".to_string(),
+            committer_date: "2024-01-15 14:30:00 +0000".to_string(),
+            message: "Subject".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_run_plugin_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        // Echo the first commit's id back with a new author name
+        let run = r#"id=$(sed 's/.*"id":"\([0-9a-f]*\)".*/\1/'); printf '[{"id":"%s","author_name":"%s"}]' "$id" "$1""#;
+        let edits = run_plugin(&plugin(run), &["New"], &input(), dir.path()).unwrap();
+        assert_eq!(
+            edits,
+            vec![PluginEdit {
+                id: "1111111111111111111111111111111111111111".to_string(),
+                author_name: Some("New".to_string()),
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_run_plugin_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_plugin(
+            &plugin("echo broken >&2; exit 3"),
+            &[],
+            &input(),
+            dir.path(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("test: broken"), "{err}");
+
+        let err = run_plugin(&plugin("echo nope"), &[], &input(), dir.path());
+        assert!(matches!(err, Err(HistError::Plugin(_))));
+    }
+
+    #[test]
+    fn test_run_callback() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = run_callback("tr a-z A-Z", "Fix bug\n\nBody", dir.path()).unwrap();
+        assert_eq!(output, "FIX BUG\n\nBODY");
+        let err = run_callback("exit 1", "x", dir.path()).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Plugin failed: exit 1: "),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_run_callback_streams_large_input() {
+        let dir = tempfile::tempdir().unwrap();
+        // More than a pipe buffer holds, echoed back as it's read
+        let value = "x".repeat(256 * 1024);
+        let output = run_callback("cat", &value, dir.path()).unwrap();
+        assert_eq!(output.len(), value.len());
+    }
+
+    #[test]
+    fn test_edit_validation() {
+        let edit = PluginEdit {
+            id: "1111111".to_string(),
+            author_date: Some("2024-02-01T10:00:00+01:00".to_string()),
+            ..Default::default()
+        };
+        let mods = edit.to_modifications(true).unwrap();
+        assert_eq!(
+            mods.author_date.unwrap().to_rfc3339(),
+            "2024-02-01T10:00:00+01:00"
+        );
+
+        let bad = PluginEdit {
+            author_email: Some("not-an-email".to_string()),
+            ..edit
+        };
+        assert!(bad.to_modifications(true).is_err());
+        // Forced
+        let mods = bad.to_modifications(false).unwrap();
+        assert_eq!(mods.author_email.as_deref(), Some("not-an-email"));
+    }
+}
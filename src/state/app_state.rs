@@ -1,7 +1,32 @@
-use crate::git::commit::{CommitData, CommitId, CommitModifications, EditableField};
+use crate::error::{HistError, Result};
+use crate::git::commit::{
+    hash_references, reflow_message, split_trailers, CommitData, CommitId, CommitModifications,
+    CommitSplit, EditableField, NewRoot, Person, SquashKind, Trailer,
+};
+use crate::git::conventional::{self, LintIssue};
+use crate::git::graph::graph_rows;
+use crate::git::mailmap::{self, Mailmap};
+use crate::git::report::{ApplyGuidance, Bypass};
+use crate::git::repository::{BackupRef, FileChange, TagRef};
+use crate::git::validation::{format_date_for_edit, validate_date};
+use crate::state::palette::CommandPalette;
+use crate::state::plan::EditPlan;
+use crate::state::search_query::{SearchQuery, TouchedPath};
+use crate::state::session::Session;
+use chrono::{DateTime, FixedOffset, Local, TimeDelta};
 use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
 use std::collections::{HashMap, HashSet};
 
+/// Maximum number of simultaneous highlight groups (one per theme color)
+pub const MAX_HIGHLIGHT_GROUPS: usize = 6;
+
+/// Register that yanks and pastes use when none is named
+pub const UNNAMED_REGISTER: char = '"';
+
+/// Register that stands for the system clipboard, as in vim
+pub const CLIPBOARD_REGISTER: char = '+';
+
 /// Type of visual selection mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VisualType {
@@ -11,6 +36,184 @@ pub enum VisualType {
     Block,
 }
 
+/// Display options toggled with `:set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayOption {
+    /// Column showing each commit's position as `HEAD~n`
+    HeadOffset,
+    /// Column showing the time elapsed since the previous (older) commit
+    TimeGap,
+    /// Leading column with each row's number
+    Number,
+    /// Leading column with each row's distance from the cursor
+    RelativeNumber,
+    /// Show dates in the local timezone instead of their recorded offsets
+    LocalTime,
+    /// Column drawing the branch and merge topology, like `git log --graph`
+    Graph,
+    /// Mark messages that aren't Conventional Commits
+    Lint,
+}
+
+impl DisplayOption {
+    /// Look up an option by its `:set` name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "offset" => Some(Self::HeadOffset),
+            "gap" => Some(Self::TimeGap),
+            "number" | "nu" => Some(Self::Number),
+            "relativenumber" | "rnu" => Some(Self::RelativeNumber),
+            "localtime" | "lt" => Some(Self::LocalTime),
+            "graph" => Some(Self::Graph),
+            "lint" => Some(Self::Lint),
+            _ => None,
+        }
+    }
+
+    /// The option's `:set` name
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::HeadOffset => "offset",
+            Self::TimeGap => "gap",
+            Self::Number => "number",
+            Self::RelativeNumber => "relativenumber",
+            Self::LocalTime => "localtime",
+            Self::Graph => "graph",
+            Self::Lint => "lint",
+        }
+    }
+}
+
+/// Column the table can be sorted by (`:sort`, `gs`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Author,
+    Email,
+    Date,
+    Message,
+}
+
+impl SortKey {
+    /// Look up a key by its `:sort` name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "author" | "name" => Some(Self::Author),
+            "email" => Some(Self::Email),
+            "date" => Some(Self::Date),
+            "message" | "msg" | "subject" => Some(Self::Message),
+            _ => None,
+        }
+    }
+
+    /// The key for a table column's field, if it can be sorted by
+    #[must_use]
+    pub const fn for_field(field: EditableField) -> Option<Self> {
+        match field {
+            EditableField::AuthorName => Some(Self::Author),
+            EditableField::AuthorEmail => Some(Self::Email),
+            EditableField::AuthorDate => Some(Self::Date),
+            EditableField::Message => Some(Self::Message),
+            _ => None,
+        }
+    }
+
+    /// The key's `:sort` name
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Author => "author",
+            Self::Email => "email",
+            Self::Date => "date",
+            Self::Message => "message",
+        }
+    }
+}
+
+/// A view-only ordering of the table rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+/// A row's value for a sort key
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Text(String),
+    Date(DateTime<FixedOffset>),
+}
+
+/// How `M` (`:fixdates`) puts dates back in order after reordering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFix {
+    /// Hand the kept commits' dates out again, oldest first, in the new order
+    Swap,
+    /// Move each commit dated before the one below it to that date plus
+    /// this many seconds
+    Nudge(i64),
+}
+
+/// How `:snap` rounds a date to its granularity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapMode {
+    /// Round down to the boundary at or before the date
+    Floor,
+    /// Round up to the boundary at or after the date
+    Ceil,
+    /// Round to the closest boundary (halfway rounds up)
+    Nearest,
+}
+
+impl SnapMode {
+    /// Look up a mode by name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "floor" | "down" => Some(Self::Floor),
+            "ceil" | "up" => Some(Self::Ceil),
+            "nearest" | "round" => Some(Self::Nearest),
+            _ => None,
+        }
+    }
+
+    /// Snap a date to a multiple of `step` seconds in its own local time
+    ///
+    /// Snapping uses the date's own offset, so snapping to a day lands on local
+    /// midnight and the offset is kept. A date whose snapped value is out of
+    /// range (a step of millennia) is left as it is.
+    #[must_use]
+    pub fn snap(self, date: DateTime<FixedOffset>, step: i64) -> DateTime<FixedOffset> {
+        let offset = i64::from(date.offset().local_minus_utc());
+        let local = date.timestamp() + offset;
+        let floor = local.div_euclid(step) * step;
+        let snapped = match self {
+            Self::Floor => Some(floor),
+            Self::Ceil if floor == local => Some(floor),
+            Self::Ceil => floor.checked_add(step),
+            Self::Nearest => match (local - floor).checked_mul(2) {
+                Some(twice) if twice < step => Some(floor),
+                _ => floor.checked_add(step),
+            },
+        };
+        snapped
+            .and_then(|snapped| snapped.checked_sub(offset))
+            .and_then(|utc| DateTime::from_timestamp(utc, 0))
+            .map_or(date, |utc| utc.with_timezone(date.offset()))
+    }
+}
+
+/// Which of a commit's two dates the other one is set to when normalizing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSource {
+    /// Set the committer date to the author date
+    Author,
+    /// Set the author date to the committer date
+    Committer,
+}
+
 /// Current mode of the application
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppMode {
@@ -30,35 +233,364 @@ pub enum AppMode {
     },
     /// Search/filter mode
     Search,
+    /// Command line mode (`:`)
+    Command,
+    /// Fuzzy-finding an action to run (`Ctrl+p`)
+    Palette(CommandPalette),
+    /// Find/replace dialog for one commit's message, or the text being edited
+    Replace(ReplaceTarget),
     /// Reordering commits (move mode)
     #[allow(dead_code)]
     Reorder,
     /// Confirmation dialog
     Confirming(ConfirmAction),
+    /// Reviewing each changed commit before applying (`W`)
+    StepApply(StepApply),
+    /// A rewrite running in the background, with its progress
+    Applying,
+    /// The rewrite succeeded but the auto-stashed changes conflict with it
+    StashConflict(Box<StashConflict>),
+    /// Choosing which of a commit's files go into the first half of a split (`S`)
+    Split(Box<SplitChooser>),
+    /// Backup and force-push guidance after a successful rewrite
+    Applied(Box<ApplyGuidance>),
+    /// Adding, changing and removing trailers of the targeted commits (`T`, `A`)
+    Trailers(Box<TrailerEditor>),
+    /// Choosing the branch whose history is edited (`B`, `:branch`)
+    BranchPicker(BranchPicker),
+    /// Choosing an identity to set as the author of the targeted commits (`i`)
+    IdentityPicker(IdentityPicker),
+    /// Commits whose author and committer dates are far apart (`:divergence`)
+    Divergence(DivergenceReport),
+    /// Commit messages that aren't Conventional Commits (`:lint`)
+    Lint(LintReport),
+    /// Commits grouped under their authors (`:authors`)
+    Authors(Box<AuthorGroups>),
+    /// The new hashes applying would give the commits (`P`)
+    Preview(Box<HashPreview>),
+    /// The undo history, to see and jump between steps (`U`)
+    UndoHistory(UndoBrowser),
+    /// Backup refs of every branch, to restore or delete (`:backups`)
+    Backups(BackupList),
     /// Help screen
     Help,
     /// Quitting (confirm if dirty)
     Quitting,
 }
 
+/// Progress through a step-through apply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepApply {
+    /// Commits with pending changes, oldest first (the order they are rewritten in)
+    pub queue: Vec<CommitId>,
+    /// Position in `queue` of the commit under review
+    pub index: usize,
+    /// Commits whose changes are held back from this apply
+    pub skipped: Vec<CommitId>,
+}
+
+/// A rewrite whose auto-stashed changes could not be restored on top of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashConflict {
+    /// Paths the stashed changes conflict on
+    pub paths: Vec<String>,
+    /// Branch tip before the rewrite
+    pub old_tip: CommitId,
+    /// The `--from` start point before the rewrite
+    pub old_start: Option<CommitId>,
+    /// Old commit ID -> new commit ID, as returned by the rewrite
+    pub commit_map: HashMap<CommitId, CommitId>,
+    /// Pending changes as they were when the apply started
+    pub pending: UndoSnapshot,
+    /// Other branches moved by the rewrite, with their tips before it
+    pub moved_branches: Vec<(String, CommitId)>,
+    /// Tags moved by the rewrite, as they were before it
+    pub moved_tags: Vec<TagRef>,
+}
+
+/// Trailers of the commits being edited (`T`, or co-authors only with `A`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrailerEditor {
+    /// Commits that changes apply to
+    pub targets: Vec<CommitId>,
+    /// Only trailers with this key (`Co-authored-by` for `A`); input is then
+    /// just the value
+    pub key: Option<String>,
+    /// Distinct trailers, with how many targets have each
+    pub trailers: Vec<(Trailer, usize)>,
+    /// Index of the highlighted trailer
+    pub cursor: usize,
+    /// Trailer being typed: a new one (`a`) or a change of one (`e`)
+    pub input: Option<String>,
+    /// The trailer `input` replaces, if it's a change
+    pub editing: Option<Trailer>,
+}
+
+impl TrailerEditor {
+    /// How a trailer is shown and typed: its value when the key is fixed
+    #[must_use]
+    pub fn display(&self, trailer: &Trailer) -> String {
+        match self.key {
+            Some(_) => trailer.value.clone(),
+            None => trailer.to_string(),
+        }
+    }
+}
+
+/// Local branches to choose from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchPicker {
+    /// Branch names, sorted
+    pub branches: Vec<String>,
+    /// Index of the highlighted branch
+    pub cursor: usize,
+}
+
+/// Identities to set as the author of the targeted commits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityPicker {
+    /// Commits to set the author of
+    pub targets: Vec<CommitId>,
+    /// Labeled identities: git config ones, then profiles
+    pub identities: Vec<(String, Person)>,
+    /// Index of the highlighted identity
+    pub cursor: usize,
+}
+
+/// Commits whose author and committer dates differ by more than a threshold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivergenceReport {
+    /// Listed commits in display order, with committer date minus author date
+    pub rows: Vec<(CommitId, TimeDelta)>,
+    /// Index of the highlighted row
+    pub cursor: usize,
+    /// Differences up to this are not listed
+    pub threshold: TimeDelta,
+}
+
+/// Commits whose subjects aren't Conventional Commits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintReport {
+    /// Listed commits in display order, with what's wrong with each
+    pub rows: Vec<(CommitId, LintIssue)>,
+    /// Index of the highlighted row
+    pub cursor: usize,
+    /// Index in `COMMIT_TYPES` of the type the quick fix gives
+    pub kind: usize,
+}
+
+/// Backup refs listed by `:backups`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupList {
+    /// Backups by branch, oldest first
+    pub rows: Vec<BackupRef>,
+    /// Index of the highlighted row
+    pub cursor: usize,
+}
+
+/// Position in the undo history browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoBrowser {
+    /// Index of the highlighted row of `AppState::undo_history`
+    pub cursor: usize,
+}
+
+/// What applying the pending changes would turn each commit's hash into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashPreview {
+    /// Loaded commits in their new order with their new IDs (None: deleted)
+    pub rows: Vec<(CommitId, Option<CommitId>)>,
+    /// How many newer commits above the loaded ones get new hashes too
+    pub descendants: usize,
+    /// The branch's new tip
+    pub tip: Option<CommitId>,
+    /// Whether the commits will be signed, which changes their hashes again
+    pub signed: bool,
+    /// Index of the highlighted row
+    pub cursor: usize,
+}
+
+/// Commits grouped by author, for fixing an identity in one edit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorGroups {
+    /// Each author with their commits, most commits first
+    pub groups: Vec<(Person, Vec<CommitId>)>,
+    /// Index of the highlighted group
+    pub cursor: usize,
+    /// `Name <email>` being typed for the highlighted group
+    pub input: Option<String>,
+}
+
+/// The text the find/replace dialog works on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceTarget {
+    /// The message of the commit at this visible row, staged as an edit
+    Message { commit_idx: usize },
+    /// The inline editor's buffer; the editor comes back afterwards
+    EditBuffer {
+        commit_idx: usize,
+        field: EditableField,
+    },
+    /// The second commit's message in the split chooser, which comes back
+    /// afterwards
+    SplitMessage(Box<SplitChooser>),
+}
+
+/// Files of a commit being split, and which half each goes into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitChooser {
+    pub commit_id: CommitId,
+    /// Changed files as `(status, path)`
+    pub files: Vec<(char, String)>,
+    /// Per file: whether it goes into the first (older) commit
+    pub first: Vec<bool>,
+    /// Index of the highlighted file
+    pub cursor: usize,
+    /// Message of the second commit
+    pub message: String,
+    /// Whether keys are typed into `message`
+    pub editing_message: bool,
+}
+
+impl SplitChooser {
+    /// Paths going into the first commit
+    #[must_use]
+    pub fn first_paths(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .zip(&self.first)
+            .filter(|(_, first)| **first)
+            .map(|((_, path), _)| path.clone())
+            .collect()
+    }
+}
+
+/// A commit's pending changes, taken out of the state for a while
+#[derive(Debug, Clone, Default)]
+pub struct HeldChanges {
+    pub modifications: Option<CommitModifications>,
+    pub deleted: bool,
+    pub squash: Option<SquashKind>,
+    pub split: Option<CommitSplit>,
+    pub note: Option<String>,
+}
+
 /// Actions that require confirmation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfirmAction {
     ApplyChanges,
     DiscardChanges,
+    /// Edit commits authored by someone other than the current user, then resume the edit
+    EditOthersCommits {
+        authors: Vec<String>,
+        resume: PendingEdit,
+    },
+    /// Fill the other half of a known identity (e.g. the email for a name) on these commits
+    AutoFillIdentity {
+        field: EditableField,
+        value: String,
+        targets: Vec<CommitId>,
+    },
+    /// Mark several commits for deletion at once
+    DeleteCommits {
+        ids: Vec<CommitId>,
+    },
+    /// Also delete later commits that revert or mention the commits just deleted
+    DeleteReferencing {
+        /// `(referencing, referenced)` pairs
+        pairs: Vec<(CommitId, CommitId)>,
+    },
+    /// Pick up the session saved when quitting last time
+    ResumeSession(Box<Session>),
+    /// Restore the changes of a session that didn't exit cleanly
+    Recover(Box<Session>),
+    /// Point the branch at a backup (`:backups`)
+    RestoreBackup(BackupRef),
+    /// Delete a backup ref (`:backups`)
+    DeleteBackup(BackupRef),
     #[allow(dead_code)]
     QuitWithChanges,
 }
 
+/// An edit waiting on a confirmation before it starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingEdit {
+    /// Inline (or external editor) edit of the cursor column; `external`
+    /// opens the editor whatever the column (`E`)
+    InlineEdit { external: bool },
+    /// Find/replace in the cursor commit's message
+    Replace,
+    /// Fill the cursor cell's value into the captured visual edit targets
+    Fill,
+    /// Paste a register into the cursor column of the commits to edit (`p`)
+    Paste { register: Option<char> },
+    /// Snap dates on the selected commits (`:snap`)
+    Snap { step: i64, mode: SnapMode },
+    /// Shift dates on the selected commits (`:shift`)
+    Shift { seconds: i64 },
+    /// Spread dates on the selected commits over a range (`:spread`)
+    Spread {
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        jitter: i64,
+    },
+    /// Put dates back in order (`M`, `:fixdates`)
+    FixDates(DateFix),
+    /// Convert dates on the selected commits to a UTC offset (`:tz`)
+    Timezone { offset: i32 },
+    /// Set the selected commits' author to the current identity (`:me`)
+    Me,
+    /// Set the selected commits' author (`:author`)
+    Author(Person),
+    /// Set the selected commits' author date (`:date`)
+    Date(DateTime<FixedOffset>),
+    /// Open the identity picker (`i`)
+    IdentityPicker,
+    /// Add `Signed-off-by` trailers (`:signoff`)
+    SignOff { all: bool },
+    /// Rewrap message bodies (`:reflow`)
+    Reflow { width: usize },
+    /// Open the trailer editor (`T`), or for co-authors only (`A`)
+    Trailers { co_authors: bool },
+    /// Change the author of one group in the author view (`:authors`)
+    AuthorGroup { row: usize },
+    /// Give commits a type (`COMMIT_TYPES` index): from the lint report
+    /// (`row`), or from `:lint <type>` (None)
+    CommitType { kind: usize, row: Option<usize> },
+    /// Normalize dates from the divergence report: one row, or every row (None)
+    NormalizeDates {
+        source: DateSource,
+        row: Option<usize>,
+        threshold: TimeDelta,
+    },
+}
+
 /// Snapshot of state for undo/redo
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UndoSnapshot {
     pub commit_order: Vec<CommitId>,
     pub modifications: HashMap<CommitId, CommitModifications>,
     pub deleted: HashSet<CommitId>,
+    #[serde(default)]
+    pub squashes: HashMap<CommitId, SquashKind>,
+    #[serde(default)]
+    pub splits: HashMap<CommitId, CommitSplit>,
+    #[serde(default)]
+    pub notes: HashMap<CommitId, String>,
+    #[serde(default)]
+    pub new_root: Option<NewRoot>,
     pub description: String,
 }
 
+/// What the `fixup!`/`squash!` commits refer to, worked out once per commit list
+#[derive(Debug, Default)]
+struct Fixups {
+    /// Each commit's target (index in `commits`), for fixups that have one
+    targets: Vec<Option<(usize, SquashKind)>>,
+    /// Commits some fixup refers to
+    targeted: HashSet<CommitId>,
+}
+
 /// Central application state
 pub struct AppState {
     /// All loaded commits in current display order
@@ -79,6 +611,19 @@ pub struct AppState {
     /// Commits marked for deletion
     pub deleted: HashSet<CommitId>,
 
+    /// Commits staged to be folded into the commit below them (autosquash)
+    pub squashes: HashMap<CommitId, SquashKind>,
+
+    /// Commits to split in two
+    pub splits: HashMap<CommitId, CommitSplit>,
+
+    /// Why a commit is being changed (`:note`), for the review screen and
+    /// the rewrite report
+    pub notes: HashMap<CommitId, String>,
+
+    /// A commit to add beneath the root commit (`:newroot`)
+    pub new_root: Option<NewRoot>,
+
     /// Index of the cursor (focused commit in visible list)
     pub cursor: usize,
 
@@ -88,15 +633,40 @@ pub struct AppState {
     /// Current search/filter query
     pub search_query: String,
 
-    /// Filtered commit indices (None = show all)
+    /// Last submitted search query (kept for n/N after the filter is cleared)
+    pub last_search: String,
+
+    /// Visible commit indices, filtered and sorted (None = show all in order)
     pub filtered_indices: Option<Vec<usize>>,
 
+    /// View-only row order; `current_order` (what gets applied) is untouched
+    pub sort: Option<SortOrder>,
+
+    /// Commits touching each path of the searches' `path:` filters (filled in
+    /// from the repository, which the state can't reach)
+    pub touched_paths: HashMap<TouchedPath, HashSet<CommitId>>,
+
+    /// Files changed by each commit shown in the detail pane so far (loaded
+    /// from the repository on first view)
+    pub file_changes: HashMap<CommitId, Vec<FileChange>>,
+
+    /// Highlight group patterns, each shown in its own color in the table
+    pub highlights: Vec<String>,
+
+    /// Yanked cell values by register name (`y`, `p`); `"` is the unnamed
+    /// register, which every yank also fills
+    pub registers: HashMap<char, String>,
+
     /// Undo stack
     pub undo_stack: Vec<UndoSnapshot>,
 
     /// Redo stack
     pub redo_stack: Vec<UndoSnapshot>,
 
+    /// Bumped whenever the pending changes may have changed, so the crash
+    /// recovery snapshot is only rebuilt after an edit
+    pub revision: u64,
+
     /// Scroll offset for table (vertical)
     pub scroll_offset: usize,
 
@@ -127,6 +697,15 @@ pub struct AppState {
     /// Cursor position within the edit buffer
     pub edit_cursor: usize,
 
+    /// Prior edit buffer states (buffer, cursor) for Ctrl+Z within the inline editor
+    pub edit_undo_stack: Vec<(String, usize)>,
+
+    /// Undone edit buffer states for Ctrl+Y within the inline editor
+    pub edit_redo_stack: Vec<(String, usize)>,
+
+    /// Whether the last buffer change was typing (a run of typing undoes as one step)
+    edit_typing: bool,
+
     /// Commits targeted by visual selection for editing
     /// Set when pressing 'e' in visual mode, cleared after edit completes
     pub visual_edit_targets: Option<Vec<CommitId>>,
@@ -145,6 +724,97 @@ pub struct AppState {
 
     /// Scroll offset for help screen (vertical)
     pub help_scroll: usize,
+
+    /// Current git author identity (for the ownership warning and `:me`)
+    pub user: Option<Person>,
+
+    /// Warn before editing commits authored by someone else (from config)
+    pub warn_foreign_commits: bool,
+
+    /// Whether the ownership warning was acknowledged this session
+    pub ownership_acknowledged: bool,
+
+    /// Commits committed longer ago than this are read-only (from config)
+    pub lock_age: Option<TimeDelta>,
+
+    /// Locked commits made editable this session (`:unlock`)
+    pub unlocked: HashSet<CommitId>,
+
+    /// Move other local branches built on the rewritten commits along with
+    /// the edited branch (from config; `b` in the apply dialog toggles it)
+    pub remap_branches: bool,
+
+    /// Other local branches built on the loaded commits, as of the last time
+    /// the apply dialog was opened
+    pub dependent_branches: Vec<String>,
+
+    /// Re-point tags of rewritten commits at the new commits (from config;
+    /// `t` in the apply dialog toggles it)
+    pub migrate_tags: bool,
+
+    /// Tags naming rewritten commits, as of the last time the apply dialog
+    /// was opened
+    pub tags_to_move: Vec<String>,
+
+    /// Sign the rewritten commits (`--sign`, `commit.gpgsign`; `s` in the
+    /// apply dialog toggles it)
+    pub sign_commits: bool,
+
+    /// The configured signing key, described for the apply dialog
+    pub signing_key: Option<String>,
+
+    /// Skip the safety checks (`--force`): stashing, the age lock, pushed
+    /// commit protection and email validation
+    pub force: bool,
+
+    /// Checks `force` skipped since the last apply, logged in its summary
+    pub bypassed: Vec<Bypass>,
+
+    /// Short hashes of the signed commits the rewrite would replace, as of
+    /// the last time the apply dialog was opened
+    pub signed_to_rewrite: Vec<String>,
+
+    /// Author/committer date differences larger than this are reported by
+    /// `:divergence` (from config)
+    pub divergence_threshold: TimeDelta,
+
+    /// Ask before marking more than this many commits for deletion at once
+    /// (from config)
+    pub delete_confirm_threshold: usize,
+
+    /// Show the `HEAD~n` column
+    pub show_head_offset: bool,
+
+    /// Show the time gap column
+    pub show_time_gap: bool,
+
+    /// Show the commit graph column (`:set graph`)
+    pub show_graph: bool,
+
+    /// Show absolute row numbers (`:set number`)
+    pub show_number: bool,
+
+    /// Show row numbers relative to the cursor (`:set relativenumber`)
+    pub show_relative_number: bool,
+
+    /// Display dates in the local timezone (`:set localtime`); edits still
+    /// start from, and keep, the recorded offsets
+    pub show_local_time: bool,
+
+    /// Mark messages that aren't Conventional Commits (`:set lint`)
+    pub show_lint: bool,
+
+    /// Number of commits between HEAD and the first loaded commit (`--from`)
+    pub head_offset_base: usize,
+
+    /// Older commits are still being loaded in the background
+    pub loading: bool,
+
+    /// Fixup targets, filled in on first use after `commits` changed
+    fixups: OnceCell<Fixups>,
+
+    /// Graph column rows, filled in on first use after `commits` changed
+    graph: OnceCell<Vec<String>>,
 }
 
 impl AppState {
@@ -161,12 +831,23 @@ impl AppState {
             modifications: HashMap::new(),
             selected: HashSet::new(),
             deleted: HashSet::new(),
+            squashes: HashMap::new(),
+            splits: HashMap::new(),
+            notes: HashMap::new(),
+            new_root: None,
             cursor: 0,
             mode: AppMode::Normal,
             search_query: String::new(),
+            last_search: String::new(),
             filtered_indices: None,
+            sort: None,
+            touched_paths: HashMap::new(),
+            file_changes: HashMap::new(),
+            highlights: Vec::new(),
+            registers: HashMap::new(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            revision: 0,
             scroll_offset: 0,
             h_scroll_offset: 0,
             column_index: 0,
@@ -177,11 +858,41 @@ impl AppState {
             edit_buffer: String::new(),
             edit_original: String::new(),
             edit_cursor: 0,
+            edit_undo_stack: Vec::new(),
+            edit_redo_stack: Vec::new(),
+            edit_typing: false,
             visual_edit_targets: None,
             detail_scroll: 0,
             detail_max_scroll: 0,
             sync_author_to_committer: true,
             help_scroll: 0,
+            user: None,
+            warn_foreign_commits: true,
+            ownership_acknowledged: false,
+            lock_age: None,
+            unlocked: HashSet::new(),
+            remap_branches: true,
+            dependent_branches: Vec::new(),
+            migrate_tags: true,
+            tags_to_move: Vec::new(),
+            sign_commits: false,
+            signing_key: None,
+            force: false,
+            bypassed: Vec::new(),
+            signed_to_rewrite: Vec::new(),
+            divergence_threshold: TimeDelta::hours(24),
+            delete_confirm_threshold: 1,
+            show_head_offset: false,
+            show_time_gap: false,
+            show_graph: false,
+            show_number: false,
+            show_relative_number: false,
+            show_local_time: false,
+            show_lint: false,
+            head_offset_base: 0,
+            loading: false,
+            fixups: OnceCell::new(),
+            graph: OnceCell::new(),
         }
     }
 
@@ -283,7 +994,7 @@ impl AppState {
     /// Set cursor to a specific row (clamped to valid range)
     #[allow(dead_code)]
     pub fn set_cursor_row(&mut self, row: usize) {
-        let max = self.visible_commits().len().saturating_sub(1);
+        let max = self.visible_len().saturating_sub(1);
         self.cursor = row.min(max);
         self.adjust_scroll();
     }
@@ -331,16 +1042,276 @@ impl AppState {
         self.h_scroll_offset = (self.h_scroll_offset + amount).min(max_scroll);
     }
 
-    /// Get the visible commits (filtered or all)
+    /// Number of visible rows (filtered or all)
+    #[must_use]
+    pub fn visible_len(&self) -> usize {
+        self.filtered_indices
+            .as_ref()
+            .map_or(self.commits.len(), Vec::len)
+    }
+
+    /// The commit on a visible row
+    #[must_use]
+    pub fn visible_commit(&self, visible_row: usize) -> Option<&CommitData> {
+        self.commits.get(self.commit_index(visible_row)?)
+    }
+
+    /// The visible commits in order (filtered or all), looked up row by row
+    /// rather than collected
+    pub fn visible_iter(&self) -> impl Iterator<Item = &CommitData> + '_ {
+        (0..self.visible_len()).filter_map(|row| self.visible_commit(row))
+    }
+
+    /// Index into `commits` of a visible row
     #[must_use]
-    pub fn visible_commits(&self) -> Vec<&CommitData> {
+    pub fn commit_index(&self, visible_row: usize) -> Option<usize> {
         match &self.filtered_indices {
-            Some(indices) => indices
-                .iter()
-                .filter_map(|&i| self.commits.get(i))
-                .collect(),
-            None => self.commits.iter().collect(),
+            Some(indices) => indices.get(visible_row).copied(),
+            None => (visible_row < self.commits.len()).then_some(visible_row),
+        }
+    }
+
+    /// A visible row's position relative to HEAD in the current order (`HEAD~n`)
+    #[must_use]
+    pub fn head_offset_label(&self, visible_row: usize) -> Option<String> {
+        let offset = self.commit_index(visible_row)? + self.head_offset_base;
+        Some(if offset == 0 {
+            "HEAD".to_string()
+        } else {
+            format!("HEAD~{offset}")
+        })
+    }
+
+    /// A date as it should be displayed: the same instant, in the local
+    /// timezone when `:set localtime` is on
+    #[must_use]
+    pub fn display_date(&self, date: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        if self.show_local_time {
+            date.with_timezone(&Local).fixed_offset()
+        } else {
+            date
+        }
+    }
+
+    /// Whether the leading line number column is shown
+    #[must_use]
+    pub const fn shows_line_numbers(&self) -> bool {
+        self.show_number || self.show_relative_number
+    }
+
+    /// A visible row's line number, as vim would show it
+    ///
+    /// Absolute numbers count from 1; relative ones are the distance from the
+    /// cursor. With both on, the cursor row shows its absolute number.
+    #[must_use]
+    pub fn line_number_label(&self, visible_row: usize) -> Option<String> {
+        if visible_row == self.cursor && self.show_number {
+            return Some((visible_row + 1).to_string());
+        }
+        if self.show_relative_number {
+            return Some(visible_row.abs_diff(self.cursor).to_string());
+        }
+        self.show_number.then(|| (visible_row + 1).to_string())
+    }
+
+    /// Time between a visible row's (possibly edited) author date and that of the
+    /// next older commit in the current order, skipping commits marked for deletion
+    ///
+    /// Negative when the commit is dated before its predecessor.
+    #[must_use]
+    pub fn time_gap(&self, visible_row: usize) -> Option<TimeDelta> {
+        let idx = self.commit_index(visible_row)?;
+        let commit = &self.commits[idx];
+        if self.is_deleted(commit.id) {
+            return None;
+        }
+        let older = self.commits[idx + 1..]
+            .iter()
+            .find(|c| !self.is_deleted(c.id))?;
+        Some(self.effective_author_date(commit) - self.effective_author_date(older))
+    }
+
+    /// A commit's author date, including any pending edit
+    fn effective_author_date(&self, commit: &CommitData) -> DateTime<FixedOffset> {
+        self.modifications
+            .get(&commit.id)
+            .and_then(|m| m.author_date)
+            .unwrap_or(commit.author_date)
+    }
+
+    /// A commit's committer date, including any pending edit
+    fn effective_committer_date(&self, commit: &CommitData) -> DateTime<FixedOffset> {
+        self.modifications
+            .get(&commit.id)
+            .and_then(|m| m.committer_date)
+            .unwrap_or(commit.committer_date)
+    }
+
+    /// Commits (not marked for deletion) whose effective committer date is
+    /// more than `threshold` away from their author date, newest first, with
+    /// the committer date minus the author date
+    #[must_use]
+    pub fn date_divergences(&self, threshold: TimeDelta) -> Vec<(CommitId, TimeDelta)> {
+        self.commits
+            .iter()
+            .filter(|c| !self.is_deleted(c.id))
+            .filter_map(|c| {
+                let diff = self.effective_committer_date(c) - self.effective_author_date(c);
+                (diff.abs() > threshold).then_some((c.id, diff))
+            })
+            .collect()
+    }
+
+    /// What's wrong with a commit's (effective) subject as a Conventional
+    /// Commit; merges are not checked
+    #[must_use]
+    pub fn lint_issue(&self, commit: &CommitData) -> Option<LintIssue> {
+        if commit.is_merge {
+            return None;
+        }
+        let summary = self
+            .modifications
+            .get(&commit.id)
+            .map_or(commit.summary.as_str(), |m| {
+                m.effective_summary(&commit.summary)
+            });
+        conventional::lint_subject(summary)
+    }
+
+    /// Commits (not marked for deletion) whose subjects aren't Conventional
+    /// Commits, newest first
+    #[must_use]
+    pub fn lint_issues(&self) -> Vec<(CommitId, LintIssue)> {
+        self.commits
+            .iter()
+            .filter(|c| !self.is_deleted(c.id))
+            .filter_map(|c| Some((c.id, self.lint_issue(c)?)))
+            .collect()
+    }
+
+    /// Give commits' subjects the type `kind`, replacing a type already in
+    /// place (see `conventional::with_type`)
+    ///
+    /// Commits that already have that type are left alone. Saves one undo
+    /// snapshot if anything changes. Returns how many changed.
+    pub fn set_commit_type(&mut self, ids: &[CommitId], kind: &str) -> usize {
+        let changes: Vec<(CommitId, String)> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id && !c.is_merge)?;
+                let message = self
+                    .modifications
+                    .get(&id)
+                    .and_then(|m| m.message.clone())
+                    .unwrap_or_else(|| commit.message.clone());
+                let (subject, body) = message.split_once('\n').unwrap_or((&message, ""));
+                let fixed = conventional::with_type(subject, kind);
+                if fixed == subject && conventional::lint_subject(subject).is_none() {
+                    return None;
+                }
+                let new_message = if message.contains('\n') {
+                    format!("{fixed}\n{body}")
+                } else {
+                    fixed
+                };
+                (new_message != message).then_some((id, new_message))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+        self.save_undo(&format!("Set commit type to {kind}"));
+        for (id, message) in &changes {
+            self.get_or_create_modifications(*id).message = Some(message.clone());
+        }
+        changes.len()
+    }
+
+    /// Editable commits grouped by their current author, the authors with
+    /// the most commits first
+    #[must_use]
+    pub fn author_groups(&self) -> Vec<(Person, Vec<CommitId>)> {
+        let mut groups: Vec<(Person, Vec<CommitId>)> = Vec::new();
+        for commit in &self.commits {
+            if commit.is_merge || self.is_deleted(commit.id) {
+                continue;
+            }
+            let mods = self.modifications.get(&commit.id);
+            let author = Person::new(
+                mods.map_or(commit.author.name.as_str(), |m| {
+                    m.effective_author_name(&commit.author.name)
+                }),
+                mods.map_or(commit.author.email.as_str(), |m| {
+                    m.effective_author_email(&commit.author.email)
+                }),
+            );
+            match groups.iter_mut().find(|(person, _)| *person == author) {
+                Some((_, ids)) => ids.push(commit.id),
+                None => groups.push((author, vec![commit.id])),
+            }
+        }
+        groups.sort_by(|(a, a_ids), (b, b_ids)| {
+            b_ids
+                .len()
+                .cmp(&a_ids.len())
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        groups
+    }
+
+    /// Make each commit's author and committer dates equal, keeping the one
+    /// from `source`
+    ///
+    /// Returns how many commits changed.
+    pub fn normalize_dates(&mut self, ids: &[CommitId], source: DateSource) -> usize {
+        let changes: Vec<(CommitId, DateTime<FixedOffset>)> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id)?;
+                let author = self.effective_author_date(commit);
+                let committer = self.effective_committer_date(commit);
+                let date = match source {
+                    DateSource::Author => author,
+                    DateSource::Committer => committer,
+                };
+                (author != committer).then_some((id, date))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
         }
+
+        let which = match source {
+            DateSource::Author => "author",
+            DateSource::Committer => "committer",
+        };
+        self.save_undo(&format!(
+            "Normalize dates to the {which} date on {} commit(s)",
+            changes.len()
+        ));
+        for &(id, date) in &changes {
+            let mods = self.get_or_create_modifications(id);
+            match source {
+                DateSource::Author => mods.committer_date = Some(date),
+                DateSource::Committer => mods.author_date = Some(date),
+            }
+        }
+        changes.len()
+    }
+
+    /// Set a display option (`None` toggles it), returning its new value
+    pub fn set_display_option(&mut self, option: DisplayOption, value: Option<bool>) -> bool {
+        let flag = match option {
+            DisplayOption::HeadOffset => &mut self.show_head_offset,
+            DisplayOption::TimeGap => &mut self.show_time_gap,
+            DisplayOption::Number => &mut self.show_number,
+            DisplayOption::RelativeNumber => &mut self.show_relative_number,
+            DisplayOption::LocalTime => &mut self.show_local_time,
+            DisplayOption::Graph => &mut self.show_graph,
+            DisplayOption::Lint => &mut self.show_lint,
+        };
+        *flag = value.unwrap_or(!*flag);
+        *flag
     }
 
     /// Get the commit at the cursor position
@@ -395,7 +1366,17 @@ impl AppState {
         }
     }
 
-    /// Mark a specific commit for deletion
+    /// The confirmation to ask for before marking `ids` for deletion, when
+    /// there are more than `delete_confirm_threshold` of them
+    ///
+    /// A stray `d` with a big selection shouldn't mark everything silently.
+    #[must_use]
+    pub fn delete_confirmation(&self, ids: &[CommitId]) -> Option<ConfirmAction> {
+        (ids.len() > self.delete_confirm_threshold)
+            .then(|| ConfirmAction::DeleteCommits { ids: ids.to_vec() })
+    }
+
+    /// Mark a specific commit for deletion
     pub fn mark_deleted(&mut self, id: CommitId) {
         self.deleted.insert(id);
     }
@@ -416,6 +1397,44 @@ impl AppState {
         self.deleted.clear();
     }
 
+    /// Resolve a full or abbreviated hash to an index into `commits`
+    ///
+    /// Matching is a case-insensitive prefix match against loaded commits.
+    pub fn resolve_hash(&self, hash: &str) -> Result<usize> {
+        let prefix = hash.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Err(HistError::CommitNotFound(hash.to_string()));
+        }
+
+        let mut matches = self
+            .commits
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.id.0.to_string().starts_with(&prefix))
+            .map(|(i, _)| i);
+
+        match (matches.next(), matches.next()) {
+            (Some(idx), None) => Ok(idx),
+            (Some(_), Some(_)) => Err(HistError::AmbiguousHash(hash.to_string())),
+            (None, _) => Err(HistError::CommitNotFound(hash.to_string())),
+        }
+    }
+
+    /// Select all commits between two hashes (inclusive, in current order)
+    ///
+    /// The endpoints may be given in either order. Returns the number of
+    /// commits in the range.
+    pub fn select_range_by_hash(&mut self, from: &str, to: &str) -> Result<usize> {
+        let from_idx = self.resolve_hash(from)?;
+        let to_idx = self.resolve_hash(to)?;
+        let (start, end) = (from_idx.min(to_idx), from_idx.max(to_idx));
+
+        let ids: Vec<CommitId> = self.commits[start..=end].iter().map(|c| c.id).collect();
+        let count = ids.len();
+        self.selected.extend(ids);
+        Ok(count)
+    }
+
     /// Toggle selection of the commit at cursor
     pub fn toggle_selection(&mut self) {
         if let Some(id) = self.cursor_commit_id() {
@@ -429,7 +1448,7 @@ impl AppState {
 
     /// Select all visible commits
     pub fn select_all(&mut self) {
-        let ids: Vec<_> = self.visible_commits().iter().map(|c| c.id).collect();
+        let ids: Vec<_> = self.visible_iter().map(|c| c.id).collect();
         for id in ids {
             self.selected.insert(id);
         }
@@ -451,7 +1470,7 @@ impl AppState {
 
     /// Move cursor down
     pub fn cursor_down(&mut self) {
-        let max = self.visible_commits().len().saturating_sub(1);
+        let max = self.visible_len().saturating_sub(1);
         if self.cursor < max {
             self.cursor += 1;
             self.adjust_scroll();
@@ -468,7 +1487,7 @@ impl AppState {
 
     /// Move cursor to bottom
     pub fn cursor_bottom(&mut self) {
-        self.cursor = self.visible_commits().len().saturating_sub(1);
+        self.cursor = self.visible_len().saturating_sub(1);
         self.adjust_scroll();
         self.reset_detail_scroll();
     }
@@ -482,7 +1501,7 @@ impl AppState {
 
     /// Page down
     pub fn page_down(&mut self, page_size: usize) {
-        let max = self.visible_commits().len().saturating_sub(1);
+        let max = self.visible_len().saturating_sub(1);
         self.cursor = (self.cursor + page_size).min(max);
         self.adjust_scroll();
         self.reset_detail_scroll();
@@ -510,12 +1529,24 @@ impl AppState {
         }
     }
 
+    /// Re-fit the cursor and scroll offset after the table height changed
+    ///
+    /// Keeps the cursor in range and avoids blank rows below the last commit
+    /// when the table grew.
+    pub fn fit_to_height(&mut self, height: usize) {
+        let len = self.visible_len();
+        self.cursor = self.cursor.min(len.saturating_sub(1));
+        self.scroll_offset = self.scroll_offset.min(len.saturating_sub(height));
+        self.update_scroll_for_height(height);
+    }
+
     /// Move commit at cursor up (for reordering)
     pub fn move_commit_up(&mut self) {
         if self.cursor > 0 && self.filtered_indices.is_none() {
             self.save_undo("Reorder commits");
             self.current_order.swap(self.cursor, self.cursor - 1);
             self.commits.swap(self.cursor, self.cursor - 1);
+            self.commits_changed();
             self.cursor -= 1;
         }
     }
@@ -526,878 +1557,4156 @@ impl AppState {
             self.save_undo("Reorder commits");
             self.current_order.swap(self.cursor, self.cursor + 1);
             self.commits.swap(self.cursor, self.cursor + 1);
+            self.commits_changed();
             self.cursor += 1;
         }
     }
 
-    /// Apply search filter
-    pub fn apply_filter(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_indices = None;
-            return;
+    /// Append older commits loaded beyond the current limit
+    ///
+    /// They go at the end of the list in their original order, keeping all
+    /// pending changes to the already-loaded commits (older commits always
+    /// come after them, however they were reordered). Commits already loaded
+    /// are skipped, so an overlapping page can't list a commit twice.
+    pub fn extend_commits(&mut self, older: Vec<CommitData>) {
+        let loaded: HashSet<CommitId> = self.original_order.iter().copied().collect();
+        for commit in older.into_iter().filter(|c| !loaded.contains(&c.id)) {
+            self.original_order.push(commit.id);
+            self.current_order.push(commit.id);
+            self.commits.push(commit);
         }
+        self.commits_changed();
+        self.apply_filter();
+    }
 
-        let query = self.search_query.to_lowercase();
-        let indices: Vec<usize> = self
-            .commits
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| {
-                c.author.name.to_lowercase().contains(&query)
-                    || c.author.email.to_lowercase().contains(&query)
-                    || c.message.to_lowercase().contains(&query)
-                    || c.short_hash.to_lowercase().contains(&query)
-            })
-            .map(|(i, _)| i)
-            .collect();
+    /// Drop loaded commits beyond the first `limit` (in original order)
+    ///
+    /// Returns the number of commits dropped.
+    ///
+    /// # Errors
+    /// Returns an error if any dropped commit has pending changes or was moved.
+    pub fn truncate_commits(&mut self, limit: usize) -> Result<usize> {
+        if limit >= self.original_order.len() {
+            return Ok(0);
+        }
 
-        self.filtered_indices = if indices.is_empty() {
-            None
-        } else {
-            Some(indices)
-        };
-        self.cursor = 0;
-        self.scroll_offset = 0;
+        let dropped = &self.original_order[limit..];
+        let untouched = self.current_order.ends_with(dropped)
+            && dropped.iter().all(|id| {
+                !self.is_modified(*id)
+                    && !self.deleted.contains(id)
+                    && !self.squashes.contains_key(id)
+                    && !self.splits.contains_key(id)
+            });
+        if !untouched {
+            return Err(HistError::InvalidCommand(
+                "commits beyond the new limit have pending changes".to_string(),
+            ));
+        }
+
+        let count = dropped.len();
+        let dropped: HashSet<CommitId> = dropped.iter().copied().collect();
+        let cursor_dropped = self
+            .cursor_commit_id()
+            .is_some_and(|id| dropped.contains(&id));
+        self.original_order.truncate(limit);
+        self.current_order
+            .truncate(self.current_order.len() - count);
+        self.commits.retain(|c| !dropped.contains(&c.id));
+        self.commits_changed();
+        self.selected.retain(|id| !dropped.contains(id));
+
+        self.apply_filter();
+        if cursor_dropped {
+            self.cursor_bottom();
+        }
+        Ok(count)
     }
 
-    /// Clear search filter
-    pub fn clear_filter(&mut self) {
-        self.search_query.clear();
-        self.filtered_indices = None;
+    /// Get the position of a commit in the visible list, if it is visible
+    #[must_use]
+    pub fn visible_index_of(&self, id: CommitId) -> Option<usize> {
+        match &self.filtered_indices {
+            Some(indices) => indices
+                .iter()
+                .position(|&i| self.commits.get(i).is_some_and(|c| c.id == id)),
+            None => self.commits.iter().position(|c| c.id == id),
+        }
     }
 
-    /// Save current state to undo stack
-    pub fn save_undo(&mut self, description: &str) {
-        let snapshot = UndoSnapshot {
-            commit_order: self.current_order.clone(),
-            modifications: self.modifications.clone(),
-            deleted: self.deleted.clone(),
-            description: description.to_string(),
+    /// Count how many of the given commits are hidden by the current filter
+    #[must_use]
+    pub fn hidden_count(&self, ids: &[CommitId]) -> usize {
+        self.count_hidden(ids.iter())
+    }
+
+    /// Count selected commits that are hidden by the current filter
+    #[must_use]
+    pub fn hidden_selected_count(&self) -> usize {
+        self.count_hidden(self.selected.iter())
+    }
+
+    /// Count the commits not on a visible row, looking each one up in a set
+    /// of the visible commits rather than scanning the rows for it
+    fn count_hidden<'a>(&self, ids: impl Iterator<Item = &'a CommitId>) -> usize {
+        let Some(indices) = &self.filtered_indices else {
+            return 0;
         };
-        self.undo_stack.push(snapshot);
-        self.redo_stack.clear(); // Clear redo stack on new change
+        let visible: HashSet<CommitId> = indices
+            .iter()
+            .filter_map(|&i| self.commits.get(i).map(|c| c.id))
+            .collect();
+        ids.filter(|id| !visible.contains(id)).count()
     }
 
-    /// Undo last change
-    pub fn undo(&mut self) -> bool {
-        if let Some(snapshot) = self.undo_stack.pop() {
-            // Save current state to redo stack
-            let current = UndoSnapshot {
-                commit_order: self.current_order.clone(),
-                modifications: self.modifications.clone(),
-                deleted: self.deleted.clone(),
-                description: snapshot.description.clone(),
-            };
-            self.redo_stack.push(current);
+    /// Move the cursor onto a commit if visible, otherwise to the top
+    fn restore_cursor_to(&mut self, id: Option<CommitId>) {
+        self.cursor = id.and_then(|id| self.visible_index_of(id)).unwrap_or(0);
+        self.scroll_offset = self.scroll_offset.min(self.cursor);
+        self.adjust_scroll();
+        self.reset_detail_scroll();
+    }
 
-            // Restore from snapshot
-            self.current_order = snapshot.commit_order;
-            self.modifications = snapshot.modifications;
-            self.deleted = snapshot.deleted;
+    /// Apply search filter and sort order
+    ///
+    /// A query that matches nothing shows every commit. The cursor stays on
+    /// the same commit if it is still visible.
+    pub fn apply_filter(&mut self) {
+        let cursor_id = self.cursor_commit_id();
 
-            // Rebuild commits array in new order
-            self.rebuild_commits_order();
+        let query = if self.search_query.is_empty() {
+            None
+        } else {
+            self.last_search.clone_from(&self.search_query);
+            SearchQuery::parse(&self.search_query).ok()
+        };
+        let filtered = query.and_then(|query| {
+            let indices: Vec<usize> = self
+                .commits
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| query.matches(c, &self.touched_paths))
+                .map(|(i, _)| i)
+                .collect();
+            (!indices.is_empty()).then_some(indices)
+        });
+
+        self.filtered_indices = match self.sort {
+            Some(sort) => {
+                let mut indices = filtered.unwrap_or_else(|| (0..self.commits.len()).collect());
+                self.sort_rows(&mut indices, sort);
+                Some(indices)
+            }
+            None => filtered,
+        };
+        self.restore_cursor_to(cursor_id);
+    }
 
-            true
+    /// Sort rows (indices into `commits`) by their current values, keeping
+    /// ties in order
+    fn sort_rows(&self, rows: &mut [usize], sort: SortOrder) {
+        let value = |idx: usize| {
+            let commit = &self.commits[idx];
+            let mods = self.modifications.get(&commit.id);
+            let text = |value: &str| SortValue::Text(value.to_lowercase());
+            match sort.key {
+                SortKey::Author => text(mods.map_or(&commit.author.name, |m| {
+                    m.effective_author_name(&commit.author.name)
+                })),
+                SortKey::Email => text(mods.map_or(&commit.author.email, |m| {
+                    m.effective_author_email(&commit.author.email)
+                })),
+                SortKey::Date => SortValue::Date(self.effective_author_date(commit)),
+                SortKey::Message => text(
+                    mods.map_or(commit.message.as_str(), |m| {
+                        m.effective_message(&commit.message)
+                    })
+                    .lines()
+                    .next()
+                    .unwrap_or(""),
+                ),
+            }
+        };
+        if sort.descending {
+            rows.sort_by_cached_key(|&idx| std::cmp::Reverse(value(idx)));
         } else {
-            false
+            rows.sort_by_cached_key(|&idx| value(idx));
         }
     }
 
-    /// Redo last undone change
-    pub fn redo(&mut self) -> bool {
-        if let Some(snapshot) = self.redo_stack.pop() {
-            // Save current state to undo stack
-            let current = UndoSnapshot {
-                commit_order: self.current_order.clone(),
-                modifications: self.modifications.clone(),
-                deleted: self.deleted.clone(),
-                description: snapshot.description.clone(),
-            };
-            self.undo_stack.push(current);
+    /// Sort the table rows, or restore the current order (None)
+    pub fn set_sort(&mut self, sort: Option<SortOrder>) {
+        self.sort = sort;
+        self.apply_filter();
+    }
 
-            // Restore from snapshot
-            self.current_order = snapshot.commit_order;
-            self.modifications = snapshot.modifications;
-            self.deleted = snapshot.deleted;
+    /// Clear search filter
+    ///
+    /// The cursor is restored to the commit it was on in the filtered view.
+    pub fn clear_filter(&mut self) {
+        self.search_query.clear();
+        self.apply_filter();
+    }
 
-            // Rebuild commits array in new order
-            self.rebuild_commits_order();
+    /// Move the cursor to the next (or previous) visible commit matching the last search
+    ///
+    /// Unlike the filter, this leaves the view untouched. The search wraps around
+    /// the ends of the list like vim's `n`/`N`.
+    ///
+    /// Returns `None` if there is no last search or nothing matches, otherwise
+    /// whether the search wrapped around.
+    pub fn jump_to_match(&mut self, forward: bool) -> Option<bool> {
+        if self.last_search.is_empty() {
+            return None;
+        }
 
-            true
+        let query = SearchQuery::parse(&self.last_search).ok()?;
+        let len = self.visible_len();
+        let found = (1..=len).find_map(|step| {
+            let index = if forward {
+                (self.cursor + step) % len
+            } else {
+                (self.cursor + len - step % len) % len
+            };
+            self.visible_commit(index)
+                .filter(|commit| query.matches(commit, &self.touched_paths))
+                .map(|_| index)
+        })?;
+
+        let wrapped = if forward {
+            found <= self.cursor
         } else {
-            false
-        }
+            found >= self.cursor
+        };
+        self.cursor = found;
+        self.adjust_scroll();
+        self.reset_detail_scroll();
+        Some(wrapped)
     }
 
-    /// Rebuild commits vector in `current_order`
-    fn rebuild_commits_order(&mut self) {
-        let commit_map: HashMap<CommitId, CommitData> =
-            self.commits.drain(..).map(|c| (c.id, c)).collect();
+    /// Distinct author emails among these commits that aren't the current user's
+    ///
+    /// Empty when the ownership warning is disabled, was already acknowledged
+    /// this session, or the git user email is unknown.
+    #[must_use]
+    pub fn foreign_authors(&self, ids: &[CommitId]) -> Vec<String> {
+        let Some(user_email) = self.user.as_ref().map(|u| &u.email) else {
+            return Vec::new();
+        };
+        if !self.warn_foreign_commits || self.ownership_acknowledged {
+            return Vec::new();
+        }
 
-        self.commits = self
-            .current_order
+        let mut authors: Vec<String> = self
+            .commits
             .iter()
-            .filter_map(|id| commit_map.get(id).cloned())
+            .filter(|c| ids.contains(&c.id))
+            .map(|c| c.author.email.clone())
+            .filter(|email| !email.eq_ignore_ascii_case(user_email))
             .collect();
+        authors.sort();
+        authors.dedup();
+        authors
     }
 
-    /// Check if there are any pending changes
+    /// Whether a commit is too old to edit without `:unlock`
     #[must_use]
-    pub fn is_dirty(&self) -> bool {
-        // Check for modifications
-        if self
-            .modifications
-            .values()
-            .any(super::super::git::commit::CommitModifications::has_modifications)
-        {
-            return true;
-        }
-        // Check for deletions
-        if !self.deleted.is_empty() {
-            return true;
-        }
-        // Check for reordering
-        if self.current_order != self.original_order {
-            return true;
-        }
-        false
+    pub fn is_locked(&self, id: CommitId) -> bool {
+        let Some(age) = self.lock_age else {
+            return false;
+        };
+        !self.unlocked.contains(&id)
+            && self
+                .commits
+                .iter()
+                .find(|c| c.id == id)
+                .is_some_and(|c| c.committer_date < Local::now() - age)
     }
 
-    /// Get count of modified commits
+    /// How many of `ids` are locked
     #[must_use]
-    pub fn modified_count(&self) -> usize {
-        self.modifications
-            .values()
-            .filter(|m| m.has_modifications())
-            .count()
+    pub fn locked_count(&self, ids: &[CommitId]) -> usize {
+        ids.iter().filter(|&&id| self.is_locked(id)).count()
     }
 
-    /// Clear all modifications
-    pub fn clear_modifications(&mut self) {
-        self.modifications.clear();
-        self.deleted.clear();
-        self.current_order = self.original_order.clone();
-        self.rebuild_commits_order();
-        self.undo_stack.clear();
-        self.redo_stack.clear();
+    /// Get the effective (possibly modified) value of a name or email field
+    ///
+    /// Returns None for non-identity fields or unknown commits.
+    #[must_use]
+    pub fn effective_identity_value(&self, id: CommitId, field: EditableField) -> Option<String> {
+        let commit = self.commits.iter().find(|c| c.id == id)?;
+        let mods = self.modifications.get(&id).cloned().unwrap_or_default();
+        let value = match field {
+            EditableField::AuthorName => mods.effective_author_name(&commit.author.name),
+            EditableField::AuthorEmail => mods.effective_author_email(&commit.author.email),
+            EditableField::CommitterName => mods.effective_committer_name(&commit.committer.name),
+            EditableField::CommitterEmail => {
+                mods.effective_committer_email(&commit.committer.email)
+            }
+            _ => return None,
+        };
+        Some(value.to_string())
     }
 
-    /// Set error message (auto-cleared on next action)
-    pub fn set_error(&mut self, msg: impl Into<String>) {
-        self.error_message = Some(msg.into());
-        self.success_message = None;
+    /// A commit's value for `field`, with its pending edit, as it is edited
+    #[must_use]
+    pub fn field_value(&self, id: CommitId, field: EditableField) -> Option<String> {
+        let commit = self.commits.iter().find(|c| c.id == id)?;
+        let mods = self.modifications.get(&id);
+        Some(match field {
+            EditableField::AuthorDate => format_date_for_edit(
+                &mods
+                    .and_then(|m| m.author_date)
+                    .unwrap_or(commit.author_date),
+            ),
+            EditableField::CommitterDate => format_date_for_edit(
+                &mods
+                    .and_then(|m| m.committer_date)
+                    .unwrap_or(commit.committer_date),
+            ),
+            EditableField::Message => mods
+                .map_or(commit.message.as_str(), |m| {
+                    m.effective_message(&commit.message)
+                })
+                .to_string(),
+            _ => return self.effective_identity_value(id, field),
+        })
     }
 
-    /// Set success message (auto-cleared on next action)
-    pub fn set_success(&mut self, msg: impl Into<String>) {
-        self.success_message = Some(msg.into());
-        self.error_message = None;
+    /// Stage a field edit on a single commit
+    ///
+    /// When `sync_author_to_committer` is enabled, editing author fields
+    /// (name, email, date) will also update the corresponding committer
+    /// fields. This is the default behavior since most workflows keep author
+    /// and committer identical.
+    pub fn set_field(
+        &mut self,
+        commit_id: CommitId,
+        field: EditableField,
+        new_value: &str,
+        original_value: &str,
+    ) {
+        let sync = self.sync_author_to_committer;
+        let mods = self.get_or_create_modifications(commit_id);
+
+        match field {
+            EditableField::AuthorName => {
+                mods.author_name = Some(new_value.to_string());
+                // Sync to committer if enabled
+                if sync {
+                    mods.committer_name = Some(new_value.to_string());
+                }
+            }
+            EditableField::AuthorEmail => {
+                mods.author_email = Some(new_value.to_string());
+                // Sync to committer if enabled
+                if sync {
+                    mods.committer_email = Some(new_value.to_string());
+                }
+            }
+            EditableField::AuthorDate => {
+                if new_value != original_value {
+                    if let Ok(dt) = validate_date(new_value) {
+                        mods.author_date = Some(dt);
+                        // Sync to committer if enabled
+                        if sync {
+                            mods.committer_date = Some(dt);
+                        }
+                    }
+                }
+            }
+            EditableField::CommitterName => {
+                mods.committer_name = Some(new_value.to_string());
+            }
+            EditableField::CommitterEmail => {
+                mods.committer_email = Some(new_value.to_string());
+            }
+            EditableField::CommitterDate => {
+                if new_value != original_value {
+                    if let Ok(dt) = validate_date(new_value) {
+                        mods.committer_date = Some(dt);
+                    }
+                }
+            }
+            EditableField::Message => {
+                mods.message = Some(new_value.to_string());
+            }
+        }
     }
 
-    /// Clear status messages
-    pub fn clear_messages(&mut self) {
-        self.error_message = None;
-        self.success_message = None;
+    /// Copy `source`'s value for `field` (with its pending edit) into the
+    /// other `targets`, spreadsheet-style
+    ///
+    /// Merge commits and commits that already hold the value are skipped.
+    /// Saves one undo snapshot if anything changes. Returns the commits filled.
+    pub fn fill(
+        &mut self,
+        source: CommitId,
+        targets: &[CommitId],
+        field: EditableField,
+    ) -> Vec<CommitId> {
+        let Some(value) = self.field_value(source, field) else {
+            return Vec::new();
+        };
+        let fills: Vec<(CommitId, String)> = targets
+            .iter()
+            .filter(|&&id| id != source)
+            .filter(|&&id| self.commits.iter().any(|c| c.id == id && !c.is_merge))
+            .filter_map(|&id| Some((id, self.field_value(id, field)?)))
+            .filter(|(_, current)| *current != value)
+            .collect();
+        if fills.is_empty() {
+            return Vec::new();
+        }
+
+        self.save_undo(&format!(
+            "Fill {} on {} commit(s)",
+            field.display_name(),
+            fills.len()
+        ));
+        for (id, current) in &fills {
+            self.set_field(*id, field, &value, current);
+        }
+        fills.into_iter().map(|(id, _)| id).collect()
     }
 
-    // ==================== Visual Selection Methods ====================
+    /// Record a change to the inline edit buffer for field-level undo
+    ///
+    /// Call after handling a key with the buffer state from before it. Consecutive
+    /// typing is coalesced into one undo step; cursor movement ends the run.
+    pub fn record_buffer_change(
+        &mut self,
+        before_buffer: String,
+        before_cursor: usize,
+        typing: bool,
+    ) {
+        if before_buffer == self.edit_buffer {
+            self.edit_typing = false;
+            return;
+        }
+        if !(typing && self.edit_typing) {
+            self.edit_undo_stack.push((before_buffer, before_cursor));
+        }
+        self.edit_redo_stack.clear();
+        self.edit_typing = typing;
+    }
 
-    /// Enter visual mode at current cursor position
-    pub fn enter_visual_mode(&mut self, visual_type: VisualType) {
-        let anchor = (self.cursor, self.column_index);
-        self.mode = AppMode::Visual {
-            anchor,
-            visual_type,
+    /// Restore the edit buffer to its previous state
+    pub fn undo_buffer_edit(&mut self) -> bool {
+        let Some((buffer, cursor)) = self.edit_undo_stack.pop() else {
+            return false;
         };
+        let current = std::mem::replace(&mut self.edit_buffer, buffer);
+        self.edit_redo_stack.push((current, self.edit_cursor));
+        self.edit_cursor = cursor;
+        self.edit_typing = false;
+        true
     }
 
-    /// Exit visual mode without applying selection
-    pub fn exit_visual_mode(&mut self) {
-        self.mode = AppMode::Normal;
+    /// Re-apply an undone edit buffer change
+    pub fn redo_buffer_edit(&mut self) -> bool {
+        let Some((buffer, cursor)) = self.edit_redo_stack.pop() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.edit_buffer, buffer);
+        self.edit_undo_stack.push((current, self.edit_cursor));
+        self.edit_cursor = cursor;
+        self.edit_typing = false;
+        true
     }
 
-    /// Get the visual selection range as ((`start_row`, `start_col`), (`end_row`, `end_col`))
-    /// Returns None if not in visual mode
-    #[must_use]
-    pub fn visual_range(&self) -> Option<((usize, usize), (usize, usize))> {
-        match &self.mode {
-            AppMode::Visual { anchor, .. } => {
-                let current = (self.cursor, self.column_index);
-                // Normalize so start <= end
-                let start_row = anchor.0.min(current.0);
-                let end_row = anchor.0.max(current.0);
-                let start_col = anchor.1.min(current.1);
-                let end_col = anchor.1.max(current.1);
-                Some(((start_row, start_col), (end_row, end_col)))
-            }
-            _ => None,
+    /// Forget edit buffer history (when a new inline edit starts)
+    pub fn reset_buffer_history(&mut self) {
+        self.edit_undo_stack.clear();
+        self.edit_redo_stack.clear();
+        self.edit_typing = false;
+    }
+
+    /// Replace every occurrence of `find` in the inline edit buffer, as one
+    /// step of the buffer's undo history
+    ///
+    /// The cursor moves to the end. Returns the number of occurrences replaced.
+    pub fn replace_in_edit_buffer(&mut self, find: &str, replacement: &str) -> usize {
+        if find.is_empty() {
+            return 0;
+        }
+        let count = self.edit_buffer.matches(find).count();
+        if count == 0 || find == replacement {
+            return count;
         }
+        let replaced = self.edit_buffer.replace(find, replacement);
+        let before_buffer = std::mem::replace(&mut self.edit_buffer, replaced);
+        let before_cursor = std::mem::replace(&mut self.edit_cursor, self.edit_buffer.len());
+        self.record_buffer_change(before_buffer, before_cursor, false);
+        count
     }
 
-    /// Check if a cell is within the visual selection
-    #[allow(dead_code)]
-    #[must_use]
-    pub fn is_in_visual_selection(&self, row: usize, col: usize) -> bool {
-        match &self.mode {
-            AppMode::Visual {
-                anchor,
-                visual_type,
-            } => {
-                let current = (self.cursor, self.column_index);
-                let start_row = anchor.0.min(current.0);
-                let end_row = anchor.0.max(current.0);
+    /// Replace every occurrence of `find` in one commit's (effective) message
+    ///
+    /// Saves an undo snapshot when anything changes. Returns the number of
+    /// occurrences replaced.
+    pub fn replace_in_message(&mut self, id: CommitId, find: &str, replacement: &str) -> usize {
+        if find.is_empty() {
+            return 0;
+        }
+        let Some(commit) = self.commits.iter().find(|c| c.id == id) else {
+            return 0;
+        };
+        let message = self
+            .modifications
+            .get(&id)
+            .and_then(|m| m.message.clone())
+            .unwrap_or_else(|| commit.message.clone());
 
-                match visual_type {
-                    VisualType::Line => {
-                        // Line-wise: entire row is selected if row is in range
-                        row >= start_row && row <= end_row
-                    }
-                    VisualType::Block => {
-                        // Block-wise: cell must be within rectangular region
-                        let start_col = anchor.1.min(current.1);
-                        let end_col = anchor.1.max(current.1);
-                        row >= start_row && row <= end_row && col >= start_col && col <= end_col
-                    }
-                }
-            }
-            _ => false,
+        let count = message.matches(find).count();
+        if count == 0 || find == replacement {
+            return count;
         }
+
+        self.save_undo(&format!("Replace '{find}' in message"));
+        let new_message = message.replace(find, replacement);
+        self.get_or_create_modifications(id).message = Some(new_message);
+        count
     }
 
-    /// Check if a row is within the visual selection (for row-level styling)
-    #[allow(dead_code)]
-    #[must_use]
-    pub fn is_row_in_visual_selection(&self, row: usize) -> bool {
-        match &self.mode {
-            AppMode::Visual { anchor, .. } => {
-                let start_row = anchor.0.min(self.cursor);
-                let end_row = anchor.0.max(self.cursor);
-                row >= start_row && row <= end_row
+    /// Rewrap commits' (effective) message bodies to `width` columns (see
+    /// `reflow_message`)
+    ///
+    /// Saves one undo snapshot if anything changes. Returns how many changed.
+    pub fn reflow_messages(&mut self, ids: &[CommitId], width: usize) -> usize {
+        let changes: Vec<(CommitId, String)> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id && !c.is_merge)?;
+                let message = self
+                    .modifications
+                    .get(&id)
+                    .map_or(commit.message.as_str(), |m| {
+                        m.effective_message(&commit.message)
+                    });
+                let reflowed = reflow_message(message, width);
+                (reflowed != message).then_some((id, reflowed))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+        self.save_undo(&format!("Reflow messages to {width} columns"));
+        for (id, message) in &changes {
+            self.get_or_create_modifications(*id).message = Some(message.clone());
+        }
+        changes.len()
+    }
+
+    /// Snap the author dates of commits to a multiple of `step` seconds
+    ///
+    /// Committer dates follow when author/committer sync is on. Commits whose
+    /// date is already on a boundary are left alone. Returns how many changed.
+    pub fn snap_dates(&mut self, ids: &[CommitId], step: i64, mode: SnapMode) -> usize {
+        let changes: Vec<(CommitId, DateTime<FixedOffset>)> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id)?;
+                let date = self.effective_author_date(commit);
+                let snapped = mode.snap(date, step);
+                (snapped != date).then_some((id, snapped))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+
+        self.save_undo(&format!("Snap dates on {} commit(s)", changes.len()));
+        let sync = self.sync_author_to_committer;
+        for &(id, date) in &changes {
+            let mods = self.get_or_create_modifications(id);
+            mods.author_date = Some(date);
+            if sync {
+                mods.committer_date = Some(date);
             }
-            _ => false,
         }
+        changes.len()
     }
 
-    /// Get the visual selection type if in visual mode
+    /// Move the author and committer dates of commits by `seconds`, keeping
+    /// their timezones
+    ///
+    /// Returns how many commits changed.
+    pub fn shift_dates(&mut self, ids: &[CommitId], seconds: i64) -> usize {
+        let delta = TimeDelta::seconds(seconds);
+        let changes: Vec<(CommitId, DateTime<FixedOffset>, DateTime<FixedOffset>)> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id)?;
+                Some((
+                    id,
+                    self.effective_author_date(commit)
+                        .checked_add_signed(delta)?,
+                    self.effective_committer_date(commit)
+                        .checked_add_signed(delta)?,
+                ))
+            })
+            .collect();
+        if changes.is_empty() || seconds == 0 {
+            return 0;
+        }
+
+        self.save_undo(&format!("Shift dates on {} commit(s)", changes.len()));
+        for (id, author_date, committer_date) in &changes {
+            let mods = self.get_or_create_modifications(*id);
+            mods.author_date = Some(*author_date);
+            mods.committer_date = Some(*committer_date);
+        }
+        changes.len()
+    }
+
+    /// Space the author and committer dates of commits evenly from `start`
+    /// (the oldest, in the current order) to `end` (the newest)
+    ///
+    /// Each date then moves by up to `jitter` seconds either way, by an
+    /// amount taken from the commit's hash so the same spread always gives
+    /// the same dates. The jitter is capped below half the spacing, so the
+    /// dates keep rising. Commits keep their timezones. Returns how many
+    /// commits changed.
+    pub fn spread_dates(
+        &mut self,
+        ids: &[CommitId],
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        jitter: i64,
+    ) -> usize {
+        // Oldest first
+        let commits: Vec<&CommitData> = self
+            .commits
+            .iter()
+            .rev()
+            .filter(|c| ids.contains(&c.id) && !self.deleted.contains(&c.id))
+            .collect();
+        let span = (end - start).num_seconds();
+        let gaps = i64::try_from(commits.len().saturating_sub(1)).unwrap_or(i64::MAX);
+        let spacing = if gaps == 0 { span } else { span / gaps };
+        let jitter = jitter.min((spacing - 1) / 2).max(0);
+
+        let mut index = 0;
+        let changes: Vec<(CommitId, DateTime<FixedOffset>, DateTime<FixedOffset>)> = commits
+            .iter()
+            .filter_map(|commit| {
+                let base = if gaps == 0 { 0 } else { span * index / gaps };
+                index += 1;
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(&commit.id.0.as_bytes()[..8]);
+                let wobble = if jitter > 0 {
+                    i64::try_from(u64::from_le_bytes(bytes) % (2 * jitter.unsigned_abs() + 1))
+                        .unwrap_or(0)
+                        - jitter
+                } else {
+                    0
+                };
+                let instant = start + TimeDelta::seconds((base + wobble).clamp(0, span));
+                let author = self.effective_author_date(commit);
+                let committer = self.effective_committer_date(commit);
+                let new_author = instant.with_timezone(author.offset());
+                let new_committer = instant.with_timezone(committer.offset());
+                (new_author != author || new_committer != committer).then_some((
+                    commit.id,
+                    new_author,
+                    new_committer,
+                ))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+
+        self.save_undo(&format!("Spread dates on {} commit(s)", changes.len()));
+        for (id, author, committer) in &changes {
+            let mods = self.get_or_create_modifications(*id);
+            mods.author_date = Some(*author);
+            mods.committer_date = Some(*committer);
+        }
+        changes.len()
+    }
+
+    /// How many kept commits are dated (by author date) before the kept
+    /// commit below them
     #[must_use]
-    pub fn visual_type(&self) -> Option<VisualType> {
-        match &self.mode {
-            AppMode::Visual { visual_type, .. } => Some(*visual_type),
-            _ => None,
+    pub fn dates_out_of_order(&self) -> usize {
+        let dates: Vec<_> = self
+            .commits
+            .iter()
+            .filter(|c| !self.is_deleted(c.id))
+            .map(|c| self.effective_author_date(c))
+            .collect();
+        dates.windows(2).filter(|pair| pair[0] < pair[1]).count()
+    }
+
+    /// The new (author, committer) dates that would put the kept commits'
+    /// dates in order, for the commits whose dates change
+    #[must_use]
+    pub fn date_order_fixes(
+        &self,
+        fix: DateFix,
+    ) -> Vec<(CommitId, DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        // Oldest first
+        let kept: Vec<&CommitData> = self
+            .commits
+            .iter()
+            .rev()
+            .filter(|c| !self.is_deleted(c.id))
+            .collect();
+        let current: Vec<_> = kept
+            .iter()
+            .map(|c| {
+                (
+                    self.effective_author_date(c),
+                    self.effective_committer_date(c),
+                )
+            })
+            .collect();
+
+        let fixed: Vec<_> = match fix {
+            DateFix::Swap => {
+                let mut sorted = current.clone();
+                sorted.sort_by_key(|(author, _)| *author);
+                // Each commit keeps its own timezones
+                sorted
+                    .iter()
+                    .zip(&current)
+                    .map(|((author, committer), (old_author, old_committer))| {
+                        (
+                            author.with_timezone(old_author.offset()),
+                            committer.with_timezone(old_committer.offset()),
+                        )
+                    })
+                    .collect()
+            }
+            DateFix::Nudge(step) => {
+                let step = TimeDelta::seconds(step);
+                let mut previous: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = None;
+                current
+                    .iter()
+                    .map(|&(author, committer)| {
+                        let nudge =
+                            |date: DateTime<FixedOffset>, floor: Option<DateTime<FixedOffset>>| {
+                                match floor {
+                                    Some(floor) if date < floor => {
+                                        (floor + step).with_timezone(date.offset())
+                                    }
+                                    _ => date,
+                                }
+                            };
+                        let dates = (
+                            nudge(author, previous.map(|p| p.0)),
+                            nudge(committer, previous.map(|p| p.1)),
+                        );
+                        previous = Some(dates);
+                        dates
+                    })
+                    .collect()
+            }
+        };
+
+        kept.iter()
+            .zip(current.iter().zip(fixed))
+            .filter(|(_, (old, new))| *old != new)
+            .map(|(commit, (_, (author, committer)))| (commit.id, author, committer))
+            .collect()
+    }
+
+    /// Put the kept commits' dates in order after reordering, as one undo
+    /// step
+    ///
+    /// Returns how many commits changed.
+    pub fn fix_date_order(&mut self, fix: DateFix) -> usize {
+        let changes = self.date_order_fixes(fix);
+        if changes.is_empty() {
+            return 0;
+        }
+        self.save_undo(&format!("Fix date order on {} commit(s)", changes.len()));
+        for (id, author, committer) in &changes {
+            let mods = self.get_or_create_modifications(*id);
+            mods.author_date = Some(*author);
+            mods.committer_date = Some(*committer);
         }
+        changes.len()
     }
 
-    /// Apply visual selection to the selected set (confirm visual selection)
-    #[allow(dead_code)]
-    pub fn apply_visual_selection(&mut self) {
-        if let Some(((start_row, _), (end_row, _))) = self.visual_range() {
-            // Collect IDs first to avoid borrow issues
-            let ids: Vec<CommitId> = self
-                .visible_commits()
+    /// Express the author and committer dates of commits at another UTC
+    /// offset, keeping the instant
+    ///
+    /// Only dates at a different offset change. Returns how many commits
+    /// changed.
+    pub fn convert_timezone(&mut self, ids: &[CommitId], offset: FixedOffset) -> usize {
+        let convert = |date: DateTime<FixedOffset>| {
+            (*date.offset() != offset).then(|| date.with_timezone(&offset))
+        };
+        let changes: Vec<_> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id)?;
+                let author = convert(self.effective_author_date(commit));
+                let committer = convert(self.effective_committer_date(commit));
+                (author.is_some() || committer.is_some()).then_some((id, author, committer))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+
+        self.save_undo(&format!("Convert timezone on {} commit(s)", changes.len()));
+        for (id, author, committer) in &changes {
+            let mods = self.get_or_create_modifications(*id);
+            if author.is_some() {
+                mods.author_date = *author;
+            }
+            if committer.is_some() {
+                mods.committer_date = *committer;
+            }
+        }
+        changes.len()
+    }
+
+    /// Drop pending edits of `fields` from commits, keeping their other edits
+    ///
+    /// With author/committer sync on, clearing an author field clears its
+    /// committer counterpart too, since edits set both. Returns how many
+    /// commits changed.
+    pub fn clear_fields(&mut self, ids: &[CommitId], fields: &[EditableField]) -> usize {
+        let mut fields = fields.to_vec();
+        if self.sync_author_to_committer {
+            for field in fields.clone() {
+                let counterpart = match field {
+                    EditableField::AuthorName => EditableField::CommitterName,
+                    EditableField::AuthorEmail => EditableField::CommitterEmail,
+                    EditableField::AuthorDate => EditableField::CommitterDate,
+                    _ => continue,
+                };
+                if !fields.contains(&counterpart) {
+                    fields.push(counterpart);
+                }
+            }
+        }
+
+        let changed: Vec<CommitId> = ids
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.modifications
+                    .get(id)
+                    .is_some_and(|m| fields.iter().any(|&f| m.is_field_modified(f)))
+            })
+            .collect();
+        if changed.is_empty() {
+            return 0;
+        }
+
+        self.save_undo(&format!("Clear edits on {} commit(s)", changed.len()));
+        for id in &changed {
+            if let Some(mods) = self.modifications.get_mut(id) {
+                for &field in &fields {
+                    mods.clear_field(field);
+                }
+                if mods.is_empty() {
+                    self.modifications.remove(id);
+                }
+            }
+        }
+        changed.len()
+    }
+
+    /// The commit a `fixup!`/`squash!` commit at `idx` (in `commits`) targets
+    ///
+    /// Like `git rebase --autosquash`, the target is the nearest older commit
+    /// whose subject matches exactly, else whose hash starts with the given
+    /// text, else whose subject starts with it. Returns the target's index.
+    #[must_use]
+    pub fn fixup_target(&self, idx: usize) -> Option<(usize, SquashKind)> {
+        self.fixups().targets.get(idx).copied().flatten()
+    }
+
+    /// Every commit's fixup target, worked out on first use
+    fn fixups(&self) -> &Fixups {
+        self.fixups.get_or_init(|| {
+            let targets: Vec<_> = (0..self.commits.len())
+                .map(|idx| self.find_fixup_target(idx))
+                .collect();
+            let targeted = targets
                 .iter()
-                .enumerate()
-                .filter_map(|(idx, c)| {
-                    if idx >= start_row && idx <= end_row {
-                        Some(c.id)
-                    } else {
-                        None
-                    }
-                })
+                .flatten()
+                .map(|&(target, _)| self.commits[target].id)
                 .collect();
-            for id in ids {
-                self.selected.insert(id);
+            Fixups { targets, targeted }
+        })
+    }
+
+    fn find_fixup_target(&self, idx: usize) -> Option<(usize, SquashKind)> {
+        let (kind, target) = SquashKind::from_subject(&self.commits.get(idx)?.summary)?;
+        let older = || self.commits.iter().enumerate().skip(idx + 1);
+        let found = older()
+            .find(|(_, c)| c.summary == target)
+            .or_else(|| {
+                (target.len() >= 4)
+                    .then(|| older().find(|(_, c)| c.id.starts_with(target)))
+                    .flatten()
+            })
+            .or_else(|| older().find(|(_, c)| c.summary.starts_with(target)));
+        found.map(|(i, _)| (i, kind))
+    }
+
+    /// Later commits that revert or mention one of `ids` by hash
+    ///
+    /// Deleting a commit but keeping such a commit leaves its reference (and,
+    /// for a revert, its change) dangling. Returns `(referencing, referenced)`
+    /// pairs, at most one per referencing commit, skipping commits that are
+    /// already deleted or in `ids`.
+    #[must_use]
+    pub fn referencing_commits(&self, ids: &[CommitId]) -> Vec<(CommitId, CommitId)> {
+        let position = |id: &CommitId| self.original_order.iter().position(|o| o == id);
+        let mut pairs = Vec::new();
+        for commit in &self.commits {
+            if ids.contains(&commit.id) || self.is_deleted(commit.id) {
+                continue;
+            }
+            let message = self
+                .modifications
+                .get(&commit.id)
+                .map_or(commit.message.as_str(), |m| {
+                    m.effective_message(&commit.message)
+                });
+            let refs = hash_references(message);
+            let referenced = ids.iter().find(|target| {
+                let Some(original) = self.commits.iter().find(|c| c.id == **target) else {
+                    return false;
+                };
+                let is_later = matches!(
+                    (position(&commit.id), position(target)),
+                    (Some(a), Some(b)) if a < b
+                );
+                let full = target.0.to_string();
+                is_later
+                    && (refs
+                        .iter()
+                        .any(|(_, r)| full.starts_with(&r.to_lowercase()))
+                        || commit.summary == format!("Revert \"{}\"", original.summary))
+            });
+            if let Some(&target) = referenced {
+                pairs.push((commit.id, target));
             }
         }
-        self.mode = AppMode::Normal;
+        pairs
     }
 
-    /// Get the count of rows in visual selection
+    /// Commits that some `fixup!`/`squash!` commit refers to
     #[must_use]
-    pub fn visual_selection_count(&self) -> usize {
-        match self.visual_range() {
-            Some(((start_row, _), (end_row, _))) => end_row - start_row + 1,
-            None => 0,
-        }
+    pub fn fixup_targets(&self) -> &HashSet<CommitId> {
+        &self.fixups().targeted
     }
 
-    /// Capture visual selection as edit targets and exit visual mode
-    /// Returns the number of commits captured
-    pub fn capture_visual_edit_targets(&mut self) -> usize {
-        if let Some(((start_row, _), (end_row, _))) = self.visual_range() {
-            let ids: Vec<CommitId> = self
-                .visible_commits()
+    /// Move every `fixup!`/`squash!` commit directly above its target and stage
+    /// it to be folded in, like `git rebase --autosquash`
+    ///
+    /// Fixups for the same target keep their relative order. Deleted and merge
+    /// commits are left alone. Returns how many commits were staged.
+    pub fn autosquash(&mut self) -> usize {
+        // Oldest first, so later fixups end up above earlier ones
+        let pairs: Vec<(CommitId, CommitId, SquashKind)> = (0..self.commits.len())
+            .rev()
+            .filter_map(|idx| {
+                let (target, kind) = self.fixup_target(idx)?;
+                let commit = &self.commits[idx];
+                let usable = !commit.is_merge
+                    && !self.deleted.contains(&commit.id)
+                    && !self.squashes.contains_key(&commit.id)
+                    // Folding would drop the staged split on apply
+                    && !self.splits.contains_key(&commit.id)
+                    && (self.force
+                        || !self.is_locked(commit.id) && !self.is_locked(self.commits[target].id));
+                usable.then(|| (commit.id, self.commits[target].id, kind))
+            })
+            .collect();
+        if pairs.is_empty() {
+            return 0;
+        }
+        if pairs
+            .iter()
+            .any(|(id, target, _)| self.is_locked(*id) || self.is_locked(*target))
+        {
+            self.record_bypass(Bypass::Locked);
+        }
+
+        self.save_undo(&format!("Autosquash {} commit(s)", pairs.len()));
+        let cursor_id = self.cursor_commit_id();
+        // The most recent member of each target's group
+        let mut group_top: HashMap<CommitId, CommitId> = HashMap::new();
+        for &(fixup, target, kind) in &pairs {
+            let Some(from) = self.current_order.iter().position(|id| *id == fixup) else {
+                continue;
+            };
+            self.current_order.remove(from);
+            let top = group_top.get(&target).copied().unwrap_or(target);
+            let to = self
+                .current_order
                 .iter()
-                .enumerate()
-                .filter_map(|(idx, c)| {
-                    if idx >= start_row && idx <= end_row {
-                        Some(c.id)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            let count = ids.len();
-            self.visual_edit_targets = Some(ids);
-            self.mode = AppMode::Normal;
-            count
+                .position(|id| *id == top)
+                .unwrap_or(0);
+            self.current_order.insert(to, fixup);
+            group_top.insert(target, fixup);
+            self.squashes.insert(fixup, kind);
+        }
+        self.rebuild_commits_order();
+        self.restore_cursor_to(cursor_id);
+        pairs.len()
+    }
+
+    /// Stage commits to be folded into the commit below them (`None` unstages)
+    pub fn set_squash(&mut self, ids: &[CommitId], kind: Option<SquashKind>) {
+        let description = match kind {
+            Some(SquashKind::Fixup) => format!("Fixup {} commit(s)", ids.len()),
+            Some(SquashKind::Squash) => format!("Squash {} commit(s)", ids.len()),
+            None => format!("Unstage fold of {} commit(s)", ids.len()),
+        };
+        self.save_undo(&description);
+        for &id in ids {
+            match kind {
+                Some(kind) => self.squashes.insert(id, kind),
+                None => self.squashes.remove(&id),
+            };
+        }
+    }
+
+    /// The commit `id` would be folded into: the next older commit still kept
+    #[must_use]
+    pub fn fold_target(&self, id: CommitId) -> Option<CommitId> {
+        let pos = self.current_order.iter().position(|o| *o == id)?;
+        self.current_order[pos + 1..]
+            .iter()
+            .find(|o| !self.deleted.contains(o))
+            .copied()
+    }
+
+    /// Whether any of `ids` has no commit below it to fold into
+    ///
+    /// That is the oldest loaded commit still kept, or a merge.
+    #[must_use]
+    pub fn cannot_fold(&self, ids: &[CommitId]) -> bool {
+        ids.iter().any(|id| {
+            self.fold_target(*id).is_none()
+                || self.commits.iter().any(|c| c.id == *id && c.is_merge)
+        })
+    }
+
+    /// Stage a commit to be split in two (`None` removes its split)
+    ///
+    /// # Errors
+    /// Returns an error for merge commits and commits staged to be folded.
+    pub fn set_split(&mut self, id: CommitId, split: Option<CommitSplit>) -> Result<()> {
+        if split.is_some() {
+            if self.commits.iter().any(|c| c.id == id && c.is_merge) {
+                return Err(HistError::InvalidCommand(
+                    "merge commits can't be split".to_string(),
+                ));
+            }
+            if self.squashes.contains_key(&id) {
+                return Err(HistError::InvalidCommand(
+                    "a commit staged to be folded can't be split".to_string(),
+                ));
+            }
+        }
+        self.save_undo(if split.is_some() {
+            "Split commit"
         } else {
-            0
+            "Unsplit commit"
+        });
+        match split {
+            Some(split) => self.splits.insert(id, split),
+            None => self.splits.remove(&id),
+        };
+        Ok(())
+    }
+
+    /// Add a highlight group for a pattern
+    ///
+    /// Returns the group's index (which picks its color).
+    ///
+    /// # Errors
+    /// Returns an error if the pattern is already highlighted or all groups are in use.
+    pub fn add_highlight(&mut self, pattern: &str) -> Result<usize> {
+        if self
+            .highlights
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case(pattern))
+        {
+            return Err(HistError::InvalidCommand(format!(
+                "'{pattern}' is already highlighted"
+            )));
         }
+        if self.highlights.len() >= MAX_HIGHLIGHT_GROUPS {
+            return Err(HistError::InvalidCommand(format!(
+                "at most {MAX_HIGHLIGHT_GROUPS} highlight groups (use :nohl to remove one)"
+            )));
+        }
+        self.highlights.push(pattern.to_string());
+        Ok(self.highlights.len() - 1)
     }
 
-    /// Clear visual edit targets (called after edit completes)
-    pub fn clear_visual_edit_targets(&mut self) {
-        self.visual_edit_targets = None;
+    /// Remove the highlight group for a pattern, returning whether it existed
+    pub fn remove_highlight(&mut self, pattern: &str) -> bool {
+        let before = self.highlights.len();
+        self.highlights.retain(|h| !h.eq_ignore_ascii_case(pattern));
+        self.highlights.len() != before
     }
 
-    /// Get the commits to edit: visual targets > checkbox selected > just cursor
+    /// Find the first highlight group whose pattern appears in a value (case-insensitive)
     #[must_use]
-    pub fn commits_to_edit(&self) -> Vec<CommitId> {
-        if let Some(ref targets) = self.visual_edit_targets {
-            targets.clone()
-        } else if !self.selected.is_empty() {
-            self.selected.iter().copied().collect()
-        } else if let Some(id) = self.cursor_commit_id() {
-            vec![id]
+    pub fn highlight_group(&self, value: &str) -> Option<usize> {
+        if self.highlights.is_empty() {
+            return None;
+        }
+        let value = value.to_lowercase();
+        self.highlights
+            .iter()
+            .position(|h| value.contains(&h.to_lowercase()))
+    }
+
+    /// Store a yanked value in a named register (if any) and the unnamed one
+    pub fn yank(&mut self, register: Option<char>, value: &str) {
+        if let Some(name) = register {
+            self.registers.insert(name, value.to_string());
+        }
+        self.registers.insert(UNNAMED_REGISTER, value.to_string());
+    }
+
+    /// The value in a register (None: the unnamed one)
+    #[must_use]
+    pub fn register(&self, register: Option<char>) -> Option<&str> {
+        self.registers
+            .get(&register.unwrap_or(UNNAMED_REGISTER))
+            .map(String::as_str)
+    }
+
+    /// Save current state to undo stack
+    pub fn save_undo(&mut self, description: &str) {
+        let snapshot = self.snapshot(description);
+        self.undo_stack.push(snapshot);
+        self.redo_stack.clear(); // Clear redo stack on new change
+        self.revision += 1;
+    }
+
+    /// Capture the pending changes (order, edits, deletions, squashes, splits, notes)
+    #[must_use]
+    pub fn snapshot(&self, description: &str) -> UndoSnapshot {
+        UndoSnapshot {
+            commit_order: self.current_order.clone(),
+            modifications: self.modifications.clone(),
+            deleted: self.deleted.clone(),
+            squashes: self.squashes.clone(),
+            splits: self.splits.clone(),
+            notes: self.notes.clone(),
+            new_root: self.new_root.clone(),
+            description: description.to_string(),
+        }
+    }
+
+    /// Replace the pending changes with those of a snapshot
+    pub fn restore_snapshot(&mut self, snapshot: UndoSnapshot) {
+        self.current_order = snapshot.commit_order;
+        self.modifications = snapshot.modifications;
+        self.deleted = snapshot.deleted;
+        self.squashes = snapshot.squashes;
+        self.splits = snapshot.splits;
+        self.notes = snapshot.notes;
+        self.new_root = snapshot.new_root;
+        self.revision += 1;
+
+        // Rebuild commits array in new order
+        self.rebuild_commits_order();
+    }
+
+    /// Undo last change
+    pub fn undo(&mut self) -> bool {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            // Save current state to redo stack
+            let current = self.snapshot(&snapshot.description);
+            self.redo_stack.push(current);
+            self.restore_snapshot(snapshot);
+
+            true
         } else {
-            vec![]
+            false
+        }
+    }
+
+    /// Redo last undone change
+    pub fn redo(&mut self) -> bool {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            // Save current state to undo stack
+            let current = self.snapshot(&snapshot.description);
+            self.undo_stack.push(current);
+            self.restore_snapshot(snapshot);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Undo history as the history browser lists it (`U`), newest first: the
+    /// undone steps that can be redone (furthest first), then the steps that
+    /// can be undone; each with its description and whether it's undone
+    #[must_use]
+    pub fn undo_history(&self) -> Vec<(&str, bool)> {
+        self.redo_stack
+            .iter()
+            .map(|s| (s.description.as_str(), true))
+            .chain(
+                self.undo_stack
+                    .iter()
+                    .rev()
+                    .map(|s| (s.description.as_str(), false)),
+            )
+            .collect()
+    }
+
+    /// Undo or redo up to and including the step on `row` of `undo_history`
+    ///
+    /// Returns the number of steps undone or redone (redone when `row` is
+    /// one of the undone steps).
+    pub fn jump_undo_history(&mut self, row: usize) -> usize {
+        let redoable = self.redo_stack.len();
+        if row < redoable {
+            (row..redoable).take_while(|_| self.redo()).count()
+        } else {
+            (redoable..=row).take_while(|_| self.undo()).count()
+        }
+    }
+
+    /// What the step on `row` of `undo_history` changes, one line each
+    #[must_use]
+    pub fn undo_step_changes(&self, row: usize) -> Vec<String> {
+        let redoable = self.redo_stack.len();
+        let current = self.snapshot("");
+        let (before, after) = if row < redoable {
+            let before = self.redo_stack.get(row + 1).unwrap_or(&current);
+            (before, &self.redo_stack[row])
+        } else {
+            let Some(idx) = self.undo_stack.len().checked_sub(row - redoable + 1) else {
+                return Vec::new();
+            };
+            let after = self.undo_stack.get(idx + 1).unwrap_or(&current);
+            (&self.undo_stack[idx], after)
+        };
+        self.snapshot_changes(before, after)
+    }
+
+    /// Differences between two snapshots of the pending changes, one line each
+    fn snapshot_changes(&self, before: &UndoSnapshot, after: &UndoSnapshot) -> Vec<String> {
+        let mut lines = Vec::new();
+        if before.commit_order != after.commit_order {
+            lines.push("Commit order changed".to_string());
+        }
+        if before.new_root != after.new_root {
+            lines.push(match after.new_root {
+                Some(_) => "New root commit added".to_string(),
+                None => "New root commit removed".to_string(),
+            });
+        }
+        let no_changes = CommitModifications::default();
+        for commit in &self.commits {
+            let id = commit.id;
+            let hash = &commit.short_hash;
+            let old = before.modifications.get(&id).unwrap_or(&no_changes);
+            let new = after.modifications.get(&id).unwrap_or(&no_changes);
+            if old != new {
+                let old_fields = old.field_changes(commit);
+                let new_fields = new.field_changes(commit);
+                for (label, original, value) in &new_fields {
+                    let was = old_fields
+                        .iter()
+                        .find(|(l, _, _)| l == label)
+                        .map_or(original, |(_, _, v)| v);
+                    if was != value {
+                        lines.push(format!("{hash} {label}: {was} → {value}"));
+                    }
+                }
+                for (label, original, value) in &old_fields {
+                    if !new_fields.iter().any(|(l, _, _)| l == label) {
+                        lines.push(format!("{hash} {label}: {value} → {original}"));
+                    }
+                }
+                if old.message != new.message {
+                    lines.push(format!(
+                        "{hash} Message: {} → {}",
+                        old.effective_summary(&commit.summary),
+                        new.effective_summary(&commit.summary)
+                    ));
+                }
+            }
+            match (before.deleted.contains(&id), after.deleted.contains(&id)) {
+                (false, true) => lines.push(format!("{hash} marked for deletion")),
+                (true, false) => lines.push(format!("{hash} restored")),
+                _ => {}
+            }
+            match (before.squashes.get(&id), after.squashes.get(&id)) {
+                (old, Some(kind)) if old != Some(kind) => {
+                    let kind = match kind {
+                        SquashKind::Fixup => "fixup",
+                        SquashKind::Squash => "squash",
+                    };
+                    lines.push(format!("{hash} staged as {kind}"));
+                }
+                (Some(_), None) => lines.push(format!("{hash} no longer folded")),
+                _ => {}
+            }
+            if before.splits.get(&id) != after.splits.get(&id) {
+                lines.push(if after.splits.contains_key(&id) {
+                    format!("{hash} split in two")
+                } else {
+                    format!("{hash} split removed")
+                });
+            }
+            if before.notes.get(&id) != after.notes.get(&id) {
+                lines.push(format!("{hash} note changed"));
+            }
         }
+        lines
+    }
+
+    /// Rebuild commits vector in `current_order`
+    fn rebuild_commits_order(&mut self) {
+        let commit_map: HashMap<CommitId, CommitData> =
+            self.commits.drain(..).map(|c| (c.id, c)).collect();
+
+        // The snapshot may predate a limit change: drop commits that are no longer
+        // loaded and keep commits loaded since then at their original position
+        self.current_order.retain(|id| commit_map.contains_key(id));
+        let known: HashSet<CommitId> = self.current_order.iter().copied().collect();
+        let missing: Vec<CommitId> = self
+            .original_order
+            .iter()
+            .filter(|id| !known.contains(id))
+            .copied()
+            .collect();
+        self.current_order.extend(missing);
+
+        self.commits = self
+            .current_order
+            .iter()
+            .filter_map(|id| commit_map.get(id).cloned())
+            .collect();
+        self.commits_changed();
+    }
+
+    /// Forget what was worked out from the commit list; call after changing
+    /// `commits`
+    pub fn commits_changed(&mut self) {
+        self.fixups.take();
+        self.graph.take();
+    }
+
+    /// The graph column's row for each commit (in `commits`), drawn for the
+    /// current order
+    #[must_use]
+    pub fn graph_rows(&self) -> &[String] {
+        self.graph.get_or_init(|| graph_rows(&self.commits))
+    }
+
+    /// Check if there are any pending changes
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        // Check for modifications
+        if self
+            .modifications
+            .values()
+            .any(super::super::git::commit::CommitModifications::has_modifications)
+        {
+            return true;
+        }
+        // Check for deletions, squashes, splits and a new root
+        if !self.deleted.is_empty()
+            || !self.squashes.is_empty()
+            || !self.splits.is_empty()
+            || self.new_root.is_some()
+        {
+            return true;
+        }
+        // Check for reordering
+        if self.current_order != self.original_order {
+            return true;
+        }
+        false
+    }
+
+    /// Get count of modified commits
+    #[must_use]
+    pub fn modified_count(&self) -> usize {
+        self.modifications
+            .values()
+            .filter(|m| m.has_modifications())
+            .count()
+    }
+
+    /// The pending changes in short, e.g. `["5 edits", "2 deletions", "reordered"]`
+    #[must_use]
+    pub fn change_stats(&self) -> Vec<String> {
+        let count = |n: usize, one: &str, many: &str| match n {
+            0 => None,
+            1 => Some(format!("1 {one}")),
+            n => Some(format!("{n} {many}")),
+        };
+        let mut stats: Vec<String> = [
+            count(self.modified_count(), "edit", "edits"),
+            count(self.deleted.len(), "deletion", "deletions"),
+            count(self.squashes.len(), "squash", "squashes"),
+            count(self.splits.len(), "split", "splits"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if self.current_order != self.original_order {
+            stats.push("reordered".to_string());
+        }
+        if self.new_root.is_some() {
+            stats.push("new root".to_string());
+        }
+        stats
+    }
+
+    /// Commits with pending metadata edits, deletions, squashes or splits, oldest first
+    #[must_use]
+    pub fn pending_commits(&self) -> Vec<CommitId> {
+        self.current_order
+            .iter()
+            .rev()
+            .filter(|id| {
+                self.deleted.contains(id)
+                    || self.squashes.contains_key(id)
+                    || self.splits.contains_key(id)
+                    || self
+                        .modifications
+                        .get(id)
+                        .is_some_and(CommitModifications::has_modifications)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Commits the pending changes touch: edited, deleted, squashed, split,
+    /// moved to another position, or put on a new root
+    #[must_use]
+    pub fn changed_commits(&self) -> HashSet<CommitId> {
+        let mut changed: HashSet<CommitId> = self.pending_commits().into_iter().collect();
+        if self.new_root.is_some() {
+            changed.extend(self.original_order.last());
+        }
+        changed.extend(
+            self.original_order
+                .iter()
+                .zip(&self.current_order)
+                .filter(|(old, new)| old != new)
+                .map(|(old, _)| *old),
+        );
+        changed
+    }
+
+    /// Loaded commits the rewrite would give new IDs: the changed ones (except
+    /// deleted) and every commit built on a changed one, oldest first
+    #[must_use]
+    pub fn rewritten_commits(&self) -> Vec<CommitId> {
+        let changed = self.changed_commits();
+        let mut rewritten: HashSet<CommitId> = HashSet::new();
+        for commit in self.commits.iter().rev() {
+            if changed.contains(&commit.id)
+                || commit.parent_ids.iter().any(|p| rewritten.contains(p))
+            {
+                rewritten.insert(commit.id);
+            }
+        }
+        self.commits
+            .iter()
+            .rev()
+            .map(|c| c.id)
+            .filter(|id| rewritten.contains(id) && !self.deleted.contains(id))
+            .collect()
+    }
+
+    /// Note that `--force` let something through (once per apply)
+    pub fn record_bypass(&mut self, bypass: Bypass) {
+        if !self.bypassed.contains(&bypass) {
+            self.bypassed.push(bypass);
+        }
+    }
+
+    /// Take the pending changes of `ids` out of the state (reordering stays)
+    pub fn take_changes(&mut self, ids: &[CommitId]) -> Vec<(CommitId, HeldChanges)> {
+        ids.iter()
+            .map(|&id| {
+                let held = HeldChanges {
+                    modifications: self.modifications.remove(&id),
+                    deleted: self.deleted.remove(&id),
+                    squash: self.squashes.remove(&id),
+                    split: self.splits.remove(&id),
+                    note: self.notes.remove(&id),
+                };
+                (id, held)
+            })
+            .collect()
+    }
+
+    /// Put held changes back, on the commits' new IDs after a rewrite
+    ///
+    /// Commits missing from `commit_map` keep their ID.
+    pub fn restore_changes(
+        &mut self,
+        held: Vec<(CommitId, HeldChanges)>,
+        commit_map: &HashMap<CommitId, CommitId>,
+    ) {
+        for (id, changes) in held {
+            let id = commit_map.get(&id).copied().unwrap_or(id);
+            if let Some(mods) = changes.modifications {
+                self.modifications.insert(id, mods);
+            }
+            if changes.deleted {
+                self.deleted.insert(id);
+            }
+            if let Some(kind) = changes.squash {
+                self.squashes.insert(id, kind);
+            }
+            if let Some(split) = changes.split {
+                self.splits.insert(id, split);
+            }
+            if let Some(note) = changes.note {
+                self.notes.insert(id, note);
+            }
+        }
+    }
+
+    /// Clear all modifications
+    pub fn clear_modifications(&mut self) {
+        self.modifications.clear();
+        self.deleted.clear();
+        self.squashes.clear();
+        self.splits.clear();
+        self.notes.clear();
+        self.new_root = None;
+        self.current_order = self.original_order.clone();
+        self.rebuild_commits_order();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.revision += 1;
+    }
+
+    /// Attach a note explaining the change to commits, or remove their notes
+    ///
+    /// Returns how many commits' notes changed.
+    pub fn set_note(&mut self, ids: &[CommitId], note: Option<&str>) -> usize {
+        let changed: Vec<CommitId> = ids
+            .iter()
+            .copied()
+            .filter(|id| self.notes.get(id).map(String::as_str) != note)
+            .collect();
+        if changed.is_empty() {
+            return 0;
+        }
+
+        let description = match note {
+            Some(note) => format!("Note on {} commit(s): {note}", changed.len()),
+            None => format!("Remove notes from {} commit(s)", changed.len()),
+        };
+        self.save_undo(&description);
+        for &id in &changed {
+            match note {
+                Some(note) => self.notes.insert(id, note.to_string()),
+                None => self.notes.remove(&id),
+            };
+        }
+        changed.len()
+    }
+
+    /// Append a trailer to the messages of commits that don't carry it yet
+    ///
+    /// Duplicate copies of the trailer are collapsed to one. Returns how many
+    /// commits' messages changed.
+    pub fn add_trailer(&mut self, ids: &[CommitId], trailer: &Trailer) -> usize {
+        self.edit_messages(
+            ids,
+            |count| format!("Add {} to {count} commit(s)", trailer.key),
+            |mods, original| mods.add_trailer(original, trailer),
+        )
+    }
+
+    /// Remove a trailer from the messages of commits
+    ///
+    /// Returns how many commits' messages changed.
+    pub fn remove_trailer(&mut self, ids: &[CommitId], trailer: &Trailer) -> usize {
+        self.edit_messages(
+            ids,
+            |count| format!("Remove {} from {count} commit(s)", trailer.key),
+            |mods, original| mods.remove_trailer(original, trailer),
+        )
+    }
+
+    /// Run `edit` on the modifications of commits, as one undo step for the
+    /// commits whose messages it changed; returns how many did
+    fn edit_messages(
+        &mut self,
+        ids: &[CommitId],
+        description: impl Fn(usize) -> String,
+        edit: impl Fn(&mut CommitModifications, &str) -> bool,
+    ) -> usize {
+        let changes: Vec<(CommitId, CommitModifications)> = ids
+            .iter()
+            .filter_map(|&id| {
+                let commit = self.commits.iter().find(|c| c.id == id)?;
+                let mut mods = self.modifications.get(&id).cloned().unwrap_or_default();
+                edit(&mut mods, &commit.message).then_some((id, mods))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+
+        self.save_undo(&description(changes.len()));
+        for (id, mods) in &changes {
+            self.modifications.insert(*id, mods.clone());
+        }
+        changes.len()
+    }
+
+    /// Replace a trailer with another in the messages of commits
+    ///
+    /// Returns how many commits' messages changed.
+    pub fn replace_trailer(&mut self, ids: &[CommitId], old: &Trailer, new: &Trailer) -> usize {
+        self.edit_messages(
+            ids,
+            |count| format!("Change {} on {count} commit(s)", old.key),
+            |mods, original| mods.replace_trailer(original, old, new),
+        )
+    }
+
+    /// Distinct trailers in the commits' messages (with pending edits), only
+    /// those with `key` if given, in order of appearance, with how many
+    /// commits have each
+    #[must_use]
+    pub fn trailers(&self, ids: &[CommitId], key: Option<&str>) -> Vec<(Trailer, usize)> {
+        let mut trailers: Vec<(Trailer, usize)> = Vec::new();
+        for commit in self.commits.iter().filter(|c| ids.contains(&c.id)) {
+            let message = self
+                .modifications
+                .get(&commit.id)
+                .map_or(commit.message.as_str(), |m| {
+                    m.effective_message(&commit.message)
+                });
+            let mut seen: Vec<Trailer> = Vec::new();
+            for trailer in split_trailers(message).1 {
+                if key.is_some_and(|key| !trailer.is(key))
+                    || seen.iter().any(|t| t.matches(&trailer))
+                {
+                    continue;
+                }
+                match trailers.iter_mut().find(|(t, _)| t.matches(&trailer)) {
+                    Some((_, count)) => *count += 1,
+                    None => trailers.push((trailer.clone(), 1)),
+                }
+                seen.push(trailer);
+            }
+        }
+        trailers
+    }
+
+    /// How many kept commits gain a `key` trailer they didn't have
+    #[must_use]
+    pub fn trailers_added(&self, key: &str) -> usize {
+        let count = |message: &str| {
+            split_trailers(message)
+                .1
+                .iter()
+                .filter(|t| t.is(key))
+                .count()
+        };
+        self.commits
+            .iter()
+            .filter(|c| !self.is_deleted(c.id))
+            .filter(|c| {
+                self.modifications.get(&c.id).is_some_and(|mods| {
+                    count(mods.effective_message(&c.message)) > count(&c.message)
+                })
+            })
+            .count()
+    }
+
+    /// Replace the pending order, edits and deletions with a plan's, as one
+    /// undo step
+    ///
+    /// # Errors
+    /// Returns an error if the plan names a commit that isn't loaded, or its
+    /// order doesn't list each loaded commit once.
+    pub fn apply_plan(&mut self, plan: &EditPlan) -> Result<()> {
+        let loaded: HashSet<CommitId> = self.original_order.iter().copied().collect();
+        if let Some(id) = plan.commit_ids().find(|id| !loaded.contains(id)) {
+            return Err(HistError::Plan(format!(
+                "commit {} is not loaded (load more with --limit or --all)",
+                id.0
+            )));
+        }
+        let listed: HashSet<&CommitId> = plan.order.iter().collect();
+        if !plan.order.is_empty()
+            && (plan.order.len() != loaded.len() || listed.len() != loaded.len())
+        {
+            return Err(HistError::Plan(
+                "the order must list every loaded commit once".to_string(),
+            ));
+        }
+
+        self.save_undo("Load plan");
+        let mut snapshot = self.snapshot("Load plan");
+        snapshot.commit_order = if plan.order.is_empty() {
+            self.original_order.clone()
+        } else {
+            plan.order.clone()
+        };
+        snapshot.modifications.clone_from(&plan.modifications);
+        snapshot.deleted.clone_from(&plan.deleted);
+        self.restore_snapshot(snapshot);
+        Ok(())
+    }
+
+    /// The pending changes and undo history as a session to save
+    #[must_use]
+    pub fn session(&self, saved_at: DateTime<FixedOffset>) -> Session {
+        Session {
+            branch: self.branch_name.clone(),
+            saved_at,
+            current: self.snapshot("Saved session"),
+            undo_stack: self.undo_stack.clone(),
+            redo_stack: self.redo_stack.clone(),
+        }
+    }
+
+    /// Pick up a saved session: its pending changes and undo history replace
+    /// the current ones
+    ///
+    /// # Errors
+    /// Returns an error if the session was saved on another branch or for
+    /// commits that aren't the loaded ones.
+    pub fn resume_session(&mut self, session: Session) -> Result<()> {
+        if session.branch != self.branch_name {
+            return Err(HistError::Session(format!(
+                "saved on branch '{}'",
+                session.branch
+            )));
+        }
+        let loaded: HashSet<&CommitId> = self.original_order.iter().collect();
+        let fits = |snapshot: &UndoSnapshot| {
+            snapshot.commit_order.len() == loaded.len()
+                && snapshot.commit_order.iter().all(|id| loaded.contains(id))
+                && snapshot.modifications.keys().all(|id| loaded.contains(id))
+        };
+        if !std::iter::once(&session.current)
+            .chain(&session.undo_stack)
+            .chain(&session.redo_stack)
+            .all(fits)
+        {
+            return Err(HistError::Session(
+                "saved for other commits (load the same ones with --limit or --all)".to_string(),
+            ));
+        }
+        self.restore_snapshot(session.current);
+        self.undo_stack = session.undo_stack;
+        self.redo_stack = session.redo_stack;
+        Ok(())
+    }
+
+    /// The pending order, edits and deletions as a plan (`:export plan.json`)
+    ///
+    /// # Errors
+    /// Returns an error if squashes, splits or a new root commit are pending,
+    /// which a plan can't hold.
+    pub fn edit_plan(&self) -> Result<EditPlan> {
+        if !self.squashes.is_empty() || !self.splits.is_empty() || self.new_root.is_some() {
+            return Err(HistError::Plan(
+                "squashes, splits and new root commits can't be exported".to_string(),
+            ));
+        }
+        let order = if self.current_order == self.original_order {
+            Vec::new()
+        } else {
+            self.current_order.clone()
+        };
+        Ok(EditPlan {
+            order,
+            modifications: self
+                .modifications
+                .iter()
+                .filter(|(_, mods)| mods.has_modifications())
+                .map(|(id, mods)| (*id, mods.clone()))
+                .collect(),
+            deleted: self.deleted.clone(),
+        })
+    }
+
+    /// Remap the (effective) authors and committers of commits through a
+    /// mailmap, as one undo step
+    ///
+    /// Returns how many commits changed.
+    pub fn apply_mailmap(&mut self, ids: &[CommitId], mailmap: &git2::Mailmap) -> usize {
+        let changes: Vec<(CommitId, Option<Person>, Option<Person>)> = self
+            .commits
+            .iter()
+            .filter(|c| ids.contains(&c.id))
+            .filter_map(|commit| {
+                let mods = self
+                    .modifications
+                    .get(&commit.id)
+                    .cloned()
+                    .unwrap_or_default();
+                let author = Person::new(
+                    mods.effective_author_name(&commit.author.name),
+                    mods.effective_author_email(&commit.author.email),
+                );
+                let committer = Person::new(
+                    mods.effective_committer_name(&commit.committer.name),
+                    mods.effective_committer_email(&commit.committer.email),
+                );
+                let author = mailmap::resolve(mailmap, &author);
+                let committer = mailmap::resolve(mailmap, &committer);
+                (author.is_some() || committer.is_some()).then_some((commit.id, author, committer))
+            })
+            .collect();
+        if changes.is_empty() {
+            return 0;
+        }
+
+        let count = changes.len();
+        self.save_undo(&format!("Apply mailmap to {count} commit(s)"));
+        for (id, author, committer) in changes {
+            let mods = self.get_or_create_modifications(id);
+            if let Some(author) = author {
+                mods.author_name = Some(author.name);
+                mods.author_email = Some(author.email);
+            }
+            if let Some(committer) = committer {
+                mods.committer_name = Some(committer.name);
+                mods.committer_email = Some(committer.email);
+            }
+        }
+        count
+    }
+
+    /// The pending identity edits of kept commits, as old → new mailmap
+    /// entries
+    #[must_use]
+    pub fn identity_mailmap(&self) -> Mailmap {
+        let mut mailmap = Mailmap::default();
+        for commit in self.commits.iter().filter(|c| !self.is_deleted(c.id)) {
+            let Some(mods) = self.modifications.get(&commit.id) else {
+                continue;
+            };
+            let author = Person::new(
+                mods.effective_author_name(&commit.author.name),
+                mods.effective_author_email(&commit.author.email),
+            );
+            let committer = Person::new(
+                mods.effective_committer_name(&commit.committer.name),
+                mods.effective_committer_email(&commit.committer.email),
+            );
+            for (old, new) in [(&commit.author, author), (&commit.committer, committer)] {
+                if *old != new {
+                    mailmap.add(old, &new);
+                }
+            }
+        }
+        mailmap
+    }
+
+    /// The oldest loaded commit, if it is the root commit (so a new root can
+    /// go beneath it)
+    #[must_use]
+    pub fn root_commit(&self) -> Option<&CommitData> {
+        let oldest = self.original_order.last()?;
+        self.commits
+            .iter()
+            .find(|c| c.id == *oldest && c.parent_ids.is_empty())
+    }
+
+    /// Stage a commit to add beneath the root commit, or drop the staged one
+    ///
+    /// Returns whether anything changed.
+    pub fn set_new_root(&mut self, root: Option<NewRoot>) -> bool {
+        if self.new_root == root {
+            return false;
+        }
+        self.save_undo(if root.is_some() {
+            "Add a new root commit"
+        } else {
+            "Remove the new root commit"
+        });
+        self.new_root = root;
+        true
+    }
+
+    /// Set error message (auto-cleared on next action)
+    pub fn set_error(&mut self, msg: impl Into<String>) {
+        self.error_message = Some(msg.into());
+        self.success_message = None;
+    }
+
+    /// Set success message (auto-cleared on next action)
+    pub fn set_success(&mut self, msg: impl Into<String>) {
+        self.success_message = Some(msg.into());
+        self.error_message = None;
+    }
+
+    /// Clear status messages
+    pub fn clear_messages(&mut self) {
+        self.error_message = None;
+        self.success_message = None;
+    }
+
+    // ==================== Visual Selection Methods ====================
+
+    /// Enter visual mode at current cursor position
+    pub fn enter_visual_mode(&mut self, visual_type: VisualType) {
+        let anchor = (self.cursor, self.column_index);
+        self.mode = AppMode::Visual {
+            anchor,
+            visual_type,
+        };
+    }
+
+    /// Jump the cursor to the other end of the visual selection (vim `o`)
+    ///
+    /// Swaps anchor and cursor so the opposite edge of the range can be
+    /// adjusted. In block mode this moves to the diagonally opposite corner.
+    pub fn swap_visual_anchor(&mut self) {
+        if let AppMode::Visual {
+            anchor,
+            visual_type,
+        } = self.mode.clone()
+        {
+            self.mode = AppMode::Visual {
+                anchor: (self.cursor, self.column_index),
+                visual_type,
+            };
+            self.cursor = anchor.0;
+            self.column_index = anchor.1;
+            self.adjust_scroll();
+            self.reset_detail_scroll();
+        }
+    }
+
+    /// Exit visual mode without applying selection
+    pub fn exit_visual_mode(&mut self) {
+        self.mode = AppMode::Normal;
+    }
+
+    /// Get the visual selection range as ((`start_row`, `start_col`), (`end_row`, `end_col`))
+    /// Returns None if not in visual mode
+    #[must_use]
+    pub fn visual_range(&self) -> Option<((usize, usize), (usize, usize))> {
+        match &self.mode {
+            AppMode::Visual { anchor, .. } => {
+                let current = (self.cursor, self.column_index);
+                // Normalize so start <= end
+                let start_row = anchor.0.min(current.0);
+                let end_row = anchor.0.max(current.0);
+                let start_col = anchor.1.min(current.1);
+                let end_col = anchor.1.max(current.1);
+                Some(((start_row, start_col), (end_row, end_col)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if a cell is within the visual selection
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn is_in_visual_selection(&self, row: usize, col: usize) -> bool {
+        match &self.mode {
+            AppMode::Visual {
+                anchor,
+                visual_type,
+            } => {
+                let current = (self.cursor, self.column_index);
+                let start_row = anchor.0.min(current.0);
+                let end_row = anchor.0.max(current.0);
+
+                match visual_type {
+                    VisualType::Line => {
+                        // Line-wise: entire row is selected if row is in range
+                        row >= start_row && row <= end_row
+                    }
+                    VisualType::Block => {
+                        // Block-wise: cell must be within rectangular region
+                        let start_col = anchor.1.min(current.1);
+                        let end_col = anchor.1.max(current.1);
+                        row >= start_row && row <= end_row && col >= start_col && col <= end_col
+                    }
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if a row is within the visual selection (for row-level styling)
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn is_row_in_visual_selection(&self, row: usize) -> bool {
+        match &self.mode {
+            AppMode::Visual { anchor, .. } => {
+                let start_row = anchor.0.min(self.cursor);
+                let end_row = anchor.0.max(self.cursor);
+                row >= start_row && row <= end_row
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the visual selection type if in visual mode
+    #[must_use]
+    pub fn visual_type(&self) -> Option<VisualType> {
+        match &self.mode {
+            AppMode::Visual { visual_type, .. } => Some(*visual_type),
+            _ => None,
+        }
+    }
+
+    /// Apply visual selection to the selected set (confirm visual selection)
+    #[allow(dead_code)]
+    pub fn apply_visual_selection(&mut self) {
+        if let Some(((start_row, _), (end_row, _))) = self.visual_range() {
+            // Collect IDs first to avoid borrow issues
+            let ids: Vec<CommitId> = (start_row..=end_row)
+                .filter_map(|row| self.visible_commit(row))
+                .map(|c| c.id)
+                .collect();
+            for id in ids {
+                self.selected.insert(id);
+            }
+        }
+        self.mode = AppMode::Normal;
+    }
+
+    /// Get the count of rows in visual selection
+    #[must_use]
+    pub fn visual_selection_count(&self) -> usize {
+        match self.visual_range() {
+            Some(((start_row, _), (end_row, _))) => end_row - start_row + 1,
+            None => 0,
+        }
+    }
+
+    /// Capture visual selection as edit targets and exit visual mode
+    /// Returns the number of commits captured
+    pub fn capture_visual_edit_targets(&mut self) -> usize {
+        if let Some(((start_row, _), (end_row, _))) = self.visual_range() {
+            let ids: Vec<CommitId> = (start_row..=end_row)
+                .filter_map(|row| self.visible_commit(row))
+                .map(|c| c.id)
+                .collect();
+            let count = ids.len();
+            self.visual_edit_targets = Some(ids);
+            self.mode = AppMode::Normal;
+            count
+        } else {
+            0
+        }
+    }
+
+    /// Clear visual edit targets (called after edit completes)
+    pub fn clear_visual_edit_targets(&mut self) {
+        self.visual_edit_targets = None;
+    }
+
+    /// Get the commits to edit: visual targets > checkbox selected > just cursor
+    #[must_use]
+    pub fn commits_to_edit(&self) -> Vec<CommitId> {
+        if let Some(ref targets) = self.visual_edit_targets {
+            targets.clone()
+        } else if !self.selected.is_empty() {
+            self.selected.iter().copied().collect()
+        } else if let Some(id) = self.cursor_commit_id() {
+            vec![id]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Check whether a commit matches a (lowercased) search query
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn create_test_commit(id_str: &str, summary: &str) -> CommitData {
+        let oid = git2::Oid::from_str(id_str).unwrap();
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let dt = utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 0).unwrap();
+
+        CommitData {
+            id: CommitId(oid),
+            short_hash: id_str[..7].to_string(),
+            author: crate::git::commit::Person::new("Test Author", "test@example.com"),
+            author_date: dt,
+            committer: crate::git::commit::Person::new("Test Author", "test@example.com"),
+            committer_date: dt,
+            message: summary.to_string(),
+            summary: summary.to_string(),
+            parent_ids: vec![],
+            tree_id: git2::Oid::from_str("abcdef1234567890abcdef1234567890abcdef12").unwrap(),
+            is_merge: false,
+        }
+    }
+
+    fn create_test_state() -> AppState {
+        let commits = vec![
+            create_test_commit("1111111111111111111111111111111111111111", "First commit"),
+            create_test_commit("2222222222222222222222222222222222222222", "Second commit"),
+            create_test_commit("3333333333333333333333333333333333333333", "Third commit"),
+        ];
+        AppState::new(commits, "main".to_string(), false)
+    }
+
+    #[test]
+    fn test_app_state_creation() {
+        let state = create_test_state();
+        assert_eq!(state.commits.len(), 3);
+        assert_eq!(state.cursor, 0);
+        assert_eq!(state.branch_name, "main");
+        assert!(!state.has_upstream);
+        assert!(state.modifications.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_movement() {
+        let mut state = create_test_state();
+
+        // Test cursor down
+        state.cursor_down();
+        assert_eq!(state.cursor, 1);
+        state.cursor_down();
+        assert_eq!(state.cursor, 2);
+
+        // Test cursor up
+        state.cursor_up();
+        assert_eq!(state.cursor, 1);
+
+        // Test cursor top
+        state.cursor_top();
+        assert_eq!(state.cursor, 0);
+
+        // Test cursor bottom
+        state.cursor_bottom();
+        assert_eq!(state.cursor, 2);
+    }
+
+    #[test]
+    fn test_cursor_movement_bounds() {
+        let mut state = create_test_state();
+
+        // Try to move up from top
+        state.cursor_up();
+        assert_eq!(state.cursor, 0);
+
+        // Try to move down beyond bottom
+        state.cursor_bottom();
+        state.cursor_down();
+        assert_eq!(state.cursor, 2); // Should stay at bottom
+    }
+
+    #[test]
+    fn test_column_navigation() {
+        let mut state = create_test_state();
+
+        assert_eq!(state.column_index, 0);
+
+        state.column_right();
+        assert_eq!(state.column_index, 1);
+
+        state.column_left();
+        assert_eq!(state.column_index, 0);
+    }
+
+    #[test]
+    fn test_column_navigation_wraps() {
+        let mut state = create_test_state();
+
+        // Test wrapping right
+        state.column_index = AppState::NUM_COLUMNS - 1;
+        state.column_right();
+        assert_eq!(state.column_index, 0);
+
+        // Test wrapping left
+        state.column_index = 0;
+        state.column_left();
+        assert_eq!(state.column_index, AppState::NUM_COLUMNS - 1);
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut state = create_test_state();
+
+        // Toggle selection on first commit
+        state.toggle_selection();
+        let first_id = state.commits[0].id;
+        assert!(state.is_selected(first_id));
+
+        // Toggle again to deselect
+        state.toggle_selection();
+        assert!(!state.is_selected(first_id));
+    }
+
+    #[test]
+    fn test_select_all() {
+        let mut state = create_test_state();
+
+        state.select_all();
+        assert_eq!(state.selected.len(), 3);
+
+        for commit in &state.commits {
+            assert!(state.is_selected(commit.id));
+        }
+    }
+
+    #[test]
+    fn test_deselect_all() {
+        let mut state = create_test_state();
+
+        state.select_all();
+        state.deselect_all();
+        assert_eq!(state.selected.len(), 0);
+    }
+
+    #[test]
+    fn test_modifications() {
+        let mut state = create_test_state();
+        let commit_id = state.commits[0].id;
+
+        // Initially no modifications
+        assert!(!state.is_modified(commit_id));
+        assert!(!state.is_dirty());
+
+        // Add a modification
+        let mods = state.get_or_create_modifications(commit_id);
+        mods.author_name = Some("New Author".to_string());
+
+        assert!(state.is_modified(commit_id));
+        assert!(state.is_dirty());
+        assert_eq!(state.modified_count(), 1);
+    }
+
+    #[test]
+    fn test_clear_modifications() {
+        let mut state = create_test_state();
+        let commit_id = state.commits[0].id;
+
+        // Add modifications
+        let mods = state.get_or_create_modifications(commit_id);
+        mods.author_name = Some("New Author".to_string());
+
+        assert!(state.is_dirty());
+
+        // Clear all modifications
+        state.clear_modifications();
+        assert!(!state.is_dirty());
+        assert_eq!(state.modified_count(), 0);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut state = create_test_state();
+        let commit_id = state.commits[0].id;
+
+        // Save initial state
+        state.save_undo("Initial modification");
+
+        // Make a modification
+        let mods = state.get_or_create_modifications(commit_id);
+        mods.author_name = Some("New Author".to_string());
+
+        // Undo should restore
+        let undone = state.undo();
+        assert!(undone);
+        assert!(!state.is_modified(commit_id));
+
+        // Redo should restore the modification
+        let redone = state.redo();
+        assert!(redone);
+        assert!(state.is_modified(commit_id));
+    }
+
+    #[test]
+    fn test_undo_history() {
+        let mut state = create_test_state();
+        let (first, second) = (state.commits[0].id, state.commits[1].id);
+        for (id, name) in [(first, "Alice"), (second, "Bob"), (first, "Carol")] {
+            state.save_undo(&format!("Set author to {name}"));
+            state.get_or_create_modifications(id).author_name = Some(name.to_string());
+        }
+
+        assert_eq!(
+            state.undo_history(),
+            vec![
+                ("Set author to Carol", false),
+                ("Set author to Bob", false),
+                ("Set author to Alice", false),
+            ]
+        );
+        assert_eq!(
+            state.undo_step_changes(0),
+            vec!["1111111 Author name: Alice → Carol".to_string()]
+        );
+        assert_eq!(
+            state.undo_step_changes(2),
+            vec!["1111111 Author name: Test Author → Alice".to_string()]
+        );
+
+        // Jumping to the second step undoes it and everything after it
+        assert_eq!(state.jump_undo_history(1), 2);
+        assert_eq!(
+            state.undo_history(),
+            vec![
+                ("Set author to Carol", true),
+                ("Set author to Bob", true),
+                ("Set author to Alice", false),
+            ]
+        );
+        assert!(!state.is_modified(second));
+        assert_eq!(
+            state.undo_step_changes(1),
+            vec!["2222222 Author name: Test Author → Bob".to_string()]
+        );
+        assert_eq!(
+            state.undo_step_changes(0),
+            vec!["1111111 Author name: Alice → Carol".to_string()]
+        );
+
+        // Jumping to an undone step redoes up to it
+        assert_eq!(state.jump_undo_history(0), 2);
+        assert_eq!(
+            state.modifications[&first].author_name.as_deref(),
+            Some("Carol")
+        );
+        assert_eq!(state.redo_stack.len(), 0);
+    }
+
+    #[test]
+    fn test_undo_redo_empty() {
+        let mut state = create_test_state();
+
+        // Undo with empty stack
+        let undone = state.undo();
+        assert!(!undone);
+
+        // Redo with empty stack
+        let redone = state.redo();
+        assert!(!redone);
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let mut state = create_test_state();
+
+        // Apply filter
+        state.search_query = "Second".to_string();
+        state.apply_filter();
+
+        // Should only show one commit
+        assert_eq!(state.visible_len(), 1);
+        assert_eq!(state.visible_commit(0).unwrap().summary, "Second commit");
+        assert!(state.visible_commit(1).is_none());
+
+        // Clear filter
+        state.clear_filter();
+        assert_eq!(state.visible_len(), 3);
+        assert_eq!(state.visible_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let mut state = create_test_state();
+        let summaries = |state: &AppState| {
+            state
+                .visible_iter()
+                .map(|c| c.summary.clone())
+                .collect::<Vec<_>>()
+        };
+
+        state.set_sort(Some(SortOrder {
+            key: SortKey::Message,
+            descending: true,
+        }));
+        assert_eq!(
+            summaries(&state),
+            ["Third commit", "Second commit", "First commit"]
+        );
+        // The commit order itself is untouched
+        assert_eq!(state.commits[0].summary, "First commit");
+
+        // Filtered rows are sorted too, and the sort outlives the filter
+        state.search_query = "ir".to_string();
+        state.apply_filter();
+        assert_eq!(summaries(&state), ["Third commit", "First commit"]);
+        state.clear_filter();
+        assert_eq!(
+            summaries(&state),
+            ["Third commit", "Second commit", "First commit"]
+        );
+
+        state.set_sort(None);
+        assert!(state.filtered_indices.is_none());
+    }
+
+    #[test]
+    fn test_search_filter_case_insensitive() {
+        let mut state = create_test_state();
+
+        state.search_query = "SECOND".to_string();
+        state.apply_filter();
+
+        assert_eq!(state.visible_len(), 1);
+    }
+
+    #[test]
+    fn test_search_filter_empty_result() {
+        let mut state = create_test_state();
+
+        state.search_query = "nonexistent".to_string();
+        state.apply_filter();
+
+        assert!(state.filtered_indices.is_none());
+    }
+
+    #[test]
+    fn test_filter_keeps_cursor_on_commit() {
+        let mut state = create_test_state();
+        state.cursor = 2; // "Third commit"
+
+        state.search_query = "commit".to_string();
+        state.apply_filter();
+        assert_eq!(state.cursor_commit().unwrap().summary, "Third commit");
+
+        // Cursor commit filtered out - falls back to top
+        state.search_query = "First".to_string();
+        state.apply_filter();
+        assert_eq!(state.cursor, 0);
+        assert_eq!(state.cursor_commit().unwrap().summary, "First commit");
+    }
+
+    #[test]
+    fn test_clear_filter_restores_cursor_context() {
+        let mut state = create_test_state();
+
+        state.search_query = "Third".to_string();
+        state.apply_filter();
+        assert_eq!(state.cursor, 0);
+
+        state.clear_filter();
+        assert_eq!(state.cursor, 2);
+        assert_eq!(state.cursor_commit().unwrap().summary, "Third commit");
+    }
+
+    #[test]
+    fn test_selection_preserved_across_filter() {
+        let mut state = create_test_state();
+        state.select_all();
+
+        state.search_query = "Second".to_string();
+        state.apply_filter();
+        assert_eq!(state.selected.len(), 3);
+        assert_eq!(state.hidden_selected_count(), 2);
+
+        state.clear_filter();
+        assert_eq!(state.selected.len(), 3);
+        assert_eq!(state.hidden_selected_count(), 0);
+    }
+
+    #[test]
+    fn test_visual_mode() {
+        let mut state = create_test_state();
+
+        // Enter visual mode
+        state.enter_visual_mode(VisualType::Line);
+        assert!(matches!(state.mode, AppMode::Visual { .. }));
+
+        // Check visual range
+        let range = state.visual_range();
+        assert!(range.is_some());
+
+        // Exit visual mode
+        state.exit_visual_mode();
+        assert_eq!(state.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_visual_selection_line() {
+        let mut state = create_test_state();
+
+        state.enter_visual_mode(VisualType::Line);
+        state.cursor_down();
+        state.cursor_down();
+
+        // Should select rows 0, 1, 2
+        assert_eq!(state.visual_selection_count(), 3);
+        assert!(state.is_row_in_visual_selection(0));
+        assert!(state.is_row_in_visual_selection(1));
+        assert!(state.is_row_in_visual_selection(2));
+    }
+
+    #[test]
+    fn test_swap_visual_anchor() {
+        let mut state = create_test_state();
+        state.column_index = 2;
+
+        state.enter_visual_mode(VisualType::Block);
+        state.cursor_down();
+        state.cursor_down();
+        state.column_right();
+        let range_before = state.visual_range();
+
+        state.swap_visual_anchor();
+        assert_eq!(state.cursor_position(), (0, 2));
+        assert_eq!(
+            state.mode,
+            AppMode::Visual {
+                anchor: (2, 3),
+                visual_type: VisualType::Block,
+            }
+        );
+        // Selected region is unchanged
+        assert_eq!(state.visual_range(), range_before);
+
+        // Swapping again returns to the original end
+        state.swap_visual_anchor();
+        assert_eq!(state.cursor_position(), (2, 3));
+    }
+
+    #[test]
+    fn test_swap_visual_anchor_outside_visual_mode() {
+        let mut state = create_test_state();
+        state.cursor = 1;
+
+        state.swap_visual_anchor();
+        assert_eq!(state.cursor, 1);
+        assert_eq!(state.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_visual_selection_block() {
+        let mut state = create_test_state();
+
+        state.enter_visual_mode(VisualType::Block);
+        state.cursor_down();
+        state.column_right();
+
+        // Check that specific cells are selected
+        assert!(state.is_in_visual_selection(0, 0));
+        assert!(state.is_in_visual_selection(0, 1));
+        assert!(state.is_in_visual_selection(1, 0));
+        assert!(state.is_in_visual_selection(1, 1));
+        assert!(!state.is_in_visual_selection(2, 0));
+    }
+
+    #[test]
+    fn test_apply_visual_selection() {
+        let mut state = create_test_state();
+
+        state.enter_visual_mode(VisualType::Line);
+        state.cursor_down();
+
+        state.apply_visual_selection();
+
+        // Should have selected 2 commits
+        assert_eq!(state.selected.len(), 2);
+        assert_eq!(state.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_capture_visual_edit_targets() {
+        let mut state = create_test_state();
+
+        state.enter_visual_mode(VisualType::Line);
+        state.cursor_down();
+
+        let count = state.capture_visual_edit_targets();
+        assert_eq!(count, 2);
+        assert!(state.visual_edit_targets.is_some());
+        assert_eq!(state.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_commits_to_edit_priority() {
+        let mut state = create_test_state();
+
+        // Test 1: Just cursor (no selection, no visual targets)
+        let to_edit = state.commits_to_edit();
+        assert_eq!(to_edit.len(), 1);
+        assert_eq!(to_edit[0], state.commits[0].id);
+
+        // Test 2: Checkbox selection takes priority over cursor
+        state.toggle_selection();
+        let to_edit = state.commits_to_edit();
+        assert_eq!(to_edit.len(), 1);
+
+        // Test 3: Visual targets take priority over checkbox
+        state.visual_edit_targets = Some(vec![state.commits[1].id, state.commits[2].id]);
+        let to_edit = state.commits_to_edit();
+        assert_eq!(to_edit.len(), 2);
+        assert_eq!(to_edit[0], state.commits[1].id);
+    }
+
+    #[test]
+    fn test_cursor_position_queries() {
+        let mut state = create_test_state();
+
+        state.set_cursor_position(1, 2);
+        assert_eq!(state.cursor_row(), 1);
+        assert_eq!(state.cursor_column(), 2);
+        assert_eq!(state.cursor_position(), (1, 2));
+        assert!(state.is_cursor_on_row(1));
+        assert!(state.is_cursor_on_column(2));
+        assert!(state.is_cursor_on_cell(1, 2));
+        assert!(!state.is_cursor_on_cell(0, 2));
+    }
+
+    #[test]
+    fn test_is_cursor_on_editable_column() {
+        let mut state = create_test_state();
+
+        // Column 0 and 1 are not editable (Selection, Hash)
+        state.set_cursor_column(0);
+        assert!(!state.is_cursor_on_editable_column());
+
+        state.set_cursor_column(1);
+        assert!(!state.is_cursor_on_editable_column());
+
+        // Column 2+ are editable
+        state.set_cursor_column(2);
+        assert!(state.is_cursor_on_editable_column());
+    }
+
+    #[test]
+    fn test_page_navigation() {
+        let mut state = create_test_state();
+
+        state.page_down(2);
+        assert_eq!(state.cursor, 2);
+
+        state.page_up(1);
+        assert_eq!(state.cursor, 1);
+
+        // Page down beyond bottom
+        state.page_down(10);
+        assert_eq!(state.cursor, 2);
+
+        // Page up beyond top
+        state.page_up(10);
+        assert_eq!(state.cursor, 0);
+    }
+
+    #[test]
+    fn test_detail_scroll() {
+        let mut state = create_test_state();
+        state.detail_max_scroll = 10;
+
+        state.detail_scroll_down(5);
+        assert_eq!(state.detail_scroll, 5);
+
+        state.detail_scroll_up(2);
+        assert_eq!(state.detail_scroll, 3);
+
+        // Test bounds
+        state.detail_scroll_down(20);
+        assert_eq!(state.detail_scroll, 10); // Clamped to max
+
+        state.detail_scroll_up(100);
+        assert_eq!(state.detail_scroll, 0); // Clamped to 0
+    }
+
+    #[test]
+    fn test_reset_detail_scroll() {
+        let mut state = create_test_state();
+        state.detail_scroll = 5;
+
+        state.reset_detail_scroll();
+        assert_eq!(state.detail_scroll, 0);
+    }
+
+    #[test]
+    fn test_cursor_commit() {
+        let mut state = create_test_state();
+
+        let commit = state.cursor_commit();
+        assert!(commit.is_some());
+        assert_eq!(commit.unwrap().summary, "First commit");
+
+        state.cursor_down();
+        let commit = state.cursor_commit();
+        assert_eq!(commit.unwrap().summary, "Second commit");
+    }
+
+    #[test]
+    fn test_cursor_commit_id() {
+        let state = create_test_state();
+
+        let id = state.cursor_commit_id();
+        assert!(id.is_some());
+        assert_eq!(id.unwrap(), state.commits[0].id);
+    }
+
+    #[test]
+    fn test_messages() {
+        let mut state = create_test_state();
+
+        state.set_error("Test error");
+        assert!(state.error_message.is_some());
+        assert!(state.success_message.is_none());
+
+        state.set_success("Test success");
+        assert!(state.success_message.is_some());
+        assert!(state.error_message.is_none());
+
+        state.clear_messages();
+        assert!(state.error_message.is_none());
+        assert!(state.success_message.is_none());
+    }
+
+    #[test]
+    fn test_move_commit_up() {
+        let mut state = create_test_state();
+        let first_id = state.commits[0].id;
+        let second_id = state.commits[1].id;
+
+        state.cursor = 1;
+        state.move_commit_up();
+
+        // Second commit should now be first
+        assert_eq!(state.commits[0].id, second_id);
+        assert_eq!(state.commits[1].id, first_id);
+        assert_eq!(state.cursor, 0);
+        assert!(state.is_dirty()); // Order changed
+    }
+
+    #[test]
+    fn test_move_commit_down() {
+        let mut state = create_test_state();
+        let first_id = state.commits[0].id;
+        let second_id = state.commits[1].id;
+
+        state.cursor = 0;
+        state.move_commit_down();
+
+        // First commit should now be second
+        assert_eq!(state.commits[0].id, second_id);
+        assert_eq!(state.commits[1].id, first_id);
+        assert_eq!(state.cursor, 1);
     }
-}
 
-#[cfg(test)]
-#[allow(clippy::unwrap_used)]
-mod tests {
-    use super::*;
-    use chrono::{FixedOffset, TimeZone};
+    #[test]
+    fn test_visual_type() {
+        let mut state = create_test_state();
 
-    fn create_test_commit(id_str: &str, summary: &str) -> CommitData {
-        let oid = git2::Oid::from_str(id_str).unwrap();
-        let utc = FixedOffset::east_opt(0).unwrap();
-        let dt = utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 0).unwrap();
+        assert!(state.visual_type().is_none());
 
-        CommitData {
-            id: CommitId(oid),
-            short_hash: id_str[..7].to_string(),
-            author: crate::git::commit::Person::new("Test Author", "test@example.com"),
-            author_date: dt,
-            committer: crate::git::commit::Person::new("Test Author", "test@example.com"),
-            committer_date: dt,
-            message: summary.to_string(),
-            summary: summary.to_string(),
-            parent_ids: vec![],
-            tree_id: git2::Oid::from_str("abcdef1234567890abcdef1234567890abcdef12").unwrap(),
-            is_merge: false,
-        }
+        state.enter_visual_mode(VisualType::Line);
+        assert_eq!(state.visual_type(), Some(VisualType::Line));
+
+        state.exit_visual_mode();
+        assert!(state.visual_type().is_none());
     }
 
-    fn create_test_state() -> AppState {
+    #[test]
+    fn test_resolve_hash() {
+        let state = create_test_state();
+
+        assert_eq!(state.resolve_hash("2222222").unwrap(), 1);
+        assert_eq!(state.resolve_hash("333").unwrap(), 2);
+        assert!(matches!(
+            state.resolve_hash("abc"),
+            Err(HistError::CommitNotFound(_))
+        ));
+        assert!(state.resolve_hash("").is_err());
+    }
+
+    #[test]
+    fn test_resolve_hash_ambiguous() {
         let commits = vec![
-            create_test_commit("1111111111111111111111111111111111111111", "First commit"),
-            create_test_commit("2222222222222222222222222222222222222222", "Second commit"),
-            create_test_commit("3333333333333333333333333333333333333333", "Third commit"),
+            create_test_commit("1111111111111111111111111111111111111111", "First"),
+            create_test_commit("1111111222222222222222222222222222222222", "Second"),
         ];
-        AppState::new(commits, "main".to_string(), false)
+        let state = AppState::new(commits, "main".to_string(), false);
+
+        assert!(matches!(
+            state.resolve_hash("1111111"),
+            Err(HistError::AmbiguousHash(_))
+        ));
+        assert_eq!(state.resolve_hash("11111112").unwrap(), 1);
     }
 
     #[test]
-    fn test_app_state_creation() {
+    fn test_select_range_by_hash() {
+        let mut state = create_test_state();
+
+        // Endpoints in reverse order still select the inclusive range
+        let count = state.select_range_by_hash("3333333", "2222222").unwrap();
+        assert_eq!(count, 2);
+        assert!(!state.is_selected(state.commits[0].id));
+        assert!(state.is_selected(state.commits[1].id));
+        assert!(state.is_selected(state.commits[2].id));
+
+        // Unknown hash leaves selection untouched
+        assert!(state.select_range_by_hash("1111111", "ffff").is_err());
+        assert_eq!(state.selected.len(), 2);
+    }
+
+    #[test]
+    fn test_sync_author_to_committer_default() {
         let state = create_test_state();
-        assert_eq!(state.commits.len(), 3);
-        assert_eq!(state.cursor, 0);
-        assert_eq!(state.branch_name, "main");
-        assert!(!state.has_upstream);
-        assert!(state.modifications.is_empty());
+        // Default should be true (sync enabled)
+        assert!(state.sync_author_to_committer);
     }
 
     #[test]
-    fn test_cursor_movement() {
+    fn test_set_sync_author_to_committer() {
         let mut state = create_test_state();
 
-        // Test cursor down
-        state.cursor_down();
-        assert_eq!(state.cursor, 1);
-        state.cursor_down();
-        assert_eq!(state.cursor, 2);
+        // Default is true
+        assert!(state.sync_author_to_committer);
 
-        // Test cursor up
-        state.cursor_up();
-        assert_eq!(state.cursor, 1);
+        // Disable sync
+        state.set_sync_author_to_committer(false);
+        assert!(!state.sync_author_to_committer);
 
-        // Test cursor top
-        state.cursor_top();
+        // Re-enable sync
+        state.set_sync_author_to_committer(true);
+        assert!(state.sync_author_to_committer);
+    }
+
+    #[test]
+    fn test_jump_to_match_wraps_without_filtering() {
+        let mut state = create_test_state();
+        state.last_search = "ir".to_string(); // "First" and "Third"
+
+        assert_eq!(state.jump_to_match(true), Some(false));
+        assert_eq!(state.cursor, 2);
+        assert!(state.filtered_indices.is_none());
+
+        // Wraps back to the top
+        assert_eq!(state.jump_to_match(true), Some(true));
         assert_eq!(state.cursor, 0);
 
-        // Test cursor bottom
-        state.cursor_bottom();
+        // Backwards from the top wraps to the bottom
+        assert_eq!(state.jump_to_match(false), Some(true));
         assert_eq!(state.cursor, 2);
+        assert_eq!(state.jump_to_match(false), Some(false));
+        assert_eq!(state.cursor, 0);
     }
 
     #[test]
-    fn test_cursor_movement_bounds() {
+    fn test_jump_to_match_no_search_or_no_match() {
         let mut state = create_test_state();
+        assert_eq!(state.jump_to_match(true), None);
 
-        // Try to move up from top
-        state.cursor_up();
+        state.last_search = "nothing like this".to_string();
+        assert_eq!(state.jump_to_match(true), None);
         assert_eq!(state.cursor, 0);
-
-        // Try to move down beyond bottom
-        state.cursor_bottom();
-        state.cursor_down();
-        assert_eq!(state.cursor, 2); // Should stay at bottom
     }
 
     #[test]
-    fn test_column_navigation() {
+    fn test_regex_search() {
         let mut state = create_test_state();
+        state.commits[1].message = "Second commit\n\nFixes: #12\n".to_string();
 
-        assert_eq!(state.column_index, 0);
+        state.search_query = "re:^(first|third)".to_string();
+        state.apply_filter();
+        assert_eq!(state.filtered_indices, Some(vec![0, 2]));
 
-        state.column_right();
-        assert_eq!(state.column_index, 1);
+        // Matches lines of the body too
+        state.search_query = "re:^fixes: #\\d+$".to_string();
+        state.apply_filter();
+        assert_eq!(state.filtered_indices, Some(vec![1]));
 
-        state.column_left();
-        assert_eq!(state.column_index, 0);
+        // Without the prefix it's a plain substring
+        state.search_query = "(first|third)".to_string();
+        state.apply_filter();
+        assert_eq!(state.filtered_indices, None);
+
+        let err = SearchQuery::parse("re:(unclosed").unwrap_err().to_string();
+        assert_eq!(err, "Invalid search: bad regex (unclosed group)");
+        assert!(!err.contains('\n'));
     }
 
     #[test]
-    fn test_column_navigation_wraps() {
+    fn test_last_search_survives_clear_filter() {
         let mut state = create_test_state();
+        state.search_query = "second".to_string();
+        state.apply_filter();
+        state.clear_filter();
+        state.cursor_top();
 
-        // Test wrapping right
-        state.column_index = AppState::NUM_COLUMNS - 1;
-        state.column_right();
-        assert_eq!(state.column_index, 0);
-
-        // Test wrapping left
-        state.column_index = 0;
-        state.column_left();
-        assert_eq!(state.column_index, AppState::NUM_COLUMNS - 1);
+        assert_eq!(state.last_search, "second");
+        assert_eq!(state.jump_to_match(true), Some(false));
+        assert_eq!(state.cursor, 1);
     }
 
     #[test]
-    fn test_selection() {
+    fn test_highlight_groups() {
         let mut state = create_test_state();
+        assert_eq!(state.highlight_group("anything"), None);
 
-        // Toggle selection on first commit
-        state.toggle_selection();
-        let first_id = state.commits[0].id;
-        assert!(state.is_selected(first_id));
+        assert_eq!(state.add_highlight("first").unwrap(), 0);
+        assert_eq!(state.add_highlight("Commit").unwrap(), 1);
+        assert!(state.add_highlight("FIRST").is_err());
 
-        // Toggle again to deselect
-        state.toggle_selection();
-        assert!(!state.is_selected(first_id));
+        // Earlier groups win when several patterns match
+        assert_eq!(state.highlight_group("First commit"), Some(0));
+        assert_eq!(state.highlight_group("Second commit"), Some(1));
+        assert_eq!(state.highlight_group("unrelated"), None);
+
+        assert!(state.remove_highlight("first"));
+        assert!(!state.remove_highlight("first"));
+        assert_eq!(state.highlight_group("First commit"), Some(0));
     }
 
     #[test]
-    fn test_select_all() {
+    fn test_highlight_group_limit() {
         let mut state = create_test_state();
+        for i in 0..MAX_HIGHLIGHT_GROUPS {
+            state.add_highlight(&format!("p{i}")).unwrap();
+        }
+        assert!(state.add_highlight("one too many").is_err());
+    }
 
-        state.select_all();
-        assert_eq!(state.selected.len(), 3);
+    #[test]
+    fn test_replace_in_message() {
+        let mut state = create_test_state();
+        state.commits[0].message = "Fix teh bug\n\nteh details of teh fix".to_string();
+        let id = state.commits[0].id;
 
-        for commit in &state.commits {
-            assert!(state.is_selected(commit.id));
-        }
+        assert_eq!(state.replace_in_message(id, "teh", "the"), 3);
+        assert_eq!(
+            state.modifications[&id].message.as_deref(),
+            Some("Fix the bug\n\nthe details of the fix")
+        );
+        assert_eq!(state.undo_stack.len(), 1);
+
+        // Works on the already-edited message; no match means no undo entry
+        assert_eq!(state.replace_in_message(id, "teh", "the"), 0);
+        assert_eq!(state.undo_stack.len(), 1);
+
+        // Other commits are untouched
+        assert!(!state.is_modified(state.commits[1].id));
+
+        assert!(state.undo());
+        assert!(!state.is_modified(id));
     }
 
     #[test]
-    fn test_deselect_all() {
+    fn test_delete_confirmation() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+
+        // One commit at a time is marked right away
+        assert_eq!(state.delete_confirmation(&ids[..1]), None);
+        assert_eq!(
+            state.delete_confirmation(&ids[..2]),
+            Some(ConfirmAction::DeleteCommits {
+                ids: ids[..2].to_vec()
+            })
+        );
 
-        state.select_all();
-        state.deselect_all();
-        assert_eq!(state.selected.len(), 0);
+        state.delete_confirm_threshold = 2;
+        assert_eq!(state.delete_confirmation(&ids[..2]), None);
+        assert!(state.delete_confirmation(&ids).is_some());
+        // Asking marks nothing yet
+        assert!(state.deleted.is_empty());
     }
 
     #[test]
-    fn test_modifications() {
+    fn test_fill() {
         let mut state = create_test_state();
-        let commit_id = state.commits[0].id;
+        state.extend_commits(vec![create_test_commit(
+            "4444444444444444444444444444444444444444",
+            "Fourth commit",
+        )]);
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        state.commits[1].is_merge = true;
+        state.commits[3].author.name = "Jane Doe".to_string();
+        // The source's pending edit is what gets filled
+        state.get_or_create_modifications(ids[0]).author_name = Some("Jane Doe".to_string());
+
+        // The merge and the commit already named Jane are skipped
+        assert_eq!(
+            state.fill(ids[0], &ids, EditableField::AuthorName),
+            [ids[2]]
+        );
+        assert_eq!(
+            state
+                .field_value(ids[2], EditableField::AuthorName)
+                .as_deref(),
+            Some("Jane Doe")
+        );
+        assert!(!state.is_modified(ids[1]));
+        assert!(!state.is_modified(ids[3]));
+        // Author edits carry over to the committer, as typing them does
+        assert_eq!(
+            state
+                .field_value(ids[2], EditableField::CommitterName)
+                .as_deref(),
+            Some("Jane Doe")
+        );
+
+        // One undo step, and filling again changes nothing
+        assert_eq!(state.undo_stack.len(), 1);
+        assert_eq!(state.fill(ids[0], &ids, EditableField::AuthorName), []);
+        assert_eq!(state.undo_stack.len(), 1);
+        assert!(state.undo());
+        assert!(!state.is_modified(ids[2]));
+
+        // Dates fill as they are edited, timezone included
+        let date = "2023-06-01 09:00:00 +0200";
+        state.get_or_create_modifications(ids[0]).author_date = Some(validate_date(date).unwrap());
+        assert_eq!(
+            state.fill(ids[0], &ids, EditableField::AuthorDate),
+            [ids[2], ids[3]]
+        );
+        assert_eq!(
+            state
+                .field_value(ids[3], EditableField::AuthorDate)
+                .as_deref(),
+            Some(date)
+        );
+    }
 
-        // Initially no modifications
-        assert!(!state.is_modified(commit_id));
-        assert!(!state.is_dirty());
+    #[test]
+    fn test_replace_in_edit_buffer() {
+        let mut state = create_test_state();
+        state.edit_buffer = "Jon Smith-Jon".to_string();
+        state.edit_cursor = 2;
+
+        assert_eq!(state.replace_in_edit_buffer("Jon", "John"), 2);
+        assert_eq!(state.edit_buffer, "John Smith-John");
+        assert_eq!(state.edit_cursor, state.edit_buffer.len());
+        // Nothing staged: it's one step of the buffer's own undo
+        assert_eq!(state.undo_stack.len(), 0);
+        assert_eq!(state.replace_in_edit_buffer("Jon", "John"), 0);
+
+        assert!(state.undo_buffer_edit());
+        assert_eq!(state.edit_buffer, "Jon Smith-Jon");
+        assert_eq!(state.edit_cursor, 2);
+    }
 
-        // Add a modification
-        let mods = state.get_or_create_modifications(commit_id);
-        mods.author_name = Some("New Author".to_string());
+    /// Simulate one key's effect on the edit buffer
+    fn edit_buffer_to(state: &mut AppState, buffer: &str, typing: bool) {
+        let before = state.edit_buffer.clone();
+        let before_cursor = state.edit_cursor;
+        state.edit_buffer = buffer.to_string();
+        state.edit_cursor = buffer.len();
+        state.record_buffer_change(before, before_cursor, typing);
+    }
 
-        assert!(state.is_modified(commit_id));
-        assert!(state.is_dirty());
-        assert_eq!(state.modified_count(), 1);
+    #[test]
+    fn test_buffer_undo_coalesces_typing() {
+        let mut state = create_test_state();
+        state.edit_buffer = "Jane".to_string();
+        state.edit_cursor = 4;
+
+        edit_buffer_to(&mut state, "Jane ", true);
+        edit_buffer_to(&mut state, "Jane D", true);
+        edit_buffer_to(&mut state, "Jane Do", true);
+        // Word delete is its own step
+        edit_buffer_to(&mut state, "Jane ", false);
+
+        assert!(state.undo_buffer_edit());
+        assert_eq!(state.edit_buffer, "Jane Do");
+        assert!(state.undo_buffer_edit());
+        assert_eq!(state.edit_buffer, "Jane");
+        assert_eq!(state.edit_cursor, 4);
+        assert!(!state.undo_buffer_edit());
+
+        assert!(state.redo_buffer_edit());
+        assert_eq!(state.edit_buffer, "Jane Do");
+        assert!(state.redo_buffer_edit());
+        assert_eq!(state.edit_buffer, "Jane ");
+        assert!(!state.redo_buffer_edit());
     }
 
     #[test]
-    fn test_clear_modifications() {
+    fn test_buffer_undo_new_change_clears_redo() {
         let mut state = create_test_state();
-        let commit_id = state.commits[0].id;
+        edit_buffer_to(&mut state, "a", true);
+        assert!(state.undo_buffer_edit());
+        edit_buffer_to(&mut state, "b", true);
+        assert!(!state.redo_buffer_edit());
+
+        // Unchanged buffer (cursor move) records nothing
+        edit_buffer_to(&mut state, "b", false);
+        assert_eq!(state.edit_undo_stack.len(), 1);
+
+        state.reset_buffer_history();
+        assert!(!state.undo_buffer_edit());
+        // Field-level undo never touches the app-level undo stack
+        assert_eq!(state.undo_stack.len(), 0);
+    }
 
-        // Add modifications
-        let mods = state.get_or_create_modifications(commit_id);
-        mods.author_name = Some("New Author".to_string());
+    #[test]
+    fn test_effective_identity_value() {
+        let mut state = create_test_state();
+        let id = state.commits[0].id;
+        assert_eq!(
+            state.effective_identity_value(id, EditableField::AuthorEmail),
+            Some("test@example.com".to_string())
+        );
+
+        state.get_or_create_modifications(id).author_email = Some("new@example.com".to_string());
+        assert_eq!(
+            state.effective_identity_value(id, EditableField::AuthorEmail),
+            Some("new@example.com".to_string())
+        );
+        assert_eq!(
+            state.effective_identity_value(id, EditableField::CommitterEmail),
+            Some("test@example.com".to_string())
+        );
+        assert_eq!(
+            state.effective_identity_value(id, EditableField::AuthorDate),
+            None
+        );
+    }
 
-        assert!(state.is_dirty());
+    #[test]
+    fn test_foreign_authors() {
+        let mut state = create_test_state();
+        state.commits[1].author.email = "alice@example.com".to_string();
+        state.commits[2].author.email = "alice@example.com".to_string();
+        let ids: Vec<CommitId> = state.commits.iter().map(|c| c.id).collect();
 
-        // Clear all modifications
-        state.clear_modifications();
-        assert!(!state.is_dirty());
-        assert_eq!(state.modified_count(), 0);
+        // Unknown user: no warning
+        assert_eq!(state.foreign_authors(&ids), Vec::<String>::new());
+
+        state.user = Some(Person::new("Test", "TEST@example.com"));
+        assert_eq!(state.foreign_authors(&ids), ["alice@example.com"]);
+        assert_eq!(state.foreign_authors(&ids[..1]), Vec::<String>::new());
+
+        state.ownership_acknowledged = true;
+        assert_eq!(state.foreign_authors(&ids), Vec::<String>::new());
+
+        state.ownership_acknowledged = false;
+        state.warn_foreign_commits = false;
+        assert_eq!(state.foreign_authors(&ids), Vec::<String>::new());
     }
 
     #[test]
-    fn test_undo_redo() {
+    fn test_extend_commits_keeps_changes() {
         let mut state = create_test_state();
-        let commit_id = state.commits[0].id;
+        let first = state.commits[0].id;
+        state.save_undo("edit");
+        state.get_or_create_modifications(first).author_name = Some("New".to_string());
+
+        state.extend_commits(vec![create_test_commit(
+            "4444444444444444444444444444444444444444",
+            "Fourth commit",
+        )]);
+
+        assert_eq!(state.commits.len(), 4);
+        assert_eq!(state.current_order.len(), 4);
+        assert_eq!(state.commits[3].summary, "Fourth commit");
+        assert!(state.is_modified(first));
+
+        // Undoing to a snapshot from before the extension keeps the new commit
+        assert!(state.undo());
+        assert_eq!(state.commits.len(), 4);
+        assert_eq!(state.current_order.len(), 4);
+    }
 
-        // Save initial state
-        state.save_undo("Initial modification");
+    #[test]
+    fn test_graph_rows_follow_commits() {
+        let mut state = create_test_state();
+        // Newest first: the first commit's parent is the second
+        state.commits[0].parent_ids = vec![state.commits[1].id];
+        state.commits_changed();
+        assert_eq!(state.graph_rows(), ["*", "*", "*"]);
 
-        // Make a modification
-        let mods = state.get_or_create_modifications(commit_id);
-        mods.author_name = Some("New Author".to_string());
+        // The child now sits below the second commit, so its lane never ends
+        state.move_commit_down();
+        assert_eq!(state.graph_rows(), ["*", "*", "| *"]);
 
-        // Undo should restore
-        let undone = state.undo();
-        assert!(undone);
-        assert!(!state.is_modified(commit_id));
+        state.extend_commits(vec![create_test_commit(
+            "4444444444444444444444444444444444444444",
+            "Fourth commit",
+        )]);
+        assert_eq!(state.graph_rows().len(), 4);
+    }
 
-        // Redo should restore the modification
-        let redone = state.redo();
-        assert!(redone);
-        assert!(state.is_modified(commit_id));
+    #[test]
+    fn test_extend_commits_after_reorder() {
+        let mut state = create_test_state();
+        state.move_commit_down();
+        let reordered = state.current_order.clone();
+        let fourth =
+            create_test_commit("4444444444444444444444444444444444444444", "Fourth commit");
+        let fourth_id = fourth.id;
+
+        // The first commit again (an overlapping page) is left out
+        state.extend_commits(vec![state.commits[0].clone(), fourth]);
+        assert_eq!(state.current_order[..3], reordered[..]);
+        assert_eq!(state.current_order[3], fourth_id);
+        assert_eq!(state.original_order.len(), 4);
+        assert_eq!(
+            state.commits.iter().map(|c| c.id).collect::<Vec<_>>(),
+            state.current_order
+        );
     }
 
     #[test]
-    fn test_undo_redo_empty() {
+    fn test_truncate_commits() {
         let mut state = create_test_state();
+        state.cursor_bottom();
 
-        // Undo with empty stack
-        let undone = state.undo();
-        assert!(!undone);
+        assert_eq!(state.truncate_commits(2).unwrap(), 1);
+        assert_eq!(state.commits.len(), 2);
+        assert_eq!(state.original_order.len(), 2);
+        assert_eq!(state.cursor, 1);
+        assert_eq!(state.truncate_commits(5).unwrap(), 0);
+    }
 
-        // Redo with empty stack
-        let redone = state.redo();
-        assert!(!redone);
+    #[test]
+    fn test_truncate_commits_refuses_pending_changes() {
+        let mut state = create_test_state();
+        let last = state.commits[2].id;
+        state.mark_deleted(last);
+        assert!(state.truncate_commits(2).is_err());
+        assert_eq!(state.commits.len(), 3);
+
+        // A moved commit can't be dropped either
+        let mut state = create_test_state();
+        state.cursor_bottom();
+        state.move_commit_up();
+        assert!(state.truncate_commits(2).is_err());
     }
 
     #[test]
-    fn test_search_filter() {
+    fn test_head_offset_label() {
         let mut state = create_test_state();
+        assert_eq!(state.head_offset_label(0).as_deref(), Some("HEAD"));
+        assert_eq!(state.head_offset_label(2).as_deref(), Some("HEAD~2"));
+        assert_eq!(state.head_offset_label(3), None);
 
-        // Apply filter
-        state.search_query = "Second".to_string();
+        // Filtered rows keep their position in the full list
+        state.search_query = "third".to_string();
         state.apply_filter();
+        assert_eq!(state.head_offset_label(0).as_deref(), Some("HEAD~2"));
 
-        // Should only show one commit
-        let visible = state.visible_commits();
-        assert_eq!(visible.len(), 1);
-        assert_eq!(visible[0].summary, "Second commit");
-
-        // Clear filter
-        state.clear_filter();
-        let visible = state.visible_commits();
-        assert_eq!(visible.len(), 3);
+        // Commits above a --from start point count too
+        state.head_offset_base = 5;
+        assert_eq!(state.head_offset_label(0).as_deref(), Some("HEAD~7"));
     }
 
     #[test]
-    fn test_search_filter_case_insensitive() {
+    fn test_line_number_label() {
         let mut state = create_test_state();
+        assert_eq!(state.line_number_label(0), None);
 
-        state.search_query = "SECOND".to_string();
-        state.apply_filter();
+        state.show_number = true;
+        assert_eq!(state.line_number_label(2).as_deref(), Some("3"));
 
-        let visible = state.visible_commits();
-        assert_eq!(visible.len(), 1);
+        state.cursor_down();
+        state.show_number = false;
+        state.show_relative_number = true;
+        assert_eq!(state.line_number_label(0).as_deref(), Some("1"));
+        assert_eq!(state.line_number_label(1).as_deref(), Some("0"));
+        assert_eq!(state.line_number_label(2).as_deref(), Some("1"));
+
+        // Hybrid: the cursor row shows its absolute number
+        state.show_number = true;
+        assert_eq!(state.line_number_label(1).as_deref(), Some("2"));
+        assert_eq!(state.line_number_label(2).as_deref(), Some("1"));
     }
 
     #[test]
-    fn test_search_filter_empty_result() {
+    fn test_display_date() {
         let mut state = create_test_state();
+        let date = DateTime::parse_from_rfc3339("2024-01-15T14:30:00+09:00").unwrap();
+        assert_eq!(state.display_date(date).offset(), date.offset());
+
+        state.set_display_option(DisplayOption::LocalTime, Some(true));
+        let local = state.display_date(date);
+        assert_eq!(local, date);
+        assert_eq!(
+            *local.offset(),
+            date.with_timezone(&Local).fixed_offset().timezone()
+        );
+    }
 
-        state.search_query = "nonexistent".to_string();
-        state.apply_filter();
+    #[test]
+    fn test_set_display_option() {
+        let mut state = create_test_state();
+        assert!(state.set_display_option(DisplayOption::HeadOffset, None));
+        assert!(state.show_head_offset);
+        assert!(state.set_display_option(DisplayOption::HeadOffset, Some(true)));
+        assert!(!state.set_display_option(DisplayOption::HeadOffset, None));
+    }
 
-        assert!(state.filtered_indices.is_none());
+    #[test]
+    fn test_time_gap() {
+        let mut state = create_test_state();
+        let (first, second) = (state.commits[0].id, state.commits[1].id);
+        assert_eq!(state.time_gap(0), Some(TimeDelta::zero()));
+        assert_eq!(state.time_gap(2), None);
+
+        // Pending date edits count, and negative gaps are kept
+        let earlier = state.commits[0].author_date - TimeDelta::hours(3);
+        state.get_or_create_modifications(first).author_date = Some(earlier);
+        assert_eq!(state.time_gap(0), Some(TimeDelta::hours(-3)));
+
+        // Deleted commits are skipped
+        state.mark_deleted(second);
+        assert_eq!(state.time_gap(1), None);
+        assert_eq!(state.time_gap(0), Some(TimeDelta::hours(-3)));
+    }
+
+    #[test]
+    fn test_snap_mode() {
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let date = tz.with_ymd_and_hms(2024, 1, 15, 14, 31, 10).unwrap();
+        let at = |h, m| tz.with_ymd_and_hms(2024, 1, 15, h, m, 0).unwrap();
+
+        assert_eq!(SnapMode::Floor.snap(date, 3600), at(14, 0));
+        assert_eq!(SnapMode::Ceil.snap(date, 3600), at(15, 0));
+        assert_eq!(SnapMode::Nearest.snap(date, 3600), at(15, 0));
+        assert_eq!(SnapMode::Nearest.snap(date, 15 * 60), at(14, 30));
+        // Days snap to local midnight, keeping the offset
+        assert_eq!(SnapMode::Floor.snap(date, 86_400), at(0, 0));
+        assert_eq!(SnapMode::Ceil.snap(at(15, 0), 3600), at(15, 0));
+
+        // A step of millennia can't overflow, before or after 1970
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let epoch = tz.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap();
+        for date in [
+            tz.with_ymd_and_hms(2024, 1, 15, 14, 31, 10).unwrap(),
+            tz.with_ymd_and_hms(1960, 6, 1, 12, 0, 0).unwrap(),
+        ] {
+            for step in [106_751_991_167_300 * 86_400, i64::MAX] {
+                for mode in [SnapMode::Floor, SnapMode::Ceil, SnapMode::Nearest] {
+                    let snapped = mode.snap(date, step);
+                    assert!(snapped == date || snapped == epoch);
+                }
+            }
+        }
+        assert_eq!(
+            SnapMode::Ceil.snap(epoch + TimeDelta::days(1), i64::MAX),
+            epoch + TimeDelta::days(1)
+        );
     }
 
     #[test]
-    fn test_visual_mode() {
+    fn test_age_lock() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        // Test commits are from 2024
+        assert!(!state.is_locked(ids[0]));
 
-        // Enter visual mode
-        state.enter_visual_mode(VisualType::Line);
-        assert!(matches!(state.mode, AppMode::Visual { .. }));
-
-        // Check visual range
-        let range = state.visual_range();
-        assert!(range.is_some());
+        state.lock_age = Some(TimeDelta::days(90));
+        assert_eq!(state.locked_count(&ids), 3);
+        state.commits[0].committer_date = Local::now().fixed_offset();
+        assert!(!state.is_locked(ids[0]));
 
-        // Exit visual mode
-        state.exit_visual_mode();
-        assert_eq!(state.mode, AppMode::Normal);
+        state.unlocked.insert(ids[1]);
+        assert_eq!(state.locked_count(&ids), 1);
     }
 
     #[test]
-    fn test_visual_selection_line() {
+    fn test_set_squash() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
 
-        state.enter_visual_mode(VisualType::Line);
-        state.cursor_down();
-        state.cursor_down();
-
-        // Should select rows 0, 1, 2
-        assert_eq!(state.visual_selection_count(), 3);
-        assert!(state.is_row_in_visual_selection(0));
-        assert!(state.is_row_in_visual_selection(1));
-        assert!(state.is_row_in_visual_selection(2));
+        state.set_squash(&ids[..1], Some(SquashKind::Fixup));
+        assert_eq!(state.squashes.get(&ids[0]), Some(&SquashKind::Fixup));
+        assert!(state.is_dirty());
+        state.set_squash(&ids[..1], None);
+        assert!(state.squashes.is_empty());
+        assert!(state.undo());
+        assert_eq!(state.squashes.get(&ids[0]), Some(&SquashKind::Fixup));
+
+        // The oldest commit has nothing below it, unless the one below is deleted
+        assert!(!state.cannot_fold(&ids[..2]));
+        assert!(state.cannot_fold(&ids[2..]));
+        state.mark_deleted(ids[2]);
+        assert!(state.cannot_fold(&ids[1..2]));
     }
 
     #[test]
-    fn test_visual_selection_block() {
+    fn test_set_split() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let split = CommitSplit {
+            paths: vec!["a.txt".to_string()],
+            message: "Second half".to_string(),
+        };
 
-        state.enter_visual_mode(VisualType::Block);
-        state.cursor_down();
-        state.column_right();
-
-        // Check that specific cells are selected
-        assert!(state.is_in_visual_selection(0, 0));
-        assert!(state.is_in_visual_selection(0, 1));
-        assert!(state.is_in_visual_selection(1, 0));
-        assert!(state.is_in_visual_selection(1, 1));
-        assert!(!state.is_in_visual_selection(2, 0));
+        state.set_split(ids[1], Some(split.clone())).unwrap();
+        assert!(state.is_dirty());
+        assert_eq!(state.pending_commits(), vec![ids[1]]);
+        assert!(state.undo());
+        assert!(!state.is_dirty());
+        assert!(state.redo());
+        assert_eq!(state.splits.get(&ids[1]), Some(&split));
+
+        // A commit staged to be folded can't also be split
+        state.set_squash(&ids[..1], Some(SquashKind::Fixup));
+        assert!(state.set_split(ids[0], Some(split)).is_err());
+        state.set_split(ids[1], None).unwrap();
+        assert!(state.splits.is_empty());
     }
 
     #[test]
-    fn test_apply_visual_selection() {
+    fn test_clear_fields() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let date = state.commits[0].author_date - TimeDelta::hours(1);
+        state.modifications.insert(
+            ids[0],
+            CommitModifications {
+                author_name: Some("Kept".to_string()),
+                author_date: Some(date),
+                committer_date: Some(date),
+                ..Default::default()
+            },
+        );
+        state.modifications.insert(
+            ids[1],
+            CommitModifications {
+                author_date: Some(date),
+                ..Default::default()
+            },
+        );
+
+        // Synced by default: the committer date goes with the author date
+        assert_eq!(state.clear_fields(&ids, &[EditableField::AuthorDate]), 2);
+        let kept = &state.modifications[&ids[0]];
+        assert_eq!(kept.author_name.as_deref(), Some("Kept"));
+        assert_eq!(kept.author_date, None);
+        assert_eq!(kept.committer_date, None);
+        // Commits left without edits are no longer modified
+        assert!(!state.is_modified(ids[1]));
+        assert_eq!(state.undo_stack.len(), 1);
+
+        // Nothing to clear: no undo entry
+        assert_eq!(state.clear_fields(&ids, &[EditableField::Message]), 0);
+        assert_eq!(state.undo_stack.len(), 1);
+    }
 
-        state.enter_visual_mode(VisualType::Line);
-        state.cursor_down();
-
-        state.apply_visual_selection();
+    #[test]
+    fn test_snap_dates() {
+        let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+
+        // Test commits are at 14:30:00
+        assert_eq!(state.snap_dates(&ids, 60, SnapMode::Nearest), 0);
+        assert_eq!(state.undo_stack, Vec::new());
+
+        assert_eq!(state.snap_dates(&ids[..2], 3600, SnapMode::Floor), 2);
+        let mods = &state.modifications[&ids[0]];
+        let expected = state.commits[0].author_date - TimeDelta::minutes(30);
+        assert_eq!(mods.author_date, Some(expected));
+        assert_eq!(mods.committer_date, Some(expected));
+        assert!(!state.is_modified(ids[2]));
+        assert_eq!(state.undo_stack.len(), 1);
+    }
 
-        // Should have selected 2 commits
-        assert_eq!(state.selected.len(), 2);
-        assert_eq!(state.mode, AppMode::Normal);
+    #[test]
+    fn test_shift_dates() {
+        let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let author = state.commits[1].author_date;
+        let committer = state.commits[1].committer_date;
+
+        assert_eq!(state.shift_dates(&ids[1..2], -5400), 1);
+        let mods = &state.modifications[&ids[1]];
+        assert_eq!(mods.author_date, Some(author - TimeDelta::minutes(90)));
+        assert_eq!(
+            mods.committer_date,
+            Some(committer - TimeDelta::minutes(90))
+        );
+        assert_eq!(
+            mods.author_date.unwrap().offset(),
+            author.offset(),
+            "timezone kept"
+        );
+
+        // Shifts add up, from the pending dates
+        state.shift_dates(&ids[1..2], 5400);
+        assert_eq!(state.modifications[&ids[1]].author_date, Some(author));
+        assert!(state.undo());
+        assert_eq!(
+            state.modifications[&ids[1]].author_date,
+            Some(author - TimeDelta::minutes(90))
+        );
     }
 
     #[test]
-    fn test_capture_visual_edit_targets() {
+    fn test_spread_dates() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let start = utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap();
+
+        // The oldest (last row) gets the start, the newest the end
+        assert_eq!(state.spread_dates(&ids, start, end, 0), 3);
+        let date = |state: &AppState, id| state.modifications[&id].author_date.unwrap();
+        assert_eq!(date(&state, ids[2]), start);
+        assert_eq!(date(&state, ids[1]), start + TimeDelta::days(1));
+        assert_eq!(date(&state, ids[0]), end);
+        assert_eq!(state.modifications[&ids[0]].committer_date, Some(end));
+
+        // Jitter wobbles the dates, but they keep rising
+        state.spread_dates(&ids, start, end, 86_400 * 5);
+        let dates: Vec<_> = ids.iter().rev().map(|id| date(&state, *id)).collect();
+        assert!(dates.windows(2).all(|pair| pair[0] < pair[1]), "{dates:?}");
+        assert!(dates.iter().all(|d| *d >= start && *d <= end));
+    }
 
-        state.enter_visual_mode(VisualType::Line);
-        state.cursor_down();
+    #[test]
+    fn test_fix_date_order() {
+        let mut state = create_test_state();
+        // Newest first, one day apart
+        for (days, commit) in (0..).zip(&mut state.commits) {
+            commit.author_date -= TimeDelta::days(days);
+            commit.committer_date = commit.author_date;
+        }
+        let dates: Vec<_> = state.commits.iter().map(|c| c.author_date).collect();
+        // Move the oldest to the top
+        state.cursor = 2;
+        state.move_commit_up();
+        state.move_commit_up();
+        assert_eq!(state.dates_out_of_order(), 1);
 
-        let count = state.capture_visual_edit_targets();
-        assert_eq!(count, 2);
-        assert!(state.visual_edit_targets.is_some());
-        assert_eq!(state.mode, AppMode::Normal);
+        assert_eq!(state.fix_date_order(DateFix::Swap), 3);
+        assert_eq!(state.dates_out_of_order(), 0);
+        // The dates stay with the positions
+        let swapped: Vec<_> = state
+            .commits
+            .iter()
+            .map(|c| state.modifications[&c.id].author_date.unwrap())
+            .collect();
+        assert_eq!(swapped, dates);
+        assert!(state.undo());
+
+        // Only the moved commit is nudged, to a minute after the one below
+        assert_eq!(state.fix_date_order(DateFix::Nudge(60)), 1);
+        let moved = &state.commits[0];
+        assert_eq!(
+            state.modifications[&moved.id].author_date,
+            Some(dates[0] + TimeDelta::minutes(1))
+        );
+        assert_eq!(state.dates_out_of_order(), 0);
+        assert_eq!(state.fix_date_order(DateFix::Nudge(60)), 0);
     }
 
     #[test]
-    fn test_commits_to_edit_priority() {
+    fn test_convert_timezone() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let date = state.commits[0].author_date;
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_ne!(*date.offset(), offset);
+
+        assert_eq!(state.convert_timezone(&ids[..1], offset), 1);
+        let mods = &state.modifications[&ids[0]];
+        let converted = mods.author_date.unwrap();
+        assert_eq!(*converted.offset(), offset);
+        assert_eq!(converted, date, "same instant");
+        assert_eq!(*mods.committer_date.unwrap().offset(), offset);
+
+        // Already there
+        assert_eq!(state.convert_timezone(&ids[..1], offset), 0);
+    }
 
-        // Test 1: Just cursor (no selection, no visual targets)
-        let to_edit = state.commits_to_edit();
-        assert_eq!(to_edit.len(), 1);
-        assert_eq!(to_edit[0], state.commits[0].id);
+    #[test]
+    fn test_apply_plan() {
+        let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let mut plan = EditPlan {
+            order: vec![ids[1], ids[0], ids[2]],
+            deleted: HashSet::from([ids[2]]),
+            ..Default::default()
+        };
+        plan.modifications.insert(
+            ids[0],
+            CommitModifications {
+                author_name: Some("Planned".to_string()),
+                ..Default::default()
+            },
+        );
+
+        state.apply_plan(&plan).unwrap();
+        assert_eq!(state.commits[0].id, ids[1]);
+        assert!(state.is_deleted(ids[2]));
+        assert_eq!(
+            state.modifications[&ids[0]].author_name.as_deref(),
+            Some("Planned")
+        );
+        assert!(state.undo());
+        assert!(!state.is_dirty());
 
-        // Test 2: Checkbox selection takes priority over cursor
-        state.toggle_selection();
-        let to_edit = state.commits_to_edit();
-        assert_eq!(to_edit.len(), 1);
+        // The order must cover exactly the loaded commits
+        plan.order.pop();
+        assert!(state.apply_plan(&plan).is_err());
+        plan.order.clear();
+        plan.deleted.insert(CommitId(git2::Oid::zero()));
+        assert!(state.apply_plan(&plan).is_err());
+    }
 
-        // Test 3: Visual targets take priority over checkbox
-        state.visual_edit_targets = Some(vec![state.commits[1].id, state.commits[2].id]);
-        let to_edit = state.commits_to_edit();
-        assert_eq!(to_edit.len(), 2);
-        assert_eq!(to_edit[0], state.commits[1].id);
+    #[test]
+    fn test_resume_session() {
+        let mut state = create_test_state();
+        let id = state.commits[1].id;
+        state.save_undo("Edit author name");
+        state.get_or_create_modifications(id).author_name = Some("Saved".to_string());
+        state.save_undo("Delete commit");
+        state.mark_deleted(id);
+        assert!(state.undo());
+        let saved_at = DateTime::parse_from_rfc3339("2024-01-15T14:30:00Z").unwrap();
+        let session = state.session(saved_at);
+
+        let mut resumed = create_test_state();
+        resumed.resume_session(session.clone()).unwrap();
+        assert_eq!(
+            resumed.modifications[&id].author_name.as_deref(),
+            Some("Saved")
+        );
+        assert_eq!(resumed.undo_history(), state.undo_history());
+        assert!(resumed.redo());
+        assert!(resumed.is_deleted(id));
+
+        // Only on the same branch and commits
+        let mut other = create_test_state();
+        other.branch_name = "feature".to_string();
+        assert!(other.resume_session(session.clone()).is_err());
+        let mut other = create_test_state();
+        other.original_order.pop();
+        assert!(other.resume_session(session).is_err());
+        assert!(other.modifications.is_empty());
     }
 
     #[test]
-    fn test_cursor_position_queries() {
+    fn test_edit_plan_round_trip() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        assert_eq!(state.edit_plan().unwrap(), EditPlan::default());
 
-        state.set_cursor_position(1, 2);
-        assert_eq!(state.cursor_row(), 1);
-        assert_eq!(state.cursor_column(), 2);
-        assert_eq!(state.cursor_position(), (1, 2));
-        assert!(state.is_cursor_on_row(1));
-        assert!(state.is_cursor_on_column(2));
-        assert!(state.is_cursor_on_cell(1, 2));
-        assert!(!state.is_cursor_on_cell(0, 2));
+        state.get_or_create_modifications(ids[1]).author_email =
+            Some("planned@example.com".to_string());
+        state.mark_deleted(ids[2]);
+        state.move_commit_down();
+        let plan = state.edit_plan().unwrap();
+        assert_eq!(plan.order, state.current_order);
+
+        let mut fresh = create_test_state();
+        fresh
+            .apply_plan(&EditPlan::parse(&plan.to_json()).unwrap())
+            .unwrap();
+        assert_eq!(fresh.current_order, state.current_order);
+        assert_eq!(fresh.modifications, state.modifications);
+        assert_eq!(fresh.deleted, state.deleted);
+
+        state.squashes.insert(ids[0], SquashKind::Fixup);
+        assert!(state.edit_plan().is_err());
     }
 
     #[test]
-    fn test_is_cursor_on_editable_column() {
+    fn test_author_groups() {
         let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        state.get_or_create_modifications(ids[1]).author_email =
+            Some("test@old.example.com".to_string());
+        state.commits[2].is_merge = true;
+
+        // Grouped by the current identity; merges can't be edited
+        let groups = state.author_groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0.email, "test@example.com");
+        assert_eq!(groups[0].1, [ids[0]]);
+        assert_eq!(groups[1].0.email, "test@old.example.com");
+
+        state.modifications.clear();
+        state.commits[2].is_merge = false;
+        let groups = state.author_groups();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].1, ids);
+    }
 
-        // Column 0 and 1 are not editable (Selection, Hash)
-        state.set_cursor_column(0);
-        assert!(!state.is_cursor_on_editable_column());
-
-        state.set_cursor_column(1);
-        assert!(!state.is_cursor_on_editable_column());
+    #[test]
+    fn test_registers() {
+        let mut state = create_test_state();
+        assert_eq!(state.register(None), None);
+
+        state.yank(None, "Jane Doe");
+        state.yank(Some('a'), "jane@example.com");
+        assert_eq!(state.register(Some('a')), Some("jane@example.com"));
+        // Every yank also lands in the unnamed register
+        assert_eq!(state.register(None), Some("jane@example.com"));
+        assert_eq!(
+            state.register(Some(UNNAMED_REGISTER)),
+            Some("jane@example.com")
+        );
+        assert_eq!(state.register(Some('b')), None);
+    }
 
-        // Column 2+ are editable
-        state.set_cursor_column(2);
-        assert!(state.is_cursor_on_editable_column());
+    #[test]
+    fn test_reflow_messages() {
+        let mut state = create_test_state();
+        state.commits[0].message =
+            "First commit\n\nA body that is long enough to need wrapping.\n".to_string();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+
+        // Subject-only messages have nothing to wrap
+        assert_eq!(state.reflow_messages(&ids, 20), 1);
+        assert_eq!(
+            state.modifications[&ids[0]].message.as_deref(),
+            Some("First commit\n\nA body that is long\nenough to need\nwrapping.\n")
+        );
+        assert_eq!(state.reflow_messages(&ids, 20), 0);
+
+        // Wider again starts from the pending message
+        assert_eq!(state.reflow_messages(&ids[..1], 72), 1);
+        assert_eq!(
+            state.modifications[&ids[0]].message.as_deref(),
+            Some(state.commits[0].message.as_str())
+        );
+        assert_eq!(state.undo_stack.len(), 2);
     }
 
     #[test]
-    fn test_page_navigation() {
+    fn test_lint_and_set_commit_type() {
         let mut state = create_test_state();
+        state.commits[0].message = "feat: first".to_string();
+        state.commits[0].summary = "feat: first".to_string();
+        state.commits[1].message = "feature(ui): second\n\nBody".to_string();
+        state.commits[1].summary = "feature(ui): second".to_string();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+
+        assert_eq!(
+            state.lint_issues(),
+            vec![
+                (ids[1], LintIssue::UnknownType("feature".to_string())),
+                (ids[2], LintIssue::MissingType)
+            ]
+        );
+
+        // Already a feat: left alone
+        assert_eq!(state.set_commit_type(&ids, "feat"), 2);
+        assert_eq!(state.undo_stack.len(), 1);
+        assert!(!state.is_modified(ids[0]));
+        assert_eq!(
+            state.modifications[&ids[1]].message.as_deref(),
+            Some("feat(ui): second\n\nBody")
+        );
+        assert_eq!(
+            state.modifications[&ids[2]].message.as_deref(),
+            Some("feat: Third commit")
+        );
+        assert_eq!(state.lint_issues(), Vec::new());
+        assert_eq!(state.set_commit_type(&ids, "feat"), 0);
+
+        // Pending messages are what's checked
+        state.get_or_create_modifications(ids[0]).message = Some("First".to_string());
+        assert_eq!(state.lint_issues(), vec![(ids[0], LintIssue::MissingType)]);
+        state.mark_deleted(ids[0]);
+        assert_eq!(state.lint_issues(), Vec::new());
+    }
 
-        state.page_down(2);
-        assert_eq!(state.cursor, 2);
+    #[test]
+    fn test_date_divergences() {
+        let mut state = create_test_state();
+        state.commits[1].committer_date += TimeDelta::days(3);
+        state.commits[2].committer_date -= TimeDelta::hours(2);
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+
+        assert_eq!(
+            state.date_divergences(TimeDelta::hours(1)),
+            vec![(ids[1], TimeDelta::days(3)), (ids[2], -TimeDelta::hours(2))]
+        );
+        assert_eq!(
+            state.date_divergences(TimeDelta::hours(24)),
+            vec![(ids[1], TimeDelta::days(3))]
+        );
+
+        assert_eq!(state.normalize_dates(&ids, DateSource::Author), 2);
+        assert_eq!(state.date_divergences(TimeDelta::zero()), Vec::new());
+        assert_eq!(
+            state.modifications[&ids[1]].committer_date,
+            Some(state.commits[1].author_date)
+        );
+        assert!(!state.is_modified(ids[0]));
+        assert_eq!(state.normalize_dates(&ids, DateSource::Author), 0);
+        assert_eq!(state.undo_stack.len(), 1);
+
+        state.undo();
+        assert_eq!(state.normalize_dates(&ids[1..2], DateSource::Committer), 1);
+        assert_eq!(
+            state.modifications[&ids[1]].author_date,
+            Some(state.commits[1].committer_date)
+        );
+    }
 
-        state.page_up(1);
-        assert_eq!(state.cursor, 1);
+    #[test]
+    fn test_set_note() {
+        let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
 
-        // Page down beyond bottom
-        state.page_down(10);
-        assert_eq!(state.cursor, 2);
+        assert_eq!(state.set_note(&ids[..2], Some("wrong laptop email")), 2);
+        assert_eq!(state.notes[&ids[0]], "wrong laptop email");
+        assert_eq!(state.set_note(&ids[..2], Some("wrong laptop email")), 0);
+        // A note alone is not a change to apply
+        assert!(!state.is_dirty());
 
-        // Page up beyond top
-        state.page_up(10);
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.set_note(&ids[1..], None), 1);
+        assert!(!state.notes.contains_key(&ids[1]));
+        assert!(state.undo());
+        assert!(state.notes.contains_key(&ids[1]));
     }
 
     #[test]
-    fn test_detail_scroll() {
+    fn test_add_trailer() {
         let mut state = create_test_state();
-        state.detail_max_scroll = 10;
-
-        state.detail_scroll_down(5);
-        assert_eq!(state.detail_scroll, 5);
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let trailer = Trailer::signed_off_by(&Person::new("Jane", "jane@example.com"));
+
+        assert_eq!(state.add_trailer(&ids[..2], &trailer), 2);
+        assert!(state.modifications[&ids[0]]
+            .message
+            .as_deref()
+            .is_some_and(|m| m.ends_with("Signed-off-by: Jane <jane@example.com>\n")));
+        assert_eq!(state.trailers_added("signed-off-by"), 2);
+        // Already signed off
+        assert_eq!(state.add_trailer(&ids, &trailer), 1);
+        assert_eq!(state.trailers_added("Signed-off-by"), 3);
+
+        state.mark_deleted(ids[0]);
+        assert_eq!(state.trailers_added("Signed-off-by"), 2);
+        state.unmark_deleted(ids[0]);
+        assert!(state.undo());
+        assert_eq!(state.trailers_added("Signed-off-by"), 2);
+    }
 
-        state.detail_scroll_up(2);
-        assert_eq!(state.detail_scroll, 3);
+    #[test]
+    fn test_change_stats() {
+        let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        assert_eq!(state.change_stats(), Vec::<String>::new());
 
-        // Test bounds
-        state.detail_scroll_down(20);
-        assert_eq!(state.detail_scroll, 10); // Clamped to max
+        state.get_or_create_modifications(ids[0]).author_name = Some("A".to_string());
+        state.get_or_create_modifications(ids[1]).author_name = Some("B".to_string());
+        state.mark_deleted(ids[2]);
+        assert_eq!(state.change_stats(), vec!["2 edits", "1 deletion"]);
 
-        state.detail_scroll_up(100);
-        assert_eq!(state.detail_scroll, 0); // Clamped to 0
+        state.cursor = 0;
+        state.move_commit_down();
+        assert_eq!(
+            state.change_stats(),
+            vec!["2 edits", "1 deletion", "reordered"]
+        );
     }
 
     #[test]
-    fn test_reset_detail_scroll() {
+    fn test_trailers() {
         let mut state = create_test_state();
-        state.detail_scroll = 5;
-
-        state.reset_detail_scroll();
-        assert_eq!(state.detail_scroll, 0);
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        let bob = Trailer::co_authored_by(&Person::new("Bob", "bob@example.com"));
+        let eve = Trailer::co_authored_by(&Person::new("Eve", "eve@example.com"));
+
+        assert_eq!(state.add_trailer(&ids, &bob), 3);
+        assert_eq!(state.add_trailer(&ids[..1], &eve), 1);
+        assert_eq!(
+            state.trailers(&ids, Some("co-authored-by")),
+            vec![(bob.clone(), 3), (eve.clone(), 1)]
+        );
+
+        assert_eq!(state.remove_trailer(&ids, &bob), 3);
+        assert_eq!(state.remove_trailer(&ids, &bob), 0);
+        assert_eq!(state.trailers(&ids, None), vec![(eve.clone(), 1)]);
+        assert!(state.undo());
+        assert_eq!(state.trailers(&ids, Some("Co-authored-by")).len(), 2);
+
+        // Changing a trailer keeps its place
+        let ticket = Trailer::new("Ticket", "ABC-1");
+        assert_eq!(state.add_trailer(&ids[..1], &ticket), 1);
+        assert_eq!(state.replace_trailer(&ids, &eve, &bob), 1);
+        assert_eq!(state.trailers(&ids[..1], None), vec![(bob, 1), (ticket, 1)]);
     }
 
     #[test]
-    fn test_cursor_commit() {
+    fn test_apply_mailmap() {
         let mut state = create_test_state();
-
-        let commit = state.cursor_commit();
-        assert!(commit.is_some());
-        assert_eq!(commit.unwrap().summary, "First commit");
-
-        state.cursor_down();
-        let commit = state.cursor_commit();
-        assert_eq!(commit.unwrap().summary, "Second commit");
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        state.commits[2].committer = Person::new("CI", "ci@example.com");
+        state.get_or_create_modifications(ids[1]).author_email = Some("old@example.com".into());
+
+        let mailmap = git2::Mailmap::from_buffer(
+            "Test Author <test@example.com> <old@example.com>\n\
+             Tess Author <tess@example.com> <test@example.com>\n",
+        )
+        .unwrap();
+        assert_eq!(state.apply_mailmap(&ids, &mailmap), 3);
+        let tess = Person::new("Tess Author", "tess@example.com");
+        assert_eq!(
+            state.effective_identity_value(ids[0], EditableField::CommitterEmail),
+            Some(tess.email.clone())
+        );
+        // Pending edits are remapped too
+        assert_eq!(
+            state.modifications[&ids[1]].author_email.as_deref(),
+            Some("test@example.com")
+        );
+        // Unmatched identities are left alone
+        assert_eq!(state.modifications[&ids[2]].committer_name, None);
+        assert_eq!(state.modifications[&ids[2]].author_name, Some(tess.name));
+        assert_eq!(state.undo_stack.len(), 1);
     }
 
     #[test]
-    fn test_cursor_commit_id() {
-        let state = create_test_state();
+    fn test_identity_mailmap() {
+        let mut state = create_test_state();
+        let ids: Vec<_> = state.commits.iter().map(|c| c.id).collect();
+        assert!(state.identity_mailmap().is_empty());
 
-        let id = state.cursor_commit_id();
-        assert!(id.is_some());
-        assert_eq!(id.unwrap(), state.commits[0].id);
+        for &id in &ids {
+            let mods = state.get_or_create_modifications(id);
+            mods.author_name = Some("Tess Author".to_string());
+            mods.committer_name = Some("Tess Author".to_string());
+        }
+        state.get_or_create_modifications(ids[2]).author_email = Some("tess@example.com".into());
+        let mailmap = state.identity_mailmap();
+        assert_eq!(
+            mailmap.to_string(),
+            "Tess Author <test@example.com> Test Author <test@example.com>\n\
+             Tess Author <tess@example.com> Test Author <test@example.com>\n"
+        );
+
+        // Deleted commits aren't kept, so their identities don't matter
+        state.mark_deleted(ids[2]);
+        assert_eq!(state.identity_mailmap().entries.len(), 1);
     }
 
     #[test]
-    fn test_messages() {
+    fn test_set_new_root() {
         let mut state = create_test_state();
+        // The test commits have no parents; the oldest one is the root
+        let root = state.root_commit().unwrap().clone();
+        assert_eq!(Some(&root.id), state.original_order.last());
+        assert!(!state.is_dirty());
 
-        state.set_error("Test error");
-        assert!(state.error_message.is_some());
-        assert!(state.success_message.is_none());
-
-        state.set_success("Test success");
-        assert!(state.success_message.is_some());
-        assert!(state.error_message.is_none());
+        let new_root = NewRoot {
+            paths: vec!["LICENSE".to_string()],
+            message: "Initial commit".to_string(),
+            author: root.author.clone(),
+            date: root.author_date,
+        };
+        assert!(state.set_new_root(Some(new_root.clone())));
+        assert!(!state.set_new_root(Some(new_root)));
+        assert!(state.is_dirty());
+        // The old root (and what's built on it) is rewritten on top of it
+        assert_eq!(state.rewritten_commits(), vec![root.id]);
 
-        state.clear_messages();
-        assert!(state.error_message.is_none());
-        assert!(state.success_message.is_none());
+        assert!(state.undo());
+        assert!(state.new_root.is_none());
+        assert!(!state.is_dirty());
     }
 
     #[test]
-    fn test_move_commit_up() {
-        let mut state = create_test_state();
-        let first_id = state.commits[0].id;
-        let second_id = state.commits[1].id;
+    fn test_fixup_target() {
+        let commits = vec![
+            create_test_commit("4444444444444444444444444444444444444444", "fixup! 2222222"),
+            create_test_commit("3333333333333333333333333333333333333333", "squash! First"),
+            create_test_commit("2222222222222222222222222222222222222222", "Second commit"),
+            create_test_commit("1111111111111111111111111111111111111111", "First commit"),
+        ];
+        let mut state = AppState::new(commits, "main".to_string(), false);
 
-        state.cursor = 1;
+        // By hash prefix, and by subject prefix
+        assert_eq!(state.fixup_target(0), Some((2, SquashKind::Fixup)));
+        assert_eq!(state.fixup_target(1), Some((3, SquashKind::Squash)));
+        assert_eq!(state.fixup_target(2), None);
+
+        let targets = state.fixup_targets();
+        assert_eq!(targets.len(), 2);
+        assert!(targets.contains(&state.commits[2].id));
+        assert!(targets.contains(&state.commits[3].id));
+
+        // Reordering moves the targets along
+        state.cursor = 2;
         state.move_commit_up();
+        assert_eq!(state.fixup_target(0), Some((1, SquashKind::Fixup)));
+        assert_eq!(state.fixup_target(2), Some((3, SquashKind::Squash)));
+    }
 
-        // Second commit should now be first
-        assert_eq!(state.commits[0].id, second_id);
-        assert_eq!(state.commits[1].id, first_id);
-        assert_eq!(state.cursor, 0);
-        assert!(state.is_dirty()); // Order changed
+    #[test]
+    fn test_referencing_commits() {
+        let commits = vec![
+            create_test_commit(
+                "4444444444444444444444444444444444444444",
+                "Revert \"Second commit\"",
+            ),
+            create_test_commit(
+                "3333333333333333333333333333333333333333",
+                "Follow up on 1111111",
+            ),
+            create_test_commit("2222222222222222222222222222222222222222", "Second commit"),
+            create_test_commit("1111111111111111111111111111111111111111", "First commit"),
+        ];
+        let mut state = AppState::new(commits, "main".to_string(), false);
+        let first = state.commits[3].id;
+        let second = state.commits[2].id;
+        let revert = state.commits[0].id;
+        let follow_up = state.commits[1].id;
+
+        assert_eq!(state.referencing_commits(&[second]), vec![(revert, second)]);
+        assert_eq!(
+            state.referencing_commits(&[first, second]),
+            vec![(revert, second), (follow_up, first)]
+        );
+        // Older commits and already deleted ones don't count
+        assert_eq!(state.referencing_commits(&[revert]), Vec::new());
+        state.mark_deleted(revert);
+        assert_eq!(state.referencing_commits(&[second]), Vec::new());
     }
 
     #[test]
-    fn test_move_commit_down() {
+    fn test_take_and_restore_changes() {
         let mut state = create_test_state();
-        let first_id = state.commits[0].id;
-        let second_id = state.commits[1].id;
-
-        state.cursor = 0;
-        state.move_commit_down();
+        let first = state.commits[0].id;
+        let second = state.commits[1].id;
+        let third = state.commits[2].id;
+        state.modifications.insert(
+            first,
+            CommitModifications {
+                author_name: Some("New".to_string()),
+                ..Default::default()
+            },
+        );
+        state.mark_deleted(third);
+        assert_eq!(state.pending_commits(), vec![third, first]);
+
+        let held = state.take_changes(&[first, third]);
+        assert!(!state.is_dirty());
 
-        // First commit should now be second
-        assert_eq!(state.commits[0].id, second_id);
-        assert_eq!(state.commits[1].id, first_id);
-        assert_eq!(state.cursor, 1);
+        // Restored on the rewritten IDs
+        let map = HashMap::from([(first, second)]);
+        state.restore_changes(held, &map);
+        assert!(state.modifications.contains_key(&second));
+        assert!(state.is_deleted(third));
     }
 
     #[test]
-    fn test_visual_type() {
+    fn test_changed_commits() {
         let mut state = create_test_state();
+        let ids: Vec<CommitId> = state.commits.iter().map(|c| c.id).collect();
+        assert_eq!(state.changed_commits(), HashSet::new());
 
-        assert!(state.visual_type().is_none());
+        state.mark_deleted(ids[0]);
+        state.current_order.swap(1, 2);
+        let expected: HashSet<CommitId> = ids.iter().copied().collect();
+        assert_eq!(state.changed_commits(), expected);
 
-        state.enter_visual_mode(VisualType::Line);
-        assert_eq!(state.visual_type(), Some(VisualType::Line));
+        // Deleted commits aren't rewritten
+        assert_eq!(state.rewritten_commits(), vec![ids[2], ids[1]]);
 
-        state.exit_visual_mode();
-        assert!(state.visual_type().is_none());
+        // Everything built on a changed commit is
+        let mut state = create_test_state();
+        state.commits[0].parent_ids = vec![ids[1]];
+        state.commits[1].parent_ids = vec![ids[2]];
+        state.get_or_create_modifications(ids[1]).author_name = Some("New".to_string());
+        assert_eq!(state.rewritten_commits(), vec![ids[1], ids[0]]);
+
+        state.record_bypass(Bypass::Locked);
+        state.record_bypass(Bypass::Locked);
+        assert_eq!(state.bypassed, vec![Bypass::Locked]);
     }
 
     #[test]
-    fn test_sync_author_to_committer_default() {
-        let state = create_test_state();
-        // Default should be true (sync enabled)
-        assert!(state.sync_author_to_committer);
+    fn test_fit_to_height() {
+        let commits = (1..=9)
+            .map(|n| create_test_commit(&n.to_string().repeat(40), &format!("Commit {n}")))
+            .collect();
+        let mut state = AppState::new(commits, "main".to_string(), false);
+        state.cursor = 8;
+        state.update_scroll_for_height(5);
+        assert_eq!(state.scroll_offset, 4);
+
+        // Shrinking keeps the cursor on screen
+        state.fit_to_height(2);
+        assert_eq!(state.scroll_offset, 7);
+
+        // Growing doesn't leave blank rows at the bottom
+        state.fit_to_height(20);
+        assert_eq!(state.scroll_offset, 0);
+        assert_eq!(state.cursor, 8);
     }
 
     #[test]
-    fn test_set_sync_author_to_committer() {
-        let mut state = create_test_state();
-
-        // Default is true
-        assert!(state.sync_author_to_committer);
+    fn test_autosquash() {
+        let commits = vec![
+            create_test_commit(
+                "5555555555555555555555555555555555555555",
+                "fixup! First commit",
+            ),
+            create_test_commit("4444444444444444444444444444444444444444", "Third commit"),
+            create_test_commit(
+                "3333333333333333333333333333333333333333",
+                "squash! First commit",
+            ),
+            create_test_commit("2222222222222222222222222222222222222222", "Second commit"),
+            create_test_commit("1111111111111111111111111111111111111111", "First commit"),
+        ];
+        let mut state = AppState::new(commits, "main".to_string(), false);
+
+        assert_eq!(state.autosquash(), 2);
+        let summaries: Vec<&str> = state.commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            [
+                "Third commit",
+                "Second commit",
+                "fixup! First commit",
+                "squash! First commit",
+                "First commit"
+            ]
+        );
+        assert_eq!(state.squashes.len(), 2);
+        assert!(state.is_dirty());
 
-        // Disable sync
-        state.set_sync_author_to_committer(false);
-        assert!(!state.sync_author_to_committer);
+        // Already staged: nothing more to do; undo restores everything
+        assert_eq!(state.autosquash(), 0);
+        assert!(state.undo());
+        assert!(state.squashes.is_empty());
+        assert_eq!(state.commits[0].summary, "fixup! First commit");
+        assert!(!state.is_dirty());
+    }
 
-        // Re-enable sync
-        state.set_sync_author_to_committer(true);
-        assert!(state.sync_author_to_committer);
+    #[test]
+    fn test_autosquash_skips_split_commits() {
+        let fixup = "3333333333333333333333333333333333333333";
+        let commits = vec![
+            create_test_commit(fixup, "fixup! First commit"),
+            create_test_commit("2222222222222222222222222222222222222222", "Second commit"),
+            create_test_commit("1111111111111111111111111111111111111111", "First commit"),
+        ];
+        let mut state = AppState::new(commits, "main".to_string(), false);
+        let fixup = CommitId(git2::Oid::from_str(fixup).unwrap());
+        state
+            .set_split(
+                fixup,
+                Some(CommitSplit {
+                    paths: vec!["a.txt".to_string()],
+                    message: "Second half".to_string(),
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(state.autosquash(), 0);
+        assert!(state.squashes.is_empty());
+        assert!(state.splits.contains_key(&fixup));
     }
 }
@@ -0,0 +1,1213 @@
+use crate::error::{HistError, Result};
+use crate::git::commit::{EditableField, Person};
+use crate::git::conventional::COMMIT_TYPES;
+use crate::git::validation::{validate_date, validate_email};
+use crate::state::app_state::{DateFix, DisplayOption, SnapMode, SortKey, SortOrder};
+use chrono::{DateTime, FixedOffset};
+use std::ops::RangeInclusive;
+
+/// Column `:reflow` wraps message bodies at, as git's own guidelines suggest
+const DEFAULT_REFLOW_WIDTH: usize = 72;
+
+/// A command entered on the `:` command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Select every commit between two hashes (inclusive, in current order)
+    SelectRange { from: String, to: String },
+    /// Move the cursor to the commit a full or abbreviated hash names
+    Goto(String),
+    /// Add a highlight group for a pattern
+    Highlight(String),
+    /// Remove one highlight group, or all of them
+    NoHighlight(Option<String>),
+    /// Change how many commits are loaded (None = all)
+    Limit(Option<usize>),
+    /// Move `fixup!`/`squash!` commits above their targets and stage the squash
+    Autosquash,
+    /// Edit the cursor column on every visible commit
+    EditAll,
+    /// Set the author of the selected commits to the current git identity
+    Me,
+    /// Set the author of the selected commits
+    Author(Person),
+    /// Set the author date of the selected commits
+    Date(DateTime<FixedOffset>),
+    /// Mark the selected commits for deletion
+    Delete,
+    /// Apply the pending changes (after the usual confirmation), then quit
+    /// if `quit`
+    Write { quit: bool },
+    /// Quit, asking first about unapplied changes unless `force`
+    Quit { force: bool },
+    /// Add a commit beneath the root commit holding these paths from it
+    NewRoot(Vec<String>),
+    /// Drop the new root commit
+    NoNewRoot,
+    /// Make locked commits editable: the selected ones, or all (`all`)
+    Unlock { all: bool },
+    /// Add a `Signed-off-by` trailer for the current git identity to the
+    /// selected commits, or all of them (`all`)
+    SignOff { all: bool },
+    /// Rewrap the selected commits' message bodies to this many columns
+    Reflow(usize),
+    /// Remap authors and committers through a mailmap file (None = the
+    /// repo's `.mailmap`)
+    Mailmap(Option<String>),
+    /// Write the pending edits to a file, in the format its name calls for
+    Export(String),
+    /// Write the loaded commits' metadata, with pending values, to a CSV or
+    /// JSON file
+    Dump(String),
+    /// Edit another branch's history (None = pick one from a list)
+    Branch(Option<String>),
+    /// Show the commits grouped under their authors
+    Authors,
+    /// Show the new hashes applying would give the commits
+    Preview,
+    /// List backup refs to restore or delete
+    Backups,
+    /// List commits whose author and committer dates differ by more than a
+    /// threshold in seconds (None = the configured one)
+    Divergence(Option<i64>),
+    /// List commit messages that aren't Conventional Commits, or give the
+    /// selected commits a type (index in `COMMIT_TYPES`)
+    Lint(Option<usize>),
+    /// Note why the selected commits are changed (None = remove their notes)
+    Note(Option<String>),
+    /// Drop pending edits of these fields from the selected commits
+    Clear(Vec<EditableField>),
+    /// Snap dates to a multiple of `step` seconds
+    Snap { step: i64, mode: SnapMode },
+    /// Move author and committer dates by this many seconds
+    Shift(i64),
+    /// Space author and committer dates evenly from `start` to `end`, each
+    /// moved by up to `jitter` seconds
+    Spread {
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        jitter: i64,
+    },
+    /// Put dates back in order after reordering
+    FixDates(DateFix),
+    /// Show author and committer dates at this UTC offset (seconds east),
+    /// keeping the instant
+    Timezone(i32),
+    /// Sort the table rows for viewing (None = back to the commit order)
+    Sort(Option<SortOrder>),
+    /// Turn a display option on or off (None = toggle)
+    Set {
+        option: DisplayOption,
+        value: Option<bool>,
+    },
+}
+
+impl Command {
+    /// Parse a command line (without the leading `:` or a range)
+    ///
+    /// Arguments are separated by whitespace; double quotes keep one
+    /// together, as in `author "Jane Doe" jane@example.com`.
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::from_words(&split_words(input)?)
+    }
+
+    /// Parse a command line split into words
+    fn from_words(words: &[String]) -> Result<Self> {
+        let Some((name, args)) = words.split_first() else {
+            return Err(HistError::InvalidCommand("empty command".to_string()));
+        };
+        let name = name.as_str();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        match name {
+            "range" | "sel" => match args.as_slice() {
+                [from, to] => Ok(Command::SelectRange {
+                    from: (*from).to_string(),
+                    to: (*to).to_string(),
+                }),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: range <from-hash> <to-hash>".to_string(),
+                )),
+            },
+            "goto" | "g" => match args.as_slice() {
+                [hash] => Ok(Command::Goto((*hash).to_string())),
+                _ => Err(HistError::InvalidCommand("usage: goto <hash>".to_string())),
+            },
+            // A bare hash jumps to it, like a line number in vim
+            hash if args.is_empty() && is_hash(hash) => Ok(Command::Goto(hash.to_string())),
+            "highlight" | "hl" => {
+                if args.is_empty() {
+                    Err(HistError::InvalidCommand(
+                        "usage: highlight <pattern>".to_string(),
+                    ))
+                } else {
+                    Ok(Command::Highlight(args.join(" ")))
+                }
+            }
+            "nohighlight" | "nohl" => Ok(Command::NoHighlight(
+                (!args.is_empty()).then(|| args.join(" ")),
+            )),
+            "limit" => match args.as_slice() {
+                ["all" | "0"] => Ok(Command::Limit(None)),
+                [n] => n
+                    .parse()
+                    .map(|n| Command::Limit(Some(n)))
+                    .map_err(|_| HistError::InvalidCommand(format!("invalid limit '{n}'"))),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: limit <count|all>".to_string(),
+                )),
+            },
+            "autosquash" => Ok(Command::Autosquash),
+            "editall" => Ok(Command::EditAll),
+            "me" => Ok(Command::Me),
+            "author" => parse_author(&args),
+            "date" => {
+                let date = args.join(" ");
+                if date.is_empty() {
+                    return Err(HistError::InvalidCommand(
+                        "usage: date <date|[+|-]offset> (e.g. date 2024-01-15 14:30, date +2h)"
+                            .to_string(),
+                    ));
+                }
+                // A signed offset moves the dates, like :shift
+                if date.starts_with(['+', '-']) {
+                    return parse_offset(&date).map(Command::Shift).ok_or_else(|| {
+                        HistError::InvalidCommand(format!(
+                            "invalid offset '{date}' (e.g. +3d4h, -90m)"
+                        ))
+                    });
+                }
+                validate_date(&date).map(Command::Date)
+            }
+            "delete" | "d" => match args.as_slice() {
+                [] => Ok(Command::Delete),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: delete [rows] (e.g. delete 3,7)".to_string(),
+                )),
+            },
+            "w" | "write" => Ok(Command::Write { quit: false }),
+            "wq" | "x" => Ok(Command::Write { quit: true }),
+            "q" | "quit" => Ok(Command::Quit { force: false }),
+            "q!" | "quit!" => Ok(Command::Quit { force: true }),
+            "authors" | "who" => Ok(Command::Authors),
+            "preview" => Ok(Command::Preview),
+            "backups" => Ok(Command::Backups),
+            "unlock" => match args.as_slice() {
+                [] => Ok(Command::Unlock { all: false }),
+                ["all"] => Ok(Command::Unlock { all: true }),
+                _ => Err(HistError::InvalidCommand("usage: unlock [all]".to_string())),
+            },
+            "newroot" => Ok(Command::NewRoot(
+                args.iter().map(|path| (*path).to_string()).collect(),
+            )),
+            "nonewroot" => Ok(Command::NoNewRoot),
+            "signoff" | "so" => match args.as_slice() {
+                [] => Ok(Command::SignOff { all: false }),
+                ["all"] => Ok(Command::SignOff { all: true }),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: signoff [all]".to_string(),
+                )),
+            },
+            "reflow" => match args.as_slice() {
+                [] => Ok(Command::Reflow(DEFAULT_REFLOW_WIDTH)),
+                [width] => width
+                    .parse()
+                    .ok()
+                    .filter(|&width| width > 0)
+                    .map(Command::Reflow)
+                    .ok_or_else(|| HistError::InvalidCommand(format!("invalid width '{width}'"))),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: reflow [width]".to_string(),
+                )),
+            },
+            "mailmap" => match args.as_slice() {
+                [] => Ok(Command::Mailmap(None)),
+                [path] => Ok(Command::Mailmap(Some((*path).to_string()))),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: mailmap [file]".to_string(),
+                )),
+            },
+            "export" => match args.as_slice() {
+                [path] => Ok(Command::Export((*path).to_string())),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: export <file>".to_string(),
+                )),
+            },
+            "dump" => match args.as_slice() {
+                [path] => Ok(Command::Dump((*path).to_string())),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: dump <file.csv|file.json>".to_string(),
+                )),
+            },
+            "branch" | "br" => match args.as_slice() {
+                [] => Ok(Command::Branch(None)),
+                [name] => Ok(Command::Branch(Some((*name).to_string()))),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: branch [name]".to_string(),
+                )),
+            },
+            "divergence" | "div" => match args.as_slice() {
+                [] => Ok(Command::Divergence(None)),
+                [threshold] => parse_granularity(threshold)
+                    .map(|secs| Command::Divergence(Some(secs)))
+                    .ok_or_else(|| {
+                        HistError::InvalidCommand(format!("invalid threshold '{threshold}'"))
+                    }),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: divergence [threshold]".to_string(),
+                )),
+            },
+            "lint" => match args.as_slice() {
+                [] => Ok(Command::Lint(None)),
+                [kind] => COMMIT_TYPES
+                    .iter()
+                    .position(|t| t == kind)
+                    .map(|idx| Command::Lint(Some(idx)))
+                    .ok_or_else(|| {
+                        HistError::InvalidCommand(format!(
+                            "unknown type '{kind}' (use {})",
+                            COMMIT_TYPES.join(", ")
+                        ))
+                    }),
+                _ => Err(HistError::InvalidCommand("usage: lint [type]".to_string())),
+            },
+            "note" => Ok(Command::Note((!args.is_empty()).then(|| args.join(" ")))),
+            "clear" => {
+                if args.is_empty() {
+                    return Err(HistError::InvalidCommand(
+                        "usage: clear <name|email|date|message|cname|cemail|cdate|committer|all>..."
+                            .to_string(),
+                    ));
+                }
+                let mut fields = Vec::new();
+                for arg in &args {
+                    let named = clear_fields(arg).ok_or_else(|| {
+                        HistError::InvalidCommand(format!("unknown field '{arg}'"))
+                    })?;
+                    for field in named {
+                        if !fields.contains(field) {
+                            fields.push(*field);
+                        }
+                    }
+                }
+                Ok(Command::Clear(fields))
+            }
+            "sort" => {
+                let usage = || {
+                    HistError::InvalidCommand(
+                        "usage: sort <author|email|date|message> [asc|desc]".to_string(),
+                    )
+                };
+                let (key, descending) = match args.as_slice() {
+                    [key] => (key, false),
+                    [key, "asc"] => (key, false),
+                    [key, "desc"] => (key, true),
+                    _ => return Err(usage()),
+                };
+                let key = SortKey::from_name(key).ok_or_else(usage)?;
+                Ok(Command::Sort(Some(SortOrder { key, descending })))
+            }
+            "nosort" => Ok(Command::Sort(None)),
+            "snap" => {
+                let usage = || {
+                    HistError::InvalidCommand(
+                        "usage: snap <granularity> [floor|ceil|nearest]".to_string(),
+                    )
+                };
+                let (granularity, mode) = match args.as_slice() {
+                    [granularity] => (*granularity, SnapMode::Nearest),
+                    [granularity, mode] => (
+                        *granularity,
+                        SnapMode::from_name(mode).ok_or_else(|| {
+                            HistError::InvalidCommand(format!("unknown snap mode '{mode}'"))
+                        })?,
+                    ),
+                    _ => return Err(usage()),
+                };
+                let step = parse_granularity(granularity).ok_or_else(|| {
+                    HistError::InvalidCommand(format!("invalid granularity '{granularity}'"))
+                })?;
+                Ok(Command::Snap { step, mode })
+            }
+            "shift" => match args.as_slice() {
+                [offset] => parse_offset(offset).map(Command::Shift).ok_or_else(|| {
+                    HistError::InvalidCommand(format!(
+                        "invalid offset '{offset}' (e.g. +3d4h, -90m)"
+                    ))
+                }),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: shift <[+|-]offset> (e.g. +3d4h, -90m)".to_string(),
+                )),
+            },
+            "spread" => parse_spread(&args),
+            "fixdates" => match args.as_slice() {
+                [] | ["swap"] => Ok(Command::FixDates(DateFix::Swap)),
+                ["nudge"] => Ok(Command::FixDates(DateFix::Nudge(60))),
+                ["nudge", step] => parse_offset(step)
+                    .filter(|step| *step > 0)
+                    .map(|step| Command::FixDates(DateFix::Nudge(step)))
+                    .ok_or_else(|| HistError::InvalidCommand(format!("invalid step '{step}'"))),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: fixdates [swap|nudge [step]]".to_string(),
+                )),
+            },
+            "tz" | "timezone" => match args.as_slice() {
+                [offset] => parse_utc_offset(offset)
+                    .map(Command::Timezone)
+                    .ok_or_else(|| {
+                        HistError::InvalidCommand(format!(
+                            "invalid UTC offset '{offset}' (e.g. +0200, -05:30, UTC)"
+                        ))
+                    }),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: tz <+HHMM|UTC>".to_string(),
+                )),
+            },
+            "set" => match args.as_slice() {
+                [option] => parse_set(option),
+                _ => Err(HistError::InvalidCommand(
+                    "usage: set [no]<option>[!]".to_string(),
+                )),
+            },
+            _ => Err(HistError::InvalidCommand(format!(
+                "unknown command '{name}'"
+            ))),
+        }
+    }
+
+    /// Whether the command acts on the commits to edit, so a range can pick
+    /// them instead
+    #[must_use]
+    pub fn takes_range(&self) -> bool {
+        matches!(
+            self,
+            Command::Me
+                | Command::Author(_)
+                | Command::Date(_)
+                | Command::Delete
+                | Command::Unlock { all: false }
+                | Command::SignOff { all: false }
+                | Command::Reflow(_)
+                | Command::Lint(Some(_))
+                | Command::Note(_)
+                | Command::Clear(_)
+                | Command::Snap { .. }
+                | Command::Shift(_)
+                | Command::Spread { .. }
+                | Command::Timezone(_)
+        )
+    }
+}
+
+/// A row named on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowRef {
+    /// A 1-based line number, as `:set nu` shows
+    Line(usize),
+    /// The cursor's row (`.`)
+    Cursor,
+    /// The last row (`$`)
+    Last,
+}
+
+/// The rows a command line applies to, like vim's `:3,7` or `:%`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRange {
+    pub start: RowRef,
+    pub end: RowRef,
+}
+
+impl RowRange {
+    /// Every row (`%`)
+    pub const ALL: Self = Self {
+        start: RowRef::Line(1),
+        end: RowRef::Last,
+    };
+
+    /// Parse a whole range: `%`, `N` or `N,M`, with `.` and `$` for rows
+    pub fn parse(input: &str) -> Result<Self> {
+        match split_range(input) {
+            (Some(range), "") => Ok(range),
+            _ => Err(HistError::InvalidCommand(format!(
+                "invalid range '{input}' (e.g. 3,7 or %)"
+            ))),
+        }
+    }
+
+    /// The visible rows (0-based) it covers with the cursor on row `cursor`
+    /// of `len`, in order
+    pub fn rows(&self, cursor: usize, len: usize) -> Result<RangeInclusive<usize>> {
+        let row = |row: RowRef| match row {
+            RowRef::Line(line) if (1..=len).contains(&line) => Ok(line - 1),
+            RowRef::Line(line) => Err(HistError::InvalidCommand(format!(
+                "no row {line} (there are {len})"
+            ))),
+            RowRef::Cursor if len > 0 => Ok(cursor.min(len - 1)),
+            RowRef::Last if len > 0 => Ok(len - 1),
+            RowRef::Cursor | RowRef::Last => Err(HistError::InvalidCommand("no rows".to_string())),
+        };
+        let (start, end) = (row(self.start)?, row(self.end)?);
+        // A backwards range is taken the right way round
+        Ok(start.min(end)..=start.max(end))
+    }
+}
+
+/// A whole `:` command line: an optional range, then a command
+///
+/// A range alone moves the cursor to its last row (`:42`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandLine {
+    pub range: Option<RowRange>,
+    pub command: Option<Command>,
+}
+
+impl CommandLine {
+    /// Parse a command line (without the leading `:`)
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        // A bare hash is a command of its own, even when it starts with
+        // digits; all digits is a row number, as in vim
+        if is_hash(input) && !input.bytes().all(|b| b.is_ascii_digit()) {
+            return Ok(Self {
+                range: None,
+                command: Some(Command::parse(input)?),
+            });
+        }
+        let (mut range, rest) = split_range(input);
+        if rest.trim().is_empty() {
+            return match range {
+                Some(_) => Ok(Self {
+                    range,
+                    command: None,
+                }),
+                None => Err(HistError::InvalidCommand("empty command".to_string())),
+            };
+        }
+
+        let mut words = split_words(rest)?;
+        // `:delete 3,7` names its rows after the command, too
+        if range.is_none() && words.len() == 2 && matches!(words[0].as_str(), "delete" | "d") {
+            range = words.pop().as_deref().map(RowRange::parse).transpose()?;
+        }
+        let command = Command::from_words(&words)?;
+        if range.is_some() && !command.takes_range() {
+            return Err(HistError::InvalidCommand(format!(
+                "'{}' doesn't take a range",
+                words[0]
+            )));
+        }
+        Ok(Self {
+            range,
+            command: Some(command),
+        })
+    }
+}
+
+/// Split a range off the front of a command line: `%`, or one or two rows
+/// (a line number, `.` or `$`) separated by a comma
+fn split_range(input: &str) -> (Option<RowRange>, &str) {
+    if let Some(rest) = input.strip_prefix('%') {
+        return (Some(RowRange::ALL), rest);
+    }
+    let Some((start, rest)) = split_row(input) else {
+        return (None, input);
+    };
+    match rest.strip_prefix(',').and_then(split_row) {
+        Some((end, rest)) => (Some(RowRange { start, end }), rest),
+        None => (Some(RowRange { start, end: start }), rest),
+    }
+}
+
+/// Split one row off the front of a range
+fn split_row(input: &str) -> Option<(RowRef, &str)> {
+    if let Some(rest) = input.strip_prefix('.') {
+        return Some((RowRef::Cursor, rest));
+    }
+    if let Some(rest) = input.strip_prefix('$') {
+        return Some((RowRef::Last, rest));
+    }
+    let digits = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let line = input[..digits].parse().ok()?;
+    Some((RowRef::Line(line), &input[digits..]))
+}
+
+/// Split a command line into words at whitespace, keeping what's in double
+/// quotes together (`\"` is a quote inside them)
+fn split_words(input: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            '\\' if quoted && chars.as_str().starts_with('"') => {
+                chars.next();
+                word.get_or_insert_with(String::new).push('"');
+            }
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err(HistError::InvalidCommand("unterminated quote".to_string()));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Parse `:author` arguments: a name and an email, or `Name <email>`
+fn parse_author(args: &[&str]) -> Result<Command> {
+    let usage = || {
+        HistError::InvalidCommand(
+            "usage: author <name> <email> (e.g. author \"Jane Doe\" jane@example.com)".to_string(),
+        )
+    };
+    let person = match args.split_last() {
+        Some((email, name)) if !name.is_empty() && !email.contains(['<', '>']) => {
+            Person::new(name.join(" "), *email)
+        }
+        Some(_) => Person::parse(&args.join(" ")).ok_or_else(usage)?,
+        None => return Err(usage()),
+    };
+    validate_email(&person.email)?;
+    Ok(Command::Author(person))
+}
+
+/// Whether a word is a full or abbreviated commit hash (at least 4 hex digits)
+fn is_hash(word: &str) -> bool {
+    (4..=40).contains(&word.len()) && word.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// The fields a `:clear` argument names
+fn clear_fields(name: &str) -> Option<&'static [EditableField]> {
+    Some(match name {
+        "name" => &[EditableField::AuthorName],
+        "email" => &[EditableField::AuthorEmail],
+        "date" => &[EditableField::AuthorDate],
+        "message" | "msg" => &[EditableField::Message],
+        "cname" => &[EditableField::CommitterName],
+        "cemail" => &[EditableField::CommitterEmail],
+        "cdate" => &[EditableField::CommitterDate],
+        "committer" => &[
+            EditableField::CommitterName,
+            EditableField::CommitterEmail,
+            EditableField::CommitterDate,
+        ],
+        "all" => EditableField::all(),
+        _ => return None,
+    })
+}
+
+/// Parse a snap granularity or divergence threshold in seconds: `minute`, `hour`, `day`, or `<n>m`/`<n>h`/`<n>d`
+fn parse_granularity(input: &str) -> Option<i64> {
+    match input {
+        "minute" | "min" => return Some(60),
+        "hour" => return Some(3600),
+        "day" => return Some(86_400),
+        _ => {}
+    }
+    let unit = match input.chars().last()? {
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    let count: i64 = input[..input.len() - 1].parse().ok()?;
+    (count > 0).then_some(count.checked_mul(unit)?)
+}
+
+/// Parse a signed date offset in seconds: units `w`, `d`, `h`, `m` and `s`
+/// combined largest first, like `+3d4h` or `-90m` (no sign means later)
+fn parse_offset(input: &str) -> Option<i64> {
+    let (sign, mut rest) = match input.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let mut total: i64 = 0;
+    let mut last_unit = i64::MAX;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let count: i64 = rest[..digits].parse().ok()?;
+        let unit = match rest[digits..].chars().next()? {
+            'w' => 604_800,
+            'd' => 86_400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        if unit >= last_unit {
+            return None;
+        }
+        last_unit = unit;
+        total = total.checked_add(count.checked_mul(unit)?)?;
+        rest = &rest[digits + 1..];
+    }
+    (total > 0).then_some(sign * total)
+}
+
+/// Parse `:spread` arguments: `<start> .. <end> [~jitter]`, with dates as
+/// in the edit popup
+fn parse_spread(args: &[&str]) -> Result<Command> {
+    let usage = || {
+        HistError::InvalidCommand(
+            "usage: spread <start> .. <end> [~jitter] (e.g. spread 2024-01-01 09:00 .. 2024-01-05 ~2h)"
+                .to_string(),
+        )
+    };
+    let (jitter, args) = match args.split_last() {
+        Some((last, rest)) if last.starts_with('~') => {
+            let jitter = parse_granularity(&last[1..]).ok_or_else(|| {
+                HistError::InvalidCommand(format!("invalid jitter '{last}' (e.g. ~30m)"))
+            })?;
+            (jitter, rest)
+        }
+        _ => (0, args),
+    };
+    let split = args.iter().position(|arg| *arg == "..").ok_or_else(usage)?;
+    let (start, end) = (args[..split].join(" "), args[split + 1..].join(" "));
+    if start.is_empty() || end.is_empty() {
+        return Err(usage());
+    }
+    let start = validate_date(&start)?;
+    let end = validate_date(&end)?;
+    if end <= start {
+        return Err(HistError::InvalidCommand(
+            "the end of the spread must be after its start".to_string(),
+        ));
+    }
+    Ok(Command::Spread { start, end, jitter })
+}
+
+/// Parse a UTC offset in seconds east: `+HHMM`, `-HH:MM` or `UTC`/`Z`
+fn parse_utc_offset(input: &str) -> Option<i32> {
+    if input.eq_ignore_ascii_case("utc") || input.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+    let (sign, rest) = match input.as_bytes().first()? {
+        b'+' => (1, &input[1..]),
+        b'-' => (-1, &input[1..]),
+        _ => return None,
+    };
+    let digits = rest.replacen(':', "", 1);
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parse a vim-style `:set` argument: `opt`, `noopt`, `opt!` or `invopt`
+fn parse_set(arg: &str) -> Result<Command> {
+    // An option's own name wins over a prefix it happens to start with
+    if let Some(option) = DisplayOption::from_name(arg) {
+        return Ok(Command::Set {
+            option,
+            value: Some(true),
+        });
+    }
+    let (name, value) = if let Some(name) = arg.strip_suffix('!') {
+        (name, None)
+    } else if let Some(name) = arg.strip_prefix("inv") {
+        (name, None)
+    } else if let Some(name) = arg.strip_prefix("no") {
+        (name, Some(false))
+    } else {
+        (arg, Some(true))
+    };
+
+    DisplayOption::from_name(name)
+        .map(|option| Command::Set { option, value })
+        .ok_or_else(|| HistError::InvalidCommand(format!("unknown option '{name}'")))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let cmd = Command::parse("range abc1234 def5678").unwrap();
+        assert_eq!(
+            cmd,
+            Command::SelectRange {
+                from: "abc1234".to_string(),
+                to: "def5678".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_range_alias_and_whitespace() {
+        let cmd = Command::parse("  sel   abc   def ").unwrap();
+        assert!(matches!(cmd, Command::SelectRange { .. }));
+    }
+
+    #[test]
+    fn test_parse_range_wrong_arity() {
+        assert!(Command::parse("range abc").is_err());
+        assert!(Command::parse("range a b c").is_err());
+    }
+
+    #[test]
+    fn test_parse_goto() {
+        assert_eq!(
+            Command::parse("goto abc1234").unwrap(),
+            Command::Goto("abc1234".to_string())
+        );
+        assert_eq!(
+            Command::parse("  ABC1234 ").unwrap(),
+            Command::Goto("ABC1234".to_string())
+        );
+        assert!(Command::parse("g").is_err());
+        // Too short to be a hash, or not hex
+        assert!(Command::parse("abc").is_err());
+        assert!(Command::parse("abcdefg").is_err());
+    }
+
+    #[test]
+    fn test_parse_highlight() {
+        assert_eq!(
+            Command::parse("hl Jane Doe").unwrap(),
+            Command::Highlight("Jane Doe".to_string())
+        );
+        assert!(Command::parse("highlight").is_err());
+        assert_eq!(Command::parse("nohl").unwrap(), Command::NoHighlight(None));
+        assert_eq!(
+            Command::parse("nohighlight jane@example.com").unwrap(),
+            Command::NoHighlight(Some("jane@example.com".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_limit() {
+        assert_eq!(
+            Command::parse("limit 500").unwrap(),
+            Command::Limit(Some(500))
+        );
+        assert_eq!(Command::parse("limit all").unwrap(), Command::Limit(None));
+        assert_eq!(Command::parse("limit 0").unwrap(), Command::Limit(None));
+        assert!(Command::parse("limit lots").is_err());
+        assert!(Command::parse("limit").is_err());
+    }
+
+    #[test]
+    fn test_parse_editall() {
+        assert_eq!(Command::parse("editall").unwrap(), Command::EditAll);
+        assert_eq!(Command::parse("autosquash").unwrap(), Command::Autosquash);
+        assert_eq!(Command::parse("me").unwrap(), Command::Me);
+        assert_eq!(Command::parse("who").unwrap(), Command::Authors);
+        assert_eq!(Command::parse("preview").unwrap(), Command::Preview);
+        assert_eq!(Command::parse("backups").unwrap(), Command::Backups);
+    }
+
+    #[test]
+    fn test_parse_snap() {
+        assert_eq!(
+            Command::parse("snap hour").unwrap(),
+            Command::Snap {
+                step: 3600,
+                mode: SnapMode::Nearest
+            }
+        );
+        assert_eq!(
+            Command::parse("snap 15m floor").unwrap(),
+            Command::Snap {
+                step: 900,
+                mode: SnapMode::Floor
+            }
+        );
+        assert_eq!(
+            Command::parse("snap 2d ceil").unwrap(),
+            Command::Snap {
+                step: 172_800,
+                mode: SnapMode::Ceil
+            }
+        );
+        assert!(Command::parse("snap").is_err());
+        assert!(Command::parse("snap 0h").is_err());
+        assert!(Command::parse("snap week").is_err());
+        assert!(Command::parse("snap day sideways").is_err());
+        // Too big to count in seconds
+        assert!(Command::parse("snap 99999999999999999d").is_err());
+        assert!(Command::parse("snap 144115188075855872d").is_err());
+    }
+
+    #[test]
+    fn test_parse_shift() {
+        let shift = |input: &str| Command::parse(input).ok();
+        assert_eq!(
+            shift("shift +3d4h"),
+            Some(Command::Shift(3 * 86_400 + 4 * 3600))
+        );
+        assert_eq!(shift("shift -90m"), Some(Command::Shift(-5400)));
+        assert_eq!(shift("shift 1w30s"), Some(Command::Shift(604_830)));
+        assert_eq!(shift("shift"), None);
+        assert_eq!(shift("shift 0h"), None);
+        assert_eq!(shift("shift 4h3d"), None);
+        assert_eq!(shift("shift +3x"), None);
+        assert_eq!(shift("shift 90"), None);
+    }
+
+    #[test]
+    fn test_parse_spread() {
+        use chrono::TimeZone;
+
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(
+            Command::parse("spread 2024-01-01 09:00 .. 2024-01-05 ~2h").unwrap(),
+            Command::Spread {
+                start: utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap(),
+                end: utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap(),
+                jitter: 7200,
+            }
+        );
+        assert!(Command::parse("spread 2024-01-01 .. 2024-01-05").is_ok());
+        assert!(Command::parse("spread 2024-01-05 .. 2024-01-01").is_err());
+        assert!(Command::parse("spread 2024-01-01 2024-01-05").is_err());
+        assert!(Command::parse("spread .. 2024-01-05").is_err());
+        assert!(Command::parse("spread 2024-01-01 .. 2024-01-05 ~soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_fixdates() {
+        let fix = |input: &str| Command::parse(input).ok();
+        assert_eq!(fix("fixdates"), Some(Command::FixDates(DateFix::Swap)));
+        assert_eq!(
+            fix("fixdates nudge"),
+            Some(Command::FixDates(DateFix::Nudge(60)))
+        );
+        assert_eq!(
+            fix("fixdates nudge 5m"),
+            Some(Command::FixDates(DateFix::Nudge(300)))
+        );
+        assert_eq!(fix("fixdates nudge -5m"), None);
+        assert_eq!(fix("fixdates shuffle"), None);
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        let tz = |input: &str| Command::parse(input).ok();
+        assert_eq!(tz("tz +0200"), Some(Command::Timezone(7200)));
+        assert_eq!(tz("tz -05:30"), Some(Command::Timezone(-19_800)));
+        assert_eq!(tz("timezone UTC"), Some(Command::Timezone(0)));
+        assert_eq!(tz("tz 0200"), None);
+        assert_eq!(tz("tz +2"), None);
+        assert_eq!(tz("tz +2460"), None);
+    }
+
+    #[test]
+    fn test_parse_unlock() {
+        assert_eq!(
+            Command::parse("unlock").unwrap(),
+            Command::Unlock { all: false }
+        );
+        assert_eq!(
+            Command::parse("unlock all").unwrap(),
+            Command::Unlock { all: true }
+        );
+        assert!(Command::parse("unlock some").is_err());
+    }
+
+    #[test]
+    fn test_parse_newroot() {
+        assert_eq!(
+            Command::parse("newroot").unwrap(),
+            Command::NewRoot(Vec::new())
+        );
+        assert_eq!(
+            Command::parse("newroot LICENSE README.md").unwrap(),
+            Command::NewRoot(vec!["LICENSE".to_string(), "README.md".to_string()])
+        );
+        assert_eq!(Command::parse("nonewroot").unwrap(), Command::NoNewRoot);
+    }
+
+    #[test]
+    fn test_parse_mailmap() {
+        assert_eq!(Command::parse("mailmap").unwrap(), Command::Mailmap(None));
+        assert_eq!(
+            Command::parse("mailmap ../canonical.mailmap").unwrap(),
+            Command::Mailmap(Some("../canonical.mailmap".to_string()))
+        );
+        assert!(Command::parse("mailmap a b").is_err());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        assert_eq!(
+            Command::parse("export .mailmap").unwrap(),
+            Command::Export(".mailmap".to_string())
+        );
+        assert!(Command::parse("export").is_err());
+        assert_eq!(
+            Command::parse("dump audit.csv").unwrap(),
+            Command::Dump("audit.csv".to_string())
+        );
+        assert!(Command::parse("dump").is_err());
+    }
+
+    #[test]
+    fn test_parse_signoff() {
+        assert_eq!(
+            Command::parse("signoff").unwrap(),
+            Command::SignOff { all: false }
+        );
+        assert_eq!(
+            Command::parse("so all").unwrap(),
+            Command::SignOff { all: true }
+        );
+        assert!(Command::parse("signoff twice").is_err());
+    }
+
+    #[test]
+    fn test_parse_reflow() {
+        assert_eq!(Command::parse("reflow").unwrap(), Command::Reflow(72));
+        assert_eq!(Command::parse("reflow 50").unwrap(), Command::Reflow(50));
+        assert!(Command::parse("reflow 0").is_err());
+        assert!(Command::parse("reflow wide").is_err());
+    }
+
+    #[test]
+    fn test_parse_branch() {
+        assert_eq!(Command::parse("branch").unwrap(), Command::Branch(None));
+        assert_eq!(
+            Command::parse("br feature").unwrap(),
+            Command::Branch(Some("feature".to_string()))
+        );
+        assert!(Command::parse("branch a b").is_err());
+    }
+
+    #[test]
+    fn test_parse_divergence() {
+        assert_eq!(
+            Command::parse("divergence").unwrap(),
+            Command::Divergence(None)
+        );
+        assert_eq!(
+            Command::parse("div 2h").unwrap(),
+            Command::Divergence(Some(7200))
+        );
+        assert!(Command::parse("div soon").is_err());
+        assert!(Command::parse("div 1h 2h").is_err());
+    }
+
+    #[test]
+    fn test_parse_lint() {
+        assert_eq!(Command::parse("lint").unwrap(), Command::Lint(None));
+        assert_eq!(Command::parse("lint fix").unwrap(), Command::Lint(Some(1)));
+        let err = Command::parse("lint feature").unwrap_err();
+        assert!(err.to_string().contains("unknown type 'feature'"));
+    }
+
+    #[test]
+    fn test_parse_note() {
+        assert_eq!(
+            Command::parse("note fix  laptop email").unwrap(),
+            Command::Note(Some("fix laptop email".to_string()))
+        );
+        assert_eq!(Command::parse("note").unwrap(), Command::Note(None));
+    }
+
+    #[test]
+    fn test_parse_clear() {
+        assert_eq!(
+            Command::parse("clear date").unwrap(),
+            Command::Clear(vec![EditableField::AuthorDate])
+        );
+        assert_eq!(
+            Command::parse("clear cdate committer").unwrap(),
+            Command::Clear(vec![
+                EditableField::CommitterDate,
+                EditableField::CommitterName,
+                EditableField::CommitterEmail,
+            ])
+        );
+        assert!(Command::parse("clear").is_err());
+        assert!(Command::parse("clear hash").is_err());
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(
+            Command::parse("sort email").unwrap(),
+            Command::Sort(Some(SortOrder {
+                key: SortKey::Email,
+                descending: false
+            }))
+        );
+        assert_eq!(
+            Command::parse("sort date desc").unwrap(),
+            Command::Sort(Some(SortOrder {
+                key: SortKey::Date,
+                descending: true
+            }))
+        );
+        assert_eq!(Command::parse("nosort").unwrap(), Command::Sort(None));
+        assert!(Command::parse("sort").is_err());
+        assert!(Command::parse("sort hash").is_err());
+        assert!(Command::parse("sort date sideways").is_err());
+    }
+
+    #[test]
+    fn test_parse_set() {
+        let set = |value| Command::Set {
+            option: DisplayOption::HeadOffset,
+            value,
+        };
+        assert_eq!(Command::parse("set offset").unwrap(), set(Some(true)));
+        assert_eq!(Command::parse("set nooffset").unwrap(), set(Some(false)));
+        assert_eq!(Command::parse("set offset!").unwrap(), set(None));
+        assert_eq!(Command::parse("set invoffset").unwrap(), set(None));
+        assert_eq!(
+            Command::parse("set nornu").unwrap(),
+            Command::Set {
+                option: DisplayOption::RelativeNumber,
+                value: Some(false)
+            }
+        );
+        let number = |value| Command::Set {
+            option: DisplayOption::Number,
+            value,
+        };
+        assert_eq!(Command::parse("set number").unwrap(), number(Some(true)));
+        assert_eq!(Command::parse("set nonumber").unwrap(), number(Some(false)));
+        assert_eq!(Command::parse("set number!").unwrap(), number(None));
+        assert_eq!(Command::parse("set invnumber").unwrap(), number(None));
+        assert!(Command::parse("set nosuchoption").is_err());
+        assert!(Command::parse("set").is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_and_empty() {
+        assert!(matches!(
+            Command::parse("frobnicate"),
+            Err(HistError::InvalidCommand(_))
+        ));
+        assert!(Command::parse("   ").is_err());
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"author "Jane Doe" jane@example.com"#).unwrap(),
+            ["author", "Jane Doe", "jane@example.com"]
+        );
+        assert_eq!(
+            split_words(r#"note say "\"hi\"" ''"#).unwrap(),
+            ["note", "say", "\"hi\"", "''"]
+        );
+        assert_eq!(split_words(r#"note """#).unwrap(), ["note", ""]);
+        assert!(split_words(r#"note "open"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_author() {
+        let jane = Command::Author(Person::new("Jane Doe", "jane@example.com"));
+        assert_eq!(
+            Command::parse(r#"author "Jane Doe" jane@example.com"#).unwrap(),
+            jane
+        );
+        assert_eq!(
+            Command::parse("author Jane Doe jane@example.com").unwrap(),
+            jane
+        );
+        assert_eq!(
+            Command::parse("author Jane Doe <jane@example.com>").unwrap(),
+            jane
+        );
+        assert!(Command::parse("author").is_err());
+        assert!(Command::parse("author jane@example.com").is_err());
+        assert!(Command::parse("author Jane not-an-email").is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(Command::parse("date +2h").unwrap(), Command::Shift(7200));
+        assert_eq!(
+            Command::parse("date 2024-01-15 14:30").unwrap(),
+            Command::Date(validate_date("2024-01-15 14:30").unwrap())
+        );
+        assert!(Command::parse("date").is_err());
+        assert!(Command::parse("date +2x").is_err());
+        assert!(Command::parse("date tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_parse_write_quit() {
+        assert_eq!(Command::parse("w").unwrap(), Command::Write { quit: false });
+        assert_eq!(Command::parse("wq").unwrap(), Command::Write { quit: true });
+        assert_eq!(Command::parse("q").unwrap(), Command::Quit { force: false });
+        assert_eq!(Command::parse("q!").unwrap(), Command::Quit { force: true });
+        assert_eq!(Command::parse("delete").unwrap(), Command::Delete);
+    }
+
+    #[test]
+    fn test_parse_command_line_range() {
+        let line = |input: &str| CommandLine::parse(input).unwrap();
+        let rows = |start, end| Some(RowRange { start, end });
+
+        assert_eq!(
+            line("3,7delete"),
+            CommandLine {
+                range: rows(RowRef::Line(3), RowRef::Line(7)),
+                command: Some(Command::Delete),
+            }
+        );
+        // The rows can follow :delete instead
+        assert_eq!(line("delete 3,7"), line("3,7delete"));
+        assert_eq!(
+            line(r#"%author "Jane Doe" jane@example.com"#).range,
+            Some(RowRange::ALL)
+        );
+        assert_eq!(
+            line(".,$ shift +1h").range,
+            rows(RowRef::Cursor, RowRef::Last)
+        );
+        assert_eq!(
+            line("42"),
+            CommandLine {
+                range: rows(RowRef::Line(42), RowRef::Line(42)),
+                command: None,
+            }
+        );
+        // Long row numbers aren't hashes
+        assert_eq!(
+            line("1234"),
+            CommandLine {
+                range: rows(RowRef::Line(1234), RowRef::Line(1234)),
+                command: None,
+            }
+        );
+        // No range, or a hash that starts with digits
+        assert_eq!(line("w").range, None);
+        assert_eq!(
+            line("1234abc").command,
+            Some(Command::Goto("1234abc".to_string()))
+        );
+
+        assert!(CommandLine::parse("3,7limit 10").is_err());
+        assert!(CommandLine::parse("delete 3,x").is_err());
+        assert!(CommandLine::parse("3,7bogus").is_err());
+    }
+
+    #[test]
+    fn test_row_range_rows() {
+        let range = |input: &str| RowRange::parse(input).unwrap();
+        assert_eq!(range("3,7").rows(0, 10).unwrap(), 2..=6);
+        assert_eq!(range("7,3").rows(0, 10).unwrap(), 2..=6);
+        assert_eq!(range("%").rows(4, 10).unwrap(), 0..=9);
+        assert_eq!(range(".,$").rows(4, 10).unwrap(), 4..=9);
+        assert_eq!(range(".").rows(4, 10).unwrap(), 4..=4);
+        assert!(range("0").rows(0, 10).is_err());
+        assert!(range("3,11").rows(0, 10).is_err());
+        assert!(range("$").rows(0, 0).is_err());
+        assert!(RowRange::parse("3,").is_err());
+    }
+}
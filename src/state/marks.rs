@@ -0,0 +1,155 @@
+use crate::git::commit::CommitId;
+use std::collections::HashMap;
+
+/// Maximum number of positions kept in the jump list (as in vim)
+const MAX_JUMPS: usize = 100;
+
+/// Named marks (`ma`, `'a`) and the jump list (`Ctrl+o`/`Ctrl+i`)
+///
+/// Both remember commits rather than rows, so they survive filtering,
+/// sorting, reordering and loading more history.
+#[derive(Debug, Default)]
+pub struct Marks {
+    named: HashMap<char, CommitId>,
+    /// Commits jumped away from, oldest first
+    jumps: Vec<CommitId>,
+    /// Index into `jumps` while going back and forth (`jumps.len()` = not browsing)
+    position: usize,
+}
+
+impl Marks {
+    /// Whether a mark can be named `name` (a-z)
+    #[must_use]
+    pub fn is_valid_name(name: char) -> bool {
+        name.is_ascii_lowercase()
+    }
+
+    /// Put mark `name` on a commit, replacing where it was
+    pub fn set(&mut self, name: char, id: CommitId) {
+        self.named.insert(name, id);
+    }
+
+    #[must_use]
+    pub fn get(&self, name: char) -> Option<CommitId> {
+        self.named.get(&name).copied()
+    }
+
+    /// Remember `from` before jumping away from it
+    ///
+    /// Jumping while going back through the list forgets the newer entries,
+    /// like a browser's history.
+    pub fn record_jump(&mut self, from: CommitId) {
+        if self.position < self.jumps.len() {
+            self.jumps.truncate(self.position + 1);
+        }
+        if self.jumps.last() != Some(&from) {
+            self.jumps.push(from);
+        }
+        if self.jumps.len() > MAX_JUMPS {
+            self.jumps.remove(0);
+        }
+        self.position = self.jumps.len();
+    }
+
+    /// The commit to go back to from `current` (`Ctrl+o`), if any
+    pub fn back(&mut self, current: CommitId) -> Option<CommitId> {
+        if self.position >= self.jumps.len() {
+            // Remember where browsing started, to come forward to it again
+            if self.jumps.last() != Some(&current) {
+                self.jumps.push(current);
+            }
+            self.position = self.jumps.len() - 1;
+        }
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.jumps[self.position])
+    }
+
+    /// The commit to go forward to again (`Ctrl+i`), if any
+    pub fn forward(&mut self) -> Option<CommitId> {
+        if self.position + 1 >= self.jumps.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(self.jumps[self.position])
+    }
+
+    /// Follow rewritten commits to their new IDs after an apply (marks and
+    /// jumps on deleted commits are dropped)
+    pub fn remap(&mut self, commit_map: &HashMap<CommitId, CommitId>) {
+        self.named = self
+            .named
+            .iter()
+            .filter_map(|(name, id)| commit_map.get(id).map(|new| (*name, *new)))
+            .collect();
+        self.jumps = self
+            .jumps
+            .iter()
+            .filter_map(|id| commit_map.get(id).copied())
+            .collect();
+        self.position = self.jumps.len();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn id(n: u8) -> CommitId {
+        CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap())
+    }
+
+    #[test]
+    fn test_marks() {
+        let mut marks = Marks::default();
+        assert!(Marks::is_valid_name('a'));
+        assert!(!Marks::is_valid_name('A'));
+        assert_eq!(marks.get('a'), None);
+        marks.set('a', id(1));
+        marks.set('a', id(2));
+        assert_eq!(marks.get('a'), Some(id(2)));
+    }
+
+    #[test]
+    fn test_jump_list() {
+        let mut marks = Marks::default();
+        assert_eq!(marks.back(id(1)), None);
+
+        // Jumped 1 -> 2 -> 3 -> 4
+        marks.record_jump(id(1));
+        marks.record_jump(id(2));
+        marks.record_jump(id(3));
+        assert_eq!(marks.forward(), None);
+        assert_eq!(marks.back(id(4)), Some(id(3)));
+        assert_eq!(marks.back(id(3)), Some(id(2)));
+        assert_eq!(marks.forward(), Some(id(3)));
+        assert_eq!(marks.forward(), Some(id(4)));
+        assert_eq!(marks.forward(), None);
+
+        // Jumping from 2 forgets 3 and 4
+        assert_eq!(marks.back(id(4)), Some(id(3)));
+        assert_eq!(marks.back(id(3)), Some(id(2)));
+        marks.record_jump(id(2));
+        assert_eq!(marks.back(id(5)), Some(id(2)));
+        assert_eq!(marks.back(id(2)), Some(id(1)));
+        assert_eq!(marks.back(id(1)), None);
+    }
+
+    #[test]
+    fn test_remap() {
+        let mut marks = Marks::default();
+        marks.set('a', id(1));
+        marks.set('b', id(2));
+        marks.record_jump(id(1));
+        marks.record_jump(id(2));
+
+        // 1 was rewritten, 2 deleted
+        marks.remap(&HashMap::from([(id(1), id(9))]));
+        assert_eq!(marks.get('a'), Some(id(9)));
+        assert_eq!(marks.get('b'), None);
+        assert_eq!(marks.back(id(3)), Some(id(9)));
+    }
+}
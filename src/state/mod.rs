@@ -1,3 +1,22 @@
 pub mod app_state;
+pub mod command;
+pub mod marks;
+pub mod palette;
+pub mod plan;
+pub mod search_history;
+pub mod search_query;
+pub mod session;
 
-pub use app_state::{AppMode, AppState, ConfirmAction, VisualType};
+pub use app_state::{
+    AppMode, AppState, AuthorGroups, BackupList, BranchPicker, ConfirmAction, DateFix, DateSource,
+    DisplayOption, DivergenceReport, HashPreview, IdentityPicker, LintReport, PendingEdit,
+    ReplaceTarget, SnapMode, SortKey, SortOrder, SplitChooser, StashConflict, StepApply,
+    TrailerEditor, UndoBrowser, VisualType, CLIPBOARD_REGISTER, UNNAMED_REGISTER,
+};
+pub use command::{Command, CommandLine};
+pub use marks::Marks;
+pub use palette::{CommandPalette, PaletteAction};
+pub use plan::EditPlan;
+pub use search_history::SearchHistory;
+pub use search_query::{SearchQuery, TouchedPath};
+pub use session::Session;
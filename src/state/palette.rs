@@ -0,0 +1,304 @@
+use crate::plugin::PluginCommand;
+use std::borrow::Cow;
+
+/// What choosing a palette entry does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Press this key in normal mode (with Ctrl when `ctrl`)
+    Key { key: char, ctrl: bool },
+    /// Run this command line (a built-in or a config command)
+    Run(Cow<'static, str>),
+    /// Open the command line with this typed, for the arguments to follow
+    Prompt(&'static str),
+}
+
+/// An action listed in the command palette (`Ctrl+p`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub name: Cow<'static, str>,
+    /// Keys or command that do the same
+    pub keys: Cow<'static, str>,
+    pub action: PaletteAction,
+}
+
+impl PaletteEntry {
+    /// The entry for a `[[commands]]` command from the config
+    fn plugin(plugin: &PluginCommand) -> Self {
+        Self {
+            name: Cow::Owned(
+                plugin
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| plugin.name.clone()),
+            ),
+            keys: Cow::Owned(format!(":{}", plugin.name)),
+            action: PaletteAction::Run(Cow::Owned(plugin.name.clone())),
+        }
+    }
+}
+
+const fn key(name: &'static str, keys: &'static str, key: char) -> PaletteEntry {
+    PaletteEntry {
+        name: Cow::Borrowed(name),
+        keys: Cow::Borrowed(keys),
+        action: PaletteAction::Key { key, ctrl: false },
+    }
+}
+
+const fn ctrl(name: &'static str, keys: &'static str, key: char) -> PaletteEntry {
+    PaletteEntry {
+        name: Cow::Borrowed(name),
+        keys: Cow::Borrowed(keys),
+        action: PaletteAction::Key { key, ctrl: true },
+    }
+}
+
+const fn run(name: &'static str, keys: &'static str, command: &'static str) -> PaletteEntry {
+    PaletteEntry {
+        name: Cow::Borrowed(name),
+        keys: Cow::Borrowed(keys),
+        action: PaletteAction::Run(Cow::Borrowed(command)),
+    }
+}
+
+const fn prompt(name: &'static str, keys: &'static str, command: &'static str) -> PaletteEntry {
+    PaletteEntry {
+        name: Cow::Borrowed(name),
+        keys: Cow::Borrowed(keys),
+        action: PaletteAction::Prompt(command),
+    }
+}
+
+/// Every built-in action in the palette, in the order shown before anything
+/// is typed
+pub const PALETTE_ENTRIES: &[PaletteEntry] = &[
+    // Editing
+    key("Edit cell", "e", 'e'),
+    key("Edit cell in $EDITOR", "E", 'E'),
+    run("Edit column on all visible commits", "ge", "editall"),
+    key("Find and replace in message", "R", 'R'),
+    key("Set author from identities", "i", 'i'),
+    run("Set author to me", ":me", "me"),
+    prompt("Set author", ":author", "author "),
+    key("Edit trailers", "T", 'T'),
+    key("Edit co-authors", "A", 'A'),
+    key("Sign off", "O", 'O'),
+    run("Reflow message bodies", ":reflow", "reflow"),
+    prompt("Note why commits change", ":note", "note "),
+    prompt("Clear pending edits of fields", ":clear", "clear "),
+    key("Yank cell", "y", 'y'),
+    key("Paste into cell", "p", 'p'),
+    key("Copy full hash", "Y", 'Y'),
+    // History
+    key("Delete commit", "d", 'd'),
+    prompt("Delete rows", ":delete", "delete "),
+    key("Fixup into commit below", "f", 'f'),
+    key("Split commit", "S", 'S'),
+    key("Move commit up", "K", 'K'),
+    key("Move commit down", "J", 'J'),
+    run(
+        "Autosquash fixup!/squash! commits",
+        ":autosquash",
+        "autosquash",
+    ),
+    prompt("Add a new root commit", ":newroot", "newroot "),
+    // Dates
+    prompt("Set author date", ":date", "date "),
+    prompt("Shift dates", ":shift", "shift "),
+    prompt("Snap dates", ":snap", "snap "),
+    prompt("Spread dates", ":spread", "spread "),
+    prompt("Convert dates to a timezone", ":tz", "tz "),
+    key("Put dates back in order", "M", 'M'),
+    run(
+        "List diverging author/committer dates",
+        ":divergence",
+        "divergence",
+    ),
+    // Navigation and selection
+    key("Go to first commit", "g", 'g'),
+    key("Go to last commit", "G", 'G'),
+    prompt("Go to commit by hash", ":goto", "goto "),
+    ctrl("Jump back", "Ctrl+o", 'o'),
+    key("Load older commits", "+", '+'),
+    prompt("Load more or fewer commits", ":limit", "limit "),
+    key("Toggle selection", "Space", ' '),
+    ctrl("Select all", "Ctrl+a", 'a'),
+    ctrl("Deselect all", "Ctrl+n", 'n'),
+    prompt("Select range by hashes", ":range", "range "),
+    key("Visual mode", "v", 'v'),
+    ctrl("Block visual mode", "Ctrl+v", 'v'),
+    // Search and view
+    key("Search and filter", "/", '/'),
+    key("Next match", "n", 'n'),
+    key("Previous match", "N", 'N'),
+    prompt("Sort rows", ":sort", "sort "),
+    run("Unsort rows", ":nosort", "nosort"),
+    prompt("Highlight matching cells", ":hl", "hl "),
+    run("Clear highlights", ":nohl", "nohl"),
+    run("Toggle offset column", ":set offset!", "set offset!"),
+    run("Toggle time gap column", ":set gap!", "set gap!"),
+    run("Toggle graph column", ":set graph!", "set graph!"),
+    run("Toggle line numbers", ":set nu!", "set nu!"),
+    run("Toggle relative line numbers", ":set rnu!", "set rnu!"),
+    run("Toggle local time", ":set localtime!", "set localtime!"),
+    run("Toggle lint markers", ":set lint!", "set lint!"),
+    // Undo
+    key("Undo", "u", 'u'),
+    ctrl("Redo", "Ctrl+r", 'r'),
+    key("Undo history", "U", 'U'),
+    key("Discard all changes", "r", 'r'),
+    // Review and write
+    key("Write changes", "w", 'w'),
+    key("Write, reviewing each commit", "W", 'W'),
+    key("Preview new hashes", "P", 'P'),
+    prompt("Export plan, mailmap or rebase todo", ":export", "export "),
+    prompt("Dump commit metadata", ":dump", "dump "),
+    run("Backups", ":backups", "backups"),
+    run("Authors", ":authors", "authors"),
+    run("Lint commit messages", ":lint", "lint"),
+    run("Remap identities with mailmap", ":mailmap", "mailmap"),
+    run("Unlock locked commits", ":unlock", "unlock"),
+    key("Switch branch", "B", 'B'),
+    // General
+    key("Help", "?", '?'),
+    key("Quit", "q", 'q'),
+];
+
+/// The command palette: a query and the highlighted match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandPalette {
+    pub query: String,
+    /// Index into `matches()` of the highlighted entry
+    pub cursor: usize,
+    /// The built-in actions, then the config's commands
+    entries: Vec<PaletteEntry>,
+}
+
+impl CommandPalette {
+    /// Open the palette with `query` typed, listing the config's `plugins`
+    /// after the built-in actions
+    #[must_use]
+    pub fn new(query: String, plugins: &[PluginCommand]) -> Self {
+        Self {
+            query,
+            cursor: 0,
+            entries: PALETTE_ENTRIES
+                .iter()
+                .cloned()
+                .chain(plugins.iter().map(PaletteEntry::plugin))
+                .collect(),
+        }
+    }
+
+    /// Entries matching the query, the best first (all of them, in order,
+    /// when nothing is typed)
+    #[must_use]
+    pub fn matches(&self) -> Vec<&PaletteEntry> {
+        let mut scored: Vec<(i32, &PaletteEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let name = fuzzy_score(&self.query, &entry.name);
+                let keys = fuzzy_score(&self.query, &entry.keys);
+                name.max(keys).map(|score| (score, entry))
+            })
+            .collect();
+        // Stable, so equal scores keep the listed order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// The highlighted entry, if anything matches
+    #[must_use]
+    pub fn selected(&self) -> Option<&PaletteEntry> {
+        self.matches().get(self.cursor).copied()
+    }
+}
+
+/// How well `query` matches `text` as a subsequence, ignoring case (None if
+/// it doesn't); consecutive characters and word starts score higher
+#[must_use]
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + text[next..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+        assert!(fuzzy_score("sq", "Autosquash").is_some());
+        assert!(fuzzy_score("ha", "Autosquash").is_none());
+        // Word starts and runs beat scattered letters
+        assert!(fuzzy_score("sd", "Shift dates") > fuzzy_score("sd", "Toggle selection mode"));
+        assert!(fuzzy_score("date", "Shift dates") > fuzzy_score("date", "Delete commit"));
+    }
+
+    #[test]
+    fn test_palette_matches() {
+        let mut palette = CommandPalette::new(String::new(), &[]);
+        assert_eq!(palette.matches().len(), PALETTE_ENTRIES.len());
+        assert_eq!(palette.selected(), PALETTE_ENTRIES.first());
+
+        palette.query = "squash".to_string();
+        assert_eq!(
+            palette.selected().unwrap().action,
+            PaletteAction::Run("autosquash".into())
+        );
+        // Matching the keys works too
+        palette.query = ":goto".to_string();
+        assert_eq!(
+            palette.selected().unwrap().action,
+            PaletteAction::Prompt("goto ")
+        );
+        palette.query = "zzz".to_string();
+        assert_eq!(palette.selected(), None);
+    }
+
+    #[test]
+    fn test_palette_lists_config_commands() {
+        let plugins = [PluginCommand {
+            name: "upper".to_string(),
+            run: "./upper.sh".to_string(),
+            description: Some("Shout the subjects".to_string()),
+        }];
+        let mut palette = CommandPalette::new("shout".to_string(), &plugins);
+        let entry = palette.selected().unwrap();
+        assert_eq!(entry.keys, ":upper");
+        assert_eq!(entry.action, PaletteAction::Run("upper".into()));
+
+        palette.query.clear();
+        assert_eq!(palette.matches().len(), PALETTE_ENTRIES.len() + 1);
+    }
+
+    #[test]
+    fn test_palette_commands_parse() {
+        for entry in PALETTE_ENTRIES {
+            if let PaletteAction::Run(command) = &entry.action {
+                assert!(
+                    crate::state::Command::parse(command).is_ok(),
+                    "{command} doesn't parse"
+                );
+            }
+        }
+    }
+}
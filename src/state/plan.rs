@@ -0,0 +1,114 @@
+use crate::error::{HistError, Result};
+use crate::git::commit::{CommitId, CommitModifications};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Pending edits kept in a JSON file, to review or apply without the TUI
+/// (`--plan`)
+///
+/// Commits are named by their full hashes. Maps and sets are written sorted,
+/// so the same edits always give the same file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditPlan {
+    /// New order of the loaded commits, newest first (empty: keep the order)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<CommitId>,
+    /// Metadata changes per commit
+    #[serde(default, serialize_with = "sorted_map")]
+    pub modifications: HashMap<CommitId, CommitModifications>,
+    /// Commits to delete
+    #[serde(default, serialize_with = "sorted_set")]
+    pub deleted: HashSet<CommitId>,
+}
+
+impl EditPlan {
+    /// Read a plan from a JSON file
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read or isn't a valid plan.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| HistError::Plan(format!("{}: {e}", path.display())))
+    }
+
+    /// Parse a plan from JSON
+    ///
+    /// # Errors
+    /// Returns an error if the JSON isn't a valid plan.
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content).map_err(|e| HistError::Plan(e.to_string()))
+    }
+
+    /// The plan as pretty-printed JSON
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Every commit the plan names
+    pub fn commit_ids(&self) -> impl Iterator<Item = &CommitId> {
+        self.order
+            .iter()
+            .chain(self.modifications.keys())
+            .chain(&self.deleted)
+    }
+}
+
+fn sorted_map<S: Serializer>(
+    map: &HashMap<CommitId, CommitModifications>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let sorted: BTreeMap<String, &CommitModifications> =
+        map.iter().map(|(id, m)| (id.0.to_string(), m)).collect();
+    sorted.serialize(serializer)
+}
+
+fn sorted_set<S: Serializer>(
+    set: &HashSet<CommitId>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let sorted: BTreeSet<String> = set.iter().map(|id| id.0.to_string()).collect();
+    sorted.serialize(serializer)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn id(n: u8) -> CommitId {
+        CommitId(git2::Oid::from_bytes(&[n; 20]).unwrap())
+    }
+
+    #[test]
+    fn test_plan_round_trip() {
+        let plan = EditPlan {
+            order: vec![id(2), id(1)],
+            modifications: HashMap::from([(
+                id(1),
+                CommitModifications {
+                    author_email: Some("new@example.com".to_string()),
+                    ..Default::default()
+                },
+            )]),
+            deleted: HashSet::from([id(3)]),
+        };
+        let json = plan.to_json();
+        assert!(json.contains(&format!("\"{}\"", "03".repeat(20))));
+        assert_eq!(EditPlan::parse(&json).unwrap(), plan);
+
+        // Everything but the order is optional
+        let plan = EditPlan::parse(&format!("{{\"deleted\": [\"{}\"]}}", "01".repeat(20))).unwrap();
+        assert!(plan.order.is_empty() && plan.modifications.is_empty());
+        assert_eq!(plan.commit_ids().collect::<Vec<_>>(), [&id(1)]);
+    }
+
+    #[test]
+    fn test_invalid_plan() {
+        let err = EditPlan::parse("{\"deleted\": [\"nothex\"]}").unwrap_err();
+        assert!(err.to_string().starts_with("Invalid plan: "));
+    }
+}
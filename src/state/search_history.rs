@@ -0,0 +1,204 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of queries kept in the history
+const MAX_ENTRIES: usize = 100;
+
+/// Get the directory used for retcon's persisted state
+///
+/// Uses the platform state directory (e.g. `~/.local/state/retcon`), falling
+/// back to the local data directory on platforms without one.
+#[must_use]
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("retcon"))
+}
+
+/// History of past search queries with shell-style Up/Down recall
+///
+/// Entries are stored oldest first, one query per line. Persistence is
+/// best-effort: failing to read or write the history file never interrupts
+/// a session.
+#[derive(Debug, Default)]
+pub struct SearchHistory {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    /// Index into `entries` while browsing (None = not browsing)
+    position: Option<usize>,
+    /// Query being typed before browsing started (restored past the newest entry)
+    draft: String,
+}
+
+impl SearchHistory {
+    /// Load history from the default location in the state directory
+    #[must_use]
+    pub fn load_default() -> Self {
+        match state_dir() {
+            Some(dir) => Self::load(dir.join("search_history")),
+            None => Self::default(),
+        }
+    }
+
+    /// Load history from a file (missing or unreadable files give an empty history)
+    #[must_use]
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = fs::read_to_string(&path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            path: Some(path),
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Past queries, oldest first
+    #[must_use]
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record a submitted query and persist the history
+    ///
+    /// Empty queries are ignored; repeating a query moves it to the newest slot.
+    pub fn record(&mut self, query: &str) {
+        self.reset_navigation();
+
+        let query = query.trim();
+        if query.is_empty() || query.contains('\n') {
+            return;
+        }
+
+        self.entries.retain(|e| e != query);
+        self.entries.push(query.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+
+        if let Some(path) = &self.path {
+            let _ = Self::save(path, &self.entries);
+        }
+    }
+
+    /// Step back to an older query
+    ///
+    /// `current` is the text in the search bar, remembered so that stepping
+    /// forward past the newest entry restores it. Returns None when there is
+    /// nothing older.
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let pos = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(p) => p - 1,
+        };
+        self.position = Some(pos);
+        Some(self.entries[pos].clone())
+    }
+
+    /// Step forward to a newer query (or back to the draft past the newest)
+    pub fn newer(&mut self) -> Option<String> {
+        let pos = self.position?;
+        if pos + 1 < self.entries.len() {
+            self.position = Some(pos + 1);
+            Some(self.entries[pos + 1].clone())
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    /// Stop browsing (called when the search bar opens or closes)
+    pub fn reset_navigation(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    fn save(path: &Path, entries: &[String]) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut content = entries.join("\n");
+        content.push('\n');
+        fs::write(path, content)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_dedupes_and_ignores_empty() {
+        let mut history = SearchHistory::default();
+        history.record("alice");
+        history.record("bob");
+        history.record("   ");
+        history.record("alice");
+
+        assert_eq!(history.entries(), ["bob", "alice"]);
+    }
+
+    #[test]
+    fn test_record_caps_entries() {
+        let mut history = SearchHistory::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.record(&format!("query {i}"));
+        }
+
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert_eq!(history.entries()[0], "query 5");
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut history = SearchHistory::default();
+        history.record("first");
+        history.record("second");
+
+        assert_eq!(history.older("typed").as_deref(), Some("second"));
+        assert_eq!(history.older("second").as_deref(), Some("first"));
+        assert_eq!(history.older("first"), None);
+
+        assert_eq!(history.newer().as_deref(), Some("second"));
+        // Past the newest entry the draft comes back
+        assert_eq!(history.newer().as_deref(), Some("typed"));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn test_navigation_empty_history() {
+        let mut history = SearchHistory::default();
+        assert_eq!(history.older("x"), None);
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn test_persistence_roundtrip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("nested").join("search_history");
+
+        let mut history = SearchHistory::load(&path);
+        assert_eq!(history.entries(), Vec::<String>::new());
+        history.record("alice@example.com");
+        history.record("fix typo");
+
+        let reloaded = SearchHistory::load(&path);
+        assert_eq!(reloaded.entries(), ["alice@example.com", "fix typo"]);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::error::{HistError, Result};
+use crate::git::commit::{CommitData, CommitId};
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, HashSet};
+
+/// A search (`/`) query: structured filters plus free text
+///
+/// Words like `author:alice`, `email:@corp.com`, `before:2023-01-01`,
+/// `after:2022-06-01` and `path:src/main.rs` (`path!:` to follow renames)
+/// are filters; the remaining words are the free text, a
+/// case-insensitive substring or, with a `re:` prefix, a regular expression.
+/// A commit matches when it passes every filter and the text.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    text: Option<TextMatch>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone)]
+enum TextMatch {
+    /// Lowercased substring
+    Substring(String),
+    /// Case-insensitive regex; `^`/`$` match at each line of the message
+    Regex(Regex),
+}
+
+/// A `path:` filter's path, and whether its renames are followed (`path!:`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TouchedPath {
+    pub path: String,
+    pub follow_renames: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    /// Author name or email contains this (lowercased)
+    Author(String),
+    /// Author email contains this (lowercased)
+    Email(String),
+    /// Authored before the start of this day
+    Before(NaiveDate),
+    /// Authored on this day or later
+    After(NaiveDate),
+    /// Changes this file, or something below this directory
+    Path(TouchedPath),
+}
+
+impl SearchQuery {
+    /// Parse a query as typed into the search bar
+    ///
+    /// # Errors
+    /// Returns an error if a `re:` pattern is not a valid regex, or a date
+    /// filter is not `YYYY-MM-DD`.
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut filters = Vec::new();
+        let mut words = Vec::new();
+        for word in query.split_whitespace() {
+            match word.split_once(':') {
+                Some(("author", value)) if !value.is_empty() => {
+                    filters.push(Filter::Author(value.to_lowercase()));
+                }
+                Some(("email", value)) if !value.is_empty() => {
+                    filters.push(Filter::Email(value.to_lowercase()));
+                }
+                Some((key @ ("path" | "path!"), value)) if !value.is_empty() => {
+                    filters.push(Filter::Path(TouchedPath {
+                        path: value.trim_end_matches('/').to_string(),
+                        follow_renames: key == "path!",
+                    }));
+                }
+                Some((key @ ("before" | "after"), value)) => {
+                    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                        HistError::InvalidSearch(format!("bad date in {word} (use YYYY-MM-DD)"))
+                    })?;
+                    filters.push(if key == "before" {
+                        Filter::Before(date)
+                    } else {
+                        Filter::After(date)
+                    });
+                }
+                _ => words.push(word),
+            }
+        }
+
+        // A query that's only free text is kept as typed
+        let text = if filters.is_empty() {
+            query.to_string()
+        } else {
+            words.join(" ")
+        };
+        let text = match text.strip_prefix("re:") {
+            Some(pattern) => Some(TextMatch::Regex(build_regex(pattern)?)),
+            None if text.is_empty() => None,
+            None => Some(TextMatch::Substring(text.to_lowercase())),
+        };
+        Ok(Self { text, filters })
+    }
+
+    /// The paths of the `path:` filters, whose touching commits `matches`
+    /// needs
+    pub fn paths(&self) -> impl Iterator<Item = &TouchedPath> {
+        self.filters.iter().filter_map(|filter| match filter {
+            Filter::Path(path) => Some(path),
+            _ => None,
+        })
+    }
+
+    /// Whether a commit passes the filters, and its author, message (subject
+    /// and body) or hash matches the text
+    ///
+    /// `touched` maps each of the query's `paths` to the commits that touch it.
+    #[must_use]
+    pub fn matches(
+        &self,
+        commit: &CommitData,
+        touched: &HashMap<TouchedPath, HashSet<CommitId>>,
+    ) -> bool {
+        self.filters
+            .iter()
+            .all(|filter| filter.matches(commit, touched))
+            && self.text.as_ref().is_none_or(|text| {
+                let fields = [
+                    &commit.author.name,
+                    &commit.author.email,
+                    &commit.message,
+                    &commit.short_hash,
+                ];
+                match text {
+                    TextMatch::Substring(query) => {
+                        fields.iter().any(|f| f.to_lowercase().contains(query))
+                    }
+                    TextMatch::Regex(regex) => fields.iter().any(|f| regex.is_match(f)),
+                }
+            })
+    }
+}
+
+impl Filter {
+    fn matches(
+        &self,
+        commit: &CommitData,
+        touched: &HashMap<TouchedPath, HashSet<CommitId>>,
+    ) -> bool {
+        let day = commit.author_date.date_naive();
+        match self {
+            Self::Author(value) => {
+                commit.author.name.to_lowercase().contains(value)
+                    || commit.author.email.to_lowercase().contains(value)
+            }
+            Self::Email(value) => commit.author.email.to_lowercase().contains(value),
+            Self::Before(date) => day < *date,
+            Self::After(date) => day >= *date,
+            Self::Path(path) => touched
+                .get(path)
+                .is_some_and(|ids| ids.contains(&commit.id)),
+        }
+    }
+}
+
+fn build_regex(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .multi_line(true)
+        .build()
+        .map_err(|e| {
+            // The last line says what's wrong; the rest repeats the pattern
+            let msg = e.to_string();
+            let reason = msg.lines().last().unwrap_or(&msg).trim();
+            let reason = reason.strip_prefix("error: ").unwrap_or(reason);
+            HistError::InvalidSearch(format!("bad regex ({reason})"))
+        })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::{CommitId, Person};
+    use chrono::{FixedOffset, TimeZone};
+
+    fn commit(name: &str, email: &str, date: (i32, u32, u32), message: &str) -> CommitData {
+        let dt = FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(date.0, date.1, date.2, 0, 30, 0)
+            .unwrap();
+        CommitData {
+            id: CommitId(git2::Oid::zero()),
+            short_hash: "abc1234".to_string(),
+            author: Person::new(name, email),
+            author_date: dt,
+            committer: Person::new(name, email),
+            committer_date: dt,
+            message: message.to_string(),
+            summary: message.lines().next().unwrap_or("").to_string(),
+            parent_ids: vec![],
+            tree_id: git2::Oid::zero(),
+            is_merge: false,
+        }
+    }
+
+    #[test]
+    fn test_structured_filters() {
+        let alice = commit("Alice", "alice@corp.com", (2022, 6, 1), "Fix login");
+        let bob = commit("Bob", "bob@home.net", (2023, 1, 1), "Fix typo");
+        let matches = |query: &str| {
+            let query = SearchQuery::parse(query).unwrap();
+            let touched = HashMap::new();
+            (
+                query.matches(&alice, &touched),
+                query.matches(&bob, &touched),
+            )
+        };
+
+        assert_eq!(matches("author:ALICE"), (true, false));
+        assert_eq!(matches("author:home.net"), (false, true));
+        assert_eq!(matches("email:@corp.com"), (true, false));
+        // Days in the commit's own timezone; after: includes the day
+        assert_eq!(matches("after:2022-06-01"), (true, true));
+        assert_eq!(matches("before:2023-01-01"), (true, false));
+        assert_eq!(matches("after:2022-06-02 before:2023-01-02"), (false, true));
+        // Combined with free text, in any order
+        assert_eq!(matches("fix email:@corp.com"), (true, false));
+        assert_eq!(matches("author:bob fix login"), (false, false));
+        assert_eq!(matches("author:bob re:^fix t"), (false, true));
+        // Unknown keys are plain text
+        assert_eq!(matches("topic:auth"), (false, false));
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let mut bob = commit("Bob", "bob@home.net", (2023, 1, 1), "Add docs");
+        bob.id = CommitId(git2::Oid::from_str("b0b").unwrap());
+        let alice = commit("Alice", "alice@corp.com", (2022, 6, 1), "Add docs");
+
+        let query = SearchQuery::parse("path:docs/ add").unwrap();
+        let docs = TouchedPath {
+            path: "docs".to_string(),
+            follow_renames: false,
+        };
+        assert_eq!(query.paths().collect::<Vec<_>>(), [&docs]);
+        // Nothing matches until the touching commits are known
+        assert!(!query.matches(&bob, &HashMap::new()));
+
+        let touched = HashMap::from([(docs, HashSet::from([bob.id]))]);
+        assert!(query.matches(&bob, &touched));
+        assert!(!query.matches(&alice, &touched));
+
+        // `path!:` follows renames, so it needs its own touching commits
+        let query = SearchQuery::parse("path!:docs").unwrap();
+        let followed = query.paths().next().unwrap();
+        assert_eq!(followed.path, "docs");
+        assert!(followed.follow_renames);
+        assert!(!query.matches(&bob, &touched));
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        let err = SearchQuery::parse("re:(unclosed").unwrap_err().to_string();
+        assert_eq!(err, "Invalid search: bad regex (unclosed group)");
+        let err = SearchQuery::parse("before:2023-13-01")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Invalid search: bad date in before:2023-13-01 (use YYYY-MM-DD)"
+        );
+    }
+}
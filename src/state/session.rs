@@ -0,0 +1,200 @@
+use crate::error::{HistError, Result};
+use crate::state::app_state::UndoSnapshot;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+/// File in the git directory the session is saved to
+pub const SESSION_FILE: &str = "retcon-session.json";
+
+/// File in the git directory the crash recovery snapshot is written to
+pub const RECOVERY_FILE: &str = "retcon-recovery.json";
+
+/// Latest recovery snapshot not yet written, and where it goes; the panic
+/// hook writes it out
+static PENDING_RECOVERY: Mutex<Option<(PathBuf, String)>> = Mutex::new(None);
+
+/// Pending changes with their undo history, saved on quit to resume later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// Branch the changes were made on
+    pub branch: String,
+    /// When the session was saved
+    pub saved_at: DateTime<FixedOffset>,
+    /// The pending changes
+    pub current: UndoSnapshot,
+    #[serde(default)]
+    pub undo_stack: Vec<UndoSnapshot>,
+    #[serde(default)]
+    pub redo_stack: Vec<UndoSnapshot>,
+}
+
+impl Session {
+    /// Where a repository's session is saved
+    #[must_use]
+    pub fn path(git_dir: &Path) -> PathBuf {
+        git_dir.join(SESSION_FILE)
+    }
+
+    /// Where a repository's crash recovery snapshot is written
+    #[must_use]
+    pub fn recovery_path(git_dir: &Path) -> PathBuf {
+        git_dir.join(RECOVERY_FILE)
+    }
+
+    /// Read a saved session, if there is one
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but can't be read or isn't a
+    /// valid session.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| HistError::Session(format!("{}: {e}", path.display())))
+    }
+
+    /// Write the session, replacing any saved before
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// The session as JSON
+    ///
+    /// # Errors
+    /// Returns an error if it can't be serialized.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| HistError::Session(e.to_string()))
+    }
+
+    /// Delete a saved session (a missing file is fine)
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but can't be removed.
+    pub fn remove(path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keep a recovery snapshot to write at the next `flush_recovery`
+pub fn stage_recovery(path: PathBuf, json: String) {
+    *PENDING_RECOVERY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some((path, json));
+}
+
+/// Write the staged recovery snapshot, if there is one
+///
+/// Called periodically from the event loop and from the panic hook.
+///
+/// # Errors
+/// Returns an error if the file can't be written.
+pub fn flush_recovery() -> Result<()> {
+    let pending = PENDING_RECOVERY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    if let Some((path, json)) = pending {
+        fs::write(path, json)?;
+    }
+    Ok(())
+}
+
+/// Drop the staged recovery snapshot and delete the written one, once there
+/// is nothing left to recover
+///
+/// # Errors
+/// Returns an error if the file exists but can't be removed.
+pub fn discard_recovery(path: &Path) -> Result<()> {
+    PENDING_RECOVERY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    Session::remove(path)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::git::commit::{CommitId, CommitModifications};
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Session::path(dir.path());
+        assert_eq!(Session::load(&path).unwrap(), None);
+
+        let id = CommitId(git2::Oid::from_bytes(&[1; 20]).unwrap());
+        let snapshot = UndoSnapshot {
+            commit_order: vec![id],
+            modifications: HashMap::from([(
+                id,
+                CommitModifications {
+                    author_name: Some("New Name".to_string()),
+                    ..Default::default()
+                },
+            )]),
+            deleted: HashSet::new(),
+            squashes: HashMap::new(),
+            splits: HashMap::new(),
+            notes: HashMap::new(),
+            new_root: None,
+            description: "Edit author name".to_string(),
+        };
+        let session = Session {
+            branch: "main".to_string(),
+            saved_at: DateTime::parse_from_rfc3339("2024-01-15T14:30:00+01:00").unwrap(),
+            current: snapshot.clone(),
+            undo_stack: vec![snapshot],
+            redo_stack: Vec::new(),
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Some(session));
+
+        Session::remove(&path).unwrap();
+        assert!(!path.exists());
+        Session::remove(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recovery_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Session::recovery_path(dir.path());
+
+        // Staged snapshots only reach the disk when flushed
+        stage_recovery(path.clone(), "{\"first\": 1}".to_string());
+        stage_recovery(path.clone(), "{\"second\": 2}".to_string());
+        assert!(!path.exists());
+        flush_recovery().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"second\": 2}");
+
+        // Discarding drops the staged one and deletes the written one
+        stage_recovery(path.clone(), "{\"third\": 3}".to_string());
+        discard_recovery(&path).unwrap();
+        flush_recovery().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_invalid_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Session::path(dir.path());
+        fs::write(&path, "{}").unwrap();
+        let err = Session::load(&path).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid session: "));
+    }
+}
@@ -0,0 +1,520 @@
+//! Building blocks for testing history rewrites
+//!
+//! [`TestRepo`] builds small synthetic repositories (linear, with a merge,
+//! tagged or signed) and [`rewrite_violations`] checks what every rewrite
+//! must preserve. retcon's own tests use them; code embedding the library can
+//! use them to test its own transforms.
+
+use crate::error::Result;
+use crate::git::commit::CommitId;
+use crate::git::rewrite::{order_changed, RewritePlan};
+use crate::git::signing::Signer;
+use crate::git::Repository;
+use git2::{Oid, Signature, Time};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Signature carried by the commits of [`TestRepo::signed`] (and made by
+/// [`TestRepo::fake_signer`])
+pub const FAKE_SIGNATURE: &str =
+    "-----BEGIN PGP SIGNATURE-----\n\nretcon-test\n-----END PGP SIGNATURE-----\n";
+
+/// Commit time of the first commit; each later commit is a minute newer, so
+/// the same steps always build the same hashes
+const EPOCH: i64 = 1_700_000_000;
+
+/// A throwaway repository, deleted when dropped
+///
+/// Commits are written straight to the object database and the working tree
+/// is then checked out, so the repository is always clean.
+pub struct TestRepo {
+    dir: tempfile::TempDir,
+    repo: Repository,
+    /// Commits made so far, for the commit times
+    count: Cell<i64>,
+    /// Sign new commits with [`FAKE_SIGNATURE`]
+    sign: bool,
+}
+
+impl TestRepo {
+    /// An empty repository with `user.name` and `user.email` set
+    pub fn new() -> Result<Self> {
+        let dir = tempfile::tempdir()?;
+        let git = git2::Repository::init(dir.path())?;
+        let mut config = git.config()?;
+        config.set_str("user.name", "Test User")?;
+        config.set_str("user.email", "test@example.com")?;
+        let repo = Repository::open(dir.path())?;
+        Ok(Self {
+            dir,
+            repo,
+            count: Cell::new(0),
+            sign: false,
+        })
+    }
+
+    /// A linear history: commit `i` adds `{i}.txt` with message `messages[i]`
+    pub fn linear(messages: &[&str]) -> Result<Self> {
+        let repo = Self::new()?;
+        for (i, message) in messages.iter().enumerate() {
+            repo.commit(&format!("{i}.txt"), message)?;
+        }
+        Ok(repo)
+    }
+
+    /// `linear`, with every commit signed
+    pub fn signed(messages: &[&str]) -> Result<Self> {
+        let mut repo = Self::new()?;
+        repo.sign = true;
+        for (i, message) in messages.iter().enumerate() {
+            repo.commit(&format!("{i}.txt"), message)?;
+        }
+        Ok(repo)
+    }
+
+    /// `linear`, with tag `v{i}` on commit `i`: annotated for odd `i`,
+    /// lightweight for even
+    pub fn tagged(messages: &[&str]) -> Result<Self> {
+        let repo = Self::new()?;
+        for (i, message) in messages.iter().enumerate() {
+            let id = repo.commit(&format!("{i}.txt"), message)?;
+            repo.tag(&format!("v{i}"), id, i % 2 == 1)?;
+        }
+        Ok(repo)
+    }
+
+    /// "Base", then "Side work" on branch `side` next to "Main work", then
+    /// "Merge branch 'side'" on the checked-out branch
+    pub fn merged() -> Result<Self> {
+        let repo = Self::new()?;
+        let base = repo.commit("base.txt", "Base")?;
+        repo.branch("side", base)?;
+        repo.commit_on("side", "side.txt", "Side work")?;
+        repo.commit("main.txt", "Main work")?;
+        repo.merge("side", "Merge branch 'side'")?;
+        Ok(repo)
+    }
+
+    /// Path of the working tree
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The repository, opened the way retcon opens it
+    pub const fn repo(&self) -> &Repository {
+        &self.repo
+    }
+
+    /// The underlying git2 repository
+    pub fn git(&self) -> &git2::Repository {
+        self.repo.inner()
+    }
+
+    /// Name of the checked-out branch
+    pub fn branch_name(&self) -> Result<String> {
+        // HEAD names the branch even before its first commit
+        let head = self.repo.inner().find_reference("HEAD")?;
+        Ok(head
+            .symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .unwrap_or("HEAD")
+            .to_string())
+    }
+
+    /// Commit a new file (holding `message`) on the checked-out branch
+    pub fn commit(&self, file: &str, message: &str) -> Result<CommitId> {
+        let branch = self.branch_name()?;
+        let id = self.commit_on(&branch, file, message)?;
+        self.checkout()?;
+        Ok(id)
+    }
+
+    /// Commit a new file (holding `message`) on top of `branch`, creating the
+    /// branch if it has no commits yet; the working tree is left alone
+    pub fn commit_on(&self, branch: &str, file: &str, message: &str) -> Result<CommitId> {
+        let git = self.repo.inner();
+        let parent = git
+            .find_reference(&format!("refs/heads/{branch}"))
+            .ok()
+            .and_then(|r| r.peel_to_commit().ok());
+        let base_tree = parent.as_ref().map(git2::Commit::tree).transpose()?;
+        let mut builder = git.treebuilder(base_tree.as_ref())?;
+        builder.insert(file, git.blob(message.as_bytes())?, 0o100_644)?;
+        let tree = builder.write()?;
+        let parents: Vec<Oid> = parent.iter().map(git2::Commit::id).collect();
+        let oid = self.write_commit(message, tree, &parents)?;
+        self.repo
+            .inner()
+            .reference(&format!("refs/heads/{branch}"), oid, true, "test: commit")?;
+        Ok(CommitId(oid))
+    }
+
+    /// Merge `branch` into the checked-out branch
+    pub fn merge(&self, branch: &str, message: &str) -> Result<CommitId> {
+        let git = self.repo.inner();
+        let ours = git.head()?.peel_to_commit()?;
+        let theirs = git
+            .find_reference(&format!("refs/heads/{branch}"))?
+            .peel_to_commit()?;
+        let tree = git
+            .merge_commits(&ours, &theirs, None)?
+            .write_tree_to(git)?;
+        let parents = [ours.id(), theirs.id()];
+        let oid = self.write_commit(message, tree, &parents)?;
+        let head = self.branch_name()?;
+        self.repo
+            .inner()
+            .reference(&format!("refs/heads/{head}"), oid, true, "test: merge")?;
+        self.checkout()?;
+        Ok(CommitId(oid))
+    }
+
+    /// Create a branch at `at`
+    pub fn branch(&self, name: &str, at: CommitId) -> Result<()> {
+        let git = self.repo.inner();
+        git.branch(name, &git.find_commit(at.0)?, false)?;
+        Ok(())
+    }
+
+    /// Tag `at`, with an annotated tag or a lightweight one
+    pub fn tag(&self, name: &str, at: CommitId, annotated: bool) -> Result<()> {
+        let git = self.repo.inner();
+        let target = git.find_object(at.0, Some(git2::ObjectType::Commit))?;
+        if annotated {
+            git.tag(
+                name,
+                &target,
+                &self.signature()?,
+                &format!("Release {name}"),
+                false,
+            )?;
+        } else {
+            git.tag_lightweight(name, &target, false)?;
+        }
+        Ok(())
+    }
+
+    /// Files in the tip of `branch`, sorted
+    pub fn files(&self, branch: &str) -> Result<Vec<String>> {
+        let tree = self
+            .repo
+            .inner()
+            .find_reference(&format!("refs/heads/{branch}"))?
+            .peel_to_tree()?;
+        let mut files: Vec<String> = tree
+            .iter()
+            .filter_map(|entry| entry.name().map(str::to_string))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// A signer that runs a script stamping [`FAKE_SIGNATURE`], for testing
+    /// signing without a real key
+    #[cfg(unix)]
+    pub fn fake_signer(&self) -> Result<Signer> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = self.dir.path().join(".git").join("fake-gpg");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\ncat >/dev/null\nprintf '%s' '{FAKE_SIGNATURE}'\n"),
+        )?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+        Ok(Signer {
+            format: crate::git::signing::SignFormat::OpenPgp,
+            key: Some("TEST".to_string()),
+            program: script.display().to_string(),
+        })
+    }
+
+    fn signature(&self) -> Result<Signature<'static>> {
+        let time = Time::new(EPOCH + 60 * self.count.get(), 0);
+        Ok(Signature::new("Test User", "test@example.com", &time)?)
+    }
+
+    fn write_commit(&self, message: &str, tree: Oid, parents: &[Oid]) -> Result<Oid> {
+        let git = self.repo.inner();
+        let sig = self.signature()?;
+        let tree = git.find_tree(tree)?;
+        let parents = parents
+            .iter()
+            .map(|oid| git.find_commit(*oid))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+        let oid = if self.sign {
+            let buffer = git.commit_create_buffer(&sig, &sig, message, &tree, &parent_refs)?;
+            let content = String::from_utf8_lossy(&buffer).into_owned();
+            git.commit_signed(&content, FAKE_SIGNATURE, None)?
+        } else {
+            git.commit(None, &sig, &sig, message, &tree, &parent_refs)?
+        };
+        self.count.set(self.count.get() + 1);
+        Ok(oid)
+    }
+
+    fn checkout(&self) -> Result<()> {
+        self.repo
+            .inner()
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+        Ok(())
+    }
+}
+
+/// Check what `rewrite_history(repo, plan)` must have preserved, given the
+/// commit map it returned; returns one line per broken invariant
+///
+/// - the branch points at the rewritten tip (or the last replayed descendant)
+///   and moved dependent branches at the new counterparts of their tips
+/// - every kept commit keeps its metadata, except for pending modifications
+///   (and hash references, with `update_hash_references`)
+/// - without reordering, deletions, squashes or splits, every commit keeps its tree
+/// - without deletions, the rewritten tip has the original tip's tree
+/// - deleted commits are no longer part of the branch
+/// - for a linear segment, the branch's first parents list the kept commits in
+///   `new_order`, down to the original base (or the new root commit, which
+///   has no parents)
+pub fn rewrite_violations(
+    repo: &git2::Repository,
+    plan: &RewritePlan<'_>,
+    commit_map: &HashMap<CommitId, CommitId>,
+) -> Vec<String> {
+    let mut violations = Vec::new();
+    let mut check = |ok: bool, what: String| {
+        if !ok {
+            violations.push(what);
+        }
+    };
+    let find = |id: CommitId| repo.find_commit(id.0).ok();
+
+    let kept: Vec<CommitId> = plan
+        .new_order
+        .iter()
+        .filter(|id| !plan.deleted.contains(id))
+        .copied()
+        .collect();
+    let Some(segment_tip) = kept.first().and_then(|id| commit_map.get(id)).copied() else {
+        return vec!["the rewritten tip is missing from the commit map".to_string()];
+    };
+    let tip = plan
+        .descendants
+        .first()
+        .and_then(|c| commit_map.get(&c.id))
+        .copied()
+        .unwrap_or(segment_tip);
+    let branch_tip = repo
+        .find_reference(&format!("refs/heads/{}", plan.branch_name))
+        .ok()
+        .and_then(|r| r.target());
+    check(
+        branch_tip == Some(tip.0),
+        format!("{} is at {branch_tip:?}, not {tip}", plan.branch_name),
+    );
+    for branch in plan.branches {
+        let Some(new) = commit_map.get(&branch.tip) else {
+            continue;
+        };
+        let at = repo
+            .find_reference(&format!("refs/heads/{}", branch.name))
+            .ok()
+            .and_then(|r| r.target());
+        check(
+            at == Some(new.0),
+            format!("branch {} is at {at:?}, not {new}", branch.name),
+        );
+    }
+
+    let reshaped = order_changed(plan.original_order, plan.new_order)
+        || !plan.deleted.is_empty()
+        || !plan.squashes.is_empty()
+        || !plan.splits.is_empty();
+    for original in plan.commits {
+        let id = original.id;
+        if plan.deleted.contains(&id) || plan.squashes.contains_key(&id) {
+            continue;
+        }
+        let Some(new) = commit_map.get(&id).and_then(|new| find(*new)) else {
+            check(false, format!("{id} has no rewritten commit"));
+            continue;
+        };
+        let mods = plan.modifications.get(&id);
+        let author_name = mods
+            .and_then(|m| m.author_name.as_deref())
+            .unwrap_or(&original.author.name);
+        let author_email = mods
+            .and_then(|m| m.author_email.as_deref())
+            .unwrap_or(&original.author.email);
+        let author_date = mods
+            .and_then(|m| m.author_date)
+            .unwrap_or(original.author_date);
+        let committer_name = mods
+            .and_then(|m| m.committer_name.as_deref())
+            .unwrap_or(&original.committer.name);
+        let committer_date = mods
+            .and_then(|m| m.committer_date)
+            .unwrap_or(original.committer_date);
+        check(
+            new.author().name() == Some(author_name)
+                && new.author().email() == Some(author_email)
+                && new.author().when().seconds() == author_date.timestamp(),
+            format!("{id}: author not kept"),
+        );
+        check(
+            new.committer().name() == Some(committer_name)
+                && new.committer().when().seconds() == committer_date.timestamp(),
+            format!("{id}: committer not kept"),
+        );
+        if !plan.update_hash_references && !plan.splits.contains_key(&id) {
+            let message = mods
+                .and_then(|m| m.message.as_deref())
+                .unwrap_or(&original.message);
+            check(
+                new.message() == Some(message),
+                format!("{id}: message not kept"),
+            );
+        }
+        if !reshaped {
+            check(
+                new.tree_id() == original.tree_id,
+                format!("{id}: tree changed"),
+            );
+        }
+    }
+
+    if plan.deleted.is_empty() {
+        let original_tree = plan
+            .original_order
+            .first()
+            .and_then(|id| find(*id))
+            .map(|c| c.tree_id());
+        let new_tree = find(segment_tip).map(|c| c.tree_id());
+        check(
+            original_tree == new_tree,
+            "the rewritten tip's tree differs from the original tip's".to_string(),
+        );
+    }
+    for deleted in plan.deleted {
+        let reachable =
+            deleted.0 == tip.0 || repo.graph_descendant_of(tip.0, deleted.0).unwrap_or(false);
+        check(
+            !reachable,
+            format!("deleted {deleted} is still on the branch"),
+        );
+    }
+
+    if plan.commits.iter().all(|c| !c.is_merge) {
+        // Consecutive kept commits map to the same new commit when squashed
+        let mut expected: Vec<(CommitId, bool)> = Vec::new();
+        for id in &kept {
+            let Some(new) = commit_map.get(id) else {
+                continue;
+            };
+            if expected.last().map(|(last, _)| last) != Some(new) {
+                expected.push((*new, plan.splits.contains_key(id)));
+            }
+        }
+        let mut walk = Some(segment_tip.0);
+        for (new, split) in expected {
+            check(
+                walk == Some(new.0),
+                format!("expected {new} in the new history, found {walk:?}"),
+            );
+            walk = walk.and_then(|oid| first_parent(repo, oid));
+            if split {
+                walk = walk.and_then(|oid| first_parent(repo, oid));
+            }
+        }
+        if let Some(root) = plan.new_root {
+            let new_root = walk.and_then(|oid| repo.find_commit(oid).ok());
+            check(
+                new_root.is_some_and(|c| {
+                    c.parent_count() == 0 && c.message() == Some(root.message.as_str())
+                }),
+                format!("expected the new root commit, found {walk:?}"),
+            );
+            walk = None;
+        }
+        let base = plan
+            .original_order
+            .last()
+            .and_then(|id| plan.commits.iter().find(|c| c.id == *id))
+            .and_then(|c| c.parent_ids.first())
+            .map(|p| p.0);
+        check(
+            walk == base,
+            format!("the new history ends on {walk:?}, not the original base {base:?}"),
+        );
+    }
+    violations
+}
+
+/// Whether each commit the rewrite created carries a signature, for checking
+/// that signed commits lose (or, with a signer, regain) their signatures
+pub fn signed_commits(
+    repo: &git2::Repository,
+    commit_map: &HashMap<CommitId, CommitId>,
+) -> HashMap<CommitId, bool> {
+    commit_map
+        .values()
+        .map(|new| (*new, repo.extract_signature(&new.0, None).is_ok()))
+        .collect()
+}
+
+fn first_parent(repo: &git2::Repository, oid: Oid) -> Option<Oid> {
+    repo.find_commit(oid).ok()?.parent_id(0).ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders() {
+        let linear = TestRepo::linear(&["one", "two", "three"]).unwrap();
+        let commits = linear.repo().load_commits(10).unwrap();
+        let summaries: Vec<&str> = commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, ["three", "two", "one"]);
+        let branch = linear.branch_name().unwrap();
+        assert_eq!(linear.files(&branch).unwrap(), ["0.txt", "1.txt", "2.txt"]);
+        assert_eq!(
+            linear.git().statuses(None).unwrap().len(),
+            0,
+            "working tree is clean"
+        );
+        // Same steps, same hashes
+        let again = TestRepo::linear(&["one", "two", "three"]).unwrap();
+        assert_eq!(again.repo().load_commits(10).unwrap()[0].id, commits[0].id);
+
+        let merged = TestRepo::merged().unwrap();
+        let commits = merged.repo().load_commits(10).unwrap();
+        assert_eq!(commits.len(), 4);
+        assert!(commits[0].is_merge);
+        let branch = merged.branch_name().unwrap();
+        assert_eq!(
+            merged.files(&branch).unwrap(),
+            ["base.txt", "main.txt", "side.txt"]
+        );
+
+        let tagged = TestRepo::tagged(&["one", "two"]).unwrap();
+        let all = tagged
+            .repo()
+            .load_commits(10)
+            .unwrap()
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        let tags = tagged.repo().tags_on(&all).unwrap();
+        let kinds: Vec<(&str, bool)> = tags
+            .iter()
+            .map(|t| (t.name.as_str(), t.annotated))
+            .collect();
+        assert_eq!(kinds, [("v0", false), ("v1", true)]);
+
+        let signed = TestRepo::signed(&["one"]).unwrap();
+        let head = signed.git().head().unwrap().target().unwrap();
+        let (signature, _) = signed.git().extract_signature(&head, None).unwrap();
+        assert_eq!(signature.as_str(), Some(FAKE_SIGNATURE));
+    }
+}
@@ -63,7 +63,6 @@ pub struct Theme {
     // Search
     pub search_prompt: Style,
     pub search_input: Style,
-    #[allow(dead_code)]
     pub search_match: Style,
 
     // Selection checkbox
@@ -72,6 +71,9 @@ pub struct Theme {
 
     // Deletion marker
     pub deleted: Style,
+
+    // Highlight groups (cycled by group index)
+    pub highlight_groups: [Style; 6],
 }
 
 impl Default for Theme {
@@ -163,6 +165,16 @@ impl Default for Theme {
             deleted: Style::default()
                 .fg(Color::Red)
                 .add_modifier(Modifier::BOLD | Modifier::CROSSED_OUT),
+
+            // Highlight groups - background colors so field colors stay readable
+            highlight_groups: [
+                Style::default().bg(Color::Green).fg(Color::Black),
+                Style::default().bg(Color::Magenta).fg(Color::Black),
+                Style::default().bg(Color::Cyan).fg(Color::Black),
+                Style::default().bg(Color::Yellow).fg(Color::Black),
+                Style::default().bg(Color::Blue).fg(Color::White),
+                Style::default().bg(Color::Red).fg(Color::White),
+            ],
         }
     }
 }
@@ -0,0 +1,92 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::git::report::ApplyGuidance;
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// Render the screen shown after a successful rewrite
+pub fn render_apply_guidance(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    guidance: &ApplyGuidance,
+    theme: &Theme,
+) {
+    let command = |text: String| Line::from(Span::styled(format!("  {text}"), theme.keybinding));
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw(format!("Rewrote {}: ", guidance.branch_name)),
+            Span::styled(guidance.old_tip.to_string(), theme.hash),
+            Span::raw(" → "),
+            Span::styled(guidance.new_tip.to_string(), theme.hash),
+        ]),
+        Line::from(""),
+    ];
+    if !guidance.forced.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Safety checks skipped with --force:",
+            theme.warning,
+        )));
+        lines.extend(guidance.forced.iter().map(|bypass| {
+            Line::from(Span::styled(
+                format!("  {}", bypass.describe()),
+                theme.warning,
+            ))
+        }));
+        lines.push(Line::from(""));
+    }
+    lines.extend([
+        Line::from("The original history is kept at:"),
+        command(guidance.backup_ref()),
+        Line::from("To undo this rewrite:"),
+        command(guidance.undo_command()),
+        Line::from(""),
+    ]);
+    match guidance.push_command() {
+        Some(push) => lines.extend([
+            Line::from("Publish it (the remote branch is overwritten):"),
+            command(push),
+            Line::from(format!(
+                "Teammates who have the old history then run, on {}:",
+                guidance.branch_name
+            )),
+            command("git pull --rebase".to_string()),
+        ]),
+        None => lines.push(Line::from(Span::styled(
+            "The branch has no upstream; nothing needs to be pushed.",
+            theme.info,
+        ))),
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("y", theme.keybinding_key),
+            Span::raw(": copy push command  "),
+            Span::styled("Y", theme.keybinding_key),
+            Span::raw(": copy all  "),
+            Span::styled("Enter/Esc", theme.keybinding_key),
+            Span::raw(": close"),
+        ]),
+    ]);
+
+    let width = 72u16.min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(" History rewritten ").style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner_area);
+}
@@ -0,0 +1,65 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::git::worker::{ApplyPhase, RewriteWorker};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Render the progress of a rewrite running in the background
+pub fn render_apply_progress(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    worker: &RewriteWorker,
+    theme: &Theme,
+) {
+    let width = 60u16.min(area.width.saturating_sub(4));
+    let bar_width = width.saturating_sub(4) as usize;
+    let (done, total) = worker.progress().counts();
+    let filled = (bar_width * done.min(total))
+        .checked_div(total)
+        .unwrap_or(0);
+
+    let phase = worker.phase();
+    let mut lines = vec![
+        Line::from(phase.label()),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("█".repeat(filled), theme.keybinding),
+            Span::styled("░".repeat(bar_width - filled), theme.info),
+        ]),
+    ];
+    lines.push(if phase == ApplyPhase::Rewriting && total > 0 {
+        Line::from(Span::styled(
+            format!("{done} of {total} commit(s)"),
+            theme.info,
+        ))
+    } else {
+        Line::from("")
+    });
+    lines.push(Line::from(""));
+    lines.push(if worker.progress().is_cancelled() {
+        Line::from(Span::styled("Cancelling...", theme.warning))
+    } else {
+        Line::from(vec![
+            Span::styled("Esc", theme.keybinding_key),
+            Span::raw(": cancel (nothing is written until every commit is done)"),
+        ])
+    });
+
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(" Applying changes ").style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
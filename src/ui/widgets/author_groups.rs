@@ -0,0 +1,79 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::AuthorGroups;
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Render each author with their commit count, and the new author being typed
+pub fn render_author_groups(frame: &mut Frame<'_>, area: Rect, view: &AuthorGroups, theme: &Theme) {
+    let count_width = view
+        .groups
+        .first()
+        .map_or(1, |(_, ids)| ids.len().to_string().len());
+    let mut lines: Vec<Line<'_>> = view
+        .groups
+        .iter()
+        .enumerate()
+        .map(|(idx, (person, ids))| {
+            let line = Line::from(vec![
+                Span::styled(format!("{:>count_width$}  ", ids.len()), theme.info),
+                Span::styled(person.name.clone(), theme.author),
+                Span::raw(format!(" <{}>", person.email)),
+            ]);
+            if idx == view.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            }
+        })
+        .collect();
+    if let Some(input) = &view.input {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("New author: ", theme.info),
+            Span::raw(input.clone()),
+            Span::styled("_", theme.info),
+        ]));
+    }
+
+    let width = lines
+        .iter()
+        .map(|line| line.width() as u16 + 4)
+        .max()
+        .unwrap_or(0)
+        .max(50)
+        .min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted author (or the input) in view
+    let visible_height = height.saturating_sub(2) as usize;
+    let bottom = if view.input.is_some() {
+        lines.len()
+    } else {
+        view.cursor + 1
+    };
+    let scroll = bottom.saturating_sub(visible_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let total: usize = view.groups.iter().map(|(_, ids)| ids.len()).sum();
+    let title = format!(" {} author(s) of {total} commit(s) ", view.groups.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        popup_area,
+    );
+}
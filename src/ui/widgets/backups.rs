@@ -0,0 +1,104 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::{AppState, BackupList};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Lines below the backup list: blank, two key hint lines
+const FOOTER_LINES: usize = 3;
+
+/// Render the backup refs of every branch
+pub fn render_backups(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    list: &BackupList,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let rows = list.rows.len().max(1);
+    let width = 96u16.min(area.width.saturating_sub(4));
+    let height = ((rows + FOOTER_LINES) as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted backup in view above the footer
+    let list_height = (height.saturating_sub(2) as usize).saturating_sub(FOOTER_LINES);
+    let scroll = (list.cursor + 1).saturating_sub(list_height.max(1));
+
+    let names: Vec<String> = list
+        .rows
+        .iter()
+        .map(|backup| match backup.number {
+            Some(n) => format!("{} #{n}", backup.branch),
+            None => format!("{} (original)", backup.branch),
+        })
+        .collect();
+    let name_width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+    let mut lines: Vec<Line<'_>> = list
+        .rows
+        .iter()
+        .zip(&names)
+        .enumerate()
+        .skip(scroll)
+        .take(list_height.max(1))
+        .map(|(idx, (backup, name))| {
+            let created = backup.created.map_or_else(
+                || "date unknown    ".to_string(),
+                |date| date.format("%Y-%m-%d %H:%M").to_string(),
+            );
+            let name_style = if backup.branch == state.branch_name {
+                theme.keybinding
+            } else {
+                Style::default()
+            };
+            let line = Line::from(vec![
+                Span::styled(format!("{name:<name_width$}  "), name_style),
+                Span::styled(format!("{created}  "), theme.info),
+                Span::styled(format!("{}  ", backup.tip), theme.hash),
+                Span::raw(backup.summary.clone()),
+            ]);
+            if idx == list.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            }
+        })
+        .collect();
+    if list.rows.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No backups yet: applying a rewrite makes one, n makes one now",
+            theme.info,
+        )));
+    }
+
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Enter", theme.keybinding_key),
+            Span::raw(": point the branch at this backup  "),
+            Span::styled("n", theme.keybinding_key),
+            Span::raw(": back up the branch tip now"),
+        ]),
+        Line::from(vec![
+            Span::styled("d", theme.keybinding_key),
+            Span::raw(": delete  "),
+            Span::styled("Esc", theme.keybinding_key),
+            Span::raw(": close"),
+        ]),
+    ]);
+
+    frame.render_widget(Clear, popup_area);
+
+    let title = format!(" Backups: {} ref(s) ", list.rows.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
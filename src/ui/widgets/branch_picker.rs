@@ -0,0 +1,71 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::{AppState, BranchPicker};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Render the list of local branches to edit
+pub fn render_branch_picker(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    picker: &BranchPicker,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let lines: Vec<Line<'_>> = picker
+        .branches
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let marker = if *name == state.branch_name {
+                "* "
+            } else {
+                "  "
+            };
+            let line = Line::from(vec![
+                Span::styled(marker, theme.info),
+                Span::raw(name.clone()),
+            ]);
+            if idx == picker.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let width = picker
+        .branches
+        .iter()
+        .map(|name| name.len() as u16 + 6)
+        .max()
+        .unwrap_or(0)
+        .max(30)
+        .min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted branch in view
+    let visible_height = height.saturating_sub(2) as usize;
+    let scroll = (picker.cursor + 1).saturating_sub(visible_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(" Edit branch ").style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        popup_area,
+    );
+}
@@ -0,0 +1,33 @@
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+/// Render the `:` command line in place of the status bar
+pub fn render_command_bar(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    input: &str,
+    cursor_pos: usize,
+    theme: &Theme,
+) {
+    let cursor_style = theme.search_input.add_modifier(Modifier::REVERSED);
+    let cursor_pos = cursor_pos.min(input.len());
+
+    let mut spans = vec![Span::styled(":", theme.search_prompt)];
+
+    let (before, at_and_after) = input.split_at(cursor_pos);
+    spans.push(Span::styled(before.to_string(), theme.search_input));
+
+    let mut rest = at_and_after.chars();
+    if let Some(c) = rest.next() {
+        spans.push(Span::styled(c.to_string(), cursor_style));
+        spans.push(Span::styled(rest.as_str().to_string(), theme.search_input));
+    } else {
+        spans.push(Span::styled(" ", cursor_style));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
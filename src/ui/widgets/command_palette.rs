@@ -0,0 +1,65 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::palette::CommandPalette;
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+/// Render the command palette: the query, then the matching actions with
+/// their keys
+pub fn render_command_palette(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    palette: &CommandPalette,
+    theme: &Theme,
+) {
+    let width = 64u16.min(area.width.saturating_sub(4));
+    let height = 20u16.min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 4;
+    let popup_area = Rect::new(x, y, width, height);
+    let inner_width = width.saturating_sub(2) as usize;
+
+    let matches = palette.matches();
+    // Keep the highlighted entry in view below the query line
+    let list_height = height.saturating_sub(4) as usize;
+    let scroll = (palette.cursor + 1).saturating_sub(list_height);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("> ", theme.keybinding_key),
+            Span::raw(palette.query.clone()),
+            Span::styled("█", theme.info),
+        ]),
+        Line::from(""),
+    ];
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled("No matching action", theme.info)));
+    }
+    for (idx, entry) in matches.iter().enumerate().skip(scroll).take(list_height) {
+        let gap = inner_width.saturating_sub(entry.name.width() + entry.keys.width() + 2);
+        let line = Line::from(vec![
+            Span::raw(format!(" {}", entry.name)),
+            Span::raw(" ".repeat(gap)),
+            Span::styled(format!("{} ", entry.keys), theme.keybinding_key),
+        ]);
+        lines.push(if idx == palette.cursor {
+            line.style(theme.cell_cursor)
+        } else {
+            line
+        });
+    }
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(" Command palette ").style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
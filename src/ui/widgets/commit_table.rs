@@ -1,9 +1,10 @@
 #![allow(clippy::cast_possible_truncation)]
 
-use crate::git::commit::{CommitData, CommitModifications, EditableField};
-use crate::state::{AppMode, AppState, VisualType};
+use crate::git::commit::{CommitData, CommitModifications, EditableField, SquashKind};
+use crate::state::{AppMode, AppState, SortKey, VisualType};
 use crate::ui::theme::Theme;
-use ratatui::layout::{Constraint, Rect};
+use chrono::TimeDelta;
+use ratatui::layout::{Alignment, Constraint, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
@@ -69,8 +70,9 @@ const COLUMNS: &[ColumnDef] = &[
     },
     ColumnDef {
         header: "Hash",
-        min_width: 7,
-        max_width: 7,
+        // Room for the modification badges after the short hash
+        min_width: 12,
+        max_width: 12,
         weight: 0,
     },
     ColumnDef {
@@ -101,6 +103,21 @@ const COLUMNS: &[ColumnDef] = &[
 
 const MESSAGE_MAX_WIDTH: usize = 50;
 
+/// Message prefix for `fixup!`/`squash!` commits that aren't staged yet
+const FIXUP_MARKER: &str = "↓ ";
+
+/// Message prefix for commits that a `fixup!`/`squash!` commit refers to
+const TARGET_MARKER: &str = "◆ ";
+
+/// Message prefix for messages that aren't Conventional Commits (`:set lint`)
+const LINT_MARKER: &str = "⚠ ";
+
+/// Width of the time gap column (fits e.g. "-12d 23h")
+const GAP_WIDTH: u16 = 8;
+
+/// Where optional, display-only columns are inserted (after the hash)
+const OPTIONAL_COLUMN_POS: usize = Column::Hash as usize + 1;
+
 /// Context for rendering a single row
 struct RowContext<'a> {
     row_idx: usize,
@@ -108,10 +125,20 @@ struct RowContext<'a> {
     cursor_col: usize,
     is_selected: bool,
     is_deleted: bool,
+    /// Staged squash into the commit below, if any
+    squash: Option<SquashKind>,
+    /// Whether the cursor commit is a fixup!/squash! of this commit
+    is_fixup_target: bool,
+    /// Whether any fixup!/squash! commit refers to this commit
+    has_fixups: bool,
     is_editing: bool,
     visual_selection: Option<VisualSelection>,
     mods: Option<&'a CommitModifications>,
+    /// This commit's row of the graph column, when shown
+    graph: Option<&'a str>,
     edit_buffer: &'a str,
+    /// App state, for highlight group lookups
+    state: &'a AppState,
     theme: &'a Theme,
 }
 
@@ -157,7 +184,7 @@ pub fn render_commit_table(frame: &mut Frame<'_>, area: Rect, state: &AppState,
     };
 
     // Build header
-    let header_cells: Vec<Cell<'_>> = COLUMNS
+    let mut header_cells: Vec<Cell<'_>> = COLUMNS
         .iter()
         .enumerate()
         .map(|(idx, col)| {
@@ -167,17 +194,72 @@ pub fn render_commit_table(frame: &mut Frame<'_>, area: Rect, state: &AppState,
             } else {
                 theme.table_header
             };
-            Cell::from(col.header).style(style)
+            let sorted = state.sort.filter(|sort| {
+                Column::from_index(idx)
+                    .and_then(|column| column.to_editable_field())
+                    .and_then(SortKey::for_field)
+                    == Some(sort.key)
+            });
+            match sorted {
+                Some(sort) => {
+                    let arrow = if sort.descending { " ▼" } else { " ▲" };
+                    Cell::from(format!("{}{arrow}", col.header)).style(style)
+                }
+                None => Cell::from(col.header).style(style),
+            }
         })
         .collect();
+    let visible_len = state.visible_len();
+
+    // Optional display-only columns go right after the hash
+    let mut widths = calculate_column_widths(area.width, state.h_scroll_offset);
+    let mut optional: Vec<(Cell<'_>, Constraint)> = Vec::new();
+    let graph = state.show_graph.then(|| state.graph_rows());
+    if let Some(graph) = graph {
+        let graph_width = graph.iter().map(String::len).max().unwrap_or(0);
+        optional.push((
+            Cell::from("Graph").style(theme.table_header),
+            Constraint::Length(graph_width.max(5) as u16),
+        ));
+    }
+    if state.show_head_offset {
+        let offset_width = state
+            .head_offset_label(visible_len.saturating_sub(1))
+            .map_or(0, |label| label.len());
+        optional.push((
+            Cell::from("HEAD~n").style(theme.table_header),
+            Constraint::Length(offset_width.max(6) as u16),
+        ));
+    }
+    if state.show_time_gap {
+        optional.push((
+            Cell::from("Gap").style(theme.table_header),
+            Constraint::Length(GAP_WIDTH),
+        ));
+    }
+    let (optional_headers, optional_widths): (Vec<_>, Vec<_>) = optional.into_iter().unzip();
+    header_cells.splice(OPTIONAL_COLUMN_POS..OPTIONAL_COLUMN_POS, optional_headers);
+    widths.splice(OPTIONAL_COLUMN_POS..OPTIONAL_COLUMN_POS, optional_widths);
+    // Line numbers lead the row, ahead of the checkbox
+    if state.shows_line_numbers() {
+        let number_width = visible_len.max(1).to_string().len().max(2);
+        header_cells.insert(0, Cell::from("#").style(theme.table_header));
+        widths.insert(0, Constraint::Length(number_width as u16));
+    }
     let header = Row::new(header_cells).height(1);
 
-    let visible = state.visible_commits();
-
-    // Build rows
-    let rows: Vec<Row<'_>> = visible
-        .iter()
-        .enumerate()
+    // The commit the cursor's fixup!/squash! commit refers to, if any
+    let fixup_target = state
+        .commit_index(state.cursor)
+        .and_then(|idx| state.fixup_target(idx))
+        .map(|(target, _)| state.commits[target].id);
+    let fixup_targets = state.fixup_targets();
+
+    // Build rows for the window on screen only, so long histories stay fast
+    // (the scroll offset was fitted to this height before rendering)
+    let height = area.height.saturating_sub(3) as usize;
+    let rows: Vec<Row<'_>> = (state.scroll_offset..visible_len.min(state.scroll_offset + height))
+        .filter_map(|idx| Some((idx, state.visible_commit(idx)?)))
         .map(|(idx, commit)| {
             let ctx = RowContext {
                 row_idx: idx,
@@ -185,6 +267,9 @@ pub fn render_commit_table(frame: &mut Frame<'_>, area: Rect, state: &AppState,
                 cursor_col: state.column_index,
                 is_selected: state.is_selected(commit.id),
                 is_deleted: state.is_deleted(commit.id),
+                squash: state.squashes.get(&commit.id).copied(),
+                is_fixup_target: fixup_target == Some(commit.id),
+                has_fixups: fixup_targets.contains(&commit.id),
                 is_editing: editing_row == Some(idx),
                 visual_selection: visual_selection.as_ref().map(|v| VisualSelection {
                     visual_type: v.visual_type,
@@ -194,15 +279,21 @@ pub fn render_commit_table(frame: &mut Frame<'_>, area: Rect, state: &AppState,
                     end_col: v.end_col,
                 }),
                 mods: state.modifications.get(&commit.id),
+                graph: graph.and_then(|graph| {
+                    state
+                        .commit_index(idx)
+                        .and_then(|i| graph.get(i))
+                        .map(String::as_str)
+                }),
                 edit_buffer: &state.edit_buffer,
+                state,
                 theme,
             };
             create_row(commit, &ctx)
         })
         .collect();
 
-    let widths = calculate_column_widths(area.width, state.h_scroll_offset);
-    let title = build_title(state, &visible);
+    let title = build_title(state, visible_len);
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -217,7 +308,7 @@ pub fn render_commit_table(frame: &mut Frame<'_>, area: Rect, state: &AppState,
     let table = Table::new(rows, widths).header(header).block(block);
 
     let mut table_state = TableState::default();
-    table_state.select(Some(state.cursor));
+    table_state.select(state.cursor.checked_sub(state.scroll_offset));
 
     frame.render_stateful_widget(table, area, &mut table_state);
 
@@ -237,7 +328,7 @@ pub fn render_commit_table(frame: &mut Frame<'_>, area: Rect, state: &AppState,
     }
 }
 
-fn build_title(state: &AppState, visible: &[&CommitData]) -> String {
+fn build_title(state: &AppState, visible_len: usize) -> String {
     let modified = state.modified_count();
     let deleted = state.deleted_count();
 
@@ -248,7 +339,7 @@ fn build_title(state: &AppState, visible: &[&CommitData]) -> String {
     } else if modified > 0 {
         format!(" Commits ({modified} modified) ")
     } else {
-        format!(" Commits ({}) ", visible.len())
+        format!(" Commits ({visible_len}) ")
     }
 }
 
@@ -256,16 +347,27 @@ fn build_title(state: &AppState, visible: &[&CommitData]) -> String {
 fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
     let is_cursor_row = ctx.row_idx == ctx.cursor_row;
 
-    // Selection checkbox - show 'D' for deleted, 'x' for selected
+    // Selection checkbox - show 'D' for deleted, 'f'/'s' for staged squashes, '/' for
+    // staged splits, 'x' for selected, 'L' for locked by age
+    let is_locked = ctx.state.is_locked(commit.id);
+    let is_split = ctx.state.splits.contains_key(&commit.id);
     let checkbox_text = if ctx.is_deleted {
         "[D]"
+    } else if let Some(kind) = ctx.squash {
+        kind.marker()
+    } else if is_split {
+        "[/]"
     } else if ctx.is_selected {
         "[x]"
+    } else if is_locked {
+        "[L]"
     } else {
         "[ ]"
     };
     let checkbox_base_style = if ctx.is_deleted {
         ctx.theme.deleted
+    } else if ctx.squash.is_some() || is_split {
+        ctx.theme.info
     } else if ctx.is_selected {
         ctx.theme.checkbox_checked
     } else {
@@ -275,13 +377,32 @@ fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
         ctx,
         Column::Selection as usize,
         false, // checkbox not modifiable
+        None,
         checkbox_base_style,
     );
     let checkbox = Cell::from(Span::styled(checkbox_text, checkbox_style));
 
     // Hash
-    let hash_style = cell_style(ctx, Column::Hash as usize, false, ctx.theme.hash);
-    let hash = Cell::from(Span::styled(commit.short_hash.clone(), hash_style));
+    let hash_style = cell_style(
+        ctx,
+        Column::Hash as usize,
+        false,
+        highlight_style(ctx, &commit.short_hash),
+        if ctx.is_fixup_target {
+            ctx.theme.search_match
+        } else {
+            ctx.theme.hash
+        },
+    );
+    let hash = Cell::from(Line::from(vec![
+        Span::styled(commit.short_hash.clone(), hash_style),
+        Span::styled(
+            ctx.mods
+                .map(CommitModifications::badges)
+                .unwrap_or_default(),
+            hash_style.patch(ctx.theme.modified_value),
+        ),
+    ]));
 
     // Name
     let name_modified = ctx.mods.and_then(|m| m.author_name.as_ref()).is_some();
@@ -292,7 +413,13 @@ fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
             .and_then(|m| m.author_name.clone())
             .unwrap_or_else(|| commit.author.name.clone())
     };
-    let name_style = cell_style(ctx, Column::Name as usize, name_modified, ctx.theme.author);
+    let name_style = cell_style(
+        ctx,
+        Column::Name as usize,
+        name_modified,
+        highlight_style(ctx, &name_value),
+        ctx.theme.author,
+    );
     let name = Cell::from(Span::styled(truncate_string(&name_value, 30), name_style));
 
     // Email
@@ -309,6 +436,7 @@ fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
         ctx,
         Column::Email as usize,
         email_modified,
+        highlight_style(ctx, &email_value),
         ctx.theme.author,
     );
     let email = Cell::from(Span::styled(truncate_string(&email_value, 35), email_style));
@@ -318,12 +446,22 @@ fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
     let date_value = if ctx.is_editing && is_cursor_row && ctx.cursor_col == Column::Date as usize {
         ctx.edit_buffer.to_string()
     } else {
-        ctx.mods.and_then(|m| m.author_date).map_or_else(
-            || commit.format_author_date(),
-            |d| d.format("%Y-%m-%d %H:%M").to_string(),
-        )
+        let date = ctx
+            .mods
+            .and_then(|m| m.author_date)
+            .unwrap_or(commit.author_date);
+        ctx.state
+            .display_date(date)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
     };
-    let date_style = cell_style(ctx, Column::Date as usize, date_modified, ctx.theme.date);
+    let date_style = cell_style(
+        ctx,
+        Column::Date as usize,
+        date_modified,
+        None,
+        ctx.theme.date,
+    );
     let date = Cell::from(Span::styled(date_value, date_style));
 
     // Message
@@ -342,11 +480,89 @@ fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
         ctx,
         Column::Message as usize,
         message_modified,
+        highlight_style(ctx, &message_value),
         ctx.theme.message,
     );
-    let message = Cell::from(Span::styled(message_value, message_style));
+    // Mark cleanup candidates: fixup!/squash! commits not yet staged, and their targets
+    let editing_message =
+        ctx.is_editing && is_cursor_row && ctx.cursor_col == Column::Message as usize;
+    let marker = if editing_message {
+        None
+    } else if ctx.squash.is_none() && SquashKind::from_subject(&commit.summary).is_some() {
+        Some(FIXUP_MARKER)
+    } else if ctx.has_fixups {
+        Some(TARGET_MARKER)
+    } else {
+        None
+    };
+    let lint_marker = (ctx.state.show_lint && !editing_message)
+        .then(|| ctx.state.lint_issue(commit))
+        .flatten()
+        .map(|_| Span::styled(LINT_MARKER, message_style.patch(ctx.theme.warning)));
+    let message = Cell::from(Line::from(
+        lint_marker
+            .into_iter()
+            .chain(marker.map(|m| Span::styled(m, message_style.patch(ctx.theme.info))))
+            .chain(std::iter::once(Span::styled(message_value, message_style)))
+            .collect::<Vec<_>>(),
+    ));
+
+    let mut cells = vec![checkbox, hash, name, email, date, message];
+    let mut optional = Vec::new();
+    if let Some(graph) = ctx.graph {
+        optional.push(Cell::from(Span::styled(
+            graph,
+            display_cell_style(ctx, ctx.theme.info),
+        )));
+    }
+    if ctx.state.show_head_offset {
+        let label = ctx.state.head_offset_label(ctx.row_idx).unwrap_or_default();
+        optional.push(Cell::from(Span::styled(
+            label,
+            display_cell_style(ctx, ctx.theme.hash),
+        )));
+    }
+    if ctx.state.show_time_gap {
+        let gap = ctx.state.time_gap(ctx.row_idx);
+        // A commit dated before its parent is worth noticing
+        let base = if gap.is_some_and(|g| g < TimeDelta::zero()) {
+            ctx.theme.warning
+        } else {
+            ctx.theme.date
+        };
+        optional.push(Cell::from(Span::styled(
+            gap.map(format_gap).unwrap_or_default(),
+            display_cell_style(ctx, base),
+        )));
+    }
+    cells.splice(OPTIONAL_COLUMN_POS..OPTIONAL_COLUMN_POS, optional);
+    if let Some(number) = ctx.state.line_number_label(ctx.row_idx) {
+        let base = if ctx.row_idx == ctx.cursor_row {
+            ctx.theme.keybinding.add_modifier(Modifier::BOLD)
+        } else {
+            ctx.theme.keybinding
+        };
+        cells.insert(
+            0,
+            Cell::from(Line::from(number).alignment(Alignment::Right))
+                .style(display_cell_style(ctx, base)),
+        );
+    }
+
+    Row::new(cells)
+}
 
-    Row::new([checkbox, hash, name, email, date, message])
+/// Style for a display-only cell: shaded with the row in line-wise visual selection
+fn display_cell_style(ctx: &RowContext<'_>, base: Style) -> Style {
+    let in_visual_line = ctx
+        .visual_selection
+        .as_ref()
+        .is_some_and(|v| v.visual_type == VisualType::Line && v.contains_cell(ctx.row_idx, 0));
+    if in_visual_line {
+        ctx.theme.cell_visual.patch(base)
+    } else {
+        base
+    }
 }
 
 /// Compute the style for a single cell
@@ -354,9 +570,16 @@ fn create_row<'a>(commit: &CommitData, ctx: &RowContext<'a>) -> Row<'a> {
 /// Priority (highest to lowest):
 /// 1. Cursor cell (active editing or navigation target)
 /// 2. Visual selection
-/// 3. Modified value (yellow)
-/// 4. Base field color
-fn cell_style(ctx: &RowContext<'_>, col: usize, is_modified: bool, base: Style) -> Style {
+/// 3. Highlight group (background color)
+/// 4. Modified value (yellow)
+/// 5. Base field color
+fn cell_style(
+    ctx: &RowContext<'_>,
+    col: usize,
+    is_modified: bool,
+    highlight: Option<Style>,
+    base: Style,
+) -> Style {
     let is_cursor_cell = ctx.row_idx == ctx.cursor_row && col == ctx.cursor_col;
     let is_in_visual = ctx
         .visual_selection
@@ -385,12 +608,23 @@ fn cell_style(ctx: &RowContext<'_>, col: usize, is_modified: bool, base: Style)
     } else if is_in_visual {
         // In visual selection but not cursor
         ctx.theme.cell_visual.patch(field_style)
+    } else if let Some(highlight) = highlight {
+        // Matches a highlight group
+        field_style.patch(highlight)
     } else {
         // Normal cell
         field_style
     }
 }
 
+/// Style for a cell value matching one of the highlight groups, if any
+fn highlight_style(ctx: &RowContext<'_>, value: &str) -> Option<Style> {
+    let groups = &ctx.theme.highlight_groups;
+    ctx.state
+        .highlight_group(value)
+        .map(|group| groups[group % groups.len()])
+}
+
 fn calculate_column_widths(total_width: u16, h_scroll: usize) -> Vec<Constraint> {
     let available = total_width.saturating_sub(4);
 
@@ -409,13 +643,9 @@ fn calculate_column_widths(total_width: u16, h_scroll: usize) -> Vec<Constraint>
             if col.weight == 0 {
                 Constraint::Length(col.min_width)
             } else {
-                let flex_width = if total_weight > 0 {
-                    (flexible_remaining * col.weight / total_weight)
-                        .max(col.min_width)
-                        .min(col.max_width)
-                } else {
-                    col.min_width
-                };
+                let flex_width = (flexible_remaining * col.weight)
+                    .checked_div(total_weight)
+                    .map_or(col.min_width, |w| w.max(col.min_width).min(col.max_width));
                 Constraint::Length(flex_width)
             }
         })
@@ -425,6 +655,26 @@ fn calculate_column_widths(total_width: u16, h_scroll: usize) -> Vec<Constraint>
     widths
 }
 
+/// Format a time gap compactly, keeping the two most significant units
+pub(crate) fn format_gap(gap: TimeDelta) -> String {
+    let sign = if gap < TimeDelta::zero() { "-" } else { "" };
+    let secs = gap.num_seconds().unsigned_abs();
+    let (days, hours, mins) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+
+    let text = if days >= 365 {
+        format!("{}y {}d", days / 365, days % 365)
+    } else if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m")
+    } else {
+        format!("{secs}s")
+    };
+    format!("{sign}{text}")
+}
+
 fn truncate_string(s: &str, max_width: usize) -> String {
     let width = s.width();
     if width <= max_width {
@@ -1,5 +1,6 @@
 #![allow(clippy::cast_possible_truncation)]
 
+use crate::git::commit::{CommitData, CommitId};
 use crate::git::rewrite::generate_change_summary;
 use crate::state::{AppState, ConfirmAction};
 use crate::ui::layout::DialogLayout;
@@ -129,6 +130,79 @@ fn build_dialog_content(
 
             let mut content = vec!["This will rewrite git history.".to_string(), String::new()];
             content.extend(summary);
+            let squashed = state
+                .squashes
+                .keys()
+                .filter(|id| !state.is_deleted(**id))
+                .count();
+            if squashed > 0 {
+                content.push(format!(
+                    "{squashed} commit(s) will be squashed into the commit below"
+                ));
+            }
+            let split = state
+                .splits
+                .keys()
+                .filter(|id| !state.is_deleted(**id))
+                .count();
+            if split > 0 {
+                content.push(format!("{split} commit(s) will be split in two"));
+            }
+            if let Some(root) = &state.new_root {
+                let holding = if root.paths.is_empty() {
+                    "(empty)".to_string()
+                } else {
+                    format!("with {}", root.paths.join(", "))
+                };
+                content.push(format!(
+                    "Adds a new root commit \"{}\" {holding}",
+                    root.message.lines().next().unwrap_or("")
+                ));
+            }
+            let signed_off = state.trailers_added("Signed-off-by");
+            if signed_off > 0 {
+                content.push(format!(
+                    "{signed_off} commit(s) get a Signed-off-by trailer"
+                ));
+            }
+            if !state.dependent_branches.is_empty() {
+                content.push(String::new());
+                content.push(if state.remap_branches {
+                    "Also moves the branches built on these commits (b: don't):".to_string()
+                } else {
+                    "Leaves the branches built on these commits behind (b: move):".to_string()
+                });
+                content.push(format!("  {}", state.dependent_branches.join(", ")));
+            }
+            if !state.signed_to_rewrite.is_empty() {
+                let count = state.signed_to_rewrite.len();
+                content.push(String::new());
+                content.push(if state.sign_commits {
+                    format!("{count} signed commit(s) will be re-signed with your key:")
+                } else {
+                    format!("{count} signed commit(s) will lose their signatures:")
+                });
+                content.push(format!("  {}", state.signed_to_rewrite.join(", ")));
+            }
+            if let Some(key) = &state.signing_key {
+                if state.signed_to_rewrite.is_empty() {
+                    content.push(String::new());
+                }
+                content.push(if state.sign_commits {
+                    format!("Signs the new commits with the {key} (s: don't)")
+                } else {
+                    format!("New commits are not signed (s: sign with the {key})")
+                });
+            }
+            if !state.tags_to_move.is_empty() {
+                content.push(String::new());
+                content.push(if state.migrate_tags {
+                    "Re-points the tags of these commits (t: don't):".to_string()
+                } else {
+                    "Leaves the tags of these commits behind (t: move):".to_string()
+                });
+                content.push(format!("  {}", state.tags_to_move.join(", ")));
+            }
 
             let warning = if state.has_upstream {
                 Some("Branch has upstream - will require force push!".to_string())
@@ -157,6 +231,162 @@ fn build_dialog_content(
             (title, content, None)
         }
 
+        ConfirmAction::EditOthersCommits { authors, .. } => {
+            let title = "Edit Others' Commits".to_string();
+            let mut content = vec!["These commits were authored by someone else:".to_string()];
+            content.extend(authors.iter().take(5).map(|a| format!("  {a}")));
+            if authors.len() > 5 {
+                content.push(format!("  ... and {} more", authors.len() - 5));
+            }
+            content.push(String::new());
+            content.push("Continue? (won't ask again this session)".to_string());
+            let warning = Some("Rewriting teammates' commits is often unintended".to_string());
+            (title, content, warning)
+        }
+
+        ConfirmAction::AutoFillIdentity {
+            field,
+            value,
+            targets,
+        } => {
+            let title = "Known Identity".to_string();
+            let content = vec![
+                format!("This identity is known with {}:", field.display_name()),
+                format!("  {value}"),
+                String::new(),
+                format!(
+                    "Also set {} on {} commit(s)?",
+                    field.display_name(),
+                    targets.len()
+                ),
+            ];
+            (title, content, None)
+        }
+
+        ConfirmAction::DeleteCommits { ids } => {
+            let title = "Delete Commits".to_string();
+            // List them in display order (the selection is unordered)
+            let targets: Vec<&CommitData> = state
+                .commits
+                .iter()
+                .filter(|c| ids.contains(&c.id))
+                .collect();
+            let mut content = vec![format!("Mark {} commits for deletion?", ids.len())];
+            content.push(String::new());
+            content.extend(
+                targets
+                    .iter()
+                    .take(8)
+                    .map(|c| format!("  {} {}", c.short_hash, c.summary)),
+            );
+            if targets.len() > 8 {
+                content.push(format!("  ... and {} more", targets.len() - 8));
+            }
+            let hidden = state.hidden_count(ids);
+            let warning =
+                (hidden > 0).then(|| format!("{hidden} of them are hidden by the current filter"));
+            (title, content, warning)
+        }
+        ConfirmAction::DeleteReferencing { pairs } => {
+            let title = "Referenced Commits".to_string();
+            let short = |id: &CommitId| {
+                state
+                    .commits
+                    .iter()
+                    .find(|c| c.id == *id)
+                    .map(|c| (c.short_hash.clone(), c.summary.clone()))
+                    .unwrap_or_default()
+            };
+            let mut content = vec!["These later commits refer to a deleted commit:".to_string()];
+            content.extend(pairs.iter().take(5).map(|(referencing, referenced)| {
+                let (hash, summary) = short(referencing);
+                let (target, _) = short(referenced);
+                format!("  {hash} {summary} -> {target}")
+            }));
+            if pairs.len() > 5 {
+                content.push(format!("  ... and {} more", pairs.len() - 5));
+            }
+            content.push(String::new());
+            content.push("Delete them too?".to_string());
+            let warning =
+                Some("Keeping them leaves reverts and hash references dangling".to_string());
+            (title, content, warning)
+        }
+
+        ConfirmAction::ResumeSession(session) => {
+            let title = "Resume Session".to_string();
+            let current = &session.current;
+            let edited = current
+                .modifications
+                .values()
+                .filter(|m| m.has_modifications())
+                .count();
+            let content = vec![
+                format!(
+                    "A session on '{}' was saved {}:",
+                    session.branch,
+                    session.saved_at.format("%Y-%m-%d %H:%M")
+                ),
+                format!(
+                    "  {edited} edited and {} deleted commit(s), {} undo step(s)",
+                    current.deleted.len(),
+                    session.undo_stack.len()
+                ),
+                String::new(),
+                "Resume where you left off?".to_string(),
+            ];
+            let warning =
+                Some("No starts fresh; the session is offered again next time".to_string());
+            (title, content, warning)
+        }
+        ConfirmAction::Recover(session) => {
+            let title = "Recover Changes".to_string();
+            let current = &session.current;
+            let edited = current
+                .modifications
+                .values()
+                .filter(|m| m.has_modifications())
+                .count();
+            let content = vec![
+                format!(
+                    "retcon didn't exit cleanly on '{}'. Changes as of {}:",
+                    session.branch,
+                    session.saved_at.format("%Y-%m-%d %H:%M:%S")
+                ),
+                format!(
+                    "  {edited} edited and {} deleted commit(s)",
+                    current.deleted.len()
+                ),
+                String::new(),
+                "Restore them?".to_string(),
+            ];
+            let warning = Some("No starts fresh; they are deleted when retcon exits".to_string());
+            (title, content, warning)
+        }
+        ConfirmAction::RestoreBackup(backup) => {
+            let title = "Restore Backup".to_string();
+            let content = vec![
+                format!("Point {} at {}:", backup.branch, backup.name),
+                format!("  {} {}", backup.tip, backup.summary),
+                String::new(),
+                "The current tip is kept as a new numbered backup.".to_string(),
+            ];
+            let warning =
+                Some("Commits made since the backup are then only in that new backup".to_string());
+            (title, content, warning)
+        }
+        ConfirmAction::DeleteBackup(backup) => {
+            let title = "Delete Backup".to_string();
+            let content = vec![
+                format!("Delete {}?", backup.name),
+                format!("  {} {}", backup.tip, backup.summary),
+            ];
+            let warning = backup
+                .number
+                .is_none()
+                .then(|| "It keeps the history from before the first rewrite".to_string());
+            (title, content, warning)
+        }
         ConfirmAction::QuitWithChanges => {
             let title = "Quit with Changes".to_string();
             let modified = state.modified_count();
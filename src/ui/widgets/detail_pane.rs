@@ -1,8 +1,10 @@
 #![allow(clippy::cast_possible_truncation)]
 
-use crate::git::commit::{CommitData, CommitModifications};
+use crate::git::commit::{split_trailers, CommitData, CommitModifications};
+use crate::git::repository::FileChange;
 use crate::state::AppState;
 use crate::ui::theme::Theme;
+use chrono::{DateTime, FixedOffset};
 use ratatui::layout::Rect;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
@@ -23,7 +25,7 @@ pub fn render_detail_pane(frame: &mut Frame<'_>, area: Rect, state: &AppState, t
     };
 
     let mods = state.modifications.get(&commit.id);
-    let lines = build_detail_lines(commit, mods, theme);
+    let lines = build_detail_lines(commit, mods, state, theme);
 
     // Calculate content height for scrollbar
     let content_height = lines.len();
@@ -62,10 +64,27 @@ pub fn render_detail_pane(frame: &mut Frame<'_>, area: Rect, state: &AppState, t
     }
 }
 
+/// Format a date as shown in the detail pane
+fn format_date(date: DateTime<FixedOffset>) -> String {
+    date.format("%Y-%m-%d %H:%M:%S %z").to_string()
+}
+
+/// The recorded offset, when dates are shown in local time and it differs
+fn recorded_date_span(
+    date: DateTime<FixedOffset>,
+    state: &AppState,
+    theme: &Theme,
+) -> Option<Span<'static>> {
+    let shown = state.display_date(date);
+    (shown.offset() != date.offset())
+        .then(|| Span::styled(format!("  (recorded {})", format_date(date)), theme.info))
+}
+
 /// Build the lines for the detail pane
 fn build_detail_lines<'a>(
     commit: &CommitData,
     mods: Option<&CommitModifications>,
+    state: &AppState,
     theme: &Theme,
 ) -> Vec<Line<'a>> {
     let mut lines = Vec::new();
@@ -102,15 +121,19 @@ fn build_detail_lines<'a>(
 
     // Author date
     let author_date_mod = mods.and_then(|m| m.author_date).is_some();
-    let author_date = mods.and_then(|m| m.author_date).map_or_else(
-        || commit.format_author_date_full(),
-        |d| d.format("%Y-%m-%d %H:%M:%S %z").to_string(),
-    );
+    let author_date = mods
+        .and_then(|m| m.author_date)
+        .unwrap_or(commit.author_date);
 
-    lines.push(Line::from(vec![
+    let mut author_date_line = vec![
         Span::styled("A. Date:   ", theme.info),
-        Span::styled(author_date, theme.field_style(author_date_mod, theme.date)),
-    ]));
+        Span::styled(
+            format_date(state.display_date(author_date)),
+            theme.field_style(author_date_mod, theme.date),
+        ),
+    ];
+    author_date_line.extend(recorded_date_span(author_date, state, theme));
+    lines.push(Line::from(author_date_line));
 
     // Committer
     let committer_name_mod = mods.and_then(|m| m.committer_name.as_ref()).is_some();
@@ -138,18 +161,19 @@ fn build_detail_lines<'a>(
 
     // Committer date
     let committer_date_mod = mods.and_then(|m| m.committer_date).is_some();
-    let committer_date = mods.and_then(|m| m.committer_date).map_or_else(
-        || commit.format_committer_date_full(),
-        |d| d.format("%Y-%m-%d %H:%M:%S %z").to_string(),
-    );
+    let committer_date = mods
+        .and_then(|m| m.committer_date)
+        .unwrap_or(commit.committer_date);
 
-    lines.push(Line::from(vec![
+    let mut committer_date_line = vec![
         Span::styled("C. Date:   ", theme.info),
         Span::styled(
-            committer_date,
+            format_date(state.display_date(committer_date)),
             theme.field_style(committer_date_mod, theme.date),
         ),
-    ]));
+    ];
+    committer_date_line.extend(recorded_date_span(committer_date, state, theme));
+    lines.push(Line::from(committer_date_line));
 
     // Parent info
     if !commit.parent_ids.is_empty() {
@@ -173,6 +197,48 @@ fn build_detail_lines<'a>(
         ]));
     }
 
+    // Staged split
+    if let Some(split) = state.splits.get(&commit.id) {
+        lines.push(Line::from(vec![
+            Span::styled("Split:     ", theme.info),
+            Span::styled(
+                format!("first commit takes {}", split.paths.join(", ")),
+                theme.modified_value,
+            ),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("           ", theme.info),
+            Span::styled(
+                format!("second commit: {}", split.message),
+                theme.modified_value,
+            ),
+        ]));
+    }
+
+    // Why it's being changed
+    if let Some(note) = state.notes.get(&commit.id) {
+        lines.push(Line::from(vec![
+            Span::styled("Note:      ", theme.info),
+            Span::raw(note.clone()),
+        ]));
+    }
+
+    // Co-authors credited with trailers (new ones highlighted)
+    let (_, original) = split_trailers(&commit.message);
+    let co_authors = state.trailers(&[commit.id], Some("Co-authored-by"));
+    for (idx, (co_author, _)) in co_authors.into_iter().enumerate() {
+        let label = if idx == 0 {
+            "Co-author: "
+        } else {
+            "           "
+        };
+        let added = !original.iter().any(|t| t.matches(&co_author));
+        lines.push(Line::from(vec![
+            Span::styled(label, theme.info),
+            Span::styled(co_author.value, theme.field_style(added, theme.author)),
+        ]));
+    }
+
     // Empty line before message
     lines.push(Line::from(""));
 
@@ -193,5 +259,53 @@ fn build_detail_lines<'a>(
         ]));
     }
 
+    // Files the commit changes, once loaded
+    if let Some(files) = state.file_changes.get(&commit.id) {
+        lines.push(Line::from(""));
+        lines.extend(file_change_lines(files, theme));
+    }
+
+    lines
+}
+
+/// A summary line, then each file with its status and line counts
+fn file_change_lines<'a>(files: &[FileChange], theme: &Theme) -> Vec<Line<'a>> {
+    let insertions: usize = files.iter().map(|f| f.insertions).sum();
+    let deletions: usize = files.iter().map(|f| f.deletions).sum();
+    let mut lines = vec![Line::from(vec![
+        Span::styled("Files:    ", theme.info),
+        Span::raw(format!("{} changed, ", files.len())),
+        Span::styled(format!("+{insertions}"), theme.success),
+        Span::raw(" "),
+        Span::styled(format!("-{deletions}"), theme.error),
+    ])];
+
+    let width = |count: usize| count.to_string().len() + 1;
+    let add_width = files.iter().map(|f| width(f.insertions)).max().unwrap_or(2);
+    let del_width = files.iter().map(|f| width(f.deletions)).max().unwrap_or(2);
+    for file in files {
+        let counts = if file.binary {
+            vec![Span::styled(
+                format!("{:<w$}", "bin", w = add_width + del_width + 1),
+                theme.info,
+            )]
+        } else {
+            vec![
+                Span::styled(
+                    format!("{:>add_width$}", format!("+{}", file.insertions)),
+                    theme.success,
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{:>del_width$}", format!("-{}", file.deletions)),
+                    theme.error,
+                ),
+            ]
+        };
+        let mut spans = vec![Span::styled(format!("  {} ", file.status), theme.info)];
+        spans.extend(counts);
+        spans.push(Span::raw(format!("  {}", file.path)));
+        lines.push(Line::from(spans));
+    }
     lines
 }
@@ -0,0 +1,113 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::{AppState, DivergenceReport};
+use crate::ui::theme::Theme;
+use crate::ui::widgets::commit_table::format_gap;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Lines below the commit list: blank, two dates, blank, two key hint lines
+const FOOTER_LINES: usize = 6;
+
+/// Render the list of commits whose author and committer dates are far apart
+pub fn render_divergence_report(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    report: &DivergenceReport,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let width = 78u16.min(area.width.saturating_sub(4));
+    let height = ((report.rows.len() + FOOTER_LINES) as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted commit in view above the footer
+    let list_height = (height.saturating_sub(2) as usize).saturating_sub(FOOTER_LINES);
+    let scroll = (report.cursor + 1).saturating_sub(list_height.max(1));
+
+    let mut lines: Vec<Line<'_>> = report
+        .rows
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(list_height.max(1))
+        .filter_map(|(idx, (id, diff))| {
+            let commit = state.commits.iter().find(|c| c.id == *id)?;
+            let line = Line::from(vec![
+                Span::styled(commit.short_hash.clone(), theme.hash),
+                Span::styled(format!(" {:>9}  ", format_gap(*diff)), theme.warning),
+                Span::raw(commit.summary.clone()),
+            ]);
+            Some(if idx == report.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            })
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    if let Some(commit) = report
+        .rows
+        .get(report.cursor)
+        .and_then(|(id, _)| state.commits.iter().find(|c| c.id == *id))
+    {
+        let mods = state.modifications.get(&commit.id);
+        let author = mods
+            .and_then(|m| m.author_date)
+            .unwrap_or(commit.author_date);
+        let committer = mods
+            .and_then(|m| m.committer_date)
+            .unwrap_or(commit.committer_date);
+        for (label, date) in [
+            ("Author date:    ", author),
+            ("Committer date: ", committer),
+        ] {
+            lines.push(Line::from(vec![
+                Span::styled(label, theme.keybinding),
+                Span::raw(
+                    state
+                        .display_date(date)
+                        .format("%Y-%m-%d %H:%M:%S %z")
+                        .to_string(),
+                ),
+            ]));
+        }
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("a", theme.keybinding_key),
+            Span::raw(": committer date = author date  "),
+            Span::styled("c", theme.keybinding_key),
+            Span::raw(": author date = committer date"),
+        ]),
+        Line::from(vec![
+            Span::styled("A/C", theme.keybinding_key),
+            Span::raw(": same for every listed commit  "),
+            Span::styled("Enter", theme.keybinding_key),
+            Span::raw(": go to commit  "),
+            Span::styled("Esc", theme.keybinding_key),
+            Span::raw(": close"),
+        ]),
+    ]);
+
+    frame.render_widget(Clear, popup_area);
+
+    let title = format!(
+        " Dates more than {} apart: {} commit(s) ",
+        format_gap(report.threshold),
+        report.rows.len()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
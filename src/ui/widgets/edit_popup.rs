@@ -38,8 +38,17 @@ pub fn render_edit_popup(
     // Clear background
     frame.render_widget(Clear, popup_area);
 
-    // Build title
-    let title = format!(" Edit: {} ", field.display_name());
+    // Build title, noting multi-commit edits and targets hidden by the filter
+    let targets = state.commits_to_edit();
+    let hidden = state.hidden_count(&targets);
+    let title = match (targets.len(), hidden) {
+        (0 | 1, _) => format!(" Edit: {} ", field.display_name()),
+        (count, 0) => format!(" Edit: {} ({count} commits) ", field.display_name()),
+        (count, hidden) => format!(
+            " Edit: {} ({count} commits, {hidden} hidden) ",
+            field.display_name()
+        ),
+    };
 
     let block = Block::default()
         .borders(Borders::ALL)
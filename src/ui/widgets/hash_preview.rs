@@ -0,0 +1,105 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::{AppState, HashPreview};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Lines below the commit list: blank, descendants/signing notes, key hints
+const FOOTER_LINES: usize = 4;
+
+/// Render each commit's current hash next to the one applying would give it
+pub fn render_hash_preview(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    preview: &HashPreview,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let width = 78u16.min(area.width.saturating_sub(4));
+    let height =
+        ((preview.rows.len() + FOOTER_LINES) as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted commit in view above the footer
+    let list_height = (height.saturating_sub(2) as usize).saturating_sub(FOOTER_LINES);
+    let scroll = (preview.cursor + 1).saturating_sub(list_height.max(1));
+
+    let mut lines: Vec<Line<'_>> = preview
+        .rows
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(list_height.max(1))
+        .filter_map(|(idx, (old, new))| {
+            let commit = state.commits.iter().find(|c| c.id == *old)?;
+            let summary = state
+                .modifications
+                .get(old)
+                .map_or(commit.summary.as_str(), |m| {
+                    m.effective_summary(&commit.summary)
+                });
+            let new = match new {
+                None => Span::styled("deleted", theme.deleted),
+                Some(new) if new == old => Span::styled("same   ", theme.info),
+                Some(new) => Span::styled(new.0.to_string()[..7].to_string(), theme.modified_value),
+            };
+            let folded = if state.squashes.contains_key(old) {
+                "(folded) "
+            } else {
+                ""
+            };
+            let line = Line::from(vec![
+                Span::styled(commit.short_hash.clone(), theme.hash),
+                Span::raw(" → "),
+                new,
+                Span::raw("  "),
+                Span::styled(folded, theme.info),
+                Span::raw(summary.to_string()),
+            ]);
+            Some(if idx == preview.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            })
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    let mut notes = Vec::new();
+    if preview.descendants > 0 {
+        notes.push(format!(
+            "{} newer commit(s) above these are replayed with new hashes too.",
+            preview.descendants
+        ));
+    }
+    if preview.signed {
+        notes.push("Signing on apply will change these hashes.".to_string());
+    }
+    lines.push(Line::from(Span::styled(notes.join(" "), theme.warning)));
+    lines.push(Line::from(vec![
+        Span::styled("w", theme.keybinding_key),
+        Span::raw(": apply  "),
+        Span::styled("Esc", theme.keybinding_key),
+        Span::raw(": close (nothing was changed)"),
+    ]));
+
+    frame.render_widget(Clear, popup_area);
+
+    let tip = preview.tip.map_or_else(
+        || "none".to_string(),
+        |tip| tip.0.to_string()[..7].to_string(),
+    );
+    let title = format!(" New hashes: {} would point at {tip} ", state.branch_name);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
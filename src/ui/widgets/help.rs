@@ -12,7 +12,7 @@ use ratatui::Frame;
 pub fn help_content_height() -> usize {
     // This should match the number of lines in build_help_text
     // We return a constant here to avoid rebuilding the text just to count
-    105 // Approximate number of help lines (including delete, reorder and help navigation sections)
+    181 // Approximate number of help lines (including delete, reorder and help navigation sections)
 }
 
 /// Render the help screen with scrolling support
@@ -77,12 +77,29 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
     lines.push(Line::from(""));
     lines.push(key_line("j / ↓", "Move cursor down (row)", key_style));
     lines.push(key_line("k / ↑", "Move cursor up (row)", key_style));
+    lines.push(key_line("5j / 5k", "Move by a count of rows", key_style));
     lines.push(key_line("h / ←", "Move to previous column", key_style));
     lines.push(key_line("l / →", "Move to next column", key_style));
-    lines.push(key_line("g / Home", "Go to first commit", key_style));
+    lines.push(key_line("gg / Home", "Go to first commit", key_style));
     lines.push(key_line("G / End", "Go to last commit", key_style));
     lines.push(key_line("Ctrl+d", "Page down", key_style));
     lines.push(key_line("Ctrl+u", "Page up", key_style));
+    lines.push(key_line(
+        "m{a-z}",
+        "Set a mark on the current commit",
+        key_style,
+    ));
+    lines.push(key_line("'{a-z}", "Jump to a mark", key_style));
+    lines.push(key_line(
+        "Ctrl+o / Ctrl+i",
+        "Back/forward through the jump list (or Alt+i)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "+",
+        "Load 50 older commits (also moving down past the last one)",
+        key_style,
+    ));
 
     // Selection section (for batch editing)
     lines.push(Line::from(""));
@@ -121,7 +138,18 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
         key_style,
     ));
     lines.push(key_line("g/G", "Extend to first/last commit", key_style));
+    lines.push(key_line("o", "Jump to other end of selection", key_style));
     lines.push(key_line("e / Enter", "Edit selected commits", key_style));
+    lines.push(key_line(
+        "f",
+        "Fill cursor cell's value into the selection",
+        key_style,
+    ));
+    lines.push(key_line(
+        "p",
+        "Paste a register over the selection",
+        key_style,
+    ));
     lines.push(key_line(
         "Space",
         "Toggle checkbox on visual range",
@@ -139,9 +167,53 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
         "Start editing current cell",
         key_style,
     ));
+    lines.push(key_line("E", "Edit current cell in $EDITOR", key_style));
+    lines.push(key_line(
+        "[\"x]y / [\"x]p",
+        "Yank cell / paste into cell (register x)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "Y / \"+y",
+        "Copy full hash / cell to the clipboard",
+        key_style,
+    ));
     lines.push(key_line("Tab", "Move to next column", key_style));
     lines.push(key_line("Shift+Tab", "Move to previous column", key_style));
+    lines.push(key_line(
+        "R",
+        "Find/replace in this commit's message",
+        key_style,
+    ));
+    lines.push(key_line(
+        "Ctrl+r",
+        "While editing: find/replace in the value",
+        key_style,
+    ));
+    lines.push(key_line(
+        "ge",
+        "Edit this column on all visible commits",
+        key_style,
+    ));
+    lines.push(key_line(
+        "T",
+        "Add/change/remove trailers (a/e/d/Space)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "A",
+        "Same, for Co-authored-by trailers only",
+        key_style,
+    ));
+    lines.push(key_line(
+        "i",
+        "Set author from git config / identities.toml (1-9)",
+        key_style,
+    ));
     lines.push(Line::from("  (Changes apply to selected commits if any)"));
+    lines.push(Line::from(
+        "  (Entering a known name/email offers to fill in the other half)",
+    ));
 
     // In Edit Mode section
     lines.push(Line::from(""));
@@ -164,14 +236,241 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
         key_style,
     ));
     lines.push(key_line("Ctrl+A/E", "Move to start/end of line", key_style));
+    lines.push(key_line(
+        "Ctrl+Z/Y",
+        "Undo/redo within the field being edited",
+        key_style,
+    ));
+    lines.push(key_line("Ctrl+V", "Paste from the clipboard", key_style));
 
     // Search section
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled("Search/Filter", title_style)));
     lines.push(Line::from(""));
-    lines.push(key_line("/", "Open search bar", key_style));
+    lines.push(key_line(
+        "/",
+        "Open search bar (re:PATTERN for a regex)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "/ filters",
+        "author: email: path: path!: after:/before:YYYY-MM-DD",
+        key_style,
+    ));
     lines.push(key_line("Enter", "Apply filter", key_style));
     lines.push(key_line("Esc", "Clear filter", key_style));
+    lines.push(key_line("Up/Down", "Recall previous searches", key_style));
+    lines.push(key_line(
+        "n/N",
+        "Jump to next/previous match of last search",
+        key_style,
+    ));
+    lines.push(key_line(
+        "gs",
+        "Sort by this column (asc, desc, off)",
+        key_style,
+    ));
+
+    // Command line section
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Command Line", title_style)));
+    lines.push(Line::from(""));
+    lines.push(key_line(":", "Open command line", key_style));
+    lines.push(key_line(
+        "Ctrl+p",
+        "Command palette: find any action by name (also from :)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":3,7CMD, :%CMD",
+        "Run a command on rows 3-7, or all rows (. cursor, $ last)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":author NAME EMAIL",
+        "Set the author (quote names with spaces)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":date DATE|±OFFSET",
+        "Set the author date, or shift dates like :shift",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":delete [3,7]",
+        "Mark the selection (or rows) for deletion",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":w, :wq, :q, :q!",
+        "Write; write and quit; quit; quit dropping changes",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":range A B",
+        "Select commits from hash A to B (inclusive)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":HASH, :goto HASH",
+        "Jump to a commit (loading older commits if needed)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":hl PATTERN",
+        "Highlight matching cells in a new color",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":nohl [PATTERN]",
+        "Remove one highlight group (or all)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":limit N|all",
+        "Load more (or fewer) commits",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":snap UNIT [MODE]",
+        "Round dates to hour/day/15m... (floor/ceil/nearest)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":shift ±OFFSET",
+        "Move author+committer dates (e.g. +3d4h, -90m)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":tz +HHMM|UTC",
+        "Convert dates to a UTC offset, keeping the instant",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":spread A .. B [~J]",
+        "Space dates evenly from A to B (± jitter J)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":me",
+        "Set the author to your identity (GIT_AUTHOR_* aware)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":newroot [path...]",
+        "Add a commit beneath the root with these of its files",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":sort KEY [desc]",
+        "Sort rows by author/email/date/message (:nosort)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":mailmap [file]",
+        "Remap authors/committers via .mailmap (or file)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":export FILE.mailmap",
+        "Add your name/email edits (old → new) to a mailmap",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":export FILE.json",
+        "Save pending edits as a plan (for retcon apply --plan)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":export rebase-todo",
+        "Save reorders/drops/squashes as a git rebase -i todo",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":dump FILE.csv|.json",
+        "Write commit metadata and pending values for auditing",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":signoff [all]",
+        "Add Signed-off-by for your identity to selected (or all)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":autosquash",
+        "Move fixup!/squash! commits onto their targets",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":unlock [all]",
+        "Allow editing commits locked by age",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":note [text]",
+        "Note why the selected commits change (none: remove)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":authors",
+        "Commits per author; e changes one author's commits",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":backups",
+        "List backup refs: restore (Enter), new (n), delete (d)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":divergence [2h]",
+        "List commits whose author/committer dates differ",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":reflow [72]",
+        "Rewrap message bodies, keeping subject and trailers",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":lint [type]",
+        "List non-Conventional Commits, or set a type",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":clear <field>...",
+        "Drop pending edits of fields (date, name, ..., all)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":set [no]offset",
+        "Show/hide the HEAD~n column (offset! toggles)",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":set [no]gap",
+        "Show/hide time since the previous commit",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":set [no]graph",
+        "Show/hide the branch and merge graph",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":set [no]nu / [no]rnu",
+        "Show/hide absolute/relative line numbers",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":set [no]localtime",
+        "Show dates in your local timezone",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":set [no]lint",
+        "Mark messages that aren't Conventional Commits",
+        key_style,
+    ));
 
     // Undo/Redo section
     lines.push(Line::from(""));
@@ -179,6 +478,11 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
     lines.push(Line::from(""));
     lines.push(key_line("u", "Undo last change", key_style));
     lines.push(key_line("Ctrl+r", "Redo", key_style));
+    lines.push(key_line(
+        "U",
+        "Browse the undo history, jump several steps",
+        key_style,
+    ));
 
     // Delete section
     lines.push(Line::from(""));
@@ -189,6 +493,16 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
         "Mark/unmark commit for deletion",
         key_style,
     ));
+    lines.push(key_line(
+        "f",
+        "Fold commit into the one below (fixup)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "S",
+        "Split commit in two by choosing files",
+        key_style,
+    ));
     lines.push(Line::from("  (Works on selected commits if any)"));
     lines.push(Line::from("  (Child commits are reparented)"));
 
@@ -206,6 +520,16 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
         "Move commit down (later in history)",
         key_style,
     ));
+    lines.push(key_line(
+        "M",
+        "Put dates back in order after reordering",
+        key_style,
+    ));
+    lines.push(key_line(
+        ":fixdates [swap|nudge [N]]",
+        "Same, swapping dates or nudging by N (default 1m)",
+        key_style,
+    ));
 
     // Actions section
     lines.push(Line::from(""));
@@ -213,10 +537,22 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
     lines.push(Line::from(""));
     lines.push(key_line(
         "w",
-        "Write/apply changes (rewrite history)",
+        "Write/apply changes (in the dialog, b/t/s: branches/tags/signing)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "W",
+        "Write, reviewing each changed commit (y/s/Esc)",
+        key_style,
+    ));
+    lines.push(key_line(
+        "P",
+        "Preview new hashes without writing (:preview)",
         key_style,
     ));
     lines.push(key_line("r", "Reset/discard all changes", key_style));
+    lines.push(key_line("B", "Switch to another branch", key_style));
+    lines.push(key_line("O", "Sign off the selected commits", key_style));
 
     // General section
     lines.push(Line::from(""));
@@ -225,7 +561,7 @@ fn build_help_text(theme: &Theme) -> Vec<Line<'static>> {
     lines.push(key_line("?", "Show this help", key_style));
     lines.push(key_line(
         "q",
-        "Quit (prompts if unsaved changes)",
+        "Quit (prompts if unsaved changes; s saves the session)",
         key_style,
     ));
 
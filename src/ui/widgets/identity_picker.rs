@@ -0,0 +1,77 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::IdentityPicker;
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Render the identities to set as author, numbered for one-key picking
+pub fn render_identity_picker(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    picker: &IdentityPicker,
+    theme: &Theme,
+) {
+    let label_width = picker
+        .identities
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line<'_>> = picker
+        .identities
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, person))| {
+            let key = if idx < 9 {
+                format!("{} ", idx + 1)
+            } else {
+                "  ".to_string()
+            };
+            let line = Line::from(vec![
+                Span::styled(key, theme.info),
+                Span::styled(format!("{label:<label_width$}  "), theme.author),
+                Span::raw(person.format_full()),
+            ]);
+            if idx == picker.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let width = lines
+        .iter()
+        .map(|line| line.width() as u16 + 2)
+        .max()
+        .unwrap_or(0)
+        .max(30)
+        .min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted identity in view
+    let visible_height = height.saturating_sub(2) as usize;
+    let scroll = (picker.cursor + 1).saturating_sub(visible_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let title = format!(" Set author of {} commit(s) ", picker.targets.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        popup_area,
+    );
+}
@@ -0,0 +1,112 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::git::conventional::COMMIT_TYPES;
+use crate::state::{AppState, LintReport};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Lines below the commit list: blank, type chooser, blank, two key hint lines
+const FOOTER_LINES: usize = 5;
+
+/// Width of the issue column (fits e.g. "unknown type 'feature'")
+const ISSUE_WIDTH: usize = 24;
+
+/// Render the list of commit messages that aren't Conventional Commits
+pub fn render_lint_report(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    report: &LintReport,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let width = 90u16.min(area.width.saturating_sub(4));
+    let height = ((report.rows.len() + FOOTER_LINES) as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted commit in view above the footer
+    let list_height = (height.saturating_sub(2) as usize).saturating_sub(FOOTER_LINES);
+    let scroll = (report.cursor + 1).saturating_sub(list_height.max(1));
+
+    let mut lines: Vec<Line<'_>> = report
+        .rows
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(list_height.max(1))
+        .filter_map(|(idx, (id, issue))| {
+            let commit = state.commits.iter().find(|c| c.id == *id)?;
+            let summary = state
+                .modifications
+                .get(id)
+                .map_or(commit.summary.as_str(), |m| {
+                    m.effective_summary(&commit.summary)
+                });
+            let (checkbox, checkbox_style) = if state.is_selected(*id) {
+                ("[x] ", theme.checkbox_checked)
+            } else {
+                ("[ ] ", theme.checkbox_unchecked)
+            };
+            let issue: String = issue.to_string().chars().take(ISSUE_WIDTH).collect();
+            let line = Line::from(vec![
+                Span::styled(checkbox, checkbox_style),
+                Span::styled(commit.short_hash.clone(), theme.hash),
+                Span::styled(format!(" {issue:<ISSUE_WIDTH$}  "), theme.warning),
+                Span::raw(summary.to_string()),
+            ]);
+            Some(if idx == report.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            })
+        })
+        .collect();
+
+    let mut types = vec![Span::styled("Type: ", theme.keybinding)];
+    for (idx, kind) in COMMIT_TYPES.iter().enumerate() {
+        let style = if idx == report.kind {
+            theme.cell_cursor
+        } else {
+            Style::default()
+        };
+        types.push(Span::styled(*kind, style));
+        types.push(Span::raw(" "));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(types),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("h/l", theme.keybinding_key),
+            Span::raw(": choose type  "),
+            Span::styled("f", theme.keybinding_key),
+            Span::raw(": set it on the selected commits (or this one)"),
+        ]),
+        Line::from(vec![
+            Span::styled("Space", theme.keybinding_key),
+            Span::raw(": select  "),
+            Span::styled("Enter", theme.keybinding_key),
+            Span::raw(": go to commit  "),
+            Span::styled("Esc", theme.keybinding_key),
+            Span::raw(": close"),
+        ]),
+    ]);
+
+    frame.render_widget(Clear, popup_area);
+
+    let title = format!(
+        " Not Conventional Commits: {} commit(s) ",
+        report.rows.len()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
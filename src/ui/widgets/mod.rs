@@ -1,18 +1,52 @@
+pub mod apply_guidance;
+pub mod apply_progress;
+pub mod author_groups;
+pub mod backups;
+pub mod branch_picker;
+pub mod command_bar;
+pub mod command_palette;
 pub mod commit_table;
 pub mod confirmation;
 pub mod detail_pane;
+pub mod divergence_report;
 pub mod edit_popup;
+pub mod hash_preview;
 
 pub mod help;
+pub mod identity_picker;
+pub mod lint_report;
+pub mod replace_dialog;
 pub mod search_bar;
+pub mod split_pane;
+pub mod stash_dialog;
 pub mod status_bar;
+pub mod step_dialog;
 pub mod title_bar;
+pub mod trailer_editor;
+pub mod undo_history;
 
+pub use apply_guidance::render_apply_guidance;
+pub use apply_progress::render_apply_progress;
+pub use author_groups::render_author_groups;
+pub use backups::render_backups;
+pub use branch_picker::render_branch_picker;
+pub use command_bar::render_command_bar;
+pub use command_palette::render_command_palette;
 pub use commit_table::{get_column_value, render_commit_table, Column};
 pub use confirmation::{render_confirmation_dialog, ConfirmDialogState};
 pub use detail_pane::render_detail_pane;
+pub use divergence_report::render_divergence_report;
 pub use edit_popup::render_edit_popup;
+pub use hash_preview::render_hash_preview;
 pub use help::{help_max_scroll, render_help_screen};
+pub use identity_picker::render_identity_picker;
+pub use lint_report::render_lint_report;
+pub use replace_dialog::{render_replace_dialog, ReplaceState};
 pub use search_bar::{render_search_bar, SearchState};
-pub use status_bar::render_status_bar;
+pub use split_pane::render_split_pane;
+pub use stash_dialog::render_stash_dialog;
+pub use status_bar::{render_status_bar, StatusBarConfig, StatusSegment};
+pub use step_dialog::render_step_dialog;
 pub use title_bar::render_title_bar;
+pub use trailer_editor::render_trailer_editor;
+pub use undo_history::render_undo_history;
@@ -0,0 +1,170 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::ui::theme::Theme;
+use crate::ui::widgets::SearchState;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Maximum number of matching message lines shown in the preview
+const MAX_PREVIEW_LINES: usize = 8;
+
+/// State for the find/replace dialog
+pub struct ReplaceState {
+    pub find: SearchState,
+    pub replacement: SearchState,
+    /// Whether the replacement input has focus (otherwise the find input)
+    pub replacement_focused: bool,
+}
+
+impl ReplaceState {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            find: SearchState::new(),
+            replacement: SearchState::new(),
+            replacement_focused: false,
+        }
+    }
+
+    /// The input that currently has focus
+    pub fn focused_input(&mut self) -> &mut SearchState {
+        if self.replacement_focused {
+            &mut self.replacement
+        } else {
+            &mut self.find
+        }
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.replacement_focused = !self.replacement_focused;
+    }
+}
+
+impl Default for ReplaceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render the find/replace dialog for a single commit message
+pub fn render_replace_dialog(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    replace: &ReplaceState,
+    message: &str,
+    theme: &Theme,
+) {
+    let find = replace.find.query.as_str();
+    let matching_lines: Vec<&str> = if find.is_empty() {
+        Vec::new()
+    } else {
+        message.lines().filter(|l| l.contains(find)).collect()
+    };
+    let preview_count = matching_lines.len().min(MAX_PREVIEW_LINES);
+
+    let width = 70u16.min(area.width.saturating_sub(4));
+    let height = (preview_count as u16 + 7).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(" Replace in message ").style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+
+    let match_count = if find.is_empty() {
+        0
+    } else {
+        message.matches(find).count()
+    };
+
+    let mut lines = vec![
+        input_line(
+            "Find:    ",
+            &replace.find,
+            !replace.replacement_focused,
+            theme,
+        ),
+        input_line(
+            "Replace: ",
+            &replace.replacement,
+            replace.replacement_focused,
+            theme,
+        ),
+        Line::from(Span::styled(
+            format!("{match_count} match(es)"),
+            if match_count == 0 && !find.is_empty() {
+                theme.warning
+            } else {
+                theme.info
+            },
+        )),
+    ];
+
+    for line in matching_lines.iter().take(MAX_PREVIEW_LINES) {
+        lines.push(highlight_matches(line, find, theme));
+    }
+
+    lines.push(Line::from(vec![
+        Span::styled("Enter", theme.keybinding_key),
+        Span::raw(": replace all  "),
+        Span::styled("Tab", theme.keybinding_key),
+        Span::raw(": switch field  "),
+        Span::styled("Esc", theme.keybinding_key),
+        Span::raw(": cancel"),
+    ]));
+
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines), inner_area);
+}
+
+/// Build a labelled input line, showing the cursor when focused
+fn input_line<'a>(label: &'a str, input: &SearchState, focused: bool, theme: &Theme) -> Line<'a> {
+    let label_style = if focused {
+        theme.search_prompt
+    } else {
+        theme.keybinding
+    };
+    let mut spans = vec![Span::styled(label, label_style)];
+
+    if !focused {
+        spans.push(Span::styled(input.query.clone(), theme.search_input));
+        return Line::from(spans);
+    }
+
+    let cursor_style = theme.search_input.add_modifier(Modifier::REVERSED);
+    let cursor = input.cursor.min(input.query.len());
+    let (before, at_and_after) = input.query.split_at(cursor);
+    spans.push(Span::styled(before.to_string(), theme.search_input));
+
+    let mut rest = at_and_after.chars();
+    if let Some(c) = rest.next() {
+        spans.push(Span::styled(c.to_string(), cursor_style));
+        spans.push(Span::styled(rest.as_str().to_string(), theme.search_input));
+    } else {
+        spans.push(Span::styled(" ", cursor_style));
+    }
+
+    Line::from(spans)
+}
+
+/// Show a message line with occurrences of `find` highlighted
+fn highlight_matches<'a>(line: &str, find: &str, theme: &Theme) -> Line<'a> {
+    let mut spans = vec![Span::raw("  ")];
+    let mut rest = line;
+    while let Some(pos) = rest.find(find) {
+        spans.push(Span::raw(rest[..pos].to_string()));
+        spans.push(Span::styled(find.to_string(), theme.search_match));
+        rest = &rest[pos + find.len()..];
+    }
+    spans.push(Span::raw(rest.to_string()));
+    Line::from(spans)
+}
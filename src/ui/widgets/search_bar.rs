@@ -5,12 +5,15 @@ use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 
 /// Render the search bar
+///
+/// `error` explains why the query (a `re:` pattern) is invalid.
 pub fn render_search_bar(
     frame: &mut Frame<'_>,
     area: Rect,
     query: &str,
     cursor_pos: usize,
     result_count: Option<usize>,
+    error: Option<&str>,
     theme: &Theme,
 ) {
     let block = Block::default()
@@ -57,8 +60,11 @@ pub fn render_search_bar(
         spans.push(Span::styled(after.to_string(), theme.search_input));
     }
 
-    // Show result count
-    if let Some(count) = result_count {
+    // Show what's wrong with the pattern, else the result count
+    if let Some(error) = error {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(error.to_string(), theme.error));
+    } else if let Some(count) = result_count {
         spans.push(Span::raw("  "));
         spans.push(Span::styled(format!("({count} matches)"), theme.info));
     }
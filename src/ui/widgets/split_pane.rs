@@ -0,0 +1,73 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::{AppState, SplitChooser};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+/// Render the file chooser for splitting a commit, in place of the detail pane
+pub fn render_split_pane(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    chooser: &SplitChooser,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let summary = state
+        .commits
+        .iter()
+        .find(|c| c.id == chooser.commit_id)
+        .map(|c| format!("{} {}", c.short_hash, c.summary))
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        Line::from(Span::styled(summary, theme.hash)),
+        Line::from(""),
+    ];
+    for (idx, ((status, path), first)) in chooser.files.iter().zip(&chooser.first).enumerate() {
+        let (part, style) = if *first {
+            ("1st", theme.modified_value)
+        } else {
+            ("2nd", theme.info)
+        };
+        let line = Line::from(vec![
+            Span::styled(format!("[{part}] "), style),
+            Span::raw(format!("{status} {path}")),
+        ]);
+        lines.push(if idx == chooser.cursor && !chooser.editing_message {
+            line.style(theme.cell_cursor)
+        } else {
+            line
+        });
+    }
+
+    let message_style = if chooser.editing_message {
+        theme.search_input
+    } else {
+        theme.keybinding
+    };
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("2nd message: ", theme.info),
+            Span::styled(chooser.message.clone(), message_style),
+        ]),
+    ]);
+
+    // Keep the cursor row in view
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let scroll = (chooser.cursor + 3).saturating_sub(visible_height);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border_focused)
+        .title(Line::from(" Split: choose the first commit's files ").style(theme.title));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        area,
+    );
+}
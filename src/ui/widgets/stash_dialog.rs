@@ -0,0 +1,77 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::StashConflict;
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// Maximum number of conflicted paths listed
+const MAX_PATHS: usize = 8;
+
+/// Render the screen shown when the auto-stash can't be restored after a rewrite
+pub fn render_stash_dialog(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    conflict: &StashConflict,
+    theme: &Theme,
+) {
+    let old_tip = conflict.old_tip.0.to_string();
+    let mut lines = vec![
+        Line::from("History was rewritten, but your uncommitted changes (stashed"),
+        Line::from("before the rewrite) conflict with the new history:"),
+        Line::from(""),
+    ];
+    lines.extend(
+        conflict
+            .paths
+            .iter()
+            .take(MAX_PATHS)
+            .map(|path| Line::from(Span::styled(format!("  {path}"), theme.warning))),
+    );
+    let hidden = conflict.paths.len().saturating_sub(MAX_PATHS);
+    if hidden > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("  ... {hidden} more path(s)"),
+            theme.warning,
+        )));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from("Nothing was applied; the changes are safe in stash@{0}."),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("k/Enter", theme.keybinding_key),
+            Span::raw(": keep the rewrite and the stash, and resolve with"),
+        ]),
+        Line::from("         `git stash pop` yourself"),
+        Line::from(vec![
+            Span::styled("a", theme.keybinding_key),
+            Span::raw(format!(
+                ": abort - restore the branch to {}, pop the stash",
+                &old_tip[..7]
+            )),
+        ]),
+        Line::from("   and bring back the pending edits"),
+    ]);
+
+    let width = 72u16.min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(" Stashed changes conflict ").style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner_area);
+}
@@ -6,48 +6,98 @@ use ratatui::layout::Rect;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
+use serde::Deserialize;
 
-/// Render the status bar at the bottom of the screen
-pub fn render_status_bar(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Theme) {
-    let mut spans = Vec::new();
+/// A piece of the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusSegment {
+    /// Mode indicator (NORMAL, V-LINE, ...)
+    Mode,
+    /// Current branch name
+    Branch,
+    /// Context-sensitive key hints
+    Hints,
+    /// Row count of the visual selection (visual mode only)
+    Visual,
+    /// Checkbox selection count
+    Selection,
+    /// Unsaved changes marker
+    Dirty,
+    /// Force-push warning when the branch has an upstream
+    Upstream,
+    /// Cursor position, e.g. 3/50
+    Position,
+    /// The configured custom text
+    Text,
+}
 
-    // Mode indicator
-    let mode_str = match &state.mode {
-        AppMode::Normal => " NORMAL ",
-        AppMode::Visual { visual_type, .. } => match visual_type {
-            VisualType::Line => " V-LINE ",
-            VisualType::Block => " V-BLOCK ",
-        },
-        AppMode::Editing { .. } => " EDIT ",
-        AppMode::Search => " SEARCH ",
-        AppMode::Reorder => " REORDER ",
-        AppMode::Confirming(_) => " CONFIRM ",
-        AppMode::Help => " HELP ",
-        AppMode::Quitting => " QUIT? ",
-    };
-    spans.push(Span::styled(mode_str, theme.status_bar_mode));
-    spans.push(Span::raw(" "));
+/// Which segments the status bar shows, and in what order
+///
+/// Error and success messages always appear right after the left segments
+/// (in place of the key hints).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusBarConfig {
+    /// Segments on the left
+    pub left: Vec<StatusSegment>,
+    /// Segments aligned to the right
+    pub right: Vec<StatusSegment>,
+    /// Content of the `text` segment
+    pub text: String,
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            left: vec![
+                StatusSegment::Mode,
+                StatusSegment::Branch,
+                StatusSegment::Hints,
+            ],
+            right: vec![
+                StatusSegment::Visual,
+                StatusSegment::Selection,
+                StatusSegment::Dirty,
+                StatusSegment::Upstream,
+                StatusSegment::Position,
+            ],
+            text: String::new(),
+        }
+    }
+}
 
-    // Branch name
-    spans.push(Span::styled(format!("[{}]", state.branch_name), theme.info));
-    spans.push(Span::raw(" "));
+/// Render the status bar at the bottom of the screen
+pub fn render_status_bar(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    state: &AppState,
+    config: &StatusBarConfig,
+    theme: &Theme,
+) {
+    let has_message = state.error_message.is_some() || state.success_message.is_some();
+    let mut spans = Vec::new();
+    for segment in &config.left {
+        // A message takes the place of the hints
+        if *segment == StatusSegment::Hints && has_message {
+            continue;
+        }
+        spans.extend(segment_spans(*segment, state, config, theme));
+    }
 
-    // Error/success message or keybindings
+    // Error/success message
     if let Some(ref err) = state.error_message {
         spans.push(Span::styled(err.clone(), theme.error));
     } else if let Some(ref msg) = state.success_message {
         spans.push(Span::styled(msg.clone(), theme.success));
-    } else {
-        // Show context-sensitive keybindings
-        let keybindings = get_keybindings(&state.mode);
-        for (key, desc) in keybindings {
-            spans.push(Span::styled(key, theme.keybinding_key));
-            spans.push(Span::styled(format!(" {desc} "), theme.keybinding));
-        }
     }
 
-    // Right side: dirty indicator and position
-    let right_info = build_right_info(state, theme);
+    // Right-aligned segments
+    let right_info: Vec<Span<'_>> = config
+        .right
+        .iter()
+        .flat_map(|segment| segment_spans(*segment, state, config, theme))
+        .collect();
 
     // Calculate padding to right-align the info
     let left_width: usize = spans.iter().map(|s| s.content.len()).sum();
@@ -67,6 +117,129 @@ pub fn render_status_bar(frame: &mut Frame<'_>, area: Rect, state: &AppState, th
     frame.render_widget(para, area);
 }
 
+/// Spans for one segment, including its trailing space (empty when it has nothing to show)
+fn segment_spans<'a>(
+    segment: StatusSegment,
+    state: &AppState,
+    config: &StatusBarConfig,
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    match segment {
+        StatusSegment::Mode => {
+            let mode_str = match &state.mode {
+                AppMode::Normal => " NORMAL ",
+                AppMode::Visual { visual_type, .. } => match visual_type {
+                    VisualType::Line => " V-LINE ",
+                    VisualType::Block => " V-BLOCK ",
+                },
+                AppMode::Editing { .. } => " EDIT ",
+                AppMode::Search => " SEARCH ",
+                AppMode::Command => " COMMAND ",
+                AppMode::Palette(_) => " PALETTE ",
+                AppMode::Replace { .. } => " REPLACE ",
+                AppMode::Reorder => " REORDER ",
+                AppMode::Confirming(_) => " CONFIRM ",
+                AppMode::StepApply(_) => " STEP ",
+                AppMode::Applying => " APPLYING ",
+                AppMode::StashConflict(_) => " CONFLICT ",
+                AppMode::Split(_) => " SPLIT ",
+                AppMode::Applied(_) => " APPLIED ",
+                AppMode::Trailers(_) => " TRAILERS ",
+                AppMode::BranchPicker(_) => " BRANCH ",
+                AppMode::IdentityPicker(_) => " IDENTITY ",
+                AppMode::Divergence(_) => " DIVERGENCE ",
+                AppMode::Lint(_) => " LINT ",
+                AppMode::Authors(_) => " AUTHORS ",
+                AppMode::Preview(_) => " PREVIEW ",
+                AppMode::UndoHistory(_) => " HISTORY ",
+                AppMode::Backups(_) => " BACKUPS ",
+                AppMode::Help => " HELP ",
+                AppMode::Quitting => " QUIT? ",
+            };
+            vec![
+                Span::styled(mode_str, theme.status_bar_mode),
+                Span::raw(" "),
+            ]
+        }
+        StatusSegment::Branch => vec![
+            Span::styled(format!("[{}]", state.branch_name), theme.info),
+            Span::raw(" "),
+        ],
+        // Show context-sensitive keybindings
+        StatusSegment::Hints => get_keybindings(&state.mode)
+            .into_iter()
+            .flat_map(|(key, desc)| {
+                [
+                    Span::styled(key, theme.keybinding_key),
+                    Span::styled(format!(" {desc} "), theme.keybinding),
+                ]
+            })
+            .collect(),
+        // Visual selection count
+        StatusSegment::Visual => {
+            if !matches!(state.mode, AppMode::Visual { .. }) {
+                return Vec::new();
+            }
+            let count = state.visual_selection_count();
+            vec![Span::styled(
+                format!("[{} row{}] ", count, if count == 1 { "" } else { "s" }),
+                theme.info,
+            )]
+        }
+        // Checkbox selection count, calling out commits hidden by the filter
+        StatusSegment::Selection => {
+            if state.selected.is_empty() {
+                return Vec::new();
+            }
+            let count = state.selected.len();
+            let hidden = state.hidden_selected_count();
+            let text = if hidden > 0 {
+                format!("[{count} sel, {hidden} hidden] ")
+            } else {
+                format!("[{count} sel] ")
+            };
+            vec![Span::styled(
+                text,
+                if hidden > 0 {
+                    theme.warning
+                } else {
+                    theme.info
+                },
+            )]
+        }
+        StatusSegment::Dirty => {
+            if state.is_dirty() {
+                vec![Span::styled("[*] ", theme.warning)]
+            } else {
+                Vec::new()
+            }
+        }
+        StatusSegment::Upstream => {
+            if state.has_upstream && state.is_dirty() {
+                vec![Span::styled("(force push) ", theme.warning)]
+            } else {
+                Vec::new()
+            }
+        }
+        StatusSegment::Position => {
+            let total = state.visible_len();
+            let pos = if total > 0 {
+                format!("{}/{}", state.cursor + 1, total)
+            } else {
+                "0/0".to_string()
+            };
+            vec![Span::raw(pos), Span::raw(" ")]
+        }
+        StatusSegment::Text => {
+            if config.text.is_empty() {
+                Vec::new()
+            } else {
+                vec![Span::styled(format!("{} ", config.text), theme.info)]
+            }
+        }
+    }
+}
+
 /// Get keybindings for the current mode
 fn get_keybindings(mode: &AppMode) -> Vec<(&'static str, &'static str)> {
     match mode {
@@ -77,66 +250,115 @@ fn get_keybindings(mode: &AppMode) -> Vec<(&'static str, &'static str)> {
             ("Space", "sel"),
             ("Enter", "edit"),
             ("/", "search"),
+            (":", "cmd"),
             ("w", "write"),
             ("?", "help"),
         ],
         AppMode::Visual { visual_type, .. } => match visual_type {
             VisualType::Line => vec![
                 ("j/k", "extend"),
+                ("o", "other end"),
                 ("e", "edit"),
+                ("f", "fill"),
                 ("Space", "toggle"),
                 ("^V", "block"),
                 ("Esc", "cancel"),
             ],
             VisualType::Block => vec![
                 ("h/j/k/l", "extend"),
+                ("o", "other end"),
                 ("e", "edit"),
+                ("f", "fill"),
                 ("Space", "toggle"),
                 ("V", "line"),
                 ("Esc", "cancel"),
             ],
         },
-        AppMode::Editing { .. } => vec![("Enter", "save"), ("Esc", "cancel"), ("Tab", "next")],
-        AppMode::Search => vec![("Enter", "filter"), ("Esc", "cancel")],
+        AppMode::Editing { .. } => vec![
+            ("Enter", "save"),
+            ("Esc", "cancel"),
+            ("Tab", "next"),
+            ("^Z", "undo"),
+            ("^R", "replace"),
+        ],
+        AppMode::Search => vec![
+            ("Enter", "filter"),
+            ("Up/Down", "history"),
+            ("Esc", "cancel"),
+        ],
+        AppMode::Command => vec![("Enter", "run"), ("Esc", "cancel")],
+        AppMode::Palette(_) => vec![("↑/↓", "move"), ("Enter", "run"), ("Esc", "cancel")],
+        AppMode::Replace { .. } => vec![
+            ("Enter", "replace all"),
+            ("Tab", "switch field"),
+            ("Esc", "cancel"),
+        ],
         AppMode::Reorder => vec![("Esc", "cancel")],
         AppMode::Confirming(_) => vec![("y", "yes"), ("n", "no"), ("Esc", "cancel")],
+        AppMode::StepApply(_) => vec![("y", "include"), ("s", "skip"), ("Esc", "abort")],
+        AppMode::Applying => vec![("Esc", "cancel")],
+        AppMode::StashConflict(_) => vec![("k", "keep stash"), ("a", "abort rewrite")],
+        AppMode::BranchPicker(_) => vec![("j/k", "move"), ("Enter", "switch"), ("Esc", "cancel")],
+        AppMode::IdentityPicker(_) => vec![
+            ("1-9", "pick"),
+            ("j/k", "move"),
+            ("Enter", "set author"),
+            ("Esc", "cancel"),
+        ],
+        AppMode::Trailers(editor) if editor.input.is_some() && editor.key.is_some() => {
+            vec![("Enter", "save"), ("Tab", "complete"), ("Esc", "cancel")]
+        }
+        AppMode::Trailers(editor) if editor.input.is_some() => {
+            vec![("Enter", "save"), ("Esc", "cancel")]
+        }
+        AppMode::Trailers(_) => vec![
+            ("a", "add"),
+            ("e", "change"),
+            ("d", "remove"),
+            ("Space", "add to all"),
+            ("Esc", "close"),
+        ],
+        AppMode::Divergence(_) => vec![
+            ("a/c", "use author/committer date"),
+            ("A/C", "all"),
+            ("Enter", "go to"),
+            ("Esc", "close"),
+        ],
+        AppMode::Lint(_) => vec![
+            ("h/l", "type"),
+            ("f", "set type"),
+            ("Space", "select"),
+            ("Enter", "go to"),
+            ("Esc", "close"),
+        ],
+        AppMode::Authors(view) if view.input.is_some() => {
+            vec![("Enter", "save"), ("Tab", "complete"), ("Esc", "cancel")]
+        }
+        AppMode::Authors(_) => vec![
+            ("e/Enter", "change author"),
+            ("s", "select commits"),
+            ("Esc", "close"),
+        ],
+        AppMode::Preview(_) => vec![("j/k", "move"), ("w", "apply"), ("Esc", "close")],
+        AppMode::UndoHistory(_) => vec![("j/k", "move"), ("Enter", "jump here"), ("Esc", "close")],
+        AppMode::Backups(_) => vec![
+            ("Enter", "restore"),
+            ("n", "new backup"),
+            ("d", "delete"),
+            ("Esc", "close"),
+        ],
+        AppMode::Applied(_) => vec![("y", "copy push"), ("Y", "copy all"), ("Enter", "close")],
+        AppMode::Split(chooser) if chooser.editing_message => {
+            vec![("Enter", "done"), ("^R", "replace")]
+        }
+        AppMode::Split(_) => vec![
+            ("Space", "1st/2nd"),
+            ("m", "message"),
+            ("Enter", "split"),
+            ("d", "unsplit"),
+            ("Esc", "cancel"),
+        ],
         AppMode::Help => vec![("q/Esc", "close")],
-        AppMode::Quitting => vec![("y", "quit"), ("n", "stay")],
-    }
-}
-
-/// Build the right-side info (position, dirty indicator)
-fn build_right_info<'a>(state: &AppState, theme: &Theme) -> Vec<Span<'a>> {
-    let mut spans = Vec::new();
-
-    // Visual selection count
-    if matches!(state.mode, AppMode::Visual { .. }) {
-        let count = state.visual_selection_count();
-        spans.push(Span::styled(
-            format!("[{} row{}] ", count, if count == 1 { "" } else { "s" }),
-            theme.info,
-        ));
+        AppMode::Quitting => vec![("y", "quit"), ("s", "save session and quit"), ("n", "stay")],
     }
-
-    // Dirty indicator
-    if state.is_dirty() {
-        spans.push(Span::styled("[*] ", theme.warning));
-    }
-
-    // Upstream warning
-    if state.has_upstream && state.is_dirty() {
-        spans.push(Span::styled("(force push) ", theme.warning));
-    }
-
-    // Position
-    let total = state.visible_commits().len();
-    let pos = if total > 0 {
-        format!("{}/{}", state.cursor + 1, total)
-    } else {
-        "0/0".to_string()
-    };
-    spans.push(Span::raw(pos));
-    spans.push(Span::raw(" "));
-
-    spans
 }
@@ -0,0 +1,132 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::git::commit::SquashKind;
+use crate::state::{AppState, StepApply};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// Maximum number of old/new message lines shown
+const MAX_MESSAGE_LINES: usize = 6;
+
+/// Render the review dialog for the commit at the current step
+pub fn render_step_dialog(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    step: &StepApply,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let Some(commit) = step
+        .queue
+        .get(step.index)
+        .and_then(|id| state.commits.iter().find(|c| c.id == *id))
+    else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(commit.short_hash.clone(), theme.hash),
+            Span::raw(" "),
+            Span::raw(commit.summary.clone()),
+        ]),
+        Line::from(""),
+    ];
+
+    if let Some(note) = state.notes.get(&commit.id) {
+        lines.push(Line::from(vec![
+            Span::styled("Note: ", theme.keybinding),
+            Span::raw(note.clone()),
+        ]));
+        lines.push(Line::from(""));
+    }
+
+    if state.is_deleted(commit.id) {
+        lines.push(Line::from(Span::styled(
+            "Deleted (its children move onto its parent)",
+            theme.deleted,
+        )));
+    } else {
+        if let Some(kind) = state.squashes.get(&commit.id) {
+            let how = match kind {
+                SquashKind::Fixup => "keeping the target's message",
+                SquashKind::Squash => "appending its message",
+            };
+            lines.push(Line::from(Span::styled(
+                format!("Folded into the commit below, {how}"),
+                theme.info,
+            )));
+        }
+        if let Some(mods) = state.modifications.get(&commit.id) {
+            for (label, old, new) in mods.field_changes(commit) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{label}: "), theme.keybinding),
+                    Span::raw(old),
+                    Span::raw(" → "),
+                    Span::styled(new, theme.modified_value),
+                ]));
+            }
+            if let Some(message) = &mods.message {
+                lines.push(Line::from(Span::styled("Message:", theme.keybinding)));
+                lines.extend(message_lines("- ", &commit.message, theme.deleted));
+                lines.extend(message_lines("+ ", message, theme.modified_value));
+            }
+        }
+    }
+
+    lines.push(Line::from(""));
+    if !step.skipped.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("{} commit(s) skipped so far", step.skipped.len()),
+            theme.warning,
+        )));
+    }
+    lines.push(Line::from(vec![
+        Span::styled("y/Enter", theme.keybinding_key),
+        Span::raw(": include  "),
+        Span::styled("s", theme.keybinding_key),
+        Span::raw(": skip (keep pending)  "),
+        Span::styled("Esc", theme.keybinding_key),
+        Span::raw(": abort"),
+    ]));
+
+    let width = 76u16.min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let title = format!(" Apply: commit {} of {} ", step.index + 1, step.queue.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner_area);
+}
+
+/// A message as prefixed diff lines, cut off after a few lines
+fn message_lines<'a>(prefix: &'a str, message: &str, style: Style) -> Vec<Line<'a>> {
+    let mut lines: Vec<Line<'a>> = message
+        .lines()
+        .take(MAX_MESSAGE_LINES)
+        .map(|line| Line::from(Span::styled(format!("{prefix}{line}"), style)))
+        .collect();
+    let hidden = message.lines().count().saturating_sub(MAX_MESSAGE_LINES);
+    if hidden > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("{prefix}... {hidden} more line(s)"),
+            style,
+        )));
+    }
+    lines
+}
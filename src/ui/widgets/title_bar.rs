@@ -14,13 +14,24 @@ pub fn render_title_bar(frame: &mut Frame<'_>, area: Rect, state: &AppState, the
         Span::raw("- Retroactive Continuity CLI"),
     ];
 
-    // Add dirty indicator
-    if state.is_dirty() {
-        spans.push(Span::styled(" [modified]", theme.warning));
+    // Scope of the pending rewrite
+    let stats = state.change_stats();
+    if !stats.is_empty() {
+        spans.push(Span::styled(
+            format!(" [{}]", stats.join(" · ")),
+            theme.warning,
+        ));
+    }
+
+    if state.loading {
+        spans.push(Span::styled(
+            format!(" loading… ({} commits)", state.commits.len()),
+            theme.info,
+        ));
     }
 
     // Right-align branch name
-    let left_width: usize = spans.iter().map(|s| s.content.len()).sum();
+    let left_width: usize = spans.iter().map(|s| s.content.chars().count()).sum();
     let branch_text = format!("[{}] ", state.branch_name);
     let padding = area
         .width
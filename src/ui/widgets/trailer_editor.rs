@@ -0,0 +1,102 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::TrailerEditor;
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Render the trailers of the targeted commits, and the one being typed
+pub fn render_trailer_editor(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    editor: &TrailerEditor,
+    theme: &Theme,
+) {
+    let total = editor.targets.len();
+    let shown: Vec<String> = editor
+        .trailers
+        .iter()
+        .map(|(trailer, _)| editor.display(trailer))
+        .collect();
+    let mut lines: Vec<Line<'_>> = shown
+        .iter()
+        .zip(&editor.trailers)
+        .enumerate()
+        .map(|(idx, (text, (_, count)))| {
+            let mut spans = vec![Span::raw(format!("  {text}"))];
+            if *count < total {
+                spans.push(Span::styled(format!("  ({count}/{total})"), theme.info));
+            }
+            let line = Line::from(spans);
+            if idx == editor.cursor && editor.input.is_none() {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        let empty = match editor.key {
+            Some(_) => "  None yet",
+            None => "  No trailers",
+        };
+        lines.push(Line::from(Span::styled(empty, theme.info)));
+    }
+    if let Some(input) = &editor.input {
+        let label = if editor.editing.is_some() {
+            "Change: "
+        } else {
+            "Add: "
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled(label, theme.info),
+            Span::raw(input.clone()),
+            Span::styled("_", theme.info),
+        ]));
+    }
+
+    let width = shown
+        .iter()
+        .map(|text| text.len() as u16 + 14)
+        .max()
+        .unwrap_or(0)
+        .max(50)
+        .min(area.width.saturating_sub(4));
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted trailer (or the input) in view
+    let visible_height = height.saturating_sub(2) as usize;
+    let bottom = if editor.input.is_some() {
+        lines.len()
+    } else {
+        editor.cursor + 1
+    };
+    let scroll = bottom.saturating_sub(visible_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let what = match &editor.key {
+        Some(key) if key == "Co-authored-by" => "Co-authors",
+        Some(key) => key,
+        None => "Trailers",
+    };
+    let title = format!(" {what} of {total} commit(s) ");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        popup_area,
+    );
+}
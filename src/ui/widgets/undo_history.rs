@@ -0,0 +1,116 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use crate::state::{AppState, UndoBrowser};
+use crate::ui::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Changes of the highlighted step shown below the list
+const PREVIEW_LINES: usize = 8;
+
+/// Lines below the step list: blank, the preview, blank, key hints
+const FOOTER_LINES: usize = PREVIEW_LINES + 3;
+
+/// Render the undo history with what the highlighted step changed
+pub fn render_undo_history(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    browser: UndoBrowser,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let history = state.undo_history();
+    let redoable = state.redo_stack.len();
+    // One extra row marks where the current state sits between the two stacks
+    let rows = history.len() + 1;
+
+    let width = 78u16.min(area.width.saturating_sub(4));
+    let height = ((rows + FOOTER_LINES) as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    // Keep the highlighted step in view above the footer
+    let cursor_row = browser.cursor + usize::from(browser.cursor >= redoable);
+    let list_height = (height.saturating_sub(2) as usize).saturating_sub(FOOTER_LINES);
+    let scroll = (cursor_row + 1).saturating_sub(list_height.max(1));
+
+    let mut list: Vec<Line<'_>> = history
+        .iter()
+        .enumerate()
+        .map(|(idx, (description, undone))| {
+            let line = if *undone {
+                Line::from(vec![
+                    Span::styled("  undone  ", theme.info),
+                    Span::styled(*description, theme.info),
+                ])
+            } else {
+                Line::from(vec![Span::raw("          "), Span::raw(*description)])
+            };
+            if idx == browser.cursor {
+                line.style(theme.cell_cursor)
+            } else {
+                line
+            }
+        })
+        .collect();
+    list.insert(
+        redoable,
+        Line::from(Span::styled("  ── current state ──", theme.keybinding)),
+    );
+    let mut lines: Vec<Line<'_>> = list
+        .into_iter()
+        .skip(scroll)
+        .take(list_height.max(1))
+        .collect();
+
+    lines.push(Line::from(""));
+    let mut changes = state.undo_step_changes(browser.cursor);
+    if changes.is_empty() {
+        changes.push("No change to the loaded commits".to_string());
+    }
+    let more = changes.len().saturating_sub(PREVIEW_LINES);
+    for change in changes
+        .into_iter()
+        .take(PREVIEW_LINES - usize::from(more > 0))
+    {
+        lines.push(Line::from(Span::styled(
+            format!("  {change}"),
+            theme.modified_value,
+        )));
+    }
+    if more > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("  ... and {} more", more + 1),
+            theme.info,
+        )));
+    }
+    while lines.len() < list_height.max(1) + 1 + PREVIEW_LINES {
+        lines.push(Line::from(""));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Enter", theme.keybinding_key),
+            Span::raw(": undo (or redo) up to and including this step  "),
+            Span::styled("Esc", theme.keybinding_key),
+            Span::raw(": close"),
+        ]),
+    ]);
+
+    frame.render_widget(Clear, popup_area);
+
+    let title = format!(
+        " Undo history: {} step(s), {redoable} undone ",
+        history.len()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.dialog_border)
+        .title(Line::from(title).style(theme.dialog_title))
+        .style(Style::default().bg(theme.dialog_bg));
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
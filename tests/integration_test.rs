@@ -103,7 +103,7 @@ fn test_app_state_workflow() -> Result<()> {
 #[serial]
 fn test_commit_rewriting() -> Result<()> {
     use retcon::git::commit::{CommitId, CommitModifications};
-    use retcon::git::rewrite::rewrite_history;
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
     use std::collections::{HashMap, HashSet};
 
     let commits_data = vec![("file1.txt", "First"), ("file2.txt", "Second")];
@@ -129,13 +129,23 @@ fn test_commit_rewriting() -> Result<()> {
     let current_order: Vec<_> = commits.iter().map(|c| c.id).collect();
 
     // Rewrite history
-    rewrite_history(
+    let commit_map = rewrite_history(
         repo.inner(),
-        &commits,
-        &modifications,
-        &deleted,
-        &current_order,
-        &branch_name,
+        &RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &deleted,
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &current_order,
+            new_order: &current_order,
+            branch_name: &branch_name,
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        },
     )?;
 
     // Reopen and verify changes
@@ -145,6 +155,10 @@ fn test_commit_rewriting() -> Result<()> {
     // The commit IDs should be different (new commits were created)
     assert_ne!(new_commits[0].id, commits[0].id);
 
+    // The returned map points each old commit at its rewritten counterpart
+    assert_eq!(commit_map.len(), 2);
+    assert_eq!(commit_map[&commits[0].id], new_commits[0].id);
+
     // But the author name and message should be modified
     assert_eq!(new_commits[0].author.name, "Modified Author");
     assert_eq!(new_commits[0].message, "Modified message");
@@ -156,6 +170,828 @@ fn test_commit_rewriting() -> Result<()> {
     // For now, we just verify the first commit changed
     assert_eq!(new_commits.len(), 2);
 
+    // The branch update says what was done in the reflog
+    let reflog = repo2.inner().reflog(&format!("refs/heads/{branch_name}"))?;
+    assert_eq!(
+        reflog
+            .get(0)
+            .and_then(|entry| entry.message().map(str::to_string)),
+        Some("retcon: rewrite 1 commit (1 modified)".to_string())
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_preview_rewrite_matches_apply() -> Result<()> {
+    use retcon::git::commit::{CommitId, CommitModifications};
+    use retcon::git::rewrite::{preview_rewrite, rewrite_history, RewritePlan};
+    use std::collections::{HashMap, HashSet};
+
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&[
+        ("file1.txt", "First"),
+        ("file2.txt", "Second"),
+        ("file3.txt", "Third"),
+    ]);
+    let repo = Repository::open(&repo_path)?;
+    let commits = repo.load_commits(10)?;
+    let branch_name = repo.current_branch_name()?;
+    let old_tip = repo.tip_commit_id()?;
+
+    let modifications = HashMap::from([(
+        commits[1].id,
+        CommitModifications {
+            author_email: Some("new@example.com".to_string()),
+            ..Default::default()
+        },
+    )]);
+    let order: Vec<_> = commits.iter().map(|c| c.id).collect();
+    let deleted: HashSet<CommitId> = HashSet::new();
+    let plan = RewritePlan {
+        commits: &commits,
+        descendants: &[],
+        modifications: &modifications,
+        deleted: &deleted,
+        squashes: &HashMap::new(),
+        splits: &HashMap::new(),
+        original_order: &order,
+        new_order: &order,
+        branch_name: &branch_name,
+        branches: &[],
+        update_hash_references: false,
+        signer: None,
+        new_root: None,
+    };
+
+    // The preview leaves the branch alone
+    let preview = preview_rewrite(repo.inner(), &plan)?;
+    assert_eq!(repo.tip_commit_id()?, old_tip);
+    assert_eq!(preview[&commits[2].id], commits[2].id);
+    assert_ne!(preview[&commits[0].id], commits[0].id);
+
+    // Applying gives exactly the previewed hashes
+    let applied = rewrite_history(repo.inner(), &plan)?;
+    assert_eq!(applied, preview);
+    assert_eq!(repo.tip_commit_id()?, preview[&commits[0].id]);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_plan_dry_run_and_apply() -> Result<()> {
+    use retcon::{App, Config};
+
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&[
+        ("file1.txt", "First"),
+        ("file2.txt", "Second"),
+        ("file3.txt", "Third"),
+    ]);
+    let repo = Repository::open(&repo_path)?;
+    let commits = repo.load_commits(10)?;
+    let old_tip = repo.tip_commit_id()?;
+
+    let plan_path = repo_path.join("plan.json");
+    fs::write(
+        &plan_path,
+        format!(
+            r#"{{"modifications": {{"{}": {{"author_email": "new@example.com"}}}}, "deleted": []}}"#,
+            commits[1].id.0
+        ),
+    )
+    .unwrap();
+
+    let mut app = App::new(repo, 10, true, &Config::default())?;
+    app.load_plan(&plan_path)?;
+    let text = app.dry_run()?;
+    assert!(text.contains("1 commit(s) modified, 0 deleted."));
+    assert!(text.contains("    Author email: test@example.com -> new@example.com\n"));
+    // The newest commit is replayed on the new parent; the oldest keeps its hash
+    assert!(text.contains(&format!("{} -> ", commits[0].short_hash)));
+    assert!(!text.contains(&commits[2].short_hash));
+
+    let repo = Repository::open(&repo_path)?;
+    assert_eq!(repo.tip_commit_id()?, old_tip);
+
+    // Applying headless lands on the predicted tip
+    let summary = app.apply_headless()?;
+    let predicted = format!(
+        "{} -> {}",
+        commits[0].short_hash,
+        &summary.new_tip.0.to_string()[..7]
+    );
+    assert!(text.contains(&predicted));
+    assert_eq!((summary.rewritten, summary.fields_changed), (2, 1));
+    let repo = Repository::open(&repo_path)?;
+    assert_eq!(repo.load_commits(10)?[1].author.email, "new@example.com");
+
+    // The plan's commits are gone now
+    let err = app.load_plan(&plan_path).unwrap_err();
+    assert!(err.to_string().contains("is not loaded"));
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_callbacks_stage_edits() -> Result<()> {
+    use retcon::{App, Config};
+
+    let (_temp_dir, repo_path) =
+        create_test_repo_with_commits(&[("file1.txt", "WIP: First"), ("file2.txt", "Second")]);
+    let repo = Repository::open(&repo_path)?;
+    let mut app = App::new(repo, 10, true, &Config::default())?;
+
+    let changed = app.apply_callbacks(Some("sed 's/^WIP: //'"), Some("tr a-z A-Z"))?;
+    assert_eq!(changed, 2);
+    let oldest = &app.state.commits[1];
+    let mods = &app.state.modifications[&oldest.id];
+    assert_eq!(mods.message.as_deref(), Some("First"));
+    assert_eq!(mods.author_name.as_deref(), Some("TEST USER"));
+    assert_eq!(mods.committer_name.as_deref(), Some("TEST USER"));
+    // The second message was left as it was
+    let newest = &app.state.commits[0];
+    assert_eq!(app.state.modifications[&newest.id].message, None);
+
+    // One undo step
+    assert!(app.state.undo());
+    assert!(!app.state.is_dirty());
+    assert!(app.apply_callbacks(Some("exit 2"), None).is_err());
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_from_start_point() -> Result<()> {
+    use retcon::git::commit::CommitModifications;
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
+    use std::collections::{HashMap, HashSet};
+
+    let commits_data = vec![
+        ("file1.txt", "First"),
+        ("file2.txt", "Second"),
+        ("file3.txt", "Third"),
+    ];
+
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&commits_data);
+    let mut repo = Repository::open(&repo_path)?;
+    assert!(repo.set_start("no-such-rev").is_err());
+    repo.set_start("HEAD~1")?;
+
+    // Loading starts below HEAD; the newer commit is kept aside for replay
+    let commits = repo.load_commits(10)?;
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].summary, "Second");
+    let descendants = repo.commits_above_start()?;
+    assert_eq!(descendants.len(), 1);
+    assert_eq!(descendants[0].summary, "Third");
+
+    let mut modifications = HashMap::new();
+    modifications.insert(
+        commits[0].id,
+        CommitModifications {
+            author_name: Some("Modified Author".to_string()),
+            ..Default::default()
+        },
+    );
+    let current_order: Vec<_> = commits.iter().map(|c| c.id).collect();
+    let branch_name = repo.current_branch_name()?;
+    let commit_map = rewrite_history(
+        repo.inner(),
+        &RewritePlan {
+            commits: &commits,
+            descendants: &descendants,
+            modifications: &modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &current_order,
+            new_order: &current_order,
+            branch_name: &branch_name,
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        },
+    )?;
+
+    // The branch keeps its newest commit, now on top of the rewritten one
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    assert_eq!(new_commits.len(), 3);
+    assert_eq!(new_commits[0].summary, "Third");
+    assert_eq!(new_commits[0].id, commit_map[&descendants[0].id]);
+    assert_eq!(new_commits[1].author.name, "Modified Author");
+    assert_eq!(new_commits[0].parent_ids, vec![new_commits[1].id]);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rev_range_reattaches_newer_commits() -> Result<()> {
+    let commits_data = vec![
+        ("a.txt", "Add a"),
+        ("b.txt", "Add b"),
+        ("c.txt", "Add c"),
+        ("d.txt", "Add d"),
+    ];
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&commits_data);
+    let mut repo = Repository::open(&repo_path)?;
+    let base = repo.load_commits(10)?[3].id;
+    repo.set_range("HEAD~3..HEAD~1")?;
+
+    // Only the range is loaded; swap its two commits
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+    let summaries: Vec<_> = state.commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, ["Add c", "Add b"]);
+    state.move_commit_down();
+    rewrite_from_state(&repo, &state, &[])?;
+
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    let summaries: Vec<_> = new_commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, ["Add d", "Add b", "Add c", "Add a"]);
+    assert_eq!(new_commits[3].id, base);
+    assert_eq!(
+        tree_paths(&repo, new_commits[0].id),
+        ["a.txt", "b.txt", "c.txt", "d.txt"]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_other_branch_leaves_head_alone() -> Result<()> {
+    let commits_data = vec![("a.txt", "Add a"), ("b.txt", "Add b")];
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&commits_data);
+    let mut repo = Repository::open(&repo_path)?;
+    let head = repo.tip_commit_id()?;
+    {
+        let git = repo.inner();
+        let older = git
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .parent(0)
+            .unwrap();
+        git.branch("older", &older, false).unwrap();
+    }
+    repo.set_branch("older")?;
+
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+    assert_eq!(state.branch_name, "older");
+    let id = state.commits[0].id;
+    state.get_or_create_modifications(id).author_name = Some("Someone".to_string());
+    rewrite_from_state(&repo, &state, &[])?;
+
+    let reopened = Repository::open(&repo_path)?;
+    assert_eq!(reopened.tip_commit_id()?, head);
+    assert_eq!(reopened.load_commits(10)?.len(), 2);
+    let older = repo.load_commits(10)?;
+    assert_eq!(older.len(), 1);
+    assert_eq!(older[0].author.name, "Someone");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_moves_dependent_branches() -> Result<()> {
+    use std::collections::HashSet;
+
+    let (_temp_dir, repo_path) =
+        create_test_repo_with_commits(&[("a.txt", "Add a"), ("b.txt", "Add b")]);
+    let repo = Repository::open(&repo_path)?;
+    {
+        // `feature` adds a commit on top of "Add a"; `marker` points at it
+        let git = repo.inner();
+        let base = git
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .parent(0)
+            .unwrap();
+        git.branch("marker", &base, false).unwrap();
+        git.branch("feature", &base, false).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let mut builder = git.treebuilder(Some(&base.tree().unwrap())).unwrap();
+        let blob = git.blob(b"c").unwrap();
+        builder.insert("c.txt", blob, 0o100_644).unwrap();
+        let tree = git.find_tree(builder.write().unwrap()).unwrap();
+        git.commit(
+            Some("refs/heads/feature"),
+            &sig,
+            &sig,
+            "Add c",
+            &tree,
+            &[&base],
+        )
+        .unwrap();
+    }
+
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+    let rewritten: HashSet<_> = state.commits.iter().map(|c| c.id).collect();
+    let branches = repo.dependent_branches(&rewritten)?;
+    let names: Vec<&str> = branches.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["feature", "marker"]);
+    assert_eq!(branches[0].commits.len(), 1);
+    assert_eq!(branches[1].commits.len(), 0);
+
+    let id = state.commits[1].id;
+    state.get_or_create_modifications(id).author_name = Some("Someone".to_string());
+    rewrite_from_state(&repo, &state, &branches)?;
+
+    let git = repo.inner();
+    let new_base = git
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .parent(0)
+        .unwrap();
+    assert_eq!(new_base.author().name(), Some("Someone"));
+    let marker = git.find_branch("marker", git2::BranchType::Local).unwrap();
+    assert_eq!(marker.get().target(), Some(new_base.id()));
+    let feature = git
+        .find_branch("feature", git2::BranchType::Local)
+        .unwrap()
+        .get()
+        .peel_to_commit()
+        .unwrap();
+    assert_eq!(feature.message(), Some("Add c"));
+    assert_eq!(feature.parent_id(0).unwrap(), new_base.id());
+    assert!(feature.tree().unwrap().get_name("c.txt").is_some());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_updates_hash_references() -> Result<()> {
+    use retcon::git::commit::CommitModifications;
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
+    use std::collections::{HashMap, HashSet};
+
+    let (_temp_dir, repo_path) =
+        create_test_repo_with_commits(&[("a.txt", "Add a"), ("b.txt", "Add b")]);
+    let repo = Repository::open(&repo_path)?;
+    let commits = repo.load_commits(10)?;
+    let old_hash = commits[1].id.0.to_string();
+
+    let mut modifications = HashMap::new();
+    modifications.insert(
+        commits[1].id,
+        CommitModifications {
+            author_name: Some("Modified Author".to_string()),
+            ..Default::default()
+        },
+    );
+    modifications.insert(
+        commits[0].id,
+        CommitModifications {
+            message: Some(format!(
+                "Add b\n\nFollows up on {old_hash} ({}).",
+                &old_hash[..7]
+            )),
+            ..Default::default()
+        },
+    );
+    let order: Vec<_> = commits.iter().map(|c| c.id).collect();
+    let commit_map = rewrite_history(
+        repo.inner(),
+        &RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &order,
+            new_order: &order,
+            branch_name: &repo.current_branch_name()?,
+            branches: &[],
+            update_hash_references: true,
+            signer: None,
+            new_root: None,
+        },
+    )?;
+
+    let new_hash = commit_map[&commits[1].id].0.to_string();
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    assert_eq!(
+        new_commits[0].message,
+        format!("Add b\n\nFollows up on {new_hash} ({}).", &new_hash[..7])
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_signs_commits() -> Result<()> {
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
+    use retcon::testing::{rewrite_violations, signed_commits, TestRepo};
+    use std::collections::{HashMap, HashSet};
+
+    let test = TestRepo::signed(&["Add a", "Add b"])?;
+    let repo = test.repo();
+    let branch = test.branch_name()?;
+    let mut state = AppState::new(repo.load_commits(10)?, branch.clone(), false);
+    let tip = state.commits[0].id;
+    let id = state.commits[1].id;
+    state.get_or_create_modifications(id).author_name = Some("Someone".to_string());
+    let signer = test.fake_signer()?;
+
+    for signer in [None, Some(&signer)] {
+        repo.move_branch(&branch, tip)?;
+        let plan = RewritePlan {
+            commits: &state.commits,
+            descendants: &[],
+            modifications: &state.modifications,
+            deleted: &HashSet::new(),
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &state.original_order,
+            new_order: &state.current_order,
+            branch_name: &branch,
+            branches: &[],
+            update_hash_references: false,
+            signer,
+            new_root: None,
+        };
+        let commit_map = rewrite_history(repo.inner(), &plan)?;
+        assert_eq!(
+            rewrite_violations(repo.inner(), &plan, &commit_map),
+            Vec::<String>::new()
+        );
+        // Both the edited commit and the one replayed on top of it lose their
+        // signatures, or get new ones
+        let signed = signed_commits(repo.inner(), &commit_map);
+        assert_eq!(signed.len(), 2);
+        assert!(signed.values().all(|signed| *signed == signer.is_some()));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_new_root() -> Result<()> {
+    use retcon::git::commit::{NewRoot, Person};
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
+    use retcon::testing::{rewrite_violations, TestRepo};
+    use std::collections::{HashMap, HashSet};
+
+    let test = TestRepo::linear(&["Add a", "Add b", "Add c"])?;
+    let repo = test.repo();
+    let branch = test.branch_name()?;
+    let commits = repo.load_commits(10)?;
+    let order: Vec<_> = commits.iter().map(|c| c.id).collect();
+    let tip = order[0];
+    let date = commits[2].author_date - chrono::TimeDelta::minutes(1);
+    let mut root = NewRoot {
+        paths: vec!["0.txt".to_string()],
+        message: "Initial commit".to_string(),
+        author: Person::new("Jane", "jane@example.com"),
+        date,
+    };
+
+    for deleted in [HashSet::new(), HashSet::from([order[2]])] {
+        repo.move_branch(&branch, tip)?;
+        let plan = RewritePlan {
+            commits: &commits,
+            descendants: &[],
+            modifications: &HashMap::new(),
+            deleted: &deleted,
+            squashes: &HashMap::new(),
+            splits: &HashMap::new(),
+            original_order: &order,
+            new_order: &order,
+            branch_name: &branch,
+            branches: &[],
+            update_hash_references: false,
+            signer: None,
+            new_root: Some(&root),
+        };
+        let commit_map = rewrite_history(repo.inner(), &plan)?;
+        assert_eq!(
+            rewrite_violations(repo.inner(), &plan, &commit_map),
+            Vec::<String>::new()
+        );
+        let new_commits = repo.load_commits(10)?;
+        let new_root = new_commits.last().unwrap();
+        assert_eq!(new_root.summary, "Initial commit");
+        assert_eq!(new_root.author.name, "Jane");
+        assert_eq!(new_root.author_date, date);
+        assert_eq!(new_commits.len(), 4 - deleted.len());
+    }
+    // The old root's tree was kept
+    assert_eq!(test.files(&branch)?, vec!["0.txt", "1.txt", "2.txt"]);
+
+    // The paths must come from the root commit, and the root must be loaded
+    repo.move_branch(&branch, tip)?;
+    root.paths = vec!["2.txt".to_string()];
+    let mut plan = RewritePlan {
+        commits: &commits,
+        descendants: &[],
+        modifications: &HashMap::new(),
+        deleted: &HashSet::new(),
+        squashes: &HashMap::new(),
+        splits: &HashMap::new(),
+        original_order: &order,
+        new_order: &order,
+        branch_name: &branch,
+        branches: &[],
+        update_hash_references: false,
+        signer: None,
+        new_root: Some(&root),
+    };
+    assert!(rewrite_history(repo.inner(), &plan).is_err());
+    plan.original_order = &order[..2];
+    plan.new_order = &order[..2];
+    plan.commits = &commits[..2];
+    assert!(rewrite_history(repo.inner(), &plan).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_rewrite_reorder_delete_combinations() -> Result<()> {
+    use retcon::git::commit::CommitId;
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
+    use retcon::testing::{rewrite_violations, TestRepo};
+    use std::collections::{HashMap, HashSet};
+
+    /// Every ordering of `items`
+    fn permutations(items: &[CommitId]) -> Vec<Vec<CommitId>> {
+        if items.len() <= 1 {
+            return vec![items.to_vec()];
+        }
+        let mut all = Vec::new();
+        for (i, first) in items.iter().enumerate() {
+            let mut rest = items.to_vec();
+            rest.remove(i);
+            for mut tail in permutations(&rest) {
+                tail.insert(0, *first);
+                all.push(tail);
+            }
+        }
+        all
+    }
+
+    // Each commit adds its own file, so any order applies cleanly
+    let test = TestRepo::linear(&["Add 0", "Add 1", "Add 2", "Add 3"])?;
+    let repo = test.repo();
+    let branch = test.branch_name()?;
+    let commits = repo.load_commits(10)?;
+    let original_order: Vec<CommitId> = commits.iter().map(|c| c.id).collect();
+    let file = |id: &CommitId| {
+        let idx = original_order.iter().position(|o| o == id).unwrap();
+        format!("{}.txt", commits.len() - 1 - idx)
+    };
+    let mut modifications = HashMap::new();
+    modifications.insert(
+        commits[2].id,
+        retcon::git::commit::CommitModifications {
+            author_name: Some("Someone".to_string()),
+            ..Default::default()
+        },
+    );
+
+    for new_order in permutations(&original_order) {
+        for mask in 0..1u32 << commits.len() {
+            let deleted: HashSet<CommitId> = original_order
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, id)| *id)
+                .collect();
+            repo.move_branch(&branch, original_order[0])?;
+            let plan = RewritePlan {
+                commits: &commits,
+                descendants: &[],
+                modifications: &modifications,
+                deleted: &deleted,
+                squashes: &HashMap::new(),
+                splits: &HashMap::new(),
+                original_order: &original_order,
+                new_order: &new_order,
+                branch_name: &branch,
+                branches: &[],
+                update_hash_references: false,
+                signer: None,
+                new_root: None,
+            };
+            let result = rewrite_history(repo.inner(), &plan);
+            if deleted.len() == commits.len() {
+                assert!(result.is_err(), "deleting every commit must fail");
+                continue;
+            }
+            let commit_map = result?;
+            let case = format!("order {new_order:?}, deleted {deleted:?}");
+            assert_eq!(
+                rewrite_violations(repo.inner(), &plan, &commit_map),
+                Vec::<String>::new(),
+                "{case}"
+            );
+            // Without reordering, kept commits keep their trees (so deleting
+            // one below only drops it from the history); reordering replays
+            // each kept commit's change instead
+            let mut files: Vec<String> = if new_order == original_order {
+                let newest = original_order
+                    .iter()
+                    .position(|id| !deleted.contains(id))
+                    .unwrap();
+                original_order[newest..].iter().map(file).collect()
+            } else {
+                new_order
+                    .iter()
+                    .filter(|id| !deleted.contains(id))
+                    .map(file)
+                    .collect()
+            };
+            files.sort();
+            assert_eq!(test.files(&branch)?, files, "{case}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrite a repo with the pending changes in `state`
+fn rewrite_from_state(
+    repo: &Repository,
+    state: &AppState,
+    branches: &[retcon::git::rewrite::DependentBranch],
+) -> Result<()> {
+    use retcon::git::rewrite::{rewrite_history, RewritePlan};
+
+    rewrite_history(
+        repo.inner(),
+        &RewritePlan {
+            commits: &state.commits,
+            descendants: &repo.commits_above_start()?,
+            modifications: &state.modifications,
+            deleted: &state.deleted,
+            squashes: &state.squashes,
+            splits: &state.splits,
+            original_order: &state.original_order,
+            new_order: &state.current_order,
+            branch_name: &repo.current_branch_name()?,
+            branches,
+            update_hash_references: false,
+            signer: None,
+            new_root: None,
+        },
+    )?;
+    Ok(())
+}
+
+/// Paths in a commit's tree
+fn tree_paths(repo: &Repository, id: retcon::git::commit::CommitId) -> Vec<String> {
+    let tree = repo.inner().find_commit(id.0).unwrap().tree().unwrap();
+    tree.iter()
+        .filter_map(|entry| entry.name().map(str::to_string))
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_reorder_rewrites_content() -> Result<()> {
+    let commits_data = vec![("a.txt", "Add a"), ("b.txt", "Add b"), ("c.txt", "Add c")];
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&commits_data);
+    let repo = Repository::open(&repo_path)?;
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+
+    // Move "Add c" below "Add b"
+    state.move_commit_down();
+    rewrite_from_state(&repo, &state, &[])?;
+
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    let summaries: Vec<_> = new_commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, ["Add b", "Add c", "Add a"]);
+    assert_eq!(tree_paths(&repo, new_commits[1].id), ["a.txt", "c.txt"]);
+    assert_eq!(
+        tree_paths(&repo, new_commits[0].id),
+        ["a.txt", "b.txt", "c.txt"]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_autosquash_folds_fixups() -> Result<()> {
+    let commits_data = vec![
+        ("a.txt", "Add a"),
+        ("b.txt", "Add b"),
+        ("a2.txt", "fixup! Add a"),
+        ("notes.txt", "squash! Add a\n\nAlso add notes"),
+    ];
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&commits_data);
+    let repo = Repository::open(&repo_path)?;
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+
+    assert_eq!(state.autosquash(), 2);
+    rewrite_from_state(&repo, &state, &[])?;
+
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    assert_eq!(new_commits.len(), 2);
+    assert_eq!(new_commits[0].summary, "Add b");
+    assert_eq!(new_commits[1].message, "Add a\n\nAlso add notes\n");
+    assert_eq!(
+        tree_paths(&repo, new_commits[1].id),
+        ["a.txt", "a2.txt", "notes.txt"]
+    );
+    assert_eq!(new_commits[1].parent_ids, Vec::new());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_fixup_folds_into_commit_below() -> Result<()> {
+    use retcon::git::commit::SquashKind;
+
+    let commits_data = vec![("a.txt", "Add a"), ("b.txt", "Add b"), ("c.txt", "Oops")];
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&commits_data);
+    let repo = Repository::open(&repo_path)?;
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+
+    let newest = state.commits[0].id;
+    state.set_squash(&[newest], Some(SquashKind::Fixup));
+    rewrite_from_state(&repo, &state, &[])?;
+
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    let summaries: Vec<_> = new_commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, ["Add b", "Add a"]);
+    assert_eq!(
+        tree_paths(&repo, new_commits[0].id),
+        ["a.txt", "b.txt", "c.txt"]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_split_commit_in_two() -> Result<()> {
+    use retcon::git::commit::CommitSplit;
+
+    let (_temp_dir, repo_path) = create_test_repo_with_commits(&[("a.txt", "Add a")]);
+    // One commit adding two files, with another commit on top
+    {
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        for (files, message) in [
+            (&["b.txt", "c.txt"][..], "Add b and c"),
+            (&["d.txt"], "Add d"),
+        ] {
+            let mut index = repo.index().unwrap();
+            for file in files {
+                fs::write(repo_path.join(file), *file).unwrap();
+                index.add_path(std::path::Path::new(file)).unwrap();
+            }
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.head().unwrap().peel_to_commit().unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+                .unwrap();
+        }
+    }
+    let repo = Repository::open(&repo_path)?;
+    let mut state = AppState::new(repo.load_commits(10)?, repo.current_branch_name()?, false);
+
+    let middle = state.commits[1].id;
+    assert_eq!(
+        repo.changed_files(middle)?,
+        [('A', "b.txt".to_string()), ('A', "c.txt".to_string())]
+    );
+    state.set_split(
+        middle,
+        Some(CommitSplit {
+            paths: vec!["b.txt".to_string()],
+            message: "Add c".to_string(),
+        }),
+    )?;
+    rewrite_from_state(&repo, &state, &[])?;
+
+    let new_commits = Repository::open(&repo_path)?.load_commits(10)?;
+    let summaries: Vec<_> = new_commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, ["Add d", "Add c", "Add b and c", "Add a"]);
+    assert_eq!(tree_paths(&repo, new_commits[2].id), ["a.txt", "b.txt"]);
+    assert_eq!(
+        tree_paths(&repo, new_commits[1].id),
+        ["a.txt", "b.txt", "c.txt"]
+    );
+    assert_eq!(
+        tree_paths(&repo, new_commits[0].id),
+        ["a.txt", "b.txt", "c.txt", "d.txt"]
+    );
+
     Ok(())
 }
 
@@ -196,13 +1032,14 @@ fn test_filter_commits() -> Result<()> {
     state.search_query = "authentication".to_string();
     state.apply_filter();
 
-    let visible = state.visible_commits();
-    assert_eq!(visible.len(), 2);
+    assert_eq!(state.visible_len(), 2);
+    assert!(state
+        .visible_iter()
+        .all(|c| c.summary.contains("authentication")));
 
     // Clear filter
     state.clear_filter();
-    let visible = state.visible_commits();
-    assert_eq!(visible.len(), 3);
+    assert_eq!(state.visible_len(), 3);
 
     Ok(())
 }